@@ -1,6 +1,10 @@
 pub mod bytecode;
+pub mod eof;
 pub mod evmrunner;
 pub mod format_kzg_setup;
+pub mod profile;
+pub mod repl;
+pub mod run;
 pub mod statetest;
 
 use structopt::{clap::AppSettings, StructOpt};
@@ -21,6 +25,20 @@ pub enum MainCmd {
     Evm(evmrunner::Cmd),
     #[structopt(alias = "bc", about = "Prints the opcodes of an hex Bytecodes.")]
     Bytecode(bytecode::Cmd),
+    #[structopt(
+        about = "Interactive prompt to deploy and call contracts against an in-memory or forked state."
+    )]
+    Repl(repl::Cmd),
+    #[structopt(
+        about = "Execute bytecode with the given transaction, block and pre state and print the result as JSON."
+    )]
+    Run(run::Cmd),
+    #[structopt(about = "EOF tools, `eof validate` validates containers with detailed errors.")]
+    Eof(eof::Cmd),
+    #[structopt(
+        about = "Execute a directory of transactions and bytecodes and print opcode frequency, basic block heat and gas histograms as JSON or CSV."
+    )]
+    Profile(profile::Cmd),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -31,6 +49,14 @@ pub enum Error {
     KzgErrors(#[from] format_kzg_setup::KzgErrors),
     #[error(transparent)]
     EvmRunnerErrors(#[from] evmrunner::Errors),
+    #[error(transparent)]
+    Repl(#[from] repl::Errors),
+    #[error(transparent)]
+    Run(#[from] run::Errors),
+    #[error(transparent)]
+    Eof(#[from] eof::Errors),
+    #[error(transparent)]
+    Profile(#[from] profile::Errors),
 }
 
 impl MainCmd {
@@ -43,6 +69,10 @@ impl MainCmd {
                 cmd.run();
                 Ok(())
             }
+            Self::Repl(cmd) => cmd.run().map_err(Into::into),
+            Self::Run(cmd) => cmd.run().map_err(Into::into),
+            Self::Eof(cmd) => cmd.run().map_err(Into::into),
+            Self::Profile(cmd) => cmd.run().map_err(Into::into),
         }
     }
 }
@@ -0,0 +1,194 @@
+use bcevm::{
+    interpreter::{
+        analysis::{validate_raw_eof_with_location, EofDiagnostic},
+        opcode::OpCode,
+    },
+    primitives::Eof,
+};
+use serde_json::json;
+use std::io::{self, BufRead, Error as IoError};
+use structopt::StructOpt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Errors {
+    #[error("Invalid hex string `{0}`")]
+    InvalidHex(String),
+    #[error("{0} of {1} containers are invalid")]
+    InvalidContainers(usize, usize),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+/// EOF tools.
+#[derive(StructOpt, Debug)]
+pub enum Cmd {
+    #[structopt(
+        about = "Validates EOF containers and prints the location of the first error.\nContainers are read from the arguments or one per line from stdin."
+    )]
+    Validate(ValidateCmd),
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result<(), Errors> {
+        match self {
+            Self::Validate(cmd) => cmd.run(),
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+pub struct ValidateCmd {
+    /// Hex encoded containers. If empty, containers are read from stdin.
+    containers: Vec<String>,
+    /// Prints one JSON object per container.
+    #[structopt(long)]
+    json: bool,
+}
+
+impl ValidateCmd {
+    pub fn run(&self) -> Result<(), Errors> {
+        let containers = if self.containers.is_empty() {
+            io::stdin()
+                .lock()
+                .lines()
+                .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            self.containers.clone()
+        };
+
+        let mut invalid = 0;
+        for container in &containers {
+            let trimmed = container.trim();
+            let bytes = hex::decode(trimmed.trim_start_matches("0x"))
+                .map_err(|_| Errors::InvalidHex(trimmed.to_string()))?;
+            let result = validate_raw_eof_with_location(bytes.into());
+            invalid += result.is_err() as usize;
+            if self.json {
+                println!("{}", json_report(&result));
+            } else {
+                println!("{}", report(&result));
+            }
+        }
+        if invalid > 0 {
+            return Err(Errors::InvalidContainers(invalid, containers.len()));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the human readable report of the validation.
+fn report(result: &Result<Eof, EofDiagnostic>) -> String {
+    let diagnostic = match result {
+        Ok(eof) => {
+            return format!(
+                "OK: {} code sections, {} containers, {} bytes of data",
+                eof.body.code_section.len(),
+                eof.body.container_section.len(),
+                eof.header.data_size
+            )
+        }
+        Err(diagnostic) => diagnostic,
+    };
+    let location = &diagnostic.location;
+    let mut report = format!("Error: {:?}", diagnostic.error);
+    if !location.container.is_empty() {
+        report += &format!("\n  container: {}", container_path(&location.container));
+    }
+    if let Some(section) = location.code_section {
+        report += &format!("\n  code section: {section}");
+    }
+    if let Some(offset) = location.offset {
+        report += &format!("\n  offset: {offset}");
+    }
+    if let Some(opcode) = location.opcode {
+        report += &format!("\n  opcode: 0x{opcode:02x} ({})", opcode_name(opcode));
+    }
+    if let Some((min, max)) = location.stack_height {
+        report += &format!("\n  stack height: {min}..={max}");
+    }
+    report
+}
+
+/// Returns the JSON report of the validation.
+fn json_report(result: &Result<Eof, EofDiagnostic>) -> serde_json::Value {
+    let diagnostic = match result {
+        Ok(_) => return json!({ "valid": true }),
+        Err(diagnostic) => diagnostic,
+    };
+    let location = &diagnostic.location;
+    json!({
+        "valid": false,
+        "error": format!("{:?}", diagnostic.error),
+        "container": location.container,
+        "codeSection": location.code_section,
+        "offset": location.offset,
+        "opcode": location.opcode.map(opcode_name),
+        "stackHeight": location.stack_height.map(|(min, max)| json!({ "min": min, "max": max })),
+    })
+}
+
+/// Path of the nested container, e.g. `0.1` for the second container of the first one.
+fn container_path(path: &[usize]) -> String {
+    path.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn opcode_name(opcode: u8) -> &'static str {
+    OpCode::new(opcode).map_or("UNKNOWN", |opcode| opcode.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bcevm::{
+        interpreter::opcode,
+        primitives::{
+            eof::{EofBody, TypesSection},
+            Bytes,
+        },
+    };
+
+    fn container(code: &[u8], max_stack_size: u16) -> Bytes {
+        EofBody {
+            types_section: vec![TypesSection {
+                inputs: 0,
+                outputs: 0x80,
+                max_stack_size,
+            }],
+            code_section: vec![Bytes::copy_from_slice(code)],
+            ..Default::default()
+        }
+        .into_eof()
+        .raw()
+        .clone()
+    }
+
+    #[test]
+    fn reports_location() {
+        let result = validate_raw_eof_with_location(container(&[opcode::STOP], 0));
+        assert_eq!(
+            report(&result),
+            "OK: 1 code sections, 0 containers, 0 bytes of data"
+        );
+
+        let code = [opcode::PUSH0, opcode::POP, opcode::POP, opcode::STOP];
+        let result = validate_raw_eof_with_location(container(&code, 1));
+        assert_eq!(
+            report(&result),
+            "Error: Validation(StackUnderflow)\n  code section: 0\n  offset: 2\n  opcode: 0x50 (POP)\n  stack height: 0..=0"
+        );
+        let json = json_report(&result);
+        assert_eq!(json["error"], "Validation(StackUnderflow)");
+        assert_eq!(json["opcode"], "POP");
+        assert_eq!(json["stackHeight"]["max"], 0);
+
+        let result = validate_raw_eof_with_location(container(&[opcode::STOP], 1));
+        assert_eq!(
+            report(&result),
+            "Error: Validation(MaxStackMismatch)\n  code section: 0"
+        );
+    }
+}
@@ -0,0 +1,473 @@
+use super::run::{insert_state, StateAccount};
+use bcevm::{
+    db::{CacheDB, EmptyDB},
+    inspector_handle_register,
+    inspectors::OpcodeGasInspector,
+    interpreter::{
+        opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInput,
+        EOFCreateOutcome, Interpreter, OpCode,
+    },
+    primitives::{
+        address, keccak256, AccountInfo, Address, Bytecode, Bytes, SpecId, TransactTo, B256, U256,
+    },
+    Database, DatabaseRef, Evm, EvmContext, Inspector,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
+use std::{fs, str::FromStr};
+use structopt::StructOpt;
+use walkdir::{DirEntry, WalkDir};
+
+/// Sender of the profiled transactions.
+const CALLER: Address = Address::with_last_byte(1);
+/// Address the profiled code is deployed at.
+const RECEIVER: Address = address!("1000000000000000000000000000000000000000");
+
+#[derive(Debug, thiserror::Error)]
+pub enum Errors {
+    #[error("Invalid code in {0}: {1}")]
+    InvalidCode(PathBuf, String),
+    #[error("Invalid transaction in {0}: {1}")]
+    InvalidTransaction(PathBuf, serde_json::Error),
+    #[error("Unknown spec `{0}`")]
+    InvalidSpec(String),
+    #[error("Unknown format `{0}`, expected `json` or `csv`")]
+    InvalidFormat(String),
+    #[error("EVM error: {0}")]
+    Evm(String),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+/// Format of the profile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(Errors::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+/// Executes a directory of transactions and bytecodes and prints the aggregated opcode
+/// frequency, basic block heat and gas histogram.
+///
+/// Files with the `.hex` extension contain hex encoded code that is called without
+/// input. Files with the `.json` extension contain a [ProfileTx].
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// Directory searched recursively for `.hex` and `.json` files, or a single file.
+    path: PathBuf,
+    /// Spec name, e.g. `Shanghai` or `Cancun`.
+    #[structopt(long, default_value = "Cancun")]
+    spec: String,
+    /// Output format, `json` or `csv`.
+    #[structopt(long, default_value = "json")]
+    format: Format,
+    /// Write the profile to the file instead of stdout.
+    #[structopt(long)]
+    output: Option<PathBuf>,
+    /// Maximum number of the hottest basic blocks in the profile, 0 for all of them.
+    #[structopt(long, default_value = "1000")]
+    max_blocks: usize,
+}
+
+/// Transaction of a `.json` input file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProfileTx {
+    /// Code of the called contract, or init code if `create` is set.
+    pub code: Bytes,
+    /// Executes the code as init code of a contract creation.
+    pub create: bool,
+    pub input: Bytes,
+    pub value: U256,
+    /// Gas limit of the transaction, defaults to 30M.
+    pub gas_limit: Option<u64>,
+    /// Pre state, see [StateAccount].
+    pub state: HashMap<Address, StateAccount>,
+}
+
+/// Aggregated profile of the executed transactions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub transactions: u64,
+    /// Number of reverted or halted transactions.
+    pub failed: u64,
+    pub gas_used: u64,
+    /// Executed opcodes, ordered by opcode.
+    pub opcodes: Vec<OpcodeStats>,
+    /// Basic blocks, hottest first.
+    pub basic_blocks: Vec<BlockHeat>,
+    /// Gas used by the transactions, in power of two buckets.
+    pub gas_histogram: Vec<GasBucket>,
+}
+
+/// Executions and gas of an opcode.
+///
+/// Gas of the call and create opcodes excludes the gas spent by the called frame.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OpcodeStats {
+    pub opcode: u8,
+    pub name: &'static str,
+    pub count: u64,
+    pub gas: u64,
+}
+
+/// Number of executions of a basic block, identified by the hash of the code and the
+/// program counter of its first instruction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeat {
+    pub code_hash: B256,
+    pub pc: usize,
+    pub count: u64,
+}
+
+/// Number of transactions that used at most `upper_bound` gas, and more than the previous
+/// bucket.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasBucket {
+    pub upper_bound: u64,
+    pub count: u64,
+}
+
+impl Profile {
+    /// Returns the profile as CSV tables of opcodes, basic blocks and the gas histogram,
+    /// separated by an empty line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("opcode,name,count,gas\n");
+        for op in &self.opcodes {
+            let _ = writeln!(csv, "{},{},{},{}", op.opcode, op.name, op.count, op.gas);
+        }
+        csv.push_str("\ncode_hash,pc,count\n");
+        for block in &self.basic_blocks {
+            let _ = writeln!(csv, "{},{},{}", block.code_hash, block.pc, block.count);
+        }
+        csv.push_str("\ngas_upper_bound,count\n");
+        for bucket in &self.gas_histogram {
+            let _ = writeln!(csv, "{},{}", bucket.upper_bound, bucket.count);
+        }
+        csv
+    }
+}
+
+/// Code hash and whether the next instruction starts a basic block, of a frame.
+#[derive(Clone, Copy, Debug, Default)]
+struct FrameBlocks {
+    code_hash: B256,
+    block_start: bool,
+}
+
+/// Inspector that counts opcodes with their gas and basic block executions.
+#[derive(Debug, Default)]
+struct ProfileInspector {
+    opcodes: OpcodeGasInspector,
+    /// Frames indexed by the journal depth.
+    frames: Vec<FrameBlocks>,
+    blocks: HashMap<(B256, usize), u64>,
+}
+
+impl<DB: Database> Inspector<DB> for ProfileInspector {
+    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let depth = context.journaled_state.depth;
+        if self.frames.len() <= depth {
+            self.frames.resize(depth + 1, FrameBlocks::default());
+        }
+        self.frames[depth] = FrameBlocks {
+            code_hash: interp
+                .contract
+                .hash
+                .unwrap_or_else(|| keccak256(interp.contract.bytecode.original_byte_slice())),
+            block_start: true,
+        };
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        Inspector::<DB>::step(&mut self.opcodes, interp, context);
+        let op = interp.current_opcode();
+        let frame = &mut self.frames[context.journaled_state.depth];
+        if frame.block_start || op == opcode::JUMPDEST {
+            *self
+                .blocks
+                .entry((frame.code_hash, interp.program_counter()))
+                .or_default() += 1;
+        }
+        frame.block_start = matches!(op, opcode::JUMP | opcode::JUMPI);
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        Inspector::<DB>::step_end(&mut self.opcodes, interp, context);
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        Inspector::<DB>::call(&mut self.opcodes, context, inputs)
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        Inspector::<DB>::call_end(&mut self.opcodes, context, inputs, outcome)
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        Inspector::<DB>::create(&mut self.opcodes, context, inputs)
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        Inspector::<DB>::create_end(&mut self.opcodes, context, inputs, outcome)
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &EOFCreateInput,
+        outcome: EOFCreateOutcome,
+    ) -> EOFCreateOutcome {
+        Inspector::<DB>::eofcreate_end(&mut self.opcodes, context, inputs, outcome)
+    }
+}
+
+impl Cmd {
+    /// Run profile command.
+    pub fn run(&self) -> Result<(), Errors> {
+        let spec_id = SpecId::from(self.spec.as_str());
+        if <&str>::from(spec_id) != self.spec {
+            return Err(Errors::InvalidSpec(self.spec.clone()));
+        }
+        let txs = find_inputs(&self.path)
+            .iter()
+            .map(|path| read_input(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        let profile = profile(&txs, spec_id, self.max_blocks)?;
+
+        let output = match self.format {
+            Format::Json => {
+                serde_json::to_string_pretty(&profile).expect("profile is serializable") + "\n"
+            }
+            Format::Csv => profile.to_csv(),
+        };
+        match &self.output {
+            Some(path) => fs::write(path, output)?,
+            None => print!("{output}"),
+        }
+        Ok(())
+    }
+}
+
+/// Returns the `.hex` and `.json` files in the directory, ordered by path.
+fn find_inputs(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return vec![path.to_path_buf()];
+    }
+    let mut paths: Vec<_> = WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| {
+            let extension = e.path().extension();
+            extension == Some("hex".as_ref()) || extension == Some("json".as_ref())
+        })
+        .map(DirEntry::into_path)
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Reads the transaction of the input file.
+fn read_input(path: &Path) -> Result<ProfileTx, Errors> {
+    let content = fs::read_to_string(path)?;
+    if path.extension() == Some("json".as_ref()) {
+        return serde_json::from_str(&content)
+            .map_err(|e| Errors::InvalidTransaction(path.to_path_buf(), e));
+    }
+    let code = Bytes::from_str(content.trim())
+        .map_err(|e| Errors::InvalidCode(path.to_path_buf(), e.to_string()))?;
+    Ok(ProfileTx {
+        code,
+        ..Default::default()
+    })
+}
+
+/// Executes the transactions, each on its own pre state, and aggregates their profile.
+pub fn profile(txs: &[ProfileTx], spec_id: SpecId, max_blocks: usize) -> Result<Profile, Errors> {
+    let mut inspector = ProfileInspector::default();
+    let mut opcodes: BTreeMap<u8, (u64, u64)> = BTreeMap::new();
+    let mut histogram: BTreeMap<u64, u64> = BTreeMap::new();
+    let mut profile = Profile::default();
+
+    for tx in txs {
+        let mut db = CacheDB::new(EmptyDB::default());
+        insert_state(&mut db, tx.state.clone());
+        if !db.accounts.contains_key(&CALLER) {
+            db.insert_account_info(
+                CALLER,
+                AccountInfo::from_balance(U256::from(10).pow(U256::from(24))),
+            );
+        }
+        let (transact_to, data) = if tx.create {
+            (TransactTo::create(), tx.code.clone())
+        } else {
+            let code = Bytecode::new_raw(tx.code.clone());
+            let mut info = db
+                .basic_ref(RECEIVER)
+                .expect("in memory database is infallible")
+                .unwrap_or_default();
+            info.code_hash = code.hash_slow();
+            info.code = Some(code);
+            db.insert_account_info(RECEIVER, info);
+            (TransactTo::Call(RECEIVER), tx.input.clone())
+        };
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(&mut inspector)
+            .with_spec_id(spec_id)
+            .modify_tx_env(|env| {
+                env.caller = CALLER;
+                env.transact_to = transact_to;
+                env.data = data;
+                env.value = tx.value;
+                env.gas_limit = tx.gas_limit.unwrap_or(30_000_000);
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm
+            .transact()
+            .map_err(|e| Errors::Evm(e.to_string()))?
+            .result;
+        drop(evm);
+
+        profile.transactions += 1;
+        profile.failed += u64::from(!result.is_success());
+        profile.gas_used += result.gas_used();
+        *histogram
+            .entry(result.gas_used().next_power_of_two())
+            .or_default() += 1;
+        for (opcode, op) in inspector.opcodes.report().opcodes {
+            let entry = opcodes.entry(opcode).or_default();
+            entry.0 += op.count;
+            entry.1 += op.gas;
+        }
+    }
+
+    profile.opcodes = opcodes
+        .into_iter()
+        .map(|(opcode, (count, gas))| OpcodeStats {
+            opcode,
+            name: OpCode::name_by_op(opcode),
+            count,
+            gas,
+        })
+        .collect();
+    let mut blocks: Vec<_> = inspector
+        .blocks
+        .into_iter()
+        .map(|((code_hash, pc), count)| BlockHeat {
+            code_hash,
+            pc,
+            count,
+        })
+        .collect();
+    blocks.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then((a.code_hash, a.pc).cmp(&(b.code_hash, b.pc)))
+    });
+    if max_blocks != 0 {
+        blocks.truncate(max_blocks);
+    }
+    profile.basic_blocks = blocks;
+    profile.gas_histogram = histogram
+        .into_iter()
+        .map(|(upper_bound, count)| GasBucket { upper_bound, count })
+        .collect();
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_loop() {
+        // Counts down from 3: PUSH1 3 JUMPDEST PUSH1 1 SWAP1 SUB DUP1 PUSH1 2 JUMPI STOP
+        let code = Bytes::from_static(&[
+            0x60, 0x03, 0x5b, 0x60, 0x01, 0x90, 0x03, 0x80, 0x60, 0x02, 0x57, 0x00,
+        ]);
+        let txs = [
+            ProfileTx {
+                code: code.clone(),
+                ..Default::default()
+            },
+            ProfileTx {
+                code: code.clone(),
+                ..Default::default()
+            },
+        ];
+        let profile = profile(&txs, SpecId::CANCUN, 0).unwrap();
+        assert_eq!(profile.transactions, 2);
+        assert_eq!(profile.failed, 0);
+
+        let jumpi = profile
+            .opcodes
+            .iter()
+            .find(|op| op.opcode == opcode::JUMPI)
+            .unwrap();
+        assert_eq!((jumpi.name, jumpi.count, jumpi.gas), ("JUMPI", 6, 60));
+
+        // loop body, entry and the STOP after the loop.
+        let code_hash = keccak256(&code);
+        let heat: Vec<_> = profile
+            .basic_blocks
+            .iter()
+            .map(|block| (block.code_hash, block.pc, block.count))
+            .collect();
+        assert_eq!(
+            heat,
+            [(code_hash, 2, 6), (code_hash, 0, 2), (code_hash, 11, 2)]
+        );
+
+        let gas = profile.gas_used / 2;
+        assert_eq!(
+            profile.gas_histogram,
+            [GasBucket {
+                upper_bound: gas.next_power_of_two(),
+                count: 2
+            }]
+        );
+        let csv = profile.to_csv();
+        assert!(csv.starts_with("opcode,name,count,gas\n0,STOP,2,0\n"));
+        assert!(csv.contains("\ngas_upper_bound,count\n"));
+    }
+}
@@ -0,0 +1,407 @@
+use bcevm::{
+    db::{CacheDB, EmptyDB, EthersDB},
+    inspector_handle_register,
+    inspectors::TracerEip3155,
+    primitives::{
+        AccountInfo, Address, Bytes, Env, ExecutionResult, Output, ResultAndState, TransactTo, U256,
+    },
+    Database, DatabaseCommit, DatabaseRef, Evm,
+};
+use ethers_core::types::BlockId;
+use ethers_providers::{Http, Provider};
+use std::fmt::Debug;
+use std::io::{self, BufRead, Error as IoError, Write};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// Default sender of the transactions.
+const DEFAULT_CALLER: Address = Address::with_last_byte(1);
+
+/// Seconds between blocks when advancing the chain.
+const BLOCK_TIME: u64 = 12;
+
+const HELP: &str = "\
+Commands:
+  deploy <initcode> [value]        deploy contract and print its address
+  call <address> [input] [value]   call contract
+  balance <address>                print account balance
+  setbalance <address> <value>     set account balance
+  storage <address> <slot>         print storage slot
+  code <address>                   print account code
+  caller [address]                 print or set transaction sender
+  mine [blocks]                    advance block number and timestamp
+  trace <on|off>                   toggle EIP-3155 tracing of executions
+  help                             print this message
+  exit                             exit the repl";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Errors {
+    #[error("Invalid fork url")]
+    InvalidForkUrl,
+    #[error("Could not connect to the fork node")]
+    ForkConnection,
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+/// Error of a single repl command. Printed to the user and the repl continues.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum CommandError {
+    #[error("Unknown command `{0}`, type `help` for the list of commands")]
+    UnknownCommand(String),
+    #[error("Missing argument `{0}`")]
+    MissingArgument(&'static str),
+    #[error("Invalid argument `{0}`")]
+    InvalidArgument(String),
+    #[error("Database error: {0}")]
+    Database(String),
+    #[error("EVM error: {0}")]
+    Evm(String),
+}
+
+/// Interactive prompt to deploy and call contracts and inspect the state.
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// RPC url of the node to fork the state from. Empty in-memory state is used if not set.
+    #[structopt(long)]
+    fork_url: Option<String>,
+    /// Block number to fork the state at. Latest block is used if not set.
+    #[structopt(long)]
+    fork_block: Option<u64>,
+}
+
+impl Cmd {
+    /// Run repl command.
+    pub fn run(&self) -> Result<(), Errors> {
+        let stdin = io::stdin();
+        match &self.fork_url {
+            Some(url) => {
+                let client =
+                    Provider::<Http>::try_from(url.as_str()).map_err(|_| Errors::InvalidForkUrl)?;
+                let db = EthersDB::new(Arc::new(client), self.fork_block.map(BlockId::from))
+                    .ok_or(Errors::ForkConnection)?;
+                Repl::new(CacheDB::new(db)).run(stdin.lock())
+            }
+            None => {
+                let mut db = CacheDB::new(EmptyDB::default());
+                // fund the default sender so it can pay for value transfers.
+                db.insert_account_info(
+                    DEFAULT_CALLER,
+                    AccountInfo {
+                        balance: U256::from(10).pow(U256::from(24)),
+                        ..Default::default()
+                    },
+                );
+                Repl::new(db).run(stdin.lock())
+            }
+        }
+    }
+}
+
+/// Parsed repl command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Deploy {
+        code: Bytes,
+        value: U256,
+    },
+    Call {
+        to: Address,
+        input: Bytes,
+        value: U256,
+    },
+    Balance(Address),
+    SetBalance(Address, U256),
+    Storage(Address, U256),
+    Code(Address),
+    Caller(Option<Address>),
+    Mine(u64),
+    Trace(bool),
+    Help,
+    Exit,
+}
+
+impl Command {
+    /// Parses a line of user input. Returns `None` for empty lines.
+    pub fn parse(line: &str) -> Result<Option<Self>, CommandError> {
+        let mut args = line.split_whitespace();
+        let Some(name) = args.next() else {
+            return Ok(None);
+        };
+        let command = match name {
+            "deploy" => Self::Deploy {
+                code: parse_bytes(
+                    args.next()
+                        .ok_or(CommandError::MissingArgument("initcode"))?,
+                )?,
+                value: args.next().map(parse_u256).transpose()?.unwrap_or_default(),
+            },
+            "call" => Self::Call {
+                to: parse_address(args.next())?,
+                input: args
+                    .next()
+                    .map(parse_bytes)
+                    .transpose()?
+                    .unwrap_or_default(),
+                value: args.next().map(parse_u256).transpose()?.unwrap_or_default(),
+            },
+            "balance" => Self::Balance(parse_address(args.next())?),
+            "setbalance" => Self::SetBalance(
+                parse_address(args.next())?,
+                parse_u256(args.next().ok_or(CommandError::MissingArgument("value"))?)?,
+            ),
+            "storage" => Self::Storage(
+                parse_address(args.next())?,
+                parse_u256(args.next().ok_or(CommandError::MissingArgument("slot"))?)?,
+            ),
+            "code" => Self::Code(parse_address(args.next())?),
+            "caller" => Self::Caller(args.next().map(|a| parse_address(Some(a))).transpose()?),
+            "mine" => Self::Mine(
+                args.next()
+                    .map(|n| {
+                        n.parse()
+                            .map_err(|_| CommandError::InvalidArgument(n.into()))
+                    })
+                    .transpose()?
+                    .unwrap_or(1),
+            ),
+            "trace" => match args.next() {
+                Some("on") => Self::Trace(true),
+                Some("off") => Self::Trace(false),
+                Some(arg) => return Err(CommandError::InvalidArgument(arg.into())),
+                None => return Err(CommandError::MissingArgument("on|off")),
+            },
+            "help" => Self::Help,
+            "exit" | "quit" => Self::Exit,
+            _ => return Err(CommandError::UnknownCommand(name.into())),
+        };
+        Ok(Some(command))
+    }
+}
+
+fn parse_address(arg: Option<&str>) -> Result<Address, CommandError> {
+    let arg = arg.ok_or(CommandError::MissingArgument("address"))?;
+    arg.parse()
+        .map_err(|_| CommandError::InvalidArgument(arg.into()))
+}
+
+fn parse_bytes(arg: &str) -> Result<Bytes, CommandError> {
+    hex::decode(arg.trim_start_matches("0x"))
+        .map(Into::into)
+        .map_err(|_| CommandError::InvalidArgument(arg.into()))
+}
+
+fn parse_u256(arg: &str) -> Result<U256, CommandError> {
+    arg.parse()
+        .map_err(|_| CommandError::InvalidArgument(arg.into()))
+}
+
+/// Repl session state.
+struct Repl<ExtDB> {
+    db: CacheDB<ExtDB>,
+    env: Box<Env>,
+    tracing: bool,
+}
+
+impl<ExtDB: DatabaseRef> Repl<ExtDB>
+where
+    ExtDB::Error: Debug,
+{
+    fn new(db: CacheDB<ExtDB>) -> Self {
+        let mut env = Box::<Env>::default();
+        env.tx.caller = DEFAULT_CALLER;
+        Self {
+            db,
+            env,
+            tracing: false,
+        }
+    }
+
+    fn run(&mut self, input: impl BufRead) -> Result<(), Errors> {
+        println!("bcevme repl, type `help` for the list of commands");
+        print_prompt()?;
+        for line in input.lines() {
+            match Command::parse(&line?) {
+                Ok(Some(Command::Exit)) => break,
+                Ok(Some(command)) => match self.execute(command) {
+                    Ok(output) => println!("{output}"),
+                    Err(e) => println!("{e}"),
+                },
+                Ok(None) => (),
+                Err(e) => println!("{e}"),
+            }
+            print_prompt()?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, command: Command) -> Result<String, CommandError> {
+        let output = match command {
+            Command::Deploy { code, value } => {
+                let result = self.transact(TransactTo::Create, code, value)?;
+                match result {
+                    ExecutionResult::Success {
+                        output: Output::Create(_, Some(address)),
+                        gas_used,
+                        ..
+                    } => format!("Deployed at {address}, gas used {gas_used}"),
+                    result => format_result(&result),
+                }
+            }
+            Command::Call { to, input, value } => {
+                format_result(&self.transact(TransactTo::Call(to), input, value)?)
+            }
+            Command::Balance(address) => self
+                .db
+                .basic(address)
+                .map_err(db_error)?
+                .map(|info| info.balance)
+                .unwrap_or_default()
+                .to_string(),
+            Command::SetBalance(address, balance) => {
+                let account = self.db.load_account(address).map_err(db_error)?;
+                account.info.balance = balance;
+                format!("Balance of {address} set to {balance}")
+            }
+            Command::Storage(address, slot) => {
+                format!("{:#x}", self.db.storage(address, slot).map_err(db_error)?)
+            }
+            Command::Code(address) => {
+                let code = match self.db.basic(address).map_err(db_error)? {
+                    Some(info) => match info.code {
+                        Some(code) => code,
+                        None => self.db.code_by_hash(info.code_hash).map_err(db_error)?,
+                    },
+                    None => Default::default(),
+                };
+                code.original_bytes().to_string()
+            }
+            Command::Caller(Some(caller)) => {
+                self.env.tx.caller = caller;
+                format!("Caller set to {caller}")
+            }
+            Command::Caller(None) => self.env.tx.caller.to_string(),
+            Command::Mine(blocks) => {
+                let block = &mut self.env.block;
+                block.number += U256::from(blocks);
+                block.timestamp += U256::from(blocks * BLOCK_TIME);
+                format!("Block number {}", block.number)
+            }
+            Command::Trace(tracing) => {
+                self.tracing = tracing;
+                format!("Tracing {}", if tracing { "enabled" } else { "disabled" })
+            }
+            Command::Help => HELP.to_string(),
+            Command::Exit => String::new(),
+        };
+        Ok(output)
+    }
+
+    /// Executes transaction from the current caller and commits the state.
+    fn transact(
+        &mut self,
+        transact_to: TransactTo,
+        data: Bytes,
+        value: U256,
+    ) -> Result<ExecutionResult, CommandError> {
+        let mut env = self.env.clone();
+        env.tx.transact_to = transact_to;
+        env.tx.data = data;
+        env.tx.value = value;
+
+        let builder = Evm::builder().with_db(&mut self.db).with_env(env);
+        let result = if self.tracing {
+            builder
+                .with_external_context(TracerEip3155::new(Box::new(io::stdout())))
+                .append_handler_register(inspector_handle_register)
+                .build()
+                .transact()
+        } else {
+            builder.build().transact()
+        };
+        let ResultAndState { result, state, .. } =
+            result.map_err(|e| CommandError::Evm(format!("{e:?}")))?;
+        self.db.commit(state);
+        Ok(result)
+    }
+}
+
+fn db_error(e: impl Debug) -> CommandError {
+    CommandError::Database(format!("{e:?}"))
+}
+
+fn format_result(result: &ExecutionResult) -> String {
+    match result {
+        ExecutionResult::Success {
+            gas_used,
+            logs,
+            output,
+            ..
+        } => format!(
+            "Success, gas used {gas_used}, logs {}, output {}",
+            logs.len(),
+            output.data()
+        ),
+        ExecutionResult::Revert { gas_used, output } => {
+            format!("Revert, gas used {gas_used}, output {output}")
+        }
+        ExecutionResult::Halt {
+            reason, gas_used, ..
+        } => {
+            format!("Halt {reason:?}, gas used {gas_used}")
+        }
+    }
+}
+
+fn print_prompt() -> Result<(), IoError> {
+    print!("> ");
+    io::stdout().flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Command::parse("  "), Ok(None));
+        assert_eq!(
+            Command::parse("call 0x0000000000000000000000000000000000000002 0xab 10"),
+            Ok(Some(Command::Call {
+                to: Address::with_last_byte(2),
+                input: Bytes::from_static(&[0xab]),
+                value: U256::from(10),
+            }))
+        );
+        assert_eq!(Command::parse("mine"), Ok(Some(Command::Mine(1))));
+        assert_eq!(
+            Command::parse("trace"),
+            Err(CommandError::MissingArgument("on|off"))
+        );
+        assert_eq!(
+            Command::parse("jump"),
+            Err(CommandError::UnknownCommand("jump".into()))
+        );
+    }
+
+    #[test]
+    fn deploy_and_call() {
+        let mut repl = Repl::new(CacheDB::new(EmptyDB::default()));
+        // init code returning runtime code `PUSH1 0x2a PUSH1 0x00 SSTORE STOP`.
+        let deploy = Command::parse("deploy 0x6006600c60003960066000f3602a60005500").unwrap();
+        let output = repl.execute(deploy.unwrap()).unwrap();
+        assert!(output.starts_with("Deployed at"), "{output}");
+
+        let address = DEFAULT_CALLER.create(0);
+        repl.execute(Command::Call {
+            to: address,
+            input: Bytes::new(),
+            value: U256::ZERO,
+        })
+        .unwrap();
+        assert_eq!(
+            repl.execute(Command::Storage(address, U256::ZERO)),
+            Ok("0x2a".to_string())
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use bcevm::{
+    db::{CacheDB, EmptyDB},
+    primitives::{
+        AccountInfo, Address, Bytecode, Bytes, ExecutionResult, Log, Output, SpecId, TransactTo,
+        B256, U256,
+    },
+    DatabaseRef, Evm,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Error as IoError;
+use std::path::PathBuf;
+use std::{fs, str::FromStr};
+use structopt::StructOpt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Errors {
+    #[error("Either the code or --code-file needs to be set")]
+    MissingCode,
+    #[error("Invalid code: {0}")]
+    InvalidCode(String),
+    #[error("Unknown spec `{0}`")]
+    InvalidSpec(String),
+    #[error("Invalid state file: {0}")]
+    InvalidState(#[from] serde_json::Error),
+    #[error("EVM error: {0}")]
+    Evm(String),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+/// Executes bytecode with the given transaction and block environment and prints the
+/// result as JSON.
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// Hex encoded code to execute.
+    code: Option<Bytes>,
+    /// Path to a file with the hex encoded code. Overrides the code argument.
+    #[structopt(long)]
+    code_file: Option<PathBuf>,
+    /// Executes the code as init code of a contract creation.
+    #[structopt(long)]
+    create: bool,
+    /// Hex encoded calldata.
+    #[structopt(long)]
+    input: Option<Bytes>,
+    /// Transferred value.
+    #[structopt(long, default_value = "0")]
+    value: U256,
+    /// Sender of the transaction.
+    #[structopt(long, default_value = "0x0000000000000000000000000000000000000001")]
+    caller: Address,
+    /// Address the code is deployed at and called.
+    #[structopt(long, default_value = "0x1000000000000000000000000000000000000000")]
+    receiver: Address,
+    /// Gas limit of the transaction.
+    #[structopt(long, default_value = "30000000")]
+    gas: u64,
+    /// Gas price of the transaction.
+    #[structopt(long, default_value = "0")]
+    gas_price: U256,
+    /// Spec name, e.g. `Shanghai` or `Cancun`.
+    #[structopt(long, default_value = "Cancun")]
+    spec: String,
+    /// Chain id.
+    #[structopt(long, default_value = "1")]
+    chain_id: u64,
+    /// Block number.
+    #[structopt(long, default_value = "1")]
+    number: U256,
+    /// Block timestamp.
+    #[structopt(long, default_value = "1")]
+    timestamp: U256,
+    /// Block beneficiary.
+    #[structopt(long, default_value = "0x0000000000000000000000000000000000000000")]
+    coinbase: Address,
+    /// Block basefee.
+    #[structopt(long, default_value = "0")]
+    basefee: U256,
+    /// Block gas limit.
+    #[structopt(long, default_value = "30000000")]
+    block_gas_limit: U256,
+    /// Block prevrandao.
+    #[structopt(long)]
+    prevrandao: Option<B256>,
+    /// Path to a JSON file with the pre state, a map of addresses to accounts with
+    /// `balance`, `nonce`, `code` and `storage` fields.
+    #[structopt(long)]
+    state: Option<PathBuf>,
+}
+
+/// Account of the pre state file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StateAccount {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Bytes,
+    pub storage: HashMap<U256, U256>,
+}
+
+/// Result of the execution.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunOutput {
+    pub success: bool,
+    /// Success, revert or halt reason.
+    pub status: String,
+    pub gas_used: u64,
+    pub gas_refunded: u64,
+    pub output: Bytes,
+    /// Address of the created contract.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_address: Option<Address>,
+    pub logs: Vec<Log>,
+    /// Changed accounts with their new values.
+    pub state_diff: BTreeMap<Address, AccountDiff>,
+}
+
+/// Changed account. Storage contains only changed slots.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AccountDiff {
+    pub balance: U256,
+    pub nonce: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<U256, U256>,
+    #[serde(skip_serializing_if = "core::ops::Not::not")]
+    pub selfdestructed: bool,
+}
+
+impl Cmd {
+    /// Run run command.
+    pub fn run(&self) -> Result<(), Errors> {
+        let output = self.execute()?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).expect("output is serializable")
+        );
+        Ok(())
+    }
+
+    /// Executes the code and returns the result.
+    pub fn execute(&self) -> Result<RunOutput, Errors> {
+        let code = match (&self.code_file, &self.code) {
+            (Some(path), _) => Bytes::from_str(fs::read_to_string(path)?.trim())
+                .map_err(|e| Errors::InvalidCode(e.to_string()))?,
+            (None, Some(code)) => code.clone(),
+            (None, None) => return Err(Errors::MissingCode),
+        };
+        let spec_id = SpecId::from(self.spec.as_str());
+        if <&str>::from(spec_id) != self.spec {
+            return Err(Errors::InvalidSpec(self.spec.clone()));
+        }
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        if let Some(path) = &self.state {
+            let state: HashMap<Address, StateAccount> =
+                serde_json::from_str(&fs::read_to_string(path)?)?;
+            insert_state(&mut db, state);
+        }
+        if !db.accounts.contains_key(&self.caller) {
+            // fund the sender so it can pay for the value and gas.
+            db.insert_account_info(
+                self.caller,
+                AccountInfo::from_balance(U256::from(10).pow(U256::from(24))),
+            );
+        }
+        let (transact_to, data) = if self.create {
+            (TransactTo::create(), code)
+        } else {
+            let code = Bytecode::new_raw(code);
+            let mut info = db
+                .basic_ref(self.receiver)
+                .expect("in memory database is infallible")
+                .unwrap_or_default();
+            info.code_hash = code.hash_slow();
+            info.code = Some(code);
+            db.insert_account_info(self.receiver, info);
+            (
+                TransactTo::Call(self.receiver),
+                self.input.clone().unwrap_or_default(),
+            )
+        };
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(spec_id)
+            .modify_cfg_env(|cfg| cfg.chain_id = self.chain_id)
+            .modify_block_env(|block| {
+                block.number = self.number;
+                block.timestamp = self.timestamp;
+                block.coinbase = self.coinbase;
+                block.basefee = self.basefee;
+                block.gas_limit = self.block_gas_limit;
+                block.prevrandao = Some(self.prevrandao.unwrap_or_default());
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = self.caller;
+                tx.transact_to = transact_to;
+                tx.data = data;
+                tx.value = self.value;
+                tx.gas_limit = self.gas;
+                tx.gas_price = self.gas_price;
+            })
+            .build();
+        let result = evm.transact().map_err(|e| Errors::Evm(e.to_string()))?;
+
+        let db = evm.db();
+        let state_diff = result
+            .state
+            .into_iter()
+            .filter_map(|(address, account)| {
+                let original = db
+                    .basic_ref(address)
+                    .expect("in memory database is infallible")
+                    .unwrap_or_default();
+                let storage: BTreeMap<_, _> = account
+                    .changed_storage_slots()
+                    .map(|(slot, value)| (*slot, value.present_value))
+                    .collect();
+                let code_changed = original.code_hash != account.info.code_hash;
+                let diff = AccountDiff {
+                    balance: account.info.balance,
+                    nonce: account.info.nonce,
+                    code: code_changed
+                        .then(|| account.info.code.as_ref().map(Bytecode::original_bytes))
+                        .flatten(),
+                    storage,
+                    selfdestructed: account.is_selfdestructed(),
+                };
+                let changed = diff.balance != original.balance
+                    || diff.nonce != original.nonce
+                    || code_changed
+                    || !diff.storage.is_empty()
+                    || diff.selfdestructed;
+                changed.then_some((address, diff))
+            })
+            .collect();
+
+        let success = result.result.is_success();
+        let gas_used = result.result.gas_used();
+        let (status, gas_refunded, output, logs, created_address) = match result.result {
+            ExecutionResult::Success {
+                reason,
+                gas_refunded,
+                logs,
+                output,
+                ..
+            } => {
+                let created_address = match &output {
+                    Output::Create(_, address) => *address,
+                    Output::Call(_) => None,
+                };
+                (
+                    format!("{reason:?}"),
+                    gas_refunded,
+                    output.into_data(),
+                    logs,
+                    created_address,
+                )
+            }
+            ExecutionResult::Revert { output, .. } => {
+                ("Revert".to_string(), 0, output, Vec::new(), None)
+            }
+            ExecutionResult::Halt { reason, .. } => {
+                (format!("{reason:?}"), 0, Bytes::new(), Vec::new(), None)
+            }
+        };
+        Ok(RunOutput {
+            success,
+            status,
+            gas_used,
+            gas_refunded,
+            output,
+            created_address,
+            logs,
+            state_diff,
+        })
+    }
+}
+
+/// Inserts the accounts of the pre state into the database.
+pub(crate) fn insert_state(db: &mut CacheDB<EmptyDB>, state: HashMap<Address, StateAccount>) {
+    for (address, account) in state {
+        let code = Bytecode::new_raw(account.code);
+        db.insert_account_info(
+            address,
+            AccountInfo::new(account.balance, account.nonce, code.hash_slow(), code),
+        );
+        for (slot, value) in account.storage {
+            db.insert_account_storage(address, slot, value)
+                .expect("in memory database is infallible");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bcevm::primitives::address;
+
+    #[test]
+    fn run_call() {
+        // SSTORE(0, CALLDATALOAD(0)) and RETURN(0, 32).
+        let cmd = Cmd::from_iter([
+            "run",
+            "0x60003560005560206000f3",
+            "--input",
+            "0x000000000000000000000000000000000000000000000000000000000000002a",
+        ]);
+        let output = cmd.execute().unwrap();
+        assert!(output.success, "{output:?}");
+        assert_eq!(output.status, "Return");
+        // nothing is stored in memory.
+        assert_eq!(output.output, Bytes::from(vec![0; 32]));
+        assert_eq!(
+            output.state_diff[&address!("1000000000000000000000000000000000000000")].storage
+                [&U256::ZERO],
+            U256::from(0x2a)
+        );
+        // sender nonce is bumped.
+        assert_eq!(output.state_diff[&Address::with_last_byte(1)].nonce, 1);
+    }
+
+    #[test]
+    fn invalid_spec() {
+        let cmd = Cmd::from_iter(["run", "0x00", "--spec", "Cancum"]);
+        assert!(matches!(cmd.execute(), Err(Errors::InvalidSpec(_))));
+    }
+
+    #[test]
+    fn parse_state() {
+        let state: HashMap<Address, StateAccount> = serde_json::from_str(
+            r#"{"0x0000000000000000000000000000000000000003": {"balance": "0x10", "storage": {"0x1": "0x2"}}}"#,
+        )
+        .unwrap();
+        let mut db = CacheDB::new(EmptyDB::default());
+        insert_state(&mut db, state);
+        let address = Address::with_last_byte(3);
+        assert_eq!(
+            db.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(0x10)
+        );
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(2)));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod conformance;
 pub mod merkle_trie;
 pub mod models;
 mod runner;
@@ -5,8 +6,8 @@ pub mod utils;
 
 pub use runner::TestError as Error;
 
-use runner::{find_all_json_tests, run, TestError};
-use std::path::PathBuf;
+use runner::{find_all_json_tests, run, RunOptions, TestError, TestSummary};
+use std::{fs, path::PathBuf};
 use structopt::StructOpt;
 
 /// Statetest command
@@ -21,6 +22,15 @@ pub struct Cmd {
     /// Run tests in a single thread.
     #[structopt(short = "s", long)]
     single_thread: bool,
+    /// Number of threads. Defaults to the available parallelism.
+    #[structopt(short = "j", long)]
+    threads: Option<usize>,
+    /// Run only tests whose name or file path contains the pattern.
+    #[structopt(long = "match")]
+    name_filter: Option<String>,
+    /// Write a JSON summary of the results, including the failed tests, to the file.
+    #[structopt(long)]
+    report: Option<PathBuf>,
     /// Output results in JSON format.
     /// It will stop second run of evm on failure.
     #[structopt(long)]
@@ -36,16 +46,34 @@ pub struct Cmd {
 impl Cmd {
     /// Run statetest command.
     pub fn run(&self) -> Result<(), TestError> {
+        let options = RunOptions {
+            single_thread: self.single_thread,
+            threads: self.threads,
+            trace: self.json,
+            print_outcome: self.json_outcome,
+            keep_going: self.keep_going,
+            name_filter: self.name_filter.clone(),
+        };
+        let mut summary = TestSummary::default();
+        let mut result = Ok(());
         for path in &self.path {
             println!("\nRunning tests in {}...", path.display());
             let test_files = find_all_json_tests(path);
-            run(
-                test_files,
-                self.single_thread,
-                self.json,
-                self.json_outcome,
-                self.keep_going,
-            )?
+            result = run(test_files, &options, &mut summary);
+            if result.is_err() || !summary.failures.is_empty() {
+                break;
+            }
+        }
+
+        if let Some(report) = &self.report {
+            let json = serde_json::to_string_pretty(&summary).expect("summary is serializable");
+            if let Err(err) = fs::write(report, json) {
+                println!("Failed to write the report to {}: {err}", report.display());
+            }
+        }
+        result?;
+        if !summary.failures.is_empty() {
+            std::process::exit(1);
         }
         Ok(())
     }
@@ -0,0 +1,229 @@
+//! Library form of the state test runner, used to measure spec coverage against
+//! [execution-spec-tests](https://github.com/ethereum/execution-spec-tests) fixtures.
+//!
+//! Failures are checked against a list of known failures, so a CI job fails only on
+//! regressions and on known failures that started to pass.
+use super::runner::{execute_test_suite, find_all_json_tests, skip_test, TestError};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Format of the fixture file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FixtureKind {
+    /// `state_test` fixture, single transaction executed against pre state.
+    StateTest,
+    /// `blockchain_test` fixture, chain of blocks imported on top of genesis.
+    BlockchainTest,
+    /// File is not a recognized fixture.
+    Unknown,
+}
+
+impl FixtureKind {
+    /// Detects the format of the fixture from its first test.
+    pub fn detect(fixture: &serde_json::Value) -> Self {
+        let Some(test) = fixture
+            .as_object()
+            .and_then(|tests| tests.values().next())
+            .and_then(serde_json::Value::as_object)
+        else {
+            return Self::Unknown;
+        };
+        if test.contains_key("blocks") && test.contains_key("genesisBlockHeader") {
+            Self::BlockchainTest
+        } else if test.contains_key("transaction") && test.contains_key("post") {
+            Self::StateTest
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+/// List of tests that are expected to fail.
+///
+/// Entry matches a failure if it is equal to the test name or if the fixture path
+/// ends with it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KnownFailures(HashSet<String>);
+
+impl KnownFailures {
+    /// Parses known failures from text, one entry per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(s: &str) -> Self {
+        s.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Reads known failures from a file, see [KnownFailures::parse].
+    pub fn from_file(path: &Path) -> std::io::Result<Self> {
+        std::fs::read_to_string(path).map(|s| Self::parse(&s))
+    }
+
+    /// Returns entry that matches the failed test.
+    fn find(&self, path: &Path, name: &str) -> Option<&String> {
+        self.0
+            .iter()
+            .find(|entry| *entry == name || path.ends_with(entry.as_str()))
+    }
+}
+
+impl FromIterator<String> for KnownFailures {
+    fn from_iter<T: IntoIterator<Item = String>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Outcome of the conformance run.
+#[derive(Debug, Default)]
+pub struct ConformanceReport {
+    /// Fixture files where all tests passed.
+    pub passed: Vec<PathBuf>,
+    /// Fixture files skipped by the runner.
+    pub skipped: Vec<PathBuf>,
+    /// Fixture files in a format that is not supported yet.
+    pub unsupported: Vec<PathBuf>,
+    /// Failures that are in the list of known failures.
+    pub known_failures: Vec<TestError>,
+    /// Failures that are not in the list of known failures.
+    pub failures: Vec<TestError>,
+    /// Known failures that did not fail.
+    pub stale_known_failures: Vec<String>,
+}
+
+impl ConformanceReport {
+    /// Returns `true` if there are no unexpected failures and all known failures still fail.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty() && self.stale_known_failures.is_empty()
+    }
+}
+
+/// Runs all fixtures found in the given paths and checks failures against known failures.
+///
+/// Directories are searched recursively for files with the extension `.json`.
+/// Execution of a fixture file stops on its first failing test.
+pub fn run_conformance(paths: &[PathBuf], known_failures: &KnownFailures) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    let mut matched = HashSet::new();
+    let elapsed = Arc::new(Mutex::new(Duration::ZERO));
+
+    for path in paths.iter().flat_map(|path| find_all_json_tests(path)) {
+        if skip_test(&path) {
+            report.skipped.push(path);
+            continue;
+        }
+
+        let kind = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .map_or(FixtureKind::Unknown, |fixture| {
+                FixtureKind::detect(&fixture)
+            });
+        if kind != FixtureKind::StateTest {
+            report.unsupported.push(path);
+            continue;
+        }
+
+        match execute_test_suite(&path, &elapsed, false, false, None) {
+            Ok(()) => report.passed.push(path),
+            Err(error) => match known_failures.find(&path, &error.name) {
+                Some(entry) => {
+                    matched.insert(entry.clone());
+                    report.known_failures.push(error);
+                }
+                None => report.failures.push(error),
+            },
+        }
+    }
+
+    report.stale_known_failures = known_failures
+        .0
+        .iter()
+        .filter(|entry| !matched.contains(*entry))
+        .cloned()
+        .collect();
+    report.stale_known_failures.sort();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATE_TEST: &str = r#"{
+        "failing_test": {
+            "env": {
+                "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                "currentDifficulty": "0x0",
+                "currentGasLimit": "0x989680",
+                "currentNumber": "0x1",
+                "currentTimestamp": "0x3e8",
+                "currentBaseFee": "0x7",
+                "currentRandom": "0x0000000000000000000000000000000000000000000000000000000000000000"
+            },
+            "pre": {
+                "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": {
+                    "balance": "0x3635c9adc5dea00000",
+                    "code": "0x",
+                    "nonce": "0x0",
+                    "storage": {}
+                }
+            },
+            "transaction": {
+                "data": ["0x"],
+                "gasLimit": ["0x5208"],
+                "gasPrice": "0xa",
+                "nonce": "0x0",
+                "secretKey": "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8",
+                "sender": "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b",
+                "to": "0x1000000000000000000000000000000000000000",
+                "value": ["0x1"]
+            },
+            "post": {
+                "Cancun": [{
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "logs": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "indexes": { "data": 0, "gas": 0, "value": 0 },
+                    "state": {}
+                }]
+            }
+        }
+    }"#;
+
+    const BLOCKCHAIN_TEST: &str = r#"{
+        "test": { "genesisBlockHeader": {}, "blocks": [], "pre": {}, "postState": {} }
+    }"#;
+
+    #[test]
+    fn known_failures() {
+        let dir = std::env::temp_dir().join(format!("bcevme-conformance-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("state_test.json"), STATE_TEST).unwrap();
+        std::fs::write(dir.join("blockchain_test.json"), BLOCKCHAIN_TEST).unwrap();
+        let paths = [dir.clone()];
+
+        let report = run_conformance(&paths, &KnownFailures::default());
+        assert_eq!(report.unsupported, vec![dir.join("blockchain_test.json")]);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].name, "failing_test");
+        assert!(!report.is_success());
+
+        let known = KnownFailures::parse("# expected\nfailing_test\n\nstale_test\n");
+        let report = run_conformance(&paths, &known);
+        assert!(report.failures.is_empty());
+        assert_eq!(report.known_failures.len(), 1);
+        assert_eq!(report.stale_known_failures, vec!["stale_test".to_string()]);
+        assert!(!report.is_success());
+
+        let report = run_conformance(&paths, &KnownFailures::parse("state_test.json"));
+        assert!(report.is_success());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
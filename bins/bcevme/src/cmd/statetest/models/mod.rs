@@ -24,6 +24,9 @@ pub struct TestUnit {
     pub transaction: TransactionParts,
     #[serde(default)]
     pub out: Option<Bytes>,
+    /// Chain configuration of execution-spec-tests fixtures.
+    #[serde(default)]
+    pub config: Option<serde_json::Value>,
 }
 
 /// State test indexed state result deserialization.
@@ -37,7 +40,7 @@ pub struct Test {
     /// Post state hash
     pub hash: B256,
     /// Post state
-    #[serde(default)]
+    #[serde(default, alias = "state")]
     pub post_state: HashMap<Address, AccountInfo>,
 
     /// Logs root
@@ -3,7 +3,6 @@ use super::{
     models::{SpecName, Test, TestSuite},
     utils::recover_address,
 };
-use indicatif::{ProgressBar, ProgressDrawTarget};
 use bcevm::{
     db::EmptyDB,
     inspector_handle_register,
@@ -14,6 +13,8 @@ use bcevm::{
     },
     Evm, State,
 };
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use serde::Serialize;
 use serde_json::json;
 use std::{
     convert::Infallible,
@@ -72,7 +73,7 @@ pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
     }
 }
 
-fn skip_test(path: &Path) -> bool {
+pub fn skip_test(path: &Path) -> bool {
     let path_str = path.to_str().expect("Path is not valid UTF-8");
     let name = path.file_name().unwrap().to_str().unwrap();
 
@@ -225,15 +226,66 @@ fn check_evm_execution<EXT>(
     Ok(())
 }
 
+/// Options of the [run] function.
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    /// Run tests in a single thread.
+    pub single_thread: bool,
+    /// Number of threads, available parallelism is used if not set.
+    pub threads: Option<usize>,
+    /// Print EIP-3155 traces of the executions.
+    pub trace: bool,
+    /// Print outcome of the executions in JSON format.
+    pub print_outcome: bool,
+    /// Continue running the tests after a failure.
+    pub keep_going: bool,
+    /// Run only tests whose name or file path contains the pattern.
+    pub name_filter: Option<String>,
+}
+
+/// Failed test file in the [TestSummary].
+#[derive(Clone, Debug, Serialize)]
+pub struct TestFailure {
+    pub path: PathBuf,
+    /// Name of the failed test in the file.
+    pub name: String,
+    pub error: String,
+}
+
+/// Summary of the executed test files.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestSummary {
+    pub total: usize,
+    pub passed: usize,
+    pub skipped: usize,
+    /// Total CPU time spent executing transactions.
+    pub elapsed_secs: f64,
+    pub failures: Vec<TestFailure>,
+}
+
+impl TestSummary {
+    /// Adds the results of the other summary.
+    pub fn extend(&mut self, other: TestSummary) {
+        self.total += other.total;
+        self.passed += other.passed;
+        self.skipped += other.skipped;
+        self.elapsed_secs += other.elapsed_secs;
+        self.failures.extend(other.failures);
+    }
+}
+
 pub fn execute_test_suite(
     path: &Path,
     elapsed: &Arc<Mutex<Duration>>,
     trace: bool,
     print_json_outcome: bool,
+    name_filter: Option<&str>,
 ) -> Result<(), TestError> {
     if skip_test(path) {
         return Ok(());
     }
+    let path_matches = name_filter.is_none_or(|filter| path.to_string_lossy().contains(filter));
 
     let s = std::fs::read_to_string(path).unwrap();
     let suite: TestSuite = serde_json::from_str(&s).map_err(|e| TestError {
@@ -242,6 +294,10 @@ pub fn execute_test_suite(
     })?;
 
     for (name, unit) in suite.0 {
+        if !path_matches && !name.contains(name_filter.unwrap_or_default()) {
+            continue;
+        }
+
         // Create database and insert cache
         let mut cache_state = bcevm::CacheState::new(false);
         for (address, info) in unit.pre {
@@ -455,13 +511,23 @@ pub fn execute_test_suite(
     Ok(())
 }
 
+/// Runs the test files and adds their results to the summary.
+///
+/// Returns the first error if execution stopped after a failure, failures of
+/// `keep_going` runs are only added to the summary.
 pub fn run(
     test_files: Vec<PathBuf>,
-    mut single_thread: bool,
-    trace: bool,
-    mut print_outcome: bool,
-    keep_going: bool,
+    options: &RunOptions,
+    summary: &mut TestSummary,
 ) -> Result<(), TestError> {
+    let RunOptions {
+        mut single_thread,
+        threads,
+        trace,
+        mut print_outcome,
+        keep_going,
+        ref name_filter,
+    } = *options;
     // trace implies print_outcome
     if trace {
         print_outcome = true;
@@ -473,6 +539,8 @@ pub fn run(
     let n_files = test_files.len();
 
     let n_errors = Arc::new(AtomicUsize::new(0));
+    let n_skipped = Arc::new(AtomicUsize::new(0));
+    let failures = Arc::new(Mutex::new(Vec::new()));
     let console_bar = Arc::new(ProgressBar::with_draw_target(
         Some(n_files as u64),
         ProgressDrawTarget::stdout(),
@@ -480,17 +548,21 @@ pub fn run(
     let queue = Arc::new(Mutex::new((0usize, test_files)));
     let elapsed = Arc::new(Mutex::new(std::time::Duration::ZERO));
 
-    let num_threads = match (single_thread, std::thread::available_parallelism()) {
-        (true, _) | (false, Err(_)) => 1,
-        (false, Ok(n)) => n.get(),
+    let num_threads = match (single_thread, threads, std::thread::available_parallelism()) {
+        (true, _, _) | (false, None, Err(_)) => 1,
+        (false, Some(n), _) => n.max(1),
+        (false, None, Ok(n)) => n.get(),
     };
     let num_threads = num_threads.min(n_files);
     let mut handles = Vec::with_capacity(num_threads);
     for i in 0..num_threads {
         let queue = queue.clone();
         let n_errors = n_errors.clone();
+        let n_skipped = n_skipped.clone();
+        let failures = failures.clone();
         let console_bar = console_bar.clone();
         let elapsed = elapsed.clone();
+        let name_filter = name_filter.clone();
 
         let thread = std::thread::Builder::new().name(format!("runner-{i}"));
 
@@ -510,8 +582,23 @@ pub fn run(
             };
 
             console_bar.inc(1);
-            if let Err(err) = execute_test_suite(&test_path, &elapsed, trace, print_outcome) {
+            if skip_test(&test_path) {
+                n_skipped.fetch_add(1, Ordering::SeqCst);
+                continue;
+            }
+            if let Err(err) = execute_test_suite(
+                &test_path,
+                &elapsed,
+                trace,
+                print_outcome,
+                name_filter.as_deref(),
+            ) {
                 n_errors.fetch_add(1, Ordering::SeqCst);
+                failures.lock().unwrap().push(TestFailure {
+                    path: test_path,
+                    name: err.name.clone(),
+                    error: err.kind.to_string(),
+                });
                 if !keep_going {
                     return Err(err);
                 }
@@ -534,12 +621,22 @@ pub fn run(
     }
     console_bar.finish();
 
-    println!(
-        "Finished execution. Total CPU time: {:.6}s",
-        elapsed.lock().unwrap().as_secs_f64()
-    );
+    let elapsed_secs = elapsed.lock().unwrap().as_secs_f64();
+    println!("Finished execution. Total CPU time: {elapsed_secs:.6}s");
 
     let n_errors = n_errors.load(Ordering::SeqCst);
+    let n_skipped = n_skipped.load(Ordering::SeqCst);
+    let failures = std::mem::take(&mut *failures.lock().unwrap());
+    // files that were not executed after a failure are not counted as passed.
+    let n_executed = queue.lock().unwrap().0;
+    summary.extend(TestSummary {
+        total: n_files,
+        passed: n_executed.saturating_sub(n_skipped + failures.len()),
+        skipped: n_skipped,
+        elapsed_secs,
+        failures,
+    });
+
     let n_thread_errors = thread_errors.len();
     if n_errors == 0 && n_thread_errors == 0 {
         println!("All tests passed!");
@@ -548,7 +645,7 @@ pub fn run(
         println!("Encountered {n_errors} errors out of {n_files} total tests");
 
         if n_thread_errors == 0 {
-            std::process::exit(1);
+            return Ok(());
         }
 
         if n_thread_errors > 1 {
@@ -2,12 +2,17 @@ use criterion::{
     criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion,
 };
 use bcevm::{
-    db::BenchmarkDB,
+    db::{BenchmarkDB, Database},
+    handler::opcode_hooks::OpcodeHooks,
+    inspector_handle_register,
     interpreter::{analysis::to_analysed, Contract, DummyHost, Interpreter},
     primitives::{address, bytes, hex, BerlinSpec, Bytecode, Bytes, TransactTo, U256},
-    Evm,
+    Evm, EvmContext, Inspector,
+};
+use bcevm_interpreter::{
+    opcode::{self, make_instruction_table},
+    SharedMemory, EMPTY_SHARED_MEMORY,
 };
-use bcevm_interpreter::{opcode::make_instruction_table, SharedMemory, EMPTY_SHARED_MEMORY};
 use std::time::Duration;
 
 fn analysis(c: &mut Criterion) {
@@ -88,6 +93,54 @@ fn transfer(c: &mut Criterion) {
     g.finish();
 }
 
+/// Counts the executed SLOAD opcodes.
+#[derive(Default)]
+struct SloadCounter(u64);
+
+impl<DB: Database> Inspector<DB> for SloadCounter {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        if interp.current_opcode() == opcode::SLOAD {
+            self.0 += 1;
+        }
+    }
+}
+
+fn hooks(c: &mut Criterion) {
+    let evm = Evm::builder()
+        .with_db(BenchmarkDB::new_bytecode(bytecode(SNAILTRACER)))
+        .modify_tx_env(|tx| {
+            tx.caller = address!("1000000000000000000000000000000000000000");
+            tx.transact_to = TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+            tx.data = bytes!("30627b7c");
+        })
+        .build();
+
+    let mut g = c.benchmark_group("hooks");
+    g.noise_threshold(0.03)
+        .warm_up_time(Duration::from_secs(3))
+        .measurement_time(Duration::from_secs(10))
+        .sample_size(10);
+
+    let mut evm = evm
+        .modify()
+        .reset_handler_with_external_context(SloadCounter::default())
+        .append_handler_register(inspector_handle_register)
+        .build();
+    g.bench_function("inspector", |b| b.iter(|| evm.transact().unwrap()));
+
+    let hooks = OpcodeHooks::new().before(opcode::SLOAD, |_, evm: &mut Evm<'_, u64, _>| {
+        evm.context.external += 1
+    });
+    let mut evm = evm
+        .modify()
+        .reset_handler_with_external_context(0u64)
+        .append_handler_register_box(hooks.into_register())
+        .build();
+    g.bench_function("opcode_hooks", |b| b.iter(|| evm.transact().unwrap()));
+
+    g.finish();
+}
+
 fn bench_transact<EXT>(g: &mut BenchmarkGroup<'_, WallTime>, evm: &mut Evm<'_, EXT, BenchmarkDB>) {
     let state = match evm.context.evm.db.0 {
         Bytecode::LegacyRaw(_) => "raw",
@@ -131,6 +184,7 @@ criterion_group!(
     analysis,
     snailtracer,
     transfer,
+    hooks,
 );
 criterion_main!(benches);
 
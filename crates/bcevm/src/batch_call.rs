@@ -0,0 +1,564 @@
+//! Utility precompile that executes a batch of calls.
+//!
+//! [BatchCallPrecompile] takes the ABI encoding of `(address target, bytes callData)[]`
+//! and calls every target in order, returning the ABI encoding of
+//! `(bool success, bytes returnData)[]`. A failed call does not abort the batch.
+//!
+//! The precompile is a [ContextCallPrecompile]: calls are made by the precompile address
+//! with [EvmContext::make_call_frame], so they see the precompiles of the context, and their
+//! state changes and logs are kept. The calls inherit the static context of the call to the
+//! precompile. Frames of the calls run the mainnet instructions of the spec with the opcode
+//! gas overrides of the configuration, instruction tables and inspectors of the handler are
+//! not used.
+
+use crate::{
+    db::Database,
+    interpreter::{
+        gas,
+        opcode::{make_instruction_table, InstructionTables},
+        return_ok, return_revert, CallInputs, CallOutcome, CallScheme, CallValue, CreateOutcome,
+        EOFCreateOutcome, Gas, HostRead, HostWrite, InstructionResult, InterpreterAction,
+        InterpreterResult, LoadAccountResult, SStoreResult, SelfDestructResult, SharedMemory,
+    },
+    primitives::{
+        spec_to_generic, Address, Bytecode, Bytes, EVMError, Env, Log, PrecompileError,
+        PrecompileOutput, PrecompileResult, Spec, SpecId, B256, U256,
+    },
+    ContextCallPrecompile, ContextPrecompile, EvmContext, Frame, FrameOrResult, FrameResult,
+};
+use std::{sync::Arc, vec, vec::Vec};
+
+/// Gas charged for every call of the batch, on top of the account access cost.
+pub const BATCH_CALL_GAS: u64 = 100;
+
+/// Precompile that executes a batch of calls, see [module](self) documentation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchCallPrecompile;
+
+impl<DB: Database> From<BatchCallPrecompile> for ContextPrecompile<DB> {
+    fn from(precompile: BatchCallPrecompile) -> Self {
+        ContextPrecompile::ContextCall(Arc::new(precompile))
+    }
+}
+
+impl<DB: Database> ContextCallPrecompile<DB> for BatchCallPrecompile {
+    fn call(&self, inputs: &CallInputs, context: &mut EvmContext<DB>) -> PrecompileResult {
+        let calls = decode_calls(&inputs.input)
+            .ok_or_else(|| PrecompileError::other("invalid batch input"))?;
+        let result = spec_to_generic!(
+            context.spec_id(),
+            execute_batch::<SPEC, DB>(inputs, &calls, context)
+        );
+        // database errors fail the transaction, the same way as in the frames of the EVM.
+        result.map_err(|e| {
+            context.error = Err(e);
+            PrecompileError::other("database error")
+        })?
+    }
+}
+
+fn execute_batch<SPEC: Spec, DB: Database>(
+    inputs: &CallInputs,
+    calls: &[(Address, Bytes)],
+    context: &mut EvmContext<DB>,
+) -> Result<PrecompileResult, EVMError<DB::Error>> {
+    let mut gas = Gas::new(inputs.gas_limit);
+    let mut executor = BatchExecutor::<SPEC, DB> {
+        context,
+        _spec: core::marker::PhantomData,
+    };
+    let mut results = Vec::with_capacity(calls.len());
+    for (target, data) in calls {
+        let (_, is_cold) = executor.context.load_account(*target)?;
+        let access_cost = if SPEC::enabled(SpecId::BERLIN) {
+            gas::warm_cold_cost(is_cold)
+        } else {
+            0
+        };
+        if !gas.record_cost(BATCH_CALL_GAS + access_cost) {
+            return Ok(Err(PrecompileError::OutOfGas));
+        }
+        // EIP-150: all but one 64th of the remaining gas is forwarded.
+        let gas_limit = if SPEC::enabled(SpecId::TANGERINE) {
+            gas.remaining() - gas.remaining() / 64
+        } else {
+            gas.remaining()
+        };
+        let call = CallInputs {
+            input: data.clone(),
+            return_memory_offset: 0..0,
+            gas_limit,
+            bytecode_address: *target,
+            target_address: *target,
+            caller: inputs.target_address,
+            value: CallValue::Transfer(U256::ZERO),
+            scheme: CallScheme::Call,
+            is_static: inputs.is_static,
+            is_eof: false,
+        };
+        let result = executor.call(&call)?;
+        if result.result == InstructionResult::Canceled {
+            return Ok(Err(PrecompileError::other("execution canceled")));
+        }
+        // halted calls consume all of their gas.
+        let spent = match result.result {
+            return_ok!() | return_revert!() => result.gas.spent(),
+            _ => gas_limit,
+        };
+        if !gas.record_cost(spent) {
+            return Ok(Err(PrecompileError::OutOfGas));
+        }
+        results.push((result.is_ok(), result.output));
+    }
+    Ok(Ok(PrecompileOutput::new(
+        gas.spent(),
+        encode_results(&results),
+    )))
+}
+
+/// Frame loop and host of the batched calls.
+///
+/// Frames are made and returned by the [EvmContext], database errors are stored in
+/// [InnebcevmContext::error](crate::InnebcevmContext::error) the same way as the host of
+/// [Evm](crate::Evm) does.
+struct BatchExecutor<'a, SPEC, DB: Database> {
+    context: &'a mut EvmContext<DB>,
+    _spec: core::marker::PhantomData<SPEC>,
+}
+
+impl<SPEC: Spec, DB: Database> BatchExecutor<'_, SPEC, DB> {
+    /// Executes the call and the frames of its sub calls and creates.
+    fn call(&mut self, inputs: &CallInputs) -> Result<InterpreterResult, EVMError<DB::Error>> {
+        let frame = match self.context.make_call_frame(inputs)? {
+            FrameOrResult::Frame(frame) => frame,
+            FrameOrResult::Result(result) => return Ok(result.into_interpreter_result()),
+        };
+        let mut table = InstructionTables::Plain(make_instruction_table::<Self, SPEC>());
+        if let Some(overrides) = &self.context.env.cfg.gas_schedule_overrides {
+            table.apply_gas_schedule_overrides(overrides, SPEC::SPEC_ID);
+        }
+        let mut call_stack = vec![frame];
+        let mut memory = SharedMemory::new();
+        memory.new_context();
+        loop {
+            let interpreter = &mut call_stack
+                .last_mut()
+                .expect("call stack is not empty")
+                .frame_data_mut()
+                .interpreter;
+            if let Some(token) = &self.context.cancellation {
+                interpreter
+                    .cancellation
+                    .get_or_insert_with(|| token.clone());
+            }
+            let action = match &table {
+                InstructionTables::Plain(table) => interpreter.run(memory, table, self),
+                InstructionTables::Boxed(table) => interpreter.run(memory, table, self),
+                InstructionTables::Specialized(_) | InstructionTables::Cached(_) => {
+                    unreachable!("batch call table is plain or boxed")
+                }
+            };
+            self.context.take_error()?;
+            memory = interpreter.take_memory();
+
+            let frame_or_result = match action {
+                InterpreterAction::Call { inputs } => self.context.make_call_frame(&inputs)?,
+                InterpreterAction::Create { inputs } => {
+                    self.context.make_create_frame(SPEC::SPEC_ID, &inputs)?
+                }
+                InterpreterAction::EOFCreate { inputs } => {
+                    self.context.make_eofcreate_frame(SPEC::SPEC_ID, &inputs)?
+                }
+                InterpreterAction::Return { mut result } => {
+                    memory.free_context();
+                    let result = match call_stack.pop().expect("call stack is not empty") {
+                        Frame::Call(frame) => {
+                            self.context
+                                .call_return(&result, frame.frame_data.checkpoint);
+                            FrameResult::Call(CallOutcome::new(result, frame.return_memory_range))
+                        }
+                        Frame::Create(frame) => {
+                            self.context.create_return::<SPEC>(
+                                &mut result,
+                                frame.created_address,
+                                frame.frame_data.checkpoint,
+                            );
+                            FrameResult::Create(CreateOutcome::new(
+                                result,
+                                Some(frame.created_address),
+                            ))
+                        }
+                        Frame::EOFCreate(frame) => {
+                            self.context.eofcreate_return::<SPEC>(
+                                &mut result,
+                                frame.created_address,
+                                frame.frame_data.checkpoint,
+                            );
+                            FrameResult::EOFCreate(EOFCreateOutcome::new(
+                                result,
+                                frame.created_address,
+                                frame.return_memory_range,
+                            ))
+                        }
+                    };
+                    FrameOrResult::Result(result)
+                }
+                InterpreterAction::None => unreachable!("InterpreterAction::None is not expected"),
+            };
+
+            match frame_or_result {
+                FrameOrResult::Frame(frame) => {
+                    memory.new_context();
+                    call_stack.push(frame);
+                }
+                FrameOrResult::Result(result) => {
+                    let Some(frame) = call_stack.last_mut() else {
+                        return Ok(result.into_interpreter_result());
+                    };
+                    let interpreter = &mut frame.frame_data_mut().interpreter;
+                    match result {
+                        FrameResult::Call(outcome) => {
+                            interpreter.insert_call_outcome(&mut memory, outcome)
+                        }
+                        FrameResult::Create(outcome) => interpreter.insert_create_outcome(outcome),
+                        FrameResult::EOFCreate(outcome) => {
+                            interpreter.insert_eofcreate_outcome(outcome)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<SPEC: Spec, DB: Database> HostRead for BatchExecutor<'_, SPEC, DB> {
+    fn env(&self) -> &Env {
+        &self.context.env
+    }
+
+    fn load_account(&mut self, address: Address) -> Option<LoadAccountResult> {
+        self.context
+            .load_account_exist(address)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn block_hash(&mut self, number: U256) -> Option<B256> {
+        self.context
+            .block_hash(number)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn balance(&mut self, address: Address) -> Option<(U256, bool)> {
+        self.context
+            .balance(address)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn code(&mut self, address: Address) -> Option<(Bytecode, bool)> {
+        self.context
+            .code(address)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn code_hash(&mut self, address: Address) -> Option<(B256, bool)> {
+        self.context
+            .code_hash(address)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Option<Bytecode> {
+        self.context
+            .code_by_hash(code_hash)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn copy_code(&mut self, address: Address, code_offset: usize, dest: &mut [u8]) -> Option<()> {
+        self.context
+            .copy_code(address, code_offset, dest)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn sload(&mut self, address: Address, index: U256) -> Option<(U256, bool)> {
+        self.context
+            .sload(address, index)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn tload(&mut self, address: Address, index: U256) -> U256 {
+        self.context.tload(address, index)
+    }
+
+    fn cold_access_charged(&mut self, gas: u64) {
+        self.context.journaled_state.record_cold_access(gas);
+    }
+}
+
+impl<SPEC: Spec, DB: Database> HostWrite for BatchExecutor<'_, SPEC, DB> {
+    fn env_mut(&mut self) -> &mut Env {
+        &mut self.context.env
+    }
+
+    fn sstore(&mut self, address: Address, index: U256, value: U256) -> Option<SStoreResult> {
+        self.context
+            .sstore(address, index, value)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+
+    fn tstore(&mut self, address: Address, index: U256, value: U256) {
+        self.context.tstore(address, index, value)
+    }
+
+    fn log(&mut self, log: Log) {
+        self.context.journaled_state.log(log);
+    }
+
+    fn selfdestruct(&mut self, address: Address, target: Address) -> Option<SelfDestructResult> {
+        self.context
+            .selfdestruct(address, target)
+            .map_err(|e| self.context.error = Err(e))
+            .ok()
+    }
+}
+
+/// Decodes the ABI encoding of `(address, bytes)[]`.
+fn decode_calls(input: &[u8]) -> Option<Vec<(Address, Bytes)>> {
+    let array = read_offset(input, 0, 0)?;
+    let len = read_usize(input, array)?;
+    let items = array.checked_add(32)?;
+    // every item takes at least the head offset.
+    if len > input.len() / 32 {
+        return None;
+    }
+    (0..len)
+        .map(|i| {
+            let item = read_offset(input, items, items.checked_add(i * 32)?)?;
+            let address = read_word(input, item)?;
+            if address[..12] != [0; 12] {
+                return None;
+            }
+            let data = read_offset(input, item, item.checked_add(32)?)?;
+            let data_len = read_usize(input, data)?;
+            let start = data.checked_add(32)?;
+            let data = input.get(start..start.checked_add(data_len)?)?;
+            Some((
+                Address::from_slice(&address[12..]),
+                Bytes::copy_from_slice(data),
+            ))
+        })
+        .collect()
+}
+
+/// Encodes the ABI encoding of `(bool, bytes)[]`.
+fn encode_results(results: &[(bool, Bytes)]) -> Bytes {
+    let mut out = Vec::new();
+    out.extend_from_slice(&word(32));
+    out.extend_from_slice(&word(results.len()));
+    let mut offset = results.len() * 32;
+    for (_, output) in results {
+        out.extend_from_slice(&word(offset));
+        offset += 96 + output.len().div_ceil(32) * 32;
+    }
+    for (success, output) in results {
+        out.extend_from_slice(&word(*success as usize));
+        out.extend_from_slice(&word(64));
+        out.extend_from_slice(&word(output.len()));
+        out.extend_from_slice(output);
+        out.resize(out.len() + (32 - output.len() % 32) % 32, 0);
+    }
+    out.into()
+}
+
+fn word(value: usize) -> [u8; 32] {
+    U256::from(value).to_be_bytes()
+}
+
+fn read_word(input: &[u8], offset: usize) -> Option<&[u8]> {
+    input.get(offset..offset.checked_add(32)?)
+}
+
+fn read_usize(input: &[u8], offset: usize) -> Option<usize> {
+    U256::from_be_slice(read_word(input, offset)?)
+        .try_into()
+        .ok()
+}
+
+/// Reads the offset at `offset`, relative to `base`.
+fn read_offset(input: &[u8], base: usize, offset: usize) -> Option<usize> {
+    base.checked_add(read_usize(input, offset)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        interpreter::opcode,
+        primitives::{address, AccountInfo, TransactTo},
+        Evm,
+    };
+    use alloy_sol_types::SolValue;
+
+    const BATCH: Address = address!("0000000000000000000000000000000000000b01");
+
+    #[test]
+    fn abi_round_trip() {
+        let calls = vec![
+            (Address::with_last_byte(1), Bytes::from_static(&[1, 2, 3])),
+            (Address::with_last_byte(2), Bytes::new()),
+        ];
+        let input = (calls.clone(),).abi_encode_params();
+        assert_eq!(decode_calls(&input), Some(calls));
+        assert_eq!(decode_calls(&input[..input.len() - 1]), None);
+        assert_eq!(decode_calls(&[]), None);
+
+        let results = vec![(true, Bytes::from(vec![7; 33])), (false, Bytes::new())];
+        let encoded = encode_results(&results);
+        assert_eq!(encoded, (results,).abi_encode_params());
+    }
+
+    #[test]
+    fn batch_calls() {
+        // returns CALLER and stores to slot zero if called with input.
+        let echo = address!("1000000000000000000000000000000000000001");
+        let echo_code = Bytecode::new_raw(Bytes::from(vec![
+            opcode::CALLDATASIZE,
+            opcode::PUSH1,
+            0x0b,
+            opcode::JUMPI,
+            opcode::CALLER,
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH0,
+            opcode::RETURN,
+            opcode::JUMPDEST,
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ]));
+        // static calls echo and returns the output.
+        let proxy = address!("2000000000000000000000000000000000000002");
+        let proxy_code = Bytecode::new_raw(Bytes::from(vec![
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH20,
+            0x10,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0x01,
+            opcode::GAS,
+            opcode::STATICCALL,
+            opcode::POP,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH0,
+            opcode::RETURN,
+        ]));
+
+        let mut db = InMemoryDB::default();
+        for (address, code) in [(echo, echo_code), (proxy, proxy_code)] {
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+
+        let identity = Address::with_last_byte(4);
+        let calls = vec![
+            (echo, Bytes::new()),
+            (proxy, Bytes::new()),
+            (identity, Bytes::from_static(&[0xaa, 0xbb])),
+            (Address::with_last_byte(0xff), Bytes::new()),
+            // precompiles of the context are called, including the batch itself.
+            (
+                BATCH,
+                (Vec::<(Address, Bytes)>::new(),).abi_encode_params().into(),
+            ),
+            // state changes are kept.
+            (echo, Bytes::from_static(&[1])),
+        ];
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .append_precompile(BATCH, BatchCallPrecompile)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(BATCH);
+                tx.data = (calls.clone(),).abi_encode_params().into();
+            })
+            .build();
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            result.state[&echo].storage[&U256::ZERO].present_value,
+            U256::from(1)
+        );
+
+        let output = result.result.output().unwrap();
+        let (results,) = <(Vec<(bool, Bytes)>,)>::abi_decode_params(output, true).unwrap();
+        assert_eq!(
+            results,
+            [
+                (true, Bytes::from(BATCH.into_word().to_vec())),
+                (true, Bytes::from(proxy.into_word().to_vec())),
+                (true, Bytes::from_static(&[0xaa, 0xbb])),
+                (true, Bytes::new()),
+                (true, encode_results(&[])),
+                (true, Bytes::new()),
+            ]
+        );
+
+        // calls inherit the static context, the state change fails.
+        let inputs = CallInputs {
+            input: (calls[5..].to_vec(),).abi_encode_params().into(),
+            return_memory_offset: 0..0,
+            gas_limit: 1_000_000,
+            bytecode_address: BATCH,
+            target_address: BATCH,
+            caller: Address::ZERO,
+            value: CallValue::Transfer(U256::ZERO),
+            scheme: CallScheme::StaticCall,
+            is_static: true,
+            is_eof: false,
+        };
+        let output = BatchCallPrecompile
+            .call(&inputs, &mut evm.context.evm)
+            .unwrap();
+        let (results,) = <(Vec<(bool, Bytes)>,)>::abi_decode_params(&output.bytes, true).unwrap();
+        assert_eq!(results, [(false, Bytes::new())]);
+
+        // invalid input fails the precompile.
+        evm.tx_mut().data = Bytes::from_static(&[1, 2, 3]);
+        assert!(!evm.transact().unwrap().result.is_success());
+    }
+}
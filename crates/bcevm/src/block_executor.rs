@@ -0,0 +1,420 @@
+use crate::{
+    db::{Database, DatabaseCommit},
+    primitives::{
+        hash_map::Entry, Account, Address, BlockEnv, Bytes, EVMError, ExecutionResult, HashMap,
+        InvalidTransaction, ReceiptContext, ResultAndState, RpcReceipt, SpecId, TransactTo, TxEnv,
+        B256, BEACON_ROOTS_ADDRESS, BLOCKHASH_STORAGE_ADDRESS, SYSTEM_ADDRESS,
+        SYSTEM_CALL_GAS_LIMIT, U256,
+    },
+    Evm,
+};
+use std::vec::Vec;
+
+/// One ether in wei.
+const ETHER: u128 = 1_000_000_000_000_000_000;
+
+/// One gwei in wei.
+const GWEI: u64 = 1_000_000_000;
+
+/// EIP-4895: Withdrawal of the validator balance to the execution layer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Withdrawal {
+    /// Monotonically increasing index of the withdrawal.
+    pub index: u64,
+    /// Index of the validator.
+    pub validator_index: u64,
+    /// Recipient of the withdrawal.
+    pub address: Address,
+    /// Amount of the withdrawal in gwei.
+    pub amount: u64,
+}
+
+impl Withdrawal {
+    /// Returns amount of the withdrawal in wei.
+    pub fn amount_wei(&self) -> U256 {
+        U256::from(self.amount) * U256::from(GWEI)
+    }
+}
+
+/// Ommer (uncle) block header fields needed for the block reward.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ommer {
+    /// Beneficiary of the ommer block.
+    pub beneficiary: Address,
+    /// Number of the ommer block.
+    pub number: u64,
+}
+
+/// Block that is executed by the [BlockExecutor].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Block {
+    /// Environment of the block.
+    pub env: BlockEnv,
+    /// Transactions of the block.
+    pub transactions: Vec<TxEnv>,
+    /// Ommers of the block, rewarded before the Merge.
+    pub ommers: Vec<Ommer>,
+    /// Withdrawals of the block, processed from Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// EIP-2935: Hash of the parent block, stored from Prague.
+    pub parent_hash: Option<B256>,
+}
+
+/// Outcome of the block execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockExecutionOutput {
+    /// Results of the transactions.
+    pub results: Vec<ExecutionResult>,
+    /// Gas used by all transactions of the block.
+    pub gas_used: u64,
+}
+
+impl BlockExecutionOutput {
+    /// Returns the receipts of the transactions in the shape of the Ethereum JSON-RPC
+    /// response, with cumulative gas used and log indices counted from the start of the
+    /// block.
+    ///
+    /// Transaction hashes are in the order of the transactions, missing hashes are zero.
+    pub fn rpc_receipts(
+        &self,
+        block_hash: B256,
+        block_number: u64,
+        transaction_hashes: &[B256],
+    ) -> Vec<RpcReceipt> {
+        let mut context = ReceiptContext {
+            block_hash,
+            block_number,
+            ..Default::default()
+        };
+        let mut receipts = Vec::with_capacity(self.results.len());
+        for (i, result) in self.results.iter().enumerate() {
+            context.transaction_hash = transaction_hashes.get(i).copied().unwrap_or_default();
+            context.transaction_index = i as u64;
+            let receipt = result.to_rpc_receipt(&context);
+            context.cumulative_gas_used = receipt.cumulative_gas_used;
+            context.first_log_index += receipt.logs.len() as u64;
+            receipts.push(receipt);
+        }
+        receipts
+    }
+}
+
+/// Executes blocks on top of the database of the [Evm] and commits the changes.
+///
+/// Besides transactions it applies the system calls of EIP-4788 and EIP-2935 at the
+/// start of the block, and the withdrawals and the block rewards at the end of it,
+/// depending on the spec id of the EVM.
+pub struct BlockExecutor<'a, EXT, DB: Database> {
+    evm: Evm<'a, EXT, DB>,
+}
+
+impl<'a, EXT, DB: Database + DatabaseCommit> BlockExecutor<'a, EXT, DB> {
+    /// Creates new block executor.
+    pub fn new(evm: Evm<'a, EXT, DB>) -> Self {
+        Self { evm }
+    }
+
+    /// Returns reference to the EVM.
+    pub fn evm(&self) -> &Evm<'a, EXT, DB> {
+        &self.evm
+    }
+
+    /// Returns mutable reference to the EVM.
+    pub fn evm_mut(&mut self) -> &mut Evm<'a, EXT, DB> {
+        &mut self.evm
+    }
+
+    /// Returns the EVM.
+    pub fn into_evm(self) -> Evm<'a, EXT, DB> {
+        self.evm
+    }
+
+    /// Executes the block and commits its changes to the database.
+    ///
+    /// Execution stops on the first invalid transaction, changes of the previous
+    /// transactions stay committed.
+    pub fn execute_block(
+        &mut self,
+        block: &Block,
+    ) -> Result<BlockExecutionOutput, EVMError<DB::Error>> {
+        let spec_id = self.evm.spec_id();
+        *self.evm.block_mut() = block.env.clone();
+        let number = block.env.number.saturating_to::<u64>();
+
+        if SpecId::enabled(spec_id, SpecId::CANCUN) && number != 0 {
+            if let Some(root) = block.env.parent_beacon_block_root {
+                self.system_call(BEACON_ROOTS_ADDRESS, root)?;
+            }
+        }
+        if SpecId::enabled(spec_id, SpecId::PRAGUE) && number != 0 {
+            if let Some(parent_hash) = block.parent_hash {
+                self.system_call(BLOCKHASH_STORAGE_ADDRESS, parent_hash)?;
+            }
+        }
+
+        let mut output = BlockExecutionOutput::default();
+        let block_gas_limit = block.env.gas_limit.saturating_to::<u64>();
+        for tx in &block.transactions {
+            if output.gas_used.saturating_add(tx.gas_limit) > block_gas_limit {
+                return Err(InvalidTransaction::CallerGasLimitMoreThanBlock.into());
+            }
+            *self.evm.tx_mut() = tx.clone();
+            let result = self.evm.transact_commit()?;
+            output.gas_used += result.gas_used();
+            output.results.push(result);
+        }
+
+        let mut increments = Vec::new();
+        if SpecId::enabled(spec_id, SpecId::SHANGHAI) {
+            if let Some(withdrawals) = &block.withdrawals {
+                increments.extend(withdrawals.iter().map(|w| (w.address, w.amount_wei())));
+            }
+        }
+        if let Some(reward) = block_reward(spec_id) {
+            let ommers = U256::from(block.ommers.len());
+            increments.push((
+                block.env.coinbase,
+                reward + reward / U256::from(32) * ommers,
+            ));
+            for ommer in &block.ommers {
+                let distance = U256::from(8 + ommer.number).saturating_sub(U256::from(number));
+                increments.push((ommer.beneficiary, reward * distance / U256::from(8)));
+            }
+        }
+        self.increment_balances(increments)?;
+
+        Ok(output)
+    }
+
+    /// Calls the system contract with the given input and commits its changes.
+    ///
+    /// System call does not pay for gas, is not limited by the block gas limit and does
+    /// not change the system address and the beneficiary.
+    fn system_call(&mut self, contract: Address, input: B256) -> Result<(), EVMError<DB::Error>> {
+        let block = self.evm.block().clone();
+        let tx = core::mem::replace(
+            self.evm.tx_mut(),
+            TxEnv {
+                caller: SYSTEM_ADDRESS,
+                transact_to: TransactTo::Call(contract),
+                data: Bytes::copy_from_slice(input.as_slice()),
+                gas_limit: SYSTEM_CALL_GAS_LIMIT,
+                ..Default::default()
+            },
+        );
+        let block_env = self.evm.block_mut();
+        block_env.basefee = U256::ZERO;
+        block_env.gas_limit = block_env.gas_limit.max(U256::from(SYSTEM_CALL_GAS_LIMIT));
+
+        let result = self.evm.transact();
+        *self.evm.tx_mut() = tx;
+        *self.evm.block_mut() = block;
+
+        let ResultAndState { mut state, .. } = result?;
+        state.remove(&SYSTEM_ADDRESS);
+        state.remove(&self.evm.block().coinbase);
+        self.evm.db_mut().commit(state);
+        Ok(())
+    }
+
+    /// Increments balances of the accounts and commits the changes.
+    fn increment_balances(
+        &mut self,
+        increments: Vec<(Address, U256)>,
+    ) -> Result<(), EVMError<DB::Error>> {
+        let db = self.evm.db_mut();
+        let mut changes: HashMap<Address, Account> = HashMap::new();
+        for (address, amount) in increments {
+            if amount.is_zero() {
+                continue;
+            }
+            let account = match changes.entry(address) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let info = db
+                        .basic(address)
+                        .map_err(EVMError::Database)?
+                        .unwrap_or_default();
+                    entry.insert(info.into())
+                }
+            };
+            account.info.balance = account.info.balance.saturating_add(amount);
+            account.mark_touch();
+        }
+        db.commit(changes);
+        Ok(())
+    }
+}
+
+/// Returns static block reward of the beneficiary before the Merge.
+pub fn block_reward(spec_id: SpecId) -> Option<U256> {
+    let reward = if SpecId::enabled(spec_id, SpecId::MERGE) {
+        return None;
+    } else if SpecId::enabled(spec_id, SpecId::PETERSBURG) {
+        2 * ETHER
+    } else if SpecId::enabled(spec_id, SpecId::BYZANTIUM) {
+        3 * ETHER
+    } else {
+        5 * ETHER
+    };
+    Some(U256::from(reward))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::{address, AccountInfo, Bytecode},
+    };
+
+    #[test]
+    fn cancun_block() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let recipient = address!("1000000000000000000000000000000000000002");
+        // PUSH1 0 CALLDATALOAD TIMESTAMP SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x35, 0x42, 0x55]));
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(ETHER)));
+        db.insert_account_info(
+            BEACON_ROOTS_ADDRESS,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut executor = BlockExecutor::new(
+            Evm::builder()
+                .with_db(db)
+                .with_spec_id(SpecId::CANCUN)
+                .build(),
+        );
+        let block = Block {
+            env: BlockEnv {
+                number: U256::from(1),
+                timestamp: U256::from(12),
+                gas_limit: U256::from(30_000_000),
+                parent_beacon_block_root: Some(B256::with_last_byte(0xaa)),
+                ..Default::default()
+            },
+            transactions: vec![TxEnv {
+                caller,
+                transact_to: TransactTo::Call(recipient),
+                value: U256::from(100),
+                gas_limit: 21_000,
+                ..Default::default()
+            }],
+            withdrawals: Some(vec![Withdrawal {
+                address: recipient,
+                amount: 1,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        let output = executor.execute_block(&block).unwrap();
+        assert_eq!(output.gas_used, 21_000);
+        assert!(output.results[0].is_success());
+
+        let db = executor.evm_mut().db_mut();
+        assert_eq!(
+            db.basic(recipient).unwrap().unwrap().balance,
+            U256::from(100 + GWEI)
+        );
+        assert_eq!(
+            db.storage(BEACON_ROOTS_ADDRESS, U256::from(12)),
+            Ok(U256::from(0xaa))
+        );
+        // nonce of the system address is not increased.
+        assert!(db.basic(SYSTEM_ADDRESS).unwrap().is_none());
+    }
+
+    #[test]
+    fn pre_merge_rewards() {
+        let coinbase = address!("2000000000000000000000000000000000000001");
+        let uncle = address!("2000000000000000000000000000000000000002");
+        let mut executor = BlockExecutor::new(
+            Evm::builder()
+                .with_db(InMemoryDB::default())
+                .with_spec_id(SpecId::BYZANTIUM)
+                .build(),
+        );
+        let block = Block {
+            env: BlockEnv {
+                number: U256::from(10),
+                coinbase,
+                ..Default::default()
+            },
+            ommers: vec![Ommer {
+                beneficiary: uncle,
+                number: 9,
+            }],
+            ..Default::default()
+        };
+        executor.execute_block(&block).unwrap();
+
+        let db = executor.evm_mut().db_mut();
+        let reward = U256::from(3 * ETHER);
+        assert_eq!(
+            db.basic(coinbase).unwrap().unwrap().balance,
+            reward + reward / U256::from(32)
+        );
+        assert_eq!(
+            db.basic(uncle).unwrap().unwrap().balance,
+            reward * U256::from(7) / U256::from(8)
+        );
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn rpc_receipts_json() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let emitter = address!("1000000000000000000000000000000000000002");
+        // LOG0(0, 0) LOG0(0, 0)
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x5f, 0x5f, 0xa0, 0x5f, 0x5f, 0xa0]));
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(ETHER)));
+        db.insert_account_info(
+            emitter,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        let mut executor = BlockExecutor::new(Evm::builder().with_db(db).build());
+        let tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(emitter),
+            gas_limit: 100_000,
+            nonce: None,
+            ..Default::default()
+        };
+        let block = Block {
+            env: BlockEnv {
+                gas_limit: U256::from(30_000_000),
+                ..Default::default()
+            },
+            transactions: vec![tx.clone(), tx],
+            ..Default::default()
+        };
+        let output = executor.execute_block(&block).unwrap();
+        let receipts = output.rpc_receipts(B256::with_last_byte(1), 7, &[]);
+
+        let json = serde_json::to_value(&receipts).unwrap();
+        let gas_used = output.results[0].gas_used();
+        assert_eq!(json[1]["status"], "0x1");
+        assert_eq!(json[1]["blockNumber"], "0x7");
+        assert_eq!(json[1]["transactionIndex"], "0x1");
+        assert_eq!(
+            json[1]["cumulativeGasUsed"],
+            std::format!("{:#x}", 2 * gas_used)
+        );
+        assert_eq!(json[1]["contractAddress"], serde_json::Value::Null);
+        assert_eq!(json[1]["logs"][0]["logIndex"], "0x2");
+        assert_eq!(json[1]["logs"][1]["logIndex"], "0x3");
+        assert_eq!(json[1]["logs"][0]["address"], emitter.to_string());
+        assert_eq!(json[1]["logs"][0]["removed"], false);
+        assert_eq!(
+            serde_json::from_value::<Vec<RpcReceipt>>(json).unwrap(),
+            receipts
+        );
+    }
+}
@@ -1,13 +1,17 @@
+#[cfg(feature = "std")]
+use crate::AnalysisCache;
 use crate::{
     db::{Database, DatabaseRef, EmptyDB, WrapDatabaseRef},
     handler::register,
+    interpreter::opcode::InstructionTables,
     primitives::{
-        BlockEnv, CfgEnv, CfgEnvWithHandlerCfg, Env, EnvWithHandlerCfg, HandlerCfg, SpecId, TxEnv,
+        Address, BlockEnv, CancellationToken, CfgEnv, CfgEnvWithHandlerCfg, ChainSpec, Env,
+        EnvWithHandlerCfg, HandlerCfg, SpecId, TxEnv, U256,
     },
-    Context, ContextWithHandlerCfg, Evm, Handler,
+    Context, ContextPrecompile, ContextWithHandlerCfg, Evm, Handler,
 };
 use core::marker::PhantomData;
-use std::boxed::Box;
+use std::{boxed::Box, sync::Arc};
 
 /// Evm Builder allows building or modifying EVM.
 /// Note that some of the methods that changes underlying structures
@@ -329,7 +333,7 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
     /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
     pub fn append_handler_register_box(
         mut self,
-        handle_register: register::HandleRegisterBox<EXT, DB>,
+        handle_register: register::HandleRegisterBox<'a, EXT, DB>,
     ) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
         self.handler
             .append_handler_register(register::HandleRegisters::Box(handle_register));
@@ -341,6 +345,58 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         }
     }
 
+    /// Adds precompile at the given address, replacing the existing one.
+    ///
+    /// Precompile can be [`ContextStatefulPrecompile`](crate::ContextStatefulPrecompile)
+    /// that has access to the [`InnebcevmContext`](crate::InnebcevmContext) and can read and
+    /// write the state. It is added as a handle register so it is kept when spec id changes.
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn append_precompile(
+        self,
+        address: Address,
+        precompile: impl Into<ContextPrecompile<DB>>,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB>
+    where
+        DB: 'a,
+    {
+        let precompile = precompile.into();
+        self.append_handler_register_box(Box::new(move |handler| {
+            let precompiles = handler.pre_execution.load_precompiles();
+            let precompile = precompile.clone();
+            handler.pre_execution.load_precompiles = Arc::new(move || {
+                let mut precompiles = precompiles.clone();
+                precompiles.insert(address, precompile.clone());
+                precompiles
+            });
+        }))
+    }
+
+    /// Modifies the instruction table, for example to replace or disable opcodes of the
+    /// chain. See [`EvmBuilder::disable_opcode`].
+    ///
+    /// Modification is added as a handle register so it is kept when spec id changes.
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn modify_instruction_table(
+        self,
+        f: impl for<'e> Fn(&mut InstructionTables<'e, Evm<'e, EXT, DB>>) + 'a,
+    ) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
+        self.append_handler_register_box(Box::new(move |handler| {
+            if let Some(table) = handler.instruction_table.as_mut() {
+                f(table);
+            }
+        }))
+    }
+
+    /// Disables the opcode, executing it halts with
+    /// [`HaltReason::NotActivated`](crate::primitives::HaltReason::NotActivated).
+    ///
+    /// When called, EvmBuilder will transition from SetGenericStage to HandlerStage.
+    pub fn disable_opcode(self, opcode: u8) -> EvmBuilder<'a, HandlerStage, EXT, DB> {
+        self.modify_instruction_table(move |table| table.disable_opcode(opcode))
+    }
+
     /// Sets specification Id , that will mark the version of EVM.
     /// It represent the hard fork of ethereum.
     ///
@@ -358,6 +414,47 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         }
     }
 
+    /// Sets the chain id, block number and timestamp, and the specification Id that
+    /// is active at that block according to the given [`ChainSpec`].
+    ///
+    /// # Note
+    ///
+    /// Same as [`EvmBuilder::with_spec_id`] it will reapply all handle registers.
+    pub fn with_chain_spec(mut self, chain_spec: &ChainSpec, block: u64, timestamp: u64) -> Self {
+        let env = &mut self.context.evm.env;
+        env.cfg.chain_id = chain_spec.chain_id;
+        env.block.number = U256::from(block);
+        env.block.timestamp = U256::from(timestamp);
+        self.with_spec_id(chain_spec.spec_id(block, timestamp))
+    }
+
+    /// Sets the addresses that are warm at the start of every transaction, see
+    /// [`JournaledState::preload_addresses`](crate::JournaledState::preload_addresses).
+    pub fn with_preloaded_addresses(
+        mut self,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        self.context
+            .evm
+            .journaled_state
+            .preload_addresses(addresses);
+        self
+    }
+
+    /// Sets the storage slots of the account that are warm at the start of every transaction,
+    /// see [`JournaledState::preload_storage`](crate::JournaledState::preload_storage).
+    pub fn with_preloaded_storage(
+        mut self,
+        address: Address,
+        keys: impl IntoIterator<Item = U256>,
+    ) -> Self {
+        self.context
+            .evm
+            .journaled_state
+            .preload_storage(address, keys);
+        self
+    }
+
     /// Allows modification of Evm Database.
     pub fn modify_db(mut self, f: impl FnOnce(&mut DB)) -> Self {
         f(&mut self.context.evm.db);
@@ -412,6 +509,22 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         self
     }
 
+    /// Sets the bytecode analysis cache.
+    ///
+    /// Cache can be shared between multiple Evm instances so contracts are analysed only once.
+    #[cfg(feature = "std")]
+    pub fn with_analysis_cache(mut self, cache: AnalysisCache) -> Self {
+        self.context.evm.analysis_cache = Some(cache);
+        self
+    }
+
+    /// Sets the token that aborts the execution when canceled, see
+    /// [EVMError::Canceled](crate::primitives::EVMError::Canceled).
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.context.evm.cancellation = Some(token);
+        self
+    }
+
     /// Clears Environment of EVM.
     pub fn with_clear_env(mut self) -> Self {
         self.context.evm.env.clear();
@@ -440,15 +553,16 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
 mod test {
     use super::SpecId;
     use crate::{
-        db::EmptyDB,
+        db::{Database, EmptyDB},
         inspector::inspector_handle_register,
         inspectors::NoOpInspector,
         primitives::{
-            address, AccountInfo, Address, Bytecode, Bytes, PrecompileResult, TransactTo, U256,
+            address, AccountInfo, Address, Bytecode, Bytes, ChainSpec, ExecutionResult, HaltReason,
+            Log, PrecompileError, PrecompileOutput, PrecompileResult, TransactTo, B256, U256,
         },
         Context, ContextPrecompile, ContextStatefulPrecompile, Evm, InMemoryDB, InnebcevmContext,
     };
-    use bcevm_interpreter::{gas, Host, Interpreter};
+    use bcevm_interpreter::{gas, opcode, Host, Interpreter};
     use std::{cell::RefCell, rc::Rc, sync::Arc};
 
     /// Custom evm context
@@ -538,6 +652,124 @@ mod test {
         assert_eq!(result_and_state.result.gas_used(), EXPECTED_RESULT_GAS);
     }
 
+    #[test]
+    fn custom_instruction_reads_aux_env() {
+        const KEY: B256 = B256::with_last_byte(1);
+
+        fn custom_instruction(interp: &mut Interpreter, host: &mut impl Host) {
+            let value = host
+                .aux_env(&KEY)
+                .map(|value| B256::left_padding_from(value))
+                .unwrap_or_default();
+            bcevm_interpreter::push_b256!(interp, value);
+        }
+
+        // 0xEF PUSH0 MSTORE PUSH1 32 PUSH0 RETURN
+        let code = Bytecode::new_raw(
+            [
+                0xEF,
+                opcode::PUSH0,
+                opcode::MSTORE,
+                opcode::PUSH1,
+                32,
+                opcode::PUSH0,
+                opcode::RETURN,
+            ]
+            .into(),
+        );
+        let code_hash = code.hash_slow();
+        let to_addr = address!("ffffffffffffffffffffffffffffffffffffffff");
+
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(to_addr, AccountInfo::new(U256::ZERO, 0, code_hash, code))
+            })
+            .modify_env(|env| {
+                env.aux_env.insert(KEY, Bytes::from_static(&[0x12, 0x34]));
+            })
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(to_addr))
+            .modify_instruction_table(|table| table.insert(0xEF, custom_instruction))
+            .build();
+
+        let result = evm.transact().unwrap().result;
+        let output = result.output().unwrap();
+        assert_eq!(U256::from_be_slice(output), U256::from(0x1234));
+    }
+
+    #[test]
+    fn cached_instruction_table() {
+        use crate::primitives::CancunSpec;
+        use bcevm_interpreter::{
+            opcode::{cached_instruction_table, InstructionTables},
+            DummyHost,
+        };
+
+        let table = cached_instruction_table::<DummyHost, CancunSpec>();
+        assert!(core::ptr::eq(
+            table,
+            cached_instruction_table::<DummyHost, CancunSpec>()
+        ));
+
+        // PUSH1 1 PUSH0 SSTORE
+        let code = Bytecode::new_raw([0x60, 0x01, 0x5F, 0x55].into());
+        let code_hash = code.hash_slow();
+        let to_addr = address!("ffffffffffffffffffffffffffffffffffffffff");
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(to_addr, AccountInfo::new(U256::ZERO, 0, code_hash, code))
+            })
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(to_addr))
+            .build();
+        // cached table is the default.
+        assert!(matches!(
+            evm.handler.instruction_table,
+            Some(InstructionTables::Cached(_))
+        ));
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            result.state[&to_addr].storage[&U256::ZERO].present_value,
+            U256::from(1)
+        );
+    }
+
+    #[test]
+    fn specialized_instruction_table() {
+        use crate::primitives::spec_to_generic;
+        use bcevm_interpreter::opcode::InstructionTables;
+
+        // PUSH1 1 PUSH0 SSTORE
+        let code = Bytecode::new_raw([0x60, 0x01, 0x5F, 0x55].into());
+        let code_hash = code.hash_slow();
+        let to_addr = address!("ffffffffffffffffffffffffffffffffffffffff");
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(to_addr, AccountInfo::new(U256::ZERO, 0, code_hash, code))
+            })
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(to_addr))
+            .append_handler_register(|handler| {
+                spec_to_generic!(handler.cfg.spec_id, {
+                    handler.instruction_table = Some(InstructionTables::new_specialized::<SPEC>());
+                });
+            })
+            .build();
+        assert!(matches!(
+            evm.handler.instruction_table,
+            Some(InstructionTables::Specialized(_))
+        ));
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            result.state[&to_addr].storage[&U256::ZERO].present_value,
+            U256::from(1)
+        );
+    }
+
     #[test]
     fn simple_build() {
         // build without external with latest spec
@@ -609,6 +841,83 @@ mod test {
             .build();
     }
 
+    #[test]
+    fn build_with_chain_spec() {
+        let chain_spec = ChainSpec::mainnet();
+        let evm = Evm::builder()
+            .with_chain_spec(&chain_spec, 17_034_870, 1_681_338_455)
+            .build();
+        assert_eq!(evm.spec_id(), SpecId::SHANGHAI);
+        assert_eq!(evm.block().number, U256::from(17_034_870));
+        assert_eq!(evm.cfg().chain_id, 1);
+    }
+
+    #[test]
+    fn build_with_preloaded_accounts() {
+        let account = address!("1000000000000000000000000000000000000001");
+        // SLOAD(1) BALANCE(account)
+        let mut code = vec![opcode::PUSH1, 1, opcode::SLOAD, opcode::POP, opcode::PUSH20];
+        code.extend_from_slice(account.as_slice());
+        code.extend([opcode::BALANCE, opcode::POP, opcode::STOP]);
+        let code = Bytecode::new_raw(code.into());
+        let to_addr = address!("ffffffffffffffffffffffffffffffffffffffff");
+
+        let evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(
+                    to_addr,
+                    AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+                )
+            })
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(to_addr));
+        let mut cold = evm.build();
+        let cold_gas = cold.transact().unwrap().result.gas_used();
+
+        let mut warm = cold
+            .modify()
+            .with_preloaded_addresses([account])
+            .with_preloaded_storage(to_addr, [U256::from(1)])
+            .build();
+        let warm_gas = warm.transact().unwrap().result.gas_used();
+        assert_eq!(cold_gas - warm_gas, 2000 + 2500);
+        // preloads are kept across transactions.
+        assert_eq!(warm.transact().unwrap().result.gas_used(), warm_gas);
+    }
+
+    #[test]
+    fn build_with_modified_instruction_table() {
+        fn custom_instruction(interp: &mut Interpreter, _host: &mut impl Host) {
+            gas!(interp, 100);
+        }
+
+        // 0xEF SELFDESTRUCT
+        let code = Bytecode::new_raw([0xEF, 0x30, 0xFF].into());
+        let code_hash = code.hash_slow();
+        let to_addr = address!("ffffffffffffffffffffffffffffffffffffffff");
+
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(to_addr, AccountInfo::new(U256::ZERO, 0, code_hash, code))
+            })
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(to_addr))
+            .modify_instruction_table(|table| table.insert(0xEF, custom_instruction))
+            .disable_opcode(opcode::SELFDESTRUCT)
+            .with_spec_id(SpecId::SHANGHAI)
+            .build();
+
+        let result = evm.transact().unwrap().result;
+        // 0xEF would halt with `OpcodeNotFound` if it was not replaced
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::NotActivated,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn build_custom_precompile() {
         struct CustomPrecompile;
@@ -620,7 +929,7 @@ mod test {
                 _gas_price: u64,
                 _context: &mut InnebcevmContext<EmptyDB>,
             ) -> PrecompileResult {
-                Ok((10, Bytes::new()))
+                Ok(PrecompileOutput::new(10, Bytes::new()))
             }
         }
 
@@ -642,4 +951,54 @@ mod test {
 
         evm.transact().unwrap();
     }
+
+    #[test]
+    fn build_with_stateful_precompile() {
+        /// Stores the input length at slot zero of the precompile account and logs it.
+        struct StoringPrecompile;
+
+        impl<DB: Database> ContextStatefulPrecompile<DB> for StoringPrecompile {
+            fn call(
+                &self,
+                input: &Bytes,
+                _gas_price: u64,
+                context: &mut InnebcevmContext<DB>,
+            ) -> PrecompileResult {
+                context
+                    .sstore(PRECOMPILE, U256::ZERO, U256::from(input.len()))
+                    .map_err(|_| PrecompileError::other("database error"))?;
+                let log = Log::new_unchecked(
+                    PRECOMPILE,
+                    vec![B256::with_last_byte(1)],
+                    Bytes::from(U256::from(input.len()).to_be_bytes_vec()),
+                );
+                Ok(PrecompileOutput::new(100, Bytes::new()).with_logs(vec![log]))
+            }
+        }
+
+        const PRECOMPILE: Address = address!("0000000000000000000000000000000000000100");
+
+        let mut db = InMemoryDB::default();
+        let mut evm = Evm::builder()
+            .with_db(&mut db)
+            .append_precompile(
+                PRECOMPILE,
+                ContextPrecompile::ContextStateful(Arc::new(StoringPrecompile)),
+            )
+            .with_spec_id(SpecId::CANCUN)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(PRECOMPILE);
+                tx.data = Bytes::from_static(&[1, 2, 3]);
+            })
+            .build();
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        let slot = &result.state[&PRECOMPILE].storage[&U256::ZERO];
+        assert_eq!(slot.present_value, U256::from(3));
+        let logs = result.result.logs();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].address, PRECOMPILE);
+        assert_eq!(logs[0].topics(), &[B256::with_last_byte(1)]);
+    }
 }
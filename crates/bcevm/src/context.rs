@@ -1,12 +1,17 @@
+#[cfg(feature = "std")]
+mod analysis_cache;
 mod context_precompiles;
 pub(crate) mod evm_context;
 mod inner_evm_context;
 
+#[cfg(feature = "std")]
+pub use analysis_cache::AnalysisCache;
 pub use context_precompiles::{
-    ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile, ContextStatefulPrecompileArc,
-    ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
+    ContextCallPrecompile, ContextCallPrecompileArc, ContextPrecompile, ContextPrecompiles,
+    ContextStatefulPrecompile, ContextStatefulPrecompileArc, ContextStatefulPrecompileBox,
+    ContextStatefulPrecompileMut,
 };
-pub use evm_context::EvmContext;
+pub use evm_context::{ContextSnapshot, EvmContext};
 pub use inner_evm_context::InnebcevmContext;
 
 use crate::{
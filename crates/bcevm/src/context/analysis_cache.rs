@@ -0,0 +1,112 @@
+//! Bytecode analysis cache shared between transactions and EVM instances.
+use crate::{
+    interpreter::analysis::to_analysed,
+    primitives::{Bytecode, HashMap, B256},
+};
+use std::sync::{Arc, RwLock};
+
+/// Cache of analysed legacy bytecode keyed by code hash.
+///
+/// Jump table analysis is done once per contract and reused by every call frame
+/// that executes the same code. Cache is cheap to clone and clones share the same
+/// entries, so it can be injected into multiple [`crate::Evm`] instances.
+#[derive(Clone, Debug, Default)]
+pub struct AnalysisCache {
+    entries: Arc<RwLock<HashMap<B256, Bytecode>>>,
+}
+
+impl AnalysisCache {
+    /// Creates a new empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns analysed bytecode for the given code hash, analysing and caching it if
+    /// it is not present.
+    ///
+    /// Only raw legacy bytecode is analysed and cached, other kinds are returned as-is.
+    pub fn get_or_analyse(&self, code_hash: B256, bytecode: Bytecode) -> Bytecode {
+        if !matches!(bytecode, Bytecode::LegacyRaw(_)) {
+            return bytecode;
+        }
+        if let Some(analysed) = self.get(&code_hash) {
+            return analysed;
+        }
+        let analysed = to_analysed(bytecode);
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(code_hash, analysed.clone());
+        analysed
+    }
+
+    /// Returns cached analysed bytecode for the given code hash.
+    pub fn get(&self, code_hash: &B256) -> Option<Bytecode> {
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(code_hash)
+            .cloned()
+    }
+
+    /// Returns number of cached contracts.
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Returns true if cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached entries.
+    pub fn clear(&self) {
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::{address, AccountInfo, Bytes, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn shared_between_evm_instances() {
+        // PUSH1 0x04 JUMP JUMPDEST STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x04, 0x56, 0x00, 0x5b, 0x00]));
+        let code_hash = code.hash_slow();
+        let contract = address!("1000000000000000000000000000000000000001");
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(Default::default(), 0, code_hash, code),
+        );
+
+        let cache = AnalysisCache::new();
+        for _ in 0..2 {
+            let mut evm = Evm::builder()
+                .with_ref_db(&db)
+                .with_analysis_cache(cache.clone())
+                .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(contract))
+                .build();
+            let result = evm.transact().unwrap().result;
+            assert!(result.is_success());
+        }
+
+        assert_eq!(cache.len(), 1);
+        let analysed = cache.get(&code_hash).unwrap();
+        assert!(matches!(analysed, Bytecode::LegacyAnalyzed(_)));
+        assert!(analysed.legacy_jump_table().unwrap().is_valid(4));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
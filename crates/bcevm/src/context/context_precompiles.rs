@@ -1,11 +1,13 @@
 use crate::{
+    interpreter::CallInputs,
     precompile::{Precompile, PrecompileResult},
-    primitives::{db::Database, Address, Bytes, HashMap},
+    primitives::{db::Database, Address, Bytes, HashMap, PrecompileError},
+    EvmContext,
 };
+use bcevm_precompile::Precompiles;
 use core::ops::{Deref, DerefMut};
 use dyn_clone::DynClone;
-use bcevm_precompile::Precompiles;
-use std::{boxed::Box, sync::Arc};
+use std::{boxed::Box, sync::Arc, vec::Vec};
 
 use super::InnebcevmContext;
 
@@ -19,6 +21,9 @@ pub enum ContextPrecompile<DB: Database> {
     /// Mutable stateful precompile that is Box over [`ContextStatefulPrecompileMut`] trait.
     /// It takes a reference to input, gas limit and context.
     ContextStatefulMut(ContextStatefulPrecompileBox<DB>),
+    /// Precompile that makes calls, Arc over [`ContextCallPrecompile`] trait.
+    /// It takes the inputs of its call and the [`EvmContext`] with the precompiles.
+    ContextCall(ContextCallPrecompileArc<DB>),
 }
 
 impl<DB: Database> Clone for ContextPrecompile<DB> {
@@ -27,15 +32,23 @@ impl<DB: Database> Clone for ContextPrecompile<DB> {
             Self::Ordinary(arg0) => Self::Ordinary(arg0.clone()),
             Self::ContextStateful(arg0) => Self::ContextStateful(arg0.clone()),
             Self::ContextStatefulMut(arg0) => Self::ContextStatefulMut(arg0.clone()),
+            Self::ContextCall(arg0) => Self::ContextCall(arg0.clone()),
         }
     }
 }
 
-#[derive(Clone)]
 pub struct ContextPrecompiles<DB: Database> {
     inner: HashMap<Address, ContextPrecompile<DB>>,
 }
 
+impl<DB: Database> Clone for ContextPrecompiles<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 impl<DB: Database> ContextPrecompiles<DB> {
     /// Returns precompiles addresses.
     #[inline]
@@ -54,8 +67,20 @@ impl<DB: Database> ContextPrecompiles<DB> {
         self.inner.extend(other.into_iter().map(Into::into));
     }
 
+    /// Moves the precompiles to other addresses, see [Precompiles::with_remapped_addresses].
+    pub fn remap_addresses(&mut self, remap: impl IntoIterator<Item = (Address, Address)>) {
+        let moved: Vec<_> = remap
+            .into_iter()
+            .filter_map(|(from, to)| Some((to, self.inner.remove(&from)?)))
+            .collect();
+        self.inner.extend(moved);
+    }
+
     /// Call precompile and executes it. Returns the result of the precompile execution.
     /// None if the precompile does not exist.
+    ///
+    /// [`ContextPrecompile::ContextCall`] precompiles need the [`EvmContext`], they fail
+    /// here and are called by [`EvmContext::make_call_frame`].
     #[inline]
     pub fn call(
         &mut self,
@@ -70,6 +95,9 @@ impl<DB: Database> ContextPrecompiles<DB> {
             ContextPrecompile::Ordinary(p) => Some(p.call(bytes, gas_price, &evmctx.env)),
             ContextPrecompile::ContextStatefulMut(p) => Some(p.call_mut(bytes, gas_price, evmctx)),
             ContextPrecompile::ContextStateful(p) => Some(p.call(bytes, gas_price, evmctx)),
+            ContextPrecompile::ContextCall(_) => Some(Err(PrecompileError::other(
+                "precompile makes calls and needs the EVM context",
+            ))),
         }
     }
 }
@@ -120,12 +148,24 @@ pub trait ContextStatefulPrecompileMut<DB: Database>: DynClone + Send + Sync {
 
 dyn_clone::clone_trait_object!(<DB> ContextStatefulPrecompileMut<DB>);
 
+/// Context aware precompile that makes calls. It is used to create
+/// a arc precompile in [`ContextPrecompile`].
+///
+/// It takes the inputs of its call, so it can follow the static context and the caller,
+/// and the [`EvmContext`] to make its calls with [`EvmContext::make_call_frame`].
+pub trait ContextCallPrecompile<DB: Database>: Sync + Send {
+    fn call(&self, inputs: &CallInputs, evmctx: &mut EvmContext<DB>) -> PrecompileResult;
+}
+
 /// Arc over context stateful precompile.
 pub type ContextStatefulPrecompileArc<DB> = Arc<dyn ContextStatefulPrecompile<DB>>;
 
 /// Box over context mutable stateful precompile
 pub type ContextStatefulPrecompileBox<DB> = Box<dyn ContextStatefulPrecompileMut<DB>>;
 
+/// Arc over context precompile that makes calls.
+pub type ContextCallPrecompileArc<DB> = Arc<dyn ContextCallPrecompile<DB>>;
+
 impl<DB: Database> From<Precompile> for ContextPrecompile<DB> {
     fn from(p: Precompile) -> Self {
         ContextPrecompile::Ordinary(p)
@@ -6,8 +6,8 @@ use crate::{
     interpreter::{
         return_ok, CallInputs, Contract, Gas, InstructionResult, Interpreter, InterpreterResult,
     },
-    primitives::{Address, Bytes, EVMError, Env, HashSet, U256},
-    ContextPrecompiles, FrameOrResult, CALL_STACK_LIMIT,
+    primitives::{Bytes, EVMError, Env, HashSet, U256},
+    ContextPrecompile, ContextPrecompiles, FrameOrResult, JournalCheckpoint,
 };
 use core::{
     fmt,
@@ -23,6 +23,24 @@ pub struct EvmContext<DB: Database> {
     pub precompiles: ContextPrecompiles<DB>,
 }
 
+/// Snapshot of the journaled state taken by [EvmContext::snapshot].
+///
+/// It is consumed either by [EvmContext::revert_to] or by [EvmContext::commit_snapshot].
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "snapshot should be reverted or committed"]
+pub struct ContextSnapshot {
+    checkpoint: JournalCheckpoint,
+    depth: usize,
+}
+
+impl ContextSnapshot {
+    /// Returns journal depth at which the snapshot was taken.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
 impl<DB: Database + Clone> Clone for EvmContext<DB>
 where
     DB::Error: Clone,
@@ -100,17 +118,59 @@ impl<DB: Database> EvmContext<DB> {
         self.precompiles = precompiles;
     }
 
+    /// Takes a snapshot of the state, logs and transient storage, so that the changes
+    /// made after it can be reverted with [EvmContext::revert_to].
+    ///
+    /// Snapshot opens a new journal depth that counts towards the call stack limit
+    /// until it is reverted or committed.
+    #[inline]
+    pub fn snapshot(&mut self) -> ContextSnapshot {
+        let checkpoint = self.journaled_state.checkpoint();
+        ContextSnapshot {
+            checkpoint,
+            depth: self.journaled_state.depth,
+        }
+    }
+
+    /// Reverts all changes made after the snapshot.
+    ///
+    /// Snapshot is returned back if it is not the innermost open journal depth, for
+    /// example if a call made after the snapshot is not finished yet.
+    #[inline]
+    pub fn revert_to(&mut self, snapshot: ContextSnapshot) -> Result<(), ContextSnapshot> {
+        if snapshot.depth != self.journaled_state.depth {
+            return Err(snapshot);
+        }
+        self.journaled_state.checkpoint_revert(snapshot.checkpoint);
+        Ok(())
+    }
+
+    /// Keeps all changes made after the snapshot.
+    ///
+    /// Snapshot is returned back if it is not the innermost open journal depth.
+    #[inline]
+    pub fn commit_snapshot(&mut self, snapshot: ContextSnapshot) -> Result<(), ContextSnapshot> {
+        if snapshot.depth != self.journaled_state.depth {
+            return Err(snapshot);
+        }
+        self.journaled_state.checkpoint_commit();
+        Ok(())
+    }
+
     /// Call precompile contract
     #[inline]
-    fn call_precompile(
-        &mut self,
-        address: Address,
-        input_data: &Bytes,
-        gas: Gas,
-    ) -> Option<InterpreterResult> {
-        let out = self
-            .precompiles
-            .call(address, input_data, gas.limit(), &mut self.inner)?;
+    fn call_precompile(&mut self, inputs: &CallInputs, gas: Gas) -> Option<InterpreterResult> {
+        let address = inputs.bytecode_address;
+        let input_data = &inputs.input;
+        let out = match self.precompiles.get(&address) {
+            Some(ContextPrecompile::ContextCall(precompile)) => {
+                let precompile = precompile.clone();
+                precompile.call(inputs, self)
+            }
+            _ => self
+                .precompiles
+                .call(address, input_data, gas.limit(), &mut self.inner)?,
+        };
 
         let mut result = InterpreterResult {
             result: InstructionResult::Return,
@@ -119,10 +179,12 @@ impl<DB: Database> EvmContext<DB> {
         };
 
         match out {
-            Ok((gas_used, data)) => {
-                if result.gas.record_cost(gas_used) {
+            Ok(output) => {
+                if result.gas.record_cost(output.gas_used) {
                     result.result = InstructionResult::Return;
-                    result.output = data;
+                    result.output = output.bytes;
+                    // logs are reverted with the checkpoint of the call.
+                    self.journaled_state.logs.extend(output.logs);
                 } else {
                     result.result = InstructionResult::PrecompileOOG;
                 }
@@ -135,6 +197,14 @@ impl<DB: Database> EvmContext<DB> {
                 };
             }
         }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            %address,
+            input_len = input_data.len(),
+            result = ?result.result,
+            gas_used = result.gas.spent(),
+            "precompile call"
+        );
         Some(result)
     }
 
@@ -158,7 +228,7 @@ impl<DB: Database> EvmContext<DB> {
         };
 
         // Check depth
-        if self.journaled_state.depth() > CALL_STACK_LIMIT {
+        if self.journaled_state.depth() > self.call_depth_limit() {
             return return_result(InstructionResult::CallTooDeep);
         }
 
@@ -194,7 +264,7 @@ impl<DB: Database> EvmContext<DB> {
             _ => {}
         };
 
-        if let Some(result) = self.call_precompile(inputs.bytecode_address, &inputs.input, gas) {
+        if let Some(result) = self.call_precompile(inputs, gas) {
             if matches!(result.result, return_ok!()) {
                 self.journaled_state.checkpoint_commit();
             } else {
@@ -205,6 +275,7 @@ impl<DB: Database> EvmContext<DB> {
                 inputs.return_memory_offset.clone(),
             ))
         } else if !bytecode.is_empty() {
+            let bytecode = self.inner.analyse_bytecode(code_hash, bytecode);
             let contract =
                 Contract::new_with_context(inputs.input.clone(), bytecode, Some(code_hash), inputs);
             // Create interpreter and executes call and push new CallStackFrame.
@@ -227,12 +298,16 @@ pub(crate) mod test_utils {
     use crate::{
         db::{CacheDB, EmptyDB},
         journaled_state::JournaledState,
-        primitives::{address, SpecId, B256},
+        primitives::{address, AccountInfo, Address, Bytecode, SpecId, TransactTo, B256},
+        Evm,
     };
 
     /// Mock caller address.
     pub const MOCK_CALLER: Address = address!("0000000000000000000000000000000000000000");
 
+    /// Mock contract address.
+    pub const MOCK_CONTRACT: Address = address!("2000000000000000000000000000000000000000");
+
     /// Creates `CallInputs` that calls a provided contract address from the mock caller.
     pub fn create_mock_call_inputs(to: Address) -> CallInputs {
         CallInputs {
@@ -280,13 +355,42 @@ pub(crate) mod test_utils {
                 journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::new()),
                 db,
                 error: Ok(()),
+                warnings: Default::default(),
+                storage_accesses: Default::default(),
+                subcalls: Default::default(),
+                halt_context: None,
+                #[cfg(feature = "std")]
+                analysis_cache: None,
+                cancellation: None,
                 #[cfg(feature = "optimism")]
-                l1_block_info: None,
+                l1_cost_oracle: None,
             },
             precompiles: ContextPrecompiles::default(),
         }
     }
 
+    /// Creates an evm with the provided code deployed at the mock contract address,
+    /// and a transaction that calls it from the mock caller.
+    pub fn create_contract_call_evm(
+        spec_id: SpecId,
+        code: Bytecode,
+    ) -> Evm<'static, (), CacheDB<EmptyDB>> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            MOCK_CONTRACT,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        Evm::builder()
+            .with_db(db)
+            .with_spec_id(spec_id)
+            .modify_tx_env(|tx| {
+                tx.caller = MOCK_CALLER;
+                tx.transact_to = TransactTo::Call(MOCK_CONTRACT);
+                tx.gas_limit = 1_000_000;
+            })
+            .build()
+    }
+
     /// Returns a new `EvmContext` with an empty journaled state.
     pub fn create_empty_evm_context(env: Box<Env>, db: EmptyDB) -> EvmContext<EmptyDB> {
         EvmContext {
@@ -295,8 +399,15 @@ pub(crate) mod test_utils {
                 journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::new()),
                 db,
                 error: Ok(()),
+                warnings: Default::default(),
+                storage_accesses: Default::default(),
+                subcalls: Default::default(),
+                halt_context: None,
+                #[cfg(feature = "std")]
+                analysis_cache: None,
+                cancellation: None,
                 #[cfg(feature = "optimism")]
-                l1_block_info: None,
+                l1_cost_oracle: None,
             },
             precompiles: ContextPrecompiles::default(),
         }
@@ -308,8 +419,8 @@ mod tests {
     use super::*;
     use crate::{
         db::{CacheDB, EmptyDB},
-        primitives::{address, Bytecode},
-        Frame, JournalEntry,
+        primitives::{address, AccountInfo, Bytecode},
+        Frame, JournalEntry, CALL_STACK_LIMIT,
     };
     use std::boxed::Box;
     use test_utils::*;
@@ -334,6 +445,31 @@ mod tests {
         );
     }
 
+    // Tests that the call depth limit of the configuration lowers the call stack limit.
+    #[test]
+    fn test_make_call_frame_max_call_depth() {
+        let mut env = Env::default();
+        env.cfg.max_call_depth = Some(2);
+        let db = EmptyDB::default();
+        let mut context = test_utils::create_empty_evm_context(Box::new(env), db);
+        assert_eq!(context.call_depth_limit(), 2);
+        context.journaled_state.depth = 3;
+        let contract = address!("dead10000000000000000000000000000001dead");
+        let call_inputs = test_utils::create_mock_call_inputs(contract);
+        let res = context.make_call_frame(&call_inputs);
+        let Ok(FrameOrResult::Result(err)) = res else {
+            panic!("Expected FrameOrResult::Result");
+        };
+        assert_eq!(
+            err.interpreter_result().result,
+            InstructionResult::CallTooDeep
+        );
+
+        // limits above the mainnet limit are ignored.
+        context.env.cfg.max_call_depth = Some(u64::MAX);
+        assert_eq!(context.call_depth_limit(), CALL_STACK_LIMIT);
+    }
+
     // Tests that the `EVMContext::make_call_frame` function returns an error if the
     // transfer fails on the journaled state. It also verifies that the revert was
     // checkpointed on the journaled state correctly.
@@ -397,4 +533,71 @@ mod tests {
         };
         assert_eq!(call_frame.return_memory_range, 0..0,);
     }
+
+    #[test]
+    fn snapshot_revert() {
+        let env = Env::default();
+        let cdb = CacheDB::new(EmptyDB::default());
+        let bal = U256::from(3_000_000_000_u128);
+        let mut context = create_cache_db_evm_context_with_balance(Box::new(env), cdb, bal);
+        let contract = address!("dead10000000000000000000000000000001dead");
+        let key = U256::from(1);
+        context.journaled_state.tstore(contract, key, U256::from(1));
+        context
+            .inner
+            .journaled_state
+            .load_account(MOCK_CALLER, &mut context.inner.db)
+            .unwrap();
+
+        let snapshot = context.snapshot();
+        context.journaled_state.tstore(contract, key, U256::from(2));
+        context
+            .inner
+            .journaled_state
+            .transfer(
+                &MOCK_CALLER,
+                &contract,
+                U256::from(1),
+                &mut context.inner.db,
+            )
+            .unwrap();
+
+        // inner depth that is not finished can't be skipped.
+        context.journaled_state.checkpoint();
+        let snapshot = context.revert_to(snapshot).unwrap_err();
+        context.journaled_state.checkpoint_commit();
+
+        context.revert_to(snapshot).unwrap();
+        assert_eq!(context.journaled_state.depth, 0);
+        assert_eq!(context.journaled_state.tload(contract, key), U256::from(1));
+        assert!(!context.journaled_state.state.contains_key(&contract));
+        assert_eq!(
+            context.journaled_state.state[&MOCK_CALLER].info.balance,
+            bal
+        );
+    }
+
+    #[test]
+    fn copy_code_without_loading() {
+        let contract = address!("dead10000000000000000000000000000001dead");
+        let code = Bytecode::new_raw([1u8, 2, 3, 4].to_vec().into());
+        let mut cdb = CacheDB::new(EmptyDB::default());
+        cdb.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        // code is only available by hash.
+        cdb.accounts.get_mut(&contract).unwrap().info.code = None;
+        let mut context = create_cache_db_evm_context(Box::default(), cdb);
+
+        let mut dest = [0xff; 4];
+        context.copy_code(contract, 2, &mut dest).unwrap();
+        assert_eq!(dest, [3, 4, 0, 0]);
+        assert!(context.journaled_state.state[&contract].info.code.is_none());
+
+        // code loaded in the journal is copied from there.
+        context.code(contract).unwrap();
+        context.copy_code(contract, 1, &mut dest).unwrap();
+        assert_eq!(dest, [2, 3, 4, 0]);
+    }
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "std")]
+use super::analysis_cache::AnalysisCache;
 use crate::{
     db::Database,
     interpreter::{
@@ -7,14 +9,14 @@ use crate::{
     },
     journaled_state::JournaledState,
     primitives::{
-        keccak256, Account, Address, AnalysisKind, Bytecode, Bytes, CreateScheme, EVMError, Env,
-        Eof, HashSet, Spec,
+        keccak256, Account, Address, AnalysisKind, Bytecode, Bytes, CancellationToken,
+        CreateScheme, EVMError, Env, Eof, HaltContext, HashSet, Spec,
         SpecId::{self, *},
-        B256, U256,
+        StorageAccess, SubcallGas, Warning, B256, KECCAK_EMPTY, U256,
     },
     FrameOrResult, JournalCheckpoint, CALL_STACK_LIMIT,
 };
-use std::boxed::Box;
+use std::{boxed::Box, vec::Vec};
 
 /// EVM contexts contains data that EVM needs for execution.
 #[derive(Debug)]
@@ -28,9 +30,23 @@ pub struct InnebcevmContext<DB: Database> {
     pub db: DB,
     /// Error that happened during execution.
     pub error: Result<(), EVMError<DB::Error>>,
-    /// Used as temporary value holder to store L1 block info.
+    /// Warnings about changed behavior collected during the current transaction.
+    pub warnings: Vec<Warning>,
+    /// Storage accesses recorded during the current transaction, see
+    /// [crate::primitives::CfgEnv::record_storage_accesses].
+    pub storage_accesses: Vec<StorageAccess>,
+    /// Gas of the calls and creates made directly by the current transaction.
+    pub subcalls: Vec<SubcallGas>,
+    /// Instruction that halted the last halted frame of the current transaction.
+    pub halt_context: Option<HaltContext>,
+    /// Bytecode analysis cache shared between transactions, see [AnalysisCache].
+    #[cfg(feature = "std")]
+    pub analysis_cache: Option<AnalysisCache>,
+    /// Token that aborts the execution with [EVMError::Canceled] when canceled.
+    pub cancellation: Option<CancellationToken>,
+    /// L1 cost oracle of the current transaction, see [crate::optimism::L1CostOracle].
     #[cfg(feature = "optimism")]
-    pub l1_block_info: Option<crate::optimism::L1BlockInfo>,
+    pub l1_cost_oracle: Option<std::sync::Arc<dyn crate::optimism::L1CostOracle>>,
 }
 
 impl<DB: Database + Clone> Clone for InnebcevmContext<DB>
@@ -43,8 +59,15 @@ where
             journaled_state: self.journaled_state.clone(),
             db: self.db.clone(),
             error: self.error.clone(),
+            warnings: self.warnings.clone(),
+            storage_accesses: self.storage_accesses.clone(),
+            subcalls: self.subcalls.clone(),
+            halt_context: self.halt_context,
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache.clone(),
+            cancellation: self.cancellation.clone(),
             #[cfg(feature = "optimism")]
-            l1_block_info: self.l1_block_info.clone(),
+            l1_cost_oracle: self.l1_cost_oracle.clone(),
         }
     }
 }
@@ -56,8 +79,15 @@ impl<DB: Database> InnebcevmContext<DB> {
             journaled_state: JournaledState::new(SpecId::LATEST, HashSet::new()),
             db,
             error: Ok(()),
+            warnings: Vec::new(),
+            storage_accesses: Vec::new(),
+            subcalls: Vec::new(),
+            halt_context: None,
+            #[cfg(feature = "std")]
+            analysis_cache: None,
+            cancellation: None,
             #[cfg(feature = "optimism")]
-            l1_block_info: None,
+            l1_cost_oracle: None,
         }
     }
 
@@ -69,8 +99,15 @@ impl<DB: Database> InnebcevmContext<DB> {
             journaled_state: JournaledState::new(SpecId::LATEST, HashSet::new()),
             db,
             error: Ok(()),
+            warnings: Vec::new(),
+            storage_accesses: Vec::new(),
+            subcalls: Vec::new(),
+            halt_context: None,
+            #[cfg(feature = "std")]
+            analysis_cache: None,
+            cancellation: None,
             #[cfg(feature = "optimism")]
-            l1_block_info: None,
+            l1_cost_oracle: None,
         }
     }
 
@@ -84,11 +121,53 @@ impl<DB: Database> InnebcevmContext<DB> {
             journaled_state: self.journaled_state,
             db,
             error: Ok(()),
+            warnings: self.warnings,
+            storage_accesses: self.storage_accesses,
+            subcalls: self.subcalls,
+            halt_context: self.halt_context,
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache,
+            cancellation: self.cancellation,
             #[cfg(feature = "optimism")]
-            l1_block_info: self.l1_block_info,
+            l1_cost_oracle: self.l1_cost_oracle,
         }
     }
 
+    /// Returns a copy of the context over the given database.
+    ///
+    /// Error and the data collected during the current transaction are not copied.
+    pub fn clone_with_db<ODB: Database>(&self, db: ODB) -> InnebcevmContext<ODB> {
+        InnebcevmContext {
+            env: self.env.clone(),
+            journaled_state: self.journaled_state.clone(),
+            db,
+            error: Ok(()),
+            warnings: Vec::new(),
+            storage_accesses: Vec::new(),
+            subcalls: Vec::new(),
+            halt_context: None,
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache.clone(),
+            cancellation: self.cancellation.clone(),
+            #[cfg(feature = "optimism")]
+            l1_cost_oracle: self.l1_cost_oracle.clone(),
+        }
+    }
+
+    /// Returns analysed bytecode for the given code hash, reusing [AnalysisCache] if set.
+    ///
+    /// Without a cache bytecode is returned as-is and is analysed when the contract is created.
+    #[inline]
+    pub fn analyse_bytecode(&self, code_hash: B256, bytecode: Bytecode) -> Bytecode {
+        #[cfg(feature = "std")]
+        if let Some(cache) = &self.analysis_cache {
+            return cache.get_or_analyse(code_hash, bytecode);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = code_hash;
+        bytecode
+    }
+
     /// Returns the configured EVM spec ID.
     #[inline]
     pub const fn spec_id(&self) -> SpecId {
@@ -107,12 +186,30 @@ impl<DB: Database> InnebcevmContext<DB> {
         Ok(())
     }
 
+    /// Adds warning to the current transaction, duplicates are ignored.
+    #[inline]
+    pub fn warn(&mut self, warning: Warning) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
     /// Return environment.
     #[inline]
     pub fn env(&mut self) -> &mut Env {
         &mut self.env
     }
 
+    /// Returns the maximum call depth, [CALL_STACK_LIMIT] lowered by
+    /// [CfgEnv::max_call_depth](crate::primitives::CfgEnv::max_call_depth).
+    #[inline]
+    pub fn call_depth_limit(&self) -> u64 {
+        self.env
+            .cfg
+            .max_call_depth
+            .map_or(CALL_STACK_LIMIT, |limit| limit.min(CALL_STACK_LIMIT))
+    }
+
     /// Returns the error by replacing it with `Ok(())`, if any.
     pub fn take_error(&mut self) -> Result<(), EVMError<DB::Error>> {
         core::mem::replace(&mut self.error, Ok(()))
@@ -167,6 +264,39 @@ impl<DB: Database> InnebcevmContext<DB> {
             .map(|(a, is_cold)| (a.info.code.clone().unwrap(), is_cold))
     }
 
+    /// Get code by its hash.
+    #[inline]
+    pub fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, EVMError<DB::Error>> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        self.db.code_by_hash(code_hash).map_err(EVMError::Database)
+    }
+
+    /// Copies code of the address, starting at `code_offset`, into `dest`. Bytes past the
+    /// end of the code are set to zero.
+    ///
+    /// If the code is not loaded in the journal, it is copied with
+    /// [Database::copy_code_by_hash] and it is not stored in the journal. Account access
+    /// is not recorded, it is expected to be loaded before.
+    pub fn copy_code(
+        &mut self,
+        address: Address,
+        code_offset: usize,
+        dest: &mut [u8],
+    ) -> Result<(), EVMError<DB::Error>> {
+        let (acc, _) = self.journaled_state.load_account(address, &mut self.db)?;
+        match &acc.info.code {
+            Some(code) => code.copy_original_bytes(code_offset, dest),
+            None if acc.info.code_hash == KECCAK_EMPTY => dest.fill(0),
+            None => self
+                .db
+                .copy_code_by_hash(acc.info.code_hash, code_offset, dest)
+                .map_err(EVMError::Database)?,
+        }
+        Ok(())
+    }
+
     /// Get code hash of address.
     #[inline]
     pub fn code_hash(&mut self, address: Address) -> Result<(B256, bool), EVMError<DB::Error>> {
@@ -185,7 +315,20 @@ impl<DB: Database> InnebcevmContext<DB> {
         index: U256,
     ) -> Result<(U256, bool), EVMError<DB::Error>> {
         // account is always warm. reference on that statement https://eips.ethereum.org/EIPS/eip-2929 see `Note 2:`
-        self.journaled_state.sload(address, index, &mut self.db)
+        let (value, is_cold) = self.journaled_state.sload(address, index, &mut self.db)?;
+        if self.env.cfg.record_storage_accesses {
+            let original_value =
+                self.journaled_state.state[&address].storage[&index].original_value();
+            self.storage_accesses.push(StorageAccess {
+                address,
+                key: index,
+                original_value,
+                present_value: value,
+                new_value: value,
+                is_write: false,
+            });
+        }
+        Ok((value, is_cold))
     }
 
     /// Storage change of storage slot, before storing `sload` will be called for that slot.
@@ -196,8 +339,20 @@ impl<DB: Database> InnebcevmContext<DB> {
         index: U256,
         value: U256,
     ) -> Result<SStoreResult, EVMError<DB::Error>> {
-        self.journaled_state
-            .sstore(address, index, value, &mut self.db)
+        let result = self
+            .journaled_state
+            .sstore(address, index, value, &mut self.db)?;
+        if self.env.cfg.record_storage_accesses {
+            self.storage_accesses.push(StorageAccess {
+                address,
+                key: index,
+                original_value: result.original_value,
+                present_value: result.present_value,
+                new_value: result.new_value,
+                is_write: true,
+            });
+        }
+        Ok(result)
     }
 
     /// Returns transient storage value.
@@ -243,7 +398,7 @@ impl<DB: Database> InnebcevmContext<DB> {
         };
 
         // Check depth
-        if self.journaled_state.depth() > CALL_STACK_LIMIT {
+        if self.journaled_state.depth() > self.call_depth_limit() {
             return return_error(InstructionResult::CallTooDeep);
         }
 
@@ -279,7 +434,7 @@ impl<DB: Database> InnebcevmContext<DB> {
         };
 
         let contract = Contract::new(
-            Bytes::new(),
+            inputs.input.clone(),
             // fine to clone as it is Bytes.
             Bytecode::Eof(inputs.eof_init_code.clone()),
             None,
@@ -295,6 +450,7 @@ impl<DB: Database> InnebcevmContext<DB> {
         Ok(FrameOrResult::new_eofcreate_frame(
             inputs.created_address,
             inputs.return_memory_range.clone(),
+            inputs.kind,
             checkpoint,
             interpreter,
         ))
@@ -319,6 +475,27 @@ impl<DB: Database> InnebcevmContext<DB> {
             return;
         }
 
+        // EIP-170: Contract code size limit, applies to the deployed container.
+        if interpreter_result.output.len()
+            > self
+                .env
+                .cfg
+                .limit_contract_code_size
+                .unwrap_or(MAX_CODE_SIZE)
+        {
+            self.journaled_state.checkpoint_revert(journal_checkpoint);
+            interpreter_result.result = InstructionResult::CreateContractSizeLimit;
+            return;
+        }
+
+        // deployment of the container is paid as code deposit.
+        let gas_for_code = interpreter_result.output.len() as u64 * gas::CODEDEPOSIT;
+        if !interpreter_result.gas.record_cost(gas_for_code) {
+            self.journaled_state.checkpoint_revert(journal_checkpoint);
+            interpreter_result.result = InstructionResult::OutOfGas;
+            return;
+        }
+
         // commit changes reduces depth by -1.
         self.journaled_state.checkpoint_commit();
 
@@ -353,7 +530,7 @@ impl<DB: Database> InnebcevmContext<DB> {
         };
 
         // Check depth
-        if self.journaled_state.depth() > CALL_STACK_LIMIT {
+        if self.journaled_state.depth() > self.call_depth_limit() {
             return return_error(InstructionResult::CallTooDeep);
         }
 
@@ -504,3 +681,215 @@ impl<DB: Database> InnebcevmContext<DB> {
         interpreter_result.result = InstructionResult::Return;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interpreter::opcode,
+        primitives::{
+            eof::{EofBody, TypesSection},
+            eofcreate_address,
+        },
+        test_utils::{create_contract_call_evm, MOCK_CONTRACT},
+    };
+
+    /// Returns the EOF container with a single code section.
+    fn eof_container(
+        max_stack_size: u16,
+        code: Vec<u8>,
+        container_section: Vec<Bytes>,
+        data: &[u8],
+    ) -> Eof {
+        EofBody {
+            types_section: vec![TypesSection {
+                inputs: 0,
+                outputs: 0x80,
+                max_stack_size,
+            }],
+            code_section: vec![code.into()],
+            container_section,
+            data_section: Bytes::copy_from_slice(data),
+            is_data_filled: true,
+        }
+        .into_eof()
+    }
+
+    #[test]
+    fn test_storage_accesses() {
+        // SSTORE(0, 1) SLOAD(0)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0x00,
+                opcode::SLOAD,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut evm = create_contract_call_evm(SpecId::CANCUN, code);
+        assert!(evm.transact().unwrap().storage_accesses.is_empty());
+
+        evm.cfg_mut().record_storage_accesses = true;
+        let access = |present: u64, new: u64, is_write| StorageAccess {
+            address: MOCK_CONTRACT,
+            key: U256::ZERO,
+            original_value: U256::ZERO,
+            present_value: U256::from(present),
+            new_value: U256::from(new),
+            is_write,
+        };
+        assert_eq!(
+            evm.transact().unwrap().storage_accesses,
+            vec![access(0, 1, true), access(1, 1, false)]
+        );
+    }
+
+    #[test]
+    fn test_eof_txcreate() {
+        let deployed = eof_container(0, vec![opcode::STOP], vec![], &[]);
+        // RETURNCONTRACT of the deployed container without aux data.
+        let initcode = eof_container(
+            2,
+            vec![opcode::PUSH0, opcode::PUSH0, opcode::RETURNCONTRACT, 0x00],
+            vec![deployed.raw.clone()],
+            &[],
+        )
+        .raw;
+        let initcode_hash = keccak256(&initcode);
+
+        // TXCREATE(initcode_hash, 0, 0, 0, 0) and return the created address.
+        let mut code = vec![opcode::PUSH0; 4];
+        code.push(opcode::PUSH32);
+        code.extend_from_slice(initcode_hash.as_slice());
+        code.extend([
+            opcode::TXCREATE,
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH0,
+            opcode::RETURN,
+        ]);
+        let code = Bytecode::Eof(eof_container(5, code, vec![], &[]));
+        let mut evm = create_contract_call_evm(SpecId::EXPERIMENTAL, code);
+        evm.tx_mut().set_eof_initcodes(vec![initcode.clone()]);
+
+        let created = MOCK_CONTRACT.create2(B256::ZERO, initcode_hash);
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            result.result.output().unwrap().as_ref(),
+            created.into_word().as_slice()
+        );
+        let account = &result.state[&created];
+        assert_eq!(account.info.nonce, 1);
+        assert_eq!(
+            account.info.code.as_ref().unwrap().original_bytes(),
+            deployed.raw
+        );
+        assert_eq!(result.state[&MOCK_CONTRACT].info.nonce, 2);
+
+        // unknown initcode pushes zero.
+        evm.tx_mut().set_eof_initcodes(Vec::new());
+        let result = evm.transact().unwrap().result;
+        assert!(result.is_success());
+        assert_eq!(result.output().unwrap().as_ref(), B256::ZERO.as_slice());
+    }
+
+    #[test]
+    fn test_eofcreate_nested() {
+        let eof = |max_stack_size, code, container_section, data: &[u8]| {
+            eof_container(max_stack_size, code, container_section, data).raw
+        };
+        let aux = B256::repeat_byte(0xaa);
+        let runtime = eof(0, vec![opcode::STOP], vec![], &[]);
+        // RETURNCONTRACT of the runtime container without aux data.
+        let inner_initcode = eof(
+            2,
+            vec![opcode::PUSH0, opcode::PUSH0, opcode::RETURNCONTRACT, 0x00],
+            vec![runtime.clone()],
+            &[],
+        );
+        // EOFCREATE of the inner initcode, then RETURNCONTRACT of the runtime container
+        // with the calldata as aux data.
+        let initcode = eof(
+            4,
+            vec![
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::EOFCREATE,
+                0x00,
+                opcode::POP,
+                opcode::PUSH1,
+                0x20,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::CALLDATACOPY,
+                opcode::PUSH1,
+                0x20,
+                opcode::PUSH0,
+                opcode::RETURNCONTRACT,
+                0x01,
+            ],
+            vec![inner_initcode.clone(), runtime.clone()],
+            &[],
+        );
+        // EOFCREATE of the initcode with the aux word as input, returns the created address.
+        let mut code = vec![opcode::PUSH32];
+        code.extend_from_slice(aux.as_slice());
+        code.extend([
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::EOFCREATE,
+            0x00,
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH0,
+            opcode::RETURN,
+        ]);
+        let factory = Bytecode::Eof(eof_container(4, code, vec![initcode.clone()], &[]));
+        let mut evm = create_contract_call_evm(SpecId::EXPERIMENTAL, factory);
+
+        let created = eofcreate_address(MOCK_CONTRACT, B256::ZERO, &initcode);
+        let nested = eofcreate_address(created, B256::ZERO, &inner_initcode);
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            result.result.output().unwrap().as_ref(),
+            created.into_word().as_slice()
+        );
+
+        // aux data is appended to the data section of the deployed container.
+        let deployed = &result.state[&created].info;
+        assert_eq!(deployed.nonce, 2);
+        assert_eq!(
+            deployed.code.as_ref().unwrap().original_bytes(),
+            eof(0, vec![opcode::STOP], vec![], aux.as_slice())
+        );
+        let deployed = &result.state[&nested].info;
+        assert_eq!(deployed.nonce, 1);
+        assert_eq!(deployed.code.as_ref().unwrap().original_bytes(), runtime);
+
+        // deployed container is over the code size limit.
+        evm.cfg_mut().limit_contract_code_size = Some(runtime.len());
+        let result = evm.transact().unwrap().result;
+        assert!(result.is_success());
+        assert_eq!(result.output().unwrap().as_ref(), B256::ZERO.as_slice());
+    }
+}
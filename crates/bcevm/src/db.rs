@@ -2,21 +2,37 @@
 
 #[cfg(feature = "alloydb")]
 pub mod alloydb;
+pub mod code_store;
 pub mod emptydb;
 #[cfg(feature = "ethersdb")]
 pub mod ethersdb;
+pub mod fork;
 pub mod in_memory_db;
+#[cfg(feature = "persistentdb")]
+pub mod persistentdb;
+pub mod state_override;
 pub mod states;
+pub mod verifying;
+pub mod witness;
 
 pub use crate::primitives::db::*;
 #[cfg(feature = "alloydb")]
 pub use alloydb::AlloyDB;
+pub use code_store::CodeStore;
+#[cfg(all(feature = "mmap", unix))]
+pub use code_store::MappedCodeStore;
 pub use emptydb::{EmptyDB, EmptyDBTyped};
 #[cfg(feature = "ethersdb")]
 pub use ethersdb::EthersDB;
+pub use fork::{ForkDB, ForkDBError, ForkInfo};
 pub use in_memory_db::*;
+#[cfg(feature = "persistentdb")]
+pub use persistentdb::{KeyValueStore, PersistentDB, PersistentDBError, WriteBatch};
+pub use state_override::{AccountOverride, StateOverride};
 pub use states::{
     AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
     StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
+pub use verifying::{AccountProof, StorageProof, VerifyingDatabase, VerifyingDatabaseError};
+pub use witness::{Witness, WitnessDB, WitnessDBError, WitnessRecorder};
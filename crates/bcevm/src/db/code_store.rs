@@ -0,0 +1,206 @@
+//! Read-only stores of contract code, shared by the databases.
+//!
+//! A [CodeStore] is consulted by [CacheDB](super::CacheDB) and
+//! [PersistentDB](super::PersistentDB) before the code is loaded from the underlying
+//! database. With the `mmap` feature, [MappedCodeStore] serves code from a memory-mapped
+//! file, so large contract sets are not kept in the heap.
+
+use crate::primitives::{Bytecode, HashMap, B256};
+use core::fmt;
+
+/// Read-only store of contract code by its hash.
+pub trait CodeStore: fmt::Debug + Send + Sync {
+    /// Returns the code of the hash, `None` if it is not in the store.
+    fn code_by_hash(&self, code_hash: B256) -> Option<Bytecode>;
+}
+
+impl CodeStore for HashMap<B256, Bytecode> {
+    fn code_by_hash(&self, code_hash: B256) -> Option<Bytecode> {
+        self.get(&code_hash).cloned()
+    }
+}
+
+#[cfg(all(feature = "mmap", unix))]
+pub use mapped::MappedCodeStore;
+
+#[cfg(all(feature = "mmap", unix))]
+mod mapped {
+    use super::CodeStore;
+    use crate::primitives::{keccak256, Bytecode, Bytes, HashMap, B256};
+    use core::{fmt, ptr};
+    use std::{
+        fs::File,
+        io::{self, BufWriter, Write},
+        os::fd::AsRawFd,
+        path::Path,
+    };
+
+    /// Length of an entry header: code hash and code length.
+    const HEADER_LEN: usize = 32 + 4;
+
+    /// [CodeStore] that serves code from a memory-mapped file.
+    ///
+    /// The file is a sequence of entries, each one is the code hash, the code length as a
+    /// little-endian `u32` and the code, see [MappedCodeStore::write]. Returned code
+    /// references the mapping, it is not copied, and the mapping is released when the store
+    /// and all of the returned code are dropped.
+    ///
+    /// The file must not be modified while it is mapped.
+    pub struct MappedCodeStore {
+        data: bytes::Bytes,
+        index: HashMap<B256, (usize, usize)>,
+    }
+
+    impl MappedCodeStore {
+        /// Writes the code to a new file that can be opened with [MappedCodeStore::open].
+        pub fn write<'a>(
+            path: impl AsRef<Path>,
+            codes: impl IntoIterator<Item = &'a [u8]>,
+        ) -> io::Result<()> {
+            let mut file = BufWriter::new(File::create(path)?);
+            for code in codes {
+                let len = u32::try_from(code.len())
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "code too large"))?;
+                file.write_all(keccak256(code).as_slice())?;
+                file.write_all(&len.to_le_bytes())?;
+                file.write_all(code)?;
+            }
+            file.flush()
+        }
+
+        /// Maps the file and indexes its entries.
+        pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+            let file = File::open(path)?;
+            let len = usize::try_from(file.metadata()?.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "file too large"))?;
+            let data = if len == 0 {
+                bytes::Bytes::new()
+            } else {
+                bytes::Bytes::from_owner(Mmap::new(&file, len)?)
+            };
+
+            let mut index = HashMap::new();
+            let mut offset = 0;
+            while offset < data.len() {
+                let header = data
+                    .get(offset..offset + HEADER_LEN)
+                    .ok_or_else(|| invalid_data("truncated entry header"))?;
+                let code_hash = B256::from_slice(&header[..32]);
+                let code_len = u32::from_le_bytes(header[32..].try_into().unwrap()) as usize;
+                let start = offset + HEADER_LEN;
+                let end = start
+                    .checked_add(code_len)
+                    .filter(|end| *end <= data.len())
+                    .ok_or_else(|| invalid_data("truncated code"))?;
+                index.insert(code_hash, (start, end));
+                offset = end;
+            }
+            Ok(Self { data, index })
+        }
+
+        /// Returns the number of stored contracts.
+        pub fn len(&self) -> usize {
+            self.index.len()
+        }
+
+        /// Returns true if there are no stored contracts.
+        pub fn is_empty(&self) -> bool {
+            self.index.is_empty()
+        }
+    }
+
+    impl CodeStore for MappedCodeStore {
+        fn code_by_hash(&self, code_hash: B256) -> Option<Bytecode> {
+            let (start, end) = *self.index.get(&code_hash)?;
+            Some(Bytecode::new_raw(Bytes::from(self.data.slice(start..end))))
+        }
+    }
+
+    impl fmt::Debug for MappedCodeStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("MappedCodeStore")
+                .field("mapped_len", &self.data.len())
+                .field("contracts", &self.index.len())
+                .finish()
+        }
+    }
+
+    fn invalid_data(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+
+    /// Read-only private mapping of a whole file.
+    struct Mmap {
+        ptr: *mut libc::c_void,
+        len: usize,
+    }
+
+    // SAFETY: the mapping is read-only and not tied to the thread that created it.
+    unsafe impl Send for Mmap {}
+    unsafe impl Sync for Mmap {}
+
+    impl Mmap {
+        fn new(file: &File, len: usize) -> io::Result<Self> {
+            // SAFETY: maps `len` bytes of a valid file descriptor, the result is checked.
+            let ptr = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    len,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { ptr, len })
+        }
+    }
+
+    impl AsRef<[u8]> for Mmap {
+        fn as_ref(&self) -> &[u8] {
+            // SAFETY: `ptr` is a live mapping of `len` readable bytes.
+            unsafe { core::slice::from_raw_parts(self.ptr.cast(), self.len) }
+        }
+    }
+
+    impl Drop for Mmap {
+        fn drop(&mut self) {
+            // SAFETY: `ptr` and `len` are the mapping created in `Mmap::new`.
+            unsafe { libc::munmap(self.ptr, self.len) };
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn mapped_code_store() {
+            let path = std::env::temp_dir().join(format!("bcevm-code-{}", std::process::id()));
+            let codes: [&[u8]; 3] = [&[0x60, 0x00], &[], &[0x5f, 0x5f, 0xf3]];
+            MappedCodeStore::write(&path, codes).unwrap();
+
+            let store = MappedCodeStore::open(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(store.len(), 3);
+            for code in codes {
+                let stored = store.code_by_hash(keccak256(code)).unwrap();
+                assert_eq!(stored.original_byte_slice(), code);
+            }
+            assert_eq!(store.code_by_hash(B256::ZERO), None);
+
+            // code is not copied out of the mapping.
+            let code = store.code_by_hash(keccak256(codes[2])).unwrap();
+            let mapped = store.data.as_ptr_range();
+            assert!(mapped.contains(&code.original_byte_slice().as_ptr()));
+
+            std::fs::write(&path, [0u8; HEADER_LEN - 1]).unwrap();
+            let err = MappedCodeStore::open(&path).unwrap_err();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
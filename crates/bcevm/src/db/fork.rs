@@ -0,0 +1,245 @@
+//! Database that owns multiple forks of remote chains and switches between them.
+
+use super::{AccountState, CacheDB, DatabaseCommit, DatabaseRef, EmptyDB};
+use crate::primitives::{Account, AccountInfo, Address, Bytecode, HashMap, B256, U256};
+use crate::Database;
+use core::fmt;
+use std::string::String;
+
+/// Origin of the fork.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForkInfo {
+    /// Endpoint of the provider.
+    pub endpoint: String,
+    /// Block number the fork is pinned to.
+    pub block_number: u64,
+}
+
+/// Error of the [ForkDB].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ForkDBError<E> {
+    /// Error of the database of the active fork.
+    Database(E),
+    /// No fork is selected.
+    NoActiveFork,
+}
+
+impl<E: fmt::Display> fmt::Display for ForkDBError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Database(err) => write!(f, "database error: {err}"),
+            Self::NoActiveFork => f.write_str("no fork is selected"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for ForkDBError<E> {}
+
+#[derive(Debug)]
+struct Fork<ExtDB> {
+    info: ForkInfo,
+    db: CacheDB<ExtDB>,
+}
+
+/// Database with multiple named forks, each backed by its own database (e.g. a remote
+/// provider pinned to a block), of which one is active at a time.
+///
+/// Changes committed to the database are kept in a local cache that is shared by all
+/// forks, so they stay visible after the active fork is switched. Reads of accounts that
+/// were not changed locally go to the active fork and are cached per fork.
+///
+/// Block environment is not changed by switching the fork, it needs to be updated by
+/// the caller.
+#[derive(Debug)]
+pub struct ForkDB<ExtDB> {
+    forks: HashMap<String, Fork<ExtDB>>,
+    active: Option<String>,
+    local: CacheDB<EmptyDB>,
+}
+
+impl<ExtDB> Default for ForkDB<ExtDB> {
+    fn default() -> Self {
+        Self {
+            forks: HashMap::new(),
+            active: None,
+            local: CacheDB::new(EmptyDB::default()),
+        }
+    }
+}
+
+impl<ExtDB> ForkDB<ExtDB> {
+    /// Creates new database without forks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the fork, replacing the fork with the same name.
+    ///
+    /// The first added fork becomes active.
+    pub fn create_fork(&mut self, name: impl Into<String>, info: ForkInfo, db: ExtDB) {
+        let name = name.into();
+        if self.active.is_none() {
+            self.active = Some(name.clone());
+        }
+        self.forks.insert(
+            name,
+            Fork {
+                info,
+                db: CacheDB::new(db),
+            },
+        );
+    }
+
+    /// Removes the fork and returns its database. Active fork can't be removed.
+    pub fn remove_fork(&mut self, name: &str) -> Option<ExtDB> {
+        if self.active.as_deref() == Some(name) {
+            return None;
+        }
+        self.forks.remove(name).map(|fork| fork.db.db)
+    }
+
+    /// Selects the active fork. Returns `false` if there is no fork with the name.
+    pub fn select_fork(&mut self, name: &str) -> bool {
+        if !self.forks.contains_key(name) {
+            return false;
+        }
+        self.active = Some(name.into());
+        true
+    }
+
+    /// Returns name and origin of the active fork.
+    pub fn active_fork(&self) -> Option<(&str, &ForkInfo)> {
+        let name = self.active.as_deref()?;
+        Some((name, &self.forks.get(name)?.info))
+    }
+
+    /// Returns origin of the fork.
+    pub fn fork_info(&self, name: &str) -> Option<&ForkInfo> {
+        self.forks.get(name).map(|fork| &fork.info)
+    }
+
+    /// Returns iterator over the names of the forks.
+    pub fn fork_names(&self) -> impl Iterator<Item = &str> {
+        self.forks.keys().map(String::as_str)
+    }
+
+    /// Returns the local cache with the committed changes.
+    pub fn local(&self) -> &CacheDB<EmptyDB> {
+        &self.local
+    }
+
+    /// Returns mutable local cache, e.g. to insert accounts visible in all forks.
+    pub fn local_mut(&mut self) -> &mut CacheDB<EmptyDB> {
+        &mut self.local
+    }
+
+    fn active_db(&mut self) -> Result<&mut CacheDB<ExtDB>, ForkDBError<ExtDB::Error>>
+    where
+        ExtDB: DatabaseRef,
+    {
+        self.active
+            .as_ref()
+            .and_then(|name| self.forks.get_mut(name))
+            .map(|fork| &mut fork.db)
+            .ok_or(ForkDBError::NoActiveFork)
+    }
+}
+
+impl<ExtDB: DatabaseRef> Database for ForkDB<ExtDB> {
+    type Error = ForkDBError<ExtDB::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(account) = self.local.accounts.get(&address) {
+            return Ok(account.info());
+        }
+        self.active_db()?
+            .basic(address)
+            .map_err(ForkDBError::Database)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(code) = self.local.contracts.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        self.active_db()?
+            .code_by_hash(code_hash)
+            .map_err(ForkDBError::Database)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(account) = self.local.accounts.get(&address) {
+            if let Some(value) = account.storage.get(&index) {
+                return Ok(*value);
+            }
+            if matches!(
+                account.account_state,
+                AccountState::StorageCleared | AccountState::NotExisting
+            ) {
+                return Ok(U256::ZERO);
+            }
+        }
+        self.active_db()?
+            .storage(address, index)
+            .map_err(ForkDBError::Database)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.active_db()?
+            .block_hash(number)
+            .map_err(ForkDBError::Database)
+    }
+}
+
+impl<ExtDB> DatabaseCommit for ForkDB<ExtDB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.local.commit(changes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{address, AccountStatus};
+
+    #[test]
+    fn switch_forks() {
+        let account = address!("1000000000000000000000000000000000000001");
+        let fork = |balance: u64| {
+            let mut db = CacheDB::new(EmptyDB::default());
+            db.insert_account_info(account, AccountInfo::from_balance(U256::from(balance)));
+            db.insert_account_storage(account, U256::ZERO, U256::from(balance))
+                .unwrap();
+            db
+        };
+        let mut db = ForkDB::new();
+        assert_eq!(db.basic(account), Err(ForkDBError::NoActiveFork));
+
+        let info = |block_number| ForkInfo {
+            endpoint: "http://localhost:8545".into(),
+            block_number,
+        };
+        db.create_fork("a", info(1), fork(1));
+        db.create_fork("b", info(2), fork(2));
+        assert_eq!(db.active_fork(), Some(("a", &info(1))));
+        assert_eq!(db.basic(account).unwrap().unwrap().balance, U256::from(1));
+
+        assert!(db.select_fork("b"));
+        assert!(!db.select_fork("c"));
+        assert_eq!(db.basic(account).unwrap().unwrap().balance, U256::from(2));
+        assert_eq!(db.storage(account, U256::ZERO), Ok(U256::from(2)));
+
+        // local changes are shared by all forks.
+        let mut changed: Account = AccountInfo::from_balance(U256::from(5)).into();
+        changed.status = AccountStatus::Touched;
+        db.commit(HashMap::from_iter([(account, changed)]));
+        assert!(db.select_fork("a"));
+        assert_eq!(db.basic(account).unwrap().unwrap().balance, U256::from(5));
+        // storage that was not changed is read from the fork.
+        assert_eq!(db.storage(account, U256::ZERO), Ok(U256::from(1)));
+
+        assert!(db.remove_fork("a").is_none());
+        assert!(db.remove_fork("b").is_some());
+    }
+}
@@ -1,11 +1,13 @@
-use super::{DatabaseCommit, DatabaseRef, EmptyDB};
+use super::{CodeStore, DatabaseCommit, DatabaseRef, EmptyDB, StateOverride};
 use crate::primitives::{
-    hash_map::Entry, Account, AccountInfo, Address, Bytecode, HashMap, Log, B256, KECCAK_EMPTY,
-    U256,
+    hash_map::Entry, Account, AccountInfo, Address, Bytecode, Bytes, Eof, HashMap, Log, B256,
+    KECCAK_EMPTY, U256,
 };
+#[cfg(feature = "std")]
+use crate::primitives::{HashSet, TransactTo, TxEnv};
 use crate::Database;
 use core::convert::Infallible;
-use std::vec::Vec;
+use std::{collections::BTreeMap, sync::Arc, vec::Vec};
 
 /// A [Database] implementation that stores all state changes in memory.
 pub type InMemoryDB = CacheDB<EmptyDB>;
@@ -33,6 +35,10 @@ pub struct CacheDB<ExtDB> {
     ///
     /// Note: this is read-only, data is never written to this database.
     pub db: ExtDB,
+    /// Code that is not in `contracts` is looked up in this store before the underlying
+    /// database.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub code_store: Option<Arc<dyn CodeStore>>,
 }
 
 impl<ExtDB: Default> Default for CacheDB<ExtDB> {
@@ -52,9 +58,16 @@ impl<ExtDB> CacheDB<ExtDB> {
             logs: Vec::default(),
             block_hashes: HashMap::new(),
             db,
+            code_store: None,
         }
     }
 
+    /// Sets the store that code is looked up in before the underlying database.
+    pub fn with_code_store(mut self, code_store: Arc<dyn CodeStore>) -> Self {
+        self.code_store = Some(code_store);
+        self
+    }
+
     /// Inserts the account's code into the cache.
     ///
     /// Accounts objects and code are stored separately in the cache, this will take the code from the account and instead map it to the code hash.
@@ -81,6 +94,118 @@ impl<ExtDB> CacheDB<ExtDB> {
         self.insert_contract(&mut info);
         self.accounts.entry(address).or_default().info = info;
     }
+
+    /// Returns the cached accounts and block hashes ordered by their keys.
+    ///
+    /// Logs and the underlying database are not part of the state, see
+    /// [CacheDB::load_state] for restoring it.
+    pub fn dump_state(&self) -> SerializableState {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|(address, account)| {
+                let code = account
+                    .info
+                    .code
+                    .as_ref()
+                    .or_else(|| self.contracts.get(&account.info.code_hash))
+                    .map(Bytecode::original_bytes)
+                    .unwrap_or_default();
+                let account = SerializableAccount {
+                    balance: account.info.balance,
+                    nonce: account.info.nonce,
+                    code,
+                    storage: account.storage.iter().map(|(k, v)| (*k, *v)).collect(),
+                    account_state: account.account_state.clone(),
+                };
+                (*address, account)
+            })
+            .collect();
+        SerializableState {
+            accounts,
+            block_hashes: self.block_hashes.iter().map(|(k, v)| (*k, *v)).collect(),
+        }
+    }
+
+    /// Loads the state returned by [CacheDB::dump_state].
+    ///
+    /// Accounts of the state replace the cached accounts with the same address, other cached
+    /// accounts are kept.
+    pub fn load_state(&mut self, state: SerializableState) {
+        for (address, account) in state.accounts {
+            let mut info = AccountInfo {
+                balance: account.balance,
+                nonce: account.nonce,
+                code_hash: KECCAK_EMPTY,
+                code: None,
+            };
+            if !account.code.is_empty() {
+                let code = bytecode_from_original(account.code);
+                info.code_hash = code.hash_slow();
+                info.code = Some(code);
+            }
+            self.insert_contract(&mut info);
+            self.accounts.insert(
+                address,
+                DbAccount {
+                    info,
+                    account_state: account.account_state,
+                    storage: account.storage.into_iter().collect(),
+                },
+            );
+        }
+        self.block_hashes.extend(state.block_hashes);
+    }
+}
+
+/// Returns the bytecode of the original bytes returned by [Bytecode::original_bytes].
+fn bytecode_from_original(code: Bytes) -> Bytecode {
+    if code.starts_with(&[0xEF, 0x00]) {
+        if let Ok(eof) = Eof::decode(code.clone()) {
+            return Bytecode::Eof(eof);
+        }
+    }
+    Bytecode::new_raw(code)
+}
+
+/// State of the [CacheDB] with deterministic ordering, see [CacheDB::dump_state].
+///
+/// Maps are ordered by their keys, so the same state is always serialized in the same way.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SerializableState {
+    /// Cached accounts.
+    pub accounts: BTreeMap<Address, SerializableAccount>,
+    /// Cached block hashes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub block_hashes: BTreeMap<U256, B256>,
+}
+
+/// Account of the [SerializableState].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SerializableAccount {
+    /// Account balance.
+    pub balance: U256,
+    /// Account nonce.
+    pub nonce: u64,
+    /// Original bytes of the account code.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "<[u8]>::is_empty")
+    )]
+    pub code: Bytes,
+    /// Cached storage slots.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub storage: BTreeMap<U256, U256>,
+    /// State of the account, see [AccountState].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub account_state: AccountState,
 }
 
 impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
@@ -125,6 +250,184 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         account.storage = storage.into_iter().collect();
         Ok(())
     }
+
+    /// Applies the state overrides to the cached accounts.
+    ///
+    /// Overrides are never written to the underlying database.
+    pub fn apply_overrides(&mut self, overrides: &StateOverride) -> Result<(), ExtDB::Error> {
+        for (address, account_override) in overrides {
+            let mut info = self.load_account(*address)?.info.clone();
+            if let Some(balance) = account_override.balance {
+                info.balance = balance;
+            }
+            if let Some(nonce) = account_override.nonce {
+                info.nonce = nonce;
+            }
+            if let Some(code) = &account_override.code {
+                let code = Bytecode::new_raw(code.clone());
+                info.code_hash = code.hash_slow();
+                info.code = Some(code);
+            }
+            self.insert_contract(&mut info);
+
+            let account = self.load_account(*address)?;
+            account.info = info;
+            if account.account_state == AccountState::NotExisting {
+                account.account_state = AccountState::None;
+            }
+            if let Some(state) = &account_override.state {
+                account.account_state = AccountState::StorageCleared;
+                account.storage = state.clone();
+            }
+            if let Some(state_diff) = &account_override.state_diff {
+                account.storage.extend(state_diff);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<ExtDB> CacheDB<ExtDB>
+where
+    ExtDB: DatabaseRef + Sync,
+    ExtDB::Error: Send,
+{
+    /// Loads senders, recipients and access list entries of the given transactions into the cache.
+    ///
+    /// See [CacheDB::preload].
+    pub fn preload_transactions<'a>(
+        &mut self,
+        txs: impl IntoIterator<Item = &'a TxEnv>,
+    ) -> Result<(), ExtDB::Error> {
+        let mut targets: HashMap<Address, HashSet<U256>> = HashMap::new();
+        for tx in txs {
+            targets.entry(tx.caller).or_default();
+            if let TransactTo::Call(to) = tx.transact_to {
+                targets.entry(to).or_default();
+            }
+            for (address, slots) in &tx.access_list {
+                targets
+                    .entry(*address)
+                    .or_default()
+                    .extend(slots.iter().copied());
+            }
+        }
+        self.preload(targets)
+    }
+
+    /// Loads the given accounts and their storage slots into the cache, querying the
+    /// underlying database concurrently.
+    ///
+    /// Entries that are already cached are skipped. This is useful before executing a block
+    /// on top of a remote database, where serial lookups during execution dominate the runtime.
+    pub fn preload<I>(
+        &mut self,
+        targets: impl IntoIterator<Item = (Address, I)>,
+    ) -> Result<(), ExtDB::Error>
+    where
+        I: IntoIterator<Item = U256>,
+    {
+        // (address, load account info, storage slots to load)
+        let mut work: Vec<(Address, bool, Vec<U256>)> = Vec::new();
+        for (address, slots) in targets {
+            match self.accounts.get(&address) {
+                Some(account) => {
+                    if matches!(
+                        account.account_state,
+                        AccountState::StorageCleared | AccountState::NotExisting
+                    ) {
+                        continue;
+                    }
+                    let slots: Vec<U256> = slots
+                        .into_iter()
+                        .filter(|slot| !account.storage.contains_key(slot))
+                        .collect();
+                    if !slots.is_empty() {
+                        work.push((address, false, slots));
+                    }
+                }
+                None => work.push((address, true, slots.into_iter().collect())),
+            }
+        }
+        if work.is_empty() {
+            return Ok(());
+        }
+
+        let threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(work.len());
+        let chunk_size = work.len().div_ceil(threads);
+        let db = &self.db;
+        let loaded = std::thread::scope(|scope| {
+            let handles: Vec<_> = work
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|(address, load_info, slots)| {
+                                preload_account(db, *address, *load_info, slots)
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("preload thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        for chunk in loaded {
+            for (address, info, storage) in chunk? {
+                if let Some(info) = info {
+                    let account = match info {
+                        Some(mut info) => {
+                            self.insert_contract(&mut info);
+                            DbAccount {
+                                info,
+                                ..Default::default()
+                            }
+                        }
+                        None => DbAccount::new_not_existing(),
+                    };
+                    self.accounts.insert(address, account);
+                }
+                if let Some(account) = self.accounts.get_mut(&address) {
+                    account.storage.extend(storage);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Account info (if requested) and storage slots fetched by [CacheDB::preload].
+#[cfg(feature = "std")]
+type PreloadedAccount = (Address, Option<Option<AccountInfo>>, Vec<(U256, U256)>);
+
+#[cfg(feature = "std")]
+fn preload_account<DB: DatabaseRef>(
+    db: &DB,
+    address: Address,
+    load_info: bool,
+    slots: &[U256],
+) -> Result<PreloadedAccount, DB::Error> {
+    let info = if load_info {
+        Some(db.basic_ref(address)?)
+    } else {
+        None
+    };
+    // storage of not existing account is empty.
+    if matches!(info, Some(None)) {
+        return Ok((address, info, Vec::new()));
+    }
+    let storage = slots
+        .iter()
+        .map(|slot| Ok((*slot, db.storage_ref(address, *slot)?)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((address, info, storage))
 }
 
 impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
@@ -188,8 +491,16 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
         match self.contracts.entry(code_hash) {
             Entry::Occupied(entry) => Ok(entry.get().clone()),
             Entry::Vacant(entry) => {
-                // if you return code bytes when basic fn is called this function is not needed.
-                Ok(entry.insert(self.db.code_by_hash_ref(code_hash)?).clone())
+                let code = match self
+                    .code_store
+                    .as_ref()
+                    .and_then(|s| s.code_by_hash(code_hash))
+                {
+                    Some(code) => code,
+                    // if you return code bytes when basic fn is called this function is not needed.
+                    None => self.db.code_by_hash_ref(code_hash)?,
+                };
+                Ok(entry.insert(code).clone())
             }
         }
     }
@@ -257,8 +568,15 @@ impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
     }
 
     fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
-        match self.contracts.get(&code_hash) {
-            Some(entry) => Ok(entry.clone()),
+        if let Some(code) = self.contracts.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        match self
+            .code_store
+            .as_ref()
+            .and_then(|s| s.code_by_hash(code_hash))
+        {
+            Some(code) => Ok(code),
             None => self.db.code_by_hash_ref(code_hash),
         }
     }
@@ -465,6 +783,131 @@ mod tests {
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_preload_transactions() {
+        use crate::primitives::{TransactTo, TxEnv};
+
+        let caller = Address::with_last_byte(1);
+        let contract = Address::with_last_byte(2);
+        let missing = Address::with_last_byte(3);
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(
+            caller,
+            AccountInfo {
+                nonce: 7,
+                ..Default::default()
+            },
+        );
+        init_state.insert_account_info(contract, AccountInfo::default());
+        init_state
+            .insert_account_storage(contract, U256::from(1), U256::from(100))
+            .unwrap();
+
+        let tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(contract),
+            access_list: vec![
+                (contract, vec![U256::from(1), U256::from(2)]),
+                (missing, vec![U256::from(1)]),
+            ],
+            ..Default::default()
+        };
+
+        let mut new_state = CacheDB::new(init_state);
+        new_state.preload_transactions([&tx]).unwrap();
+
+        assert_eq!(new_state.accounts[&caller].info.nonce, 7);
+        let storage = &new_state.accounts[&contract].storage;
+        assert_eq!(storage.get(&U256::from(1)), Some(&U256::from(100)));
+        assert_eq!(storage.get(&U256::from(2)), Some(&U256::ZERO));
+        assert!(new_state.accounts[&missing].info().is_none());
+        assert!(new_state.accounts[&missing].storage.is_empty());
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        use crate::db::AccountOverride;
+
+        let account = Address::with_last_byte(42);
+        let (key0, key1) = (U256::from(1), U256::from(2));
+        let mut state = CacheDB::new(EmptyDB::default());
+        state.insert_account_info(
+            account,
+            AccountInfo {
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+        state
+            .insert_account_storage(account, key0, U256::from(10))
+            .unwrap();
+
+        let overrides = [(
+            account,
+            AccountOverride::default()
+                .with_balance(U256::from(100))
+                .with_state([(key1, U256::from(20))].into())
+                .with_state_diff([(key1, U256::from(30))].into()),
+        )]
+        .into();
+        state.apply_overrides(&overrides).unwrap();
+
+        let info = state.basic(account).unwrap().unwrap();
+        assert_eq!((info.balance, info.nonce), (U256::from(100), 1));
+        assert_eq!(state.storage(account, key0), Ok(U256::ZERO));
+        assert_eq!(state.storage(account, key1), Ok(U256::from(30)));
+    }
+
+    #[test]
+    fn test_code_store() {
+        use crate::primitives::{db::DatabaseRef, Bytecode, Bytes, HashMap};
+        use std::sync::Arc;
+
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01]));
+        let code_hash = code.hash_slow();
+        let store = HashMap::from([(code_hash, code.clone())]);
+
+        let db = CacheDB::new(EmptyDB::default());
+        assert!(db.code_by_hash_ref(code_hash).unwrap().is_empty());
+
+        let mut db = db.with_code_store(Arc::new(store));
+        assert_eq!(db.code_by_hash_ref(code_hash), Ok(code.clone()));
+        assert_eq!(db.code_by_hash(code_hash), Ok(code));
+        assert!(db.contracts.contains_key(&code_hash));
+    }
+
+    #[test]
+    fn test_transact_with_overrides() {
+        use crate::{
+            db::AccountOverride,
+            primitives::{Bytes, TransactTo},
+            Evm,
+        };
+
+        let contract = Address::with_last_byte(42);
+        // PUSH1 0 SLOAD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+        let code = Bytes::from_static(&[
+            0x60, 0x00, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ]);
+        let overrides = [(
+            contract,
+            AccountOverride::default()
+                .with_code(code)
+                .with_state_diff([(U256::ZERO, U256::from(7))].into()),
+        )]
+        .into();
+
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(contract))
+            .build();
+        let result = evm.transact_with_overrides(&overrides).unwrap();
+        let output = result.result.output().unwrap();
+        assert_eq!(U256::from_be_slice(output), U256::from(7));
+        assert!(!evm.db().accounts.contains_key(&contract));
+    }
+
     #[cfg(feature = "serde-json")]
     #[test]
     fn test_serialize_deserialize_cachedb() {
@@ -488,4 +931,71 @@ mod tests {
             nonce
         );
     }
+
+    #[test]
+    fn test_dump_load_state() {
+        use crate::primitives::{Bytecode, Bytes, B256};
+
+        let contract = Address::with_last_byte(42);
+        let missing = Address::with_last_byte(43);
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x54, 0x00]));
+        let mut state = CacheDB::new(EmptyDB::default());
+        state.insert_account_info(
+            contract,
+            AccountInfo::new(U256::from(100), 1, code.hash_slow(), code.clone()),
+        );
+        for slot in [3u64, 1, 2] {
+            state
+                .insert_account_storage(contract, U256::from(slot), U256::from(slot * 10))
+                .unwrap();
+        }
+        assert_eq!(state.basic(missing), Ok(None));
+        state
+            .block_hashes
+            .insert(U256::from(1), B256::with_last_byte(1));
+
+        let dump = state.dump_state();
+        assert_eq!(
+            dump.accounts.keys().copied().collect::<Vec<_>>(),
+            [contract, missing]
+        );
+        assert_eq!(dump.accounts[&contract].code, code.original_bytes());
+
+        let mut loaded = CacheDB::new(EmptyDB::default());
+        loaded.load_state(dump.clone());
+        assert_eq!(loaded.dump_state(), dump);
+        assert_eq!(loaded.basic(missing), Ok(None));
+        let info = loaded.basic(contract).unwrap().unwrap();
+        assert_eq!((info.balance, info.nonce), (U256::from(100), 1));
+        assert_eq!(loaded.code_by_hash(info.code_hash), Ok(code));
+        assert_eq!(loaded.storage(contract, U256::from(2)), Ok(U256::from(20)));
+        assert_eq!(
+            loaded.block_hash(U256::from(1)),
+            Ok(B256::with_last_byte(1))
+        );
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn test_dump_state_json() {
+        use super::SerializableState;
+
+        let mut state = CacheDB::new(EmptyDB::default());
+        for i in 0..16 {
+            let address = Address::with_last_byte(i);
+            state.insert_account_info(address, AccountInfo::default());
+            state
+                .insert_account_storage(address, U256::from(i), U256::from(1))
+                .unwrap();
+        }
+        let json = serde_json::to_string(&state.dump_state()).unwrap();
+        // same state is serialized in the same way.
+        assert_eq!(
+            json,
+            serde_json::to_string(&state.clone().dump_state()).unwrap()
+        );
+
+        let loaded: SerializableState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, state.dump_state());
+    }
 }
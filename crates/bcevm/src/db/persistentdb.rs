@@ -0,0 +1,688 @@
+//! Database persisted in an embedded key-value store.
+
+use crate::{
+    db::CodeStore,
+    primitives::{
+        Account, AccountInfo, Address, Bytecode, Bytes, HashMap, B256, KECCAK_EMPTY, U256,
+    },
+    Database, DatabaseCommit, DatabaseRef,
+};
+use core::{convert::Infallible, fmt};
+use std::{boxed::Box, collections::BTreeMap, sync::Arc, vec::Vec};
+
+/// Key prefix of the accounts, followed by the address.
+const ACCOUNT_PREFIX: u8 = b'a';
+/// Key prefix of the storage slots, followed by the address and the slot.
+const STORAGE_PREFIX: u8 = b's';
+/// Key prefix of the code, followed by the code hash.
+const CODE_PREFIX: u8 = b'c';
+/// Key prefix of the block hashes, followed by the block number.
+const BLOCK_HASH_PREFIX: u8 = b'b';
+
+/// Length of an encoded account: balance, nonce and code hash.
+const ACCOUNT_LEN: usize = 32 + 8 + 32;
+
+/// Default number of cached entries, see [PersistentDB::with_cache_limit].
+pub const DEFAULT_CACHE_LIMIT: usize = 1 << 20;
+
+/// Changes written atomically to a [KeyValueStore].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteBatch {
+    /// Written keys with their value, `None` if the key is deleted.
+    pub ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl WriteBatch {
+    /// Sets the key to the value.
+    pub fn put(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.ops.push((key, Some(value)));
+    }
+
+    /// Deletes the key.
+    pub fn delete(&mut self, key: Vec<u8>) {
+        self.ops.push((key, None));
+    }
+
+    /// Returns `true` if the batch has no changes.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// Iterator over the entries of a [KeyValueStore].
+pub type KeyValueIter<'a, E> = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), E>> + 'a>;
+
+/// Embedded key-value store backing the [PersistentDB].
+///
+/// Implemented for [BTreeMap], which keeps the state in memory, and for `rocksdb::DB`
+/// with the `rocksdb` feature.
+pub trait KeyValueStore {
+    /// Error of the store.
+    type Error;
+
+    /// Returns the value of the key.
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Writes the batch atomically.
+    fn write(&mut self, batch: WriteBatch) -> Result<(), Self::Error>;
+
+    /// Returns the entries whose key starts with the prefix, ordered by key.
+    fn iter_prefix(&self, prefix: &[u8]) -> KeyValueIter<'_, Self::Error>;
+}
+
+impl KeyValueStore for BTreeMap<Vec<u8>, Vec<u8>> {
+    type Error = Infallible;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(BTreeMap::get(self, key).cloned())
+    }
+
+    fn write(&mut self, batch: WriteBatch) -> Result<(), Self::Error> {
+        for (key, value) in batch.ops {
+            match value {
+                Some(value) => self.insert(key, value),
+                None => self.remove(&key),
+            };
+        }
+        Ok(())
+    }
+
+    fn iter_prefix(&self, prefix: &[u8]) -> KeyValueIter<'_, Self::Error> {
+        let prefix = prefix.to_vec();
+        Box::new(
+            self.range(prefix.clone()..)
+                .take_while(move |(key, _)| key.starts_with(&prefix))
+                .map(|(key, value)| Ok((key.clone(), value.clone()))),
+        )
+    }
+}
+
+#[cfg(feature = "rocksdb")]
+impl KeyValueStore for rocksdb::DB {
+    type Error = rocksdb::Error;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        rocksdb::DB::get(self, key)
+    }
+
+    fn write(&mut self, batch: WriteBatch) -> Result<(), Self::Error> {
+        let mut rocks_batch = rocksdb::WriteBatch::default();
+        for (key, value) in batch.ops {
+            match value {
+                Some(value) => rocks_batch.put(key, value),
+                None => rocks_batch.delete(key),
+            }
+        }
+        rocksdb::DB::write(self, rocks_batch)
+    }
+
+    fn iter_prefix(&self, prefix: &[u8]) -> KeyValueIter<'_, Self::Error> {
+        let mode = rocksdb::IteratorMode::From(prefix, rocksdb::Direction::Forward);
+        let prefix = prefix.to_vec();
+        Box::new(
+            self.iterator(mode)
+                .take_while(
+                    move |entry| !matches!(entry, Ok((key, _)) if !key.starts_with(&prefix)),
+                )
+                .map(|entry| entry.map(|(key, value)| (key.into_vec(), value.into_vec()))),
+        )
+    }
+}
+
+/// Error of the [PersistentDB].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PersistentDBError<E> {
+    /// Error of the key-value store.
+    Store(E),
+    /// Stored value has an invalid encoding.
+    InvalidValue {
+        /// Key of the value.
+        key: Vec<u8>,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for PersistentDBError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Store(err) => write!(f, "store error: {err}"),
+            Self::InvalidValue { key } => {
+                write!(
+                    f,
+                    "invalid value of key {}",
+                    crate::primitives::hex::encode(key)
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for PersistentDBError<E> {}
+
+/// Database that stores accounts, storage, code and block hashes in a [KeyValueStore].
+///
+/// Reads go through a cache, that is cleared once it holds more than the
+/// [cache limit](Self::with_cache_limit) entries, so the memory used does not grow with
+/// the state. Every commit is written to the store in a single batch.
+#[derive(Debug)]
+pub struct PersistentDB<S> {
+    store: S,
+    accounts: HashMap<Address, Option<AccountInfo>>,
+    storage: HashMap<(Address, U256), U256>,
+    contracts: HashMap<B256, Bytecode>,
+    cache_limit: usize,
+    code_store: Option<Arc<dyn CodeStore>>,
+}
+
+impl<S: KeyValueStore> PersistentDB<S> {
+    /// Creates new database on top of the store.
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            accounts: HashMap::new(),
+            storage: HashMap::new(),
+            contracts: HashMap::new(),
+            cache_limit: DEFAULT_CACHE_LIMIT,
+            code_store: None,
+        }
+    }
+
+    /// Sets the store that code is looked up in before the key-value store.
+    ///
+    /// Code is still written to the key-value store on commit.
+    pub fn with_code_store(mut self, code_store: Arc<dyn CodeStore>) -> Self {
+        self.code_store = Some(code_store);
+        self
+    }
+
+    /// Sets the maximum number of cached accounts, storage slots and contracts.
+    pub fn with_cache_limit(mut self, cache_limit: usize) -> Self {
+        self.cache_limit = cache_limit;
+        self
+    }
+
+    /// Returns the store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns the store, consuming the database.
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Returns the number of cached entries.
+    pub fn cached_entries(&self) -> usize {
+        self.accounts.len() + self.storage.len() + self.contracts.len()
+    }
+
+    /// Clears the read cache.
+    pub fn clear_cache(&mut self) {
+        self.accounts.clear();
+        self.storage.clear();
+        self.contracts.clear();
+    }
+
+    /// Inserts the account info, and its code if it is set, keeping its storage.
+    pub fn insert_account_info(
+        &mut self,
+        address: Address,
+        mut info: AccountInfo,
+    ) -> Result<(), PersistentDBError<S::Error>> {
+        let mut batch = WriteBatch::default();
+        self.put_account(&mut batch, address, &mut info);
+        self.write(batch)
+    }
+
+    /// Inserts the value of the storage slot of the account.
+    pub fn insert_account_storage(
+        &mut self,
+        address: Address,
+        slot: U256,
+        value: U256,
+    ) -> Result<(), PersistentDBError<S::Error>> {
+        let mut batch = WriteBatch::default();
+        self.put_storage(&mut batch, address, slot, value);
+        self.write(batch)
+    }
+
+    /// Inserts the hash of the block.
+    pub fn insert_block_hash(
+        &mut self,
+        number: U256,
+        hash: B256,
+    ) -> Result<(), PersistentDBError<S::Error>> {
+        let mut batch = WriteBatch::default();
+        batch.put(block_hash_key(number), hash.to_vec());
+        self.write(batch)
+    }
+
+    /// Writes the changes to the store in a single batch.
+    ///
+    /// [DatabaseCommit::commit] panics on the errors returned by this function.
+    pub fn try_commit(
+        &mut self,
+        changes: HashMap<Address, Account>,
+    ) -> Result<(), PersistentDBError<S::Error>> {
+        let mut batch = WriteBatch::default();
+        for (address, mut account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                self.clear_storage(&mut batch, address)?;
+                batch.delete(account_key(address));
+                self.cache_insert_account(address, None);
+                continue;
+            }
+            if account.is_created() {
+                self.clear_storage(&mut batch, address)?;
+            }
+            self.put_account(&mut batch, address, &mut account.info);
+            for (slot, value) in account.storage {
+                self.put_storage(&mut batch, address, slot, value.present_value());
+            }
+        }
+        self.write(batch)
+    }
+
+    /// Returns the stored accounts, ordered by address.
+    pub fn accounts(
+        &self,
+    ) -> impl Iterator<Item = Result<(Address, AccountInfo), PersistentDBError<S::Error>>> + '_
+    {
+        self.store.iter_prefix(&[ACCOUNT_PREFIX]).map(|entry| {
+            let (key, value) = entry.map_err(PersistentDBError::Store)?;
+            let info = decode_account(&key, &value)?;
+            Ok((Address::from_slice(&key[1..]), info))
+        })
+    }
+
+    /// Returns the non-zero storage slots of the account, ordered by slot.
+    pub fn account_storage(
+        &self,
+        address: Address,
+    ) -> impl Iterator<Item = Result<(U256, U256), PersistentDBError<S::Error>>> + '_ {
+        let mut prefix = Vec::with_capacity(21);
+        prefix.push(STORAGE_PREFIX);
+        prefix.extend_from_slice(address.as_slice());
+        self.store.iter_prefix(&prefix).map(|entry| {
+            let (key, value) = entry.map_err(PersistentDBError::Store)?;
+            let value = decode_word(&key, &value)?;
+            Ok((U256::from_be_slice(&key[21..]), value))
+        })
+    }
+
+    /// Returns the stored code by its hash, ordered by hash.
+    pub fn contracts(
+        &self,
+    ) -> impl Iterator<Item = Result<(B256, Bytecode), PersistentDBError<S::Error>>> + '_ {
+        self.store.iter_prefix(&[CODE_PREFIX]).map(|entry| {
+            let (key, value) = entry.map_err(PersistentDBError::Store)?;
+            Ok((
+                B256::from_slice(&key[1..]),
+                Bytecode::new_raw(Bytes::from(value)),
+            ))
+        })
+    }
+
+    /// Adds the account and its code to the batch and the cache.
+    fn put_account(&mut self, batch: &mut WriteBatch, address: Address, info: &mut AccountInfo) {
+        if let Some(code) = info.code.take() {
+            if !code.is_empty() {
+                if info.code_hash == KECCAK_EMPTY {
+                    info.code_hash = code.hash_slow();
+                }
+                batch.put(code_key(info.code_hash), code.original_bytes().to_vec());
+                self.cache_insert_code(info.code_hash, code);
+            }
+        }
+        if info.code_hash == B256::ZERO {
+            info.code_hash = KECCAK_EMPTY;
+        }
+        batch.put(account_key(address), encode_account(info));
+        self.cache_insert_account(address, Some(info.clone()));
+    }
+
+    /// Adds the storage slot to the batch and the cache. Zero values are deleted.
+    fn put_storage(&mut self, batch: &mut WriteBatch, address: Address, slot: U256, value: U256) {
+        let key = storage_key(address, slot);
+        if value.is_zero() {
+            batch.delete(key);
+        } else {
+            batch.put(key, value.to_be_bytes_vec());
+        }
+        self.cache_insert_storage(address, slot, value);
+    }
+
+    /// Adds the deletion of every storage slot of the account to the batch.
+    fn clear_storage(
+        &mut self,
+        batch: &mut WriteBatch,
+        address: Address,
+    ) -> Result<(), PersistentDBError<S::Error>> {
+        let mut prefix = Vec::with_capacity(21);
+        prefix.push(STORAGE_PREFIX);
+        prefix.extend_from_slice(address.as_slice());
+        for entry in self.store.iter_prefix(&prefix) {
+            let (key, _) = entry.map_err(PersistentDBError::Store)?;
+            batch.delete(key);
+        }
+        self.storage.retain(|(cached, _), _| *cached != address);
+        Ok(())
+    }
+
+    fn write(&mut self, batch: WriteBatch) -> Result<(), PersistentDBError<S::Error>> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.store.write(batch).map_err(|err| {
+            // the cache already holds the values of the batch.
+            self.clear_cache();
+            PersistentDBError::Store(err)
+        })
+    }
+
+    /// Clears the cache if it is full.
+    fn make_room(&mut self) {
+        if self.cached_entries() >= self.cache_limit {
+            self.clear_cache();
+        }
+    }
+
+    fn cache_insert_account(&mut self, address: Address, info: Option<AccountInfo>) {
+        self.make_room();
+        self.accounts.insert(address, info);
+    }
+
+    fn cache_insert_storage(&mut self, address: Address, slot: U256, value: U256) {
+        self.make_room();
+        self.storage.insert((address, slot), value);
+    }
+
+    fn cache_insert_code(&mut self, code_hash: B256, code: Bytecode) {
+        self.make_room();
+        self.contracts.insert(code_hash, code);
+    }
+}
+
+impl<S: KeyValueStore> DatabaseCommit for PersistentDB<S>
+where
+    S::Error: fmt::Debug,
+{
+    /// # Panics
+    ///
+    /// Panics if the changes can't be written to the store, use
+    /// [PersistentDB::try_commit] to handle the error.
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.try_commit(changes)
+            .expect("failed to commit changes to the store");
+    }
+}
+
+impl<S: KeyValueStore> Database for PersistentDB<S> {
+    type Error = PersistentDBError<S::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(info) = self.accounts.get(&address) {
+            return Ok(info.clone());
+        }
+        let info = self.basic_ref(address)?;
+        self.cache_insert_account(address, info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(code) = self.contracts.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        let code = self.code_by_hash_ref(code_hash)?;
+        self.cache_insert_code(code_hash, code.clone());
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self.storage.get(&(address, index)) {
+            return Ok(*value);
+        }
+        let value = self.storage_ref(address, index)?;
+        self.cache_insert_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+impl<S: KeyValueStore> DatabaseRef for PersistentDB<S> {
+    type Error = PersistentDBError<S::Error>;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(info) = self.accounts.get(&address) {
+            return Ok(info.clone());
+        }
+        let key = account_key(address);
+        match self.store.get(&key).map_err(PersistentDBError::Store)? {
+            Some(value) => decode_account(&key, &value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        if let Some(code) = self.contracts.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        if let Some(code) = self
+            .code_store
+            .as_ref()
+            .and_then(|s| s.code_by_hash(code_hash))
+        {
+            return Ok(code);
+        }
+        let code = self
+            .store
+            .get(&code_key(code_hash))
+            .map_err(PersistentDBError::Store)?
+            .unwrap_or_default();
+        Ok(Bytecode::new_raw(Bytes::from(code)))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self.storage.get(&(address, index)) {
+            return Ok(*value);
+        }
+        let key = storage_key(address, index);
+        match self.store.get(&key).map_err(PersistentDBError::Store)? {
+            Some(value) => decode_word(&key, &value),
+            None => Ok(U256::ZERO),
+        }
+    }
+
+    /// Returns the inserted block hash, or zero if it was not inserted.
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        let key = block_hash_key(number);
+        match self.store.get(&key).map_err(PersistentDBError::Store)? {
+            Some(value) => decode_word(&key, &value).map(|hash| hash.to_be_bytes().into()),
+            None => Ok(B256::ZERO),
+        }
+    }
+}
+
+fn account_key(address: Address) -> Vec<u8> {
+    let mut key = Vec::with_capacity(21);
+    key.push(ACCOUNT_PREFIX);
+    key.extend_from_slice(address.as_slice());
+    key
+}
+
+fn storage_key(address: Address, slot: U256) -> Vec<u8> {
+    let mut key = Vec::with_capacity(53);
+    key.push(STORAGE_PREFIX);
+    key.extend_from_slice(address.as_slice());
+    key.extend_from_slice(&slot.to_be_bytes::<32>());
+    key
+}
+
+fn code_key(code_hash: B256) -> Vec<u8> {
+    let mut key = Vec::with_capacity(33);
+    key.push(CODE_PREFIX);
+    key.extend_from_slice(code_hash.as_slice());
+    key
+}
+
+fn block_hash_key(number: U256) -> Vec<u8> {
+    let mut key = Vec::with_capacity(33);
+    key.push(BLOCK_HASH_PREFIX);
+    key.extend_from_slice(&number.to_be_bytes::<32>());
+    key
+}
+
+fn encode_account(info: &AccountInfo) -> Vec<u8> {
+    let mut value = Vec::with_capacity(ACCOUNT_LEN);
+    value.extend_from_slice(&info.balance.to_be_bytes::<32>());
+    value.extend_from_slice(&info.nonce.to_be_bytes());
+    value.extend_from_slice(info.code_hash.as_slice());
+    value
+}
+
+fn decode_account<E>(key: &[u8], value: &[u8]) -> Result<AccountInfo, PersistentDBError<E>> {
+    if value.len() != ACCOUNT_LEN {
+        return Err(PersistentDBError::InvalidValue { key: key.to_vec() });
+    }
+    let mut nonce = [0; 8];
+    nonce.copy_from_slice(&value[32..40]);
+    Ok(AccountInfo {
+        balance: U256::from_be_slice(&value[..32]),
+        nonce: u64::from_be_bytes(nonce),
+        code_hash: B256::from_slice(&value[40..]),
+        code: None,
+    })
+}
+
+fn decode_word<E>(key: &[u8], value: &[u8]) -> Result<U256, PersistentDBError<E>> {
+    if value.len() != 32 {
+        return Err(PersistentDBError::InvalidValue { key: key.to_vec() });
+    }
+    Ok(U256::from_be_slice(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{address, AccountStatus, StorageSlot};
+
+    type MemoryDB = PersistentDB<BTreeMap<Vec<u8>, Vec<u8>>>;
+
+    const ACCOUNT: Address = address!("1000000000000000000000000000000000000001");
+
+    fn changed(info: AccountInfo, status: AccountStatus, storage: &[(u64, u64)]) -> Account {
+        Account {
+            info,
+            storage: storage
+                .iter()
+                .map(|(slot, value)| {
+                    let slot_value = StorageSlot::new_changed(U256::ZERO, U256::from(*value));
+                    (U256::from(*slot), slot_value)
+                })
+                .collect(),
+            status: status | AccountStatus::Touched,
+        }
+    }
+
+    fn commit_reopen(db: MemoryDB, account: Account) -> MemoryDB {
+        let mut db = db;
+        db.commit(HashMap::from([(ACCOUNT, account)]));
+        // reads go to the store instead of the cache.
+        MemoryDB::new(db.into_store())
+    }
+
+    #[test]
+    fn code_store() {
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x00]));
+        let code_hash = code.hash_slow();
+        let store = HashMap::from([(code_hash, code.clone())]);
+
+        let db = MemoryDB::new(BTreeMap::new()).with_code_store(Arc::new(store));
+        assert_eq!(db.code_by_hash_ref(code_hash).unwrap(), code);
+        assert!(db.store().is_empty());
+    }
+
+    #[test]
+    fn commit_and_read_through() {
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x00]));
+        let info = AccountInfo::new(U256::from(10), 1, KECCAK_EMPTY, code.clone());
+        let mut db = commit_reopen(
+            MemoryDB::new(BTreeMap::new()),
+            changed(info, AccountStatus::Created, &[(1, 2), (3, 4)]),
+        );
+
+        let info = db.basic(ACCOUNT).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(10));
+        assert_eq!(info.nonce, 1);
+        assert_eq!(info.code_hash, code.hash_slow());
+        assert_eq!(
+            db.code_by_hash(info.code_hash).unwrap().original_bytes(),
+            code.original_bytes()
+        );
+        assert_eq!(db.storage(ACCOUNT, U256::from(1)).unwrap(), U256::from(2));
+        assert_eq!(db.storage(ACCOUNT, U256::from(2)).unwrap(), U256::ZERO);
+        assert_eq!(db.cached_entries(), 4);
+
+        // zeroed slots are deleted, recreated accounts lose their storage.
+        let db = commit_reopen(
+            db,
+            changed(info.clone(), AccountStatus::default(), &[(1, 0)]),
+        );
+        let storage: Vec<_> = db.account_storage(ACCOUNT).map(Result::unwrap).collect();
+        assert_eq!(storage, [(U256::from(3), U256::from(4))]);
+        let mut db = commit_reopen(db, changed(info, AccountStatus::Created, &[(5, 6)]));
+        let storage: Vec<_> = db.account_storage(ACCOUNT).map(Result::unwrap).collect();
+        assert_eq!(storage, [(U256::from(5), U256::from(6))]);
+        assert_eq!(db.contracts().count(), 1);
+
+        let info = db.basic(ACCOUNT).unwrap().unwrap();
+        let mut db = commit_reopen(db, changed(info, AccountStatus::SelfDestructed, &[]));
+        assert_eq!(db.basic(ACCOUNT).unwrap(), None);
+        assert_eq!(db.storage(ACCOUNT, U256::from(5)).unwrap(), U256::ZERO);
+        assert_eq!(db.accounts().count(), 0);
+    }
+
+    #[test]
+    fn cache_limit() {
+        let mut db = MemoryDB::new(BTreeMap::new()).with_cache_limit(2);
+        for slot in 0..3 {
+            db.insert_account_storage(ACCOUNT, U256::from(slot), U256::from(slot + 1))
+                .unwrap();
+        }
+        assert_eq!(db.cached_entries(), 1);
+        for slot in 0..3 {
+            assert_eq!(
+                db.storage(ACCOUNT, U256::from(slot)).unwrap(),
+                U256::from(slot + 1)
+            );
+        }
+        assert!(db.cached_entries() <= 2);
+
+        let hash = B256::repeat_byte(1);
+        db.insert_block_hash(U256::from(7), hash).unwrap();
+        assert_eq!(db.block_hash(U256::from(7)).unwrap(), hash);
+        assert_eq!(db.block_hash(U256::from(8)).unwrap(), B256::ZERO);
+    }
+
+    #[test]
+    fn invalid_value() {
+        let mut store = BTreeMap::new();
+        store.insert(account_key(ACCOUNT), vec![1, 2, 3]);
+        let mut db = MemoryDB::new(store);
+        assert_eq!(
+            db.basic(ACCOUNT),
+            Err(PersistentDBError::InvalidValue {
+                key: account_key(ACCOUNT)
+            })
+        );
+    }
+}
@@ -0,0 +1,57 @@
+//! State overrides used to simulate transactions against modified state, the same as
+//! the state override set of `eth_call`.
+use crate::primitives::{Address, Bytes, HashMap, U256};
+
+/// Overrides of the accounts, keyed by address.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Override of a single account. Fields that are `None` are left unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
+pub struct AccountOverride {
+    /// Balance of the account.
+    pub balance: Option<U256>,
+    /// Nonce of the account.
+    pub nonce: Option<u64>,
+    /// Code of the account.
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage of the account, slots that are not set are zero.
+    pub state: Option<HashMap<U256, U256>>,
+    /// Changes the given storage slots, other slots are left unchanged.
+    ///
+    /// It is applied after [AccountOverride::state].
+    pub state_diff: Option<HashMap<U256, U256>>,
+}
+
+impl AccountOverride {
+    /// Sets the balance override.
+    pub fn with_balance(mut self, balance: U256) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    /// Sets the nonce override.
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Sets the code override.
+    pub fn with_code(mut self, code: Bytes) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Sets the storage that replaces the whole storage of the account.
+    pub fn with_state(mut self, state: HashMap<U256, U256>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Sets the storage slots that are changed.
+    pub fn with_state_diff(mut self, state_diff: HashMap<U256, U256>) -> Self {
+        self.state_diff = Some(state_diff);
+        self
+    }
+}
@@ -0,0 +1,568 @@
+//! Database wrapper that verifies account and storage reads against Merkle proofs.
+
+use crate::{
+    primitives::{
+        b256, keccak256, AccountInfo, Address, Bytecode, Bytes, HashMap, B256, KECCAK_EMPTY, U256,
+    },
+    Database,
+};
+use alloy_rlp::{Decodable, Header};
+use core::fmt;
+use std::vec::Vec;
+
+/// Root hash of an empty Merkle Patricia trie.
+pub const EMPTY_ROOT_HASH: B256 =
+    b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// Merkle proof of an account and its storage slots, as returned by `eth_getProof`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountProof {
+    /// Address of the account.
+    pub address: Address,
+    /// Trie nodes from the state root to the account leaf.
+    pub proof: Vec<Bytes>,
+    /// Proofs of the storage slots of the account.
+    pub storage_proofs: Vec<StorageProof>,
+}
+
+/// Merkle proof of a storage slot.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageProof {
+    /// Storage slot index.
+    pub key: U256,
+    /// Trie nodes from the storage root to the slot leaf.
+    pub proof: Vec<Bytes>,
+}
+
+/// Account as stored in the state trie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrieAccount {
+    /// Account nonce.
+    pub nonce: u64,
+    /// Account balance.
+    pub balance: U256,
+    /// Root of the account storage trie.
+    pub storage_root: B256,
+    /// Hash of the account code.
+    pub code_hash: B256,
+}
+
+impl Decodable for TrieAccount {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString);
+        }
+        Ok(Self {
+            nonce: u64::decode(buf)?,
+            balance: U256::decode(buf)?,
+            storage_root: B256::decode(buf)?,
+            code_hash: B256::decode(buf)?,
+        })
+    }
+}
+
+/// Error of the Merkle proof verification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// Node hash does not match the reference from its parent or the root.
+    HashMismatch,
+    /// Node is not a valid trie node.
+    InvalidNode,
+    /// Proof ended before the key was resolved.
+    IncompleteProof,
+}
+
+impl From<alloy_rlp::Error> for ProofError {
+    fn from(_: alloy_rlp::Error) -> Self {
+        Self::InvalidNode
+    }
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HashMismatch => f.write_str("trie node hash mismatch"),
+            Self::InvalidNode => f.write_str("invalid trie node"),
+            Self::IncompleteProof => f.write_str("incomplete proof"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProofError {}
+
+/// Error of the [VerifyingDatabase].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyingDatabaseError<E> {
+    /// Error of the wrapped database.
+    Database(E),
+    /// No proof was supplied for the account.
+    MissingAccountProof(Address),
+    /// No proof was supplied for the storage slot.
+    MissingStorageProof(Address, U256),
+    /// Supplied proof is invalid.
+    InvalidProof(ProofError),
+    /// Account returned by the database does not match the proof.
+    AccountMismatch(Address),
+    /// Storage value returned by the database does not match the proof.
+    StorageMismatch(Address, U256),
+    /// Code returned by the database does not match its hash.
+    CodeMismatch(B256),
+}
+
+impl<E> From<ProofError> for VerifyingDatabaseError<E> {
+    fn from(err: ProofError) -> Self {
+        Self::InvalidProof(err)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for VerifyingDatabaseError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Database(err) => write!(f, "database error: {err}"),
+            Self::MissingAccountProof(address) => write!(f, "missing proof of account {address}"),
+            Self::MissingStorageProof(address, index) => {
+                write!(
+                    f,
+                    "missing proof of storage slot {index} of account {address}"
+                )
+            }
+            Self::InvalidProof(err) => write!(f, "invalid proof: {err}"),
+            Self::AccountMismatch(address) => {
+                write!(f, "account {address} does not match the proof")
+            }
+            Self::StorageMismatch(address, index) => {
+                write!(
+                    f,
+                    "storage slot {index} of account {address} does not match the proof"
+                )
+            }
+            Self::CodeMismatch(hash) => write!(f, "code does not match its hash {hash}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for VerifyingDatabaseError<E> {}
+
+/// [Database] wrapper that verifies every account and storage read of the wrapped
+/// database against supplied Merkle proofs and a trusted state root.
+///
+/// It allows executing against an untrusted data source (e.g. a remote RPC provider):
+/// reads that do not match the proofs, or reads without a proof, result in an error.
+/// Code is verified against its hash, block hashes are not verified.
+#[derive(Clone, Debug)]
+pub struct VerifyingDatabase<DB> {
+    /// Wrapped database.
+    pub db: DB,
+    state_root: B256,
+    proofs: HashMap<Address, AccountProof>,
+    /// Accounts verified against the state root.
+    accounts: HashMap<Address, Option<TrieAccount>>,
+}
+
+impl<DB> VerifyingDatabase<DB> {
+    /// Creates new verifying database with the trusted state root.
+    pub fn new(db: DB, state_root: B256) -> Self {
+        Self {
+            db,
+            state_root,
+            proofs: HashMap::new(),
+            accounts: HashMap::new(),
+        }
+    }
+
+    /// Returns the trusted state root.
+    pub fn state_root(&self) -> B256 {
+        self.state_root
+    }
+
+    /// Adds proof of an account and its storage, replacing the previous one.
+    pub fn insert_proof(&mut self, proof: AccountProof) {
+        self.accounts.remove(&proof.address);
+        self.proofs.insert(proof.address, proof);
+    }
+
+    /// Returns the account proven by the supplied proof.
+    fn proven_account<E>(
+        &mut self,
+        address: Address,
+    ) -> Result<Option<TrieAccount>, VerifyingDatabaseError<E>> {
+        if let Some(account) = self.accounts.get(&address) {
+            return Ok(*account);
+        }
+        let proof = self
+            .proofs
+            .get(&address)
+            .ok_or(VerifyingDatabaseError::MissingAccountProof(address))?;
+        let account = verify_proof(self.state_root, keccak256(address), &proof.proof)?
+            .map(|value| TrieAccount::decode(&mut value.as_slice()))
+            .transpose()
+            .map_err(ProofError::from)?;
+        self.accounts.insert(address, account);
+        Ok(account)
+    }
+
+    /// Returns the storage value proven by the supplied proof.
+    fn proven_storage<E>(
+        &mut self,
+        address: Address,
+        index: U256,
+    ) -> Result<U256, VerifyingDatabaseError<E>> {
+        let Some(account) = self.proven_account(address)? else {
+            return Ok(U256::ZERO);
+        };
+        if account.storage_root == EMPTY_ROOT_HASH {
+            return Ok(U256::ZERO);
+        }
+        let proof = self.proofs[&address]
+            .storage_proofs
+            .iter()
+            .find(|proof| proof.key == index)
+            .ok_or(VerifyingDatabaseError::MissingStorageProof(address, index))?;
+        let key = keccak256(B256::from(index.to_be_bytes()));
+        let value = verify_proof(account.storage_root, key, &proof.proof)?
+            .map(|value| U256::decode(&mut value.as_slice()))
+            .transpose()
+            .map_err(ProofError::from)?;
+        Ok(value.unwrap_or_default())
+    }
+}
+
+impl<DB: Database> Database for VerifyingDatabase<DB> {
+    type Error = VerifyingDatabaseError<DB::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let proven = self.proven_account(address)?;
+        let info = self
+            .db
+            .basic(address)
+            .map_err(VerifyingDatabaseError::Database)?;
+
+        let matches = match (&proven, &info) {
+            (Some(proven), Some(info)) => {
+                proven.nonce == info.nonce
+                    && proven.balance == info.balance
+                    && proven.code_hash == info.code_hash
+            }
+            (None, None) => true,
+            // database can return empty account instead of none.
+            (None, Some(info)) => info.is_empty() && info.code_hash == KECCAK_EMPTY,
+            (Some(_), None) => false,
+        };
+        if !matches {
+            return Err(VerifyingDatabaseError::AccountMismatch(address));
+        }
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self
+            .db
+            .code_by_hash(code_hash)
+            .map_err(VerifyingDatabaseError::Database)?;
+        if code.hash_slow() != code_hash {
+            return Err(VerifyingDatabaseError::CodeMismatch(code_hash));
+        }
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let proven = self.proven_storage(address, index)?;
+        let value = self
+            .db
+            .storage(address, index)
+            .map_err(VerifyingDatabaseError::Database)?;
+        if proven != value {
+            return Err(VerifyingDatabaseError::StorageMismatch(address, index));
+        }
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.db
+            .block_hash(number)
+            .map_err(VerifyingDatabaseError::Database)
+    }
+}
+
+/// Verifies Merkle Patricia trie proof of the `key` against the `root`.
+///
+/// Returns the value stored at the key, or `None` if the proof shows that the key
+/// is not present in the trie.
+pub fn verify_proof(root: B256, key: B256, proof: &[Bytes]) -> Result<Option<Vec<u8>>, ProofError> {
+    if root == EMPTY_ROOT_HASH && proof.is_empty() {
+        return Ok(None);
+    }
+
+    let path: Vec<u8> = key
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect();
+    let mut path = path.as_slice();
+    let mut proof = proof.iter();
+
+    // reference to the next node, either hash or inlined node.
+    let mut child = ChildRef::Hash(root.as_slice());
+    loop {
+        let node = match child {
+            ChildRef::Hash(hash) => {
+                let node = proof.next().ok_or(ProofError::IncompleteProof)?;
+                if keccak256(node).as_slice() != hash {
+                    return Err(ProofError::HashMismatch);
+                }
+                node.as_ref()
+            }
+            ChildRef::Inline(node) => node,
+            ChildRef::Empty => return Ok(None),
+        };
+
+        let items = decode_list(node)?;
+        match items.len() {
+            // branch node
+            17 => {
+                let Some((nibble, rest)) = path.split_first() else {
+                    let value = decode_string(items[16])?;
+                    return Ok((!value.is_empty()).then(|| value.to_vec()));
+                };
+                child = ChildRef::decode(items[*nibble as usize])?;
+                path = rest;
+            }
+            // leaf or extension node
+            2 => {
+                let encoded_path = decode_string(items[0])?;
+                let (is_leaf, node_path) = decode_compact_path(encoded_path)?;
+                if is_leaf {
+                    if path != node_path.as_slice() {
+                        return Ok(None);
+                    }
+                    return Ok(Some(decode_string(items[1])?.to_vec()));
+                }
+                let Some(rest) = path.strip_prefix(node_path.as_slice()) else {
+                    return Ok(None);
+                };
+                child = ChildRef::decode(items[1])?;
+                path = rest;
+            }
+            _ => return Err(ProofError::InvalidNode),
+        }
+    }
+}
+
+/// Reference to a child node.
+enum ChildRef<'a> {
+    Empty,
+    Hash(&'a [u8]),
+    Inline(&'a [u8]),
+}
+
+impl<'a> ChildRef<'a> {
+    fn decode(item: &'a [u8]) -> Result<Self, ProofError> {
+        if Header::decode(&mut &item[..])?.list {
+            return Ok(Self::Inline(item));
+        }
+        match decode_string(item)? {
+            [] => Ok(Self::Empty),
+            hash if hash.len() == 32 => Ok(Self::Hash(hash)),
+            _ => Err(ProofError::InvalidNode),
+        }
+    }
+}
+
+/// Splits RLP list into raw encoded items.
+fn decode_list(mut buf: &[u8]) -> Result<Vec<&[u8]>, ProofError> {
+    let header = Header::decode(&mut buf)?;
+    if !header.list || header.payload_length != buf.len() {
+        return Err(ProofError::InvalidNode);
+    }
+    let mut items = Vec::with_capacity(17);
+    while !buf.is_empty() {
+        let start = buf;
+        let header = Header::decode(&mut buf)?;
+        let len = start.len() - buf.len() + header.payload_length;
+        if len > start.len() {
+            return Err(ProofError::InvalidNode);
+        }
+        items.push(&start[..len]);
+        buf = &start[len..];
+    }
+    Ok(items)
+}
+
+/// Returns payload of RLP string.
+fn decode_string(mut buf: &[u8]) -> Result<&[u8], ProofError> {
+    let header = Header::decode(&mut buf)?;
+    if header.list || header.payload_length != buf.len() {
+        return Err(ProofError::InvalidNode);
+    }
+    Ok(buf)
+}
+
+/// Decodes hex-prefix encoded path, returns if node is leaf and the path nibbles.
+fn decode_compact_path(encoded: &[u8]) -> Result<(bool, Vec<u8>), ProofError> {
+    let (first, rest) = encoded.split_first().ok_or(ProofError::InvalidNode)?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(ProofError::InvalidNode);
+    }
+    let mut path = Vec::with_capacity(rest.len() * 2 + 1);
+    // odd length paths have the first nibble in the prefix byte.
+    if flag & 1 == 1 {
+        path.push(first & 0x0f);
+    }
+    path.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+    Ok((flag & 2 == 2, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{CacheDB, EmptyDB};
+    use alloy_rlp::Encodable;
+
+    fn encode_list(items: &[&dyn Encodable]) -> Vec<u8> {
+        let payload_length = items.iter().map(|item| item.length()).sum();
+        let mut out = Vec::new();
+        Header {
+            list: true,
+            payload_length,
+        }
+        .encode(&mut out);
+        for item in items {
+            item.encode(&mut out);
+        }
+        out
+    }
+
+    /// Encodes leaf with the remaining path of the key starting at `nibble`.
+    fn leaf(key: B256, nibble: usize, value: &[u8]) -> Vec<u8> {
+        let nibbles: Vec<u8> = key
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0x0f])
+            .collect();
+        let rest = &nibbles[nibble..];
+        let mut path = Vec::new();
+        if rest.len() % 2 == 1 {
+            path.push(0x30 | rest[0]);
+            path.extend(rest[1..].chunks(2).map(|c| c[0] << 4 | c[1]));
+        } else {
+            path.push(0x20);
+            path.extend(rest.chunks(2).map(|c| c[0] << 4 | c[1]));
+        }
+        encode_list(&[&path.as_slice(), &value])
+    }
+
+    fn account_rlp(account: &TrieAccount) -> Vec<u8> {
+        encode_list(&[
+            &account.nonce,
+            &account.balance,
+            &account.storage_root,
+            &account.code_hash,
+        ])
+    }
+
+    #[test]
+    fn verify_branch_proof() {
+        // two keys that differ in the first nibble.
+        let key1 = B256::repeat_byte(0x11);
+        let key2 = B256::repeat_byte(0x22);
+        let leaf1 = leaf(key1, 1, &[0xaa; 40]);
+        let leaf2 = leaf(key2, 1, &[0xbb; 40]);
+        let empty: &[u8] = &[];
+        let mut children = vec![&empty as &dyn Encodable; 17];
+        let hash1 = keccak256(&leaf1);
+        let hash2 = keccak256(&leaf2);
+        children[1] = &hash1;
+        children[2] = &hash2;
+        let branch = encode_list(&children);
+        let root = keccak256(&branch);
+
+        let proof = [Bytes::from(branch.clone()), Bytes::from(leaf1.clone())];
+        assert_eq!(verify_proof(root, key1, &proof), Ok(Some(vec![0xaa; 40])));
+
+        // exclusion proof of a key under an empty branch child.
+        let key3 = B256::repeat_byte(0x33);
+        assert_eq!(verify_proof(root, key3, &proof[..1]), Ok(None));
+
+        // tampered leaf.
+        let proof = [Bytes::from(branch), Bytes::from(leaf(key1, 1, &[0xcc; 40]))];
+        assert_eq!(
+            verify_proof(root, key1, &proof),
+            Err(ProofError::HashMismatch)
+        );
+        assert_eq!(
+            verify_proof(root, key1, &proof[..1]),
+            Err(ProofError::IncompleteProof)
+        );
+    }
+
+    #[test]
+    fn verifying_database() {
+        let address = Address::with_last_byte(1);
+        let index = U256::from(7);
+        let value = U256::from(42);
+
+        let storage_leaf = leaf(keccak256(B256::from(index.to_be_bytes())), 0, &{
+            let mut out = Vec::new();
+            value.encode(&mut out);
+            out
+        });
+        let account = TrieAccount {
+            nonce: 1,
+            balance: U256::from(100),
+            storage_root: keccak256(&storage_leaf),
+            code_hash: KECCAK_EMPTY,
+        };
+        let account_leaf = leaf(keccak256(address), 0, &account_rlp(&account));
+        let state_root = keccak256(&account_leaf);
+
+        let mut cache = CacheDB::new(EmptyDB::default());
+        cache.insert_account_info(
+            address,
+            AccountInfo {
+                nonce: 1,
+                balance: U256::from(100),
+                ..Default::default()
+            },
+        );
+        cache.insert_account_storage(address, index, value).unwrap();
+
+        let mut db = VerifyingDatabase::new(cache, state_root);
+        assert_eq!(
+            db.basic(address),
+            Err(VerifyingDatabaseError::MissingAccountProof(address))
+        );
+        db.insert_proof(AccountProof {
+            address,
+            proof: vec![account_leaf.into()],
+            storage_proofs: vec![StorageProof {
+                key: index,
+                proof: vec![storage_leaf.into()],
+            }],
+        });
+        assert_eq!(db.basic(address).unwrap().unwrap().balance, U256::from(100));
+        assert_eq!(db.storage(address, index), Ok(value));
+        assert_eq!(
+            db.storage(address, U256::from(8)),
+            Err(VerifyingDatabaseError::MissingStorageProof(
+                address,
+                U256::from(8)
+            ))
+        );
+
+        // untrusted database returns a different value.
+        db.db
+            .insert_account_storage(address, index, U256::from(43))
+            .unwrap();
+        assert_eq!(
+            db.storage(address, index),
+            Err(VerifyingDatabaseError::StorageMismatch(address, index))
+        );
+        db.db.accounts.get_mut(&address).unwrap().info.balance = U256::from(1);
+        assert_eq!(
+            db.basic(address),
+            Err(VerifyingDatabaseError::AccountMismatch(address))
+        );
+    }
+}
@@ -0,0 +1,463 @@
+//! Recording of the state accessed during execution into a witness, and stateless
+//! execution against it.
+
+use crate::{
+    primitives::{
+        Account, AccountInfo, Address, Bytecode, EVMError, HashMap, ResultAndState, B256,
+        KECCAK_EMPTY, U256,
+    },
+    Database, DatabaseCommit, DatabaseRef, Evm,
+};
+use core::{cell::RefCell, fmt, mem};
+
+/// State read from the database during execution.
+///
+/// Contains every requested account, storage slot, code and block hash together with
+/// the value returned by the database, so it can serve the same execution without the
+/// database.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Witness {
+    /// Accounts without their code, `None` if the account does not exist.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Storage slots of the accounts.
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    /// Code by its hash.
+    pub contracts: HashMap<B256, Bytecode>,
+    /// Block hashes by block number.
+    pub block_hashes: HashMap<U256, B256>,
+}
+
+impl Witness {
+    /// Returns `true` if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+            && self.storage.is_empty()
+            && self.contracts.is_empty()
+            && self.block_hashes.is_empty()
+    }
+
+    /// Adds entries of the other witness, e.g. of the next transaction in the block.
+    /// Existing entries are kept, as they hold the value read first.
+    pub fn extend(&mut self, other: Witness) {
+        for (address, account) in other.accounts {
+            self.accounts.entry(address).or_insert(account);
+        }
+        for (address, slots) in other.storage {
+            let storage = self.storage.entry(address).or_default();
+            for (index, value) in slots {
+                storage.entry(index).or_insert(value);
+            }
+        }
+        for (hash, code) in other.contracts {
+            self.contracts.entry(hash).or_insert(code);
+        }
+        for (number, hash) in other.block_hashes {
+            self.block_hashes.entry(number).or_insert(hash);
+        }
+    }
+
+    fn record_account(&mut self, address: Address, account: &Option<AccountInfo>) {
+        let account = account.clone().map(|mut info| {
+            // code loaded together with the account is not requested by hash, so
+            // it is recorded here.
+            if let Some(code) = info.code.take() {
+                if info.code_hash != KECCAK_EMPTY {
+                    self.contracts.entry(info.code_hash).or_insert(code);
+                }
+            }
+            info
+        });
+        self.accounts.entry(address).or_insert(account);
+    }
+
+    fn record_storage(&mut self, address: Address, index: U256, value: U256) {
+        self.storage
+            .entry(address)
+            .or_default()
+            .entry(index)
+            .or_insert(value);
+    }
+
+    fn record_code(&mut self, code_hash: B256, code: &Bytecode) {
+        self.contracts
+            .entry(code_hash)
+            .or_insert_with(|| code.clone());
+    }
+
+    fn record_block_hash(&mut self, number: U256, hash: B256) {
+        self.block_hashes.entry(number).or_insert(hash);
+    }
+}
+
+/// Database wrapper that records every read of the wrapped database into a [Witness].
+///
+/// Values are recorded as returned by the database, before any change made by the
+/// execution. Both [Database] and [DatabaseRef] reads are recorded.
+#[derive(Clone, Debug, Default)]
+pub struct WitnessRecorder<DB> {
+    /// Wrapped database.
+    pub db: DB,
+    witness: RefCell<Witness>,
+}
+
+impl<DB> WitnessRecorder<DB> {
+    /// Creates new recorder wrapping the database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: RefCell::default(),
+        }
+    }
+
+    /// Returns the recorded witness and starts a new one.
+    pub fn take_witness(&mut self) -> Witness {
+        mem::take(self.witness.get_mut())
+    }
+
+    /// Returns the recorded witness.
+    pub fn witness(&mut self) -> &Witness {
+        self.witness.get_mut()
+    }
+
+    /// Returns the wrapped database and the recorded witness.
+    pub fn into_parts(self) -> (DB, Witness) {
+        (self.db, self.witness.into_inner())
+    }
+}
+
+impl<DB: Database> Database for WitnessRecorder<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let account = self.db.basic(address)?;
+        self.witness.get_mut().record_account(address, &account);
+        Ok(account)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.witness.get_mut().record_code(code_hash, &code);
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.witness.get_mut().record_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.witness.get_mut().record_block_hash(number, hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for WitnessRecorder<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let account = self.db.basic_ref(address)?;
+        self.witness.borrow_mut().record_account(address, &account);
+        Ok(account)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        self.witness.borrow_mut().record_code(code_hash, &code);
+        Ok(code)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage_ref(address, index)?;
+        self.witness
+            .borrow_mut()
+            .record_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash_ref(number)?;
+        self.witness.borrow_mut().record_block_hash(number, hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for WitnessRecorder<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes);
+    }
+}
+
+impl<EXT, DB: Database> Evm<'_, EXT, WitnessRecorder<DB>> {
+    /// Executes the transaction without committing it and returns the witness of the
+    /// state it read.
+    pub fn transact_with_witness(
+        &mut self,
+    ) -> Result<(ResultAndState, Witness), EVMError<DB::Error>> {
+        self.db_mut().take_witness();
+        let result = self.transact()?;
+        Ok((result, self.db_mut().take_witness()))
+    }
+}
+
+/// Error of the [WitnessDB], returned when the witness does not contain the requested
+/// data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessDBError {
+    /// Account is not in the witness.
+    MissingAccount(Address),
+    /// Storage slot is not in the witness.
+    MissingStorage(Address, U256),
+    /// Code is not in the witness.
+    MissingCode(B256),
+    /// Block hash is not in the witness.
+    MissingBlockHash(U256),
+}
+
+impl fmt::Display for WitnessDBError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAccount(address) => write!(f, "account {address} is not in the witness"),
+            Self::MissingStorage(address, index) => write!(
+                f,
+                "storage slot {index} of account {address} is not in the witness"
+            ),
+            Self::MissingCode(hash) => write!(f, "code {hash} is not in the witness"),
+            Self::MissingBlockHash(number) => {
+                write!(f, "hash of block {number} is not in the witness")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WitnessDBError {}
+
+/// Database that serves reads from a [Witness], for stateless execution.
+///
+/// Reads of data that is not in the witness fail with [WitnessDBError], so execution
+/// can't silently diverge from the one the witness was recorded for. Storage of accounts
+/// that don't exist is empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WitnessDB {
+    witness: Witness,
+}
+
+impl WitnessDB {
+    /// Creates new database serving the witness.
+    pub fn new(witness: Witness) -> Self {
+        Self { witness }
+    }
+
+    /// Returns the witness.
+    pub fn witness(&self) -> &Witness {
+        &self.witness
+    }
+
+    /// Returns the witness, consuming the database.
+    pub fn into_witness(self) -> Witness {
+        self.witness
+    }
+}
+
+impl DatabaseRef for WitnessDB {
+    type Error = WitnessDBError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.witness
+            .accounts
+            .get(&address)
+            .cloned()
+            .ok_or(WitnessDBError::MissingAccount(address))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        self.witness
+            .contracts
+            .get(&code_hash)
+            .cloned()
+            .ok_or(WitnessDBError::MissingCode(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self
+            .witness
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index))
+        {
+            return Ok(*value);
+        }
+        match self.witness.accounts.get(&address) {
+            Some(None) => Ok(U256::ZERO),
+            _ => Err(WitnessDBError::MissingStorage(address, index)),
+        }
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.witness
+            .block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(WitnessDBError::MissingBlockHash(number))
+    }
+}
+
+impl Database for WitnessDB {
+    type Error = WitnessDBError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{address, SpecId, TransactTo},
+    };
+
+    #[test]
+    fn record_witness() {
+        let caller = address!("1000000000000000000000000000000000000000");
+        let contract = address!("2000000000000000000000000000000000000000");
+        // SLOAD(1) BALANCE(0x99) BLOCKHASH(0)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::SLOAD,
+                opcode::PUSH1,
+                0x99,
+                opcode::BALANCE,
+                opcode::PUSH1,
+                0x00,
+                opcode::BLOCKHASH,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let code_hash = code.hash_slow();
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        db.insert_account_info(contract, AccountInfo::new(U256::ZERO, 1, code_hash, code));
+        db.insert_account_storage(contract, U256::from(1), U256::from(5))
+            .unwrap();
+
+        // from Prague BLOCKHASH is served from the history contract.
+        let mut evm = Evm::builder()
+            .with_db(WitnessRecorder::new(db))
+            .with_spec_id(SpecId::CANCUN)
+            .modify_block_env(|block| block.number = U256::from(1))
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+
+        let (result, witness) = evm.transact_with_witness().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            witness.accounts[&address!("0000000000000000000000000000000000000099")],
+            None
+        );
+        assert_eq!(witness.accounts[&contract].as_ref().unwrap().nonce, 1);
+        assert!(witness.accounts[&contract].as_ref().unwrap().code.is_none());
+        assert!(witness.contracts.contains_key(&code_hash));
+        assert_eq!(witness.storage[&contract][&U256::from(1)], U256::from(5));
+        assert!(witness.block_hashes.contains_key(&U256::ZERO));
+        assert!(witness.accounts.contains_key(&caller));
+
+        // witness is recorded per transaction.
+        assert!(evm.db_mut().witness().is_empty());
+    }
+
+    #[test]
+    fn stateless_execution() {
+        let caller = address!("1000000000000000000000000000000000000000");
+        let contract = address!("2000000000000000000000000000000000000000");
+        // SSTORE(0, SLOAD(0) + 1)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x00,
+                opcode::SLOAD,
+                opcode::PUSH1,
+                0x01,
+                opcode::ADD,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(contract, U256::ZERO, U256::from(5))
+            .unwrap();
+
+        let mut evm = Evm::builder()
+            .with_db(WitnessRecorder::new(db))
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        let (expected, mut witness) = evm.transact_with_witness().unwrap();
+
+        let mut evm = evm
+            .modify()
+            .reset_handler_with_db(WitnessDB::new(witness.clone()))
+            .build();
+        let result = evm.transact().unwrap();
+        assert_eq!(result.result, expected.result);
+        assert_eq!(
+            result.state[&contract].storage[&U256::ZERO].present_value,
+            U256::from(6)
+        );
+
+        // execution that reads state outside of the witness fails.
+        witness.storage.clear();
+        let mut evm = evm
+            .modify()
+            .reset_handler_with_db(WitnessDB::new(witness))
+            .build();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Database(WitnessDBError::MissingStorage(contract, U256::ZERO))
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    primitives::{
+        address, bytes, create2_address, db::Database, Account, AccountInfo, Address, Bytecode,
+        Bytes, EVMError, ExecutionResult, HashMap, Output, TransactTo, B256, KECCAK_EMPTY, U256,
+    },
+    DatabaseCommit, Evm,
+};
+
+/// Address of the deterministic deployment proxy, deployed at the same address on most
+/// chains. It is used by [Evm::deploy_contract] for `CREATE2` deployments.
+pub const CREATE2_DEPLOYER: Address = address!("4e59b44847b379578588920ca78fbf26c0b4956c");
+
+/// Runtime code of [CREATE2_DEPLOYER].
+///
+/// Calldata is a 32 byte salt followed by the init code. The contract is created with
+/// `CREATE2` and its 20 byte address is returned.
+pub const CREATE2_DEPLOYER_CODE: Bytes = bytes!("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf3");
+
+/// Outcome of [Evm::deploy_contract].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Deployment {
+    /// Contract was deployed.
+    Success {
+        /// Address of the contract.
+        address: Address,
+        /// Runtime code of the contract.
+        code: Bytes,
+        /// Result of the deployment transaction.
+        result: ExecutionResult,
+    },
+    /// Deployment transaction reverted or halted.
+    Failure(ExecutionResult),
+}
+
+impl Deployment {
+    /// Returns the address of the contract if it was deployed.
+    pub fn address(&self) -> Option<Address> {
+        match self {
+            Self::Success { address, .. } => Some(*address),
+            Self::Failure(_) => None,
+        }
+    }
+}
+
+impl<EXT, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
+    /// Deploys the contract from the caller of the transaction and commits the state.
+    ///
+    /// Without `salt`, the init code is executed by a create transaction and the contract
+    /// is created at [create_address](crate::primitives::create_address) of the caller. With
+    /// `salt`, [CREATE2_DEPLOYER] is called and the contract is created at
+    /// [create2_address] of the deployer. Deployer code is inserted if the account has no
+    /// code.
+    ///
+    /// Other fields of the transaction, like gas limit and gas price, are used as set,
+    /// except the nonce check that is skipped. Transaction is restored afterwards.
+    pub fn deploy_contract(
+        &mut self,
+        init_code: Bytes,
+        salt: Option<B256>,
+    ) -> Result<Deployment, EVMError<DB::Error>> {
+        let tx = self.tx().clone();
+        let deployment = self.deploy_contract_inner(init_code, salt);
+        *self.tx_mut() = tx;
+        deployment
+    }
+
+    fn deploy_contract_inner(
+        &mut self,
+        init_code: Bytes,
+        salt: Option<B256>,
+    ) -> Result<Deployment, EVMError<DB::Error>> {
+        let create2_address = match salt {
+            Some(salt) => {
+                self.insert_create2_deployer()?;
+                let address = create2_address(CREATE2_DEPLOYER, salt, &init_code);
+                let tx = self.tx_mut();
+                tx.transact_to = TransactTo::Call(CREATE2_DEPLOYER);
+                tx.data = [salt.as_slice(), &init_code].concat().into();
+                Some(address)
+            }
+            None => {
+                let tx = self.tx_mut();
+                tx.transact_to = TransactTo::Create;
+                tx.data = init_code;
+                None
+            }
+        };
+        let tx = self.tx_mut();
+        tx.value = U256::ZERO;
+        tx.nonce = None;
+
+        let result_and_state = self.transact()?;
+        let address = match (&result_and_state.result, create2_address) {
+            (ExecutionResult::Success { .. }, Some(address)) => address,
+            (
+                ExecutionResult::Success {
+                    output: Output::Create(_, Some(address)),
+                    ..
+                },
+                None,
+            ) => *address,
+            _ => return Ok(Deployment::Failure(result_and_state.result)),
+        };
+        let code = result_and_state
+            .state
+            .get(&address)
+            .and_then(|account| account.info.code.as_ref())
+            .map(Bytecode::original_bytes)
+            .unwrap_or_default();
+        self.db_mut().commit(result_and_state.state);
+        Ok(Deployment::Success {
+            address,
+            code,
+            result: result_and_state.result,
+        })
+    }
+
+    /// Inserts the code of [CREATE2_DEPLOYER] if the account has no code.
+    fn insert_create2_deployer(&mut self) -> Result<(), EVMError<DB::Error>> {
+        let info = self
+            .db_mut()
+            .basic(CREATE2_DEPLOYER)
+            .map_err(EVMError::Database)?
+            .unwrap_or_default();
+        if info.code_hash != KECCAK_EMPTY {
+            return Ok(());
+        }
+        let code = Bytecode::new_raw(CREATE2_DEPLOYER_CODE);
+        let mut account = Account::from(AccountInfo {
+            code_hash: code.hash_slow(),
+            code: Some(code),
+            ..info
+        });
+        account.mark_touch();
+        self.db_mut()
+            .commit(HashMap::from_iter([(CREATE2_DEPLOYER, account)]));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::create_address,
+    };
+
+    #[test]
+    fn deploy_contract() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+        // returns one byte of runtime code, ADDRESS.
+        let init_code = Bytes::from(
+            [
+                opcode::PUSH1,
+                opcode::ADDRESS,
+                opcode::PUSH1,
+                0x00,
+                opcode::MSTORE8,
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::RETURN,
+            ]
+            .to_vec(),
+        );
+
+        let deployment = evm.deploy_contract(init_code.clone(), None).unwrap();
+        assert_eq!(deployment.address(), Some(create_address(caller, 0)));
+        let Deployment::Success { code, .. } = deployment else {
+            panic!("deployment failed");
+        };
+        assert_eq!(code, Bytes::from_static(&[opcode::ADDRESS]));
+        assert_eq!(evm.db().accounts[&caller].info.nonce, 1);
+
+        let salt = B256::with_last_byte(1);
+        let deployment = evm.deploy_contract(init_code.clone(), Some(salt)).unwrap();
+        let address = create2_address(CREATE2_DEPLOYER, salt, &init_code);
+        assert_eq!(deployment.address(), Some(address));
+        assert_eq!(
+            evm.db().accounts[&address]
+                .info
+                .code
+                .as_ref()
+                .map(Bytecode::original_bytes),
+            Some(Bytes::from_static(&[opcode::ADDRESS]))
+        );
+
+        // address is already used.
+        let deployment = evm.deploy_contract(init_code, Some(salt)).unwrap();
+        assert!(matches!(deployment, Deployment::Failure(_)));
+        assert_eq!(evm.tx().transact_to, TransactTo::Call(Address::ZERO));
+    }
+}
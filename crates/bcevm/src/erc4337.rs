@@ -0,0 +1,619 @@
+//! ERC-4337 (account abstraction) helpers for bundlers.
+//!
+//! [UserOperationInspector] is attached to the simulation of a bundle, e.g. a
+//! `handleOps` or `simulateValidation` call to the EntryPoint. It checks the ERC-7562
+//! validation rules in the validation phase of every user operation and aggregates the gas
+//! used by the entities of every user operation.
+//!
+//! Frames are attributed to the user operations by the calls of the EntryPoint: a call to
+//! a sender starts the user operation of that sender, the following calls to its paymaster
+//! belong to the same user operation, and a call to a factory from outside of an entity
+//! frame belongs to the next user operation deployed by that factory.
+
+use crate::{
+    interpreter::{opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter},
+    primitives::{db::Database, Address, U256},
+    EvmContext, Inspector,
+};
+use std::{vec, vec::Vec};
+
+/// Selector of `validateUserOp` of EntryPoint v0.6 accounts.
+pub const VALIDATE_USER_OP_V06: [u8; 4] = [0x3a, 0x87, 0x1c, 0xdd];
+/// Selector of `validateUserOp` of EntryPoint v0.7 accounts.
+pub const VALIDATE_USER_OP_V07: [u8; 4] = [0x19, 0x82, 0x2f, 0x7c];
+/// Selector of `validatePaymasterUserOp` of EntryPoint v0.6 paymasters.
+pub const VALIDATE_PAYMASTER_USER_OP_V06: [u8; 4] = [0xf4, 0x65, 0xc7, 0x7e];
+/// Selector of `validatePaymasterUserOp` of EntryPoint v0.7 paymasters.
+pub const VALIDATE_PAYMASTER_USER_OP_V07: [u8; 4] = [0x52, 0xb7, 0x51, 0x2c];
+
+/// Opcodes that entities can not use in the validation phase (OP-011).
+///
+/// `GAS` is allowed only if it is followed by a call (OP-012) and `CREATE2` only once by
+/// the factory (OP-031), they are checked separately.
+pub const BANNED_OPCODES: [u8; 16] = [
+    opcode::GASPRICE,
+    opcode::GASLIMIT,
+    opcode::DIFFICULTY,
+    opcode::TIMESTAMP,
+    opcode::BASEFEE,
+    opcode::BLOCKHASH,
+    opcode::NUMBER,
+    opcode::SELFBALANCE,
+    opcode::BALANCE,
+    opcode::ORIGIN,
+    opcode::CREATE,
+    opcode::COINBASE,
+    opcode::SELFDESTRUCT,
+    opcode::BLOBHASH,
+    opcode::BLOBBASEFEE,
+    opcode::INVALID,
+];
+
+/// Number of slots after a hash that are associated with its preimage, e.g. the fields of
+/// a struct stored in a mapping.
+const ASSOCIATED_SLOTS: u64 = 128;
+
+/// Entities of a user operation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct UserOperationEntities {
+    /// Account of the user operation.
+    pub sender: Address,
+    /// Factory that deploys the sender, if it is not deployed yet.
+    pub factory: Option<Address>,
+    /// Paymaster that pays for the user operation.
+    pub paymaster: Option<Address>,
+}
+
+/// Kind of an entity of a user operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Sender,
+    Factory,
+    Paymaster,
+}
+
+/// Phase of a user operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Deployment of the sender and validation by the sender and the paymaster.
+    Validation,
+    /// Execution of the call data and the `postOp` of the paymaster.
+    Execution,
+}
+
+/// Violated validation rule.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+    /// Banned opcode was executed, see [BANNED_OPCODES].
+    BannedOpcode(u8),
+    /// `GAS` was not followed by a call.
+    GasNotFollowedByCall,
+    /// `CREATE2` was executed by an entity other than the factory, or more than once.
+    UnexpectedCreate2,
+    /// Storage slot that is not associated with the sender, or with a staked entity, was
+    /// accessed. Includes transient storage.
+    StorageAccess { address: Address, slot: U256 },
+}
+
+/// Validation rule violation of an entity.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RuleViolation {
+    /// Index of the user operation.
+    pub user_op: usize,
+    /// Entity whose validation violated the rule.
+    pub entity: EntityKind,
+    /// Address of the account whose code is executed.
+    pub address: Address,
+    /// Program counter of the opcode.
+    pub pc: usize,
+    /// Violated rule.
+    pub kind: ViolationKind,
+}
+
+/// Gas used by the entities of a user operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct UserOperationGas {
+    /// Gas used by the deployment and the validation of the sender and the paymaster.
+    pub validation: u64,
+    /// Gas used by the execution and the `postOp` of the paymaster.
+    pub execution: u64,
+}
+
+impl UserOperationGas {
+    /// Returns the total gas used.
+    pub const fn total(&self) -> u64 {
+        self.validation + self.execution
+    }
+}
+
+/// Attribution of a frame.
+#[derive(Clone, Copy, Debug)]
+struct FrameScope {
+    /// User operation and entity whose code is executed.
+    entity: Option<(usize, EntityKind)>,
+    phase: Phase,
+    /// Whether the gas used by the frame is added to the user operation.
+    charged: bool,
+}
+
+/// [Inspector] that checks the ERC-7562 validation rules of the entities of a bundle and
+/// aggregates the gas used by every user operation.
+#[derive(Clone, Debug)]
+pub struct UserOperationInspector {
+    entry_point: Address,
+    user_ops: Vec<UserOperationEntities>,
+    staked: Vec<Address>,
+    frames: Vec<FrameScope>,
+    current: Option<usize>,
+    /// Whether the factory of the user operation already used `CREATE2`.
+    create2: Vec<bool>,
+    /// Address that is the first word of a hashed preimage and its hash.
+    hashes: Vec<(Address, U256)>,
+    pending_hash: Option<Address>,
+    violations: Vec<RuleViolation>,
+    gas: Vec<UserOperationGas>,
+}
+
+impl UserOperationInspector {
+    /// Creates a new inspector for the user operations of the bundle, in bundle order.
+    pub fn new(entry_point: Address, user_ops: Vec<UserOperationEntities>) -> Self {
+        let len = user_ops.len();
+        Self {
+            entry_point,
+            user_ops,
+            staked: Vec::new(),
+            frames: Vec::new(),
+            current: None,
+            create2: vec![false; len],
+            hashes: Vec::new(),
+            pending_hash: None,
+            violations: Vec::new(),
+            gas: vec![UserOperationGas::default(); len],
+        }
+    }
+
+    /// Sets the staked entities. Staked entities can access their own storage.
+    pub fn with_staked(mut self, staked: impl IntoIterator<Item = Address>) -> Self {
+        self.staked = staked.into_iter().collect();
+        self
+    }
+
+    /// Returns the user operations of the bundle.
+    pub fn user_ops(&self) -> &[UserOperationEntities] {
+        &self.user_ops
+    }
+
+    /// Returns the rule violations, in execution order.
+    pub fn violations(&self) -> &[RuleViolation] {
+        &self.violations
+    }
+
+    /// Returns the gas used by every user operation.
+    pub fn gas(&self) -> &[UserOperationGas] {
+        &self.gas
+    }
+
+    /// Clears the violations and the gas, so the inspector can be reused for another
+    /// simulation of the same bundle.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.current = None;
+        self.create2.fill(false);
+        self.hashes.clear();
+        self.pending_hash = None;
+        self.violations.clear();
+        self.gas.fill(UserOperationGas::default());
+    }
+
+    /// Returns the address of the entity of the user operation.
+    fn entity_address(&self, user_op: usize, kind: EntityKind) -> Option<Address> {
+        let entities = &self.user_ops[user_op];
+        match kind {
+            EntityKind::Sender => Some(entities.sender),
+            EntityKind::Factory => entities.factory,
+            EntityKind::Paymaster => entities.paymaster,
+        }
+    }
+
+    /// Returns whether the slot is the address or in the slots after a hash of a preimage
+    /// that starts with the address.
+    fn is_associated(&self, owner: Address, slot: U256) -> bool {
+        if slot == U256::from_be_slice(owner.as_slice()) {
+            return true;
+        }
+        self.hashes.iter().any(|(address, hash)| {
+            *address == owner
+                && slot
+                    .checked_sub(*hash)
+                    .is_some_and(|offset| offset < U256::from(ASSOCIATED_SLOTS))
+        })
+    }
+
+    /// Returns the scope of a frame called by `caller`.
+    fn call_scope(&mut self, caller: Address, target: Address, input: &[u8]) -> FrameScope {
+        let parent = self.frames.last().copied();
+        let selector = input.get(..4);
+        if caller == self.entry_point {
+            if let Some(user_op) = self.user_ops.iter().position(|op| op.sender == target) {
+                self.current = Some(user_op);
+                let validation = [VALIDATE_USER_OP_V06, VALIDATE_USER_OP_V07];
+                return FrameScope {
+                    entity: Some((user_op, EntityKind::Sender)),
+                    phase: phase(selector, &validation),
+                    charged: true,
+                };
+            }
+            if let Some(user_op) = self
+                .current
+                .filter(|user_op| self.user_ops[*user_op].paymaster == Some(target))
+            {
+                let validation = [
+                    VALIDATE_PAYMASTER_USER_OP_V06,
+                    VALIDATE_PAYMASTER_USER_OP_V07,
+                ];
+                return FrameScope {
+                    entity: Some((user_op, EntityKind::Paymaster)),
+                    phase: phase(selector, &validation),
+                    charged: true,
+                };
+            }
+        }
+        if parent.and_then(|parent| parent.entity).is_none() {
+            let next = self.current.map_or(0, |user_op| user_op + 1);
+            if let Some(user_op) = (next..self.user_ops.len())
+                .find(|user_op| self.user_ops[*user_op].factory == Some(target))
+            {
+                return FrameScope {
+                    entity: Some((user_op, EntityKind::Factory)),
+                    phase: Phase::Validation,
+                    charged: true,
+                };
+            }
+        }
+        match parent {
+            Some(parent) if target != self.entry_point => FrameScope {
+                charged: false,
+                ..parent
+            },
+            _ => FrameScope {
+                entity: None,
+                phase: Phase::Execution,
+                charged: false,
+            },
+        }
+    }
+
+    /// Returns the violated rule of the opcode that is about to be executed, if any.
+    fn check(
+        &mut self,
+        interp: &Interpreter,
+        user_op: usize,
+        entity: EntityKind,
+    ) -> Option<ViolationKind> {
+        let op = interp.current_opcode();
+        if BANNED_OPCODES.contains(&op) {
+            return Some(ViolationKind::BannedOpcode(op));
+        }
+        match op {
+            opcode::GAS => {
+                let next = interp.bytecode.get(interp.program_counter() + 1).copied();
+                let is_call = matches!(
+                    next,
+                    Some(
+                        opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL
+                    )
+                );
+                (!is_call).then_some(ViolationKind::GasNotFollowedByCall)
+            }
+            opcode::CREATE2 => {
+                if entity == EntityKind::Factory && !self.create2[user_op] {
+                    self.create2[user_op] = true;
+                    return None;
+                }
+                Some(ViolationKind::UnexpectedCreate2)
+            }
+            opcode::SLOAD | opcode::SSTORE | opcode::TLOAD | opcode::TSTORE => {
+                let slot = interp.stack.peek(0).ok()?;
+                let address = interp.contract.target_address;
+                let sender = self.user_ops[user_op].sender;
+                if address == sender || self.is_associated(sender, slot) {
+                    return None;
+                }
+                let entity_address = self.entity_address(user_op, entity)?;
+                if self.staked.contains(&entity_address)
+                    && (address == entity_address || self.is_associated(entity_address, slot))
+                {
+                    return None;
+                }
+                Some(ViolationKind::StorageAccess { address, slot })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the phase of a call with the selector.
+fn phase(selector: Option<&[u8]>, validation: &[[u8; 4]]) -> Phase {
+    match selector {
+        Some(selector) if validation.iter().any(|v| v == selector) => Phase::Validation,
+        _ => Phase::Execution,
+    }
+}
+
+impl<DB: Database> Inspector<DB> for UserOperationInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        let Some(FrameScope {
+            entity: Some((user_op, entity)),
+            phase: Phase::Validation,
+            ..
+        }) = self.frames.last().copied()
+        else {
+            return;
+        };
+
+        if interp.current_opcode() == opcode::KECCAK256 {
+            // the preimage starts with the left padded address, e.g. a mapping key.
+            let offset = interp
+                .stack
+                .peek(0)
+                .ok()
+                .map(|v| v.saturating_to::<usize>());
+            let len = interp
+                .stack
+                .peek(1)
+                .ok()
+                .map(|v| v.saturating_to::<usize>());
+            if let (Some(offset), Some(len)) = (offset, len) {
+                if len >= 32 && offset.saturating_add(32) <= interp.shared_memory.len() {
+                    let word = interp.shared_memory.slice(offset, 32);
+                    if word[..12].iter().all(|b| *b == 0) {
+                        self.pending_hash = Some(Address::from_slice(&word[12..]));
+                    }
+                }
+            }
+        }
+
+        if let Some(kind) = self.check(interp, user_op, entity) {
+            self.violations.push(RuleViolation {
+                user_op,
+                entity,
+                address: interp.contract.target_address,
+                pc: interp.program_counter(),
+                kind,
+            });
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        if let Some(address) = self.pending_hash.take() {
+            if let Ok(hash) = interp.stack.peek(0) {
+                self.hashes.push((address, hash));
+            }
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        let scope = self.call_scope(inputs.caller, inputs.target_address, &inputs.input);
+        self.frames.push(scope);
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        if let Some(FrameScope {
+            entity: Some((user_op, _)),
+            phase,
+            charged: true,
+        }) = self.frames.pop()
+        {
+            let gas = &mut self.gas[user_op];
+            let spent = outcome.result.gas.spent();
+            match phase {
+                Phase::Validation => gas.validation += spent,
+                Phase::Execution => gas.execution += spent,
+            }
+        }
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        let scope = self.frames.last().copied().map_or(
+            FrameScope {
+                entity: None,
+                phase: Phase::Execution,
+                charged: false,
+            },
+            |parent| FrameScope {
+                charged: false,
+                ..parent
+            },
+        );
+        self.frames.push(scope);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.frames.pop();
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        primitives::{address, keccak256, AccountInfo, Bytecode, TransactTo},
+        Evm,
+    };
+
+    const ENTRY_POINT: Address = address!("0000000000000000000000000000000000004337");
+    const SENDER: Address = address!("1000000000000000000000000000000000000001");
+    const PAYMASTER: Address = address!("2000000000000000000000000000000000000002");
+
+    /// Stores the selector in memory and calls the target with it.
+    fn call(code: &mut Vec<u8>, target: Address, selector: [u8; 4]) {
+        code.push(opcode::PUSH4);
+        code.extend_from_slice(&selector);
+        code.extend_from_slice(&[
+            opcode::PUSH1,
+            0xe0,
+            opcode::SHL,
+            opcode::PUSH0,
+            opcode::MSTORE,
+        ]);
+        // CALL(gas, target, 0, 0, 4, 0, 0)
+        code.extend_from_slice(&[
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            0x04,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH20,
+        ]);
+        code.extend_from_slice(target.as_slice());
+        code.extend_from_slice(&[opcode::GAS, opcode::CALL, opcode::POP]);
+    }
+
+    fn bundle(inspector: UserOperationInspector) -> UserOperationInspector {
+        // validation of the sender and the paymaster, then the execution of the sender.
+        let mut entry_point = Vec::new();
+        call(&mut entry_point, SENDER, VALIDATE_USER_OP_V07);
+        call(&mut entry_point, PAYMASTER, VALIDATE_PAYMASTER_USER_OP_V07);
+        call(&mut entry_point, SENDER, [0xb6, 0x1d, 0x27, 0xf6]);
+        // TIMESTAMP POP, SSTORE(0, 1)
+        let sender = [
+            opcode::TIMESTAMP,
+            opcode::POP,
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH0,
+            opcode::SSTORE,
+        ];
+        // SLOAD(0) POP
+        let paymaster = [opcode::PUSH0, opcode::SLOAD, opcode::POP];
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [
+            (ENTRY_POINT, entry_point),
+            (SENDER, sender.to_vec()),
+            (PAYMASTER, paymaster.to_vec()),
+        ] {
+            let code = Bytecode::new_raw(code.into());
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(inspector)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(ENTRY_POINT);
+                tx.gas_limit = 1_000_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+        evm.into_context().external
+    }
+
+    #[test]
+    fn selectors() {
+        let selector =
+            |signature: &str| -> [u8; 4] { keccak256(signature)[..4].try_into().unwrap() };
+        let v06 =
+            "(address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)";
+        let v07 = "(address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes)";
+        assert_eq!(
+            selector(&format!("validateUserOp({v06},bytes32,uint256)")),
+            VALIDATE_USER_OP_V06
+        );
+        assert_eq!(
+            selector(&format!("validateUserOp({v07},bytes32,uint256)")),
+            VALIDATE_USER_OP_V07
+        );
+        assert_eq!(
+            selector(&format!("validatePaymasterUserOp({v06},bytes32,uint256)")),
+            VALIDATE_PAYMASTER_USER_OP_V06
+        );
+        assert_eq!(
+            selector(&format!("validatePaymasterUserOp({v07},bytes32,uint256)")),
+            VALIDATE_PAYMASTER_USER_OP_V07
+        );
+    }
+
+    #[test]
+    fn validation_rules_and_gas() {
+        let user_ops = vec![UserOperationEntities {
+            sender: SENDER,
+            factory: None,
+            paymaster: Some(PAYMASTER),
+        }];
+        let inspector = bundle(UserOperationInspector::new(ENTRY_POINT, user_ops.clone()));
+
+        // TIMESTAMP is only banned in the validation phase and the unstaked paymaster can
+        // not access its storage.
+        assert_eq!(
+            inspector.violations(),
+            &[
+                RuleViolation {
+                    user_op: 0,
+                    entity: EntityKind::Sender,
+                    address: SENDER,
+                    pc: 0,
+                    kind: ViolationKind::BannedOpcode(opcode::TIMESTAMP),
+                },
+                RuleViolation {
+                    user_op: 0,
+                    entity: EntityKind::Paymaster,
+                    address: PAYMASTER,
+                    pc: 1,
+                    kind: ViolationKind::StorageAccess {
+                        address: PAYMASTER,
+                        slot: U256::ZERO
+                    },
+                },
+            ]
+        );
+        let gas = inspector.gas()[0];
+        assert!(gas.validation > 0);
+        assert!(gas.execution > 0);
+        assert_eq!(gas.total(), gas.validation + gas.execution);
+
+        let inspector =
+            bundle(UserOperationInspector::new(ENTRY_POINT, user_ops).with_staked([PAYMASTER]));
+        assert_eq!(inspector.violations().len(), 1);
+        assert_eq!(inspector.violations()[0].entity, EntityKind::Sender);
+    }
+
+    #[test]
+    fn associated_slots() {
+        let mut inspector = UserOperationInspector::new(ENTRY_POINT, Vec::new());
+        let hash = U256::from_be_bytes(keccak256([0u8; 64]).0);
+        inspector.hashes.push((SENDER, hash));
+        assert!(inspector.is_associated(SENDER, hash + U256::from(127)));
+        assert!(!inspector.is_associated(SENDER, hash + U256::from(128)));
+        assert!(!inspector.is_associated(PAYMASTER, hash));
+        assert!(inspector.is_associated(SENDER, U256::from_be_slice(SENDER.as_slice())));
+    }
+}
@@ -1,21 +1,23 @@
 use crate::{
     builder::{EvmBuilder, HandlerStage, SetGenericStage},
-    db::{Database, DatabaseCommit, EmptyDB},
+    db::{CacheDB, Database, DatabaseCommit, DatabaseRef, EmptyDB, StateOverride, WrapDatabaseRef},
     handler::Handler,
+    inspector::{inspector_handle_register, owned_inspector_handle_register, GetInspector},
     interpreter::{
-        opcode::InstructionTables, Host, Interpreter, InterpreterAction, LoadAccountResult,
-        SStoreResult, SelfDestructResult, SharedMemory,
+        opcode::InstructionTables, HostRead, HostWrite, Interpreter, InterpreterAction,
+        LoadAccountResult, SStoreResult, SelfDestructResult, SharedMemory,
     },
     primitives::{
-        specification::SpecId, Address, BlockEnv, Bytecode, CfgEnv, EVMError, EVMResult, Env,
-        EnvWithHandlerCfg, ExecutionResult, HandlerCfg, Log, ResultAndState, TransactTo, TxEnv,
-        B256, U256,
+        spec_to_generic, specification::SpecId, Address, BlockEnv, Bytecode, CancellationToken,
+        CfgEnv, EVMError, EVMResult, Env, EnvWithHandlerCfg, ExecutionResult, HaltContext,
+        HandlerCfg, Log, ResultAndState, Spec, SubcallGas, TransactTo, TxEnv, Warning, B256, U256,
     },
-    Context, ContextWithHandlerCfg, Frame, FrameOrResult, FrameResult,
+    Context, ContextPrecompiles, ContextWithHandlerCfg, EvmContext, Frame, FrameOrResult,
+    FrameResult, Inspector, JournalMetrics,
 };
-use core::fmt;
 use bcevm_interpreter::{CallInputs, CreateInputs};
-use std::vec::Vec;
+use core::{cell::RefCell, fmt};
+use std::{rc::Rc, vec::Vec};
 
 /// EVM call stack limit.
 pub const CALL_STACK_LIMIT: u64 = 1024;
@@ -46,10 +48,146 @@ where
 impl<EXT, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
     /// Commit the changes to the database.
     pub fn transact_commit(&mut self) -> Result<ExecutionResult, EVMError<DB::Error>> {
-        let ResultAndState { result, state } = self.transact()?;
+        let ResultAndState { result, state, .. } = self.transact()?;
         self.context.evm.db.commit(state);
         Ok(result)
     }
+
+    /// Applies the system calls at the start of the block and commits their changes.
+    ///
+    /// It runs the [store_block_hash](crate::handler::PreExecutionHandler::store_block_hash)
+    /// and [apply_beacon_root](crate::handler::PreExecutionHandler::apply_beacon_root) handles
+    /// and has to be called once per block, before its first transaction. The journal is
+    /// finalized afterwards, so the system contracts are not warm for the transactions.
+    pub fn apply_block_system_calls(&mut self) -> Result<(), EVMError<DB::Error>> {
+        let pre_exec = self.handler.pre_execution();
+        let result = pre_exec
+            .store_block_hash(&mut self.context)
+            .and_then(|()| pre_exec.apply_beacon_root(&mut self.context));
+        let (state, _) = self.context.evm.journaled_state.finalize();
+        result?;
+        self.context.evm.db.commit(state);
+        Ok(())
+    }
+}
+
+impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
+    /// Executes the transaction with the inspector attached, without committing the state.
+    ///
+    /// The inspector is attached for this call only, it is returned together with the result.
+    /// For the call the handler is built again from the [HandlerCfg] and the handle registers
+    /// of this EVM, with the inspector handles on top of them, the EVM context is kept as is.
+    pub fn inspect<I: Inspector<DB> + 'a>(&mut self, inspector: I) -> (EVMResult<DB::Error>, I) {
+        let inspector = Rc::new(RefCell::new(inspector));
+        let mut handler = Handler::mainnet_with_gas_schedule(
+            self.handler.cfg.spec_id,
+            self.context.evm.env.cfg.gas_schedule_overrides.as_ref(),
+        );
+        for register in &self.handler.registers {
+            register.register(&mut handler);
+        }
+        handler.cfg = self.handler.cfg;
+        owned_inspector_handle_register(&mut handler, inspector.clone());
+
+        let handler = core::mem::replace(&mut self.handler, handler);
+        let result = self.transact();
+        // drops the inspector handles.
+        self.handler = handler;
+        let inspector = Rc::try_unwrap(inspector)
+            .unwrap_or_else(|_| unreachable!("inspector handles are dropped"))
+            .into_inner();
+        (result, inspector)
+    }
+}
+
+impl<'a, EXT, DB: Database + DatabaseCommit> Evm<'a, EXT, DB> {
+    /// Executes the transaction with the inspector attached and commits the changes to the
+    /// database. See [Evm::inspect].
+    pub fn inspect_commit<I: Inspector<DB> + 'a>(
+        &mut self,
+        inspector: I,
+    ) -> (Result<ExecutionResult, EVMError<DB::Error>>, I) {
+        let (result, inspector) = self.inspect(inspector);
+        let result = result.map(|ResultAndState { result, state, .. }| {
+            self.context.evm.db.commit(state);
+            result
+        });
+        (result, inspector)
+    }
+}
+
+impl<EXT, DB: Database + DatabaseRef> Evm<'_, EXT, DB> {
+    /// Executes the transaction without committing, reading the database only through
+    /// [DatabaseRef].
+    ///
+    /// Unlike [Evm::transact], the EVM is borrowed immutably and the database is not modified,
+    /// e.g. [CacheDB] does not cache the loaded accounts, so the EVM can be shared between
+    /// read-only simulations.
+    ///
+    /// The transaction is executed over a copy of the EVM context with the handler built from
+    /// the [HandlerCfg], so the optimism and chain preset registers are applied. Other handle
+    /// registers of this EVM are bound to its database type and are not applied.
+    pub fn transact_ref(&self) -> EVMResult<<DB as DatabaseRef>::Error> {
+        self.ref_evm(()).transact()
+    }
+
+    /// Executes the transaction with the inspector attached, reading the database only
+    /// through [DatabaseRef]. See [Evm::transact_ref] and [Evm::inspect].
+    pub fn inspect_ref<'b, I: GetInspector<WrapDatabaseRef<&'b DB>>>(
+        &'b self,
+        inspector: I,
+    ) -> EVMResult<<DB as DatabaseRef>::Error> {
+        self.ref_evm(inspector)
+            .modify()
+            .append_handler_register(inspector_handle_register)
+            .build()
+            .transact()
+    }
+
+    /// Returns the EVM over the copy of the context that reads the database through
+    /// [DatabaseRef].
+    fn ref_evm<'h, OEXT>(&self, external: OEXT) -> Evm<'h, OEXT, WrapDatabaseRef<&DB>> {
+        let evm = EvmContext {
+            inner: self
+                .context
+                .evm
+                .inner
+                .clone_with_db(WrapDatabaseRef(&self.context.evm.db)),
+            precompiles: ContextPrecompiles::default(),
+        };
+        Evm::new(Context::new(evm, external), Handler::new(self.handler.cfg))
+    }
+}
+
+impl<EXT, ExtDB: DatabaseRef> Evm<'_, EXT, CacheDB<ExtDB>> {
+    /// Transact with the state overrides applied on top of the database.
+    ///
+    /// Overridden accounts are restored in the database after the execution, so the
+    /// overrides are not visible to following transactions.
+    pub fn transact_with_overrides(
+        &mut self,
+        overrides: &StateOverride,
+    ) -> EVMResult<ExtDB::Error> {
+        let db = &mut self.context.evm.db;
+        let previous: Vec<_> = overrides
+            .keys()
+            .map(|address| (*address, db.accounts.get(address).cloned()))
+            .collect();
+
+        let result = match db.apply_overrides(overrides) {
+            Ok(()) => self.transact(),
+            Err(e) => Err(EVMError::Database(e)),
+        };
+
+        let accounts = &mut self.context.evm.db.accounts;
+        for (address, account) in previous {
+            match account {
+                Some(account) => accounts.insert(address, account),
+                None => accounts.remove(&address),
+            };
+        }
+        result
+    }
 }
 
 impl<'a> Evm<'a, (), EmptyDB> {
@@ -103,6 +241,8 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     /// This function will not validate the transaction.
     #[inline]
     pub fn transact_preverified(&mut self) -> EVMResult<DB::Error> {
+        #[cfg(feature = "tracing")]
+        let _span = self.transact_span().entered();
         let initial_gas_spend = self
             .handler
             .validation()
@@ -120,6 +260,8 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     /// Pre verify transaction inner.
     #[inline]
     fn preverify_transaction_inner(&mut self) -> Result<u64, EVMError<DB::Error>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validation").entered();
         self.handler.validation().env(&self.context.evm.env)?;
         let initial_gas_spend = self
             .handler
@@ -136,6 +278,8 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     /// This function will validate the transaction.
     #[inline]
     pub fn transact(&mut self) -> EVMResult<DB::Error> {
+        #[cfg(feature = "tracing")]
+        let _span = self.transact_span().entered();
         let initial_gas_spend = self.preverify_transaction_inner().map_err(|e| {
             self.clear();
             e
@@ -147,6 +291,18 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         output
     }
 
+    /// Span that covers the whole transaction execution.
+    #[cfg(feature = "tracing")]
+    fn transact_span(&self) -> tracing::Span {
+        let tx = &self.context.evm.env.tx;
+        tracing::debug_span!(
+            "transact",
+            caller = %tx.caller,
+            transact_to = ?tx.transact_to,
+            gas_limit = tx.gas_limit,
+        )
+    }
+
     /// Returns the reference of handler configuration
     #[inline]
     pub fn handler_cfg(&self) -> &HandlerCfg {
@@ -177,6 +333,19 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         &mut self.context.evm.env.tx
     }
 
+    /// Enables collection of [JournalMetrics] for the following transactions.
+    #[inline]
+    pub fn enable_metrics(&mut self) {
+        self.context.evm.journaled_state.enable_metrics();
+    }
+
+    /// Returns journal metrics collected since they were enabled or last taken, and
+    /// resets them. Returns `None` if metrics are not enabled.
+    #[inline]
+    pub fn take_metrics(&mut self) -> Option<JournalMetrics> {
+        self.context.evm.journaled_state.take_metrics()
+    }
+
     /// Returns the reference of database
     #[inline]
     pub fn db(&self) -> &DB {
@@ -245,6 +414,10 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         let frame_result = match &table {
             InstructionTables::Plain(table) => self.run_the_loop(table, first_frame),
             InstructionTables::Boxed(table) => self.run_the_loop(table, first_frame),
+            InstructionTables::Cached(table) => self.run_the_loop(table, first_frame),
+            InstructionTables::Specialized(spec_id) => {
+                spec_to_generic!(*spec_id, self.run_the_loop_specialized::<SPEC>(first_frame))
+            }
         };
 
         // return back instruction table
@@ -263,34 +436,87 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     where
         FN: Fn(&mut Interpreter, &mut Self),
     {
+        self.run_frames(first_frame, |interpreter, shared_memory, evm| {
+            interpreter.run(shared_memory, instruction_table, evm)
+        })
+    }
+
+    /// Runs main call loop with the instructions of the spec, see
+    /// [Interpreter::run_specialized].
+    #[inline]
+    pub fn run_the_loop_specialized<SPEC: Spec>(
+        &mut self,
+        first_frame: Frame,
+    ) -> Result<FrameResult, EVMError<DB::Error>> {
+        self.run_frames(first_frame, |interpreter, shared_memory, evm| {
+            interpreter.run_specialized::<SPEC, Self>(shared_memory, evm)
+        })
+    }
+
+    /// Runs main call loop, `run` executes the interpreter of the frame.
+    #[inline]
+    fn run_frames(
+        &mut self,
+        first_frame: Frame,
+        mut run: impl FnMut(&mut Interpreter, SharedMemory, &mut Self) -> InterpreterAction,
+    ) -> Result<FrameResult, EVMError<DB::Error>> {
         let mut call_stack: Vec<Frame> = Vec::with_capacity(1025);
+        #[cfg(feature = "tracing")]
+        trace_new_frame(&first_frame, 0);
         call_stack.push(first_frame);
 
-        #[cfg(feature = "memory_limit")]
         let mut shared_memory =
             SharedMemory::new_with_memory_limit(self.context.evm.env.cfg.memory_limit);
-        #[cfg(not(feature = "memory_limit"))]
-        let mut shared_memory = SharedMemory::new();
 
+        if let Some(frame_memory_limit) = self.context.evm.env.cfg.frame_memory_limit {
+            shared_memory.set_frame_memory_limit(frame_memory_limit);
+        }
+        if self.context.evm.env.cfg.bytes_arena {
+            shared_memory.enable_bytes_arena();
+        }
         shared_memory.new_context();
 
         // peek last stack frame.
         let mut stack_frame = call_stack.last_mut().unwrap();
+        // called address of the current subcall made directly by the first frame.
+        let mut subcall_address = Address::ZERO;
 
         loop {
             // run interpreter
             let interpreter = &mut stack_frame.frame_data_mut().interpreter;
-            let next_action = interpreter.run(shared_memory, instruction_table, self);
+            if let Some(token) = &self.context.evm.cancellation {
+                if token.is_canceled() {
+                    return Err(EVMError::Canceled);
+                }
+                interpreter
+                    .cancellation
+                    .get_or_insert_with(|| token.clone());
+            }
+            let next_action = run(interpreter, shared_memory, self);
 
             // take error and break the loop if there is any.
             // This error is set From Interpreter when it's interacting with Host.
             self.context.evm.take_error()?;
+            if self
+                .context
+                .evm
+                .cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_canceled)
+            {
+                return Err(EVMError::Canceled);
+            }
             // take shared memory back.
             shared_memory = interpreter.take_memory();
 
             let exec = &mut self.handler.execution;
             let frame_or_result = match next_action {
-                InterpreterAction::Call { inputs } => exec.call(&mut self.context, inputs)?,
+                InterpreterAction::Call { inputs } => {
+                    if call_stack.len() == 1 {
+                        subcall_address = inputs.bytecode_address;
+                    }
+                    exec.call(&mut self.context, inputs)?
+                }
                 InterpreterAction::Create { inputs } => exec.create(&mut self.context, inputs)?,
                 InterpreterAction::EOFCreate { inputs } => {
                     exec.eofcreate(&mut self.context, inputs)?
@@ -299,13 +525,21 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
                     // free memory context.
                     shared_memory.free_context();
 
+                    // instruction pointer is past the opcode that returned.
+                    let pc = interpreter.program_counter().saturating_sub(1);
+                    let halt_context = HaltContext {
+                        address: interpreter.contract.target_address,
+                        pc,
+                        opcode: interpreter.bytecode.get(pc).copied().unwrap_or_default(),
+                    };
+
                     // pop last frame from the stack and consume it to create FrameResult.
                     let returned_frame = call_stack
                         .pop()
                         .expect("We just returned from Interpreter frame");
 
                     let ctx = &mut self.context;
-                    FrameOrResult::Result(match returned_frame {
+                    let result = match returned_frame {
                         Frame::Call(frame) => {
                             // return_call
                             FrameResult::Call(exec.call_return(ctx, frame, result)?)
@@ -318,7 +552,19 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
                             // return_eofcreate
                             FrameResult::EOFCreate(exec.eofcreate_return(ctx, frame, result)?)
                         }
-                    })
+                    };
+                    if result.interpreter_result().is_error() {
+                        ctx.evm.halt_context = Some(halt_context);
+                    }
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(
+                        depth = call_stack.len(),
+                        result = ?result.instruction_result(),
+                        gas_used = result.gas().spent(),
+                        output_len = result.interpreter_result().output.len(),
+                        "frame returned"
+                    );
+                    FrameOrResult::Result(result)
                 }
                 InterpreterAction::None => unreachable!("InterpreterAction::None is not expected"),
             };
@@ -326,17 +572,37 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
             // handle result
             match frame_or_result {
                 FrameOrResult::Frame(frame) => {
+                    #[cfg(feature = "tracing")]
+                    trace_new_frame(&frame, call_stack.len());
                     shared_memory.new_context();
                     call_stack.push(frame);
                     stack_frame = call_stack.last_mut().unwrap();
                 }
                 FrameOrResult::Result(result) => {
+                    let is_subcall = call_stack.len() == 1;
                     let Some(top_frame) = call_stack.last_mut() else {
                         // Break the look if there are no more frames.
                         return Ok(result);
                     };
                     stack_frame = top_frame;
                     let ctx = &mut self.context;
+                    let frame_gas = result.frame_gas();
+                    stack_frame.frame_data_mut().child_gas_used += frame_gas.gas_used;
+                    if is_subcall {
+                        let (address, is_create) = match &result {
+                            FrameResult::Call(_) => (subcall_address, false),
+                            FrameResult::Create(outcome) => {
+                                (outcome.address.unwrap_or_default(), true)
+                            }
+                            FrameResult::EOFCreate(outcome) => (outcome.address, true),
+                        };
+                        ctx.evm.subcalls.push(SubcallGas {
+                            address,
+                            is_create,
+                            success: result.interpreter_result().is_ok(),
+                            gas: frame_gas,
+                        });
+                    }
                     // Insert result to the top frame.
                     match result {
                         FrameResult::Call(outcome) => {
@@ -359,14 +625,24 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
     /// Transact pre-verified transaction.
     fn transact_preverified_inner(&mut self, initial_gas_spend: u64) -> EVMResult<DB::Error> {
+        // apply opcode gas overrides of the transaction to the instruction table.
+        self.handler
+            .set_gas_schedule_overrides(self.context.evm.env.cfg.gas_schedule_overrides.as_ref());
+
         let ctx = &mut self.context;
         let pre_exec = self.handler.pre_execution();
+        #[cfg(feature = "tracing")]
+        let stage = tracing::debug_span!("pre_execution").entered();
 
         // load access list and beneficiary if needed.
         pre_exec.load_accounts(ctx)?;
 
         // load precompiles
-        let precompiles = pre_exec.load_precompiles();
+        let mut precompiles = pre_exec.load_precompiles();
+        let remap = &ctx.evm.env.cfg.precompile_address_remap;
+        if !remap.is_empty() {
+            precompiles.remap_addresses(remap.iter().copied());
+        }
         ctx.evm.set_precompiles(precompiles);
 
         // deduce caller balance with its limit.
@@ -374,6 +650,11 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
         let gas_limit = ctx.evm.env.tx.gas_limit - initial_gas_spend;
 
+        #[cfg(feature = "tracing")]
+        drop(stage);
+        #[cfg(feature = "tracing")]
+        let stage = tracing::debug_span!("execution", gas_limit).entered();
+
         let exec = self.handler.execution();
         // call inner handling of call/create
         let first_frame_or_result = match ctx.evm.env.tx.transact_to {
@@ -400,6 +681,12 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
             .execution()
             .last_frame_return(ctx, &mut result)?;
 
+        #[cfg(feature = "tracing")]
+        drop(stage);
+        #[cfg(feature = "tracing")]
+        let _stage =
+            tracing::debug_span!("post_execution", gas_used = result.gas().spent()).entered();
+
         let post_exec = self.handler.post_execution();
         // Reimburse the caller
         post_exec.reimburse_caller(ctx, result.gas())?;
@@ -410,15 +697,30 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     }
 }
 
-impl<EXT, DB: Database> Host for Evm<'_, EXT, DB> {
+/// Emits trace event for a frame pushed to the call stack at the given depth.
+#[cfg(feature = "tracing")]
+fn trace_new_frame(frame: &Frame, depth: usize) {
+    let contract = &frame.interpreter().contract;
+    let kind = match frame {
+        Frame::Call(_) => "call",
+        Frame::Create(_) => "create",
+        Frame::EOFCreate(_) => "eofcreate",
+    };
+    tracing::trace!(
+        depth,
+        kind,
+        address = %contract.target_address,
+        caller = %contract.caller,
+        gas_limit = frame.interpreter().gas.limit(),
+        "frame created"
+    );
+}
+
+impl<EXT, DB: Database> HostRead for Evm<'_, EXT, DB> {
     fn env(&self) -> &Env {
         &self.context.evm.env
     }
 
-    fn env_mut(&mut self) -> &mut Env {
-        &mut self.context.evm.env
-    }
-
     fn block_hash(&mut self, number: U256) -> Option<B256> {
         self.context
             .evm
@@ -459,18 +761,26 @@ impl<EXT, DB: Database> Host for Evm<'_, EXT, DB> {
             .ok()
     }
 
-    fn sload(&mut self, address: Address, index: U256) -> Option<(U256, bool)> {
+    fn code_by_hash(&mut self, code_hash: B256) -> Option<Bytecode> {
         self.context
             .evm
-            .sload(address, index)
+            .code_by_hash(code_hash)
             .map_err(|e| self.context.evm.error = Err(e))
             .ok()
     }
 
-    fn sstore(&mut self, address: Address, index: U256, value: U256) -> Option<SStoreResult> {
+    fn copy_code(&mut self, address: Address, code_offset: usize, dest: &mut [u8]) -> Option<()> {
         self.context
             .evm
-            .sstore(address, index, value)
+            .copy_code(address, code_offset, dest)
+            .map_err(|e| self.context.evm.error = Err(e))
+            .ok()
+    }
+
+    fn sload(&mut self, address: Address, index: U256) -> Option<(U256, bool)> {
+        self.context
+            .evm
+            .sload(address, index)
             .map_err(|e| self.context.evm.error = Err(e))
             .ok()
     }
@@ -479,6 +789,24 @@ impl<EXT, DB: Database> Host for Evm<'_, EXT, DB> {
         self.context.evm.tload(address, index)
     }
 
+    fn cold_access_charged(&mut self, gas: u64) {
+        self.context.evm.journaled_state.record_cold_access(gas);
+    }
+}
+
+impl<EXT, DB: Database> HostWrite for Evm<'_, EXT, DB> {
+    fn env_mut(&mut self) -> &mut Env {
+        &mut self.context.evm.env
+    }
+
+    fn sstore(&mut self, address: Address, index: U256, value: U256) -> Option<SStoreResult> {
+        self.context
+            .evm
+            .sstore(address, index, value)
+            .map_err(|e| self.context.evm.error = Err(e))
+            .ok()
+    }
+
     fn tstore(&mut self, address: Address, index: U256, value: U256) {
         self.context.evm.tstore(address, index, value)
     }
@@ -495,5 +823,395 @@ impl<EXT, DB: Database> Host for Evm<'_, EXT, DB> {
             .selfdestruct(address, target, &mut self.context.evm.inner.db)
             .map_err(|e| self.context.evm.error = Err(e))
             .ok()
+            .inspect(|result| {
+                // EIP-6780: only contracts created in the same transaction are destroyed.
+                if !result.destroyed {
+                    self.context
+                        .evm
+                        .warn(Warning::SelfDestructWithoutDestroy { address });
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{BenchmarkDB, EmptyDB},
+        interpreter::{gas, opcode},
+        primitives::{address, AccountInfo, Bytes, FrameGas, HaltReason},
+        test_utils::{create_contract_call_evm, MOCK_CONTRACT},
+        EvmContext,
+    };
+
+    #[derive(Default)]
+    struct StepCounter {
+        steps: usize,
+    }
+
+    impl<DB: Database> Inspector<DB> for StepCounter {
+        fn step(&mut self, _interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+            self.steps += 1;
+        }
+    }
+
+    /// Returns the database with the contract that stores 1 to the slot 0.
+    fn sstore_contract_db(contract: Address) -> CacheDB<EmptyDB> {
+        // SSTORE(0, 1), followed by the implicit STOP.
+        let code = Bytecode::new_raw(
+            [opcode::PUSH1, 0x01, opcode::PUSH0, opcode::SSTORE]
+                .to_vec()
+                .into(),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        db
+    }
+
+    #[test]
+    fn inspect_and_transact_ref() {
+        let contract = address!("1000000000000000000000000000000000000001");
+        let mut evm = Evm::builder()
+            .with_db(sstore_contract_db(contract))
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(contract))
+            .build();
+
+        // read-only execution does not cache the caller.
+        let result = evm.transact_ref().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            result.state[&contract].storage[&U256::ZERO].present_value,
+            U256::from(1)
+        );
+        assert!(!evm.db().accounts.contains_key(&Address::ZERO));
+
+        let mut counter = StepCounter::default();
+        assert!(evm.inspect_ref(&mut counter).unwrap().result.is_success());
+        assert_eq!(counter.steps, 4);
+
+        let (result, counter) = evm.inspect(StepCounter::default());
+        assert!(result.unwrap().result.is_success());
+        assert_eq!(counter.steps, 4);
+        // the loaded slot is cached, but the change is not committed.
+        assert_eq!(
+            evm.db().accounts[&contract].storage.get(&U256::ZERO),
+            Some(&U256::ZERO)
+        );
+
+        let (result, counter) = evm.inspect_commit(StepCounter::default());
+        assert!(result.unwrap().is_success());
+        assert_eq!(counter.steps, 4);
+        assert_eq!(
+            evm.db().accounts[&contract].storage[&U256::ZERO],
+            U256::from(1)
+        );
+        // the inspector is not kept.
+        assert!(evm.transact().unwrap().result.is_success());
+    }
+
+    #[test]
+    fn inspect_keeps_handle_registers() {
+        let contract = address!("1000000000000000000000000000000000000001");
+        let mut evm = Evm::builder()
+            .with_db(sstore_contract_db(contract))
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(contract))
+            .disable_opcode(opcode::SSTORE)
+            .build();
+
+        let (result, counter) = evm.inspect(StepCounter::default());
+        assert!(matches!(
+            result.unwrap().result,
+            ExecutionResult::Halt {
+                reason: HaltReason::NotActivated,
+                ..
+            }
+        ));
+        assert_eq!(counter.steps, 3);
+    }
+
+    #[test]
+    fn metrics_count_charged_cold_accesses() {
+        let code = Bytecode::new_raw(
+            [
+                // two SLOADs of the same slot.
+                opcode::PUSH1,
+                0x00,
+                opcode::SLOAD,
+                opcode::PUSH1,
+                0x00,
+                opcode::SLOAD,
+                // SELFBALANCE, BALANCE of caller (warm) and of unknown account (cold).
+                opcode::SELFBALANCE,
+                opcode::CALLER,
+                opcode::BALANCE,
+                opcode::PUSH1,
+                0x99,
+                opcode::BALANCE,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        evm.enable_metrics();
+
+        assert!(evm.transact().unwrap().result.is_success());
+        let metrics = evm.take_metrics().unwrap();
+        assert_eq!(metrics.cold_access_charges, 2);
+        assert_eq!(
+            metrics.cold_access_gas,
+            gas::COLD_ACCOUNT_ACCESS_PENALTY + gas::COLD_SLOAD_PENALTY
+        );
+
+        // cold BALANCE that runs out of gas is not charged.
+        evm.tx_mut().gas_limit = 21_000 + 2 * 3 + 2_100 + 100 + 5 + 2 + 100 + 3 + 2_000;
+        assert!(evm.transact().unwrap().result.is_halt());
+        let metrics = evm.take_metrics().unwrap();
+        assert_eq!(metrics.cold_access_charges, 1);
+        assert_eq!(metrics.cold_access_gas, gas::COLD_SLOAD_PENALTY);
+    }
+
+    #[test]
+    fn cancellation_token_aborts_execution() {
+        let contract = address!("1000000000000000000000000000000000000001");
+        // infinite loop: JUMPDEST PUSH0 JUMP
+        let code = Bytecode::new_raw(
+            [opcode::JUMPDEST, opcode::PUSH0, opcode::JUMP]
+                .to_vec()
+                .into(),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        let token = CancellationToken::new();
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_cancellation_token(token.clone())
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = u64::MAX;
+            })
+            .build();
+
+        let canceler = std::thread::spawn({
+            let token = token.clone();
+            move || {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                token.cancel();
+            }
+        });
+        assert_eq!(evm.transact(), Err(EVMError::Canceled));
+        canceler.join().unwrap();
+
+        // token stays canceled until it is reset.
+        evm.tx_mut().gas_limit = 100_000;
+        assert_eq!(evm.transact(), Err(EVMError::Canceled));
+        token.reset();
+        let result = evm.transact().unwrap().result;
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: crate::primitives::HaltReason::OutOfGas(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn precompile_address_remap() {
+        let identity = Address::with_last_byte(4);
+        let remapped = address!("0000000000000000000000000000000000000100");
+        let mut evm = Evm::builder()
+            .with_db(EmptyDB::default())
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(remapped);
+                tx.data = Bytes::from_static(b"abc");
+            })
+            .build();
+        assert_eq!(evm.transact().unwrap().result.output(), Some(&Bytes::new()));
+
+        evm.cfg_mut().precompile_address_remap = vec![(identity, remapped)];
+        assert_eq!(
+            evm.transact().unwrap().result.output(),
+            Some(&Bytes::from_static(b"abc"))
+        );
+        evm.tx_mut().transact_to = TransactTo::Call(identity);
+        assert_eq!(evm.transact().unwrap().result.output(), Some(&Bytes::new()));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_spans_and_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{span, subscriber::with_default, Event, Metadata, Subscriber};
+
+        #[derive(Default)]
+        struct Recorded {
+            spans: Vec<&'static str>,
+            events: usize,
+        }
+
+        struct Recorder(Arc<Mutex<Recorded>>);
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                let mut recorded = self.0.lock().unwrap();
+                recorded.spans.push(span.metadata().name());
+                span::Id::from_u64(recorded.spans.len() as u64)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, _: &Event<'_>) {
+                self.0.lock().unwrap().events += 1;
+            }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let contract = address!("1000000000000000000000000000000000000001");
+        // STATICCALL(gas, identity precompile, 0, 0, 0, 0)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH1,
+                0x04,
+                opcode::GAS,
+                opcode::STATICCALL,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(contract))
+            .build();
+
+        let recorded = Arc::new(Mutex::new(Recorded::default()));
+        let result = with_default(Recorder(recorded.clone()), || evm.transact().unwrap());
+        assert!(result.result.is_success());
+
+        let recorded = recorded.lock().unwrap();
+        assert_eq!(
+            recorded.spans,
+            [
+                "transact",
+                "validation",
+                "pre_execution",
+                "execution",
+                "post_execution"
+            ]
+        );
+        // frame created, precompile call and frame returned.
+        assert_eq!(recorded.events, 3);
+    }
+
+    #[test]
+    fn test_warnings() {
+        // sets and clears a slot, then selfdestructs.
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0x00,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0x99,
+                opcode::SELFDESTRUCT,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut evm = create_contract_call_evm(SpecId::CANCUN, code);
+
+        let result = evm.transact().unwrap();
+        let ExecutionResult::Success { gas_refunded, .. } = result.result else {
+            panic!("expected success");
+        };
+        assert_eq!(
+            result.warnings,
+            vec![
+                Warning::SelfDestructWithoutDestroy {
+                    address: MOCK_CONTRACT
+                },
+                Warning::RefundCapped {
+                    refunded: 19_900,
+                    capped: gas_refunded
+                },
+            ]
+        );
+
+        let mut evm = evm.modify().with_spec_id(SpecId::BERLIN).build();
+        assert!(evm.transact().unwrap().warnings.is_empty());
+    }
+
+    #[test]
+    fn test_subcall_gas() {
+        // CALL(GAS, precompile, 0, 0, 0, 0, 0) to identity and ecrecover precompiles.
+        let mut code = Vec::new();
+        for precompile in [0x04, 0x01] {
+            code.extend_from_slice(&[
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH1,
+                precompile,
+                opcode::GAS,
+                opcode::CALL,
+                opcode::POP,
+            ]);
+        }
+        code.push(opcode::STOP);
+        let mut evm = create_contract_call_evm(SpecId::CANCUN, Bytecode::new_raw(code.into()));
+
+        let subcall = |address, gas_used| SubcallGas {
+            address,
+            is_create: false,
+            success: true,
+            gas: FrameGas {
+                gas_used,
+                gas_refunded: 0,
+                child_gas_used: 0,
+            },
+        };
+        let expected = vec![
+            subcall(address!("0000000000000000000000000000000000000004"), 15),
+            subcall(address!("0000000000000000000000000000000000000001"), 3000),
+        ];
+        assert_eq!(evm.transact().unwrap().subcalls, expected);
+        // subcalls are collected per transaction.
+        assert_eq!(evm.transact().unwrap().subcalls, expected);
     }
 }
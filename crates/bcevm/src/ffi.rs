@@ -0,0 +1,301 @@
+//! C ABI for embedding the EVM in non-Rust clients.
+//!
+//! The EVM is created with an in-memory database and is referenced by an opaque
+//! [FfiEvm] pointer. Environment is set and results are returned as JSON, in the
+//! default `serde` representation of [Env], [TxEnv] and [ResultAndState].
+//!
+//! Functions that return an `i32` return `0` on success and `-1` on failure, functions
+//! that return a pointer return null. Panics are caught and reported as failures, they
+//! don't unwind into the caller. Buffers returned by the library need to be released
+//! with [bcevm_bytes_free].
+//!
+//! The crate needs to be built as `cdylib` or `staticlib` to be linked, for example
+//! with `cargo rustc --features ffi --crate-type cdylib`.
+
+use crate::{
+    db::{CacheDB, EmptyDB},
+    primitives::{AccountInfo, Address, Bytecode, Bytes, Env, ResultAndState, SpecId, TxEnv, U256},
+    DatabaseCommit, Evm,
+};
+use core::{ptr, slice};
+use std::{
+    boxed::Box,
+    panic::{catch_unwind, AssertUnwindSafe},
+    string::String,
+};
+
+/// EVM with an in-memory database, owned by the FFI caller.
+pub struct FfiEvm {
+    evm: Evm<'static, (), CacheDB<EmptyDB>>,
+}
+
+/// Outcome of [bcevm_transact], serialized as JSON.
+#[derive(serde::Serialize)]
+enum FfiOutcome {
+    /// Transaction was executed.
+    Ok(ResultAndState),
+    /// Transaction is invalid or database failed.
+    Err(String),
+}
+
+/// Creates a new EVM with an empty in-memory database and the latest spec.
+///
+/// The EVM needs to be released with [bcevm_free].
+#[no_mangle]
+pub extern "C" fn bcevm_new() -> *mut FfiEvm {
+    catch_panic(ptr::null_mut(), || {
+        let evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .build();
+        Box::into_raw(Box::new(FfiEvm { evm }))
+    })
+}
+
+/// Releases the EVM.
+///
+/// # Safety
+///
+/// `evm` needs to be returned by [bcevm_new] and not released before, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn bcevm_free(evm: *mut FfiEvm) {
+    catch_panic((), || {
+        if !evm.is_null() {
+            drop(Box::from_raw(evm));
+        }
+    })
+}
+
+/// Sets the spec id, see [SpecId] for the values.
+///
+/// # Safety
+///
+/// `evm` needs to be a valid pointer returned by [bcevm_new].
+#[no_mangle]
+pub unsafe extern "C" fn bcevm_set_spec_id(evm: *mut FfiEvm, spec_id: u8) -> i32 {
+    catch_panic(-1, || {
+        let (Some(evm), Some(spec_id)) = (evm.as_mut(), SpecId::try_from_u8(spec_id)) else {
+            return -1;
+        };
+        evm.evm.modify_spec_id(spec_id);
+        0
+    })
+}
+
+/// Sets the whole environment from JSON.
+///
+/// # Safety
+///
+/// `evm` needs to be a valid pointer returned by [bcevm_new] and `json` needs to
+/// point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bcevm_set_env(evm: *mut FfiEvm, json: *const u8, len: usize) -> i32 {
+    catch_panic(-1, || {
+        let (Some(evm), Some(env)) = (evm.as_mut(), from_json::<Env>(json, len)) else {
+            return -1;
+        };
+        *evm.evm.context.evm.env = env;
+        0
+    })
+}
+
+/// Sets the transaction environment from JSON.
+///
+/// # Safety
+///
+/// `evm` needs to be a valid pointer returned by [bcevm_new] and `json` needs to
+/// point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bcevm_set_tx(evm: *mut FfiEvm, json: *const u8, len: usize) -> i32 {
+    catch_panic(-1, || {
+        let (Some(evm), Some(tx)) = (evm.as_mut(), from_json::<TxEnv>(json, len)) else {
+            return -1;
+        };
+        *evm.evm.tx_mut() = tx;
+        0
+    })
+}
+
+/// Inserts the account into the database.
+///
+/// `address` is 20 bytes and `balance` is 32 big-endian bytes. `code` can be null if
+/// `code_len` is zero.
+///
+/// # Safety
+///
+/// `evm` needs to be a valid pointer returned by [bcevm_new] and other pointers need
+/// to point to the given number of readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bcevm_insert_account(
+    evm: *mut FfiEvm,
+    address: *const u8,
+    balance: *const u8,
+    nonce: u64,
+    code: *const u8,
+    code_len: usize,
+) -> i32 {
+    catch_panic(-1, || {
+        let (Some(evm), Some(address), Some(balance), Some(code)) = (
+            evm.as_mut(),
+            bytes(address, 20),
+            bytes(balance, 32),
+            bytes(code, code_len),
+        ) else {
+            return -1;
+        };
+        let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+        let info = AccountInfo::new(U256::from_be_slice(balance), nonce, code.hash_slow(), code);
+        evm.evm
+            .db_mut()
+            .insert_account_info(Address::from_slice(address), info);
+        0
+    })
+}
+
+/// Executes the transaction and returns the outcome as JSON, `{"Ok": ResultAndState}`
+/// with the result, logs and changed state, or `{"Err": message}`.
+///
+/// If `commit` is true, changed state is committed to the database. Length of the
+/// returned buffer is written to `out_len`. Returns null if `evm` is null or execution
+/// panicked.
+///
+/// # Safety
+///
+/// `evm` needs to be a valid pointer returned by [bcevm_new] and `out_len` needs to
+/// be writable.
+#[no_mangle]
+pub unsafe extern "C" fn bcevm_transact(
+    evm: *mut FfiEvm,
+    commit: bool,
+    out_len: *mut usize,
+) -> *mut u8 {
+    catch_panic(ptr::null_mut(), || {
+        let Some(evm) = evm.as_mut() else {
+            return ptr::null_mut();
+        };
+        let outcome = match evm.evm.transact() {
+            Ok(result) => {
+                if commit {
+                    evm.evm.db_mut().commit(result.state.clone());
+                }
+                FfiOutcome::Ok(result)
+            }
+            Err(err) => FfiOutcome::Err(err.to_string()),
+        };
+        let json = serde_json::to_vec(&outcome).expect("outcome is serializable");
+        let mut json = json.into_boxed_slice();
+        *out_len = json.len();
+        let data = json.as_mut_ptr();
+        core::mem::forget(json);
+        data
+    })
+}
+
+/// Releases the buffer returned by the library.
+///
+/// # Safety
+///
+/// `data` and `len` need to be returned by the library and not released before, or
+/// `data` needs to be null.
+#[no_mangle]
+pub unsafe extern "C" fn bcevm_bytes_free(data: *mut u8, len: usize) {
+    catch_panic((), || {
+        if !data.is_null() {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+        }
+    })
+}
+
+/// Runs the entry point, returning `failure` if it panics, as unwinding into the caller
+/// is undefined behaviour.
+fn catch_panic<T>(failure: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(failure)
+}
+
+/// Returns the slice, empty if `len` is zero, or `None` if `data` is null.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        return Some(&[]);
+    }
+    (!data.is_null()).then(|| slice::from_raw_parts(data, len))
+}
+
+unsafe fn from_json<T: serde::de::DeserializeOwned>(data: *const u8, len: usize) -> Option<T> {
+    serde_json::from_slice(bytes(data, len)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::address;
+    use std::sync::Arc;
+
+    #[test]
+    fn transact_and_commit() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let recipient = address!("1000000000000000000000000000000000000002");
+        unsafe {
+            let evm = bcevm_new();
+            let balance = U256::from(1_000_000_000u64).to_be_bytes::<32>();
+            assert_eq!(
+                bcevm_insert_account(evm, caller.as_ptr(), balance.as_ptr(), 0, ptr::null(), 0),
+                0
+            );
+            assert_eq!(bcevm_set_spec_id(evm, SpecId::CANCUN as u8), 0);
+
+            let tx = TxEnv {
+                caller,
+                transact_to: crate::primitives::TransactTo::Call(recipient),
+                value: U256::from(10),
+                nonce: Some(0),
+                ..Default::default()
+            };
+            let json = serde_json::to_vec(&tx).unwrap();
+            assert_eq!(bcevm_set_tx(evm, json.as_ptr(), json.len()), 0);
+            assert_eq!(bcevm_set_tx(evm, b"{".as_ptr(), 1), -1);
+
+            let mut len = 0;
+            let out = bcevm_transact(evm, true, &mut len);
+            let outcome: serde_json::Value =
+                serde_json::from_slice(slice::from_raw_parts(out, len)).unwrap();
+            bcevm_bytes_free(out, len);
+            assert_eq!(outcome["Ok"]["result"]["Success"]["gas_used"], 21_000);
+
+            let recipient_balance = (*evm)
+                .evm
+                .db_mut()
+                .load_account(recipient)
+                .unwrap()
+                .info
+                .balance;
+            assert_eq!(recipient_balance, U256::from(10));
+
+            // nonce is already used.
+            let out = bcevm_transact(evm, false, &mut len);
+            let outcome: serde_json::Value =
+                serde_json::from_slice(slice::from_raw_parts(out, len)).unwrap();
+            bcevm_bytes_free(out, len);
+            assert!(outcome["Err"].is_string());
+
+            bcevm_free(evm);
+        }
+    }
+
+    #[test]
+    fn panic_is_caught() {
+        unsafe {
+            let ffi = Box::from_raw(bcevm_new());
+            let evm = ffi
+                .evm
+                .modify()
+                .append_handler_register(|handler| {
+                    handler.post_execution.output = Arc::new(|_, _| panic!("output panicked"));
+                })
+                .build();
+            let evm = Box::into_raw(Box::new(FfiEvm { evm }));
+
+            let mut len = 0;
+            assert!(bcevm_transact(evm, false, &mut len).is_null());
+            bcevm_free(evm);
+        }
+    }
+}
@@ -0,0 +1,452 @@
+//! State test fixtures captured from executed transactions.
+//!
+//! [StateFixture::capture] executes a transaction on top of a [DatabaseRef] and records it
+//! as a self-contained fixture in the
+//! [GeneralStateTest](https://ethereum-tests.readthedocs.io/en/latest/state-tests/index.html)
+//! format: the block environment, the pre-state of every account the transaction loaded,
+//! the transaction itself and the expected post-state root and logs hash.
+//!
+//! The pre-state contains only the accounts and storage slots accessed by the transaction,
+//! so the post-state root is the root of that partial state and not of the chain state.
+//! Block hashes read with `BLOCKHASH` and the chain id are not part of the format and are
+//! not captured.
+
+use crate::{
+    db::{DatabaseRef, PlainAccount},
+    primitives::{
+        keccak256, Address, Bytes, EVMError, Env, Log, ResultAndState, SpecId, TransactTo, B256,
+        KECCAK_EMPTY, U256,
+    },
+    state_commitment::state_root,
+    Evm,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Transaction captured as a GeneralStateTest fixture.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateFixture {
+    /// Block environment.
+    pub env: FixtureEnv,
+    /// Accounts loaded by the transaction, with the values before the execution.
+    pub pre: BTreeMap<Address, FixtureAccount>,
+    /// Transaction, each of `data`, `gasLimit` and `value` holds a single entry.
+    pub transaction: FixtureTransaction,
+    /// Expected results keyed by fork name, see [fork_name].
+    pub post: BTreeMap<String, Vec<FixturePost>>,
+}
+
+/// Block environment of the fixture.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureEnv {
+    pub current_coinbase: Address,
+    pub current_difficulty: U256,
+    pub current_gas_limit: U256,
+    pub current_number: U256,
+    pub current_timestamp: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_base_fee: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_random: Option<B256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_beacon_root: Option<B256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_excess_blob_gas: Option<U256>,
+}
+
+/// Account of the fixture pre-state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureAccount {
+    pub balance: U256,
+    pub code: Bytes,
+    pub nonce: U256,
+    pub storage: BTreeMap<U256, U256>,
+}
+
+/// Transaction of the fixture.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureTransaction {
+    pub data: Vec<Bytes>,
+    pub gas_limit: Vec<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    pub nonce: U256,
+    /// Key is not known for captured transactions and is always zero, `sender` is set instead.
+    pub secret_key: B256,
+    pub sender: Address,
+    /// Empty string for contract creation.
+    #[serde(serialize_with = "serialize_to", deserialize_with = "deserialize_to")]
+    pub to: Option<Address>,
+    pub value: Vec<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub access_lists: Vec<Option<Vec<FixtureAccessListItem>>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blob_versioned_hashes: Vec<B256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_blob_gas: Option<U256>,
+}
+
+/// Access list entry of the fixture transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureAccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<B256>,
+}
+
+/// Expected result of the fixture transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixturePost {
+    /// Root of the post-state.
+    pub hash: B256,
+    /// Keccak hash of the RLP encoded logs.
+    pub logs: B256,
+    pub indexes: FixtureIndexes,
+}
+
+/// Indexes into the `data`, `gasLimit` and `value` lists of the transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureIndexes {
+    pub data: usize,
+    pub gas: usize,
+    pub value: usize,
+}
+
+impl StateFixture {
+    /// Executes the transaction of `env` with the given spec on top of `db` and captures it
+    /// as a fixture.
+    ///
+    /// Nothing is committed to the database, the execution result is returned alongside
+    /// the fixture.
+    pub fn capture<DB: DatabaseRef>(
+        db: DB,
+        env: Box<Env>,
+        spec_id: SpecId,
+    ) -> Result<(Self, ResultAndState), EVMError<DB::Error>> {
+        let fixture_env = FixtureEnv::from_env(&env);
+        let transaction = FixtureTransaction::from_env(&env);
+
+        let mut evm = Evm::builder()
+            .with_ref_db(&db)
+            .with_env(env)
+            .with_spec_id(spec_id)
+            .build();
+        let result_and_state = evm.transact()?;
+        let state_clear = evm.context.evm.journaled_state.is_state_clear_enabled();
+        drop(evm);
+
+        let mut pre = BTreeMap::new();
+        let mut accounts = BTreeMap::new();
+        for (address, account) in result_and_state.state.iter() {
+            let Some(mut info) = db.basic_ref(*address).map_err(EVMError::Database)? else {
+                continue;
+            };
+            if info.code.is_none() && info.code_hash != KECCAK_EMPTY {
+                info.code = Some(
+                    db.code_by_hash_ref(info.code_hash)
+                        .map_err(EVMError::Database)?,
+                );
+            }
+            let storage: BTreeMap<U256, U256> = account
+                .storage
+                .iter()
+                .map(|(key, slot)| (*key, slot.original_value()))
+                .filter(|(_, value)| *value != U256::ZERO)
+                .collect();
+            pre.insert(
+                *address,
+                FixtureAccount {
+                    balance: info.balance,
+                    code: info
+                        .code
+                        .as_ref()
+                        .map(|code| code.original_bytes())
+                        .unwrap_or_default(),
+                    nonce: U256::from(info.nonce),
+                    storage: storage.clone(),
+                },
+            );
+            accounts.insert(
+                *address,
+                PlainAccount {
+                    info,
+                    storage: storage.into_iter().collect(),
+                },
+            );
+        }
+
+        // apply the changes the same way the state is committed.
+        for (address, account) in result_and_state.state.iter() {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() || (state_clear && account.is_empty()) {
+                accounts.remove(address);
+                continue;
+            }
+            let entry = accounts.entry(*address).or_default();
+            if account.is_created() {
+                entry.storage.clear();
+            }
+            entry.info = account.info.clone();
+            for (key, slot) in account.changed_storage_slots() {
+                if slot.present_value() == U256::ZERO {
+                    entry.storage.remove(key);
+                } else {
+                    entry.storage.insert(*key, slot.present_value());
+                }
+            }
+        }
+
+        let post = FixturePost {
+            hash: state_root(
+                accounts
+                    .iter()
+                    .map(|(address, account)| (*address, account)),
+            ),
+            logs: logs_hash(result_and_state.result.logs()),
+            indexes: FixtureIndexes::default(),
+        };
+
+        let fixture = Self {
+            env: fixture_env,
+            pre,
+            transaction,
+            post: BTreeMap::from([(fork_name(spec_id).to_string(), vec![post])]),
+        };
+        Ok((fixture, result_and_state))
+    }
+
+    /// Returns the test suite containing this fixture under the given name.
+    ///
+    /// Serialized suite is a GeneralStateTest file.
+    pub fn into_suite(self, name: impl Into<String>) -> BTreeMap<String, Self> {
+        BTreeMap::from([(name.into(), self)])
+    }
+}
+
+impl FixtureEnv {
+    /// Captures the block environment.
+    pub fn from_env(env: &Env) -> Self {
+        let block = &env.block;
+        Self {
+            current_coinbase: block.coinbase,
+            current_difficulty: block.difficulty,
+            current_gas_limit: block.gas_limit,
+            current_number: block.number,
+            current_timestamp: block.timestamp,
+            current_base_fee: Some(block.basefee),
+            current_random: block.prevrandao,
+            current_beacon_root: block.parent_beacon_block_root,
+            current_excess_blob_gas: block
+                .blob_excess_gas_and_price
+                .as_ref()
+                .map(|blob| U256::from(blob.excess_blob_gas)),
+        }
+    }
+}
+
+impl FixtureTransaction {
+    /// Captures the transaction environment.
+    pub fn from_env(env: &Env) -> Self {
+        let tx = &env.tx;
+        let (gas_price, max_fee_per_gas) = match tx.gas_priority_fee {
+            Some(_) => (None, Some(tx.gas_price)),
+            None => (Some(tx.gas_price), None),
+        };
+        let access_lists = if tx.access_list.is_empty() {
+            Vec::new()
+        } else {
+            vec![Some(
+                tx.access_list
+                    .iter()
+                    .map(|(address, keys)| FixtureAccessListItem {
+                        address: *address,
+                        storage_keys: keys.iter().map(|key| B256::from(*key)).collect(),
+                    })
+                    .collect(),
+            )]
+        };
+        Self {
+            data: vec![tx.data.clone()],
+            gas_limit: vec![U256::from(tx.gas_limit)],
+            gas_price,
+            nonce: U256::from(tx.nonce.unwrap_or_default()),
+            secret_key: B256::ZERO,
+            sender: tx.caller,
+            to: match tx.transact_to {
+                TransactTo::Call(address) => Some(address),
+                TransactTo::Create => None,
+            },
+            value: vec![tx.value],
+            max_fee_per_gas,
+            max_priority_fee_per_gas: tx.gas_priority_fee,
+            access_lists,
+            blob_versioned_hashes: tx.blob_hashes.clone(),
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+        }
+    }
+}
+
+/// Returns the GeneralStateTest fork name of the spec.
+pub fn fork_name(spec_id: SpecId) -> &'static str {
+    use SpecId::*;
+    let forks = [
+        (PRAGUE, "Prague"),
+        (CANCUN, "Cancun"),
+        (SHANGHAI, "Shanghai"),
+        (MERGE, "Merge"),
+        (LONDON, "London"),
+        (BERLIN, "Berlin"),
+        (ISTANBUL, "Istanbul"),
+        (PETERSBURG, "ConstantinopleFix"),
+        (BYZANTIUM, "Byzantium"),
+        (SPURIOUS_DRAGON, "EIP158"),
+        (TANGERINE, "EIP150"),
+        (HOMESTEAD, "Homestead"),
+    ];
+    forks
+        .into_iter()
+        .find(|(fork, _)| SpecId::enabled(spec_id, *fork))
+        .map_or("Frontier", |(_, name)| name)
+}
+
+/// Returns the keccak hash of the RLP encoded logs.
+pub fn logs_hash(logs: &[Log]) -> B256 {
+    let mut out = Vec::with_capacity(alloy_rlp::list_length(logs));
+    alloy_rlp::encode_list(logs, &mut out);
+    keccak256(out)
+}
+
+fn serialize_to<S: Serializer>(to: &Option<Address>, serializer: S) -> Result<S::Ok, S::Error> {
+    match to {
+        Some(address) => address.serialize(serializer),
+        None => serializer.serialize_str(""),
+    }
+}
+
+fn deserialize_to<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Address>, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    if string.is_empty() {
+        Ok(None)
+    } else {
+        string.parse().map(Some).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheState, State},
+        interpreter::opcode,
+        primitives::{address, AccountInfo, Bytecode},
+        InMemoryDB,
+    };
+
+    #[test]
+    fn capture_matches_committed_state() {
+        let caller = address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        let contract = address!("1000000000000000000000000000000000000001");
+        // SSTORE(0, SLOAD(0) + 1), LOG0(0, 0)
+        let code = Bytecode::new_raw(Bytes::from(vec![
+            opcode::PUSH0,
+            opcode::SLOAD,
+            opcode::PUSH1,
+            0x01,
+            opcode::ADD,
+            opcode::PUSH0,
+            opcode::SSTORE,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::LOG0,
+        ]));
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(contract, U256::ZERO, U256::from(41))
+            .unwrap();
+
+        let mut env = Box::<Env>::default();
+        env.block.coinbase = address!("2adc25665018aa1fe0e6bc666dac8fc2697ff9ba");
+        env.block.basefee = U256::from(7);
+        env.block.prevrandao = Some(B256::ZERO);
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Call(contract);
+        env.tx.gas_limit = 100_000;
+        env.tx.gas_price = U256::from(10);
+
+        let (fixture, result) = StateFixture::capture(&db, env.clone(), SpecId::CANCUN).unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(fixture.pre[&contract].storage[&U256::ZERO], U256::from(41));
+        assert_eq!(fixture.pre[&caller].storage, BTreeMap::new());
+        assert!(!fixture.pre.contains_key(&env.block.coinbase));
+
+        // execute on the fixture pre-state and commit, like a state test runner does.
+        let mut cache = CacheState::new(true);
+        for (address, account) in &fixture.pre {
+            let code = Bytecode::new_raw(account.code.clone());
+            let info =
+                AccountInfo::new(account.balance, account.nonce.to(), code.hash_slow(), code);
+            cache.insert_account_with_storage(
+                *address,
+                info,
+                account.storage.iter().map(|(k, v)| (*k, *v)).collect(),
+            );
+        }
+        let mut state = State::builder()
+            .with_cached_prestate(cache)
+            .with_bundle_update()
+            .build();
+        let mut evm = Evm::builder()
+            .with_db(&mut state)
+            .with_env(env)
+            .with_spec_id(SpecId::CANCUN)
+            .build();
+        let committed = evm.transact_commit().unwrap();
+        drop(evm);
+
+        let post = &fixture.post["Cancun"][0];
+        assert_eq!(post.logs, logs_hash(committed.logs()));
+        assert_eq!(post.hash, state_root(state.cache.trie_account()));
+
+        // suite round trips through the GeneralStateTest JSON.
+        let json = serde_json::to_value(fixture.clone().into_suite("captured")).unwrap();
+        assert_eq!(
+            json["captured"]["env"]["currentCoinbase"],
+            "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba"
+        );
+        assert_eq!(
+            json["captured"]["transaction"]["to"],
+            "0x1000000000000000000000000000000000000001"
+        );
+        let suite: BTreeMap<String, StateFixture> = serde_json::from_value(json).unwrap();
+        assert_eq!(suite["captured"], fixture);
+    }
+
+    #[test]
+    fn fork_names() {
+        assert_eq!(fork_name(SpecId::FRONTIER_THAWING), "Frontier");
+        assert_eq!(fork_name(SpecId::MUIR_GLACIER), "Istanbul");
+        assert_eq!(fork_name(SpecId::GRAY_GLACIER), "London");
+        assert_eq!(fork_name(SpecId::CANCUN), "Cancun");
+        assert_eq!(fork_name(SpecId::LATEST), "Prague");
+    }
+}
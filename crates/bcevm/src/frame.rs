@@ -1,12 +1,13 @@
 use crate::{
     interpreter::Interpreter,
-    primitives::{Address, Output},
+    primitives::{Address, FrameGas, Output},
     JournalCheckpoint,
 };
-use core::ops::Range;
 use bcevm_interpreter::{
-    CallOutcome, CreateOutcome, EOFCreateOutcome, Gas, InstructionResult, InterpreterResult,
+    CallOutcome, CreateOutcome, EOFCreateKind, EOFCreateOutcome, Gas, InstructionResult,
+    InterpreterResult,
 };
+use core::ops::Range;
 use std::boxed::Box;
 
 /// Call CallStackFrame.
@@ -34,6 +35,8 @@ pub struct CreateFrame {
 pub struct EOFCreateFrame {
     pub created_address: Address,
     pub return_memory_range: Range<usize>,
+    /// Instruction that created the frame.
+    pub kind: EOFCreateKind,
     pub frame_data: FrameData,
 }
 
@@ -44,6 +47,8 @@ pub struct FrameData {
     pub checkpoint: JournalCheckpoint,
     /// Interpreter.
     pub interpreter: Interpreter,
+    /// Gas used by the calls and creates made by the frame.
+    pub child_gas_used: u64,
 }
 
 /// Call stack frame.
@@ -97,6 +102,16 @@ impl FrameResult {
         }
     }
 
+    /// Returns the gas breakdown of the frame.
+    #[inline]
+    pub fn frame_gas(&self) -> FrameGas {
+        match self {
+            FrameResult::Call(outcome) => outcome.frame_gas(),
+            FrameResult::Create(outcome) => outcome.frame_gas(),
+            FrameResult::EOFCreate(outcome) => outcome.frame_gas(),
+        }
+    }
+
     /// Returns mutable reference to interpreter result.
     #[inline]
     pub fn gas_mut(&mut self) -> &mut Gas {
@@ -154,6 +169,7 @@ impl Frame {
             frame_data: FrameData {
                 checkpoint,
                 interpreter,
+                child_gas_used: 0,
             },
         }))
     }
@@ -168,6 +184,7 @@ impl Frame {
             frame_data: FrameData {
                 checkpoint,
                 interpreter,
+                child_gas_used: 0,
             },
         }))
     }
@@ -241,15 +258,18 @@ impl FrameOrResult {
     pub fn new_eofcreate_frame(
         created_address: Address,
         return_memory_range: Range<usize>,
+        kind: EOFCreateKind,
         checkpoint: JournalCheckpoint,
         interpreter: Interpreter,
     ) -> Self {
         Self::Frame(Frame::EOFCreate(Box::new(EOFCreateFrame {
             created_address,
             return_memory_range,
+            kind,
             frame_data: FrameData {
                 checkpoint,
                 interpreter,
+                child_gas_used: 0,
             },
         })))
     }
@@ -272,10 +292,10 @@ impl FrameOrResult {
         interpreter_result: InterpreterResult,
         address: Option<Address>,
     ) -> Self {
-        FrameOrResult::Result(FrameResult::Create(CreateOutcome {
-            result: interpreter_result,
+        FrameOrResult::Result(FrameResult::Create(CreateOutcome::new(
+            interpreter_result,
             address,
-        }))
+        )))
     }
 
     pub fn new_eofcreate_result(
@@ -283,20 +303,20 @@ impl FrameOrResult {
         address: Address,
         return_memory_range: Range<usize>,
     ) -> Self {
-        FrameOrResult::Result(FrameResult::EOFCreate(EOFCreateOutcome {
-            result: interpreter_result,
+        FrameOrResult::Result(FrameResult::EOFCreate(EOFCreateOutcome::new(
+            interpreter_result,
             address,
             return_memory_range,
-        }))
+        )))
     }
 
     pub fn new_call_result(
         interpreter_result: InterpreterResult,
         memory_offset: Range<usize>,
     ) -> Self {
-        FrameOrResult::Result(FrameResult::Call(CallOutcome {
-            result: interpreter_result,
+        FrameOrResult::Result(FrameResult::Call(CallOutcome::new(
+            interpreter_result,
             memory_offset,
-        }))
+        )))
     }
 }
@@ -0,0 +1,214 @@
+use crate::{
+    interpreter::gas::CALL_STIPEND,
+    primitives::{db::Database, EVMError, ExecutionResult},
+    Evm,
+};
+
+/// Outcome of [Evm::estimate_gas].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GasEstimate {
+    /// Minimal gas limit with which the transaction succeeds.
+    Success {
+        /// Estimated gas limit.
+        gas_limit: u64,
+        /// Result of the execution with the estimated gas limit.
+        result: ExecutionResult,
+    },
+    /// Transaction fails even with the gas limit set in the transaction.
+    Failure(ExecutionResult),
+}
+
+impl GasEstimate {
+    /// Returns estimated gas limit if transaction succeeded.
+    pub fn gas_limit(&self) -> Option<u64> {
+        match self {
+            Self::Success { gas_limit, .. } => Some(*gas_limit),
+            Self::Failure(_) => None,
+        }
+    }
+}
+
+impl<EXT, DB: Database> Evm<'_, EXT, DB> {
+    /// Estimates the minimal gas limit at which the transaction succeeds.
+    ///
+    /// Gas limit of the transaction is used as the upper bound of the search. The transaction
+    /// is first executed with it, then with an optimistic guess of
+    /// `(gas_used + gas_refunded + CALL_STIPEND) * 64 / 63` that accounts for gas retained
+    /// by the 63/64 rule of sub calls, and after that the gas limit is bisected.
+    ///
+    /// State is not committed, so every iteration executes on top of the same state.
+    /// Accounts, storage and code read by the first iteration are kept in the
+    /// [database cache](crate::JournaledState::database_cache) of the journal and are not
+    /// read from the database again. Gas limit of the transaction is restored.
+    pub fn estimate_gas(&mut self) -> Result<GasEstimate, EVMError<DB::Error>> {
+        let gas_limit = self.tx().gas_limit;
+        let journaled_state = &mut self.context.evm.journaled_state;
+        let cached = journaled_state.database_cache.is_some();
+        if !cached {
+            journaled_state.enable_database_cache();
+        }
+        let estimate = self.estimate_gas_inner(gas_limit);
+        if !cached {
+            self.context.evm.journaled_state.disable_database_cache();
+        }
+        self.tx_mut().gas_limit = gas_limit;
+        estimate
+    }
+
+    fn estimate_gas_inner(&mut self, cap: u64) -> Result<GasEstimate, EVMError<DB::Error>> {
+        // invalid transaction errors with the upper bound are returned to the caller.
+        let mut result = self.transact()?.result;
+        let (gas_used, gas_refunded) = match &result {
+            ExecutionResult::Success {
+                gas_used,
+                gas_refunded,
+                ..
+            } => (*gas_used, *gas_refunded),
+            _ => return Ok(GasEstimate::Failure(result)),
+        };
+
+        // gas spent before refunds is a lower bound, anything below it fails.
+        let mut lo = (gas_used + gas_refunded).saturating_sub(1);
+        let mut hi = cap;
+
+        let optimistic = (gas_used + gas_refunded + CALL_STIPEND) * 64 / 63;
+        if optimistic < hi {
+            match self.transact_with_gas_limit(optimistic)? {
+                Some(success @ ExecutionResult::Success { .. }) => {
+                    hi = optimistic;
+                    result = success;
+                }
+                _ => lo = optimistic,
+            }
+        }
+
+        while lo + 1 < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.transact_with_gas_limit(mid)? {
+                Some(success @ ExecutionResult::Success { .. }) => {
+                    hi = mid;
+                    result = success;
+                }
+                _ => lo = mid,
+            }
+        }
+
+        Ok(GasEstimate::Success {
+            gas_limit: hi,
+            result,
+        })
+    }
+
+    /// Executes the transaction with the given gas limit.
+    ///
+    /// Returns `None` if the transaction is invalid with this gas limit.
+    fn transact_with_gas_limit(
+        &mut self,
+        gas_limit: u64,
+    ) -> Result<Option<ExecutionResult>, EVMError<DB::Error>> {
+        self.tx_mut().gas_limit = gas_limit;
+        match self.transact() {
+            Ok(result_and_state) => Ok(Some(result_and_state.result)),
+            Err(EVMError::Transaction(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        interpreter::opcode,
+        primitives::{address, Bytecode, TransactTo},
+    };
+
+    #[test]
+    fn estimate_sub_call_gas() {
+        // If calldata is empty, calls itself with all available gas and one byte of
+        // calldata. Sub call stores a value and reverts the parent if it fails.
+        let code = Bytecode::new_raw(
+            [
+                opcode::CALLDATASIZE,
+                opcode::PUSH1,
+                0x19,
+                opcode::JUMPI,
+                opcode::PUSH1,
+                0x00,
+                opcode::PUSH1,
+                0x00,
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::PUSH1,
+                0x00,
+                opcode::ADDRESS,
+                opcode::GAS,
+                opcode::CALL,
+                opcode::PUSH1,
+                0x1f,
+                opcode::JUMPI,
+                opcode::PUSH1,
+                0x00,
+                opcode::DUP1,
+                opcode::REVERT,
+                opcode::INVALID,
+                opcode::JUMPDEST,
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::JUMPDEST,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+
+        let gas_used = evm.transact().unwrap().result.gas_used();
+        let estimate = evm.estimate_gas().unwrap();
+        let gas_limit = estimate.gas_limit().unwrap();
+        assert_eq!(evm.tx().gas_limit, 1_000_000);
+        // 1/64 of the gas is retained by the caller.
+        assert!(gas_limit > gas_used);
+
+        evm.tx_mut().gas_limit = gas_limit;
+        assert!(evm.transact().unwrap().result.is_success());
+        evm.tx_mut().gas_limit = gas_limit - 1;
+        assert!(!evm.transact().unwrap().result.is_success());
+    }
+
+    #[test]
+    fn estimate_failing_transaction() {
+        let code = Bytecode::new_raw(
+            [opcode::PUSH1, 0x00, opcode::DUP1, opcode::REVERT]
+                .to_vec()
+                .into(),
+        );
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        assert!(matches!(
+            evm.estimate_gas().unwrap(),
+            GasEstimate::Failure(ExecutionResult::Revert { .. })
+        ));
+    }
+}
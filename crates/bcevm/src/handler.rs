@@ -1,7 +1,14 @@
 // Modules.
+#[cfg(feature = "chain-presets")]
+pub mod chain_presets;
+pub mod consortium;
 mod handle_types;
 pub mod mainnet;
+pub mod opcode_hooks;
+#[cfg(feature = "handler-profiler")]
+pub mod profiler;
 pub mod register;
+pub mod system_tx;
 
 // Exports.
 pub use handle_types::*;
@@ -9,7 +16,7 @@ pub use handle_types::*;
 // Includes.
 use crate::{
     interpreter::{opcode::InstructionTables, Host},
-    primitives::{db::Database, spec_to_generic, HandlerCfg, Spec, SpecId},
+    primitives::{db::Database, spec_to_generic, GasScheduleOverrides, HandlerCfg, Spec, SpecId},
     Evm,
 };
 use register::{EvmHandler, HandleRegisters};
@@ -20,13 +27,13 @@ use self::register::{HandleRegister, HandleRegisterBox};
 /// Handler acts as a proxy and allow to define different behavior for different
 /// sections of the code. This allows nice integration of different chains or
 /// to disable some mainnet behavior.
-pub struct Handler<'a, H: Host + 'a, EXT, DB: Database> {
+pub struct Handler<'a, H: Host, EXT, DB: Database> {
     /// Handler configuration.
     pub cfg: HandlerCfg,
     /// Instruction table type.
     pub instruction_table: Option<InstructionTables<'a, H>>,
     /// Registers that will be called on initialization.
-    pub registers: Vec<HandleRegisters<EXT, DB>>,
+    pub registers: Vec<HandleRegisters<'a, EXT, DB>>,
     /// Validity handles.
     pub validation: ValidationHandler<'a, EXT, DB>,
     /// Pre execution handle.
@@ -35,6 +42,8 @@ pub struct Handler<'a, H: Host + 'a, EXT, DB: Database> {
     pub post_execution: PostExecutionHandler<'a, EXT, DB>,
     /// Execution loop that handles frames.
     pub execution: ExecutionHandler<'a, EXT, DB>,
+    /// Opcode gas overrides applied to the instruction table.
+    gas_schedule_overrides: Option<GasScheduleOverrides>,
 }
 
 impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
@@ -42,7 +51,13 @@ impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
     ///
     /// Internally it calls `mainnet_with_spec` with the given spec id.
     /// Or `optimism_with_spec` if the optimism feature is enabled and `cfg.is_optimism` is set.
+    /// If the chain-presets feature is enabled and `cfg.chain` is set, the register of the
+    /// chain preset is appended, see [chain_presets].
     pub fn new(cfg: HandlerCfg) -> Self {
+        #[cfg(feature = "chain-presets")]
+        if let Some(chain) = cfg.chain {
+            return Self::new(HandlerCfg { chain: None, ..cfg }).with_chain_preset(chain);
+        }
         cfg_if::cfg_if! {
             if #[cfg(feature = "optimism")] {
                 if cfg.is_optimism {
@@ -56,16 +71,25 @@ impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
         }
     }
 
+    /// Appends the register of the chain preset, see [chain_presets].
+    #[cfg(feature = "chain-presets")]
+    pub fn with_chain_preset(mut self, chain: crate::primitives::NamedChain) -> Self {
+        self.cfg.chain = Some(chain);
+        self.append_handler_register_plain(chain_presets::chain_handle_register::<DB, EXT>(chain));
+        self
+    }
+
     /// Default handler for Ethereum mainnet.
     pub fn mainnet<SPEC: Spec>() -> Self {
         Self {
             cfg: HandlerCfg::new(SPEC::SPEC_ID),
-            instruction_table: Some(InstructionTables::new_plain::<SPEC>()),
+            instruction_table: Some(InstructionTables::cached::<SPEC>()),
             registers: Vec::new(),
             validation: ValidationHandler::new::<SPEC>(),
             pre_execution: PreExecutionHandler::new::<SPEC>(),
             post_execution: PostExecutionHandler::new::<SPEC>(),
             execution: ExecutionHandler::new::<SPEC>(),
+            gas_schedule_overrides: None,
         }
     }
 
@@ -97,11 +121,50 @@ impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
         spec_to_generic!(spec_id, Self::mainnet::<SPEC>())
     }
 
+    /// Creates mainnet handler with variable spec id and applies the opcode gas overrides to its
+    /// instruction table, see [InstructionTables::apply_gas_schedule_overrides].
+    pub fn mainnet_with_gas_schedule(
+        spec_id: SpecId,
+        overrides: Option<&GasScheduleOverrides>,
+    ) -> Self {
+        let mut handler = Self::mainnet_with_spec(spec_id);
+        if let Some(overrides) = overrides {
+            if let Some(table) = &mut handler.instruction_table {
+                table.apply_gas_schedule_overrides(overrides, spec_id);
+            }
+            handler.gas_schedule_overrides = Some(overrides.clone());
+        }
+        handler
+    }
+
+    /// Handler for zero-fee chains. Basefee is not enforced, gas is neither deducted from
+    /// the caller nor rewarded to the beneficiary, but the transferred value still has to be
+    /// covered by the caller balance. See [consortium] for details.
+    ///
+    /// Behaviour is added as a register, so it is kept when the spec id is changed.
+    pub fn gasless<SPEC: Spec>() -> Self {
+        let mut handler = Self::mainnet::<SPEC>();
+        handler.append_handler_register(HandleRegisters::Plain(
+            consortium::consortium_handle_register::<DB, EXT>,
+        ));
+        handler
+    }
+
+    /// Gasless with spec. Similar to [`Self::mainnet_with_spec`].
+    pub fn gasless_with_spec(spec_id: SpecId) -> Self {
+        spec_to_generic!(spec_id, Self::gasless::<SPEC>())
+    }
+
     /// Specification ID.
     pub fn cfg(&self) -> HandlerCfg {
         self.cfg
     }
 
+    /// Returns the opcode gas overrides applied to the instruction table.
+    pub fn gas_schedule_overrides(&self) -> Option<&GasScheduleOverrides> {
+        self.gas_schedule_overrides.as_ref()
+    }
+
     /// Take instruction table.
     pub fn take_instruction_table(&mut self) -> Option<InstructionTables<'a, Evm<'a, EXT, DB>>> {
         self.instruction_table.take()
@@ -133,7 +196,7 @@ impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
     }
 
     /// Append handle register.
-    pub fn append_handler_register(&mut self, register: HandleRegisters<EXT, DB>) {
+    pub fn append_handler_register(&mut self, register: HandleRegisters<'a, EXT, DB>) {
         register.register(self);
         self.registers.push(register);
     }
@@ -145,17 +208,20 @@ impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
     }
 
     /// Append boxed handle register.
-    pub fn append_handler_register_box(&mut self, register: HandleRegisterBox<EXT, DB>) {
+    pub fn append_handler_register_box(&mut self, register: HandleRegisterBox<'a, EXT, DB>) {
         register(self);
         self.registers.push(HandleRegisters::Box(register));
     }
 
     /// Pop last handle register and reapply all registers that are left.
-    pub fn pop_handle_register(&mut self) -> Option<HandleRegisters<EXT, DB>> {
+    pub fn pop_handle_register(&mut self) -> Option<HandleRegisters<'a, EXT, DB>> {
         let out = self.registers.pop();
         if out.is_some() {
             let registers = core::mem::take(&mut self.registers);
-            let mut base_handler = Handler::mainnet_with_spec(self.cfg.spec_id);
+            let mut base_handler = Handler::mainnet_with_gas_schedule(
+                self.cfg.spec_id,
+                self.gas_schedule_overrides.as_ref(),
+            );
             // apply all registers to default handeler and raw mainnet instruction table.
             for register in registers {
                 base_handler.append_handler_register(register)
@@ -181,10 +247,27 @@ impl<'a, EXT, DB: Database> EvmHandler<'a, EXT, DB> {
         if self.cfg.spec_id == spec_id {
             return;
         }
+        let overrides = self.gas_schedule_overrides.take();
+        self.rebuild(spec_id, overrides.as_ref());
+    }
+
+    /// Applies the opcode gas overrides to the instruction table, overrides that were applied
+    /// before are removed.
+    ///
+    /// Like [Handler::modify_spec_id], the handler is created again if the overrides changed.
+    pub fn set_gas_schedule_overrides(&mut self, overrides: Option<&GasScheduleOverrides>) {
+        if self.gas_schedule_overrides.as_ref() == overrides {
+            return;
+        }
+        self.rebuild(self.cfg.spec_id, overrides);
+    }
 
+    /// Creates the handler again for the spec and the opcode gas overrides and applies the
+    /// registers.
+    fn rebuild(&mut self, spec_id: SpecId, overrides: Option<&GasScheduleOverrides>) {
         let registers = core::mem::take(&mut self.registers);
         // register for optimism is added as a register, so we need to create mainnet handler here.
-        let mut handler = Handler::mainnet_with_spec(spec_id);
+        let mut handler = Handler::mainnet_with_gas_schedule(spec_id, overrides);
         // apply all registers to default handler and raw mainnet instruction table.
         for register in registers {
             handler.append_handler_register(register)
@@ -206,7 +289,7 @@ mod test {
 
     #[test]
     fn test_handler_register_pop() {
-        let register = |inner: &Rc<RefCell<i32>>| -> HandleRegisterBox<(), EmptyDB> {
+        let register = |inner: &Rc<RefCell<i32>>| -> HandleRegisterBox<'static, (), EmptyDB> {
             let inner = inner.clone();
             Box::new(move |h| {
                 *inner.borrow_mut() += 1;
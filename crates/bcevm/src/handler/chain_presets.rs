@@ -0,0 +1,377 @@
+//! Handler presets of chains that diverge from Ethereum mainnet, selected with
+//! [HandlerCfg::for_chain](crate::primitives::HandlerCfg::for_chain).
+//!
+//! Presets capture how the fee of a transaction is collected and the precompiles that are
+//! added by the chain:
+//!
+//! * Polygon PoS: basefee is not burned, it is transferred to the burn contract of the chain.
+//! * BNB Smart Chain: basefee is not burned, the whole fee is transferred to the system
+//!   address and distributed to the validators by the consensus. The light client and BLS
+//!   precompiles of the chain are not included, they can be added with
+//!   [ContextPrecompiles::extend](crate::ContextPrecompiles::extend).
+//! * Arbitrum Nitro: priority fee is dropped, gas is charged and reimbursed at the basefee, and
+//!   the basefee is not burned but credited to the beneficiary, which should be set to the
+//!   network fee account of the chain. The `ArbSys` precompile provides `arbChainID()` and
+//!   `arbBlockNumber()`.
+//!
+//! Refunds, intrinsic gas and the other rules of the Ethereum spec are kept.
+
+use crate::{
+    handler::{
+        register::{EvmHandler, HandleRegister},
+        FeeDistribution,
+    },
+    interpreter::{gas, num_words, Gas},
+    primitives::{
+        address, db::Database, spec_to_generic, Address, Bytes, EVMError, Env, NamedChain,
+        PrecompileError, PrecompileOutput, PrecompileResult, Spec, SpecId, TransactTo, U256,
+    },
+    Context, ContextPrecompile, ContextStatefulPrecompile, InnebcevmContext,
+};
+use std::sync::Arc;
+
+/// Polygon PoS contract that receives the basefee.
+pub const POLYGON_BURN_CONTRACT: Address = address!("70bca57f4579f58670ab2d18ef16e02c17553c38");
+
+/// BNB Smart Chain system address that receives the fee.
+pub const BSC_SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+
+/// Address of the Arbitrum `ArbSys` precompile.
+pub const ARBSYS_ADDRESS: Address = address!("0000000000000000000000000000000000000064");
+
+/// Returns the register of the chain preset.
+pub fn chain_handle_register<DB: Database, EXT>(chain: NamedChain) -> HandleRegister<EXT, DB> {
+    match chain {
+        NamedChain::Polygon => polygon_handle_register::<DB, EXT>,
+        NamedChain::Bsc => bsc_handle_register::<DB, EXT>,
+        NamedChain::Arbitrum => arbitrum_handle_register::<DB, EXT>,
+    }
+}
+
+/// Register that configures the handler for Polygon PoS.
+pub fn polygon_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    spec_to_generic!(handler.cfg.spec_id, {
+        handler.post_execution.calc_fee_distribution =
+            Arc::new(polygon_fee_distribution::<SPEC, EXT, DB>);
+    });
+}
+
+/// Register that configures the handler for BNB Smart Chain.
+pub fn bsc_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    handler.post_execution.calc_fee_distribution = Arc::new(bsc_fee_distribution::<EXT, DB>);
+}
+
+/// Register that configures the handler for Arbitrum Nitro.
+pub fn arbitrum_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    let load_precompiles = handler.pre_execution.load_precompiles.clone();
+    handler.pre_execution.load_precompiles = Arc::new(move || {
+        let mut precompiles = load_precompiles();
+        precompiles.extend([(ARBSYS_ADDRESS, ContextPrecompile::from(ArbSysPrecompile))]);
+        precompiles
+    });
+    spec_to_generic!(handler.cfg.spec_id, {
+        handler.pre_execution.deduct_caller = Arc::new(arbitrum_deduct_caller::<SPEC, EXT, DB>);
+    });
+    handler.post_execution.reimburse_caller = Arc::new(arbitrum_reimburse_caller::<EXT, DB>);
+    handler.post_execution.calc_fee_distribution = Arc::new(arbitrum_fee_distribution::<EXT, DB>);
+}
+
+/// Priority fee goes to the beneficiary and from London the basefee goes to the
+/// [POLYGON_BURN_CONTRACT].
+pub fn polygon_fee_distribution<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    gas: &Gas,
+) -> Result<FeeDistribution, EVMError<DB::Error>> {
+    let env = &context.evm.env;
+    let gas_used = U256::from(gas.spent() - gas.refunded() as u64);
+    let effective_gas_price = env.effective_gas_price();
+    if !SPEC::enabled(SpecId::LONDON) {
+        return Ok(FeeDistribution {
+            recipients: vec![(env.block.coinbase, effective_gas_price * gas_used)],
+        });
+    }
+    let basefee = env.block.basefee.min(effective_gas_price);
+    Ok(FeeDistribution {
+        recipients: vec![
+            (
+                env.block.coinbase,
+                (effective_gas_price - basefee) * gas_used,
+            ),
+            (POLYGON_BURN_CONTRACT, basefee * gas_used),
+        ],
+    })
+}
+
+/// Whole fee goes to the [BSC_SYSTEM_ADDRESS].
+pub fn bsc_fee_distribution<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    gas: &Gas,
+) -> Result<FeeDistribution, EVMError<DB::Error>> {
+    let gas_used = U256::from(gas.spent() - gas.refunded() as u64);
+    Ok(FeeDistribution {
+        recipients: vec![(
+            BSC_SYSTEM_ADDRESS,
+            context.evm.env.effective_gas_price() * gas_used,
+        )],
+    })
+}
+
+/// Deducts the gas limit at the basefee from the caller, the priority fee is not charged.
+pub fn arbitrum_deduct_caller<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    let env = &context.evm.inner.env;
+    let mut gas_cost = U256::from(env.tx.gas_limit).saturating_mul(arbitrum_gas_price(env));
+    // EIP-4844
+    if SPEC::enabled(SpecId::CANCUN) {
+        let data_fee = env.calc_data_fee().expect("already checked");
+        gas_cost = gas_cost.saturating_add(data_fee);
+    }
+
+    let (caller_account, _) = context
+        .evm
+        .inner
+        .journaled_state
+        .load_account(env.tx.caller, &mut context.evm.inner.db)?;
+    caller_account.info.balance = caller_account.info.balance.saturating_sub(gas_cost);
+
+    // bump the nonce for calls. Nonce for CREATE will be bumped in `handle_create`.
+    if matches!(env.tx.transact_to, TransactTo::Call(_)) {
+        caller_account.info.nonce = caller_account.info.nonce.saturating_add(1);
+    }
+
+    // touch account so we know it is changed.
+    caller_account.mark_touch();
+    Ok(())
+}
+
+/// Reimburses the caller with the gas that was not spent, at the basefee.
+pub fn arbitrum_reimburse_caller<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    gas: &Gas,
+) -> Result<(), EVMError<DB::Error>> {
+    let env = &context.evm.inner.env;
+    let reimbursement =
+        arbitrum_gas_price(env) * U256::from(gas.remaining() + gas.refunded() as u64);
+    let (caller_account, _) = context
+        .evm
+        .inner
+        .journaled_state
+        .load_account(env.tx.caller, &mut context.evm.inner.db)?;
+    caller_account.info.balance = caller_account.info.balance.saturating_add(reimbursement);
+    Ok(())
+}
+
+/// Whole fee, paid at the basefee, goes to the beneficiary.
+pub fn arbitrum_fee_distribution<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    gas: &Gas,
+) -> Result<FeeDistribution, EVMError<DB::Error>> {
+    let gas_used = U256::from(gas.spent() - gas.refunded() as u64);
+    let env = &context.evm.env;
+    Ok(FeeDistribution {
+        recipients: vec![(env.block.coinbase, arbitrum_gas_price(env) * gas_used)],
+    })
+}
+
+/// Returns the gas price the transaction pays, the priority fee is dropped so it is capped at
+/// the basefee.
+fn arbitrum_gas_price(env: &Env) -> U256 {
+    env.block.basefee.min(env.effective_gas_price())
+}
+
+/// Arbitrum `ArbSys` precompile, only `arbChainID()` and `arbBlockNumber()` are supported.
+///
+/// As in Nitro, the gas cost is the copy cost of the input and the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArbSysPrecompile;
+
+impl ArbSysPrecompile {
+    /// Selector of `arbChainID()`.
+    pub const ARB_CHAIN_ID: [u8; 4] = [0xd1, 0x27, 0xf5, 0x4a];
+    /// Selector of `arbBlockNumber()`.
+    pub const ARB_BLOCK_NUMBER: [u8; 4] = [0xa3, 0xb1, 0xb3, 0x1d];
+}
+
+impl<DB: Database> From<ArbSysPrecompile> for ContextPrecompile<DB> {
+    fn from(precompile: ArbSysPrecompile) -> Self {
+        ContextPrecompile::ContextStateful(Arc::new(precompile))
+    }
+}
+
+impl<DB: Database> ContextStatefulPrecompile<DB> for ArbSysPrecompile {
+    fn call(
+        &self,
+        input: &Bytes,
+        gas_limit: u64,
+        context: &mut InnebcevmContext<DB>,
+    ) -> PrecompileResult {
+        let value = match input.get(..4) {
+            Some(selector) if selector == Self::ARB_CHAIN_ID => {
+                U256::from(context.env.cfg.chain_id)
+            }
+            Some(selector) if selector == Self::ARB_BLOCK_NUMBER => context.env.block.number,
+            _ => return Err(PrecompileError::other("unsupported ArbSys method")),
+        };
+        let gas_used = gas::COPY * (num_words(input.len() as u64) + 1);
+        if gas_used > gas_limit {
+            return Err(PrecompileError::OutOfGas);
+        }
+        Ok(PrecompileOutput::new(
+            gas_used,
+            value.to_be_bytes_vec().into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{
+            keccak256, AccountInfo, Bytecode, ExecutionResult, HandlerCfg, Output, State,
+            TransactTo,
+        },
+        Evm,
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000001");
+    const COINBASE: Address = address!("1000000000000000000000000000000000000002");
+
+    /// Executes a transfer with basefee 10 and priority fee 2 on the chain, with gas left to
+    /// reimburse.
+    fn transact(chain: NamedChain) -> (State, u64) {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            CALLER,
+            AccountInfo::from_balance(U256::from(10_000_000_000u64)),
+        );
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_cfg_env(|cfg| cfg.chain_id = chain.chain_id())
+            .modify_block_env(|block| {
+                block.coinbase = COINBASE;
+                block.basefee = U256::from(10);
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TransactTo::Call(Address::ZERO);
+                tx.gas_limit = 30_000;
+                tx.gas_price = U256::from(12);
+                tx.gas_priority_fee = Some(U256::from(2));
+            })
+            .with_handler_cfg(HandlerCfg::for_chain(chain))
+            .build();
+        assert_eq!(evm.handler.cfg().chain, Some(chain));
+        let result = evm.transact().unwrap();
+        // the transaction is not modified by the preset.
+        assert_eq!(evm.tx().gas_price, U256::from(12));
+        assert_eq!(evm.tx().gas_priority_fee, Some(U256::from(2)));
+        (result.state, result.result.gas_used())
+    }
+
+    fn balance(state: &State, address: Address) -> U256 {
+        state
+            .get(&address)
+            .map(|account| account.info.balance)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn fee_distribution() {
+        let (state, gas_used) = transact(NamedChain::Polygon);
+        assert_eq!(gas_used, 21_000);
+        assert_eq!(balance(&state, COINBASE), U256::from(2 * 21_000));
+        assert_eq!(
+            balance(&state, POLYGON_BURN_CONTRACT),
+            U256::from(10 * 21_000)
+        );
+
+        let (state, _) = transact(NamedChain::Bsc);
+        assert_eq!(balance(&state, COINBASE), U256::ZERO);
+        assert_eq!(balance(&state, BSC_SYSTEM_ADDRESS), U256::from(12 * 21_000));
+
+        // priority fee is not charged.
+        let (state, _) = transact(NamedChain::Arbitrum);
+        assert_eq!(balance(&state, COINBASE), U256::from(10 * 21_000));
+        assert_eq!(
+            balance(&state, CALLER),
+            U256::from(10_000_000_000u64 - 10 * 21_000)
+        );
+    }
+
+    #[test]
+    fn preset_survives_spec_change() {
+        let mut evm = Evm::builder()
+            .with_handler_cfg(HandlerCfg::for_chain(NamedChain::Bsc))
+            .build();
+        evm.modify_spec_id(SpecId::SHANGHAI);
+        assert_eq!(evm.handler.cfg().chain, Some(NamedChain::Bsc));
+        assert_eq!(evm.handler.registers.len(), 1);
+    }
+
+    #[test]
+    fn arbsys() {
+        assert_eq!(
+            ArbSysPrecompile::ARB_CHAIN_ID,
+            keccak256("arbChainID()")[..4]
+        );
+        assert_eq!(
+            ArbSysPrecompile::ARB_BLOCK_NUMBER,
+            keccak256("arbBlockNumber()")[..4]
+        );
+
+        // STATICCALL(gas, ArbSys, 0, 4, 0, 32) with `arbChainID()` in memory, returns the word.
+        let mut code = vec![opcode::PUSH4];
+        code.extend(ArbSysPrecompile::ARB_CHAIN_ID);
+        code.extend([
+            opcode::PUSH1,
+            0xe0,
+            opcode::SHL,
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            32,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            4,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            0x64,
+            opcode::GAS,
+            opcode::STATICCALL,
+            opcode::POP,
+            opcode::PUSH1,
+            32,
+            opcode::PUSH0,
+            opcode::RETURN,
+        ]);
+        let code = Bytecode::new_raw(code.into());
+        let mut db = CacheDB::new(EmptyDB::default());
+        let contract = address!("2000000000000000000000000000000000000000");
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_cfg_env(|cfg| cfg.chain_id = NamedChain::Arbitrum.chain_id())
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_price = U256::ZERO;
+            })
+            .with_handler_cfg(HandlerCfg::for_chain(NamedChain::Arbitrum))
+            .build();
+        let ExecutionResult::Success {
+            output: Output::Call(output),
+            ..
+        } = evm.transact().unwrap().result
+        else {
+            panic!("expected successful call");
+        };
+        assert_eq!(U256::from_be_slice(&output), U256::from(42161));
+    }
+}
@@ -0,0 +1,224 @@
+//! Handler preset for zero-fee private and consortium chains.
+//!
+//! Transactions on such chains do not pay for gas: basefee is not enforced, gas is neither
+//! deducted from the caller nor rewarded to the beneficiary, senders with deployed code are
+//! accepted (EIP-3607 is disabled). The caller still has to be able to cover the value it
+//! transfers.
+
+use crate::{
+    handler::{register::EvmHandler, FeeDistribution},
+    interpreter::Gas,
+    primitives::{
+        db::Database, spec_to_generic, EVMError, Env, InvalidTransaction, Spec, SpecId, TransactTo,
+    },
+    Context,
+};
+use std::{boxed::Box, sync::Arc};
+
+/// Register that configures the handler for zero-fee consortium chains.
+pub fn consortium_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    spec_to_generic!(handler.cfg.spec_id, {
+        handler.validation.env = Arc::new(validate_env::<SPEC, DB>);
+        handler.validation.tx_against_state = Arc::new(validate_tx_against_state::<EXT, DB>);
+        handler.pre_execution.deduct_caller = Arc::new(deduct_caller::<EXT, DB>);
+        handler.post_execution.reimburse_caller = Arc::new(reimburse_caller::<EXT, DB>);
+        handler.post_execution.reward_beneficiary = Arc::new(reward_beneficiary::<EXT, DB>);
+    });
+}
+
+/// Validate environment without enforcing the basefee.
+///
+/// Chain id is still validated by the separate chain id handle.
+pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<DB::Error>> {
+    // Important: validate block before tx.
+    env.validate_block_env::<SPEC>()?;
+    env.validate_tx_without_base_fee::<SPEC>()?;
+    Ok(())
+}
+
+/// Nonce is validated by the nonce policy handle, otherwise only the caller balance is
+/// validated against the transferred value.
+///
+/// Senders with deployed code are accepted.
+pub fn validate_tx_against_state<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    let tx_caller = context.evm.env.tx.caller;
+    let (caller_account, _) = context
+        .evm
+        .inner
+        .journaled_state
+        .load_account(tx_caller, &mut context.evm.inner.db)?;
+
+    let value = context.evm.inner.env.tx.value;
+    if caller_account.info.balance < value {
+        return Err(InvalidTransaction::LackOfFundForMaxFee {
+            fee: Box::new(value),
+            balance: Box::new(caller_account.info.balance),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Bumps the caller nonce without deducting any gas cost.
+pub fn deduct_caller<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    let (caller_account, _) = context
+        .evm
+        .inner
+        .journaled_state
+        .load_account(context.evm.inner.env.tx.caller, &mut context.evm.inner.db)?;
+
+    // bump the nonce for calls. Nonce for CREATE will be bumped in `handle_create`.
+    if matches!(context.evm.inner.env.tx.transact_to, TransactTo::Call(_)) {
+        caller_account.info.nonce = caller_account.info.nonce.saturating_add(1);
+    }
+
+    // touch account so we know it is changed.
+    caller_account.mark_touch();
+    Ok(())
+}
+
+/// Nothing was deducted from the caller, so nothing is reimbursed.
+pub fn reimburse_caller<EXT, DB: Database>(
+    _context: &mut Context<EXT, DB>,
+    _gas: &Gas,
+) -> Result<(), EVMError<DB::Error>> {
+    Ok(())
+}
+
+/// Fees are not collected, so beneficiary is not rewarded.
+pub fn reward_beneficiary<EXT, DB: Database>(
+    _context: &mut Context<EXT, DB>,
+    _gas: &Gas,
+    _distribution: &FeeDistribution,
+) -> Result<(), EVMError<DB::Error>> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{BenchmarkDB, CacheDB, EmptyDB},
+        primitives::{address, AccountInfo, Bytecode, U256},
+        Evm, Handler,
+    };
+
+    #[test]
+    fn zero_fee_transaction() {
+        let caller = address!("1000000000000000000000000000000000000000");
+        let recipient = address!("2000000000000000000000000000000000000000");
+        let coinbase = address!("3000000000000000000000000000000000000000");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        // caller has code, which is rejected by EIP-3607 on mainnet.
+        let code = Bytecode::new_raw([0x00].to_vec().into());
+        db.insert_account_info(
+            caller,
+            AccountInfo::new(U256::from(100), 0, code.hash_slow(), code),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_block_env(|block| {
+                block.basefee = U256::from(10);
+                block.coinbase = coinbase;
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(recipient);
+                tx.value = U256::from(100);
+                tx.gas_price = U256::ZERO;
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(consortium_handle_register)
+            .build();
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(result.state[&caller].info.balance, U256::ZERO);
+        assert_eq!(result.state[&caller].info.nonce, 1);
+        assert_eq!(result.state[&recipient].info.balance, U256::from(100));
+        assert_eq!(
+            result
+                .state
+                .get(&coinbase)
+                .map_or(U256::ZERO, |acc| acc.info.balance),
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn unfunded_value_transfer() {
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("2000000000000000000000000000000000000000"));
+                tx.value = U256::from(100);
+                tx.gas_price = U256::ZERO;
+            })
+            .append_handler_register(consortium_handle_register)
+            .build();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::LackOfFundForMaxFee {
+                fee: Box::new(U256::from(100)),
+                balance: Box::new(U256::ZERO),
+            })
+        );
+    }
+
+    #[test]
+    fn nonce_is_validated() {
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.nonce = Some(1);
+            })
+            .append_handler_register(consortium_handle_register)
+            .build();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::NonceTooHigh { tx: 1, state: 0 })
+        );
+    }
+
+    #[test]
+    fn gasless_handler_preset() {
+        let caller = address!("1000000000000000000000000000000000000000");
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .modify_block_env(|block| block.basefee = U256::from(10))
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.gas_price = U256::ZERO;
+                tx.gas_limit = 100_000;
+            })
+            .with_handler(Handler::gasless_with_spec(SpecId::SHANGHAI))
+            .build();
+        // preset is kept when the spec is changed.
+        evm.modify_spec_id(SpecId::CANCUN);
+        assert_eq!(evm.spec_id(), SpecId::CANCUN);
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(result.state[&caller].info.nonce, 1);
+
+        // value is not covered by the gasless preset.
+        evm.tx_mut().value = U256::from(1);
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::LackOfFundForMaxFee {
+                fee: Box::new(U256::from(1)),
+                balance: Box::new(U256::ZERO),
+            })
+        );
+    }
+}
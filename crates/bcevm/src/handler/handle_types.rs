@@ -8,7 +8,8 @@ pub mod validation;
 // Exports
 
 pub use validation::{
-    ValidateEnvHandle, ValidateInitialTxGasHandle, ValidateTxEnvAgainstState, ValidationHandler,
+    ValidateChainIdHandle, ValidateEnvHandle, ValidateInitialTxGasHandle,
+    ValidateTxEnvAgainstState, ValidateTxNonceHandle, ValidationHandler,
 };
 
 pub use execution::{
@@ -21,5 +22,6 @@ pub use pre_execution::{
 };
 
 pub use post_execution::{
-    EndHandle, OutputHandle, PostExecutionHandler, ReimburseCallerHandle, RewardBeneficiaryHandle,
+    CalcFeeDistributionHandle, EndHandle, FeeDistribution, OutputHandle, PostExecutionHandler,
+    ReimburseCallerHandle, RewardBeneficiaryHandle,
 };
@@ -2,17 +2,42 @@
 use crate::{
     handler::mainnet,
     interpreter::Gas,
-    primitives::{db::Database, EVMError, EVMResultGeneric, ResultAndState, Spec},
+    primitives::{db::Database, Address, EVMError, EVMResultGeneric, ResultAndState, Spec, U256},
     Context, FrameResult,
 };
-use std::sync::Arc;
+use std::{sync::Arc, vec::Vec};
+
+/// Distribution of the fee paid for the used gas.
+///
+/// Part of the fee that is not distributed to the recipients is burned.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeDistribution {
+    /// Recipients of the fee with the amount they receive.
+    pub recipients: Vec<(Address, U256)>,
+}
 
 /// Reimburse the caller with ethereum it didn't spent.
 pub type ReimburseCallerHandle<'a, EXT, DB> =
     Arc<dyn Fn(&mut Context<EXT, DB>, &Gas) -> EVMResultGeneric<(), <DB as Database>::Error> + 'a>;
 
-/// Reward beneficiary with transaction rewards.
-pub type RewardBeneficiaryHandle<'a, EXT, DB> = ReimburseCallerHandle<'a, EXT, DB>;
+/// Calculates how the fee of the used gas is distributed.
+pub type CalcFeeDistributionHandle<'a, EXT, DB> = Arc<
+    dyn Fn(
+            &mut Context<EXT, DB>,
+            &Gas,
+        ) -> EVMResultGeneric<FeeDistribution, <DB as Database>::Error>
+        + 'a,
+>;
+
+/// Reward beneficiary with transaction rewards, distributed by [CalcFeeDistributionHandle].
+pub type RewardBeneficiaryHandle<'a, EXT, DB> = Arc<
+    dyn Fn(
+            &mut Context<EXT, DB>,
+            &Gas,
+            &FeeDistribution,
+        ) -> EVMResultGeneric<(), <DB as Database>::Error>
+        + 'a,
+>;
 
 /// Main return handle, takes state from journal and transforms internal result to external.
 pub type OutputHandle<'a, EXT, DB> = Arc<
@@ -43,6 +68,8 @@ pub type ClearHandle<'a, EXT, DB> = Arc<dyn Fn(&mut Context<EXT, DB>) + 'a>;
 pub struct PostExecutionHandler<'a, EXT, DB: Database> {
     /// Reimburse the caller with ethereum it didn't spent.
     pub reimburse_caller: ReimburseCallerHandle<'a, EXT, DB>,
+    /// Calculates the distribution of the caller fee, London rules by default.
+    pub calc_fee_distribution: CalcFeeDistributionHandle<'a, EXT, DB>,
     /// Reward the beneficiary with caller fee.
     pub reward_beneficiary: RewardBeneficiaryHandle<'a, EXT, DB>,
     /// Main return handle, returns the output of the transact.
@@ -61,7 +88,8 @@ impl<'a, EXT: 'a, DB: Database + 'a> PostExecutionHandler<'a, EXT, DB> {
     pub fn new<SPEC: Spec + 'a>() -> Self {
         Self {
             reimburse_caller: Arc::new(mainnet::reimburse_caller::<SPEC, EXT, DB>),
-            reward_beneficiary: Arc::new(mainnet::reward_beneficiary::<SPEC, EXT, DB>),
+            calc_fee_distribution: Arc::new(mainnet::calc_fee_distribution::<SPEC, EXT, DB>),
+            reward_beneficiary: Arc::new(mainnet::reward_beneficiary::<EXT, DB>),
             output: Arc::new(mainnet::output::<EXT, DB>),
             end: Arc::new(mainnet::end::<EXT, DB>),
             clear: Arc::new(mainnet::clear::<EXT, DB>),
@@ -78,13 +106,23 @@ impl<'a, EXT, DB: Database> PostExecutionHandler<'a, EXT, DB> {
     ) -> Result<(), EVMError<DB::Error>> {
         (self.reimburse_caller)(context, gas)
     }
-    /// Reward beneficiary
+    /// Calculate the fee distribution.
+    pub fn calc_fee_distribution(
+        &self,
+        context: &mut Context<EXT, DB>,
+        gas: &Gas,
+    ) -> Result<FeeDistribution, EVMError<DB::Error>> {
+        (self.calc_fee_distribution)(context, gas)
+    }
+
+    /// Reward beneficiary with the calculated fee distribution.
     pub fn reward_beneficiary(
         &self,
         context: &mut Context<EXT, DB>,
         gas: &Gas,
     ) -> Result<(), EVMError<DB::Error>> {
-        (self.reward_beneficiary)(context, gas)
+        let distribution = self.calc_fee_distribution(context, gas)?;
+        (self.reward_beneficiary)(context, gas, &distribution)
     }
 
     /// Returns the output of transaction.
@@ -15,6 +15,18 @@ pub type LoadPrecompilesHandle<'a, DB> = Arc<dyn Fn() -> ContextPrecompiles<DB>
 pub type LoadAccountsHandle<'a, EXT, DB> =
     Arc<dyn Fn(&mut Context<EXT, DB>) -> Result<(), EVMError<<DB as Database>::Error>> + 'a>;
 
+/// EIP-2935: Stores hash of the parent block into the history storage contract.
+///
+/// Called once per block by [Evm::apply_block_system_calls](crate::Evm::apply_block_system_calls).
+pub type StoreBlockHashHandle<'a, EXT, DB> =
+    Arc<dyn Fn(&mut Context<EXT, DB>) -> Result<(), EVMError<<DB as Database>::Error>> + 'a>;
+
+/// EIP-4788: Stores root of the parent beacon block into the beacon roots contract.
+///
+/// Called once per block by [Evm::apply_block_system_calls](crate::Evm::apply_block_system_calls).
+pub type ApplyBeaconRootHandle<'a, EXT, DB> =
+    Arc<dyn Fn(&mut Context<EXT, DB>) -> Result<(), EVMError<<DB as Database>::Error>> + 'a>;
+
 /// Deduct the caller to its limit.
 pub type DeductCallerHandle<'a, EXT, DB> =
     Arc<dyn Fn(&mut Context<EXT, DB>) -> EVMResultGeneric<(), <DB as Database>::Error> + 'a>;
@@ -25,6 +37,10 @@ pub struct PreExecutionHandler<'a, EXT, DB: Database> {
     pub load_precompiles: LoadPrecompilesHandle<'a, DB>,
     /// Main load handle
     pub load_accounts: LoadAccountsHandle<'a, EXT, DB>,
+    /// Store parent block hash into the history storage contract.
+    pub store_block_hash: StoreBlockHashHandle<'a, EXT, DB>,
+    /// Store parent beacon block root into the beacon roots contract.
+    pub apply_beacon_root: ApplyBeaconRootHandle<'a, EXT, DB>,
     /// Deduct max value from the caller.
     pub deduct_caller: DeductCallerHandle<'a, EXT, DB>,
 }
@@ -35,6 +51,8 @@ impl<'a, EXT: 'a, DB: Database + 'a> PreExecutionHandler<'a, EXT, DB> {
         Self {
             load_precompiles: Arc::new(mainnet::load_precompiles::<SPEC, DB>),
             load_accounts: Arc::new(mainnet::load_accounts::<SPEC, EXT, DB>),
+            store_block_hash: Arc::new(mainnet::store_block_hash::<SPEC, EXT, DB>),
+            apply_beacon_root: Arc::new(mainnet::apply_beacon_root::<SPEC, EXT, DB>),
             deduct_caller: Arc::new(mainnet::deduct_caller::<SPEC, EXT, DB>),
         }
     }
@@ -51,6 +69,22 @@ impl<'a, EXT, DB: Database> PreExecutionHandler<'a, EXT, DB> {
         (self.load_accounts)(context)
     }
 
+    /// Store parent block hash
+    pub fn store_block_hash(
+        &self,
+        context: &mut Context<EXT, DB>,
+    ) -> Result<(), EVMError<DB::Error>> {
+        (self.store_block_hash)(context)
+    }
+
+    /// Store parent beacon block root
+    pub fn apply_beacon_root(
+        &self,
+        context: &mut Context<EXT, DB>,
+    ) -> Result<(), EVMError<DB::Error>> {
+        (self.apply_beacon_root)(context)
+    }
+
     /// Load precompiles
     pub fn load_precompiles(&self) -> ContextPrecompiles<DB> {
         (self.load_precompiles)()
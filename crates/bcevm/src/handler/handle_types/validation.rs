@@ -14,6 +14,20 @@ pub type ValidateEnvHandle<'a, DB> =
 pub type ValidateTxEnvAgainstState<'a, EXT, DB> =
     Arc<dyn Fn(&mut Context<EXT, DB>) -> Result<(), EVMError<<DB as Database>::Error>> + 'a>;
 
+/// Handle that validates nonce of the transaction against the state.
+///
+/// Can be replaced by chains with custom nonce rules, e.g. 2D nonces or transactions
+/// without nonce. It is called before [ValidateTxEnvAgainstState].
+pub type ValidateTxNonceHandle<'a, EXT, DB> =
+    Arc<dyn Fn(&mut Context<EXT, DB>) -> Result<(), EVMError<<DB as Database>::Error>> + 'a>;
+
+/// Handle that validates chain id of the transaction.
+///
+/// Can be replaced to disable or customize the check, e.g. to simulate
+/// transactions signed for a different chain.
+pub type ValidateChainIdHandle<'a, DB> =
+    Arc<dyn Fn(&Env) -> Result<(), EVMError<<DB as Database>::Error>> + 'a>;
+
 /// Initial gas calculation handle
 pub type ValidateInitialTxGasHandle<'a, DB> =
     Arc<dyn Fn(&Env) -> Result<u64, EVMError<<DB as Database>::Error>> + 'a>;
@@ -24,8 +38,12 @@ pub struct ValidationHandler<'a, EXT, DB: Database> {
     pub initial_tx_gas: ValidateInitialTxGasHandle<'a, DB>,
     /// Validate transactions against state data.
     pub tx_against_state: ValidateTxEnvAgainstState<'a, EXT, DB>,
+    /// Validate transaction nonce against the state.
+    pub tx_nonce_policy: ValidateTxNonceHandle<'a, EXT, DB>,
     /// Validate Env.
     pub env: ValidateEnvHandle<'a, DB>,
+    /// Validate transaction chain id.
+    pub chain_id: ValidateChainIdHandle<'a, DB>,
 }
 
 impl<'a, EXT: 'a, DB: Database + 'a> ValidationHandler<'a, EXT, DB> {
@@ -34,15 +52,18 @@ impl<'a, EXT: 'a, DB: Database + 'a> ValidationHandler<'a, EXT, DB> {
         Self {
             initial_tx_gas: Arc::new(mainnet::validate_initial_tx_gas::<SPEC, DB>),
             env: Arc::new(mainnet::validate_env::<SPEC, DB>),
+            chain_id: Arc::new(mainnet::validate_chain_id::<DB>),
             tx_against_state: Arc::new(mainnet::validate_tx_against_state::<SPEC, EXT, DB>),
+            tx_nonce_policy: Arc::new(mainnet::validate_tx_nonce::<EXT, DB>),
         }
     }
 }
 
 impl<'a, EXT, DB: Database> ValidationHandler<'a, EXT, DB> {
-    /// Validate env.
+    /// Validate env and chain id of the transaction.
     pub fn env(&self, env: &Env) -> Result<(), EVMError<DB::Error>> {
-        (self.env)(env)
+        (self.env)(env)?;
+        (self.chain_id)(env)
     }
 
     /// Initial gas
@@ -50,11 +71,12 @@ impl<'a, EXT, DB: Database> ValidationHandler<'a, EXT, DB> {
         (self.initial_tx_gas)(env)
     }
 
-    /// Validate ttansaction against the state.
+    /// Validate ttansaction nonce and the transaction against the state.
     pub fn tx_against_state(
         &self,
         context: &mut Context<EXT, DB>,
     ) -> Result<(), EVMError<DB::Error>> {
+        (self.tx_nonce_policy)(context)?;
         (self.tx_against_state)(context)
     }
 }
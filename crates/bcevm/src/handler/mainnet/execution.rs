@@ -5,7 +5,7 @@ use crate::{
         return_ok, return_revert, CallInputs, CreateInputs, CreateOutcome, Gas, InstructionResult,
         SharedMemory,
     },
-    primitives::{EVMError, Env, Spec, SpecId},
+    primitives::{EVMError, Env, Spec, SpecId, Warning},
     CallFrame, Context, CreateFrame, Frame, FrameOrResult, FrameResult,
 };
 use bcevm_interpreter::{CallOutcome, EOFCreateInput, EOFCreateOutcome, InterpreterResult};
@@ -53,7 +53,17 @@ pub fn last_frame_return<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     frame_result: &mut FrameResult,
 ) -> Result<(), EVMError<DB::Error>> {
+    let refunded = frame_result.gas().refunded() as u64;
     frame_return_with_refund_flag::<SPEC>(&context.evm.env, frame_result, true);
+
+    // EIP-3529: Reduction in refunds
+    let capped = frame_result.gas().refunded() as u64;
+    if SPEC::enabled(SpecId::LONDON)
+        && matches!(frame_result.interpreter_result().result, return_ok!())
+        && capped < refunded
+    {
+        context.evm.warn(Warning::RefundCapped { refunded, capped });
+    }
     Ok(())
 }
 
@@ -75,10 +85,10 @@ pub fn call_return<EXT, DB: Database>(
     context
         .evm
         .call_return(&interpreter_result, frame.frame_data.checkpoint);
-    Ok(CallOutcome::new(
-        interpreter_result,
-        frame.return_memory_range,
-    ))
+    Ok(
+        CallOutcome::new(interpreter_result, frame.return_memory_range)
+            .with_child_gas_used(frame.frame_data.child_gas_used),
+    )
 }
 
 #[inline]
@@ -116,10 +126,10 @@ pub fn create_return<SPEC: Spec, EXT, DB: Database>(
         frame.created_address,
         frame.frame_data.checkpoint,
     );
-    Ok(CreateOutcome::new(
-        interpreter_result,
-        Some(frame.created_address),
-    ))
+    Ok(
+        CreateOutcome::new(interpreter_result, Some(frame.created_address))
+            .with_child_gas_used(frame.frame_data.child_gas_used),
+    )
 }
 
 #[inline]
@@ -160,7 +170,8 @@ pub fn eofcreate_return<SPEC: Spec, EXT, DB: Database>(
         interpreter_result,
         frame.created_address,
         frame.return_memory_range,
-    ))
+    )
+    .with_child_gas_used(frame.frame_data.child_gas_used))
 }
 
 #[inline]
@@ -180,7 +191,7 @@ pub fn insert_eofcreate_outcome<EXT, DB: Database>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bcevm_interpreter::primitives::CancunSpec;
+    use bcevm_interpreter::primitives::{CancunSpec, ExecutionResult, U256};
     use bcevm_precompile::Bytes;
 
     /// Creates frame result.
@@ -225,6 +236,205 @@ mod tests {
         assert_eq!(gas.refunded(), 0);
     }
 
+    #[test]
+    fn test_memory_limit() {
+        use crate::{
+            db::BenchmarkDB,
+            interpreter::opcode,
+            primitives::{address, Bytecode, HaltContext, HaltReason, OutOfGasError, TransactTo},
+            Evm,
+        };
+
+        // MSTORE(0x400, 0)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH0,
+                opcode::PUSH2,
+                0x04,
+                0x00,
+                opcode::MSTORE,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+
+        evm.cfg_mut().memory_limit = 0x400;
+        assert_eq!(
+            evm.transact().unwrap().result,
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::MemoryLimit),
+                gas_used: 100_000,
+                context: Some(HaltContext {
+                    address: address!("0000000000000000000000000000000000000000"),
+                    pc: 4,
+                    opcode: opcode::MSTORE,
+                }),
+            }
+        );
+
+        // limit of a single frame.
+        evm.cfg_mut().memory_limit = u64::MAX;
+        evm.cfg_mut().frame_memory_limit = Some(0x420);
+        assert!(evm.transact().unwrap().result.is_success());
+        evm.cfg_mut().frame_memory_limit = Some(0x400);
+        assert!(matches!(
+            evm.transact().unwrap().result,
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::MemoryLimit),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_gas_schedule_overrides() {
+        use crate::{
+            db::BenchmarkDB,
+            interpreter::opcode,
+            primitives::{address, Bytecode, GasScheduleOverrides, TransactTo},
+            Evm,
+        };
+
+        // SLOAD(0)
+        let code = Bytecode::new_raw(
+            [opcode::PUSH1, 0x00, opcode::SLOAD, opcode::STOP]
+                .to_vec()
+                .into(),
+        );
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        // PUSH1 and cold SLOAD.
+        assert_eq!(evm.transact().unwrap().result.gas_used(), 21_000 + 3 + 2100);
+
+        evm.cfg_mut().gas_schedule_overrides = Some(
+            GasScheduleOverrides::new()
+                .with_cost(opcode::SLOAD, 800)
+                .with_cost(opcode::PUSH1, 1),
+        );
+        evm.enable_metrics();
+        // override replaces the warm cost, the cold access surcharge is still charged.
+        assert_eq!(
+            evm.transact().unwrap().result.gas_used(),
+            21_000 + 1 + 800 + 2000
+        );
+        assert_eq!(evm.take_metrics().unwrap().cold_access_charges, 1);
+        assert!(evm.handler.gas_schedule_overrides().is_some());
+
+        // instruction table is built again when the overrides change.
+        evm.cfg_mut().gas_schedule_overrides = None;
+        assert_eq!(evm.transact().unwrap().result.gas_used(), 21_000 + 3 + 2100);
+        assert_eq!(evm.take_metrics().unwrap().cold_access_charges, 1);
+    }
+
+    /// Executes EOF code that calls an empty account with `EXTCALL` and the given value,
+    /// and returns the call status.
+    fn eof_extcall(value: u8, gas_limit: u64) -> (ExecutionResult, U256) {
+        use crate::{
+            db::{CacheDB, EmptyDB},
+            interpreter::opcode,
+            primitives::{
+                address,
+                eof::{EofBody, TypesSection},
+                AccountInfo, Bytecode, SpecId, TransactTo,
+            },
+            Evm,
+        };
+
+        let contract = address!("2000000000000000000000000000000000000000");
+        let callee = address!("3000000000000000000000000000000000000000");
+        let mut code = vec![
+            opcode::PUSH1,
+            value,
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+        ];
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(callee.as_slice());
+        code.extend([
+            opcode::EXTCALL,
+            opcode::PUSH1,
+            0x00,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH1,
+            0x00,
+            opcode::RETURN,
+        ]);
+        let eof = EofBody {
+            types_section: vec![TypesSection {
+                inputs: 0,
+                outputs: 0x80,
+                max_stack_size: 4,
+            }],
+            code_section: vec![code.into()],
+            is_data_filled: true,
+            ..Default::default()
+        }
+        .into_eof();
+
+        let bytecode = Bytecode::Eof(eof);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::from(1), 0, bytecode.hash_slow(), bytecode),
+        );
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::EXPERIMENTAL)
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = gas_limit;
+            })
+            .build();
+        let result = evm.transact().unwrap().result;
+        let status = U256::from_be_slice(result.output().unwrap());
+        (result, status)
+    }
+
+    #[test]
+    fn test_eof_extcall_gas() {
+        // pushes, cold account access and returning of the status.
+        let base = 21_000 + 4 * 3 + 2600 + 15;
+        let (result, status) = eof_extcall(0, 100_000);
+        assert_eq!(status, U256::ZERO);
+        assert_eq!(result.gas_used(), base);
+
+        // value transfer and new account.
+        let (result, status) = eof_extcall(1, 100_000);
+        assert_eq!(status, U256::ZERO);
+        assert_eq!(result.gas_used(), base + 9000 + 25000);
+    }
+
+    #[test]
+    fn test_eof_extcall_light_failure() {
+        // callee would get less than the minimal callee gas.
+        let (result, status) = eof_extcall(0, 21_000 + 4 * 3 + 2600 + 7200);
+        assert!(result.is_success());
+        assert_eq!(status, U256::from(1));
+    }
+
     #[test]
     fn test_revert_gas() {
         let gas = call_last_frame_return(InstructionResult::Revert, Gas::new(90));
@@ -1,57 +1,112 @@
 use crate::{
+    handler::FeeDistribution,
     interpreter::{Gas, SuccessOrHalt},
     primitives::{
         db::Database, EVMError, ExecutionResult, ResultAndState, Spec, SpecId::LONDON, U256,
     },
-    Context, FrameResult,
+    Context, FrameResult, JournaledState,
 };
+use std::format;
 
 /// Mainnet end handle does not change the output.
+///
+/// If [crate::primitives::CfgEnv::verify_journal_finalized] is enabled, successful output
+/// is replaced with an error if the journal was not finalized, see [verify_journal_finalized].
 #[inline]
 pub fn end<EXT, DB: Database>(
-    _context: &mut Context<EXT, DB>,
+    context: &mut Context<EXT, DB>,
     evm_output: Result<ResultAndState, EVMError<DB::Error>>,
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
+    if evm_output.is_ok() && context.evm.env.cfg.verify_journal_finalized {
+        verify_journal_finalized(&context.evm.journaled_state)?;
+    }
     evm_output
 }
 
+/// Returns an error if the call depth of the journal is not zero or its transient storage
+/// is not empty, as they should be after [JournaledState::finalize].
+///
+/// Transactions that fail with an error can leave the journal in the middle of execution,
+/// so it should only be checked for successful output.
+pub fn verify_journal_finalized<DBError>(
+    journaled_state: &JournaledState,
+) -> Result<(), EVMError<DBError>> {
+    if journaled_state.depth != 0 {
+        return Err(EVMError::Custom(format!(
+            "journal not finalized: call depth is {}",
+            journaled_state.depth
+        )));
+    }
+    if !journaled_state.transient_storage.is_empty() {
+        return Err(EVMError::Custom(format!(
+            "journal not finalized: {} transient storage slots left",
+            journaled_state.transient_storage.len()
+        )));
+    }
+    Ok(())
+}
+
 /// Clear handle clears error and journal state.
 #[inline]
 pub fn clear<EXT, DB: Database>(context: &mut Context<EXT, DB>) {
     // clear error and journaled state.
     let _ = context.evm.take_error();
     context.evm.inner.journaled_state.clear();
+    context.evm.inner.warnings.clear();
+    context.evm.inner.storage_accesses.clear();
+    context.evm.inner.subcalls.clear();
+    context.evm.inner.halt_context = None;
 }
 
-/// Reward beneficiary with gas fee.
+/// Calculates the fee distribution, all of it goes to the beneficiary.
+///
+/// EIP-1559 burns the basefee, so from London the beneficiary only receives the priority
+/// fee of the used gas.
 #[inline]
-pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
+pub fn calc_fee_distribution<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     gas: &Gas,
-) -> Result<(), EVMError<DB::Error>> {
+) -> Result<FeeDistribution, EVMError<DB::Error>> {
     let beneficiary = context.evm.env.block.coinbase;
     let effective_gas_price = context.evm.env.effective_gas_price();
 
-    // transfer fee to coinbase/beneficiary.
     // EIP-1559 discard basefee for coinbase transfer. Basefee amount of gas is discarded.
     let coinbase_gas_price = if SPEC::enabled(LONDON) {
         effective_gas_price.saturating_sub(context.evm.env.block.basefee)
     } else {
         effective_gas_price
     };
+    let reward = coinbase_gas_price * U256::from(gas.spent() - gas.refunded() as u64);
 
-    let (coinbase_account, _) = context
-        .evm
-        .inner
-        .journaled_state
-        .load_account(beneficiary, &mut context.evm.inner.db)?;
+    Ok(FeeDistribution {
+        recipients: vec![(beneficiary, reward)],
+    })
+}
 
-    coinbase_account.mark_touch();
-    coinbase_account.info.balance = coinbase_account
-        .info
-        .balance
-        .saturating_add(coinbase_gas_price * U256::from(gas.spent() - gas.refunded() as u64));
+/// Reward the recipients of the fee distribution.
+///
+/// The beneficiary is always touched, even if it receives nothing, so an empty beneficiary
+/// is created before Spurious Dragon and removed after it.
+#[inline]
+pub fn reward_beneficiary<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    _gas: &Gas,
+    distribution: &FeeDistribution,
+) -> Result<(), EVMError<DB::Error>> {
+    let beneficiary = context.evm.env.block.coinbase;
+    let recipients = distribution.recipients.iter().copied();
+    for (recipient, amount) in core::iter::once((beneficiary, U256::ZERO)).chain(recipients) {
+        let (account, _) = context
+            .evm
+            .inner
+            .journaled_state
+            .load_account(recipient, &mut context.evm.inner.db)?;
 
+        account.mark_touch();
+        if !amount.is_zero() {
+            account.info.balance = account.info.balance.saturating_add(amount);
+        }
+    }
     Ok(())
 }
 
@@ -94,6 +149,7 @@ pub fn output<EXT, DB: Database>(
     // reset journal and return present state.
     let (state, logs) = context.evm.journaled_state.finalize();
 
+    let halt_context = context.evm.halt_context.take();
     let result = match instruction_result.result.into() {
         SuccessOrHalt::Success(reason) => ExecutionResult::Success {
             reason,
@@ -109,7 +165,9 @@ pub fn output<EXT, DB: Database>(
         SuccessOrHalt::Halt(reason) => ExecutionResult::Halt {
             reason,
             gas_used: final_gas_used,
+            context: halt_context,
         },
+        SuccessOrHalt::Canceled => return Err(EVMError::Canceled),
         // Only two internal return flags.
         flag @ (SuccessOrHalt::FatalExternalError
         | SuccessOrHalt::InternalContinue
@@ -121,5 +179,168 @@ pub fn output<EXT, DB: Database>(
         }
     };
 
-    Ok(ResultAndState { result, state })
+    let mut result_and_state = ResultAndState {
+        result,
+        state,
+        warnings: core::mem::take(&mut context.evm.warnings),
+        storage_accesses: core::mem::take(&mut context.evm.storage_accesses),
+        subcalls: core::mem::take(&mut context.evm.subcalls),
+    };
+    if context.evm.env.cfg.prune_unchanged_state {
+        result_and_state.prune_unchanged_state();
+    }
+    Ok(result_and_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{address, AccountInfo, Address, Bytecode, SpecId, TransactTo},
+        Evm,
+    };
+    use std::sync::Arc;
+
+    const TREASURY: Address = address!("1000000000000000000000000000000000000003");
+
+    #[test]
+    fn custom_fee_distribution() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let coinbase = address!("1000000000000000000000000000000000000002");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            caller,
+            AccountInfo::from_balance(U256::from(10_000_000_000u64)),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_block_env(|block| {
+                block.coinbase = coinbase;
+                block.basefee = U256::from(10);
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(Address::ZERO);
+                tx.gas_limit = 21_000;
+                tx.gas_price = U256::from(12);
+                tx.gas_priority_fee = Some(U256::from(2));
+            })
+            .build();
+        let state = evm.transact().unwrap().state;
+        // basefee is burned.
+        assert_eq!(state[&coinbase].info.balance, U256::from(2 * 21_000));
+
+        // basefee is split between the beneficiary and the treasury.
+        let mut evm = evm
+            .modify()
+            .append_handler_register(|handler| {
+                handler.post_execution.calc_fee_distribution = Arc::new(|context, gas| {
+                    let env = &context.evm.env;
+                    let gas_used = U256::from(gas.spent());
+                    let priority_fee = (env.effective_gas_price() - env.block.basefee) * gas_used;
+                    let basefee = env.block.basefee * gas_used / U256::from(2);
+                    Ok(FeeDistribution {
+                        recipients: vec![
+                            (env.block.coinbase, priority_fee + basefee),
+                            (TREASURY, basefee),
+                        ],
+                    })
+                })
+            })
+            .build();
+        let state = evm.transact().unwrap().state;
+        assert_eq!(state[&coinbase].info.balance, U256::from(7 * 21_000));
+        assert_eq!(state[&TREASURY].info.balance, U256::from(5 * 21_000));
+    }
+
+    #[test]
+    fn prune_unchanged_state() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let callee = address!("1000000000000000000000000000000000000002");
+        let preloaded = address!("1000000000000000000000000000000000000003");
+        // SLOAD(0) POP SSTORE(1, 1)
+        let code = Bytecode::new_raw(
+            vec![
+                opcode::PUSH1,
+                0,
+                opcode::SLOAD,
+                opcode::POP,
+                opcode::PUSH1,
+                1,
+                opcode::PUSH1,
+                1,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]
+            .into(),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            caller,
+            AccountInfo::from_balance(U256::from(10_000_000_000u64)),
+        );
+        db.insert_account_info(
+            callee,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::CANCUN)
+            .with_preloaded_addresses([preloaded])
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(callee);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+        let state = evm.transact().unwrap().state;
+        assert!(state.contains_key(&preloaded));
+        // coinbase is touched even with zero reward.
+        assert!(state[&Address::ZERO].is_touched());
+        assert_eq!(state[&callee].storage.len(), 2);
+
+        let mut evm = evm
+            .modify()
+            .modify_cfg_env(|cfg| cfg.prune_unchanged_state = true)
+            .build();
+        let state = evm.transact().unwrap().state;
+        // preloaded account is only loaded.
+        assert!(!state.contains_key(&preloaded));
+        assert!(state.contains_key(&Address::ZERO));
+        assert!(state.contains_key(&caller));
+        let slots = state[&callee].storage.keys().collect::<Vec<_>>();
+        assert_eq!(slots, [&U256::from(1)]);
+    }
+
+    #[test]
+    fn verify_journal_finalized_output() {
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .modify_cfg_env(|cfg| cfg.verify_journal_finalized = true)
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(Address::ZERO))
+            .build();
+        assert!(evm.transact().is_ok());
+
+        // output handle that leaves a checkpoint open.
+        let mut evm = evm
+            .modify()
+            .append_handler_register(|handler| {
+                handler.post_execution.output = Arc::new(|context, frame_result| {
+                    let result = output(context, frame_result);
+                    context.evm.journaled_state.depth = 1;
+                    result
+                })
+            })
+            .build();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Custom("journal not finalized: call depth is 1".into())
+        );
+        // the journal is cleared for the next transaction.
+        assert_eq!(evm.context.evm.journaled_state.depth, 0);
+    }
 }
@@ -6,9 +6,10 @@ use crate::{
     precompile::{PrecompileSpecId, Precompiles},
     primitives::{
         db::Database,
-        Account, EVMError, Env, Spec,
-        SpecId::{CANCUN, SHANGHAI},
-        TransactTo, U256,
+        Account, Address, EVMError, Env, Spec,
+        SpecId::{CANCUN, PRAGUE, SHANGHAI},
+        TransactTo, BEACON_ROOTS_ADDRESS, BEACON_ROOTS_HISTORY_LENGTH, BLOCKHASH_SERVE_WINDOW,
+        BLOCKHASH_STORAGE_ADDRESS, U256,
     },
     Context, ContextPrecompiles,
 };
@@ -28,6 +29,10 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
 ) -> Result<(), EVMError<DB::Error>> {
     // set journaling state flag.
     context.evm.journaled_state.set_spec_id(SPEC::SPEC_ID);
+    let state_clear = context.evm.inner.env.cfg.state_clear;
+    context.evm.journaled_state.set_state_clear(state_clear);
+    let eip6780 = context.evm.inner.env.cfg.eip6780;
+    context.evm.journaled_state.set_eip6780(eip6780);
 
     // load coinbase
     // EIP-3651: Warm COINBASE. Starts the `COINBASE` address warm
@@ -39,10 +44,96 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
         )?;
     }
 
+    context
+        .evm
+        .inner
+        .journaled_state
+        .load_preloaded_accounts(&mut context.evm.inner.db)?;
     context.evm.load_access_list()?;
     Ok(())
 }
 
+/// EIP-2935: Stores hash of the parent block into the history storage contract.
+///
+/// It is the system call at the start of the block, applied once per block by
+/// [Evm::apply_block_system_calls](crate::Evm::apply_block_system_calls) and not before
+/// every transaction. Nothing is stored for the genesis block or if the contract is not
+/// deployed.
+#[inline]
+pub fn store_block_hash<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    if !SPEC::enabled(PRAGUE) {
+        return Ok(());
+    }
+
+    let number = context.evm.inner.env.block.number;
+    let Some(parent) = number.checked_sub(U256::from(1)) else {
+        return Ok(());
+    };
+    let parent_hash = context.evm.block_hash(parent)?;
+    let slot = parent.wrapping_rem(U256::from(BLOCKHASH_SERVE_WINDOW));
+    store_system_storage(
+        context,
+        BLOCKHASH_STORAGE_ADDRESS,
+        [(slot, U256::from_be_bytes(parent_hash.0))],
+    )
+}
+
+/// EIP-4788: Stores root of the parent beacon block into the beacon roots contract.
+///
+/// Timestamp of the block and the root are written into the ring buffers of the contract.
+/// It is the system call at the start of the block, applied once per block by
+/// [Evm::apply_block_system_calls](crate::Evm::apply_block_system_calls) and not before
+/// every transaction. Nothing is stored for the genesis block, without the root or if the
+/// contract is not deployed.
+#[inline]
+pub fn apply_beacon_root<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    let block = &context.evm.inner.env.block;
+    let Some(root) = block.parent_beacon_block_root else {
+        return Ok(());
+    };
+    if !SPEC::enabled(CANCUN) || block.number == U256::ZERO {
+        return Ok(());
+    }
+
+    let timestamp = block.timestamp;
+    let timestamp_slot = timestamp.wrapping_rem(U256::from(BEACON_ROOTS_HISTORY_LENGTH));
+    let root_slot = timestamp_slot + U256::from(BEACON_ROOTS_HISTORY_LENGTH);
+    store_system_storage(
+        context,
+        BEACON_ROOTS_ADDRESS,
+        [
+            (timestamp_slot, timestamp),
+            (root_slot, U256::from_be_bytes(root.0)),
+        ],
+    )
+}
+
+/// Writes the storage of the system contract through the journal and touches it.
+///
+/// Nothing is written if the contract is not deployed.
+fn store_system_storage<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    address: Address,
+    slots: impl IntoIterator<Item = (U256, U256)>,
+) -> Result<(), EVMError<DB::Error>> {
+    let inner = &mut context.evm.inner;
+    let (account, _) = inner.journaled_state.load_account(address, &mut inner.db)?;
+    if account.info.is_empty_code_hash() {
+        return Ok(());
+    }
+    for (slot, value) in slots {
+        inner
+            .journaled_state
+            .sstore(address, slot, value, &mut inner.db)?;
+    }
+    inner.journaled_state.touch(&address);
+    Ok(())
+}
+
 /// Helper function that deducts the caller balance.
 #[inline]
 pub fn deduct_caller_inner<SPEC: Spec>(caller_account: &mut Account, env: &Env) {
@@ -86,3 +177,110 @@ pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        db::{CacheDB, Database, EmptyDB},
+        primitives::{
+            address, keccak256, AccountInfo, Bytecode, Bytes, SpecId, TransactTo, B256,
+            BEACON_ROOTS_ADDRESS, BEACON_ROOTS_HISTORY_LENGTH, BLOCKHASH_STORAGE_ADDRESS, U256,
+        },
+        Evm,
+    };
+
+    #[test]
+    fn blockhash_served_from_history_storage() {
+        let contract = address!("0000000000000000000000000000000000000100");
+        // PUSH1 0x09 BLOCKHASH PUSH1 0x00 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x09, 0x40, 0x60, 0x00, 0x55]));
+        let history = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+        db.insert_account_info(
+            BLOCKHASH_STORAGE_ADDRESS,
+            AccountInfo::new(U256::ZERO, 1, history.hash_slow(), history),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::PRAGUE)
+            .modify_env(|env| {
+                env.block.number = U256::from(10);
+                env.tx.transact_to = TransactTo::Call(contract);
+            })
+            .build();
+        evm.apply_block_system_calls().unwrap();
+        let parent_hash = U256::from_be_bytes(keccak256("9").0);
+        assert_eq!(
+            evm.db_mut()
+                .storage(BLOCKHASH_STORAGE_ADDRESS, U256::from(9)),
+            Ok(parent_hash)
+        );
+
+        let state = evm.transact().unwrap().state;
+        // history contract is not written by the transaction.
+        assert!(!state[&BLOCKHASH_STORAGE_ADDRESS].is_touched());
+        assert_eq!(
+            state[&contract].storage[&U256::ZERO].present_value,
+            parent_hash
+        );
+    }
+
+    #[test]
+    fn block_hash_not_stored_without_contract() {
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .with_spec_id(SpecId::PRAGUE)
+            .modify_block_env(|block| block.number = U256::from(10))
+            .build();
+        evm.apply_block_system_calls().unwrap();
+        let db = evm.db_mut();
+        assert_eq!(db.basic(BLOCKHASH_STORAGE_ADDRESS), Ok(None));
+        assert_eq!(
+            db.storage(BLOCKHASH_STORAGE_ADDRESS, U256::from(9)),
+            Ok(U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn beacon_root_stored_once_per_block() {
+        let beacon_roots = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            BEACON_ROOTS_ADDRESS,
+            AccountInfo::new(U256::ZERO, 1, beacon_roots.hash_slow(), beacon_roots),
+        );
+
+        let root = B256::with_last_byte(0xaa);
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_spec_id(SpecId::CANCUN)
+            .modify_env(|env| {
+                env.block.number = U256::from(1);
+                env.block.timestamp = U256::from(12);
+                env.block.parent_beacon_block_root = Some(root);
+                env.tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000100"));
+            })
+            .build();
+        evm.apply_block_system_calls().unwrap();
+        let root_slot = U256::from(12 + BEACON_ROOTS_HISTORY_LENGTH);
+        let db = evm.db_mut();
+        assert_eq!(
+            db.storage(BEACON_ROOTS_ADDRESS, U256::from(12)),
+            Ok(U256::from(12))
+        );
+        assert_eq!(
+            db.storage(BEACON_ROOTS_ADDRESS, root_slot),
+            Ok(U256::from(0xaa))
+        );
+
+        // transactions don't load the contract.
+        let state = evm.transact().unwrap().state;
+        assert!(!state.contains_key(&BEACON_ROOTS_ADDRESS));
+    }
+}
@@ -1,19 +1,56 @@
 use bcevm_interpreter::gas;
 
 use crate::{
-    primitives::{db::Database, EVMError, Env, InvalidTransaction, Spec},
+    primitives::{db::Database, EVMError, Env, InvalidTransaction, Spec, SpecId, ValidationIssue},
     Context,
 };
+use std::vec::Vec;
 
 /// Validate environment for the mainnet.
+///
+/// Chain id is validated separately in [validate_chain_id].
 pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<DB::Error>> {
     // Important: validate block before tx.
     env.validate_block_env::<SPEC>()?;
-    env.validate_tx::<SPEC>()?;
+    env.validate_tx_without_chain_id::<SPEC>()?;
+    Ok(())
+}
+
+/// Validate environment for the given spec and return all issues at once.
+///
+/// Checks the same as [validate_env], [validate_chain_id] and [validate_initial_tx_gas]
+/// without needing a handler or database, and does not stop at the first issue.
+/// Checks against the state (nonce, balance, caller code) are not included.
+pub fn validate_env_for_spec(env: &Env, spec: SpecId) -> Result<(), Vec<ValidationIssue>> {
+    let mut issues = env.validation_issues(spec);
+
+    let initial_gas_spend = gas::validate_initial_tx_gas(
+        spec,
+        &env.tx.data,
+        env.tx.transact_to.is_create(),
+        &env.tx.access_list,
+        &env.tx.eof_initcodes,
+    );
+    if initial_gas_spend > env.tx.gas_limit {
+        issues.push(InvalidTransaction::CallGasCostMoreThanGasLimit.into());
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+/// Validate that transaction chain id matches the configured one.
+pub fn validate_chain_id<DB: Database>(env: &Env) -> Result<(), EVMError<DB::Error>> {
+    env.validate_tx_chain_id()?;
     Ok(())
 }
 
 /// Validates transaction against the state.
+///
+/// Nonce is validated separately in [validate_tx_nonce].
 pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
@@ -29,12 +66,31 @@ pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
         .evm
         .inner
         .env
-        .validate_tx_against_state::<SPEC>(caller_account)
+        .validate_tx_against_state_without_nonce::<SPEC>(caller_account)
         .map_err(EVMError::Transaction)?;
 
     Ok(())
 }
 
+/// Validates that the transaction nonce is equal to the nonce of the caller.
+pub fn validate_tx_nonce<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    let tx_caller = context.evm.env.tx.caller;
+    let (caller_account, _) = context
+        .evm
+        .inner
+        .journaled_state
+        .load_account(tx_caller, &mut context.evm.inner.db)?;
+
+    context
+        .evm
+        .inner
+        .env
+        .validate_tx_nonce(caller_account.info.nonce)?;
+    Ok(())
+}
+
 /// Validate initial transaction gas.
 pub fn validate_initial_tx_gas<SPEC: Spec, DB: Database>(
     env: &Env,
@@ -53,3 +109,179 @@ pub fn validate_initial_tx_gas<SPEC: Spec, DB: Database>(
     }
     Ok(initial_gas_spend)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::validate_env_for_spec;
+    use crate::{
+        db::EmptyDB,
+        primitives::{
+            Bytes, EVMError, Env, InvalidHeader, InvalidTransaction, SpecId, TransactTo,
+            ValidationIssue, B256, U256,
+        },
+        Evm,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn custom_chain_id_validation() {
+        let mut evm = Evm::builder()
+            .with_db(EmptyDB::default())
+            .modify_cfg_env(|cfg| cfg.chain_id = 1)
+            .modify_tx_env(|tx| tx.chain_id = Some(10))
+            .build();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::InvalidChainId)
+        );
+
+        // foreign chain transactions are accepted with chain id check disabled.
+        let mut evm = evm
+            .modify()
+            .append_handler_register(|handler| handler.validation.chain_id = Arc::new(|_| Ok(())))
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+    }
+
+    #[test]
+    fn custom_nonce_policy() {
+        let mut evm = Evm::builder()
+            .with_db(EmptyDB::default())
+            .modify_tx_env(|tx| tx.nonce = Some(5))
+            .build();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::NonceTooHigh { tx: 5, state: 0 })
+        );
+
+        // any nonce is accepted by the custom policy.
+        let mut evm = evm
+            .modify()
+            .append_handler_register(|handler| {
+                handler.validation.tx_nonce_policy = Arc::new(|_| Ok(()))
+            })
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+    }
+
+    #[test]
+    fn intrinsic_gas_breakdown() {
+        use crate::{
+            interpreter::gas::{calc_intrinsic_gas, IntrinsicGasBreakdown},
+            primitives::{Address, TxEnv},
+        };
+
+        let tx = TxEnv {
+            transact_to: TransactTo::Create,
+            data: Bytes::from_static(&[0, 0, 1]),
+            access_list: vec![(Address::ZERO, vec![U256::ZERO, U256::from(1)])],
+            blob_hashes: vec![B256::ZERO],
+            ..Default::default()
+        };
+        let breakdown = calc_intrinsic_gas(&tx, SpecId::CANCUN);
+        assert_eq!(
+            breakdown,
+            IntrinsicGasBreakdown {
+                base: 53_000,
+                zero_data_bytes: 2,
+                non_zero_data_bytes: 1,
+                data: 2 * 4 + 16,
+                access_list: 2_400 + 2 * 1_900,
+                initcode_words: 1,
+                initcode: 2,
+                blob_count: 1,
+                blob_gas: 1 << 17,
+            }
+        );
+
+        // same as the gas the handler charges.
+        let env = Env {
+            tx,
+            ..Default::default()
+        };
+        let handler =
+            crate::Handler::<Evm<'_, (), EmptyDB>, (), EmptyDB>::mainnet_with_spec(SpecId::CANCUN);
+        let initial_gas = handler.validation.initial_tx_gas(&env);
+        assert_eq!(initial_gas, Ok(breakdown.total()));
+
+        // access list and initcode are not charged before Berlin and Shanghai.
+        let breakdown = calc_intrinsic_gas(&env.tx, SpecId::ISTANBUL);
+        assert_eq!(breakdown.total(), 53_000 + 2 * 4 + 16);
+    }
+
+    #[test]
+    fn eof_initcodes_validation() {
+        let initcode = Bytes::from_static(&[0xef, 0x00, 0x01]);
+        let mut evm = Evm::builder()
+            .with_db(EmptyDB::default())
+            .with_spec_id(SpecId::EXPERIMENTAL)
+            .modify_tx_env(|tx| tx.set_eof_initcodes(vec![initcode.clone()]))
+            .build();
+        // calldata and hashing of the initcode.
+        assert_eq!(
+            evm.transact().unwrap().result.gas_used(),
+            21_000 + 4 + 2 * 16 + 6
+        );
+
+        // hashes are not calculated.
+        evm.tx_mut().eof_initcodes_hashed.clear();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::EofInitcodesHashMismatch)
+        );
+
+        evm.tx_mut().set_eof_initcodes(vec![initcode.clone()]);
+        evm.tx_mut().transact_to = TransactTo::Create;
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::EofCrateShouldHaveToAddress)
+        );
+
+        let mut evm = evm.modify().with_spec_id(SpecId::CANCUN).build();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::EofInitcodesNotSupported)
+        );
+    }
+
+    #[test]
+    fn env_validation_issues() {
+        let mut env = Env::default();
+        env.block.prevrandao = Some(B256::ZERO);
+        assert_eq!(validate_env_for_spec(&env, SpecId::SHANGHAI), Ok(()));
+
+        env.block.prevrandao = None;
+        env.cfg.chain_id = 1;
+        env.tx.chain_id = Some(10);
+        env.tx.gas_limit = 20_000;
+        env.tx.gas_priority_fee = Some(U256::from(10));
+        env.tx.blob_hashes = vec![B256::ZERO];
+
+        let issues = validate_env_for_spec(&env, SpecId::SHANGHAI).unwrap_err();
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::Header(InvalidHeader::PrevrandaoNotSet),
+                ValidationIssue::Transaction(InvalidTransaction::PriorityFeeGreaterThanMaxFee),
+                ValidationIssue::Transaction(InvalidTransaction::InvalidChainId),
+                ValidationIssue::Transaction(InvalidTransaction::BlobVersionedHashesNotSupported),
+                ValidationIssue::Transaction(InvalidTransaction::CallGasCostMoreThanGasLimit),
+            ]
+        );
+        assert_eq!(
+            issues.iter().map(|i| i.error_code()).collect::<Vec<_>>()[..2],
+            [3000, 2000]
+        );
+
+        // first issue is the one returned by the handler.
+        let mut evm = Evm::builder()
+            .with_db(EmptyDB::default())
+            .with_spec_id(SpecId::SHANGHAI)
+            .with_env(Box::new(env))
+            .build();
+        assert_eq!(
+            evm.transact().unwrap_err(),
+            EVMError::Header(InvalidHeader::PrevrandaoNotSet)
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Callbacks for selected opcodes.
+//!
+//! [OpcodeHooks] converts the instruction table to the boxed variant and wraps the entries
+//! of the hooked opcodes, so every instruction goes through dynamic dispatch as it does
+//! with an [Inspector](crate::Inspector). Unlike an inspector, no callback is made for the
+//! other opcodes, which makes hooks cheaper when only a few opcodes are of interest. See
+//! the `hooks` group of the `bench` benchmark for a comparison.
+
+use crate::{
+    db::Database,
+    handler::register::{EvmHandler, HandleRegisterBox},
+    interpreter::{
+        opcode::{BoxedInstruction, InstructionTables},
+        Interpreter,
+    },
+    Evm,
+};
+use std::{boxed::Box, sync::Arc, vec::Vec};
+
+/// Callback called with the interpreter and the EVM. External context is available as
+/// `evm.context.external`.
+pub type OpcodeHook<EXT, DB> = Arc<dyn for<'e> Fn(&mut Interpreter, &mut Evm<'e, EXT, DB>)>;
+
+/// Set of callbacks called before or after the execution of the hooked opcodes.
+///
+/// Hooks are installed with [OpcodeHooks::into_register] and
+/// [EvmBuilder::append_handler_register_box](crate::EvmBuilder::append_handler_register_box).
+/// Hooks of the same opcode are called in the order they were added.
+pub struct OpcodeHooks<EXT, DB: Database> {
+    hooks: Vec<(u8, bool, OpcodeHook<EXT, DB>)>,
+}
+
+impl<EXT, DB: Database> Default for OpcodeHooks<EXT, DB> {
+    fn default() -> Self {
+        Self { hooks: Vec::new() }
+    }
+}
+
+impl<EXT, DB: Database> Clone for OpcodeHooks<EXT, DB> {
+    fn clone(&self) -> Self {
+        Self {
+            hooks: self.hooks.clone(),
+        }
+    }
+}
+
+impl<EXT, DB: Database> OpcodeHooks<EXT, DB> {
+    /// Creates an empty set of hooks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the hook called before the opcode is executed.
+    pub fn before(
+        mut self,
+        opcode: u8,
+        hook: impl for<'e> Fn(&mut Interpreter, &mut Evm<'e, EXT, DB>) + 'static,
+    ) -> Self {
+        self.hooks.push((opcode, false, Arc::new(hook)));
+        self
+    }
+
+    /// Adds the hook called after the opcode is executed.
+    ///
+    /// Note that calls and creates are executed in a new frame after the instruction
+    /// returns, so the hook sees the interpreter before the sub call.
+    pub fn after(
+        mut self,
+        opcode: u8,
+        hook: impl for<'e> Fn(&mut Interpreter, &mut Evm<'e, EXT, DB>) + 'static,
+    ) -> Self {
+        self.hooks.push((opcode, true, Arc::new(hook)));
+        self
+    }
+
+    /// Returns the handler register that installs the hooks.
+    pub fn into_register<'a>(self) -> HandleRegisterBox<'a, EXT, DB>
+    where
+        EXT: 'a,
+        DB: 'a,
+    {
+        Box::new(move |handler| self.register(handler))
+    }
+
+    /// Converts the instruction table to the boxed variant and wraps the instructions of
+    /// the hooked opcodes.
+    pub fn register(&self, handler: &mut EvmHandler<'_, EXT, DB>) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let mut table = handler
+            .take_instruction_table()
+            .expect("Handler must have instruction table");
+        table.convert_boxed();
+        let InstructionTables::Boxed(instructions) = &mut table else {
+            unreachable!("we already converted the table to boxed variant");
+        };
+        for (opcode, after, hook) in &self.hooks {
+            let entry = &mut instructions[*opcode as usize];
+            let instruction = core::mem::replace(entry, Box::new(|_, _| ()));
+            *entry = hooked_instruction(instruction, hook.clone(), *after);
+        }
+        handler.set_instruction_table(table);
+    }
+}
+
+/// Wraps the instruction with the hook.
+fn hooked_instruction<'a, EXT, DB: Database>(
+    instruction: BoxedInstruction<'a, Evm<'a, EXT, DB>>,
+    hook: OpcodeHook<EXT, DB>,
+    after: bool,
+) -> BoxedInstruction<'a, Evm<'a, EXT, DB>> {
+    if after {
+        Box::new(move |interpreter, host| {
+            instruction(interpreter, host);
+            hook(interpreter, host);
+        })
+    } else {
+        Box::new(move |interpreter, host| {
+            hook(interpreter, host);
+            instruction(interpreter, host);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        interpreter::opcode,
+        primitives::{address, Bytecode, SpecId, TransactTo, U256},
+    };
+
+    #[test]
+    fn hook_selected_opcodes() {
+        // SSTORE(0, 1) SSTORE(1, 1)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0x01,
+                opcode::DUP1,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let hooks = OpcodeHooks::new()
+            .before(
+                opcode::SSTORE,
+                |interpreter, evm: &mut Evm<'_, Vec<U256>, _>| {
+                    // slot is on top of the stack before execution.
+                    let slot = interpreter.stack().peek(0).unwrap();
+                    evm.context.external.push(slot);
+                },
+            )
+            .after(opcode::STOP, |_, evm| evm.context.external.push(U256::MAX));
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .with_external_context(Vec::new())
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register_box(hooks.into_register())
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(
+            evm.context.external,
+            vec![U256::ZERO, U256::from(1), U256::MAX]
+        );
+
+        // hooks are kept when the handler is rebuilt.
+        evm.context.external.clear();
+        evm.modify_spec_id(SpecId::SHANGHAI);
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(evm.context.external.len(), 3);
+    }
+}
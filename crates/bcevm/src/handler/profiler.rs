@@ -0,0 +1,427 @@
+//! Timing of the handler stages.
+//!
+//! [HandlerProfiler] wraps every handle of the [EvmHandler] and measures the time spent
+//! in it. Time spent in the interpreter loop, between the creation of the first frame
+//! and the return of the last one, is reported as the `interpreter` handle of the
+//! execution stage.
+
+use crate::{
+    db::Database,
+    handler::register::{EvmHandler, HandleRegisterBox},
+    primitives::EVMResult,
+    Evm,
+};
+use core::time::Duration;
+use std::{
+    boxed::Box,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+    vec::Vec,
+};
+
+/// Stage of the handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProfiledStage {
+    /// Transaction validation.
+    Validation,
+    /// Loading of the accounts and precompiles and deduction of the caller.
+    PreExecution,
+    /// Execution of the frames.
+    Execution,
+    /// Reimbursement, reward and output of the transaction.
+    PostExecution,
+}
+
+/// Time spent in a single handle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandleTiming {
+    /// Stage of the handle.
+    pub stage: ProfiledStage,
+    /// Name of the handle, e.g. `load_accounts`.
+    pub handle: &'static str,
+    /// Number of times the handle was called.
+    pub calls: u64,
+    /// Total time spent in the handle.
+    pub elapsed: Duration,
+}
+
+/// Per handle breakdown of the time spent in the handler.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HandlerProfile {
+    /// Timings of the handles, in the order they were first called.
+    pub handles: Vec<HandleTiming>,
+}
+
+impl HandlerProfile {
+    /// Returns the timing of the handle.
+    pub fn handle(&self, stage: ProfiledStage, handle: &str) -> Option<&HandleTiming> {
+        self.handles
+            .iter()
+            .find(|timing| timing.stage == stage && timing.handle == handle)
+    }
+
+    /// Returns the total time spent in the handles of the stage.
+    pub fn stage(&self, stage: ProfiledStage) -> Duration {
+        self.handles
+            .iter()
+            .filter(|timing| timing.stage == stage)
+            .map(|timing| timing.elapsed)
+            .sum()
+    }
+
+    /// Returns the total time spent in all handles.
+    pub fn total(&self) -> Duration {
+        self.handles.iter().map(|timing| timing.elapsed).sum()
+    }
+
+    /// Adds the elapsed time to the handle.
+    fn record(&mut self, stage: ProfiledStage, handle: &'static str, elapsed: Duration) {
+        match self
+            .handles
+            .iter_mut()
+            .find(|timing| timing.stage == stage && timing.handle == handle)
+        {
+            Some(timing) => {
+                timing.calls += 1;
+                timing.elapsed += elapsed;
+            }
+            None => self.handles.push(HandleTiming {
+                stage,
+                handle,
+                calls: 1,
+                elapsed,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProfilerState {
+    profile: HandlerProfile,
+    /// Start of the interpreter loop and the time spent in frame handles since then.
+    execution: Option<(Instant, Duration)>,
+}
+
+/// Profiler of the handler stages.
+///
+/// Installed with [HandlerProfiler::into_register] and
+/// [EvmBuilder::append_handler_register_box](crate::EvmBuilder::append_handler_register_box).
+/// Clones share the collected timings, so a clone can be kept to read the profile after
+/// the transaction. Timings are accumulated until they are taken with
+/// [HandlerProfiler::take_profile].
+#[derive(Clone, Debug, Default)]
+pub struct HandlerProfiler {
+    state: Arc<Mutex<ProfilerState>>,
+}
+
+/// Wraps the handle with a closure that records the time spent in it.
+macro_rules! profile_handle {
+    ($profiler:ident, $handler:ident . $stage:ident . $handle:ident, $profiled:ident, [$($arg:ident),*]) => {{
+        let profiler = $profiler.clone();
+        let inner = $handler.$stage.$handle.clone();
+        $handler.$stage.$handle = Arc::new(move |$($arg),*| {
+            let start = Instant::now();
+            let output = inner($($arg),*);
+            profiler.record(ProfiledStage::$profiled, stringify!($handle), start.elapsed());
+            output
+        });
+    }};
+}
+
+impl HandlerProfiler {
+    /// Creates a new profiler.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the timings collected so far.
+    pub fn profile(&self) -> HandlerProfile {
+        self.state().profile.clone()
+    }
+
+    /// Takes the timings collected so far and resets the profiler.
+    pub fn take_profile(&self) -> HandlerProfile {
+        let mut state = self.state();
+        state.execution = None;
+        core::mem::take(&mut state.profile)
+    }
+
+    /// Executes the transaction and returns its result with the timings of the handler.
+    ///
+    /// The profiler needs to be registered in the handler of the EVM.
+    pub fn transact<EXT, DB: Database>(
+        &self,
+        evm: &mut Evm<'_, EXT, DB>,
+    ) -> (EVMResult<DB::Error>, HandlerProfile) {
+        self.take_profile();
+        let result = evm.transact();
+        (result, self.take_profile())
+    }
+
+    /// Returns the handler register that installs the profiler.
+    pub fn into_register<'a, EXT: 'a, DB: Database + 'a>(self) -> HandleRegisterBox<'a, EXT, DB> {
+        Box::new(move |handler| self.register(handler))
+    }
+
+    /// Wraps all handles of the handler.
+    ///
+    /// Handles should be replaced before the profiler is registered, as handles that are
+    /// replaced afterwards are not measured.
+    pub fn register<'a, EXT: 'a, DB: Database + 'a>(&self, handler: &mut EvmHandler<'a, EXT, DB>) {
+        let profiler = self;
+
+        profile_handle!(
+            profiler,
+            handler.validation.initial_tx_gas,
+            Validation,
+            [env]
+        );
+        profile_handle!(profiler, handler.validation.env, Validation, [env]);
+        profile_handle!(profiler, handler.validation.chain_id, Validation, [env]);
+        profile_handle!(
+            profiler,
+            handler.validation.tx_nonce_policy,
+            Validation,
+            [ctx]
+        );
+        profile_handle!(
+            profiler,
+            handler.validation.tx_against_state,
+            Validation,
+            [ctx]
+        );
+
+        profile_handle!(
+            profiler,
+            handler.pre_execution.load_precompiles,
+            PreExecution,
+            []
+        );
+        profile_handle!(
+            profiler,
+            handler.pre_execution.load_accounts,
+            PreExecution,
+            [ctx]
+        );
+        profile_handle!(
+            profiler,
+            handler.pre_execution.store_block_hash,
+            PreExecution,
+            [ctx]
+        );
+        profile_handle!(
+            profiler,
+            handler.pre_execution.apply_beacon_root,
+            PreExecution,
+            [ctx]
+        );
+        // the interpreter loop starts after the caller is deducted.
+        let inner = handler.pre_execution.deduct_caller.clone();
+        let deduct_profiler = profiler.clone();
+        handler.pre_execution.deduct_caller = Arc::new(move |ctx| {
+            let start = Instant::now();
+            let output = inner(ctx);
+            let end = Instant::now();
+            let mut state = deduct_profiler.state();
+            state
+                .profile
+                .record(ProfiledStage::PreExecution, "deduct_caller", end - start);
+            state.execution = Some((end, Duration::ZERO));
+            output
+        });
+
+        profile_handle!(profiler, handler.execution.call, Execution, [ctx, inputs]);
+        profile_handle!(
+            profiler,
+            handler.execution.call_return,
+            Execution,
+            [ctx, frame, result]
+        );
+        profile_handle!(
+            profiler,
+            handler.execution.insert_call_outcome,
+            Execution,
+            [ctx, frame, memory, outcome]
+        );
+        profile_handle!(profiler, handler.execution.create, Execution, [ctx, inputs]);
+        profile_handle!(
+            profiler,
+            handler.execution.create_return,
+            Execution,
+            [ctx, frame, result]
+        );
+        profile_handle!(
+            profiler,
+            handler.execution.insert_create_outcome,
+            Execution,
+            [ctx, frame, outcome]
+        );
+        profile_handle!(
+            profiler,
+            handler.execution.eofcreate,
+            Execution,
+            [ctx, inputs]
+        );
+        profile_handle!(
+            profiler,
+            handler.execution.eofcreate_return,
+            Execution,
+            [ctx, frame, result]
+        );
+        profile_handle!(
+            profiler,
+            handler.execution.insert_eofcreate_outcome,
+            Execution,
+            [ctx, frame, outcome]
+        );
+        // the interpreter loop ends when the last frame returns.
+        let inner = handler.execution.last_frame_return.clone();
+        let last_frame_profiler = profiler.clone();
+        handler.execution.last_frame_return = Arc::new(move |ctx, result| {
+            let start = Instant::now();
+            {
+                let mut state = last_frame_profiler.state();
+                if let Some((loop_start, frame_handles)) = state.execution.take() {
+                    let interpreter = (start - loop_start).saturating_sub(frame_handles);
+                    state
+                        .profile
+                        .record(ProfiledStage::Execution, "interpreter", interpreter);
+                }
+            }
+            let output = inner(ctx, result);
+            last_frame_profiler.record(
+                ProfiledStage::Execution,
+                "last_frame_return",
+                start.elapsed(),
+            );
+            output
+        });
+
+        profile_handle!(
+            profiler,
+            handler.post_execution.reimburse_caller,
+            PostExecution,
+            [ctx, gas]
+        );
+        profile_handle!(
+            profiler,
+            handler.post_execution.calc_fee_distribution,
+            PostExecution,
+            [ctx, gas]
+        );
+        profile_handle!(
+            profiler,
+            handler.post_execution.reward_beneficiary,
+            PostExecution,
+            [ctx, gas, distribution]
+        );
+        profile_handle!(
+            profiler,
+            handler.post_execution.output,
+            PostExecution,
+            [ctx, result]
+        );
+        profile_handle!(
+            profiler,
+            handler.post_execution.end,
+            PostExecution,
+            [ctx, result]
+        );
+        profile_handle!(profiler, handler.post_execution.clear, PostExecution, [ctx]);
+    }
+
+    /// Records the time spent in the handle.
+    fn record(&self, stage: ProfiledStage, handle: &'static str, elapsed: Duration) {
+        let mut state = self.state();
+        state.profile.record(stage, handle, elapsed);
+        if stage == ProfiledStage::Execution {
+            if let Some((_, frame_handles)) = &mut state.execution {
+                *frame_handles += elapsed;
+            }
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, ProfilerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        interpreter::opcode,
+        primitives::{address, Bytecode, TransactTo},
+    };
+
+    #[test]
+    fn profile_transaction() {
+        // CALL to itself without gas, then STOP.
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::ADDRESS,
+                opcode::PUSH0,
+                opcode::CALL,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let profiler = HandlerProfiler::new();
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register_box(profiler.clone().into_register())
+            .build();
+
+        let (result, profile) = profiler.transact(&mut evm);
+        assert!(result.unwrap().result.is_success());
+
+        let calls = |stage, handle| profile.handle(stage, handle).map(|timing| timing.calls);
+        assert_eq!(calls(ProfiledStage::Validation, "env"), Some(1));
+        assert_eq!(calls(ProfiledStage::PreExecution, "deduct_caller"), Some(1));
+        // the transaction and the sub call.
+        assert_eq!(calls(ProfiledStage::Execution, "call"), Some(2));
+        assert_eq!(
+            calls(ProfiledStage::Execution, "insert_call_outcome"),
+            Some(1)
+        );
+        assert_eq!(calls(ProfiledStage::Execution, "interpreter"), Some(1));
+        assert_eq!(calls(ProfiledStage::Execution, "create"), None);
+        assert_eq!(calls(ProfiledStage::PostExecution, "clear"), Some(1));
+        assert_eq!(
+            profile.total(),
+            [
+                ProfiledStage::Validation,
+                ProfiledStage::PreExecution,
+                ProfiledStage::Execution,
+                ProfiledStage::PostExecution,
+            ]
+            .into_iter()
+            .map(|stage| profile.stage(stage))
+            .sum::<Duration>()
+        );
+
+        // profile is reset for every transaction.
+        let (_, profile) = profiler.transact(&mut evm);
+        assert_eq!(
+            profile
+                .handle(ProfiledStage::Validation, "env")
+                .unwrap()
+                .calls,
+            1
+        );
+        assert!(profiler.profile().handles.is_empty());
+    }
+}
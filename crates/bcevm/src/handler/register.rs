@@ -8,16 +8,16 @@ pub type EvmHandler<'a, EXT, DB> = Handler<'a, Evm<'a, EXT, DB>, EXT, DB>;
 pub type HandleRegister<EXT, DB> = for<'a> fn(&mut EvmHandler<'a, EXT, DB>);
 
 // Boxed handle register
-pub type HandleRegisterBox<EXT, DB> = Box<dyn for<'a> Fn(&mut EvmHandler<'a, EXT, DB>)>;
+pub type HandleRegisterBox<'a, EXT, DB> = Box<dyn for<'e> Fn(&mut EvmHandler<'e, EXT, DB>) + 'a>;
 
-pub enum HandleRegisters<EXT, DB: Database> {
+pub enum HandleRegisters<'a, EXT, DB: Database> {
     /// Plain function register
     Plain(HandleRegister<EXT, DB>),
     /// Boxed function register.
-    Box(HandleRegisterBox<EXT, DB>),
+    Box(HandleRegisterBox<'a, EXT, DB>),
 }
 
-impl<EXT, DB: Database> HandleRegisters<EXT, DB> {
+impl<'a, EXT, DB: Database> HandleRegisters<'a, EXT, DB> {
     /// Call register function to modify EvmHandler.
     pub fn register(&self, handler: &mut EvmHandler<'_, EXT, DB>) {
         match self {
@@ -0,0 +1,191 @@
+//! Handler register for system transactions, see [SystemTx].
+//!
+//! System transactions are created by the chain itself, like deposits bridged from another
+//! chain. They skip the transaction validation, do not pay for gas and credit the `mint`
+//! amount to the caller before execution. Other transactions are handled by the handles
+//! that were set before the register, so it can be combined with any handler.
+
+use crate::{
+    handler::{register::EvmHandler, FeeDistribution},
+    interpreter::Gas,
+    primitives::{
+        db::Database, spec_to_generic, EVMError, Env, Spec, SpecId, SystemTx, TransactTo, U256,
+    },
+    Context,
+};
+use std::sync::Arc;
+
+/// Register that handles transactions with [TxEnv::system_tx](crate::primitives::TxEnv::system_tx)
+/// set as system transactions.
+///
+/// Needs to be appended after the registers of the chain, as the handles of other
+/// transactions are kept.
+pub fn system_tx_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    let env = handler.validation.env.clone();
+    spec_to_generic!(handler.cfg.spec_id, {
+        handler.validation.env = Arc::new(move |e| {
+            if e.tx.is_system_tx() {
+                validate_env::<SPEC, DB>(e)
+            } else {
+                env(e)
+            }
+        });
+    });
+    let chain_id = handler.validation.chain_id.clone();
+    handler.validation.chain_id = Arc::new(move |env| {
+        if env.tx.is_system_tx() {
+            return Ok(());
+        }
+        chain_id(env)
+    });
+    let tx_nonce_policy = handler.validation.tx_nonce_policy.clone();
+    handler.validation.tx_nonce_policy = Arc::new(move |context| {
+        if context.evm.env.tx.is_system_tx() {
+            return Ok(());
+        }
+        tx_nonce_policy(context)
+    });
+    let tx_against_state = handler.validation.tx_against_state.clone();
+    handler.validation.tx_against_state = Arc::new(move |context| {
+        if context.evm.env.tx.is_system_tx() {
+            return Ok(());
+        }
+        tx_against_state(context)
+    });
+    let deduct = handler.pre_execution.deduct_caller.clone();
+    handler.pre_execution.deduct_caller =
+        Arc::new(move |context| match context.evm.env.tx.system_tx.clone() {
+            Some(system_tx) => deduct_caller(context, &system_tx),
+            None => deduct(context),
+        });
+    let reimburse = handler.post_execution.reimburse_caller.clone();
+    handler.post_execution.reimburse_caller = Arc::new(move |context, gas| {
+        if context.evm.env.tx.is_system_tx() {
+            return Ok(());
+        }
+        reimburse(context, gas)
+    });
+    let reward = handler.post_execution.reward_beneficiary.clone();
+    handler.post_execution.reward_beneficiary = Arc::new(move |context, gas, distribution| {
+        if context.evm.env.tx.is_system_tx() {
+            return reward_beneficiary(context, gas, distribution);
+        }
+        reward(context, gas, distribution)
+    });
+}
+
+/// Validates only the block environment, system transactions are pre-verified by the
+/// chain.
+pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<DB::Error>> {
+    env.validate_block_env::<SPEC>()?;
+    Ok(())
+}
+
+/// Credits the mint amount to the caller and bumps its nonce without deducting any gas
+/// cost.
+///
+/// The caller account is changed before the first frame, so the changes are persisted even
+/// if the transaction reverts.
+pub fn deduct_caller<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    system_tx: &SystemTx,
+) -> Result<(), EVMError<DB::Error>> {
+    let (caller_account, _) = context
+        .evm
+        .inner
+        .journaled_state
+        .load_account(context.evm.inner.env.tx.caller, &mut context.evm.inner.db)?;
+
+    if let Some(mint) = system_tx.mint {
+        caller_account.info.balance = caller_account.info.balance.saturating_add(U256::from(mint));
+    }
+
+    // bump the nonce for calls. Nonce for CREATE will be bumped in `handle_create`.
+    if matches!(context.evm.inner.env.tx.transact_to, TransactTo::Call(_)) {
+        caller_account.info.nonce = caller_account.info.nonce.saturating_add(1);
+    }
+
+    caller_account.mark_touch();
+    Ok(())
+}
+
+/// System transactions do not pay for gas, the beneficiary is not rewarded.
+pub fn reward_beneficiary<EXT, DB: Database>(
+    _context: &mut Context<EXT, DB>,
+    _gas: &Gas,
+    _distribution: &FeeDistribution,
+) -> Result<(), EVMError<DB::Error>> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{address, AccountInfo, Bytecode, Bytes, B256},
+        Evm,
+    };
+
+    #[test]
+    fn mint_and_execute_without_fees() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("2000000000000000000000000000000000000002");
+        let beneficiary = address!("3000000000000000000000000000000000000003");
+        // REVERT(0, 0)
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::REVERT,
+        ]));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_block_env(|block| {
+                block.coinbase = beneficiary;
+                block.basefee = U256::from(7);
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(beneficiary);
+                tx.value = U256::from(40);
+                tx.gas_limit = 100_000;
+                tx.gas_price = U256::ZERO;
+                tx.chain_id = Some(12345);
+                tx.system_tx = Some(SystemTx {
+                    source_hash: B256::with_last_byte(1),
+                    mint: Some(100),
+                });
+            })
+            .append_handler_register(system_tx_handle_register)
+            .build();
+
+        let result = evm.transact_commit().unwrap();
+        assert!(result.is_success());
+        let caller_info = evm.context.evm.db.accounts[&caller].info.clone();
+        assert_eq!(caller_info.balance, U256::from(60));
+        assert_eq!(caller_info.nonce, 1);
+        assert_eq!(
+            evm.context.evm.db.accounts[&beneficiary].info.balance,
+            U256::from(40)
+        );
+
+        // mint and nonce are kept when the transaction reverts.
+        evm.context.evm.env.tx.transact_to = TransactTo::Call(contract);
+        let result = evm.transact_commit().unwrap();
+        assert!(!result.is_success());
+        let caller_info = evm.context.evm.db.accounts[&caller].info.clone();
+        assert_eq!(caller_info.balance, U256::from(160));
+        assert_eq!(caller_info.nonce, 2);
+
+        // other transactions are validated as usual.
+        evm.context.evm.env.tx.system_tx = None;
+        assert!(evm.transact().is_err());
+    }
+}
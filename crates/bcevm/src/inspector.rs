@@ -5,27 +5,53 @@ use crate::{
 };
 use auto_impl::auto_impl;
 
+mod access_list;
+mod calltracer;
+mod coverage;
 #[cfg(feature = "std")]
 mod customprinter;
 #[cfg(all(feature = "std", feature = "serde-json"))]
 mod eip3155;
 mod gas;
 mod handler_register;
+#[cfg(feature = "std")]
+mod log_stream;
 mod noop;
+mod opcode_gas;
+mod prestate;
+mod ring_buffer;
+mod sentinel;
+mod watchpoint;
 
 // Exports.
 
-pub use handler_register::{inspector_handle_register, inspector_instruction, GetInspector};
 use bcevm_interpreter::{CallOutcome, CreateOutcome};
+pub(crate) use handler_register::owned_inspector_handle_register;
+pub use handler_register::{inspector_handle_register, inspector_instruction, GetInspector};
 
 /// [Inspector] implementations.
 pub mod inspectors {
+    pub use super::access_list::{
+        AccessList, AccessListInspector, AccessListResult, MAX_ACCESS_LIST_ITERATIONS,
+    };
+    pub use super::calltracer::{CallKind, CallLog, CallTraceFrame, CallTracer, CallTracerConfig};
+    pub use super::coverage::{CoverageBitmap, CoverageInspector, CoverageMap};
     #[cfg(feature = "std")]
     pub use super::customprinter::CustomPrintTracer;
     #[cfg(all(feature = "std", feature = "serde-json"))]
     pub use super::eip3155::TracerEip3155;
     pub use super::gas::GasInspector;
+    #[cfg(feature = "std")]
+    pub use super::log_stream::LogStreamer;
     pub use super::noop::NoOpInspector;
+    pub use super::opcode_gas::{OpcodeGas, OpcodeGasInspector, OpcodeGasReport};
+    pub use super::prestate::{PrestateAccount, PrestateAccounts, PrestateDiff, PrestateTracer};
+    pub use super::ring_buffer::{decode_trace_records, RingBufferTracer, TraceRecord};
+    pub use super::sentinel::{Invariant, InvariantViolation, SentinelInspector};
+    pub use super::watchpoint::{
+        WatchAction, WatchedWrite, Watchpoint, WatchpointCallback, WatchpointHit,
+        WatchpointInspector,
+    };
 }
 
 /// EVM [Interpreter] callbacks.
@@ -65,7 +91,7 @@ pub trait Inspector<DB: Database> {
         let _ = context;
     }
 
-    /// Called when a log is emitted.
+    /// Called when a log is emitted, by a `LOG` instruction or by a precompile.
     #[inline]
     fn log(&mut self, context: &mut EvmContext<DB>, log: &Log) {
         let _ = context;
@@ -0,0 +1,351 @@
+//! Access list inspector. Generates [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930)
+//! access list of a transaction, equivalent of `eth_createAccessList`.
+
+use crate::{
+    interpreter::{opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter},
+    primitives::{db::Database, Address, EVMError, ExecutionResult, HashSet, U256},
+    Evm, EvmContext, Inspector,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    vec::Vec,
+};
+
+/// Access list in the format used by [crate::primitives::TxEnv::access_list].
+pub type AccessList = Vec<(Address, Vec<U256>)>;
+
+/// Maximum number of executions of [Evm::create_access_list] before it gives up.
+pub const MAX_ACCESS_LIST_ITERATIONS: usize = 16;
+
+/// Result of [Evm::create_access_list].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessListResult {
+    /// Generated access list.
+    pub access_list: AccessList,
+    /// Gas used by the transaction when the access list is applied.
+    pub gas_used: u64,
+    /// Result of the transaction with the access list applied.
+    pub result: ExecutionResult,
+}
+
+/// [Inspector] that records all addresses and storage slots touched during execution.
+///
+/// Sender, recipient (or created contract) and precompiles are excluded from the list
+/// unless their storage is accessed, as they are warm regardless of the access list.
+#[derive(Clone, Debug, Default)]
+pub struct AccessListInspector {
+    excluded: HashSet<Address>,
+    access_list: BTreeMap<Address, BTreeSet<U256>>,
+}
+
+impl AccessListInspector {
+    /// Creates new access list inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the access list recorded in the last transaction.
+    pub fn access_list(&self) -> AccessList {
+        self.access_list
+            .iter()
+            .map(|(address, slots)| (*address, slots.iter().copied().collect()))
+            .collect()
+    }
+
+    /// Returns the access list recorded in the last transaction merged into `previous`.
+    ///
+    /// Addresses of `previous` that are warm regardless of the access list are dropped,
+    /// unless their storage is listed.
+    pub fn merged_access_list(&self, previous: &AccessList) -> AccessList {
+        let mut merged = self.access_list.clone();
+        for (address, slots) in previous {
+            if slots.is_empty() && self.excluded.contains(address) {
+                continue;
+            }
+            merged.entry(*address).or_default().extend(slots);
+        }
+        merged
+            .into_iter()
+            .map(|(address, slots)| (address, slots.into_iter().collect()))
+            .collect()
+    }
+
+    /// Clears the recorded access list.
+    pub fn clear(&mut self) {
+        self.excluded.clear();
+        self.access_list.clear();
+    }
+
+    fn add_address(&mut self, address: Address) {
+        if !self.excluded.contains(&address) {
+            self.access_list.entry(address).or_default();
+        }
+    }
+
+    /// Slots are recorded even for excluded addresses, as only the address itself is warm.
+    fn add_slot(&mut self, address: Address, slot: U256) {
+        self.access_list.entry(address).or_default().insert(slot);
+    }
+}
+
+impl<DB: Database> Inspector<DB> for AccessListInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        let address_at = |n: usize| {
+            interp
+                .stack
+                .peek(n)
+                .ok()
+                .map(|value| Address::from_word(value.into()))
+        };
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    self.add_slot(interp.contract.target_address, slot);
+                }
+            }
+            opcode::BALANCE
+            | opcode::EXTCODESIZE
+            | opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::SELFDESTRUCT => {
+                if let Some(address) = address_at(0) {
+                    self.add_address(address);
+                }
+            }
+            opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL => {
+                if let Some(address) = address_at(1) {
+                    self.add_address(address);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if context.journaled_state.depth() == 0 {
+            self.clear();
+            self.excluded.insert(inputs.caller);
+            self.excluded.insert(inputs.target_address);
+            self.excluded
+                .extend(context.precompiles.addresses().copied());
+        }
+        None
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        if context.journaled_state.depth() == 0 {
+            self.clear();
+            self.excluded.insert(inputs.caller);
+            self.excluded
+                .extend(context.precompiles.addresses().copied());
+        }
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if context.journaled_state.depth() == 0 {
+            if let Some(address) = outcome.address {
+                self.excluded.insert(address);
+                if self
+                    .access_list
+                    .get(&address)
+                    .is_some_and(BTreeSet::is_empty)
+                {
+                    self.access_list.remove(&address);
+                }
+            }
+        }
+        outcome
+    }
+}
+
+impl<DB: Database> Evm<'_, AccessListInspector, DB> {
+    /// Generates access list of the current transaction, equivalent to `eth_createAccessList`.
+    ///
+    /// The transaction is executed repeatedly with the generated access list applied
+    /// until the list stops changing. Entries of every execution are merged into the list,
+    /// so it only grows and a transaction whose accesses depend on the list can't make it
+    /// oscillate. Fails with [EVMError::Custom] if the list doesn't settle after
+    /// [MAX_ACCESS_LIST_ITERATIONS] executions. State is not committed and the
+    /// transaction's own access list is restored afterwards.
+    ///
+    /// Evm needs to be built with [crate::inspector_handle_register].
+    pub fn create_access_list(&mut self) -> Result<AccessListResult, EVMError<DB::Error>> {
+        let original = self.tx().access_list.clone();
+        let result = self.create_access_list_inner(original.clone());
+        self.tx_mut().access_list = original;
+        result
+    }
+
+    fn create_access_list_inner(
+        &mut self,
+        mut access_list: AccessList,
+    ) -> Result<AccessListResult, EVMError<DB::Error>> {
+        for _ in 0..MAX_ACCESS_LIST_ITERATIONS {
+            self.tx_mut().access_list.clone_from(&access_list);
+            let result = self.transact()?.result;
+            let new_list = self.context.external.merged_access_list(&access_list);
+            if new_list == access_list {
+                return Ok(AccessListResult {
+                    access_list,
+                    gas_used: result.gas_used(),
+                    result,
+                });
+            }
+            access_list = new_list;
+        }
+        Err(EVMError::Custom(format!(
+            "access list did not converge after {MAX_ACCESS_LIST_ITERATIONS} iterations"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        primitives::{address, Bytecode, TransactTo},
+    };
+
+    #[test]
+    fn generate_access_list() {
+        let contract = address!("0000000000000000000000000000000000000000");
+        let other = address!("0000000000000000000000000000000000000099");
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x05,
+                opcode::SLOAD,
+                opcode::PUSH1,
+                0x99,
+                opcode::BALANCE,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .with_external_context(AccessListInspector::new())
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        let gas_without_list = evm.transact().unwrap().result.gas_used();
+        let result = evm.create_access_list().unwrap();
+
+        // contract is the recipient and is warm, only its slot is listed.
+        assert_eq!(
+            result.access_list,
+            vec![(contract, vec![U256::from(5)]), (other, vec![])]
+        );
+        assert!(result.result.is_success());
+        // each listed address costs 2400 and slot 1900, instead of cold penalties of 2500 and 2000.
+        assert_eq!(
+            result.gas_used,
+            gas_without_list + 2 * 2400 + 1900 - 2500 - 2000
+        );
+        assert!(evm.tx().access_list.is_empty());
+    }
+
+    fn access_list_evm(code: &[u8]) -> Evm<'static, AccessListInspector, BenchmarkDB> {
+        Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
+                code.to_vec().into(),
+            )))
+            .with_external_context(AccessListInspector::new())
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Call(Address::ZERO);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build()
+    }
+
+    #[test]
+    fn access_list_dependent_accesses_converge() {
+        // Without a list BALANCE(0xaa), with one listed address BALANCE(0xbb) and
+        // BALANCE(0xcc), and with more BALANCE(0xaa) again. Replacing the list every
+        // round would oscillate between [0xaa] and [0xbb, 0xcc].
+        let code = [
+            opcode::GAS,
+            opcode::PUSH3,
+            0x01,
+            0x30,
+            0xb0, // 78000
+            opcode::LT,
+            opcode::PUSH1,
+            18,
+            opcode::JUMPI,
+            opcode::GAS,
+            opcode::PUSH3,
+            0x01,
+            0x24,
+            0xf8, // 75000
+            opcode::LT,
+            opcode::PUSH1,
+            23,
+            opcode::JUMPI,
+            opcode::JUMPDEST,
+            opcode::PUSH1,
+            0xaa,
+            opcode::BALANCE,
+            opcode::STOP,
+            opcode::JUMPDEST,
+            opcode::PUSH1,
+            0xbb,
+            opcode::BALANCE,
+            opcode::POP,
+            opcode::PUSH1,
+            0xcc,
+            opcode::BALANCE,
+            opcode::STOP,
+        ];
+        let mut evm = access_list_evm(&code);
+
+        let result = evm.create_access_list().unwrap();
+        assert_eq!(
+            result.access_list,
+            vec![
+                (Address::with_last_byte(0xaa), vec![]),
+                (Address::with_last_byte(0xbb), vec![]),
+                (Address::with_last_byte(0xcc), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn access_list_iterations_are_capped() {
+        // SLOAD(GAS), every listed slot changes the gas and so the loaded slot.
+        let mut evm = access_list_evm(&[opcode::GAS, opcode::SLOAD, opcode::STOP]);
+
+        assert_eq!(
+            evm.create_access_list(),
+            Err(EVMError::Custom(format!(
+                "access list did not converge after {MAX_ACCESS_LIST_ITERATIONS} iterations"
+            )))
+        );
+        assert!(evm.tx().access_list.is_empty());
+    }
+}
@@ -0,0 +1,539 @@
+//! Call tracer inspector. Records a nested tree of calls and creates,
+//! compatible with the output of geth's `callTracer`.
+
+use crate::{
+    interpreter::{
+        CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, CreateScheme,
+        InstructionResult, InterpreterResult,
+    },
+    primitives::{db::Database, Address, Bytes, Log, RevertReason, SpecId, B256, U256},
+    EvmContext, Inspector,
+};
+use std::{string::String, vec::Vec};
+
+/// Configuration of the [CallTracer], mirrors geth's `callTracer` config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallTracerConfig {
+    /// Record only the top level call and skip all sub calls.
+    pub only_top_call: bool,
+    /// Record logs emitted inside each call frame.
+    pub with_log: bool,
+}
+
+/// Kind of the traced call frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+pub enum CallKind {
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+    Create,
+    Create2,
+}
+
+impl From<CallScheme> for CallKind {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call => Self::Call,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall => Self::DelegateCall,
+            CallScheme::StaticCall => Self::StaticCall,
+        }
+    }
+}
+
+impl From<CreateScheme> for CallKind {
+    fn from(scheme: CreateScheme) -> Self {
+        match scheme {
+            CreateScheme::Create => Self::Create,
+            CreateScheme::Create2 { .. } => Self::Create2,
+        }
+    }
+}
+
+/// Log recorded inside of a call frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallLog {
+    /// Address of the contract that emitted the log.
+    pub address: Address,
+    /// Topics of the log.
+    pub topics: Vec<B256>,
+    /// Data of the log.
+    pub data: Bytes,
+}
+
+/// Single call frame of the call tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallTraceFrame {
+    /// Type of the call.
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: CallKind,
+    /// Caller of the frame.
+    pub from: Address,
+    /// Callee of the frame. For creates this is the created address and it is
+    /// `None` if creation failed before the address was known.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub to: Option<Address>,
+    /// Value of the call. `None` for `DELEGATECALL` and `STATICCALL`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub value: Option<U256>,
+    /// Gas given to the frame.
+    #[cfg_attr(feature = "serde", serde(with = "hex_u64"))]
+    pub gas: u64,
+    /// Gas used by the frame.
+    #[cfg_attr(feature = "serde", serde(with = "hex_u64"))]
+    pub gas_used: u64,
+    /// Call data or init code.
+    pub input: Bytes,
+    /// Returned data or deployed code.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub output: Option<Bytes>,
+    /// Error string if the frame did not succeed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+    /// Decoded solidity `Error(string)` revert reason.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub revert_reason: Option<String>,
+    /// Sub calls in order of execution.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub calls: Vec<CallTraceFrame>,
+    /// Logs emitted by this frame. Only recorded with [CallTracerConfig::with_log].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub logs: Vec<CallLog>,
+}
+
+impl CallTraceFrame {
+    /// Returns `true` if the frame or any of its sub calls failed.
+    pub fn has_error(&self) -> bool {
+        self.error.is_some() || self.calls.iter().any(Self::has_error)
+    }
+
+    /// Returns the first failed frame in execution order, starting from the deepest one.
+    ///
+    /// This answers the question "which sub call reverted".
+    pub fn first_failed(&self) -> Option<&CallTraceFrame> {
+        self.calls
+            .iter()
+            .find_map(Self::first_failed)
+            .or(self.error.as_ref().map(|_| self))
+    }
+
+    fn new(kind: CallKind, from: Address, to: Option<Address>, value: Option<U256>) -> Self {
+        Self {
+            kind,
+            from,
+            to,
+            value,
+            gas: 0,
+            gas_used: 0,
+            input: Bytes::new(),
+            output: None,
+            error: None,
+            revert_reason: None,
+            calls: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+
+    fn fill_result(&mut self, result: &InterpreterResult) {
+        if result.is_ok() {
+            self.output = Some(result.output.clone());
+            return;
+        }
+        self.error = Some(geth_error_string(result.result).into());
+        if result.is_revert() {
+            if !result.output.is_empty() {
+                self.output = Some(result.output.clone());
+            }
+            self.revert_reason = decode_revert_reason(&result.output);
+        }
+    }
+}
+
+/// Call tracer [Inspector] that builds a tree of all calls and creates
+/// made during transaction execution.
+///
+/// Output is compatible with geth's `callTracer`.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+    config: CallTracerConfig,
+    /// Frames that are currently executing.
+    stack: Vec<CallTraceFrame>,
+    /// Finished top level frame.
+    root: Option<CallTraceFrame>,
+    /// Depth of sub calls that are skipped because of [CallTracerConfig::only_top_call].
+    skipped: usize,
+}
+
+impl CallTracer {
+    /// Creates new call tracer with given config.
+    pub fn new(config: CallTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the config of the tracer.
+    pub fn config(&self) -> &CallTracerConfig {
+        &self.config
+    }
+
+    /// Returns the root frame of the last traced transaction.
+    pub fn root(&self) -> Option<&CallTraceFrame> {
+        self.root.as_ref()
+    }
+
+    /// Takes the root frame of the last traced transaction and resets the tracer.
+    pub fn take_root(&mut self) -> Option<CallTraceFrame> {
+        self.clear();
+        self.root.take()
+    }
+
+    /// Serializes the root frame into geth's `callTracer` JSON.
+    #[cfg(feature = "serde-json")]
+    pub fn to_json(&self) -> Option<serde_json::Value> {
+        self.root
+            .as_ref()
+            .map(|root| serde_json::to_value(root).expect("call frame is serializable"))
+    }
+
+    /// Resets the in-flight call stack.
+    pub fn clear(&mut self) {
+        self.stack.clear();
+        self.skipped = 0;
+    }
+
+    /// Returns `true` if the new frame should not be recorded.
+    fn skip_frame(&mut self) -> bool {
+        if self.config.only_top_call && !self.stack.is_empty() {
+            self.skipped += 1;
+            return true;
+        }
+        false
+    }
+
+    /// Returns `true` if the ended frame was not recorded.
+    fn skip_frame_end(&mut self) -> bool {
+        if self.skipped > 0 {
+            self.skipped -= 1;
+            return true;
+        }
+        false
+    }
+
+    fn push_frame<DB: Database>(&mut self, mut frame: CallTraceFrame, context: &EvmContext<DB>) {
+        if self.stack.is_empty() {
+            // top level frame, gas given to it is the transaction gas limit.
+            frame.gas = context.env.tx.gas_limit;
+            self.root = None;
+        }
+        self.stack.push(frame);
+    }
+
+    fn pop_frame<DB: Database>(
+        &mut self,
+        result: &InterpreterResult,
+        created: Option<Address>,
+        context: &EvmContext<DB>,
+    ) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        if created.is_some() {
+            frame.to = created;
+        }
+        frame.fill_result(result);
+
+        if let Some(parent) = self.stack.last_mut() {
+            frame.gas_used = result.gas.spent();
+            parent.calls.push(frame);
+        } else {
+            frame.gas_used = tx_gas_used(result, context);
+            self.root = Some(frame);
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CallTracer {
+    fn log(&mut self, _context: &mut EvmContext<DB>, log: &Log) {
+        if !self.config.with_log || self.skipped > 0 {
+            return;
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.logs.push(CallLog {
+                address: log.address,
+                topics: log.topics().to_vec(),
+                data: log.data.data.clone(),
+            });
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if self.skip_frame() {
+            return None;
+        }
+        let kind = CallKind::from(inputs.scheme);
+        let value = match kind {
+            CallKind::DelegateCall | CallKind::StaticCall => None,
+            _ => Some(inputs.call_value()),
+        };
+        let mut frame =
+            CallTraceFrame::new(kind, inputs.caller, Some(inputs.target_address), value);
+        frame.gas = inputs.gas_limit;
+        frame.input = inputs.input.clone();
+        self.push_frame(frame, context);
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        if !self.skip_frame_end() {
+            self.pop_frame(&outcome.result, None, context);
+        }
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        if self.skip_frame() {
+            return None;
+        }
+        let mut frame = CallTraceFrame::new(
+            inputs.scheme.into(),
+            inputs.caller,
+            None,
+            Some(inputs.value),
+        );
+        frame.gas = inputs.gas_limit;
+        frame.input = inputs.init_code.clone();
+        self.push_frame(frame, context);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if !self.skip_frame_end() {
+            self.pop_frame(&outcome.result, outcome.address, context);
+        }
+        outcome
+    }
+}
+
+/// Gas used by the whole transaction, including intrinsic gas and refunds,
+/// as reported by geth for the top level frame.
+fn tx_gas_used<DB: Database>(result: &InterpreterResult, context: &EvmContext<DB>) -> u64 {
+    let spent = context
+        .env
+        .tx
+        .gas_limit
+        .saturating_sub(result.gas.remaining());
+    if !result.is_ok() || context.env.cfg.is_gas_refund_disabled() {
+        return spent;
+    }
+    let max_refund_quotient = if context.spec_id().is_enabled_in(SpecId::LONDON) {
+        5
+    } else {
+        2
+    };
+    let refund = (result.gas.refunded().max(0) as u64).min(spent / max_refund_quotient);
+    spent - refund
+}
+
+/// Maps instruction result to the error string used by geth.
+fn geth_error_string(result: InstructionResult) -> &'static str {
+    match result {
+        InstructionResult::Revert => "execution reverted",
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG => "out of gas",
+        InstructionResult::CallTooDeep => "max call depth exceeded",
+        InstructionResult::OutOfFunds => "insufficient balance for transfer",
+        InstructionResult::OpcodeNotFound
+        | InstructionResult::InvalidFEOpcode
+        | InstructionResult::NotActivated
+        | InstructionResult::EOFOpcodeDisabledInLegacy => "invalid opcode",
+        InstructionResult::InvalidJump => "invalid jump destination",
+        InstructionResult::StackUnderflow => "stack underflow",
+        InstructionResult::StackOverflow => "stack limit reached",
+        InstructionResult::OutOfOffset => "return data out of bounds",
+        InstructionResult::CreateCollision => "contract address collision",
+        InstructionResult::CallNotAllowedInsideStatic
+        | InstructionResult::StateChangeDuringStaticCall => "write protection",
+        InstructionResult::NonceOverflow => "nonce uint64 overflow",
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded",
+        InstructionResult::CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+        InstructionResult::CreateInitCodeSizeLimit => "max initcode size exceeded",
+        InstructionResult::PrecompileError => "precompiled contract failed",
+        InstructionResult::OverflowPayment => "gas uint64 overflow",
+        _ => "execution failed",
+    }
+}
+
+/// Decodes solidity `Error(string)` revert payload.
+fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    match RevertReason::decode(output)? {
+        RevertReason::Error(reason) => Some(reason),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "serde")]
+mod hex_u64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::{format, string::String};
+
+    pub(super) fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:#x}"))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        u64::from_str_radix(s.trim_start_matches("0x"), 16).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, Bytecode, TransactTo, ERROR_STRING_SELECTOR},
+        Evm,
+    };
+
+    #[test]
+    fn decode_error_string() {
+        let mut data = ERROR_STRING_SELECTOR.to_vec();
+        data.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+        data.extend_from_slice(&U256::from(4).to_be_bytes::<32>());
+        data.extend_from_slice(b"fail");
+        data.extend_from_slice(&[0u8; 28]);
+        assert_eq!(decode_revert_reason(&data).as_deref(), Some("fail"));
+        assert_eq!(decode_revert_reason(&data[..40]), None);
+        assert_eq!(decode_revert_reason(&[0xde, 0xad]), None);
+    }
+
+    #[test]
+    fn trace_reverted_subcall() {
+        // Calls itself with a call depth guard: at depth 1 it reverts,
+        // at depth 0 it makes a CALL to itself and stops.
+        let contract = address!("0000000000000000000000000000000000000000");
+        let code = Bytecode::new_raw(
+            [
+                // if calldata is not empty, revert.
+                opcode::CALLDATASIZE,
+                opcode::PUSH1,
+                0x1b,
+                opcode::JUMPI,
+                // call self with one byte of calldata.
+                opcode::PUSH1,
+                0x00,
+                opcode::PUSH1,
+                0x00,
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::PUSH1,
+                0x00,
+                opcode::ADDRESS,
+                opcode::PUSH2,
+                0xff,
+                0xff,
+                opcode::CALL,
+                opcode::POP,
+                opcode::STOP,
+                opcode::INVALID,
+                opcode::INVALID,
+                opcode::INVALID,
+                opcode::INVALID,
+                opcode::INVALID,
+                opcode::INVALID,
+                opcode::JUMPDEST,
+                opcode::PUSH1,
+                0x00,
+                opcode::DUP1,
+                opcode::REVERT,
+            ]
+            .to_vec()
+            .into(),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .with_external_context(CallTracer::new(CallTracerConfig::default()))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+
+        let root = evm.context.external.root().unwrap().clone();
+        assert_eq!(root.kind, CallKind::Call);
+        assert_eq!(root.gas, 100_000);
+        assert_eq!(root.gas_used, result.result.gas_used());
+        assert!(root.error.is_none());
+        assert_eq!(root.calls.len(), 1);
+
+        let sub = &root.calls[0];
+        assert_eq!(sub.from, contract);
+        assert_eq!(sub.to, Some(contract));
+        assert_eq!(sub.input.as_ref(), &[0u8]);
+        assert_eq!(sub.error.as_deref(), Some("execution reverted"));
+        assert_eq!(root.first_failed(), Some(sub));
+        assert!(root.has_error());
+    }
+}
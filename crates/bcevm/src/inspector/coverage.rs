@@ -0,0 +1,206 @@
+//! Coverage inspector. Records the executed byte offsets of every contract, for coverage
+//! reports of contract tests.
+
+use crate::{
+    interpreter::Interpreter,
+    primitives::{db::Database, HashMap, B256},
+    EvmContext, Inspector,
+};
+use std::vec::Vec;
+
+/// Bitmap of the executed byte offsets of a bytecode.
+///
+/// For EOF bytecode the offsets are relative to the start of the executed code section.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoverageBitmap {
+    words: Vec<u64>,
+}
+
+impl CoverageBitmap {
+    /// Marks the offset as executed.
+    #[inline]
+    pub fn insert(&mut self, pc: usize) {
+        let word = pc / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (pc % 64);
+    }
+
+    /// Returns true if the offset was executed.
+    #[inline]
+    pub fn contains(&self, pc: usize) -> bool {
+        self.words
+            .get(pc / 64)
+            .is_some_and(|word| word & (1 << (pc % 64)) != 0)
+    }
+
+    /// Returns the number of executed offsets.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns true if no offset was executed.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// Returns iterator over the executed offsets, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+
+    /// Adds the executed offsets of the other bitmap.
+    pub fn merge(&mut self, other: &Self) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+}
+
+/// Executed byte offsets by code hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoverageMap {
+    /// Bitmap of every executed bytecode, by its hash.
+    ///
+    /// Initcode is keyed by the hash of the initcode.
+    pub contracts: HashMap<B256, CoverageBitmap>,
+}
+
+impl CoverageMap {
+    /// Returns the bitmap of the code hash, if the code was executed.
+    pub fn get(&self, code_hash: &B256) -> Option<&CoverageBitmap> {
+        self.contracts.get(code_hash)
+    }
+
+    /// Adds the coverage of the other map, e.g. of another run.
+    pub fn merge(&mut self, other: &Self) {
+        for (code_hash, bitmap) in &other.contracts {
+            self.contracts.entry(*code_hash).or_default().merge(bitmap);
+        }
+    }
+}
+
+/// [Inspector] that records which byte offsets of every executed bytecode were executed,
+/// see [CoverageMap].
+///
+/// Coverage is accumulated across transactions until it is taken with
+/// [CoverageInspector::take_coverage].
+#[derive(Clone, Debug, Default)]
+pub struct CoverageInspector {
+    coverage: CoverageMap,
+    /// Code hash of the executing frame, indexed by the journal depth of the frame.
+    code_hashes: Vec<B256>,
+}
+
+impl CoverageInspector {
+    /// Creates new coverage inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded coverage.
+    pub fn coverage(&self) -> &CoverageMap {
+        &self.coverage
+    }
+
+    /// Returns the recorded coverage and clears it.
+    pub fn take_coverage(&mut self) -> CoverageMap {
+        core::mem::take(&mut self.coverage)
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CoverageInspector {
+    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let code_hash = interp
+            .contract
+            .hash
+            .unwrap_or_else(|| interp.contract.bytecode.hash_slow());
+        let depth = context.journaled_state.depth;
+        if self.code_hashes.len() <= depth {
+            self.code_hashes.resize(depth + 1, B256::ZERO);
+        }
+        self.code_hashes[depth] = code_hash;
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let Some(code_hash) = self.code_hashes.get(context.journaled_state.depth) else {
+            return;
+        };
+        self.coverage
+            .contracts
+            .entry(*code_hash)
+            .or_default()
+            .insert(interp.program_counter());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, Bytecode, Bytes, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn coverage() {
+        // JUMPI(5, CALLDATASIZE), STOP at 4 without calldata and at 6 with calldata.
+        let code = Bytecode::new_raw(
+            [
+                opcode::CALLDATASIZE,
+                opcode::PUSH1,
+                5,
+                opcode::JUMPI,
+                opcode::STOP,
+                opcode::JUMPDEST,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let code_hash = code.hash_slow();
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .with_external_context(CoverageInspector::new())
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        assert!(evm.transact().unwrap().result.is_success());
+        let mut coverage = evm.context.external.take_coverage();
+        let bitmap = coverage.get(&code_hash).unwrap();
+        assert_eq!(bitmap.iter().collect::<Vec<_>>(), [0, 1, 3, 4]);
+        assert!(!bitmap.contains(2));
+
+        evm.tx_mut().data = Bytes::from_static(&[1]);
+        assert!(evm.transact().unwrap().result.is_success());
+        let other = evm.context.external.take_coverage();
+        assert_eq!(other.get(&code_hash).unwrap().len(), 5);
+
+        coverage.merge(&other);
+        let bitmap = coverage.get(&code_hash).unwrap();
+        assert_eq!(bitmap.iter().collect::<Vec<_>>(), [0, 1, 3, 4, 5, 6]);
+        assert_eq!(coverage.contracts.len(), 1);
+    }
+}
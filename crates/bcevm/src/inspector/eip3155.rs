@@ -1,10 +1,11 @@
 use crate::{
     inspectors::GasInspector,
     interpreter::{
-        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, InterpreterResult,
+        opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+        InterpreterResult,
     },
-    primitives::{db::Database, hex, HashMap, B256, U256},
-    EvmContext, Inspector,
+    primitives::{db::Database, hex, Address, HashMap, B256, U256},
+    EvmContext, Inspector, JournalEntry,
 };
 use bcevm_interpreter::OpCode;
 use serde::Serialize;
@@ -27,6 +28,9 @@ pub struct TracerEip3155 {
     skip: bool,
     include_memory: bool,
     memory: Option<String>,
+    include_transient_storage: bool,
+    journal_len: usize,
+    transient_load: Option<(Address, U256)>,
 }
 
 // # Output
@@ -67,6 +71,9 @@ struct Output {
     /// Array of all stored values
     #[serde(default, skip_serializing_if = "Option::is_none")]
     storage: Option<HashMap<String, String>>,
+    /// Transient storage slots read or changed by this operation, with their values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transient_storage: Option<HashMap<String, String>>,
     /// Array of values, Stack of the called function
     #[serde(default, skip_serializing_if = "Option::is_none")]
     return_stack: Option<Vec<String>>,
@@ -133,6 +140,9 @@ impl TracerEip3155 {
             gas_inspector: GasInspector::default(),
             print_summary: true,
             include_memory: false,
+            include_transient_storage: false,
+            journal_len: 0,
+            transient_load: None,
             stack: Default::default(),
             memory: Default::default(),
             pc: 0,
@@ -156,6 +166,12 @@ impl TracerEip3155 {
         self
     }
 
+    /// Include a transient storage field for steps that read or change EIP-1153 transient storage.
+    pub fn with_transient_storage(mut self) -> Self {
+        self.include_transient_storage = true;
+        self
+    }
+
     fn write_value(&mut self, value: &impl serde::Serialize) -> std::io::Result<()> {
         serde_json::to_writer(&mut *self.output, value)?;
         self.output.write_all(b"\n")?;
@@ -182,6 +198,36 @@ impl TracerEip3155 {
             let _ = self.write_value(&value);
         }
     }
+
+    /// Returns transient storage slots read by the current step or changed by it, changes
+    /// are taken from the journal.
+    fn transient_storage<DB: Database>(
+        &self,
+        context: &EvmContext<DB>,
+    ) -> Option<HashMap<String, String>> {
+        if !self.include_transient_storage {
+            return None;
+        }
+        let journaled_state = &context.journaled_state;
+        let entries = journaled_state.journal.last()?.get(self.journal_len..)?;
+        let slots: HashMap<_, _> = entries
+            .iter()
+            .filter_map(|entry| match entry {
+                JournalEntry::TransientStorageChange { address, key, .. } => Some((*address, *key)),
+                _ => None,
+            })
+            .chain(self.transient_load)
+            .map(|(address, key)| {
+                let value = journaled_state
+                    .transient_storage
+                    .get(&(address, key))
+                    .copied()
+                    .unwrap_or_default();
+                (hex_number_u256(&key), hex_number_u256(&value))
+            })
+            .collect();
+        (!slots.is_empty()).then_some(slots)
+    }
 }
 
 impl<DB: Database> Inspector<DB> for TracerEip3155 {
@@ -202,6 +248,14 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
         self.mem_size = interp.shared_memory.len();
         self.gas = interp.gas.remaining();
         self.refunded = interp.gas.refunded();
+        if self.include_transient_storage {
+            self.journal_len = context.journaled_state.journal.last().map_or(0, Vec::len);
+            // reads are not journaled, the slot is taken from the stack.
+            self.transient_load = (self.opcode == opcode::TLOAD)
+                .then(|| interp.stack.peek(0).ok())
+                .flatten()
+                .map(|key| (interp.contract.target_address, key));
+        }
     }
 
     fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
@@ -230,6 +284,7 @@ impl<DB: Database> Inspector<DB> for TracerEip3155 {
             },
             memory: self.memory.take(),
             storage: None,
+            transient_storage: self.transient_storage(context),
             return_stack: None,
         };
         let _ = self.write_value(&value);
@@ -5,8 +5,8 @@ use crate::{
     primitives::EVMError,
     Evm, FrameOrResult, FrameResult, Inspector, JournalEntry,
 };
-use core::cell::RefCell;
 use bcevm_interpreter::opcode::InstructionTables;
+use core::{cell::RefCell, ops::DerefMut};
 use std::{boxed::Box, rc::Rc, sync::Arc, vec::Vec};
 
 /// Provides access to an `Inspector` instance.
@@ -36,26 +36,80 @@ impl<DB: Database, INSP: Inspector<DB>> GetInspector<DB> for INSP {
 /// `log` and `selfdestruct` calls.
 pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
     handler: &mut EvmHandler<'a, EXT, DB>,
+) {
+    register_inspector_handles(handler, ExternalInspector);
+}
+
+/// Register Inspector handles that call the inspector owned by the handles, external context
+/// is left untouched. See [inspector_handle_register].
+pub(crate) fn owned_inspector_handle_register<'a, DB: Database, EXT, INSP: Inspector<DB> + 'a>(
+    handler: &mut EvmHandler<'a, EXT, DB>,
+    inspector: Rc<RefCell<INSP>>,
+) {
+    register_inspector_handles(handler, inspector);
+}
+
+/// Gives the inspector handles access to the inspector.
+trait InspectorAccess<EXT, DB: Database>: Clone {
+    /// Returns the inspector, `external` is the external context of the EVM.
+    fn inspector<'s>(&'s self, external: &'s mut EXT) -> impl DerefMut<Target = impl Inspector<DB>>
+    where
+        DB: 's;
+}
+
+/// Inspector is the external context, see [GetInspector].
+#[derive(Clone, Copy)]
+struct ExternalInspector;
+
+impl<DB: Database, EXT: GetInspector<DB>> InspectorAccess<EXT, DB> for ExternalInspector {
+    #[inline]
+    fn inspector<'s>(&'s self, external: &'s mut EXT) -> impl DerefMut<Target = impl Inspector<DB>>
+    where
+        DB: 's,
+    {
+        external.get_inspector()
+    }
+}
+
+impl<DB: Database, EXT, INSP: Inspector<DB>> InspectorAccess<EXT, DB> for Rc<RefCell<INSP>> {
+    #[inline]
+    fn inspector<'s>(&'s self, _external: &'s mut EXT) -> impl DerefMut<Target = impl Inspector<DB>>
+    where
+        DB: 's,
+    {
+        self.borrow_mut()
+    }
+}
+
+/// Registers the inspector handles that reach the inspector through `access`.
+fn register_inspector_handles<'a, DB: Database, EXT, ACCESS: InspectorAccess<EXT, DB> + 'a>(
+    handler: &mut EvmHandler<'a, EXT, DB>,
+    access: ACCESS,
 ) {
     // Every instruction inside flat table that is going to be wrapped by inspector calls.
-    let table = handler
+    let mut table = handler
         .take_instruction_table()
         .expect("Handler must have instruction table");
+    table.convert_plain();
     let mut table = match table {
         InstructionTables::Plain(table) => table
             .into_iter()
-            .map(|i| inspector_instruction(i))
+            .map(|i| access_instruction(i, access.clone()))
             .collect::<Vec<_>>(),
         InstructionTables::Boxed(table) => table
             .into_iter()
-            .map(|i| inspector_instruction(i))
+            .map(|i| access_instruction(i, access.clone()))
             .collect::<Vec<_>>(),
+        InstructionTables::Specialized(_) | InstructionTables::Cached(_) => {
+            unreachable!("we already converted the table to plain variant")
+        }
     };
 
     // Register inspector Log instruction.
     let mut inspect_log = |index: u8| {
         if let Some(i) = table.get_mut(index as usize) {
             let old = core::mem::replace(i, Box::new(|_, _| ()));
+            let access = access.clone();
             *i = Box::new(
                 move |interpreter: &mut Interpreter, host: &mut Evm<'a, EXT, DB>| {
                     let old_log_len = host.context.evm.journaled_state.logs.len();
@@ -75,9 +129,8 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
                             .unwrap()
                             .clone();
                         // call Inspector
-                        host.context
-                            .external
-                            .get_inspector()
+                        access
+                            .inspector(&mut host.context.external)
                             .log(&mut host.context.evm, &last_log);
                     }
                 },
@@ -94,6 +147,7 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
     // // register selfdestruct function.
     if let Some(i) = table.get_mut(opcode::SELFDESTRUCT as usize) {
         let old = core::mem::replace(i, Box::new(|_, _| ()));
+        let access = access.clone();
         *i = Box::new(
             move |interpreter: &mut Interpreter, host: &mut Evm<'a, EXT, DB>| {
                 // execute selfdestruct
@@ -113,7 +167,7 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
                     .unwrap()
                     .last()
                 {
-                    host.context.external.get_inspector().selfdestruct(
+                    access.inspector(&mut host.context.external).selfdestruct(
                         *address,
                         *target,
                         *had_balance,
@@ -136,12 +190,15 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
 
     // Create handler
     let create_input_stack_inner = create_input_stack.clone();
+    let access_inner = access.clone();
     let old_handle = handler.execution.create.clone();
     handler.execution.create = Arc::new(
         move |ctx, mut inputs| -> Result<FrameOrResult, EVMError<DB::Error>> {
-            let inspector = ctx.external.get_inspector();
             // call inspector create to change input or return outcome.
-            if let Some(outcome) = inspector.create(&mut ctx.evm, &mut inputs) {
+            let outcome = access_inner
+                .inspector(&mut ctx.external)
+                .create(&mut ctx.evm, &mut inputs);
+            if let Some(outcome) = outcome {
                 create_input_stack_inner.borrow_mut().push(inputs.clone());
                 return Ok(FrameOrResult::Result(FrameResult::Create(outcome)));
             }
@@ -149,8 +206,8 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
 
             let mut frame_or_result = old_handle(ctx, inputs);
             if let Ok(FrameOrResult::Frame(frame)) = &mut frame_or_result {
-                ctx.external
-                    .get_inspector()
+                access_inner
+                    .inspector(&mut ctx.external)
                     .initialize_interp(frame.interpreter_mut(), &mut ctx.evm)
             }
             frame_or_result
@@ -159,21 +216,36 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
 
     // Call handler
     let call_input_stack_inner = call_input_stack.clone();
+    let access_inner = access.clone();
     let old_handle = handler.execution.call.clone();
     handler.execution.call = Arc::new(
         move |ctx, mut inputs| -> Result<FrameOrResult, EVMError<DB::Error>> {
             // Call inspector to change input or return outcome.
-            let outcome = ctx.external.get_inspector().call(&mut ctx.evm, &mut inputs);
+            let outcome = access_inner
+                .inspector(&mut ctx.external)
+                .call(&mut ctx.evm, &mut inputs);
             call_input_stack_inner.borrow_mut().push(inputs.clone());
             if let Some(outcome) = outcome {
                 return Ok(FrameOrResult::Result(FrameResult::Call(outcome)));
             }
 
+            let logs_len = ctx.evm.journaled_state.logs.len();
             let mut frame_or_result = old_handle(ctx, inputs);
-            if let Ok(FrameOrResult::Frame(frame)) = &mut frame_or_result {
-                ctx.external
-                    .get_inspector()
-                    .initialize_interp(frame.interpreter_mut(), &mut ctx.evm)
+            match &mut frame_or_result {
+                Ok(FrameOrResult::Frame(frame)) => access_inner
+                    .inspector(&mut ctx.external)
+                    .initialize_interp(frame.interpreter_mut(), &mut ctx.evm),
+                // logs emitted by precompiles don't go through the log instructions.
+                Ok(FrameOrResult::Result(_)) => {
+                    let logs = ctx.evm.journaled_state.logs.split_off(logs_len);
+                    for log in &logs {
+                        access_inner
+                            .inspector(&mut ctx.external)
+                            .log(&mut ctx.evm, log);
+                    }
+                    ctx.evm.journaled_state.logs.extend(logs);
+                }
+                Err(_) => {}
             }
             frame_or_result
         },
@@ -183,26 +255,30 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
 
     // call outcome
     let call_input_stack_inner = call_input_stack.clone();
+    let access_inner = access.clone();
     let old_handle = handler.execution.insert_call_outcome.clone();
     handler.execution.insert_call_outcome =
         Arc::new(move |ctx, frame, shared_memory, mut outcome| {
             let call_inputs = call_input_stack_inner.borrow_mut().pop().unwrap();
-            outcome = ctx
-                .external
-                .get_inspector()
-                .call_end(&mut ctx.evm, &call_inputs, outcome);
+            outcome = access_inner.inspector(&mut ctx.external).call_end(
+                &mut ctx.evm,
+                &call_inputs,
+                outcome,
+            );
             old_handle(ctx, frame, shared_memory, outcome)
         });
 
     // create outcome
     let create_input_stack_inner = create_input_stack.clone();
+    let access_inner = access.clone();
     let old_handle = handler.execution.insert_create_outcome.clone();
     handler.execution.insert_create_outcome = Arc::new(move |ctx, frame, mut outcome| {
         let create_inputs = create_input_stack_inner.borrow_mut().pop().unwrap();
-        outcome = ctx
-            .external
-            .get_inspector()
-            .create_end(&mut ctx.evm, &create_inputs, outcome);
+        outcome = access_inner.inspector(&mut ctx.external).create_end(
+            &mut ctx.evm,
+            &create_inputs,
+            outcome,
+        );
         old_handle(ctx, frame, outcome)
     });
 
@@ -211,7 +287,7 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
     // last frame outcome
     let old_handle = handler.execution.last_frame_return.clone();
     handler.execution.last_frame_return = Arc::new(move |ctx, frame_result| {
-        let inspector = ctx.external.get_inspector();
+        let mut inspector = access.inspector(&mut ctx.external);
         match frame_result {
             FrameResult::Call(outcome) => {
                 let call_inputs = call_input_stack.borrow_mut().pop().unwrap();
@@ -227,6 +303,7 @@ pub fn inspector_handle_register<'a, DB: Database, EXT: GetInspector<DB>>(
                     inspector.eofcreate_end(&mut ctx.evm, &eofcreate_inputs, outcome.clone());
             }
         }
+        drop(inspector);
         old_handle(ctx, frame_result)
     });
 }
@@ -240,15 +317,28 @@ pub fn inspector_instruction<
 >(
     instruction: Instruction,
 ) -> BoxedInstruction<'a, Evm<'a, INSP, DB>> {
+    access_instruction(instruction, ExternalInspector)
+}
+
+/// Outer closure that calls the inspector given by `access` for every instruction.
+fn access_instruction<
+    'a,
+    EXT,
+    DB: Database,
+    ACCESS: InspectorAccess<EXT, DB> + 'a,
+    Instruction: Fn(&mut Interpreter, &mut Evm<'a, EXT, DB>) + 'a,
+>(
+    instruction: Instruction,
+    access: ACCESS,
+) -> BoxedInstruction<'a, Evm<'a, EXT, DB>> {
     Box::new(
-        move |interpreter: &mut Interpreter, host: &mut Evm<'a, INSP, DB>| {
+        move |interpreter: &mut Interpreter, host: &mut Evm<'a, EXT, DB>| {
             // SAFETY: as the PC was already incremented we need to subtract 1 to preserve the
             // old Inspector behavior.
             interpreter.instruction_pointer = unsafe { interpreter.instruction_pointer.sub(1) };
 
-            host.context
-                .external
-                .get_inspector()
+            access
+                .inspector(&mut host.context.external)
                 .step(interpreter, &mut host.context.evm);
             if interpreter.instruction_result != InstructionResult::Continue {
                 return;
@@ -260,9 +350,8 @@ pub fn inspector_instruction<
             // execute instruction.
             instruction(interpreter, host);
 
-            host.context
-                .external
-                .get_inspector()
+            access
+                .inspector(&mut host.context.external)
                 .step_end(interpreter, &mut host.context.evm);
         },
     )
@@ -422,4 +511,53 @@ mod tests {
             .append_handler_register(inspector_handle_register)
             .build();
     }
+
+    #[test]
+    fn precompile_logs_are_inspected() {
+        use crate::{
+            primitives::{
+                address, Bytes, Log, Precompile, PrecompileOutput, PrecompileResult, TransactTo,
+            },
+            ContextPrecompile,
+        };
+
+        #[derive(Default)]
+        struct LogInspector {
+            logs: Vec<Log>,
+        }
+
+        impl<DB: Database> Inspector<DB> for LogInspector {
+            fn log(&mut self, _context: &mut EvmContext<DB>, log: &Log) {
+                self.logs.push(log.clone());
+            }
+        }
+
+        const PRECOMPILE: crate::primitives::Address =
+            address!("0000000000000000000000000000000000000100");
+
+        fn logging_precompile(input: &Bytes, _gas_limit: u64) -> PrecompileResult {
+            let log = Log::new_unchecked(PRECOMPILE, Vec::new(), input.clone());
+            Ok(PrecompileOutput::new(100, Bytes::new()).with_logs(vec![log]))
+        }
+
+        let mut inspector = LogInspector::default();
+        let mut evm = Evm::builder()
+            .with_db(EmptyDB::default())
+            .with_external_context(&mut inspector)
+            .append_precompile(
+                PRECOMPILE,
+                ContextPrecompile::Ordinary(Precompile::Standard(logging_precompile)),
+            )
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(PRECOMPILE);
+                tx.data = Bytes::from_static(&[1, 2, 3]);
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        let result = evm.transact().unwrap();
+        drop(evm);
+        assert_eq!(result.result.logs().len(), 1);
+        assert_eq!(inspector.logs, result.result.logs());
+    }
 }
@@ -0,0 +1,103 @@
+//! Inspector that streams logs to a channel while the transaction is executing.
+
+use crate::{
+    primitives::{db::Database, Log},
+    EvmContext, Inspector,
+};
+use std::sync::mpsc::{Receiver, Sender};
+
+/// [Inspector] that sends every emitted log to a channel.
+///
+/// Logs are sent when they are emitted, so long running simulations can consume them before
+/// `transact` returns. Logs of calls that revert later are sent as well, the final logs of
+/// the transaction are in the [ExecutionResult](crate::primitives::ExecutionResult).
+///
+/// Sending stops if the receiver is dropped, the execution is not affected.
+#[derive(Clone, Debug)]
+pub struct LogStreamer {
+    sender: Sender<Log>,
+    disconnected: bool,
+}
+
+impl LogStreamer {
+    /// Creates new streamer that sends logs to the given sender.
+    pub fn new(sender: Sender<Log>) -> Self {
+        Self {
+            sender,
+            disconnected: false,
+        }
+    }
+
+    /// Creates new streamer and the receiver of its logs.
+    pub fn channel() -> (Self, Receiver<Log>) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        (Self::new(sender), receiver)
+    }
+
+    /// Returns true if the receiver was dropped and logs are no longer sent.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+}
+
+impl<DB: Database> Inspector<DB> for LogStreamer {
+    #[inline]
+    fn log(&mut self, _context: &mut EvmContext<DB>, log: &Log) {
+        if !self.disconnected && self.sender.send(log.clone()).is_err() {
+            self.disconnected = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, Bytecode, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn streams_logs() {
+        // LOG0(0, 0) twice.
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::LOG0,
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::LOG0,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let contract = address!("0000000000000000000000000000000000000000");
+        let (streamer, receiver) = LogStreamer::channel();
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .with_external_context(streamer)
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        let result = evm.transact().unwrap().result;
+        let streamed: Vec<Log> = receiver.try_iter().collect();
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed, result.logs());
+        assert!(streamed.iter().all(|log| log.address == contract));
+
+        // execution continues without the receiver.
+        drop(receiver);
+        assert!(evm.transact().unwrap().result.is_success());
+        assert!(evm.context.external.is_disconnected());
+    }
+}
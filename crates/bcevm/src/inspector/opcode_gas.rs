@@ -0,0 +1,262 @@
+//! Opcode gas inspector. Aggregates the number of executions and the gas spent per
+//! opcode, without the cost of a full step by step trace.
+
+use crate::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInput, EOFCreateOutcome,
+        Interpreter, OpCode,
+    },
+    primitives::{db::Database, EVMError, ResultAndState},
+    Evm, EvmContext, Inspector,
+};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Number of executions and gas spent by a single opcode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpcodeGas {
+    /// Number of times the opcode was executed.
+    pub count: u64,
+    /// Total gas spent by the opcode.
+    pub gas: u64,
+}
+
+/// Gas spent per opcode during a transaction, ordered by opcode.
+///
+/// Gas of the call and create opcodes excludes the gas spent by the called frame,
+/// that is accounted to the opcodes executed in it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpcodeGasReport {
+    /// Executed opcodes.
+    pub opcodes: BTreeMap<u8, OpcodeGas>,
+}
+
+impl OpcodeGasReport {
+    /// Returns the entry of the opcode, if it was executed.
+    pub fn get(&self, opcode: u8) -> Option<&OpcodeGas> {
+        self.opcodes.get(&opcode)
+    }
+
+    /// Returns total number of executed opcodes.
+    pub fn total_count(&self) -> u64 {
+        self.opcodes.values().map(|op| op.count).sum()
+    }
+
+    /// Returns total gas spent by all opcodes.
+    pub fn total_gas(&self) -> u64 {
+        self.opcodes.values().map(|op| op.gas).sum()
+    }
+
+    /// Returns iterator over the opcode names and their entries.
+    pub fn iter_named(&self) -> impl Iterator<Item = (&'static str, &OpcodeGas)> {
+        self.opcodes
+            .iter()
+            .map(|(opcode, gas)| (OpCode::name_by_op(*opcode), gas))
+    }
+}
+
+/// [Inspector] that aggregates count and gas per opcode, see [OpcodeGasReport].
+#[derive(Clone, Debug)]
+pub struct OpcodeGasInspector {
+    opcodes: [OpcodeGas; 256],
+    /// Opcode of the current step.
+    opcode: u8,
+    /// Gas remaining before the current step.
+    gas_remaining: u64,
+    /// Last executed opcode, indexed by the journal depth of its frame.
+    last_opcode: Vec<u8>,
+}
+
+impl Default for OpcodeGasInspector {
+    fn default() -> Self {
+        Self {
+            opcodes: [OpcodeGas::default(); 256],
+            opcode: 0,
+            gas_remaining: 0,
+            last_opcode: Vec::new(),
+        }
+    }
+}
+
+impl OpcodeGasInspector {
+    /// Creates new opcode gas inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the report of the last transaction.
+    pub fn report(&self) -> OpcodeGasReport {
+        OpcodeGasReport {
+            opcodes: self
+                .opcodes
+                .iter()
+                .enumerate()
+                .filter(|(_, op)| op.count != 0)
+                .map(|(opcode, op)| (opcode as u8, *op))
+                .collect(),
+        }
+    }
+
+    /// Clears the recorded opcodes.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Removes gas given to the finished frame from the opcode that created it.
+    fn frame_end<DB: Database>(&mut self, context: &EvmContext<DB>, gas_limit: u64) {
+        let depth = context.journaled_state.depth;
+        if depth == 0 {
+            return;
+        }
+        if let Some(&opcode) = self.last_opcode.get(depth) {
+            let op = &mut self.opcodes[opcode as usize];
+            op.gas = op.gas.saturating_sub(gas_limit);
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for OpcodeGasInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        self.opcode = interp.current_opcode();
+        self.gas_remaining = interp.gas.remaining();
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let opcode = self.opcode;
+        let op = &mut self.opcodes[opcode as usize];
+        op.count += 1;
+        op.gas += self.gas_remaining.saturating_sub(interp.gas.remaining());
+
+        let depth = context.journaled_state.depth;
+        if self.last_opcode.len() <= depth {
+            self.last_opcode.resize(depth + 1, 0);
+        }
+        self.last_opcode[depth] = opcode;
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if context.journaled_state.depth() == 0 {
+            self.clear();
+        }
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.frame_end(context, inputs.gas_limit);
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        if context.journaled_state.depth() == 0 {
+            self.clear();
+        }
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.frame_end(context, inputs.gas_limit);
+        outcome
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &EOFCreateInput,
+        outcome: EOFCreateOutcome,
+    ) -> EOFCreateOutcome {
+        self.frame_end(context, inputs.gas_limit);
+        outcome
+    }
+}
+
+impl<DB: Database> Evm<'_, OpcodeGasInspector, DB> {
+    /// Executes the transaction without committing it and returns the gas spent per opcode.
+    ///
+    /// Evm needs to be built with [crate::inspector_handle_register].
+    pub fn transact_with_opcode_gas(
+        &mut self,
+    ) -> Result<(ResultAndState, OpcodeGasReport), EVMError<DB::Error>> {
+        let result = self.transact()?;
+        Ok((result, self.context.external.report()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, Bytecode, TransactTo},
+    };
+
+    #[test]
+    fn opcode_gas_report() {
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x02,
+                opcode::ADD,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .with_external_context(OpcodeGasInspector::new())
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        let (result, report) = evm.transact_with_opcode_gas().unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            report.get(opcode::PUSH1),
+            Some(&OpcodeGas { count: 3, gas: 9 })
+        );
+        assert_eq!(
+            report.get(opcode::ADD),
+            Some(&OpcodeGas { count: 1, gas: 3 })
+        );
+        assert_eq!(
+            report.get(opcode::STOP),
+            Some(&OpcodeGas { count: 1, gas: 0 })
+        );
+        assert_eq!(report.total_count(), 6);
+        // 21000 intrinsic gas, SSTORE is the rest.
+        assert_eq!(
+            report.get(opcode::SSTORE).unwrap().gas,
+            result.result.gas_used() - 21_000 - 12
+        );
+        assert_eq!(report.iter_named().next().unwrap().0, "STOP");
+    }
+}
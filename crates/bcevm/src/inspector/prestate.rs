@@ -0,0 +1,367 @@
+//! Prestate tracer inspector. Records the pre-transaction state of all touched
+//! accounts and storage slots, compatible with the output of geth's `prestateTracer`.
+
+use crate::{
+    interpreter::{opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter},
+    primitives::{db::Database, Address, Bytes, State, B256, KECCAK_EMPTY, U256},
+    EvmContext, Inspector,
+};
+use std::collections::BTreeMap;
+
+/// State of a single account as reported by the [PrestateTracer].
+///
+/// Empty fields are omitted from serialized output, same as in geth.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrestateAccount {
+    /// Account balance.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub balance: Option<U256>,
+    /// Account nonce.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub nonce: Option<u64>,
+    /// Account code.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code: Option<Bytes>,
+    /// Accessed storage slots.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+impl PrestateAccount {
+    /// Returns `true` if no field is set.
+    pub fn is_empty(&self) -> bool {
+        self.balance.is_none()
+            && self.nonce.is_none()
+            && self.code.is_none()
+            && self.storage.is_empty()
+    }
+}
+
+/// Accounts and storage keyed by address.
+pub type PrestateAccounts = BTreeMap<Address, PrestateAccount>;
+
+/// Output of the prestate tracer in diff mode.
+///
+/// `pre` contains the modified fields before the transaction and `post` contains
+/// their values after the transaction. Destructed accounts are omitted from `post`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrestateDiff {
+    pub pre: PrestateAccounts,
+    pub post: PrestateAccounts,
+}
+
+/// Prestate tracer [Inspector].
+///
+/// Pre-state of an account or slot is read from the database on its first access.
+/// Database is not modified until the transaction is committed, so the values read
+/// are the ones from before the transaction.
+///
+/// Use [PrestateTracer::prestate] for geth's default mode and [PrestateTracer::diff]
+/// with the state returned from the transaction for geth's `diffMode`.
+#[derive(Clone, Debug, Default)]
+pub struct PrestateTracer {
+    prestate: PrestateAccounts,
+}
+
+impl PrestateTracer {
+    /// Creates new prestate tracer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pre-transaction state of all touched accounts and slots.
+    pub fn prestate(&self) -> &PrestateAccounts {
+        &self.prestate
+    }
+
+    /// Takes the recorded prestate, leaving the tracer empty for the next transaction.
+    pub fn take_prestate(&mut self) -> PrestateAccounts {
+        core::mem::take(&mut self.prestate)
+    }
+
+    /// Clears the recorded prestate.
+    pub fn clear(&mut self) {
+        self.prestate.clear();
+    }
+
+    /// Computes the diff between the recorded prestate and the post-transaction `state`
+    /// (as returned in [crate::primitives::ResultAndState]).
+    ///
+    /// Only accounts and fields that changed are included.
+    pub fn diff(&self, state: &State) -> PrestateDiff {
+        let mut diff = PrestateDiff::default();
+        for (address, account) in state {
+            if !account.is_touched() {
+                continue;
+            }
+            let Some(pre) = self.prestate.get(address) else {
+                continue;
+            };
+
+            if account.is_selfdestructed() {
+                diff.pre.insert(*address, pre.clone());
+                continue;
+            }
+
+            let mut pre_changed = PrestateAccount::default();
+            let mut post = PrestateAccount::default();
+
+            let balance = account.info.balance;
+            if pre.balance.unwrap_or_default() != balance {
+                pre_changed.balance = pre.balance;
+                post.balance = Some(balance);
+            }
+            let nonce = account.info.nonce;
+            if pre.nonce.unwrap_or_default() != nonce {
+                pre_changed.nonce = pre.nonce;
+                post.nonce = Some(nonce);
+            }
+            if account.info.code_hash != KECCAK_EMPTY && pre.code.is_none() {
+                post.code = account.info.code.as_ref().map(|code| code.original_bytes());
+            }
+            for (slot, value) in &account.storage {
+                if !value.is_changed() {
+                    continue;
+                }
+                let key = B256::from(*slot);
+                if let Some(pre_value) = pre.storage.get(&key) {
+                    pre_changed.storage.insert(key, *pre_value);
+                }
+                // zero values are omitted from post state.
+                if value.present_value() != U256::ZERO {
+                    post.storage.insert(key, value.present_value().into());
+                }
+            }
+
+            if pre_changed.is_empty() && post.is_empty() {
+                continue;
+            }
+            // geth keeps the full account fields in pre for changed accounts.
+            pre_changed.balance = pre.balance;
+            pre_changed.nonce = pre.nonce;
+            pre_changed.code.clone_from(&pre.code);
+            diff.pre.insert(*address, pre_changed);
+            if !post.is_empty() {
+                diff.post.insert(*address, post);
+            }
+        }
+        diff
+    }
+
+    /// Records the pre-state of the account if it was not yet recorded.
+    fn touch_account<DB: Database>(&mut self, context: &mut EvmContext<DB>, address: Address) {
+        if self.prestate.contains_key(&address) {
+            return;
+        }
+        let mut account = PrestateAccount::default();
+        if let Ok(Some(info)) = context.db.basic(address) {
+            account.balance = Some(info.balance);
+            if info.nonce != 0 {
+                account.nonce = Some(info.nonce);
+            }
+            if info.code_hash != KECCAK_EMPTY {
+                let code = match info.code {
+                    Some(code) => Ok(code),
+                    None => context.db.code_by_hash(info.code_hash),
+                };
+                account.code = code
+                    .ok()
+                    .map(|code| code.original_bytes())
+                    .filter(|code| !code.is_empty());
+            }
+        }
+        self.prestate.insert(address, account);
+    }
+
+    /// Records the pre-state of the storage slot if it was not yet recorded.
+    fn touch_slot<DB: Database>(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        address: Address,
+        slot: U256,
+    ) {
+        self.touch_account(context, address);
+        let key = B256::from(slot);
+        let account = self.prestate.get_mut(&address).expect("account is touched");
+        if account.storage.contains_key(&key) {
+            return;
+        }
+        if let Ok(value) = context.db.storage(address, slot) {
+            account.storage.insert(key, value.into());
+        }
+    }
+
+    /// Records sender and coinbase of the transaction.
+    fn touch_tx<DB: Database>(&mut self, context: &mut EvmContext<DB>) {
+        if context.journaled_state.depth() != 0 {
+            return;
+        }
+        let caller = context.env.tx.caller;
+        let coinbase = context.env.block.coinbase;
+        self.touch_account(context, caller);
+        self.touch_account(context, coinbase);
+    }
+}
+
+impl<DB: Database> Inspector<DB> for PrestateTracer {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let address_at = |n: usize| {
+            interp
+                .stack
+                .peek(n)
+                .ok()
+                .map(|value| Address::from_word(value.into()))
+        };
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    self.touch_slot(context, interp.contract.target_address, slot);
+                }
+            }
+            opcode::BALANCE
+            | opcode::EXTCODESIZE
+            | opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::SELFDESTRUCT => {
+                if let Some(address) = address_at(0) {
+                    self.touch_account(context, address);
+                }
+            }
+            opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL => {
+                if let Some(address) = address_at(1) {
+                    self.touch_account(context, address);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.touch_tx(context);
+        self.touch_account(context, inputs.caller);
+        self.touch_account(context, inputs.target_address);
+        self.touch_account(context, inputs.bytecode_address);
+        None
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.touch_tx(context);
+        self.touch_account(context, inputs.caller);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if let Some(address) = outcome.address {
+            self.touch_account(context, address);
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        primitives::{address, AccountInfo, Bytecode, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn prestate_and_diff() {
+        let caller = address!("1000000000000000000000000000000000000000");
+        let contract = address!("2000000000000000000000000000000000000000");
+        // SSTORE(0, SLOAD(0) + 1)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x00,
+                opcode::SLOAD,
+                opcode::PUSH1,
+                0x01,
+                opcode::ADD,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code.clone()),
+        );
+        db.insert_account_storage(contract, U256::ZERO, U256::from(41))
+            .unwrap();
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(PrestateTracer::new())
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+
+        let tracer = &evm.context.external;
+        let pre = &tracer.prestate()[&contract];
+        assert_eq!(pre.code, Some(code.original_bytes()));
+        assert_eq!(pre.storage[&B256::ZERO], B256::from(U256::from(41)));
+        assert_eq!(
+            tracer.prestate()[&caller].balance,
+            Some(U256::from(1_000_000_000u64))
+        );
+
+        let diff = tracer.diff(&result.state);
+        let post = &diff.post[&contract];
+        assert_eq!(post.storage[&B256::ZERO], B256::from(U256::from(42)));
+        assert_eq!(
+            diff.pre[&contract].storage[&B256::ZERO],
+            pre.storage[&B256::ZERO]
+        );
+        assert_eq!(diff.post[&caller].nonce, Some(1));
+    }
+}
@@ -0,0 +1,242 @@
+//! Ring buffer tracer. Records every step into a preallocated circular buffer, so it
+//! can be kept enabled as a flight recorder of the last executed instructions.
+
+use crate::{interpreter::Interpreter, primitives::db::Database, EvmContext, Inspector};
+use core::num::NonZeroUsize;
+use std::{boxed::Box, vec, vec::Vec};
+
+/// Step recorded by the [RingBufferTracer].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TraceRecord {
+    /// Program counter of the instruction.
+    pub pc: u32,
+    /// Executed opcode.
+    pub opcode: u8,
+    /// Journal depth of the frame, saturated at `u16::MAX`.
+    pub depth: u16,
+    /// Gas remaining before the instruction.
+    pub gas: u64,
+}
+
+impl TraceRecord {
+    /// Size of the binary encoding of the record.
+    pub const SIZE: usize = 16;
+
+    /// Returns the binary encoding of the record: gas (8 bytes), pc (4 bytes), depth
+    /// (2 bytes), all little endian, opcode (1 byte) and a zero byte of padding.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut out = [0; Self::SIZE];
+        out[..8].copy_from_slice(&self.gas.to_le_bytes());
+        out[8..12].copy_from_slice(&self.pc.to_le_bytes());
+        out[12..14].copy_from_slice(&self.depth.to_le_bytes());
+        out[14] = self.opcode;
+        out
+    }
+
+    /// Decodes the record from its binary encoding, see [TraceRecord::to_bytes].
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            gas: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            pc: u32::from_le_bytes(bytes[8..12].try_into().unwrap()),
+            depth: u16::from_le_bytes(bytes[12..14].try_into().unwrap()),
+            opcode: bytes[14],
+        }
+    }
+}
+
+/// [Inspector] that writes a [TraceRecord] for every step into a circular buffer.
+///
+/// Buffer is allocated once on creation and the oldest records are overwritten when it
+/// is full, so tracing does not allocate. Records are kept across transactions until
+/// [RingBufferTracer::clear] is called.
+#[derive(Clone, Debug)]
+pub struct RingBufferTracer {
+    records: Box<[TraceRecord]>,
+    /// Index of the next record to write.
+    head: usize,
+    /// Number of recorded steps, including overwritten ones.
+    total: u64,
+}
+
+impl RingBufferTracer {
+    /// Creates new tracer that keeps the last `capacity` steps.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            records: vec![TraceRecord::default(); capacity.get()].into_boxed_slice(),
+            head: 0,
+            total: 0,
+        }
+    }
+
+    /// Returns the maximum number of kept records.
+    pub fn capacity(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns the number of kept records.
+    pub fn len(&self) -> usize {
+        self.total.min(self.records.len() as u64) as usize
+    }
+
+    /// Returns true if no step was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Returns the number of recorded steps, including overwritten ones.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the number of records that were overwritten.
+    pub fn overwritten(&self) -> u64 {
+        self.total - self.len() as u64
+    }
+
+    /// Returns the kept records from the oldest to the newest.
+    pub fn records(&self) -> impl DoubleEndedIterator<Item = &TraceRecord> {
+        let (newer, older) = self.records.split_at(self.head);
+        let older = if self.len() == self.capacity() {
+            older
+        } else {
+            &older[..0]
+        };
+        older.iter().chain(newer)
+    }
+
+    /// Returns the binary encoding of the kept records from the oldest to the newest,
+    /// each one is [TraceRecord::SIZE] bytes long.
+    pub fn export(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.len() * TraceRecord::SIZE);
+        for record in self.records() {
+            out.extend_from_slice(&record.to_bytes());
+        }
+        out
+    }
+
+    /// Removes all records.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.total = 0;
+    }
+
+    #[inline]
+    fn push(&mut self, record: TraceRecord) {
+        self.records[self.head] = record;
+        self.head += 1;
+        if self.head == self.records.len() {
+            self.head = 0;
+        }
+        self.total += 1;
+    }
+}
+
+impl<DB: Database> Inspector<DB> for RingBufferTracer {
+    #[inline]
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        self.push(TraceRecord {
+            pc: interp.program_counter() as u32,
+            opcode: interp.current_opcode(),
+            depth: context.journaled_state.depth.min(u16::MAX as usize) as u16,
+            gas: interp.gas.remaining(),
+        });
+    }
+}
+
+/// Decodes records exported with [RingBufferTracer::export].
+///
+/// Trailing bytes that do not form a whole record are ignored.
+pub fn decode_trace_records(bytes: &[u8]) -> impl Iterator<Item = TraceRecord> + '_ {
+    bytes
+        .chunks_exact(TraceRecord::SIZE)
+        .map(|chunk| TraceRecord::from_bytes(chunk.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, Bytecode, TransactTo},
+        Evm,
+    };
+
+    fn evm(capacity: usize) -> Evm<'static, RingBufferTracer, BenchmarkDB> {
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x02,
+                opcode::ADD,
+                opcode::POP,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .with_external_context(RingBufferTracer::new(NonZeroUsize::new(capacity).unwrap()))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build()
+    }
+
+    #[test]
+    fn records_steps() {
+        let mut evm = evm(16);
+        assert!(evm.transact().unwrap().result.is_success());
+        let tracer = &evm.context.external;
+        assert_eq!(tracer.len(), 5);
+        assert_eq!(tracer.overwritten(), 0);
+        let records: Vec<_> = tracer.records().copied().collect();
+        assert_eq!(
+            records.iter().map(|r| r.opcode).collect::<Vec<_>>(),
+            [
+                opcode::PUSH1,
+                opcode::PUSH1,
+                opcode::ADD,
+                opcode::POP,
+                opcode::STOP
+            ]
+        );
+        assert_eq!(
+            records.iter().map(|r| r.pc).collect::<Vec<_>>(),
+            [0, 2, 4, 5, 6]
+        );
+        assert_eq!(records[0].gas, 100_000 - 21_000);
+        assert_eq!(records[1].gas, 100_000 - 21_000 - 3);
+        assert!(records.iter().all(|r| r.depth == 1));
+
+        let exported = tracer.export();
+        assert_eq!(exported.len(), 5 * TraceRecord::SIZE);
+        assert_eq!(decode_trace_records(&exported).collect::<Vec<_>>(), records);
+    }
+
+    #[test]
+    fn overwrites_oldest() {
+        let mut evm = evm(3);
+        assert!(evm.transact().unwrap().result.is_success());
+        assert!(evm.transact().unwrap().result.is_success());
+        let tracer = &evm.context.external;
+        assert_eq!(tracer.len(), 3);
+        assert_eq!(tracer.total(), 10);
+        assert_eq!(tracer.overwritten(), 7);
+        assert_eq!(
+            tracer.records().map(|r| r.opcode).collect::<Vec<_>>(),
+            [opcode::ADD, opcode::POP, opcode::STOP]
+        );
+
+        evm.context.external.clear();
+        assert!(evm.context.external.is_empty());
+        assert_eq!(evm.context.external.records().count(), 0);
+    }
+}
@@ -0,0 +1,328 @@
+//! Sentinel inspector. Checks configured invariants at every call boundary and stops the
+//! transaction with a custom error when one is violated.
+
+use crate::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Gas, InstructionResult, Interpreter,
+        InterpreterResult,
+    },
+    primitives::{db::Database, Address, Bytes, EVMError, HashMap, U256},
+    EvmContext, Inspector,
+};
+use core::fmt;
+use std::{string::ToString, vec::Vec};
+
+/// Invariant checked by the [SentinelInspector].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Invariant {
+    /// Balance of the account never decreases.
+    ///
+    /// The balance is compared with the highest balance seen at the previous call boundaries,
+    /// the first one is taken when the first frame starts, after the gas fee is deducted.
+    BalanceNeverDecreases(Address),
+    /// No call into the account while a call into it is still executing.
+    ///
+    /// Calls are matched by their target address, so `DELEGATECALL` from the account counts
+    /// as a call into it.
+    NoReentrancy(Address),
+}
+
+/// Violation of an [Invariant].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InvariantViolation {
+    /// Balance of the account decreased.
+    BalanceDecreased {
+        address: Address,
+        before: U256,
+        after: U256,
+    },
+    /// Account was called while it was already executing.
+    ReentrantCall { address: Address, caller: Address },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BalanceDecreased {
+                address,
+                before,
+                after,
+            } => write!(
+                f,
+                "invariant violated: balance of {address} decreased from {before} to {after}"
+            ),
+            Self::ReentrantCall { address, caller } => {
+                write!(
+                    f,
+                    "invariant violated: reentrant call into {address} from {caller}"
+                )
+            }
+        }
+    }
+}
+
+/// [Inspector] that checks [Invariant]s at every call and create boundary.
+///
+/// On the first violation the transaction fails with [EVMError::Custom] containing the
+/// violation message, and the violation can be read with [SentinelInspector::violation].
+/// Execution is halted at the next instruction of the running frame.
+#[derive(Clone, Debug, Default)]
+pub struct SentinelInspector {
+    invariants: Vec<Invariant>,
+    /// Highest balance seen for the accounts with [Invariant::BalanceNeverDecreases].
+    balances: HashMap<Address, U256>,
+    /// Target addresses of the executing calls.
+    active_calls: Vec<Address>,
+    violation: Option<InvariantViolation>,
+}
+
+impl SentinelInspector {
+    /// Creates new sentinel with the invariants.
+    pub fn new(invariants: impl IntoIterator<Item = Invariant>) -> Self {
+        Self {
+            invariants: invariants.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds the invariant.
+    pub fn with_invariant(mut self, invariant: Invariant) -> Self {
+        self.invariants.push(invariant);
+        self
+    }
+
+    /// Returns the invariants.
+    pub fn invariants(&self) -> &[Invariant] {
+        &self.invariants
+    }
+
+    /// Returns the violation of the last transaction, if any.
+    pub fn violation(&self) -> Option<&InvariantViolation> {
+        self.violation.as_ref()
+    }
+
+    /// Clears the state of the last transaction, invariants are kept.
+    pub fn clear(&mut self) {
+        self.balances.clear();
+        self.active_calls.clear();
+        self.violation = None;
+    }
+
+    /// Clears the state when the first frame of a new transaction starts.
+    fn start_frame<DB: Database>(&mut self, context: &EvmContext<DB>) {
+        if context.journaled_state.depth() == 0 {
+            self.clear();
+        }
+    }
+
+    /// Records the violation and fails the transaction.
+    fn violate<DB: Database>(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        violation: InvariantViolation,
+    ) {
+        if self.violation.is_none() {
+            context.error = Err(EVMError::Custom(violation.to_string()));
+            self.violation = Some(violation);
+        }
+    }
+
+    /// Checks balance invariants against the current state.
+    fn check_balances<DB: Database>(&mut self, context: &mut EvmContext<DB>) {
+        if self.violation.is_some() {
+            return;
+        }
+        for invariant in &self.invariants {
+            let Invariant::BalanceNeverDecreases(address) = *invariant else {
+                continue;
+            };
+            let balance = match context.journaled_state.state.get(&address) {
+                Some(account) => account.info.balance,
+                None => match context.db.basic(address) {
+                    Ok(info) => info.map(|info| info.balance).unwrap_or_default(),
+                    Err(e) => {
+                        context.error = Err(EVMError::Database(e));
+                        return;
+                    }
+                },
+            };
+            let highest = self.balances.entry(address).or_insert(balance);
+            if balance < *highest {
+                let violation = InvariantViolation::BalanceDecreased {
+                    address,
+                    before: *highest,
+                    after: balance,
+                };
+                self.violate(context, violation);
+                return;
+            }
+            *highest = balance;
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for SentinelInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        if self.violation.is_some() {
+            interp.instruction_result = InstructionResult::FatalExternalError;
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.start_frame(context);
+        self.check_balances(context);
+
+        let address = inputs.target_address;
+        let is_reentrant = self.active_calls.contains(&address)
+            && self.invariants.contains(&Invariant::NoReentrancy(address));
+        self.active_calls.push(address);
+        if is_reentrant {
+            let violation = InvariantViolation::ReentrantCall {
+                address,
+                caller: inputs.caller,
+            };
+            self.violate(context, violation);
+        }
+
+        self.violation.is_some().then(|| {
+            CallOutcome::new(
+                InterpreterResult {
+                    result: InstructionResult::FatalExternalError,
+                    output: Bytes::new(),
+                    gas: Gas::new(inputs.gas_limit),
+                },
+                inputs.return_memory_offset.clone(),
+            )
+        })
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.active_calls.pop();
+        self.check_balances(context);
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start_frame(context);
+        self.check_balances(context);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.check_balances(context);
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, Bytecode, TransactTo},
+        Evm,
+    };
+
+    fn transact(
+        code: &[u8],
+        sentinel: SentinelInspector,
+    ) -> (
+        Result<bool, EVMError<core::convert::Infallible>>,
+        SentinelInspector,
+    ) {
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
+                code.to_vec().into(),
+            )))
+            .with_external_context(sentinel)
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Call(Address::ZERO);
+                tx.gas_limit = 1_000_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact().map(|r| r.result.is_success());
+        (result, evm.into_context().external)
+    }
+
+    #[test]
+    fn balance_never_decreases() {
+        // CALL(gas, 0x01, 1 wei, 0, 0, 0, 0)
+        let code = [
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            1,
+            opcode::PUSH1,
+            1,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::STOP,
+        ];
+        let receiver = Address::with_last_byte(1);
+        let sentinel = SentinelInspector::new([Invariant::BalanceNeverDecreases(receiver)]);
+        let (result, sentinel) = transact(&code, sentinel);
+        assert_eq!(result, Ok(true));
+        assert_eq!(sentinel.violation(), None);
+
+        let sentinel = sentinel.with_invariant(Invariant::BalanceNeverDecreases(Address::ZERO));
+        let (result, sentinel) = transact(&code, sentinel);
+        let violation = InvariantViolation::BalanceDecreased {
+            address: Address::ZERO,
+            before: U256::from(10000000),
+            after: U256::from(9999999),
+        };
+        assert_eq!(result, Err(EVMError::Custom(violation.to_string())));
+        assert_eq!(sentinel.violation(), Some(&violation));
+    }
+
+    #[test]
+    fn no_reentrancy() {
+        // CALL(gas, ADDRESS, 0, 0, 0, 0, 0), calls itself until the depth limit.
+        let code = [
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::ADDRESS,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::STOP,
+        ];
+        let (result, _) = transact(&code, SentinelInspector::default());
+        assert_eq!(result, Ok(true));
+
+        let sentinel = SentinelInspector::new([Invariant::NoReentrancy(Address::ZERO)]);
+        let (result, sentinel) = transact(&code, sentinel);
+        let violation = InvariantViolation::ReentrantCall {
+            address: Address::ZERO,
+            caller: Address::ZERO,
+        };
+        assert_eq!(result, Err(EVMError::Custom(violation.to_string())));
+        assert_eq!(sentinel.violation(), Some(&violation));
+    }
+}
@@ -0,0 +1,288 @@
+//! Watchpoint inspector. Reports writes to watched storage slots and memory ranges
+//! before they happen, so debuggers don't need to diff the state on every step.
+
+use crate::{
+    interpreter::{opcode, InstructionResult, Interpreter},
+    primitives::{db::Database, Address, U256},
+    EvmContext, Inspector,
+};
+use core::{fmt, ops::Range};
+use std::{boxed::Box, vec::Vec};
+
+/// Location watched by the [WatchpointInspector].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Watchpoint {
+    /// Storage of the account. `None` slot watches all slots of the account.
+    Storage {
+        address: Address,
+        slot: Option<U256>,
+    },
+    /// Memory range of every frame, including return data copied by calls.
+    Memory { range: Range<usize> },
+}
+
+/// Write to a watched location.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WatchedWrite {
+    /// `SSTORE` of the value to the slot.
+    Storage { slot: U256, value: U256 },
+    /// Write to the memory range.
+    Memory { range: Range<usize> },
+}
+
+/// Watchpoint hit, reported before the write is executed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WatchpointHit {
+    /// Index of the watchpoint in [WatchpointInspector::watchpoints].
+    pub watchpoint: usize,
+    /// Address of the account whose code is executed.
+    pub address: Address,
+    /// Program counter of the opcode that writes.
+    pub pc: usize,
+    /// Opcode that writes.
+    pub opcode: u8,
+    /// Call depth of the frame.
+    pub depth: u64,
+    /// The write.
+    pub write: WatchedWrite,
+}
+
+/// Action returned by the watchpoint callback.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WatchAction {
+    /// Continue the execution.
+    #[default]
+    Continue,
+    /// Halt the frame with the given result, before the write is executed.
+    Halt(InstructionResult),
+}
+
+/// Callback invoked on every watchpoint hit.
+pub type WatchpointCallback<'a> = Box<dyn FnMut(&WatchpointHit) -> WatchAction + 'a>;
+
+/// [Inspector] that invokes a callback when a watched storage slot or memory range is
+/// about to be written.
+///
+/// All hits are recorded and can be read with [WatchpointInspector::hits].
+pub struct WatchpointInspector<'a> {
+    watchpoints: Vec<Watchpoint>,
+    callback: WatchpointCallback<'a>,
+    hits: Vec<WatchpointHit>,
+}
+
+impl fmt::Debug for WatchpointInspector<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchpointInspector")
+            .field("watchpoints", &self.watchpoints)
+            .field("hits", &self.hits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for WatchpointInspector<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> WatchpointInspector<'a> {
+    /// Creates new watchpoint inspector that only records hits.
+    pub fn new() -> Self {
+        Self::with_callback(|_| WatchAction::Continue)
+    }
+
+    /// Creates new watchpoint inspector with the callback.
+    pub fn with_callback(callback: impl FnMut(&WatchpointHit) -> WatchAction + 'a) -> Self {
+        Self {
+            watchpoints: Vec::new(),
+            callback: Box::new(callback),
+            hits: Vec::new(),
+        }
+    }
+
+    /// Adds the watchpoint and returns its index.
+    pub fn watch(&mut self, watchpoint: Watchpoint) -> usize {
+        self.watchpoints.push(watchpoint);
+        self.watchpoints.len() - 1
+    }
+
+    /// Returns the watchpoints.
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// Returns recorded hits.
+    pub fn hits(&self) -> &[WatchpointHit] {
+        &self.hits
+    }
+
+    /// Clears recorded hits, watchpoints are kept.
+    pub fn clear(&mut self) {
+        self.hits.clear();
+    }
+}
+
+/// Returns the write that the current opcode is about to make, if any.
+fn pending_write(interp: &Interpreter) -> Option<WatchedWrite> {
+    let peek = |n| interp.stack.peek(n).ok();
+    let memory = |offset: U256, len: U256| {
+        let len = len.saturating_to::<usize>();
+        let offset = offset.saturating_to::<usize>();
+        (len != 0).then(|| WatchedWrite::Memory {
+            range: offset..offset.saturating_add(len),
+        })
+    };
+    match interp.current_opcode() {
+        opcode::SSTORE => Some(WatchedWrite::Storage {
+            slot: peek(0)?,
+            value: peek(1)?,
+        }),
+        opcode::MSTORE => memory(peek(0)?, U256::from(32)),
+        opcode::MSTORE8 => memory(peek(0)?, U256::from(1)),
+        opcode::CALLDATACOPY
+        | opcode::CODECOPY
+        | opcode::RETURNDATACOPY
+        | opcode::MCOPY
+        | opcode::DATACOPY => memory(peek(0)?, peek(2)?),
+        opcode::EXTCODECOPY => memory(peek(1)?, peek(3)?),
+        opcode::CALL | opcode::CALLCODE => memory(peek(5)?, peek(6)?),
+        opcode::DELEGATECALL | opcode::STATICCALL => memory(peek(4)?, peek(5)?),
+        _ => None,
+    }
+}
+
+impl<DB: Database> Inspector<DB> for WatchpointInspector<'_> {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if self.watchpoints.is_empty() {
+            return;
+        }
+        let Some(write) = pending_write(interp) else {
+            return;
+        };
+        let address = interp.contract.target_address;
+        for (index, watchpoint) in self.watchpoints.iter().enumerate() {
+            let is_hit = match (watchpoint, &write) {
+                (
+                    Watchpoint::Storage {
+                        address: watched,
+                        slot,
+                    },
+                    WatchedWrite::Storage { slot: written, .. },
+                ) => *watched == address && (slot.is_none() || *slot == Some(*written)),
+                (Watchpoint::Memory { range }, WatchedWrite::Memory { range: written }) => {
+                    range.start < written.end && written.start < range.end
+                }
+                _ => false,
+            };
+            if !is_hit {
+                continue;
+            }
+            let hit = WatchpointHit {
+                watchpoint: index,
+                address,
+                pc: interp.program_counter(),
+                opcode: interp.current_opcode(),
+                depth: context.journaled_state.depth(),
+                write: write.clone(),
+            };
+            let action = (self.callback)(&hit);
+            self.hits.push(hit);
+            if let WatchAction::Halt(result) = action {
+                interp.instruction_result = result;
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        primitives::{address, Bytecode, ExecutionResult, TransactTo},
+        Evm,
+    };
+
+    fn evm<'a>(
+        inspector: WatchpointInspector<'a>,
+    ) -> Evm<'a, WatchpointInspector<'a>, BenchmarkDB> {
+        // MSTORE(0x10, 1) SSTORE(0, 1) SSTORE(1, 2)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x10,
+                opcode::MSTORE,
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0x02,
+                opcode::PUSH1,
+                0x01,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]
+            .to_vec()
+            .into(),
+        );
+        Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(code))
+            .with_external_context(inspector)
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Call(Address::ZERO);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build()
+    }
+
+    #[test]
+    fn record_hits() {
+        let mut inspector = WatchpointInspector::new();
+        inspector.watch(Watchpoint::Memory { range: 0x2f..0x40 });
+        inspector.watch(Watchpoint::Storage {
+            address: Address::ZERO,
+            slot: None,
+        });
+        let mut evm = evm(inspector);
+        assert!(evm.transact().unwrap().result.is_success());
+
+        let hits = evm.context.external.hits();
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].watchpoint, 0);
+        assert_eq!(hits[0].pc, 4);
+        assert_eq!(hits[0].write, WatchedWrite::Memory { range: 0x10..0x30 });
+        assert_eq!(
+            hits[2].write,
+            WatchedWrite::Storage {
+                slot: U256::from(1),
+                value: U256::from(2)
+            }
+        );
+    }
+
+    #[test]
+    fn halt_on_hit() {
+        let mut inspector = WatchpointInspector::with_callback(|hit| match hit.write {
+            WatchedWrite::Storage { value, .. } if value == U256::from(2) => {
+                WatchAction::Halt(InstructionResult::OutOfGas)
+            }
+            _ => WatchAction::Continue,
+        });
+        inspector.watch(Watchpoint::Storage {
+            address: Address::ZERO,
+            slot: Some(U256::from(1)),
+        });
+        let mut evm = evm(inspector);
+        let result = evm.transact().unwrap().result;
+        assert!(matches!(result, ExecutionResult::Halt { .. }));
+        assert_eq!(evm.context.external.hits().len(), 1);
+    }
+}
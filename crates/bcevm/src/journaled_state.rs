@@ -3,9 +3,9 @@ use crate::primitives::{
     db::Database, hash_map::Entry, Account, Address, Bytecode, EVMError, HashMap, HashSet, Log,
     SpecId::*, State, StorageSlot, TransientStorage, KECCAK_EMPTY, PRECOMPILE3, U256,
 };
-use core::mem;
 use bcevm_interpreter::primitives::SpecId;
 use bcevm_interpreter::{LoadAccountResult, SStoreResult};
+use core::mem;
 use std::vec::Vec;
 
 /// JournalState is internal EVM state that is used to contain state and track changes to that state.
@@ -27,6 +27,14 @@ pub struct JournaledState {
     /// Spec is needed for two things SpuriousDragon's `EIP-161 State clear`,
     /// and for Cancun's `EIP-6780: SELFDESTRUCT in same transaction`
     pub spec: SpecId,
+    /// Overrides EIP-161 state clear semantics that are otherwise enabled from Spurious
+    /// Dragon, see [crate::primitives::CfgEnv::state_clear].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub state_clear: Option<bool>,
+    /// Overrides EIP-6780 SELFDESTRUCT semantics that are otherwise enabled from Cancun,
+    /// see [crate::primitives::CfgEnv::eip6780].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub eip6780: Option<bool>,
     /// Warm loaded addresses are used to check if loaded address
     /// should be considered cold or warm loaded when the account
     /// is first accessed.
@@ -34,6 +42,86 @@ pub struct JournaledState {
     /// Note that this not include newly loaded accounts, account and storage
     /// is considered warm if it is found in the `State`.
     pub warm_preloaded_addresses: HashSet<Address>,
+    /// Accounts with their storage slots that are loaded warm at the start of every
+    /// transaction, as if they were in the access list.
+    ///
+    /// Kept across transactions, see [Self::preload_addresses] and [Self::preload_storage].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub preloaded_accounts: HashMap<Address, Vec<U256>>,
+    /// Accounts with their storage and code as they were read from the database, `None` if
+    /// reads are not cached.
+    ///
+    /// Kept across transactions, accounts loaded from the cache are still cold.
+    /// See [Self::enable_database_cache].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub database_cache: Option<State>,
+    /// Counters of journal operations, `None` if metrics are disabled.
+    ///
+    /// Counters are kept across transactions until they are taken with [Self::take_metrics].
+    pub metrics: Option<JournalMetrics>,
+    /// Adds EIP-1153 transient storage reads to the journal as
+    /// [JournalEntry::TransientStorageLoad], see [Self::transient_entries].
+    ///
+    /// Kept across transactions. Default: false
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub record_transient_loads: bool,
+    /// New values of the storage, transient storage and code changes of the journal, in
+    /// the order of the changes. `None` if the values are not recorded.
+    ///
+    /// Needed to replicate the journal with [Self::apply_journal], values of reverted
+    /// changes are discarded together with them. See [Self::enable_journal_values].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub journal_values: Option<Vec<JournalValue>>,
+}
+
+/// Counters of [JournaledState] operations, used to investigate performance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JournalMetrics {
+    /// Number of account loads.
+    pub account_loads: u64,
+    /// Number of account loads that were cold.
+    pub cold_account_loads: u64,
+    /// Number of storage slot loads, not including loads done by stores.
+    pub storage_loads: u64,
+    /// Number of storage slot loads that were cold.
+    pub cold_storage_loads: u64,
+    /// Number of storage slot stores.
+    pub storage_stores: u64,
+    /// Number of storage slot stores to a cold slot.
+    pub cold_storage_stores: u64,
+    /// Number of created checkpoints.
+    pub checkpoints: u64,
+    /// Number of reverted checkpoints.
+    pub checkpoint_reverts: u64,
+    /// Number of cold account and storage accesses charged by the interpreter (EIP-2929).
+    ///
+    /// Unlike the loads, only accesses of instructions that paid for them are counted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cold_access_charges: u64,
+    /// Gas charged for the cold accesses, on top of what warm accesses would cost.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cold_access_gas: u64,
+}
+
+impl JournalMetrics {
+    /// Returns number of account loads that were warm.
+    #[inline]
+    pub fn warm_account_loads(&self) -> u64 {
+        self.account_loads - self.cold_account_loads
+    }
+
+    /// Returns number of storage slot loads that were warm.
+    #[inline]
+    pub fn warm_storage_loads(&self) -> u64 {
+        self.storage_loads - self.cold_storage_loads
+    }
+
+    /// Returns number of committed checkpoints.
+    #[inline]
+    pub fn checkpoint_commits(&self) -> u64 {
+        self.checkpoints - self.checkpoint_reverts
+    }
 }
 
 impl JournaledState {
@@ -56,7 +144,14 @@ impl JournaledState {
             journal: vec![vec![]],
             depth: 0,
             spec,
+            state_clear: None,
+            eip6780: None,
             warm_preloaded_addresses,
+            preloaded_accounts: HashMap::new(),
+            database_cache: None,
+            metrics: None,
+            record_transient_loads: false,
+            journal_values: None,
         }
     }
 
@@ -72,6 +167,32 @@ impl JournaledState {
         self.spec = spec;
     }
 
+    /// Overrides EIP-161 state clear semantics, `None` enables them from Spurious Dragon.
+    #[inline]
+    pub fn set_state_clear(&mut self, state_clear: Option<bool>) {
+        self.state_clear = state_clear;
+    }
+
+    /// Returns true if EIP-161 state clear semantics are enabled.
+    #[inline]
+    pub fn is_state_clear_enabled(&self) -> bool {
+        self.state_clear
+            .unwrap_or_else(|| SpecId::enabled(self.spec, SPURIOUS_DRAGON))
+    }
+
+    /// Overrides EIP-6780 SELFDESTRUCT semantics, `None` enables them from Cancun.
+    #[inline]
+    pub fn set_eip6780(&mut self, eip6780: Option<bool>) {
+        self.eip6780 = eip6780;
+    }
+
+    /// Returns true if EIP-6780 SELFDESTRUCT semantics are enabled.
+    #[inline]
+    pub fn is_eip6780_enabled(&self) -> bool {
+        self.eip6780
+            .unwrap_or_else(|| SpecId::enabled(self.spec, CANCUN))
+    }
+
     /// Mark account as touched as only touched accounts will be added to state.
     /// This is especially important for state clear where touched empty accounts needs to
     /// be removed from state.
@@ -91,10 +212,169 @@ impl JournaledState {
         }
     }
 
-    /// Clears the JournaledState. Preserving only the spec.
+    /// Clears the JournaledState. Preserving only the spec, metrics, preloaded accounts and
+    /// the database cache.
     pub fn clear(&mut self) {
         let spec = self.spec;
+        let metrics = self.metrics.take();
+        let preloaded_accounts = mem::take(&mut self.preloaded_accounts);
+        let database_cache = self.database_cache.take();
         *self = Self::new(spec, HashSet::new());
+        self.metrics = metrics;
+        self.preloaded_accounts = preloaded_accounts;
+        self.database_cache = database_cache;
+    }
+
+    /// Adds the addresses to the accounts that are warm at the start of every transaction.
+    ///
+    /// Accounts are loaded from the database by [Self::load_preloaded_accounts].
+    pub fn preload_addresses(&mut self, addresses: impl IntoIterator<Item = Address>) {
+        for address in addresses {
+            self.preloaded_accounts.entry(address).or_default();
+        }
+    }
+
+    /// Adds the account and its storage slots to the ones that are warm at the start of every
+    /// transaction.
+    ///
+    /// Slots are loaded from the database by [Self::load_preloaded_accounts].
+    pub fn preload_storage(&mut self, address: Address, keys: impl IntoIterator<Item = U256>) {
+        let slots = self.preloaded_accounts.entry(address).or_default();
+        for key in keys {
+            if !slots.contains(&key) {
+                slots.push(key);
+            }
+        }
+    }
+
+    /// Loads the [preloaded accounts](Self::preloaded_accounts) and their storage slots, so
+    /// their first access in the transaction is warm.
+    pub fn load_preloaded_accounts<DB: Database>(
+        &mut self,
+        db: &mut DB,
+    ) -> Result<(), EVMError<DB::Error>> {
+        let preloaded_accounts = mem::take(&mut self.preloaded_accounts);
+        let result = preloaded_accounts.iter().try_for_each(|(address, slots)| {
+            self.initial_account_load(*address, slots, db).map(|_| ())
+        });
+        self.preloaded_accounts = preloaded_accounts;
+        result
+    }
+
+    /// Enables collection of [JournalMetrics], counters are reset.
+    #[inline]
+    pub fn enable_metrics(&mut self) {
+        self.metrics = Some(JournalMetrics::default());
+    }
+
+    /// Disables collection of [JournalMetrics] and returns collected counters.
+    #[inline]
+    pub fn disable_metrics(&mut self) -> Option<JournalMetrics> {
+        self.metrics.take()
+    }
+
+    /// Returns collected counters and resets them. Returns `None` if metrics are disabled.
+    #[inline]
+    pub fn take_metrics(&mut self) -> Option<JournalMetrics> {
+        self.metrics.as_mut().map(mem::take)
+    }
+
+    /// Enables recording of the [JournalValue]s of the journal, see [Self::journal_values].
+    #[inline]
+    pub fn enable_journal_values(&mut self) {
+        self.journal_values = Some(Vec::new());
+    }
+
+    /// Disables recording of the [JournalValue]s and returns the recorded values.
+    #[inline]
+    pub fn disable_journal_values(&mut self) -> Option<Vec<JournalValue>> {
+        self.journal_values.take()
+    }
+
+    /// Records the new value of the last journal entry if [Self::journal_values] are enabled.
+    #[inline]
+    fn record_value(
+        journal_values: &mut Option<Vec<JournalValue>>,
+        value: impl FnOnce() -> JournalValue,
+    ) {
+        if let Some(values) = journal_values {
+            values.push(value());
+        }
+    }
+
+    /// Enables caching of database reads, so transactions executed on top of the same state
+    /// read every account, storage slot and code only once.
+    ///
+    /// The cache is not invalidated, it has to be disabled before the database changes.
+    #[inline]
+    pub fn enable_database_cache(&mut self) {
+        self.database_cache = Some(State::default());
+    }
+
+    /// Disables caching of database reads and drops the cache.
+    #[inline]
+    pub fn disable_database_cache(&mut self) {
+        self.database_cache = None;
+    }
+
+    /// Reads the account from the database cache or the database.
+    fn read_account<DB: Database>(
+        database_cache: &mut Option<State>,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<Account, EVMError<DB::Error>> {
+        if let Some(cached) = database_cache
+            .as_ref()
+            .and_then(|cache| cache.get(&address))
+        {
+            return Ok(Account {
+                info: cached.info.clone(),
+                storage: Default::default(),
+                status: cached.status,
+            });
+        }
+        let account = db
+            .basic(address)
+            .map_err(EVMError::Database)?
+            .map(|info| info.into())
+            .unwrap_or(Account::new_not_existing());
+        if let Some(cache) = database_cache {
+            cache.insert(address, account.clone());
+        }
+        Ok(account)
+    }
+
+    /// Reads the storage slot from the database cache or the database.
+    fn read_storage<DB: Database>(
+        database_cache: &mut Option<State>,
+        address: Address,
+        key: U256,
+        db: &mut DB,
+    ) -> Result<U256, EVMError<DB::Error>> {
+        let cached = database_cache
+            .as_mut()
+            .and_then(|cache| cache.get_mut(&address));
+        if let Some(slot) = cached
+            .as_ref()
+            .and_then(|account| account.storage.get(&key))
+        {
+            return Ok(slot.present_value);
+        }
+        let value = db.storage(address, key).map_err(EVMError::Database)?;
+        if let Some(account) = cached {
+            account.storage.insert(key, StorageSlot::new(value));
+        }
+        Ok(value)
+    }
+
+    /// Records a cold access charged by the interpreter with the additional `gas` of it,
+    /// see [JournalMetrics::cold_access_charges].
+    #[inline]
+    pub fn record_cold_access(&mut self, gas: u64) {
+        if let Some(metrics) = &mut self.metrics {
+            metrics.cold_access_charges += 1;
+            metrics.cold_access_gas += gas;
+        }
     }
 
     /// Does cleanup and returns modified state.
@@ -108,13 +388,23 @@ impl JournaledState {
             logs,
             depth,
             journal,
+            journal_values,
             // kept, see [Self::new]
             spec: _,
+            state_clear: _,
+            eip6780: _,
             warm_preloaded_addresses: _,
+            preloaded_accounts: _,
+            database_cache: _,
+            metrics: _,
+            record_transient_loads: _,
         } = self;
 
         *transient_storage = TransientStorage::default();
         *journal = vec![vec![]];
+        if let Some(values) = journal_values {
+            values.clear();
+        }
         *depth = 0;
         let state = mem::take(state);
         let logs = mem::take(logs);
@@ -136,6 +426,19 @@ impl JournaledState {
             .expect("Account expected to be loaded") // Always assume that acc is already loaded
     }
 
+    /// Returns journal entries of EIP-1153 transient storage reads and changes, in
+    /// the order they happened. Reads are only journaled if
+    /// [Self::record_transient_loads] is set.
+    ///
+    /// Entries of reverted calls are discarded together with the rest of their journal.
+    #[inline]
+    pub fn transient_entries(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.journal
+            .iter()
+            .flatten()
+            .filter(|entry| entry.is_transient_storage())
+    }
+
     /// Returns call depth.
     #[inline]
     pub fn depth(&self) -> u64 {
@@ -153,6 +456,9 @@ impl JournaledState {
             .last_mut()
             .unwrap()
             .push(JournalEntry::CodeChange { address });
+        Self::record_value(&mut self.journal_values, || {
+            JournalValue::Code(code.clone())
+        });
 
         account.info.code_hash = code.hash_slow();
         account.info.code = Some(code);
@@ -229,7 +535,7 @@ impl JournaledState {
     /// 2. Check if there is collision of newly created account with existing one.
     /// 3. Mark created account as created.
     /// 4. Add fund to created account
-    /// 5. Increment nonce of created account if SpuriousDragon is active
+    /// 5. Increment nonce of created account if EIP-161 state clear is enabled
     /// 6. Decrease balance of caller account.
     ///
     /// # Panics
@@ -291,7 +597,10 @@ impl JournaledState {
         account.info.balance = new_balance;
 
         // EIP-161: State trie clearing (invariant-preserving alternative)
-        if spec_id.is_enabled_in(SPURIOUS_DRAGON) {
+        if self
+            .state_clear
+            .unwrap_or_else(|| spec_id.is_enabled_in(SPURIOUS_DRAGON))
+        {
             // nonce is going to be reset to zero in AccountCreated journal entry.
             account.info.nonce = 1;
         }
@@ -316,11 +625,11 @@ impl JournaledState {
     fn journal_revert(
         state: &mut State,
         transient_storage: &mut TransientStorage,
-        journal_entries: Vec<JournalEntry>,
+        journal_entries: &[JournalEntry],
         is_spurious_dragon_enabled: bool,
     ) {
-        for entry in journal_entries.into_iter().rev() {
-            match entry {
+        for entry in journal_entries.iter().rev() {
+            match *entry {
                 JournalEntry::AccountLoaded { address } => {
                     state.remove(&address);
                 }
@@ -385,6 +694,7 @@ impl JournaledState {
                     address,
                     key,
                     had_value,
+                    ..
                 } => {
                     let tkey = (address, key);
                     if had_value == U256::ZERO {
@@ -395,6 +705,7 @@ impl JournaledState {
                         transient_storage.insert(tkey, had_value);
                     }
                 }
+                JournalEntry::TransientStorageLoad { .. } => {}
                 JournalEntry::CodeChange { address } => {
                     let acc = state.get_mut(&address).unwrap();
                     acc.info.code_hash = KECCAK_EMPTY;
@@ -410,9 +721,13 @@ impl JournaledState {
         let checkpoint = JournalCheckpoint {
             log_i: self.logs.len(),
             journal_i: self.journal.len(),
+            value_i: self.journal_values.as_ref().map_or(0, Vec::len),
         };
         self.depth += 1;
         self.journal.push(Default::default());
+        if let Some(metrics) = &mut self.metrics {
+            metrics.checkpoints += 1;
+        }
         checkpoint
     }
 
@@ -425,35 +740,150 @@ impl JournaledState {
     /// Reverts all changes to state until given checkpoint.
     #[inline]
     pub fn checkpoint_revert(&mut self, checkpoint: JournalCheckpoint) {
-        let is_spurious_dragon_enabled = SpecId::enabled(self.spec, SPURIOUS_DRAGON);
+        let is_spurious_dragon_enabled = self.is_state_clear_enabled();
         let state = &mut self.state;
         let transient_storage = &mut self.transient_storage;
         self.depth -= 1;
+        if let Some(metrics) = &mut self.metrics {
+            metrics.checkpoint_reverts += 1;
+        }
         // iterate over last N journals sets and revert our global state
-        let leng = self.journal.len();
         self.journal
-            .iter_mut()
+            .drain(checkpoint.journal_i..)
             .rev()
-            .take(leng - checkpoint.journal_i)
             .for_each(|cs| {
-                Self::journal_revert(
-                    state,
-                    transient_storage,
-                    mem::take(cs),
-                    is_spurious_dragon_enabled,
-                )
+                Self::journal_revert(state, transient_storage, &cs, is_spurious_dragon_enabled)
             });
 
         self.logs.truncate(checkpoint.log_i);
-        self.journal.truncate(checkpoint.journal_i);
+        if let Some(values) = &mut self.journal_values {
+            values.truncate(checkpoint.value_i);
+        }
+    }
+
+    /// Applies the journal of another [JournaledState] to this state, in order.
+    ///
+    /// This replicates the state transitions of the other state, the state needs to be the
+    /// state the journal was recorded on. New values of the changes are taken from the
+    /// [journal values](Self::journal_values) of the other state, which need to be enabled.
+    /// Accounts loaded in the journal are loaded from the database. Applied entries are not
+    /// added to the journal of this state, so they can be reverted only with
+    /// [Self::revert_journal].
+    pub fn apply_journal<DB: Database>(
+        &mut self,
+        journal: &[Vec<JournalEntry>],
+        values: &[JournalValue],
+        db: &mut DB,
+    ) -> Result<(), EVMError<DB::Error>> {
+        let is_spurious_dragon_enabled = self.is_state_clear_enabled();
+        let state = &mut self.state;
+        let mismatch = || EVMError::Custom("journal values do not match the journal".into());
+        let mut values = values.iter();
+        for entry in journal.iter().flatten() {
+            match *entry {
+                JournalEntry::AccountLoaded { address } => {
+                    if let Entry::Vacant(vac) = state.entry(address) {
+                        vac.insert(Self::read_account(&mut self.database_cache, address, db)?);
+                    }
+                }
+                JournalEntry::AccountTouched { address } => {
+                    state.get_mut(&address).unwrap().mark_touch();
+                }
+                JournalEntry::AccountDestroyed {
+                    address,
+                    target,
+                    had_balance,
+                    ..
+                } => {
+                    let account = state.get_mut(&address).unwrap();
+                    account.mark_selfdestruct();
+                    account.info.balance -= had_balance;
+
+                    if address != target {
+                        let target = state.get_mut(&target).unwrap();
+                        target.info.balance += had_balance;
+                    }
+                }
+                JournalEntry::BalanceTransfer { from, to, balance } => {
+                    let from = state.get_mut(&from).unwrap();
+                    from.info.balance -= balance;
+                    let to = state.get_mut(&to).unwrap();
+                    to.info.balance += balance;
+                }
+                JournalEntry::NonceChange { address } => {
+                    state.get_mut(&address).unwrap().info.nonce += 1;
+                }
+                JournalEntry::AccountCreated { address } => {
+                    let account = state.get_mut(&address).unwrap();
+                    account.mark_created();
+                    account.info.code = None;
+                    account
+                        .storage
+                        .values_mut()
+                        .for_each(|slot| *slot = StorageSlot::default());
+                    if is_spurious_dragon_enabled {
+                        account.info.nonce = 1;
+                    }
+                }
+                JournalEntry::StorageChange {
+                    address,
+                    key,
+                    had_value,
+                } => {
+                    let Some(&JournalValue::Storage(value)) = values.next() else {
+                        return Err(mismatch());
+                    };
+                    let storage = &mut state.get_mut(&address).unwrap().storage;
+                    if had_value.is_some() {
+                        storage.get_mut(&key).unwrap().present_value = value;
+                    } else {
+                        storage.insert(key, StorageSlot::new(value));
+                    }
+                }
+                JournalEntry::TransientStorageChange { address, key, .. } => {
+                    let Some(&JournalValue::Storage(value)) = values.next() else {
+                        return Err(mismatch());
+                    };
+                    let tkey = (address, key);
+                    if value == U256::ZERO {
+                        self.transient_storage.remove(&tkey);
+                    } else {
+                        self.transient_storage.insert(tkey, value);
+                    }
+                }
+                JournalEntry::TransientStorageLoad { .. } => {}
+                JournalEntry::CodeChange { address } => {
+                    let Some(JournalValue::Code(code)) = values.next() else {
+                        return Err(mismatch());
+                    };
+                    let account = state.get_mut(&address).unwrap();
+                    account.info.code_hash = code.hash_slow();
+                    account.info.code = Some(code.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reverts the journal applied with [Self::apply_journal], in reverse order.
+    pub fn revert_journal(&mut self, journal: &[Vec<JournalEntry>]) {
+        let is_spurious_dragon_enabled = self.is_state_clear_enabled();
+        for entries in journal.iter().rev() {
+            Self::journal_revert(
+                &mut self.state,
+                &mut self.transient_storage,
+                entries,
+                is_spurious_dragon_enabled,
+            );
+        }
     }
 
     /// Performances selfdestruct action.
     /// Transfers balance from address to target. Check if target exist/is_cold
     ///
     /// Note: balance will be lost if address and target are the same BUT when
-    /// current spec enables Cancun, this happens only when the account associated to address
-    /// is created in the same tx
+    /// EIP-6780 is enabled, this happens only when the account associated to address
+    /// is created in the same tx, see [Self::is_eip6780_enabled].
     ///
     /// references:
     ///  * <https://github.com/ethereum/go-ethereum/blob/141cd425310b503c5678e674a8c3872cf46b7086/core/vm/instructions.go#L832-L833>
@@ -478,13 +908,14 @@ impl JournaledState {
             target_account.info.balance += acc_balance;
         }
 
+        let is_eip6780_enabled = self.is_eip6780_enabled();
         let acc = self.state.get_mut(&address).unwrap();
         let balance = acc.info.balance;
         let previously_destroyed = acc.is_selfdestructed();
-        let is_cancun_enabled = SpecId::enabled(self.spec, CANCUN);
 
         // EIP-6780 (Cancun hard-fork): selfdestruct only if contract is created in the same tx
-        let journal_entry = if acc.is_created() || !is_cancun_enabled {
+        let destroyed = acc.is_created() || !is_eip6780_enabled;
+        let journal_entry = if destroyed {
             acc.mark_selfdestruct();
             acc.info.balance = U256::ZERO;
             Some(JournalEntry::AccountDestroyed {
@@ -502,7 +933,7 @@ impl JournaledState {
             })
         } else {
             // State is not changed:
-            // * if we are after Cancun upgrade and
+            // * if EIP-6780 is enabled and
             // * Selfdestruct account that is created in the same transaction and
             // * Specify the target is same as selfdestructed account. The balance stays unchanged.
             None
@@ -517,6 +948,7 @@ impl JournaledState {
             is_cold: load_result.is_cold,
             target_exists: !load_result.is_empty,
             previously_destroyed,
+            destroyed,
         })
     }
 
@@ -531,17 +963,14 @@ impl JournaledState {
         // load or get account.
         let account = match self.state.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(vac) => vac.insert(
-                db.basic(address)
-                    .map_err(EVMError::Database)?
-                    .map(|i| i.into())
-                    .unwrap_or(Account::new_not_existing()),
-            ),
+            Entry::Vacant(vac) => {
+                vac.insert(Self::read_account(&mut self.database_cache, address, db)?)
+            }
         };
         // preload storages.
         for slot in slots {
             if let Entry::Vacant(entry) = account.storage.entry(*slot) {
-                let storage = db.storage(address, *slot).map_err(EVMError::Database)?;
+                let storage = Self::read_storage(&mut self.database_cache, address, *slot, db)?;
                 entry.insert(StorageSlot::new(storage));
             }
         }
@@ -555,15 +984,10 @@ impl JournaledState {
         address: Address,
         db: &mut DB,
     ) -> Result<(&mut Account, bool), EVMError<DB::Error>> {
-        Ok(match self.state.entry(address) {
+        let load = match self.state.entry(address) {
             Entry::Occupied(entry) => (entry.into_mut(), false),
             Entry::Vacant(vac) => {
-                let account =
-                    if let Some(account) = db.basic(address).map_err(EVMError::Database)? {
-                        account.into()
-                    } else {
-                        Account::new_not_existing()
-                    };
+                let account = Self::read_account(&mut self.database_cache, address, db)?;
 
                 // journal loading of account. AccessList touch.
                 self.journal
@@ -576,7 +1000,12 @@ impl JournaledState {
 
                 (vac.insert(account), is_cold)
             }
-        })
+        };
+        if let Some(metrics) = &mut self.metrics {
+            metrics.account_loads += 1;
+            metrics.cold_account_loads += load.1 as u64;
+        }
+        Ok(load)
     }
 
     /// Load account from database to JournaledState.
@@ -588,11 +1017,10 @@ impl JournaledState {
         address: Address,
         db: &mut DB,
     ) -> Result<LoadAccountResult, EVMError<DB::Error>> {
-        let spec = self.spec;
+        let is_state_clear_enabled = self.is_state_clear_enabled();
         let (acc, is_cold) = self.load_account(address, db)?;
 
-        let is_spurious_dragon_enabled = SpecId::enabled(spec, SPURIOUS_DRAGON);
-        let is_empty = if is_spurious_dragon_enabled {
+        let is_empty = if is_state_clear_enabled {
             acc.is_empty()
         } else {
             let loaded_not_existing = acc.is_loaded_as_not_existing();
@@ -610,15 +1038,30 @@ impl JournaledState {
         address: Address,
         db: &mut DB,
     ) -> Result<(&mut Account, bool), EVMError<DB::Error>> {
-        let (acc, is_cold) = self.load_account(address, db)?;
+        let (_, is_cold) = self.load_account(address, db)?;
+        let acc = self.state.get_mut(&address).unwrap();
         if acc.info.code.is_none() {
             if acc.info.code_hash == KECCAK_EMPTY {
                 let empty = Bytecode::default();
                 acc.info.code = Some(empty);
             } else {
-                let code = db
-                    .code_by_hash(acc.info.code_hash)
-                    .map_err(EVMError::Database)?;
+                let cached = self
+                    .database_cache
+                    .as_mut()
+                    .and_then(|cache| cache.get_mut(&address))
+                    .filter(|cached| cached.info.code_hash == acc.info.code_hash);
+                let code = match cached.as_ref().and_then(|cached| cached.info.code.clone()) {
+                    Some(code) => code,
+                    None => {
+                        let code = db
+                            .code_by_hash(acc.info.code_hash)
+                            .map_err(EVMError::Database)?;
+                        if let Some(cached) = cached {
+                            cached.info.code = Some(code.clone());
+                        }
+                        code
+                    }
+                };
                 acc.info.code = Some(code);
             }
         }
@@ -636,6 +1079,22 @@ impl JournaledState {
         address: Address,
         key: U256,
         db: &mut DB,
+    ) -> Result<(U256, bool), EVMError<DB::Error>> {
+        let load = self.load_storage(address, key, db)?;
+        if let Some(metrics) = &mut self.metrics {
+            metrics.storage_loads += 1;
+            metrics.cold_storage_loads += load.1 as u64;
+        }
+        Ok(load)
+    }
+
+    /// Loads storage slot without recording it in the metrics.
+    #[inline]
+    fn load_storage<DB: Database>(
+        &mut self,
+        address: Address,
+        key: U256,
+        db: &mut DB,
     ) -> Result<(U256, bool), EVMError<DB::Error>> {
         // assume acc is warm
         let account = self.state.get_mut(&address).unwrap();
@@ -648,7 +1107,7 @@ impl JournaledState {
                 let value = if is_newly_created {
                     U256::ZERO
                 } else {
-                    db.storage(address, key).map_err(EVMError::Database)?
+                    Self::read_storage(&mut self.database_cache, address, key, db)?
                 };
                 // add it to journal as cold loaded.
                 self.journal
@@ -659,6 +1118,7 @@ impl JournaledState {
                         key,
                         had_value: None,
                     });
+                Self::record_value(&mut self.journal_values, || JournalValue::Storage(value));
 
                 vac.insert(StorageSlot::new(value));
 
@@ -683,7 +1143,11 @@ impl JournaledState {
         db: &mut DB,
     ) -> Result<SStoreResult, EVMError<DB::Error>> {
         // assume that acc exists and load the slot.
-        let (present, is_cold) = self.sload(address, key, db)?;
+        let (present, is_cold) = self.load_storage(address, key, db)?;
+        if let Some(metrics) = &mut self.metrics {
+            metrics.storage_stores += 1;
+            metrics.cold_storage_stores += is_cold as u64;
+        }
         let acc = self.state.get_mut(&address).unwrap();
 
         // if there is no original value in dirty return present value, that is our original.
@@ -707,6 +1171,7 @@ impl JournaledState {
                 key,
                 had_value: Some(present),
             });
+        Self::record_value(&mut self.journal_values, || JournalValue::Storage(new));
         // insert value into present state.
         slot.present_value = new;
         Ok(SStoreResult {
@@ -719,9 +1184,17 @@ impl JournaledState {
 
     /// Read transient storage tied to the account.
     ///
+    /// Read is added to the journal if [Self::record_transient_loads] is set.
+    ///
     /// EIP-1153: Transient storage opcodes
     #[inline]
     pub fn tload(&mut self, address: Address, key: U256) -> U256 {
+        if self.record_transient_loads {
+            self.journal
+                .last_mut()
+                .unwrap()
+                .push(JournalEntry::TransientStorageLoad { address, key });
+        }
         self.transient_storage
             .get(&(address, key))
             .copied()
@@ -767,6 +1240,7 @@ impl JournaledState {
                     key,
                     had_value,
                 });
+            Self::record_value(&mut self.journal_values, || JournalValue::Storage(new));
         }
     }
 
@@ -834,16 +1308,299 @@ pub enum JournalEntry {
         key: U256,
         had_value: U256,
     },
+    /// It is used to track an EIP-1153 transient storage read.
+    /// Action: Transient storage slot read.
+    /// Revert: Nothing to revert.
+    TransientStorageLoad { address: Address, key: U256 },
     /// Code changed
     /// Action: Account code changed
     /// Revert: Revert to previous bytecode.
     CodeChange { address: Address },
 }
 
+impl JournalEntry {
+    /// Returns `true` if entry is an EIP-1153 transient storage read or change.
+    #[inline]
+    pub const fn is_transient_storage(&self) -> bool {
+        matches!(
+            self,
+            Self::TransientStorageChange { .. } | Self::TransientStorageLoad { .. }
+        )
+    }
+}
+
+/// New value of a [JournalEntry] that changed the state, see [JournaledState::journal_values].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JournalValue {
+    /// Value of the slot of [JournalEntry::StorageChange] or
+    /// [JournalEntry::TransientStorageChange].
+    Storage(U256),
+    /// Code of [JournalEntry::CodeChange].
+    Code(Bytecode),
+}
+
 /// SubRoutine checkpoint that will help us to go back from this
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JournalCheckpoint {
     log_i: usize,
     journal_i: usize,
+    value_i: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{address, AccountInfo, HashSet},
+    };
+
+    #[test]
+    fn transient_entries() {
+        let address = address!("0000000000000000000000000000000000000100");
+        let mut journaled_state = JournaledState::new(SpecId::CANCUN, HashSet::new());
+        journaled_state.tstore(address, U256::from(1), U256::from(2));
+        // reads are not journaled by default.
+        assert_eq!(journaled_state.tload(address, U256::from(1)), U256::from(2));
+        assert_eq!(journaled_state.journal[0].len(), 1);
+
+        journaled_state.record_transient_loads = true;
+        assert_eq!(journaled_state.tload(address, U256::from(1)), U256::from(2));
+
+        let checkpoint = journaled_state.checkpoint();
+        journaled_state.tstore(address, U256::from(1), U256::from(3));
+        journaled_state.checkpoint_revert(checkpoint);
+        assert_eq!(journaled_state.tload(address, U256::from(1)), U256::from(2));
+
+        assert_eq!(
+            journaled_state.transient_entries().collect::<Vec<_>>(),
+            [
+                &JournalEntry::TransientStorageChange {
+                    address,
+                    key: U256::from(1),
+                    had_value: U256::ZERO,
+                },
+                &JournalEntry::TransientStorageLoad {
+                    address,
+                    key: U256::from(1),
+                },
+                &JournalEntry::TransientStorageLoad {
+                    address,
+                    key: U256::from(1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn database_cache() {
+        let address = Address::with_last_byte(0xa);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(address, AccountInfo::from_balance(U256::from(1)));
+        db.insert_account_storage(address, U256::from(1), U256::from(1))
+            .unwrap();
+
+        let mut journaled_state = JournaledState::new(SpecId::CANCUN, HashSet::new());
+        journaled_state.enable_database_cache();
+        journaled_state.load_account(address, &mut db).unwrap();
+        journaled_state
+            .sload(address, U256::from(1), &mut db)
+            .unwrap();
+        journaled_state.clear();
+
+        // changes of the database are not seen while the cache is enabled.
+        db.insert_account_info(address, AccountInfo::from_balance(U256::from(2)));
+        db.insert_account_storage(address, U256::from(1), U256::from(2))
+            .unwrap();
+        let (account, is_cold) = journaled_state.load_account(address, &mut db).unwrap();
+        assert_eq!(account.info.balance, U256::from(1));
+        assert!(is_cold);
+        assert_eq!(
+            journaled_state
+                .sload(address, U256::from(1), &mut db)
+                .unwrap(),
+            (U256::from(1), true)
+        );
+
+        journaled_state.disable_database_cache();
+        journaled_state.clear();
+        let (account, _) = journaled_state.load_account(address, &mut db).unwrap();
+        assert_eq!(account.info.balance, U256::from(2));
+    }
+
+    #[test]
+    fn apply_and_revert_journal() {
+        let (a, b, c) = (
+            Address::with_last_byte(0xa),
+            Address::with_last_byte(0xb),
+            Address::with_last_byte(0xc),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            a,
+            AccountInfo {
+                balance: U256::from(100),
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(a, U256::from(1), U256::from(5))
+            .unwrap();
+
+        let mut recorder = JournaledState::new(SpecId::CANCUN, HashSet::new());
+        let mut replica = recorder.clone();
+        recorder.enable_journal_values();
+        recorder.load_account(a, &mut db).unwrap();
+        recorder
+            .sstore(a, U256::from(1), U256::from(6), &mut db)
+            .unwrap();
+        recorder.sload(a, U256::from(2), &mut db).unwrap();
+        recorder.transfer(&a, &b, U256::from(40), &mut db).unwrap();
+        recorder.inc_nonce(a);
+        recorder.tstore(a, U256::ZERO, U256::from(7));
+
+        recorder.load_account(c, &mut db).unwrap();
+        recorder
+            .create_account_checkpoint(a, c, U256::from(10), SpecId::CANCUN)
+            .unwrap();
+        recorder.set_code(c, Bytecode::new_raw([0x5f, 0xff].into()));
+        recorder.selfdestruct(c, b, &mut db).unwrap();
+        recorder.checkpoint_commit();
+
+        // reverted changes are not in the journal.
+        let checkpoint = recorder.checkpoint();
+        recorder.transfer(&a, &b, U256::from(1), &mut db).unwrap();
+        recorder.tstore(a, U256::ZERO, U256::from(8));
+        recorder.checkpoint_revert(checkpoint);
+
+        // new values are needed to apply the journal.
+        let values = recorder.journal_values.as_deref().unwrap();
+        assert!(replica
+            .clone()
+            .apply_journal(&recorder.journal, &[], &mut db)
+            .is_err());
+        replica
+            .apply_journal(&recorder.journal, values, &mut db)
+            .unwrap();
+        assert_eq!(replica.state, recorder.state);
+        assert_eq!(replica.transient_storage, recorder.transient_storage);
+        assert_eq!(replica.state[&b].info.balance, U256::from(50));
+        assert_eq!(replica.journal, [vec![]]);
+
+        replica.revert_journal(&recorder.journal);
+        assert!(replica.state.is_empty());
+        assert!(replica.transient_storage.is_empty());
+    }
+
+    #[test]
+    fn metrics() {
+        let address = address!("0000000000000000000000000000000000000100");
+        let mut db = CacheDB::new(EmptyDB::default());
+        let mut journaled_state = JournaledState::new(SpecId::CANCUN, HashSet::new());
+        journaled_state.load_account(address, &mut db).unwrap();
+        assert_eq!(journaled_state.take_metrics(), None);
+
+        journaled_state.enable_metrics();
+        let checkpoint = journaled_state.checkpoint();
+        journaled_state.load_account(address, &mut db).unwrap();
+        journaled_state.sload(address, U256::ZERO, &mut db).unwrap();
+        journaled_state.sload(address, U256::ZERO, &mut db).unwrap();
+        journaled_state
+            .sstore(address, U256::from(1), U256::from(1), &mut db)
+            .unwrap();
+        journaled_state.checkpoint_revert(checkpoint);
+        journaled_state.checkpoint();
+        journaled_state.checkpoint_commit();
+
+        let metrics = journaled_state.take_metrics().unwrap();
+        assert_eq!(
+            metrics,
+            JournalMetrics {
+                account_loads: 1,
+                cold_account_loads: 0,
+                storage_loads: 2,
+                cold_storage_loads: 1,
+                storage_stores: 1,
+                cold_storage_stores: 1,
+                checkpoints: 2,
+                checkpoint_reverts: 1,
+                cold_access_charges: 0,
+                cold_access_gas: 0,
+            }
+        );
+        assert_eq!(metrics.warm_storage_loads(), 1);
+        assert_eq!(metrics.checkpoint_commits(), 1);
+
+        // metrics are kept enabled after they are taken.
+        journaled_state.clear();
+        assert_eq!(
+            journaled_state.take_metrics(),
+            Some(JournalMetrics::default())
+        );
+    }
+
+    #[test]
+    fn state_clear_override() {
+        let address = address!("0000000000000000000000000000000000000100");
+        let mut db = CacheDB::new(EmptyDB::default());
+        let mut journaled_state = JournaledState::new(SpecId::CANCUN, HashSet::new());
+        journaled_state.load_account(address, &mut db).unwrap();
+        journaled_state.touch(&address);
+        assert!(journaled_state.is_state_clear_enabled());
+        assert!(
+            journaled_state
+                .load_account_exist(address, &mut db)
+                .unwrap()
+                .is_empty
+        );
+
+        // touched empty account exists without state clear.
+        journaled_state.set_state_clear(Some(false));
+        assert!(
+            !journaled_state
+                .load_account_exist(address, &mut db)
+                .unwrap()
+                .is_empty
+        );
+
+        journaled_state.set_state_clear(Some(true));
+        journaled_state.set_spec_id(SpecId::HOMESTEAD);
+        assert!(journaled_state.is_state_clear_enabled());
+    }
+
+    #[test]
+    fn eip6780_override() {
+        let address = address!("0000000000000000000000000000000000000100");
+        let target = address!("0000000000000000000000000000000000000200");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                balance: U256::from(10),
+                ..Default::default()
+            },
+        );
+
+        for (spec, eip6780, destroyed) in [
+            (SpecId::CANCUN, None, false),
+            (SpecId::CANCUN, Some(false), true),
+            (SpecId::SHANGHAI, None, true),
+            (SpecId::SHANGHAI, Some(true), false),
+        ] {
+            let mut journaled_state = JournaledState::new(spec, HashSet::new());
+            journaled_state.set_eip6780(eip6780);
+            journaled_state.load_account(address, &mut db).unwrap();
+            let result = journaled_state
+                .selfdestruct(address, target, &mut db)
+                .unwrap();
+            assert_eq!(result.destroyed, destroyed);
+            assert!(result.had_value);
+
+            let account = &journaled_state.state[&address];
+            assert_eq!(account.is_selfdestructed(), destroyed);
+            assert_eq!(account.info.balance, U256::ZERO);
+            assert_eq!(journaled_state.state[&target].info.balance, U256::from(10));
+        }
+    }
 }
@@ -11,6 +11,9 @@ extern crate alloc as std;
 
 // Define modules.
 
+#[cfg(feature = "batch-call")]
+pub mod batch_call;
+mod block_executor;
 mod builder;
 mod context;
 
@@ -18,36 +21,58 @@ mod context;
 pub mod test_utils;
 
 pub mod db;
+mod deployment;
+pub mod erc4337;
 mod evm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 mod frame;
+mod gas_estimation;
 pub mod handler;
 mod inspector;
 mod journaled_state;
 #[cfg(feature = "optimism")]
 pub mod optimism;
+mod simulation;
+pub mod state_commitment;
 
 // Export items.
 
+pub use block_executor::{
+    block_reward, Block, BlockExecutionOutput, BlockExecutor, Ommer, Withdrawal,
+};
 pub use builder::EvmBuilder;
+#[cfg(feature = "std")]
+pub use context::AnalysisCache;
 pub use context::{
-    Context, ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile,
-    ContextStatefulPrecompileArc, ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
-    ContextWithHandlerCfg, EvmContext, InnebcevmContext,
+    Context, ContextCallPrecompile, ContextCallPrecompileArc, ContextPrecompile,
+    ContextPrecompiles, ContextSnapshot, ContextStatefulPrecompile, ContextStatefulPrecompileArc,
+    ContextStatefulPrecompileBox, ContextStatefulPrecompileMut, ContextWithHandlerCfg, EvmContext,
+    InnebcevmContext,
 };
 pub use db::{
     CacheState, DBBox, State, StateBuilder, StateDBBox, TransitionAccount, TransitionState,
 };
 pub use db::{Database, DatabaseCommit, DatabaseRef, InMemoryDB};
+pub use deployment::{Deployment, CREATE2_DEPLOYER, CREATE2_DEPLOYER_CODE};
 pub use evm::{Evm, CALL_STACK_LIMIT};
 pub use frame::{CallFrame, CreateFrame, Frame, FrameData, FrameOrResult, FrameResult};
+pub use gas_estimation::GasEstimate;
 pub use handler::Handler;
 pub use inspector::{
     inspector_handle_register, inspector_instruction, inspectors, GetInspector, Inspector,
 };
-pub use journaled_state::{JournalCheckpoint, JournalEntry, JournaledState};
+pub use journaled_state::{
+    JournalCheckpoint, JournalEntry, JournalMetrics, JournalValue, JournaledState,
+};
+pub use simulation::{SimTx, SimTxResult};
 // export Optimism types, helpers, and constants
 #[cfg(feature = "optimism")]
-pub use optimism::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
+pub use optimism::{
+    L1BlockInfo, L1CostOracle, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT,
+};
 
 // Reexport libraries
 
@@ -4,7 +4,10 @@ mod handler_register;
 mod l1block;
 
 pub use handler_register::{
-    deduct_caller, end, last_frame_return, load_accounts, optimism_handle_register, output,
-    reward_beneficiary, validate_env, validate_tx_against_state,
+    calc_fee_distribution, deduct_caller, end, l1_cost_oracle_handle_register, last_frame_return,
+    load_accounts, load_accounts_with_oracle, optimism_handle_register, output, validate_env,
+    validate_tx_against_state, validate_tx_nonce,
+};
+pub use l1block::{
+    L1BlockInfo, L1CostOracle, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT,
 };
-pub use l1block::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
@@ -4,9 +4,10 @@ use crate::{
     handler::{
         mainnet::{self, deduct_caller_inner},
         register::EvmHandler,
+        FeeDistribution,
     },
     interpreter::{return_ok, return_revert, Gas, InstructionResult},
-    optimism,
+    optimism::{self, L1CostOracle},
     primitives::{
         db::Database, spec_to_generic, Account, EVMError, Env, ExecutionResult, HaltReason,
         HashMap, InvalidTransaction, ResultAndState, Spec, SpecId, SpecId::REGOLITH, U256,
@@ -16,6 +17,7 @@ use crate::{
 use core::ops::Mul;
 use std::string::ToString;
 use std::sync::Arc;
+use std::vec::Vec;
 
 pub fn optimism_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
     spec_to_generic!(handler.cfg.spec_id, {
@@ -23,19 +25,34 @@ pub fn optimism_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_,
         handler.validation.env = Arc::new(validate_env::<SPEC, DB>);
         // Validate transaction against state.
         handler.validation.tx_against_state = Arc::new(validate_tx_against_state::<SPEC, EXT, DB>);
+        handler.validation.tx_nonce_policy = Arc::new(validate_tx_nonce::<EXT, DB>);
         // load l1 data
         handler.pre_execution.load_accounts = Arc::new(load_accounts::<SPEC, EXT, DB>);
         // An estimated batch cost is charged from the caller and added to L1 Fee Vault.
         handler.pre_execution.deduct_caller = Arc::new(deduct_caller::<SPEC, EXT, DB>);
         // Refund is calculated differently then mainnet.
         handler.execution.last_frame_return = Arc::new(last_frame_return::<SPEC, EXT, DB>);
-        handler.post_execution.reward_beneficiary = Arc::new(reward_beneficiary::<SPEC, EXT, DB>);
+        // Fees are also paid to the L1 Fee Vault and the Base Fee Vault.
+        handler.post_execution.calc_fee_distribution =
+            Arc::new(calc_fee_distribution::<SPEC, EXT, DB>);
         // In case of halt of deposit transaction return Error.
         handler.post_execution.output = Arc::new(output::<SPEC, EXT, DB>);
         handler.post_execution.end = Arc::new(end::<SPEC, EXT, DB>);
     });
 }
 
+/// Replaces the Optimism [L1BlockInfo](optimism::L1BlockInfo) with the given L1 cost oracle.
+///
+/// Needs to be appended after [optimism_handle_register], all other stages are kept.
+pub fn l1_cost_oracle_handle_register<O: L1CostOracle + 'static, DB: Database, EXT>(
+    handler: &mut EvmHandler<'_, EXT, DB>,
+) {
+    spec_to_generic!(handler.cfg.spec_id, {
+        handler.pre_execution.load_accounts =
+            Arc::new(load_accounts_with_oracle::<SPEC, O, EXT, DB>);
+    });
+}
+
 /// Validate environment for the Optimism chain.
 pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<DB::Error>> {
     // Do not perform any extra validation for deposit transactions, they are pre-verified on L1.
@@ -51,7 +68,7 @@ pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<
         return Err(InvalidTransaction::DepositSystemTxPostRegolith.into());
     }
 
-    env.validate_tx::<SPEC>()?;
+    env.validate_tx_without_chain_id::<SPEC>()?;
     Ok(())
 }
 
@@ -65,6 +82,16 @@ pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     mainnet::validate_tx_against_state::<SPEC, EXT, DB>(context)
 }
 
+/// Do not validate nonce of deposit transactions, they are pre-verified on L1.
+pub fn validate_tx_nonce<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    if context.evm.inner.env.tx.optimism.source_hash.is_some() {
+        return Ok(());
+    }
+    mainnet::validate_tx_nonce::<EXT, DB>(context)
+}
+
 /// Handle output of the transaction
 #[inline]
 pub fn last_frame_return<SPEC: Spec, EXT, DB: Database>(
@@ -141,16 +168,23 @@ pub fn last_frame_return<SPEC: Spec, EXT, DB: Database>(
 #[inline]
 pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    load_accounts_with_oracle::<SPEC, optimism::L1BlockInfo, EXT, DB>(context)
+}
+
+/// Load account (make them warm) and fetch the L1 cost oracle from database.
+#[inline]
+pub fn load_accounts_with_oracle<SPEC: Spec, O: L1CostOracle + 'static, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
     // the L1-cost fee is only computed for Optimism non-deposit transactions.
 
     if context.evm.inner.env.tx.optimism.source_hash.is_none() {
-        let l1_block_info =
-            crate::optimism::L1BlockInfo::try_fetch(&mut context.evm.inner.db, SPEC::SPEC_ID)
-                .map_err(EVMError::Database)?;
+        let oracle =
+            O::try_fetch(&mut context.evm.inner.db, SPEC::SPEC_ID).map_err(EVMError::Database)?;
 
-        // storage l1 block info for later use.
-        context.evm.inner.l1_block_info = Some(l1_block_info);
+        // store the oracle for later use.
+        context.evm.inner.l1_cost_oracle = Some(Arc::new(oracle));
     }
 
     mainnet::load_accounts::<SPEC, EXT, DB>(context)
@@ -192,9 +226,9 @@ pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
         let tx_l1_cost = context
             .evm
             .inner
-            .l1_block_info
+            .l1_cost_oracle
             .as_ref()
-            .expect("L1BlockInfo should be loaded")
+            .expect("L1 cost oracle should be loaded")
             .calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
         if tx_l1_cost.gt(&caller_account.info.balance) {
             return Err(EVMError::Transaction(
@@ -209,71 +243,56 @@ pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
     Ok(())
 }
 
-/// Reward beneficiary with gas fee.
+/// Calculates the fee distribution of the transaction.
+///
+/// Next to the mainnet beneficiary reward, the L1 cost is paid to the L1 Fee Vault and the
+/// base fee to the Base Fee Vault. Deposit transactions don't pay any fees.
 #[inline]
-pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
+pub fn calc_fee_distribution<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     gas: &Gas,
-) -> Result<(), EVMError<DB::Error>> {
+) -> Result<FeeDistribution, EVMError<DB::Error>> {
     let is_deposit = context.evm.inner.env.tx.optimism.source_hash.is_some();
-
-    // transfer fee to coinbase/beneficiary.
-    if !is_deposit {
-        mainnet::reward_beneficiary::<SPEC, EXT, DB>(context, gas)?;
+    if is_deposit {
+        return Ok(FeeDistribution::default());
     }
 
-    if !is_deposit {
-        // If the transaction is not a deposit transaction, fees are paid out
-        // to both the Base Fee Vault as well as the L1 Fee Vault.
-        let Some(l1_block_info) = &context.evm.inner.l1_block_info else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load L1 block information.".to_string(),
-            ));
-        };
+    // transfer fee to coinbase/beneficiary.
+    let mut distribution = mainnet::calc_fee_distribution::<SPEC, EXT, DB>(context, gas)?;
 
-        let Some(enveloped_tx) = &context.evm.inner.env.tx.optimism.enveloped_tx else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load enveloped transaction.".to_string(),
-            ));
-        };
+    // If the transaction is not a deposit transaction, fees are paid out
+    // to both the Base Fee Vault as well as the L1 Fee Vault.
+    let Some(l1_cost_oracle) = context.evm.inner.l1_cost_oracle.clone() else {
+        return Err(EVMError::Custom(
+            "[OPTIMISM] Failed to load L1 block information.".to_string(),
+        ));
+    };
 
-        let l1_cost = l1_block_info.calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
+    let Some(enveloped_tx) = &context.evm.inner.env.tx.optimism.enveloped_tx else {
+        return Err(EVMError::Custom(
+            "[OPTIMISM] Failed to load enveloped transaction.".to_string(),
+        ));
+    };
 
-        // Send the L1 cost of the transaction to the L1 Fee Vault.
-        let Ok((l1_fee_vault_account, _)) = context
-            .evm
-            .inner
-            .journaled_state
-            .load_account(optimism::L1_FEE_RECIPIENT, &mut context.evm.inner.db)
-        else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load L1 Fee Vault account.".to_string(),
-            ));
-        };
-        l1_fee_vault_account.mark_touch();
-        l1_fee_vault_account.info.balance += l1_cost;
+    // Send the L1 cost of the transaction to the L1 Fee Vault.
+    let l1_cost = l1_cost_oracle.calculate_tx_l1_cost(enveloped_tx, SPEC::SPEC_ID);
+    distribution
+        .recipients
+        .push((l1_cost_oracle.l1_fee_recipient(), l1_cost));
 
-        // Send the base fee of the transaction to the Base Fee Vault.
-        let Ok((base_fee_vault_account, _)) = context
-            .evm
-            .inner
-            .journaled_state
-            .load_account(optimism::BASE_FEE_RECIPIENT, &mut context.evm.inner.db)
-        else {
-            return Err(EVMError::Custom(
-                "[OPTIMISM] Failed to load Base Fee Vault account.".to_string(),
-            ));
-        };
-        base_fee_vault_account.mark_touch();
-        base_fee_vault_account.info.balance += context
-            .evm
-            .inner
-            .env
-            .block
-            .basefee
-            .mul(U256::from(gas.spent() - gas.refunded() as u64));
-    }
-    Ok(())
+    // Send the base fee of the transaction to the Base Fee Vault.
+    let base_fee = context
+        .evm
+        .inner
+        .env
+        .block
+        .basefee
+        .mul(U256::from(gas.spent() - gas.refunded() as u64));
+    distribution
+        .recipients
+        .push((optimism::BASE_FEE_RECIPIENT, base_fee));
+
+    Ok(distribution)
 }
 
 /// Main return handle, returns the output of the transaction.
@@ -304,7 +323,7 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     evm_output: Result<ResultAndState, EVMError<DB::Error>>,
 ) -> Result<ResultAndState, EVMError<DB::Error>> {
-    evm_output.or_else(|err| {
+    mainnet::end(context, evm_output).or_else(|err| {
         if matches!(err, EVMError::Transaction(_))
             && context.evm.inner.env().tx.optimism.source_hash.is_some()
         {
@@ -357,8 +376,12 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
                 result: ExecutionResult::Halt {
                     reason: HaltReason::FailedDeposit,
                     gas_used,
+                    context: None,
                 },
                 state,
+                warnings: Vec::new(),
+                storage_accesses: Vec::new(),
+                subcalls: Vec::new(),
             })
         } else {
             Err(err)
@@ -374,10 +397,10 @@ mod tests {
     use crate::{
         db::{EmptyDB, InMemoryDB},
         primitives::{
-            bytes, state::AccountInfo, Address, BedrockSpec, Bytes, Env, LatestSpec, RegolithSpec,
-            B256,
+            address, bytes, state::AccountInfo, Address, BedrockSpec, Bytes, Env, LatestSpec,
+            RegolithSpec, TransactTo, B256,
         },
-        L1BlockInfo,
+        Evm, L1BlockInfo,
     };
 
     /// Creates frame result.
@@ -471,12 +494,12 @@ mod tests {
             },
         );
         let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
-        context.evm.inner.l1_block_info = Some(L1BlockInfo {
+        context.evm.inner.l1_cost_oracle = Some(Arc::new(L1BlockInfo {
             l1_base_fee: U256::from(1_000),
             l1_fee_overhead: Some(U256::from(1_000)),
             l1_base_fee_scalar: U256::from(1_000),
             ..Default::default()
-        });
+        }));
         // Enveloped needs to be some but it will deduce zero fee.
         context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!(""));
         // added mint value is 10.
@@ -506,12 +529,12 @@ mod tests {
             },
         );
         let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
-        context.evm.inner.l1_block_info = Some(L1BlockInfo {
+        context.evm.inner.l1_cost_oracle = Some(Arc::new(L1BlockInfo {
             l1_base_fee: U256::from(1_000),
             l1_fee_overhead: Some(U256::from(1_000)),
             l1_base_fee_scalar: U256::from(1_000),
             ..Default::default()
-        });
+        }));
         // l1block cost is 1048 fee.
         context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
         // added mint value is 10.
@@ -544,12 +567,12 @@ mod tests {
             },
         );
         let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
-        context.evm.inner.l1_block_info = Some(L1BlockInfo {
+        context.evm.inner.l1_cost_oracle = Some(Arc::new(L1BlockInfo {
             l1_base_fee: U256::from(1_000),
             l1_fee_overhead: Some(U256::from(1_000)),
             l1_base_fee_scalar: U256::from(1_000),
             ..Default::default()
-        });
+        }));
         // l1block cost is 1048 fee.
         context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
         deduct_caller::<RegolithSpec, (), _>(&mut context).unwrap();
@@ -576,12 +599,12 @@ mod tests {
             },
         );
         let mut context: Context<(), InMemoryDB> = Context::new_with_db(db);
-        context.evm.inner.l1_block_info = Some(L1BlockInfo {
+        context.evm.inner.l1_cost_oracle = Some(Arc::new(L1BlockInfo {
             l1_base_fee: U256::from(1_000),
             l1_fee_overhead: Some(U256::from(1_000)),
             l1_base_fee_scalar: U256::from(1_000),
             ..Default::default()
-        });
+        }));
         // l1block cost is 1048 fee.
         context.evm.inner.env.tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
 
@@ -596,6 +619,66 @@ mod tests {
         );
     }
 
+    /// Oracle that charges a flat fee stored in the fee vault.
+    #[derive(Debug)]
+    struct FlatFeeOracle(U256);
+
+    const FEE_VAULT: Address = address!("00000000000000000000000000000000000fee00");
+
+    impl L1CostOracle for FlatFeeOracle {
+        fn try_fetch<DB: Database>(db: &mut DB, _spec_id: SpecId) -> Result<Self, DB::Error> {
+            db.storage(FEE_VAULT, U256::ZERO).map(FlatFeeOracle)
+        }
+
+        fn calculate_tx_l1_cost(&self, _enveloped_tx: &[u8], _spec_id: SpecId) -> U256 {
+            self.0
+        }
+
+        fn l1_fee_recipient(&self) -> Address {
+            FEE_VAULT
+        }
+    }
+
+    #[test]
+    fn test_custom_l1_cost_oracle() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1000),
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(FEE_VAULT, U256::ZERO, U256::from(100))
+            .unwrap();
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .optimism()
+            .append_handler_register(l1_cost_oracle_handle_register::<FlatFeeOracle, _, _>)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(Address::with_last_byte(0xaa));
+                tx.gas_limit = 21_000;
+                tx.optimism.enveloped_tx = Some(bytes!("FACADE"));
+            })
+            .build();
+
+        let state = evm.transact().unwrap().state;
+        assert_eq!(state[&caller].info.balance, U256::from(900));
+        assert_eq!(state[&FEE_VAULT].info.balance, U256::from(100));
+    }
+
+    #[test]
+    fn test_deposit_fee_distribution() {
+        let mut context: Context<(), EmptyDB> = Context::new_empty();
+        context.evm.inner.env.tx.optimism.source_hash = Some(B256::ZERO);
+        assert_eq!(
+            calc_fee_distribution::<RegolithSpec, (), _>(&mut context, &Gas::new(0)),
+            Ok(FeeDistribution::default())
+        );
+    }
+
     #[test]
     fn test_validate_sys_tx() {
         // mark the tx as a system transaction.
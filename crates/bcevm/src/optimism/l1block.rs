@@ -1,5 +1,5 @@
 use crate::primitives::{address, db::Database, Address, SpecId, U256};
-use core::ops::Mul;
+use core::{fmt, ops::Mul};
 
 const ZERO_BYTE_COST: u64 = 4;
 const NON_ZERO_BYTE_COST: u64 = 16;
@@ -34,6 +34,31 @@ pub const BASE_FEE_RECIPIENT: Address = address!("420000000000000000000000000000
 /// The address of the L1Block contract.
 pub const L1_BLOCK_CONTRACT: Address = address!("4200000000000000000000000000000000000015");
 
+/// Source of the L1 data fee that is charged to non-deposit transactions.
+///
+/// The oracle is fetched from the database in the `load_accounts` stage and stored in
+/// [InnebcevmContext::l1_cost_oracle](crate::InnebcevmContext::l1_cost_oracle). The
+/// `deduct_caller` and `calc_fee_distribution` stages use it to charge the caller and pay
+/// the fee to [L1CostOracle::l1_fee_recipient].
+///
+/// [L1BlockInfo] is the Optimism implementation. Rollups with a different slot layout or
+/// fee formula can implement the trait and install it with
+/// [l1_cost_oracle_handle_register](crate::optimism::l1_cost_oracle_handle_register).
+pub trait L1CostOracle: fmt::Debug + Send + Sync {
+    /// Fetches the oracle for the current block from the database.
+    fn try_fetch<DB: Database>(db: &mut DB, spec_id: SpecId) -> Result<Self, DB::Error>
+    where
+        Self: Sized;
+
+    /// Calculates the L1 data fee of the enveloped transaction.
+    fn calculate_tx_l1_cost(&self, enveloped_tx: &[u8], spec_id: SpecId) -> U256;
+
+    /// Returns the address that receives the L1 data fee.
+    fn l1_fee_recipient(&self) -> Address {
+        L1_FEE_RECIPIENT
+    }
+}
+
 /// L1 block info
 ///
 /// We can extract L1 epoch data from each L2 block, by looking at the `setL1BlockValues`
@@ -197,6 +222,16 @@ impl L1BlockInfo {
     }
 }
 
+impl L1CostOracle for L1BlockInfo {
+    fn try_fetch<DB: Database>(db: &mut DB, spec_id: SpecId) -> Result<Self, DB::Error> {
+        L1BlockInfo::try_fetch(db, spec_id)
+    }
+
+    fn calculate_tx_l1_cost(&self, enveloped_tx: &[u8], spec_id: SpecId) -> U256 {
+        L1BlockInfo::calculate_tx_l1_cost(self, enveloped_tx, spec_id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
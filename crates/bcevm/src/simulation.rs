@@ -0,0 +1,233 @@
+use crate::{
+    db::{CacheDB, DatabaseCommit, DatabaseRef, DbAccount, StateOverride},
+    primitives::{Address, BlockEnv, EVMError, ExecutionResult, HashMap, Log, State, TxEnv},
+    Evm,
+};
+use std::vec::Vec;
+
+/// Transaction of a bundle simulated with [Evm::simulate_bundle].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimTx {
+    /// Transaction environment.
+    pub tx: TxEnv,
+    /// State overrides applied before the transaction is executed.
+    ///
+    /// Overrides stay applied for the following transactions of the bundle.
+    pub state_overrides: StateOverride,
+    /// Block environment of the transaction, the block environment of the EVM is used if `None`.
+    pub block: Option<BlockEnv>,
+}
+
+impl SimTx {
+    /// Creates new simulated transaction without overrides.
+    pub fn new(tx: TxEnv) -> Self {
+        Self {
+            tx,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the state overrides.
+    pub fn with_state_overrides(mut self, state_overrides: StateOverride) -> Self {
+        self.state_overrides = state_overrides;
+        self
+    }
+
+    /// Sets the block environment.
+    pub fn with_block(mut self, block: BlockEnv) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+/// Result of a transaction simulated with [Evm::simulate_bundle].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimTxResult {
+    /// Execution result of the transaction.
+    pub result: ExecutionResult,
+    /// Accounts changed by the transaction.
+    pub state: State,
+    /// Gas used by this and all previous transactions of the bundle.
+    pub cumulative_gas_used: u64,
+}
+
+impl SimTxResult {
+    /// Returns logs of the transaction, empty if the transaction did not succeed.
+    pub fn logs(&self) -> &[Log] {
+        self.result.logs()
+    }
+}
+
+impl<EXT, ExtDB: DatabaseRef> Evm<'_, EXT, CacheDB<ExtDB>> {
+    /// Simulates the transactions in order, each one on top of the state changes of the
+    /// previous ones.
+    ///
+    /// Reverted and halted transactions are part of the results. The first invalid
+    /// transaction or database error aborts the whole bundle and the error is returned.
+    ///
+    /// Changes of the bundle are never visible afterwards, accounts changed by the
+    /// transactions or the overrides are restored in the database, while accounts loaded from
+    /// the underlying database stay cached. Transaction and block environment of the EVM are
+    /// restored as well.
+    pub fn simulate_bundle(
+        &mut self,
+        txs: Vec<SimTx>,
+    ) -> Result<Vec<SimTxResult>, EVMError<ExtDB::Error>> {
+        let tx = self.tx().clone();
+        let block = self.block().clone();
+        let mut previous = HashMap::new();
+
+        let results = self.simulate_bundle_inner(txs, &block, &mut previous);
+
+        *self.tx_mut() = tx;
+        *self.block_mut() = block;
+        let accounts = &mut self.context.evm.db.accounts;
+        for (address, account) in previous {
+            match account {
+                Some(account) => accounts.insert(address, account),
+                None => accounts.remove(&address),
+            };
+        }
+        results
+    }
+
+    fn simulate_bundle_inner(
+        &mut self,
+        txs: Vec<SimTx>,
+        block: &BlockEnv,
+        previous: &mut HashMap<Address, Option<DbAccount>>,
+    ) -> Result<Vec<SimTxResult>, EVMError<ExtDB::Error>> {
+        let mut results = Vec::with_capacity(txs.len());
+        let mut cumulative_gas_used = 0;
+        for sim_tx in txs {
+            self.save_accounts(sim_tx.state_overrides.keys(), previous);
+            self.context
+                .evm
+                .db
+                .apply_overrides(&sim_tx.state_overrides)
+                .map_err(EVMError::Database)?;
+
+            *self.block_mut() = sim_tx.block.unwrap_or_else(|| block.clone());
+            *self.tx_mut() = sim_tx.tx;
+            let result_and_state = self.transact()?;
+
+            self.save_accounts(result_and_state.state.keys(), previous);
+            self.context.evm.db.commit(result_and_state.state.clone());
+
+            cumulative_gas_used += result_and_state.result.gas_used();
+            results.push(SimTxResult {
+                result: result_and_state.result,
+                state: result_and_state.state,
+                cumulative_gas_used,
+            });
+        }
+        Ok(results)
+    }
+
+    /// Saves cached accounts that are not saved yet, so they can be restored.
+    fn save_accounts<'b>(
+        &self,
+        addresses: impl IntoIterator<Item = &'b Address>,
+        previous: &mut HashMap<Address, Option<DbAccount>>,
+    ) {
+        let accounts = &self.context.evm.db.accounts;
+        for address in addresses {
+            previous
+                .entry(*address)
+                .or_insert_with(|| accounts.get(address).cloned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{AccountOverride, EmptyDB},
+        interpreter::opcode,
+        primitives::{AccountInfo, Bytes, InvalidTransaction, TransactTo, U256},
+    };
+
+    fn transfer(from: Address, to: Address, value: u64) -> TxEnv {
+        TxEnv {
+            caller: from,
+            transact_to: TransactTo::Call(to),
+            value: U256::from(value),
+            gas_limit: 100_000,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if cached accounts are the same as in [EmptyDB].
+    fn unchanged(db: &CacheDB<EmptyDB>) -> bool {
+        db.accounts
+            .values()
+            .all(|account| account.info == AccountInfo::default() && account.storage.is_empty())
+    }
+
+    #[test]
+    fn simulate_bundle() {
+        let (a, b, c) = (
+            Address::with_last_byte(0xa),
+            Address::with_last_byte(0xb),
+            Address::with_last_byte(0xc),
+        );
+        let contract = Address::with_last_byte(0x42);
+        // NUMBER PUSH0 MSTORE PUSH1 32 PUSH0 RETURN
+        let code = Bytes::from_static(&[
+            opcode::NUMBER,
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            32,
+            opcode::PUSH0,
+            opcode::RETURN,
+        ]);
+        let block = BlockEnv {
+            number: U256::from(42),
+            ..Default::default()
+        };
+
+        let mut evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .build();
+        let txs = vec![
+            SimTx::new(transfer(a, b, 1000)).with_state_overrides(
+                [(a, AccountOverride::default().with_balance(U256::from(1000)))].into(),
+            ),
+            // funded by the first transaction.
+            SimTx::new(transfer(b, c, 1000)),
+            SimTx::new(transfer(c, contract, 0))
+                .with_state_overrides(
+                    [(contract, AccountOverride::default().with_code(code))].into(),
+                )
+                .with_block(block),
+        ];
+        let results = evm.simulate_bundle(txs.clone()).unwrap();
+
+        assert!(results.iter().all(|r| r.result.is_success()));
+        assert_eq!(results[1].state[&c].info.balance, U256::from(1000));
+        let output = results[2].result.output().unwrap();
+        assert_eq!(U256::from_be_slice(output), U256::from(42));
+        assert_eq!(
+            results
+                .iter()
+                .map(|r| r.cumulative_gas_used)
+                .collect::<Vec<_>>(),
+            [21_000, 42_000, 42_000 + results[2].result.gas_used()]
+        );
+        assert!(unchanged(evm.db()));
+        assert_eq!(evm.block().number, U256::ZERO);
+
+        // bundle is aborted by the invalid transaction.
+        let mut txs = txs;
+        txs[1].tx.value = U256::from(2000);
+        assert!(matches!(
+            evm.simulate_bundle(txs),
+            Err(EVMError::Transaction(
+                InvalidTransaction::LackOfFundForMaxFee { .. }
+            ))
+        ));
+        assert!(unchanged(evm.db()));
+    }
+}
@@ -0,0 +1,249 @@
+//! Commitments to the state changes of a transaction.
+//!
+//! [changeset_hash] commits to the [State] returned in
+//! [ResultAndState](crate::primitives::ResultAndState) with a canonical encoding that does
+//! not depend on the iteration order of the map. With the `triehash` feature enabled,
+//! Merkle-Patricia roots of the changeset, of the storage and of the full state can be
+//! computed as well.
+
+use crate::primitives::{keccak256, Account, Address, State, B256, U256};
+use std::vec::Vec;
+
+/// Account was selfdestructed, its info and storage are not encoded.
+const SELFDESTRUCTED: u8 = 1;
+/// Account was created, its storage was cleared before the changes.
+const CREATED: u8 = 2;
+
+/// Returns the keccak hash of the canonical encoding of the changeset.
+///
+/// Only touched accounts are part of the changeset, accounts that were only loaded are
+/// skipped. Accounts are sorted by address and each one is encoded as:
+///
+/// * address (20 bytes),
+/// * flags (1 byte): `1` if the account was selfdestructed, `2` if it was created,
+/// * for accounts that were not selfdestructed, nonce (8 bytes, big endian), balance
+///   (32 bytes) and code hash (32 bytes), followed by the number of changed storage slots
+///   (8 bytes, big endian) and the key and present value of every changed slot (32 bytes
+///   each), sorted by key.
+pub fn changeset_hash(state: &State) -> B256 {
+    let mut out = Vec::new();
+    for (address, account) in sorted_changeset(state) {
+        out.extend_from_slice(address.as_slice());
+        let mut flags = 0;
+        if account.is_selfdestructed() {
+            flags |= SELFDESTRUCTED;
+        }
+        if account.is_created() {
+            flags |= CREATED;
+        }
+        out.push(flags);
+        if account.is_selfdestructed() {
+            continue;
+        }
+        out.extend_from_slice(&account.info.nonce.to_be_bytes());
+        out.extend_from_slice(&account.info.balance.to_be_bytes::<32>());
+        out.extend_from_slice(account.info.code_hash.as_slice());
+        let storage = sorted_changed_storage(account);
+        out.extend_from_slice(&(storage.len() as u64).to_be_bytes());
+        for (key, value) in storage {
+            out.extend_from_slice(&key.to_be_bytes::<32>());
+            out.extend_from_slice(&value.to_be_bytes::<32>());
+        }
+    }
+    keccak256(out)
+}
+
+/// Returns the touched accounts sorted by address.
+fn sorted_changeset(state: &State) -> Vec<(&Address, &Account)> {
+    let mut accounts: Vec<_> = state
+        .iter()
+        .filter(|(_, account)| account.is_touched())
+        .collect();
+    accounts.sort_unstable_by_key(|(address, _)| *address);
+    accounts
+}
+
+/// Returns the key and present value of the changed slots sorted by key.
+fn sorted_changed_storage(account: &Account) -> Vec<(U256, U256)> {
+    let mut storage: Vec<_> = account
+        .changed_storage_slots()
+        .map(|(key, slot)| (*key, slot.present_value))
+        .collect();
+    storage.sort_unstable_by_key(|(key, _)| *key);
+    storage
+}
+
+#[cfg(feature = "triehash")]
+pub use trie::{changeset_trie_root, state_root, storage_root};
+
+#[cfg(feature = "triehash")]
+mod trie {
+    use super::*;
+    use crate::{db::PlainAccount, primitives::AccountInfo};
+    use alloy_rlp::RlpEncodable;
+    use hash_db::Hasher;
+    use plain_hasher::PlainHasher;
+    use triehash::sec_trie_root;
+
+    /// Returns the root of the storage trie. Slots with zero value are skipped.
+    pub fn storage_root(storage: impl IntoIterator<Item = (U256, U256)>) -> B256 {
+        sec_trie_root::<KeccakHasher, _, _, _>(
+            storage
+                .into_iter()
+                .filter(|(_, value)| *value != U256::ZERO)
+                .map(|(key, value)| (key.to_be_bytes::<32>(), alloy_rlp::encode(value))),
+        )
+    }
+
+    /// Returns the root of the state trie of the accounts.
+    ///
+    /// Accounts should contain the full state, e.g.
+    /// [CacheState::trie_account](crate::CacheState::trie_account) after the changes
+    /// are committed.
+    pub fn state_root<'a>(accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>) -> B256 {
+        sec_trie_root::<KeccakHasher, _, _, _>(accounts.into_iter().map(|(address, account)| {
+            let storage_root =
+                storage_root(account.storage.iter().map(|(key, value)| (*key, *value)));
+            (address, trie_account(&account.info, storage_root))
+        }))
+    }
+
+    /// Returns the root of the state trie of the changeset.
+    ///
+    /// The trie contains the touched accounts that were not selfdestructed, with the
+    /// storage root of their changed slots. It is equal to the state root only if the
+    /// changeset contains the full state, e.g. when executing on an empty database.
+    pub fn changeset_trie_root(state: &State) -> B256 {
+        sec_trie_root::<KeccakHasher, _, _, _>(
+            sorted_changeset(state)
+                .into_iter()
+                .filter(|(_, account)| !account.is_selfdestructed())
+                .map(|(address, account)| {
+                    let storage_root = storage_root(sorted_changed_storage(account));
+                    (address, trie_account(&account.info, storage_root))
+                }),
+        )
+    }
+
+    /// Returns the RLP encoded account of the state trie.
+    fn trie_account(info: &AccountInfo, storage_root: B256) -> Vec<u8> {
+        alloy_rlp::encode(TrieAccount {
+            nonce: info.nonce,
+            balance: info.balance,
+            storage_root,
+            code_hash: info.code_hash,
+        })
+    }
+
+    #[derive(RlpEncodable)]
+    struct TrieAccount {
+        nonce: u64,
+        balance: U256,
+        storage_root: B256,
+        code_hash: B256,
+    }
+
+    #[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+    struct KeccakHasher;
+
+    impl Hasher for KeccakHasher {
+        type Out = B256;
+        type StdHasher = PlainHasher;
+        const LENGTH: usize = 32;
+
+        #[inline]
+        fn hash(x: &[u8]) -> Self::Out {
+            keccak256(x)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{address, AccountInfo, AccountStatus, HashMap, StorageSlot};
+
+    fn state() -> State {
+        let mut touched = Account::from(AccountInfo::from_balance(U256::from(10)));
+        touched.mark_touch();
+        touched.storage = HashMap::from_iter([
+            (
+                U256::from(1),
+                StorageSlot::new_changed(U256::ZERO, U256::from(5)),
+            ),
+            (U256::from(2), StorageSlot::new(U256::from(7))),
+            (
+                U256::from(3),
+                StorageSlot::new_changed(U256::from(1), U256::ZERO),
+            ),
+        ]);
+        let mut destroyed = Account::from(AccountInfo::from_balance(U256::from(1)));
+        destroyed.status = AccountStatus::Touched | AccountStatus::SelfDestructed;
+        let loaded = Account::from(AccountInfo::from_balance(U256::from(3)));
+        HashMap::from_iter([
+            (
+                address!("1000000000000000000000000000000000000001"),
+                touched,
+            ),
+            (
+                address!("2000000000000000000000000000000000000002"),
+                destroyed,
+            ),
+            (address!("3000000000000000000000000000000000000003"), loaded),
+        ])
+    }
+
+    #[test]
+    fn changeset_hash_is_canonical() {
+        let state = state();
+        let hash = changeset_hash(&state);
+        // insertion order does not matter.
+        let mut accounts: Vec<_> = state.clone().into_iter().collect();
+        accounts.reverse();
+        let reversed: State = accounts.into_iter().collect();
+        assert_eq!(changeset_hash(&reversed), hash);
+
+        // loaded accounts and unchanged slots are not part of the changeset.
+        let mut modified = state.clone();
+        modified.remove(&address!("3000000000000000000000000000000000000003"));
+        modified
+            .get_mut(&address!("1000000000000000000000000000000000000001"))
+            .unwrap()
+            .storage
+            .remove(&U256::from(2));
+        assert_eq!(changeset_hash(&modified), hash);
+
+        modified
+            .get_mut(&address!("1000000000000000000000000000000000000001"))
+            .unwrap()
+            .info
+            .nonce = 1;
+        assert_ne!(changeset_hash(&modified), hash);
+        assert_ne!(changeset_hash(&State::default()), hash);
+    }
+
+    #[cfg(feature = "triehash")]
+    #[test]
+    fn changeset_trie_root_matches_state_root() {
+        use crate::{db::PlainAccount, primitives::KECCAK_EMPTY};
+
+        assert_eq!(
+            changeset_trie_root(&State::default()),
+            crate::primitives::b256!(
+                "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+            )
+        );
+        let account = PlainAccount {
+            info: AccountInfo::from_balance(U256::from(10)),
+            storage: HashMap::from_iter([(U256::from(1), U256::from(5))]),
+        };
+        assert_eq!(account.info.code_hash, KECCAK_EMPTY);
+        assert_eq!(
+            changeset_trie_root(&state()),
+            state_root([(
+                address!("1000000000000000000000000000000000000001"),
+                &account
+            )])
+        );
+    }
+}
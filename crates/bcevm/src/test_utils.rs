@@ -1,2 +1,4 @@
+pub mod differential;
+
 #[doc(hidden)]
 pub use crate::context::evm_context::test_utils::*;
@@ -0,0 +1,183 @@
+//! Differential execution of the same transaction under two handler configurations.
+//!
+//! Both sides execute the same [Env] on their own copy of the database. Journals of
+//! the transaction are compared entry by entry and then the results are compared.
+use crate::{
+    builder::SetGenericStage,
+    db::Database,
+    primitives::{EVMResult, Env},
+    Evm, EvmBuilder, JournalEntry,
+};
+use core::{cell::RefCell, fmt};
+use std::{boxed::Box, rc::Rc, sync::Arc, vec::Vec};
+
+/// Difference found between the two executions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence<DBError> {
+    /// Journals differ at the given index. `None` means that journal ended before it.
+    Journal {
+        index: usize,
+        left: Option<Box<JournalEntry>>,
+        right: Option<Box<JournalEntry>>,
+    },
+    /// Journals are the same but results differ.
+    Result {
+        left: Box<EVMResult<DBError>>,
+        right: Box<EVMResult<DBError>>,
+    },
+}
+
+impl<DBError: fmt::Debug> fmt::Display for Divergence<DBError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Journal { index, left, right } => write!(
+                f,
+                "journal entry {index} diverged: left {left:?}, right {right:?}"
+            ),
+            Self::Result { left, right } => {
+                write!(f, "result diverged: left {left:?}, right {right:?}")
+            }
+        }
+    }
+}
+
+/// Executes the transaction from `env` with both EVMs and compares the outcomes.
+///
+/// `left` and `right` receive the builder with database and environment set and
+/// configure the handler, for example with [`EvmBuilder::with_spec_id`] or by
+/// appending handle registers. Returns the result if both executions are identical.
+pub fn differential<'a, DB>(
+    db: DB,
+    env: Box<Env>,
+    left: impl FnOnce(EvmBuilder<'a, SetGenericStage, (), DB>) -> Evm<'a, (), DB>,
+    right: impl FnOnce(EvmBuilder<'a, SetGenericStage, (), DB>) -> Evm<'a, (), DB>,
+) -> Result<EVMResult<DB::Error>, Divergence<DB::Error>>
+where
+    DB: Database + Clone + 'a,
+    DB::Error: PartialEq,
+{
+    let (left_result, left_journal) = execute(left(builder(db.clone(), env.clone())));
+    let (right_result, right_journal) = execute(right(builder(db, env)));
+
+    let len = left_journal.len().max(right_journal.len());
+    if let Some(index) = (0..len).find(|&i| left_journal.get(i) != right_journal.get(i)) {
+        return Err(Divergence::Journal {
+            index,
+            left: left_journal.get(index).cloned().map(Box::new),
+            right: right_journal.get(index).cloned().map(Box::new),
+        });
+    }
+    if left_result != right_result {
+        return Err(Divergence::Result {
+            left: Box::new(left_result),
+            right: Box::new(right_result),
+        });
+    }
+    Ok(left_result)
+}
+
+/// Same as [differential] but panics on divergence.
+#[track_caller]
+pub fn assert_differential<'a, DB>(
+    db: DB,
+    env: Box<Env>,
+    left: impl FnOnce(EvmBuilder<'a, SetGenericStage, (), DB>) -> Evm<'a, (), DB>,
+    right: impl FnOnce(EvmBuilder<'a, SetGenericStage, (), DB>) -> Evm<'a, (), DB>,
+) -> EVMResult<DB::Error>
+where
+    DB: Database + Clone + 'a,
+    DB::Error: PartialEq + fmt::Debug,
+{
+    differential(db, env, left, right).unwrap_or_else(|divergence| panic!("{divergence}"))
+}
+
+fn builder<'a, DB: Database>(db: DB, env: Box<Env>) -> EvmBuilder<'a, SetGenericStage, (), DB> {
+    Evm::builder().with_db(db).with_env(env)
+}
+
+/// Executes the transaction and returns its result with the journal, that is
+/// captured before it is cleared by the output handle.
+fn execute<'a, DB: Database + 'a>(
+    mut evm: Evm<'a, (), DB>,
+) -> (EVMResult<DB::Error>, Vec<JournalEntry>) {
+    let journal = Rc::new(RefCell::new(Vec::new()));
+    let captured = journal.clone();
+    evm.handler
+        .append_handler_register_box(Box::new(move |handler| {
+            let output = handler.post_execution.output.clone();
+            let captured = captured.clone();
+            handler.post_execution.output = Arc::new(move |context, frame_result| {
+                *captured.borrow_mut() = context
+                    .evm
+                    .journaled_state
+                    .journal
+                    .iter()
+                    .flatten()
+                    .cloned()
+                    .collect();
+                output(context, frame_result)
+            });
+        }));
+    let result = evm.transact();
+    (result, journal.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::{address, AccountInfo, Bytecode, Bytes, SpecId, TransactTo, U256},
+    };
+
+    fn setup() -> (InMemoryDB, Box<Env>) {
+        let contract = address!("0000000000000000000000000000000000000100");
+        // PUSH1 0x01 PUSH1 0x00 SSTORE BASEFEE PUSH1 0x01 SSTORE
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x48, 0x60, 0x01, 0x55,
+        ]));
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+        );
+        let mut env = Box::<Env>::default();
+        env.tx.transact_to = TransactTo::Call(contract);
+        (db, env)
+    }
+
+    #[test]
+    fn identical_executions() {
+        let (db, env) = setup();
+        let result = assert_differential(
+            db,
+            env,
+            |builder| builder.with_spec_id(SpecId::CANCUN).build(),
+            |builder| {
+                builder
+                    .with_spec_id(SpecId::CANCUN)
+                    .append_handler_register(|handler| {
+                        if let Some(table) = handler.instruction_table.as_mut() {
+                            table.convert_boxed();
+                        }
+                    })
+                    .build()
+            },
+        );
+        assert!(result.unwrap().result.is_success());
+    }
+
+    #[test]
+    fn diverging_executions() {
+        let (db, env) = setup();
+        // BASEFEE is not available before London.
+        let divergence = differential(
+            db,
+            env,
+            |builder| builder.with_spec_id(SpecId::CANCUN).build(),
+            |builder| builder.with_spec_id(SpecId::BERLIN).build(),
+        )
+        .unwrap_err();
+        assert!(matches!(divergence, Divergence::Journal { .. }));
+    }
+}
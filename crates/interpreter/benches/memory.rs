@@ -0,0 +1,34 @@
+use bcevm_interpreter::{
+    bench_utils::{
+        calldatacopy_code, keccak256_code, mcopy_code, memory_expansion_code, SyntheticInterpreter,
+    },
+    primitives::{Bytes, CancunSpec},
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Sizes in bytes of the memory operations.
+const SIZES: [u32; 4] = [32, 1024, 32 * 1024, 1024 * 1024];
+
+/// Benchmarks memory operations with increasing sizes.
+fn memory_ops(c: &mut Criterion) {
+    let mut g = c.benchmark_group("memory");
+    for size in SIZES {
+        g.throughput(Throughput::Bytes(size as u64));
+        let input = Bytes::from(vec![0xff; size as usize]);
+        for (name, code) in [
+            ("mcopy", mcopy_code(size)),
+            ("keccak256", keccak256_code(size)),
+            ("calldatacopy", calldatacopy_code(size)),
+            ("expansion", memory_expansion_code(size)),
+        ] {
+            let mut interp = SyntheticInterpreter::new::<CancunSpec>(code, input.clone());
+            g.bench_with_input(BenchmarkId::new(name, size), &size, |b, _| {
+                b.iter(|| interp.run())
+            });
+        }
+    }
+    g.finish();
+}
+
+criterion_group!(benches, memory_ops);
+criterion_main!(benches);
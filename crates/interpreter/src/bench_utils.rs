@@ -0,0 +1,122 @@
+//! Synthetic interpreters and bytecode for benchmarks.
+//!
+//! [SyntheticInterpreter] runs a bytecode with the [DummyHost] and reuses its memory between
+//! the runs, so benchmarks measure the instructions and not the setup. The `*_code`
+//! functions build bytecode that executes one memory operation over `size` bytes.
+
+use crate::{
+    interpreter::{analysis::to_analysed, Contract, Interpreter, SharedMemory},
+    opcode::{self, make_instruction_table, InstructionTable},
+    primitives::{Bytecode, Bytes, Env, Spec},
+    DummyHost, InstructionResult, EMPTY_SHARED_MEMORY,
+};
+use std::vec::Vec;
+
+/// Interpreter that executes a bytecode with the [DummyHost].
+pub struct SyntheticInterpreter {
+    contract: Contract,
+    instruction_table: InstructionTable<DummyHost>,
+    host: DummyHost,
+    shared_memory: SharedMemory,
+}
+
+impl SyntheticInterpreter {
+    /// Creates a new interpreter of the code with the instructions of the spec, the input is
+    /// the calldata of the call.
+    pub fn new<SPEC: Spec>(code: Bytes, input: Bytes) -> Self {
+        Self {
+            contract: Contract {
+                input,
+                bytecode: to_analysed(Bytecode::new_raw(code)),
+                ..Default::default()
+            },
+            instruction_table: make_instruction_table::<DummyHost, SPEC>(),
+            host: DummyHost::new(Env::default()),
+            shared_memory: SharedMemory::new(),
+        }
+    }
+
+    /// Executes the code with unlimited gas and returns the result.
+    ///
+    /// Memory starts empty on every run, its allocation is reused.
+    pub fn run(&mut self) -> InstructionResult {
+        let mut interpreter = Interpreter::new(self.contract.clone(), u64::MAX, false);
+        let mut shared_memory = core::mem::replace(&mut self.shared_memory, EMPTY_SHARED_MEMORY);
+        shared_memory.new_context();
+        interpreter.run(shared_memory, &self.instruction_table, &mut self.host);
+        self.shared_memory = interpreter.take_memory();
+        self.shared_memory.free_context();
+        self.host.clear();
+        interpreter.instruction_result
+    }
+}
+
+/// Returns code that copies `size` bytes of memory with `MCOPY` to the end of the copied
+/// range, expanding the memory to `2 * size` bytes.
+pub fn mcopy_code(size: u32) -> Bytes {
+    let mut code = Vec::new();
+    push(&mut code, size);
+    code.push(opcode::PUSH0);
+    push(&mut code, size);
+    code.extend([opcode::MCOPY, opcode::STOP]);
+    code.into()
+}
+
+/// Returns code that hashes `size` bytes of memory with `KECCAK256`.
+pub fn keccak256_code(size: u32) -> Bytes {
+    let mut code = Vec::new();
+    push(&mut code, size);
+    code.extend([opcode::PUSH0, opcode::KECCAK256, opcode::POP, opcode::STOP]);
+    code.into()
+}
+
+/// Returns code that copies `size` bytes of the calldata to memory with `CALLDATACOPY`.
+pub fn calldatacopy_code(size: u32) -> Bytes {
+    let mut code = Vec::new();
+    push(&mut code, size);
+    code.extend([
+        opcode::PUSH0,
+        opcode::PUSH0,
+        opcode::CALLDATACOPY,
+        opcode::STOP,
+    ]);
+    code.into()
+}
+
+/// Returns code that expands the memory to `size` bytes with `MSTORE8`. `size` needs to be
+/// positive.
+pub fn memory_expansion_code(size: u32) -> Bytes {
+    let mut code = Vec::new();
+    code.push(opcode::PUSH0);
+    push(&mut code, size - 1);
+    code.extend([opcode::MSTORE8, opcode::STOP]);
+    code.into()
+}
+
+fn push(code: &mut Vec<u8>, value: u32) {
+    code.push(opcode::PUSH4);
+    code.extend(value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::CancunSpec;
+
+    #[test]
+    fn synthetic_interpreter() {
+        let size = 1 << 16;
+        for code in [
+            mcopy_code(size),
+            keccak256_code(size),
+            calldatacopy_code(size),
+            memory_expansion_code(size),
+        ] {
+            let input = Bytes::from(vec![1; size as usize]);
+            let mut interp = SyntheticInterpreter::new::<CancunSpec>(code, input);
+            assert_eq!(interp.run(), InstructionResult::Stop);
+            assert!(interp.shared_memory.is_empty());
+            assert_eq!(interp.run(), InstructionResult::Stop);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Bytecode disassembler.
+//!
+//! [Disassembler] iterates over instructions of legacy bytecode or of an EOF code
+//! section, and [DisasmDisplay] formats the whole [Bytecode] as a listing.
+use crate::{
+    opcode::{OpCode, OPCODE_INFO_JUMPTABLE, RJUMPV},
+    primitives::{hex, Bytecode},
+};
+use core::fmt;
+
+/// Single disassembled instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DisasmInstruction<'a> {
+    /// Offset of the instruction in the code.
+    pub pc: usize,
+    /// Opcode byte.
+    pub opcode: u8,
+    /// Immediate bytes that follow the opcode.
+    ///
+    /// It is shorter than expected if the code ends in the middle of the immediate.
+    pub immediate: &'a [u8],
+}
+
+impl DisasmInstruction<'_> {
+    /// Returns the [OpCode], or `None` if opcode is unknown.
+    #[inline]
+    pub const fn op(&self) -> Option<OpCode> {
+        OpCode::new(self.opcode)
+    }
+
+    /// Returns `true` if immediate is truncated by the end of the code.
+    #[inline]
+    pub fn is_truncated(&self, eof: bool) -> bool {
+        self.immediate.len() < immediate_size(self.opcode, self.immediate, eof)
+    }
+}
+
+impl fmt::Display for DisasmInstruction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04x}: ", self.pc)?;
+        match self.op() {
+            Some(op) => f.write_str(op.as_str())?,
+            None => write!(f, "UNKNOWN(0x{:02x})", self.opcode)?,
+        }
+        if !self.immediate.is_empty() {
+            write!(f, " 0x{}", hex::encode(self.immediate))?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over instructions of the code.
+#[derive(Clone, Debug)]
+pub struct Disassembler<'a> {
+    code: &'a [u8],
+    pc: usize,
+    eof: bool,
+}
+
+impl<'a> Disassembler<'a> {
+    /// Disassembles legacy bytecode, where only PUSH opcodes have immediates.
+    #[inline]
+    pub const fn legacy(code: &'a [u8]) -> Self {
+        Self {
+            code,
+            pc: 0,
+            eof: false,
+        }
+    }
+
+    /// Disassembles EOF code section, with immediates of the EOF opcodes.
+    #[inline]
+    pub const fn eof(code: &'a [u8]) -> Self {
+        Self {
+            code,
+            pc: 0,
+            eof: true,
+        }
+    }
+}
+
+impl<'a> Iterator for Disassembler<'a> {
+    type Item = DisasmInstruction<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pc = self.pc;
+        let opcode = *self.code.get(pc)?;
+        let rest = &self.code[pc + 1..];
+        let size = immediate_size(opcode, rest, self.eof).min(rest.len());
+        self.pc += 1 + size;
+        Some(DisasmInstruction {
+            pc,
+            opcode,
+            immediate: &rest[..size],
+        })
+    }
+}
+
+/// Returns size of the immediate of the opcode, `code` starts after the opcode.
+fn immediate_size(opcode: u8, code: &[u8], eof: bool) -> usize {
+    let Some(info) = OPCODE_INFO_JUMPTABLE[opcode as usize] else {
+        return 0;
+    };
+    if !eof && !OpCode::is_push_by_op(opcode) {
+        return 0;
+    }
+    if opcode == RJUMPV {
+        // max index byte followed by the jump table of i16 offsets.
+        return code
+            .first()
+            .map_or(1, |&max_index| 1 + (max_index as usize + 1) * 2);
+    }
+    info.immediate_size() as usize
+}
+
+/// Formats bytecode as a listing of instructions.
+///
+/// EOF bytecode is formatted as a list of code sections, followed by the number of
+/// subcontainers and size of the data section.
+#[derive(Clone, Copy, Debug)]
+pub struct DisasmDisplay<'a>(pub &'a Bytecode);
+
+impl fmt::Display for DisasmDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Bytecode::Eof(eof) = self.0 else {
+            for instruction in Disassembler::legacy(self.0.original_byte_slice()) {
+                writeln!(f, "{instruction}")?;
+            }
+            return Ok(());
+        };
+
+        for (index, code) in eof.body.code_section.iter().enumerate() {
+            let types = eof.body.types_section.get(index);
+            write!(f, "code section {index}")?;
+            if let Some(types) = types {
+                write!(
+                    f,
+                    " (inputs: {}, outputs: {}, max stack: {})",
+                    types.inputs, types.outputs, types.max_stack_size
+                )?;
+            }
+            writeln!(f, ":")?;
+            for instruction in Disassembler::eof(code) {
+                writeln!(f, "  {instruction}")?;
+            }
+        }
+        writeln!(f, "containers: {}", eof.body.container_section.len())?;
+        writeln!(f, "data: {} bytes", eof.body.data_section.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::{ADD, PUSH1, PUSH2, RJUMP, STOP};
+    use crate::primitives::{bytes, hex, Eof};
+    use std::{string::ToString, vec::Vec};
+
+    #[test]
+    fn legacy() {
+        let code = hex!("6001610203e0000c61ff");
+        let instructions: Vec<_> = Disassembler::legacy(&code).collect();
+        let expected = [
+            (0, PUSH1, &[0x01][..]),
+            (2, PUSH2, &[0x02, 0x03][..]),
+            // RJUMP has no immediate in legacy code.
+            (5, RJUMP, &[][..]),
+            (6, STOP, &[][..]),
+            (7, 0x0c, &[][..]),
+            (8, PUSH2, &[0xff][..]),
+        ];
+        assert_eq!(instructions.len(), expected.len());
+        for (instruction, (pc, opcode, immediate)) in instructions.iter().zip(expected) {
+            assert_eq!(instruction.pc, pc);
+            assert_eq!(instruction.opcode, opcode);
+            assert_eq!(instruction.immediate, immediate);
+        }
+        assert!(instructions[5].is_truncated(false));
+        assert_eq!(instructions[1].to_string(), "0002: PUSH2 0x0203");
+        assert_eq!(instructions[4].to_string(), "0007: UNKNOWN(0x0c)");
+    }
+
+    #[test]
+    fn eof_code_section() {
+        let code = hex!("6001e20100010002e0fffc01");
+        let instructions: Vec<_> = Disassembler::eof(&code)
+            .map(|i| (i.pc, i.opcode, i.immediate.len()))
+            .collect();
+        assert_eq!(
+            instructions,
+            [(0, PUSH1, 1), (2, RJUMPV, 5), (8, RJUMP, 2), (11, ADD, 0)]
+        );
+    }
+
+    #[test]
+    fn display_eof() {
+        let eof = Eof::decode(bytes!("ef000101000402000100030400010000800001600100aa")).unwrap();
+        assert_eq!(
+            DisasmDisplay(&Bytecode::Eof(eof)).to_string(),
+            "code section 0 (inputs: 0, outputs: 128, max stack: 1):\n  0000: PUSH1 0x01\n  0002: STOP\ncontainers: 0\ndata: 1 bytes\n"
+        );
+    }
+}
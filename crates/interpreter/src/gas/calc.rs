@@ -1,7 +1,7 @@
 use super::constants::*;
 use crate::{
     num_words,
-    primitives::{Address, Bytes, SpecId, U256},
+    primitives::{Address, Bytes, SpecId, TxEnv, U256},
     SelfDestructResult,
 };
 use std::vec::Vec;
@@ -360,7 +360,67 @@ pub fn validate_initial_tx_gas(
     access_list: &[(Address, Vec<U256>)],
     initcodes: &[Bytes],
 ) -> u64 {
-    let mut initial_gas = 0;
+    initial_tx_gas_breakdown(spec_id, input, is_create, access_list, initcodes).total()
+}
+
+/// Intrinsic gas of a transaction split by its parts, see [calc_intrinsic_gas].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntrinsicGasBreakdown {
+    /// Base cost of the transaction, higher for contract creation since Homestead.
+    pub base: u64,
+    /// Number of zero bytes of the input and EOF initcodes.
+    pub zero_data_bytes: u64,
+    /// Number of non-zero bytes of the input and EOF initcodes.
+    pub non_zero_data_bytes: u64,
+    /// Gas of the input and EOF initcodes bytes.
+    pub data: u64,
+    /// Gas of the access list addresses and storage keys.
+    pub access_list: u64,
+    /// Number of words of the initcode metered by EIP-3860, and of the hashed EOF initcodes.
+    pub initcode_words: u64,
+    /// Gas of the initcode words.
+    pub initcode: u64,
+    /// Number of blobs.
+    pub blob_count: u64,
+    /// Gas of the blobs. It is paid with the blob gas price, so it is not part of
+    /// [IntrinsicGasBreakdown::total].
+    pub blob_gas: u64,
+}
+
+impl IntrinsicGasBreakdown {
+    /// Returns the intrinsic gas, the gas limit of the transaction needs to cover it.
+    #[inline]
+    pub const fn total(&self) -> u64 {
+        self.base + self.data + self.access_list + self.initcode
+    }
+}
+
+/// Calculates the intrinsic gas of the transaction at the spec.
+///
+/// This is the gas that [validate_initial_tx_gas] charges, so transactions with lower gas
+/// limit can be rejected without executing them.
+pub fn calc_intrinsic_gas(tx: &TxEnv, spec_id: SpecId) -> IntrinsicGasBreakdown {
+    let mut breakdown = initial_tx_gas_breakdown(
+        spec_id,
+        &tx.data,
+        tx.transact_to.is_create(),
+        &tx.access_list,
+        &tx.eof_initcodes,
+    );
+    breakdown.blob_count = tx.blob_hashes.len() as u64;
+    breakdown.blob_gas = tx.get_total_blob_gas();
+    breakdown
+}
+
+fn initial_tx_gas_breakdown(
+    spec_id: SpecId,
+    input: &[u8],
+    is_create: bool,
+    access_list: &[(Address, Vec<U256>)],
+    initcodes: &[Bytes],
+) -> IntrinsicGasBreakdown {
+    let mut breakdown = IntrinsicGasBreakdown::default();
     let mut zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
     let mut non_zero_data_len = input.len() as u64 - zero_data_len;
 
@@ -369,12 +429,19 @@ pub fn validate_initial_tx_gas(
         let zeros = initcode.iter().filter(|v| **v == 0).count() as u64;
         zero_data_len += zeros;
         non_zero_data_len += initcode.len() as u64 - zeros;
+        // initcodes are hashed so `TXCREATE` can find them by hash. Size of the initcode
+        // is checked in `validate_env` handler so this can't overflow.
+        let words = num_words(initcode.len() as u64);
+        breakdown.initcode_words += words;
+        breakdown.initcode += words * KECCAK256WORD;
     }
+    breakdown.zero_data_bytes = zero_data_len;
+    breakdown.non_zero_data_bytes = non_zero_data_len;
 
     // initdate stipend
-    initial_gas += zero_data_len * TRANSACTION_ZERO_DATA;
+    breakdown.data = zero_data_len * TRANSACTION_ZERO_DATA;
     // EIP-2028: Transaction data gas cost reduction
-    initial_gas += non_zero_data_len
+    breakdown.data += non_zero_data_len
         * if spec_id.is_enabled_in(SpecId::ISTANBUL) {
             16
         } else {
@@ -386,12 +453,12 @@ pub fn validate_initial_tx_gas(
         let accessed_slots = access_list
             .iter()
             .fold(0, |slot_count, (_, slots)| slot_count + slots.len() as u64);
-        initial_gas += access_list.len() as u64 * ACCESS_LIST_ADDRESS;
-        initial_gas += accessed_slots * ACCESS_LIST_STORAGE_KEY;
+        breakdown.access_list = access_list.len() as u64 * ACCESS_LIST_ADDRESS
+            + accessed_slots * ACCESS_LIST_STORAGE_KEY;
     }
 
     // base stipend
-    initial_gas += if is_create {
+    breakdown.base = if is_create {
         if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
             // EIP-2: Homestead Hard-fork Changes
             53000
@@ -405,8 +472,9 @@ pub fn validate_initial_tx_gas(
     // EIP-3860: Limit and meter initcode
     // Initcode stipend for bytecode analysis
     if spec_id.is_enabled_in(SpecId::SHANGHAI) && is_create {
-        initial_gas += initcode_cost(input.len() as u64)
+        breakdown.initcode_words += num_words(input.len() as u64);
+        breakdown.initcode += initcode_cost(input.len() as u64);
     }
 
-    initial_gas
+    breakdown
 }
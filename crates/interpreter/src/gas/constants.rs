@@ -39,6 +39,11 @@ pub const TRANSACTION_NON_ZERO_DATA_FRONTIER: u64 = 68;
 
 pub const EOF_CREATE_GAS: u64 = 32000;
 
+/// EIP-7069: Minimum gas retained by the caller of `EXTCALL`, `EXTDELEGATECALL` and `EXTSTATICCALL`.
+pub const MIN_RETAINED_GAS: u64 = 5000;
+/// EIP-7069: Minimum gas available to the callee, the call fails without executing otherwise.
+pub const MIN_CALLEE_GAS: u64 = 2300;
+
 // berlin eip2929 constants
 pub const ACCESS_LIST_ADDRESS: u64 = 2400;
 pub const ACCESS_LIST_STORAGE_KEY: u64 = 1900;
@@ -46,6 +51,10 @@ pub const COLD_SLOAD_COST: u64 = 2100;
 pub const COLD_ACCOUNT_ACCESS_COST: u64 = 2600;
 pub const WARM_STORAGE_READ_COST: u64 = 100;
 pub const WARM_SSTORE_RESET: u64 = SSTORE_RESET - COLD_SLOAD_COST;
+/// Gas of a cold account access on top of a warm one.
+pub const COLD_ACCOUNT_ACCESS_PENALTY: u64 = COLD_ACCOUNT_ACCESS_COST - WARM_STORAGE_READ_COST;
+/// Gas of a cold `SLOAD` on top of a warm one.
+pub const COLD_SLOAD_PENALTY: u64 = COLD_SLOAD_COST - WARM_STORAGE_READ_COST;
 
 /// EIP-3860 : Limit and meter initcode
 pub const INITCODE_WORD_COST: u64 = 2;
@@ -1,15 +1,33 @@
-use crate::primitives::{Address, Bytecode, Env, Log, B256, U256};
+use crate::primitives::{Address, Bytecode, Bytes, Env, Log, B256, U256};
 
 mod dummy;
+mod read_only;
 pub use dummy::DummyHost;
+pub use read_only::ReadOnlyHost;
 
 /// EVM context host.
-pub trait Host {
+///
+/// Host is split into [HostRead], that is enough to execute instructions that do not
+/// change the state, and [HostWrite]. Hosts that support only view execution can
+/// implement [HostRead] and be wrapped in [ReadOnlyHost].
+///
+/// Host is implemented for every type that implements both halves.
+pub trait Host: HostRead + HostWrite {}
+
+impl<T: HostRead + HostWrite + ?Sized> Host for T {}
+
+/// Read half of the [Host]. Gives access to the environment and reads the state.
+///
+/// Methods take `&mut self` as the host can load accounts and mark them as warm.
+pub trait HostRead {
     /// Returns a reference to the environment.
     fn env(&self) -> &Env;
 
-    /// Returns a mutable reference to the environment.
-    fn env_mut(&mut self) -> &mut Env;
+    /// Returns the chain specific context value of `key`, see [Env::aux_env].
+    #[inline]
+    fn aux_env(&self, key: &B256) -> Option<&Bytes> {
+        self.env().aux_env.get(key)
+    }
 
     /// Load an account.
     ///
@@ -28,17 +46,53 @@ pub trait Host {
     /// Get code hash of `address` and if the account is cold.
     fn code_hash(&mut self, address: Address) -> Option<(B256, bool)>;
 
+    /// Get code by its hash.
+    fn code_by_hash(&mut self, code_hash: B256) -> Option<Bytecode>;
+
+    /// Copy code of `address`, starting at `code_offset`, into `dest`. Bytes past the end
+    /// of the code are set to zero.
+    ///
+    /// Unlike [HostRead::code], the code does not need to be loaded into the state, so only
+    /// the copied range of a large contract is read. The account access is not charged,
+    /// the account is expected to be loaded with [HostRead::load_account] before.
+    fn copy_code(&mut self, address: Address, code_offset: usize, dest: &mut [u8]) -> Option<()>;
+
     /// Get storage value of `address` at `index` and if the account is cold.
     fn sload(&mut self, address: Address, index: U256) -> Option<(U256, bool)>;
 
+    /// Get the transient storage value of `address` at `index`.
+    fn tload(&mut self, address: Address, index: U256) -> U256;
+
+    /// Called after an instruction charged a cold account or storage access, with the
+    /// `gas` it paid on top of a warm access (EIP-2929).
+    ///
+    /// Accesses of instructions that fail before their gas is charged are not reported.
+    #[inline]
+    fn cold_access_charged(&mut self, gas: u64) {
+        let _ = gas;
+    }
+}
+
+/// Write half of the [Host]. Modifies the environment and the state.
+pub trait HostWrite {
+    /// Returns a mutable reference to the environment.
+    fn env_mut(&mut self) -> &mut Env;
+
+    /// Returns true if the host rejects all writes, see [ReadOnlyHost].
+    ///
+    /// Instructions that change the state halt with
+    /// [StateChangeDuringStaticCall](crate::InstructionResult::StateChangeDuringStaticCall)
+    /// before the host is called, as they do in a static call.
+    #[inline]
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
     /// Set storage value of account address at index.
     ///
     /// Returns (original, present, new, is_cold).
     fn sstore(&mut self, address: Address, index: U256, value: U256) -> Option<SStoreResult>;
 
-    /// Get the transient storage value of `address` at `index`.
-    fn tload(&mut self, address: Address, index: U256) -> U256;
-
     /// Set the transient storage value of `address` at `index`.
     fn tstore(&mut self, address: Address, index: U256, value: U256);
 
@@ -80,6 +134,10 @@ pub struct SelfDestructResult {
     pub target_exists: bool,
     pub is_cold: bool,
     pub previously_destroyed: bool,
+    /// True if the account was marked as destroyed and its code and storage are removed,
+    /// false if only the balance was transferred because of EIP-6780.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub destroyed: bool,
 }
 
 #[cfg(test)]
@@ -88,9 +146,13 @@ mod tests {
 
     fn assert_host<H: Host + ?Sized>() {}
 
+    fn assert_host_read<H: HostRead + ?Sized>() {}
+
     #[test]
     fn object_safety() {
         assert_host::<DummyHost>();
         assert_host::<dyn Host>();
+        assert_host::<ReadOnlyHost<DummyHost>>();
+        assert_host_read::<dyn HostRead>();
     }
 }
@@ -1,13 +1,13 @@
 use crate::primitives::{hash_map::Entry, Bytecode, HashMap, U256};
 use crate::{
     primitives::{Address, Env, Log, B256, KECCAK_EMPTY},
-    Host, SStoreResult, SelfDestructResult,
+    HostRead, HostWrite, SStoreResult, SelfDestructResult,
 };
 use std::vec::Vec;
 
 use super::LoadAccountResult;
 
-/// A dummy [Host] implementation.
+/// A dummy [Host](crate::Host) implementation.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct DummyHost {
     pub env: Env,
@@ -34,17 +34,12 @@ impl DummyHost {
     }
 }
 
-impl Host for DummyHost {
+impl HostRead for DummyHost {
     #[inline]
     fn env(&self) -> &Env {
         &self.env
     }
 
-    #[inline]
-    fn env_mut(&mut self) -> &mut Env {
-        &mut self.env
-    }
-
     #[inline]
     fn load_account(&mut self, _address: Address) -> Option<LoadAccountResult> {
         Some(LoadAccountResult::default())
@@ -70,6 +65,17 @@ impl Host for DummyHost {
         Some((KECCAK_EMPTY, false))
     }
 
+    #[inline]
+    fn code_by_hash(&mut self, _code_hash: B256) -> Option<Bytecode> {
+        Some(Bytecode::default())
+    }
+
+    #[inline]
+    fn copy_code(&mut self, _address: Address, _code_offset: usize, dest: &mut [u8]) -> Option<()> {
+        dest.fill(0);
+        Some(())
+    }
+
     #[inline]
     fn sload(&mut self, __address: Address, index: U256) -> Option<(U256, bool)> {
         match self.storage.entry(index) {
@@ -81,6 +87,21 @@ impl Host for DummyHost {
         }
     }
 
+    #[inline]
+    fn tload(&mut self, _address: Address, index: U256) -> U256 {
+        self.transient_storage
+            .get(&index)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl HostWrite for DummyHost {
+    #[inline]
+    fn env_mut(&mut self) -> &mut Env {
+        &mut self.env
+    }
+
     #[inline]
     fn sstore(&mut self, _address: Address, index: U256, value: U256) -> Option<SStoreResult> {
         let (present, is_cold) = match self.storage.entry(index) {
@@ -99,14 +120,6 @@ impl Host for DummyHost {
         })
     }
 
-    #[inline]
-    fn tload(&mut self, _address: Address, index: U256) -> U256 {
-        self.transient_storage
-            .get(&index)
-            .copied()
-            .unwrap_or_default()
-    }
-
     #[inline]
     fn tstore(&mut self, _address: Address, index: U256, value: U256) {
         self.transient_storage.insert(index, value);
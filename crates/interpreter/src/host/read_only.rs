@@ -0,0 +1,119 @@
+use crate::{
+    primitives::{Address, Bytecode, Env, Log, B256, U256},
+    HostRead, HostWrite, LoadAccountResult, SStoreResult, SelfDestructResult,
+};
+
+/// [Host](crate::Host) that only needs the [HostRead] half, used for view-only execution.
+///
+/// The host is [read only](HostWrite::is_read_only), so instructions that change the state
+/// halt with [StateChangeDuringStaticCall] before the host is called, even if the
+/// interpreter is not static. Write methods of the host are never called by instructions,
+/// they do not change the wrapped host.
+///
+/// [StateChangeDuringStaticCall]: crate::InstructionResult::StateChangeDuringStaticCall
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReadOnlyHost<H>(pub H);
+
+impl<H: HostRead> ReadOnlyHost<H> {
+    /// Wraps the read only host.
+    #[inline]
+    pub fn new(host: H) -> Self {
+        Self(host)
+    }
+
+    /// Returns the wrapped host.
+    #[inline]
+    pub fn into_inner(self) -> H {
+        self.0
+    }
+}
+
+impl<H: HostRead> HostRead for ReadOnlyHost<H> {
+    #[inline]
+    fn env(&self) -> &Env {
+        self.0.env()
+    }
+
+    #[inline]
+    fn load_account(&mut self, address: Address) -> Option<LoadAccountResult> {
+        self.0.load_account(address)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Option<B256> {
+        self.0.block_hash(number)
+    }
+
+    #[inline]
+    fn balance(&mut self, address: Address) -> Option<(U256, bool)> {
+        self.0.balance(address)
+    }
+
+    #[inline]
+    fn code(&mut self, address: Address) -> Option<(Bytecode, bool)> {
+        self.0.code(address)
+    }
+
+    #[inline]
+    fn code_hash(&mut self, address: Address) -> Option<(B256, bool)> {
+        self.0.code_hash(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Option<Bytecode> {
+        self.0.code_by_hash(code_hash)
+    }
+
+    #[inline]
+    fn copy_code(&mut self, address: Address, code_offset: usize, dest: &mut [u8]) -> Option<()> {
+        self.0.copy_code(address, code_offset, dest)
+    }
+
+    #[inline]
+    fn sload(&mut self, address: Address, index: U256) -> Option<(U256, bool)> {
+        self.0.sload(address, index)
+    }
+
+    #[inline]
+    fn tload(&mut self, address: Address, index: U256) -> U256 {
+        self.0.tload(address, index)
+    }
+
+    #[inline]
+    fn cold_access_charged(&mut self, gas: u64) {
+        self.0.cold_access_charged(gas)
+    }
+}
+
+impl<H: HostRead> HostWrite for ReadOnlyHost<H> {
+    /// Environment can not be modified through the read only host.
+    ///
+    /// # Panics
+    ///
+    /// Always panics, instructions never modify the environment.
+    #[inline]
+    fn env_mut(&mut self) -> &mut Env {
+        panic!("environment can not be modified through the read only host")
+    }
+
+    #[inline]
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn sstore(&mut self, _address: Address, _index: U256, _value: U256) -> Option<SStoreResult> {
+        None
+    }
+
+    #[inline]
+    fn tstore(&mut self, _address: Address, _index: U256, _value: U256) {}
+
+    #[inline]
+    fn log(&mut self, _log: Log) {}
+
+    #[inline]
+    fn selfdestruct(&mut self, _address: Address, _target: Address) -> Option<SelfDestructResult> {
+        None
+    }
+}
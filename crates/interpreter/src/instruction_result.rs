@@ -53,6 +53,12 @@ pub enum InstructionResult {
     EOFOpcodeDisabledInLegacy,
     /// EOF function stack overflow
     EOFFunctionStackOverflow,
+    /// `RETURNCONTRACT` data section of the deployed container is bigger than `0xFFFF`.
+    EofAuxDataOverflow,
+    /// `RETURNCONTRACT` data section of the deployed container is smaller than declared.
+    EofAuxDataTooSmall,
+    /// Execution was canceled with the cancellation token of the interpreter.
+    Canceled,
 }
 
 impl From<SuccessReason> for InstructionResult {
@@ -93,6 +99,8 @@ impl From<HaltReason> for InstructionResult {
             HaltReason::CallNotAllowedInsideStatic => Self::CallNotAllowedInsideStatic,
             HaltReason::OutOfFunds => Self::OutOfFunds,
             HaltReason::CallTooDeep => Self::CallTooDeep,
+            HaltReason::EofAuxDataOverflow => Self::EofAuxDataOverflow,
+            HaltReason::EofAuxDataTooSmall => Self::EofAuxDataTooSmall,
             #[cfg(feature = "optimism")]
             HaltReason::FailedDeposit => Self::FatalExternalError,
         }
@@ -145,6 +153,9 @@ macro_rules! return_error {
             | InstructionResult::ReturnContractInNotInitEOF
             | InstructionResult::EOFOpcodeDisabledInLegacy
             | InstructionResult::EOFFunctionStackOverflow
+            | InstructionResult::EofAuxDataOverflow
+            | InstructionResult::EofAuxDataTooSmall
+            | InstructionResult::Canceled
     };
 }
 
@@ -174,6 +185,8 @@ pub enum SuccessOrHalt {
     Revert,
     Halt(HaltReason),
     FatalExternalError,
+    /// Execution was canceled, see [InstructionResult::Canceled].
+    Canceled,
     /// Internal instruction that signals Interpreter should continue running.
     InternalContinue,
     /// Internal instruction that signals call or create.
@@ -269,6 +282,9 @@ impl From<InstructionResult> for SuccessOrHalt {
             InstructionResult::FatalExternalError => Self::FatalExternalError,
             InstructionResult::EOFOpcodeDisabledInLegacy => Self::Halt(HaltReason::OpcodeNotFound),
             InstructionResult::EOFFunctionStackOverflow => Self::FatalExternalError,
+            InstructionResult::EofAuxDataOverflow => Self::Halt(HaltReason::EofAuxDataOverflow),
+            InstructionResult::EofAuxDataTooSmall => Self::Halt(HaltReason::EofAuxDataTooSmall),
+            InstructionResult::Canceled => Self::Canceled,
             InstructionResult::ReturnContract => {
                 panic!("Unexpected EOF internal Return Contract")
             }
@@ -340,6 +356,9 @@ mod tests {
             InstructionResult::CreateContractStartingWithEF,
             InstructionResult::CreateInitCodeSizeLimit,
             InstructionResult::FatalExternalError,
+            InstructionResult::EofAuxDataOverflow,
+            InstructionResult::EofAuxDataTooSmall,
+            InstructionResult::Canceled,
         ];
 
         for result in error_results {
@@ -4,8 +4,8 @@ use crate::{
     primitives::{Spec, U256},
     Host, Interpreter,
 };
-use core::cmp::Ordering;
 use bcevm_primitives::uint;
+use core::cmp::Ordering;
 
 pub fn lt<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     gas!(interpreter, gas::VERYLOW);
@@ -1,18 +1,18 @@
 mod call_helpers;
 
+use bcevm_primitives::{eofcreate_address, BerlinSpec};
 pub use call_helpers::{
     calc_call_gas, get_memory_input_and_out_ranges, resize_memory_and_return_range,
 };
-use bcevm_primitives::{keccak256, BerlinSpec};
 
 use crate::{
     analysis::validate_eof,
     gas::{self, cost_per_word, BASE, EOF_CREATE_GAS, KECCAK256WORD},
-    instructions::utility::read_u16,
     interpreter::Interpreter,
     primitives::{Address, Bytes, Eof, Spec, SpecId::*, B256, U256},
-    CallInputs, CallScheme, CallValue, CreateInputs, CreateScheme, EOFCreateInput, Host,
-    InstructionResult, InterpreterAction, InterpreterResult, LoadAccountResult, MAX_INITCODE_SIZE,
+    CallInputs, CallScheme, CallValue, CreateInputs, CreateScheme, EOFCreateInput, EOFCreateKind,
+    Host, InstructionResult, InterpreterAction, InterpreterResult, LoadAccountResult,
+    MAX_INITCODE_SIZE,
 };
 use core::{cmp::max, ops::Range};
 use std::boxed::Box;
@@ -37,9 +37,18 @@ pub fn resize_memory(
     }
 }
 
+/// Returns the memory range as the input of the EOF create frame.
+fn eofcreate_input(interpreter: &Interpreter, range: &Range<usize>) -> Bytes {
+    if range.is_empty() {
+        return Bytes::new();
+    }
+    Bytes::copy_from_slice(interpreter.shared_memory.slice_range(range.clone()))
+}
+
 /// EOF Create instruction
-pub fn eofcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn eofcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     require_eof!(interpreter);
+    require_non_staticcall!(interpreter, host);
     gas!(interpreter, EOF_CREATE_GAS);
     let initcontainer_index = unsafe { *interpreter.instruction_pointer };
     pop!(interpreter, value, salt, data_offset, data_size);
@@ -71,28 +80,45 @@ pub fn eofcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H)
         cost_per_word(sub_container.len() as u64, KECCAK256WORD)
     );
 
-    let created_address = interpreter
-        .contract
-        .caller
-        .create2(salt.to_be_bytes(), keccak256(sub_container));
+    let created_address = eofcreate_address(
+        interpreter.contract.target_address,
+        salt.to_be_bytes().into(),
+        &sub_container,
+    );
+
+    // EIP-150: all but one 64th of the remaining gas is passed to the new frame.
+    let gas_limit = interpreter.gas().remaining();
+    let gas_limit = gas_limit - gas_limit / 64;
+    // spend the gas. Remaining gas is reimbursed after frame returns.
+    gas!(interpreter, gas_limit);
 
     // Send container for execution container is preverified.
+    let input = eofcreate_input(interpreter, &return_range);
     interpreter.next_action = InterpreterAction::EOFCreate {
-        inputs: Box::new(EOFCreateInput::new(
-            interpreter.contract.target_address,
-            created_address,
-            value,
-            eof,
-            interpreter.gas().remaining(),
-            return_range,
-        )),
+        inputs: Box::new(
+            EOFCreateInput::new(
+                interpreter.contract.target_address,
+                created_address,
+                value,
+                eof,
+                gas_limit,
+                return_range,
+            )
+            .with_input(input),
+        ),
     };
 
     interpreter.instruction_pointer = unsafe { interpreter.instruction_pointer.offset(1) };
+    interpreter.instruction_result = InstructionResult::CallOrCreate;
 }
 
+/// TXCREATE instruction, creates a contract from the EOF initcode of the transaction.
+///
+/// Initcode is found by its hash in [crate::primitives::TxEnv::eof_initcodes_hashed], hashing
+/// of the initcodes is paid in the intrinsic gas of the transaction.
 pub fn txcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     require_eof!(interpreter);
+    require_non_staticcall!(interpreter, host);
     gas!(interpreter, EOF_CREATE_GAS);
     pop!(
         interpreter,
@@ -124,12 +150,6 @@ pub fn txcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     // deduct gas for validation
     gas_or_fail!(interpreter, cost_per_word(initcode.len() as u64, BASE));
 
-    // deduct gas for hash. TODO check order of actions.
-    gas_or_fail!(
-        interpreter,
-        cost_per_word(initcode.len() as u64, KECCAK256WORD)
-    );
-
     let Ok(eof) = Eof::decode(initcode.clone()) else {
         push!(interpreter, U256::ZERO);
         return;
@@ -147,32 +167,42 @@ pub fn txcreate<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
         return;
     }
 
-    // Create new address. Gas for it is already deducted.
+    // Create new address of the executing contract, that is also the caller of the new
+    // frame. Hash of the initcode was paid in the intrinsic gas.
     let created_address = interpreter
         .contract
-        .caller
+        .target_address
         .create2(salt.to_be_bytes(), tx_initcode_hash);
 
+    // EIP-150: all but one 64th of the remaining gas is passed to the new frame.
     let gas_limit = interpreter.gas().remaining();
-    // spend all gas. It will be reimbursed after frame returns.
+    let gas_limit = gas_limit - gas_limit / 64;
+    // spend the gas. Remaining gas is reimbursed after frame returns.
     gas!(interpreter, gas_limit);
 
+    let input = eofcreate_input(interpreter, &return_range);
     interpreter.next_action = InterpreterAction::EOFCreate {
-        inputs: Box::new(EOFCreateInput::new(
-            interpreter.contract.target_address,
-            created_address,
-            value,
-            eof,
-            gas_limit,
-            return_range,
-        )),
+        inputs: Box::new(
+            EOFCreateInput::new(
+                interpreter.contract.target_address,
+                created_address,
+                value,
+                eof,
+                gas_limit,
+                return_range,
+            )
+            .with_input(input)
+            .with_kind(EOFCreateKind::Tx {
+                initcode_hash: tx_initcode_hash,
+            }),
+        ),
     };
     interpreter.instruction_result = InstructionResult::CallOrCreate;
 }
 
 pub fn return_contract<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     require_init_eof!(interpreter);
-    let deploy_container_index = unsafe { read_u16(interpreter.instruction_pointer) };
+    let deploy_container_index = unsafe { *interpreter.instruction_pointer };
     pop!(interpreter, aux_data_offset, aux_data_size);
     let aux_data_size = as_usize_or_fail!(interpreter, aux_data_size);
     // important: offset must be ignored if len is zeros
@@ -185,7 +215,7 @@ pub fn return_contract<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &
         .expect("EOF is checked");
 
     // convert to EOF so we can check data section size.
-    let new_eof = Eof::decode(container.clone()).expect("Container is verified");
+    let mut new_eof = Eof::decode(container.clone()).expect("Container is verified");
 
     let aux_slice = if aux_data_size != 0 {
         let aux_data_offset = as_usize_or_fail!(interpreter, aux_data_offset);
@@ -201,17 +231,19 @@ pub fn return_contract<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &
     let new_data_size = new_eof.body.data_section.len() + aux_slice.len();
     if new_data_size > 0xFFFF {
         // aux data is too big
-        interpreter.instruction_result = InstructionResult::FatalExternalError;
+        interpreter.instruction_result = InstructionResult::EofAuxDataOverflow;
         return;
     }
     if new_data_size < new_eof.header.data_size as usize {
         // aux data is too small
-        interpreter.instruction_result = InstructionResult::FatalExternalError;
+        interpreter.instruction_result = InstructionResult::EofAuxDataTooSmall;
         return;
     }
 
-    // append data bytes
-    let output = [new_eof.raw(), aux_slice].concat().into();
+    // append data bytes and update the data size in the header.
+    new_eof.body.data_section = [new_eof.data(), aux_slice].concat().into();
+    new_eof.header.data_size = new_data_size as u16;
+    let output = new_eof.encode_slow();
 
     let result = InstructionResult::ReturnContract;
     interpreter.instruction_result = result;
@@ -229,6 +261,9 @@ pub fn extcall_input(interpreter: &mut Interpreter) -> Option<Bytes> {
 
     let return_memory_offset =
         resize_memory_and_return_range(interpreter, input_offset, input_size)?;
+    if return_memory_offset.is_empty() {
+        return Some(Bytes::new());
+    }
 
     Some(Bytes::copy_from_slice(
         interpreter
@@ -248,11 +283,8 @@ pub fn extcall_gas_calc<H: Host + ?Sized>(
         return None;
     };
 
-    if load_result.is_cold {
-        gas!(interpreter, gas::COLD_ACCOUNT_ACCESS_COST, None);
-    }
-
-    // TODO(EOF) is_empty should only be checked on delegatecall
+    // Warm or cold account access, value transfer and, if value is transferred to an
+    // empty account, new account cost.
     let call_cost = gas::call_cost(
         BerlinSpec::SPEC_ID,
         transfers_value,
@@ -260,21 +292,24 @@ pub fn extcall_gas_calc<H: Host + ?Sized>(
         load_result.is_empty,
     );
     gas!(interpreter, call_cost, None);
+    if load_result.is_cold {
+        host.cold_access_charged(gas::COLD_ACCOUNT_ACCESS_PENALTY);
+    }
 
-    // 7. Calculate the gas available to callee as caller’s
-    // remaining gas reduced by max(ceil(gas/64), MIN_RETAINED_GAS) (MIN_RETAINED_GAS is 5000).
-    let gas_reduce = max(interpreter.gas.remaining() / 64, 5000);
+    // Calculate the gas available to callee as caller’s
+    // remaining gas reduced by max(ceil(gas/64), MIN_RETAINED_GAS).
+    let gas_reduce = max(interpreter.gas.remaining() / 64, gas::MIN_RETAINED_GAS);
     let gas_limit = interpreter.gas().remaining().saturating_sub(gas_reduce);
 
-    if gas_limit < 2300 {
-        interpreter.instruction_result = InstructionResult::CallNotAllowedInsideStatic;
-        // TODO(EOF) error;
-        // interpreter.instruction_result = InstructionResult::CallGasTooLow;
+    if gas_limit < gas::MIN_CALLEE_GAS {
+        // Light failure, the callee is not executed and the retained gas is not spent.
+        interpreter.return_data_buffer = Bytes::new();
+        if let Err(result) = interpreter.stack.push(U256::from(1)) {
+            interpreter.instruction_result = result;
+        }
         return None;
     }
 
-    // TODO check remaining gas more then N
-
     gas!(interpreter, gas_limit, None);
     Some(gas_limit)
 }
@@ -290,11 +325,14 @@ pub fn extcall<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host
 
     pop!(interpreter, value);
     let has_transfer = value != U256::ZERO;
+    if (interpreter.is_static || host.is_read_only()) && has_transfer {
+        interpreter.instruction_result = InstructionResult::CallNotAllowedInsideStatic;
+        return;
+    }
 
     let Some(gas_limit) = extcall_gas_calc(interpreter, host, target_address, has_transfer) else {
         return;
     };
-    // TODO Check if static and value 0
 
     // Call host to interact with target contract
     interpreter.next_action = InterpreterAction::Call {
@@ -326,7 +364,6 @@ pub fn extdcall<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, hos
     let Some(gas_limit) = extcall_gas_calc(interpreter, host, target_address, false) else {
         return;
     };
-    // TODO Check if static and value 0
 
     // Call host to interact with target contract
     interpreter.next_action = InterpreterAction::Call {
@@ -382,7 +419,7 @@ pub fn create<const IS_CREATE2: bool, H: Host + ?Sized, SPEC: Spec>(
     interpreter: &mut Interpreter,
     host: &mut H,
 ) {
-    require_non_staticcall!(interpreter);
+    require_non_staticcall!(interpreter, host);
 
     // EIP-1014: Skinny CREATE2
     if IS_CREATE2 {
@@ -456,7 +493,7 @@ pub fn call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &
 
     pop!(interpreter, value);
     let has_transfer = value != U256::ZERO;
-    if interpreter.is_static && has_transfer {
+    if (interpreter.is_static || host.is_read_only()) && has_transfer {
         interpreter.instruction_result = InstructionResult::CallNotAllowedInsideStatic;
         return;
     }
@@ -478,6 +515,7 @@ pub fn call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &
     ) else {
         return;
     };
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_PENALTY);
 
     gas!(interpreter, gas_limit);
 
@@ -529,6 +567,7 @@ pub fn call_code<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
     ) else {
         return;
     };
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_PENALTY);
 
     gas!(interpreter, gas_limit);
 
@@ -575,6 +614,7 @@ pub fn delegate_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter
     else {
         return;
     };
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_PENALTY);
 
     gas!(interpreter, gas_limit);
 
@@ -617,6 +657,7 @@ pub fn static_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     else {
         return;
     };
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_PENALTY);
     gas!(interpreter, gas_limit);
 
     // Call host to interact with target contract
@@ -144,7 +144,7 @@ fn return_inner(interpreter: &mut Interpreter, instruction_result: InstructionRe
         let offset = as_usize_or_fail!(interpreter, offset);
         resize_memory!(interpreter, offset, len);
 
-        output = interpreter.shared_memory.slice(offset, len).to_vec().into()
+        output = interpreter.shared_memory.slice_to_bytes(offset, len)
     }
     interpreter.instruction_result = instruction_result;
     interpreter.next_action = crate::InterpreterAction::Return {
@@ -181,6 +181,13 @@ pub fn unknown<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
     interpreter.instruction_result = InstructionResult::OpcodeNotFound;
 }
 
+/// Disabled opcode. This opcode halts the execution, see [InstructionTables::disable_opcode].
+///
+/// [InstructionTables::disable_opcode]: crate::opcode::InstructionTables::disable_opcode
+pub fn not_activated<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+    interpreter.instruction_result = InstructionResult::NotActivated;
+}
+
 #[cfg(test)]
 mod test {
     use bcevm_primitives::{bytes, Bytecode, Eof, PragueSpec};
@@ -2,13 +2,12 @@ use crate::{
     gas::{self, warm_cold_cost},
     interpreter::Interpreter,
     primitives::{Bytes, Log, LogData, Spec, SpecId::*, B256, U256},
-    Host, InstructionResult, SStoreResult,
+    Host, HostRead, InstructionResult, LoadAccountResult, SStoreResult,
 };
-use core::cmp::min;
 use bcevm_primitives::{BLOCKHASH_SERVE_WINDOW, BLOCKHASH_STORAGE_ADDRESS, BLOCK_HASH_HISTORY};
 use std::vec::Vec;
 
-pub fn balance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn balance<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_address!(interpreter, address);
     let Some((balance, is_cold)) = host.balance(address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
@@ -27,11 +26,12 @@ pub fn balance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host
             20
         }
     );
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_PENALTY);
     push!(interpreter, balance);
 }
 
 /// EIP-1884: Repricing for trie-size-dependent opcodes
-pub fn selfbalance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn selfbalance<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, ISTANBUL);
     gas!(interpreter, gas::LOW);
     let Some((balance, _)) = host.balance(interpreter.contract.target_address) else {
@@ -41,7 +41,7 @@ pub fn selfbalance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     push!(interpreter, balance);
 }
 
-pub fn extcodesize<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn extcodesize<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_address!(interpreter, address);
     let Some((code, is_cold)) = host.code(address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
@@ -54,12 +54,13 @@ pub fn extcodesize<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     } else {
         gas!(interpreter, 20);
     }
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_PENALTY);
 
     push!(interpreter, U256::from(code.len()));
 }
 
 /// EIP-1052: EXTCODEHASH opcode
-pub fn extcodehash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn extcodehash<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CONSTANTINOPLE);
     pop_address!(interpreter, address);
     let Some((code_hash, is_cold)) = host.code_hash(address) else {
@@ -73,14 +74,16 @@ pub fn extcodehash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     } else {
         gas!(interpreter, 400);
     }
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_PENALTY);
     push_b256!(interpreter, code_hash);
 }
 
-pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn extcodecopy<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_address!(interpreter, address);
     pop!(interpreter, memory_offset, code_offset, len_u256);
 
-    let Some((code, is_cold)) = host.code(address) else {
+    // code is copied after the gas is charged, without loading it into the state.
+    let Some(LoadAccountResult { is_cold, .. }) = host.load_account(address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
@@ -90,20 +93,22 @@ pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         interpreter,
         gas::extcodecopy_cost(SPEC::SPEC_ID, len as u64, is_cold)
     );
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_PENALTY);
     if len == 0 {
         return;
     }
     let memory_offset = as_usize_or_fail!(interpreter, memory_offset);
-    let code_offset = min(as_usize_saturated!(code_offset), code.len());
+    let code_offset = as_usize_saturated!(code_offset);
     resize_memory!(interpreter, memory_offset, len);
 
     // Note: this can't panic because we resized memory to fit.
-    interpreter
-        .shared_memory
-        .set_data(memory_offset, code_offset, len, &code.original_bytes());
+    let dest = interpreter.shared_memory.slice_mut(memory_offset, len);
+    if host.copy_code(address, code_offset, dest).is_none() {
+        interpreter.instruction_result = InstructionResult::FatalExternalError;
+    }
 }
 
-pub fn blockhash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn blockhash<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     gas!(interpreter, gas::BLOCKHASH);
     pop_top!(interpreter, number);
 
@@ -115,6 +120,12 @@ pub fn blockhash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
 
             // blockhash should push zero if number is same as current block number.
             if SPEC::enabled(PRAGUE) && diff <= BLOCKHASH_SERVE_WINDOW {
+                // history contract is written at the start of the block, it is not loaded
+                // by the transaction.
+                if host.load_account(BLOCKHASH_STORAGE_ADDRESS).is_none() {
+                    interpreter.instruction_result = InstructionResult::FatalExternalError;
+                    return;
+                }
                 let value = sload!(
                     interpreter,
                     host,
@@ -141,7 +152,7 @@ pub fn blockhash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
     *number = U256::ZERO;
 }
 
-pub fn sload<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn sload<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_top!(interpreter, index);
     let value = sload!(
         interpreter,
@@ -153,7 +164,7 @@ pub fn sload<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
 }
 
 pub fn sstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
-    require_non_staticcall!(interpreter);
+    require_non_staticcall!(interpreter, host);
 
     pop!(interpreter, index, value);
     let Some(SStoreResult {
@@ -170,6 +181,7 @@ pub fn sstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
         let remaining_gas = interpreter.gas.remaining();
         gas::sstore_cost(SPEC::SPEC_ID, original, old, new, remaining_gas, is_cold)
     });
+    cold_access_charged!(host, is_cold, gas::COLD_SLOAD_COST);
     refund!(
         interpreter,
         gas::sstore_refund(SPEC::SPEC_ID, original, old, new)
@@ -180,7 +192,7 @@ pub fn sstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
 /// Store value to transient storage
 pub fn tstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CANCUN);
-    require_non_staticcall!(interpreter);
+    require_non_staticcall!(interpreter, host);
     gas!(interpreter, gas::WARM_STORAGE_READ_COST);
 
     pop!(interpreter, index, value);
@@ -190,7 +202,7 @@ pub fn tstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
 
 /// EIP-1153: Transient storage opcodes
 /// Load value from transient storage
-pub fn tload<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
+pub fn tload<H: HostRead + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CANCUN);
     gas!(interpreter, gas::WARM_STORAGE_READ_COST);
 
@@ -200,7 +212,7 @@ pub fn tload<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
 }
 
 pub fn log<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
-    require_non_staticcall!(interpreter);
+    require_non_staticcall!(interpreter, host);
 
     pop!(interpreter, offset, len);
     let len = as_usize_or_fail!(interpreter, len);
@@ -210,7 +222,7 @@ pub fn log<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, host
     } else {
         let offset = as_usize_or_fail!(interpreter, offset);
         resize_memory!(interpreter, offset, len);
-        Bytes::copy_from_slice(interpreter.shared_memory.slice(offset, len))
+        interpreter.shared_memory.slice_to_bytes(offset, len)
     };
 
     if interpreter.stack.len() < N {
@@ -233,7 +245,7 @@ pub fn log<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, host
 }
 
 pub fn selfdestruct<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
-    require_non_staticcall!(interpreter);
+    require_non_staticcall!(interpreter, host);
     pop_address!(interpreter, target);
 
     let Some(res) = host.selfdestruct(interpreter.contract.target_address, target) else {
@@ -245,7 +257,9 @@ pub fn selfdestruct<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     if !SPEC::enabled(LONDON) && !res.previously_destroyed {
         refund!(interpreter, gas::SELFDESTRUCT)
     }
+    let is_cold = res.is_cold;
     gas!(interpreter, gas::selfdestruct_cost(SPEC::SPEC_ID, res));
+    cold_access_charged!(host, is_cold, gas::COLD_ACCOUNT_ACCESS_COST);
 
     interpreter.instruction_result = InstructionResult::SelfDestruct;
 }
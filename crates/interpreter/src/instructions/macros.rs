@@ -9,6 +9,12 @@ macro_rules! require_non_staticcall {
             return;
         }
     };
+    ($interp:expr, $host:expr) => {
+        if $interp.is_static || $host.is_read_only() {
+            $interp.instruction_result = $crate::InstructionResult::StateChangeDuringStaticCall;
+            return;
+        }
+    };
 }
 
 /// Error if the current call is executing EOF.
@@ -65,10 +71,22 @@ macro_rules! sload {
             return;
         };
         $crate::gas!($interp, $crate::gas::sload_cost(SPEC::SPEC_ID, is_cold));
+        $crate::cold_access_charged!($host, is_cold, $crate::gas::COLD_SLOAD_PENALTY);
         value
     }};
 }
 
+/// Reports the `gas` paid for a cold access to the host, if the access was cold and
+/// accesses are priced warm or cold (EIP-2929). Needs to follow the charge of the gas.
+#[macro_export]
+macro_rules! cold_access_charged {
+    ($host:expr, $is_cold:expr, $gas:expr) => {
+        if $is_cold && SPEC::enabled($crate::primitives::SpecId::BERLIN) {
+            $host.cold_access_charged($gas);
+        }
+    };
+}
+
 /// Records a `gas` cost and fails the instruction if it would exceed the available gas.
 #[macro_export]
 macro_rules! gas {
@@ -115,7 +133,6 @@ macro_rules! resize_memory {
     ($interp:expr, $offset:expr, $len:expr, $ret:expr) => {
         let new_size = $offset.saturating_add($len);
         if new_size > $interp.shared_memory.len() {
-            #[cfg(feature = "memory_limit")]
             if $interp.shared_memory.limit_reached(new_size) {
                 $interp.instruction_result = $crate::InstructionResult::MemoryLimitOOG;
                 return $ret;
@@ -1,11 +1,15 @@
 pub mod analysis;
+mod bytes_arena;
 mod contract;
 #[cfg(feature = "serde")]
 pub mod serde;
+mod session;
 mod shared_memory;
 mod stack;
 
+pub use bytes_arena::BytesArena;
 pub use contract::Contract;
+pub use session::InterpreterSession;
 pub use shared_memory::{num_words, SharedMemory, EMPTY_SHARED_MEMORY};
 pub use stack::{Stack, STACK_LIMIT};
 
@@ -14,8 +18,8 @@ use crate::{
     gas, primitives::Bytes, push, push_b256, return_ok, return_revert, CallOutcome, CreateOutcome,
     FunctionStack, Gas, Host, InstructionResult, InterpreterAction,
 };
+use bcevm_primitives::{Bytecode, CancellationToken, Eof, Spec, U256};
 use core::cmp::min;
-use bcevm_primitives::{Bytecode, Eof, U256};
 use std::borrow::ToOwned;
 
 /// EVM bytecode interpreter.
@@ -60,8 +64,14 @@ pub struct Interpreter {
     /// Set inside CALL or CREATE instructions and RETURN or REVERT instructions. Additionally those instructions will set
     /// InstructionResult to CallOrCreate/Return/Revert so we know the reason.
     pub next_action: InterpreterAction,
+    /// Token checked every [CANCELLATION_CHECK_INTERVAL] instructions, execution stops with
+    /// [InstructionResult::Canceled] once it is canceled.
+    pub cancellation: Option<CancellationToken>,
 }
 
+/// Number of instructions executed between two checks of [Interpreter::cancellation].
+pub const CANCELLATION_CHECK_INTERVAL: usize = 4096;
+
 impl Default for Interpreter {
     fn default() -> Self {
         Self::new(Contract::default(), 0, false)
@@ -102,6 +112,7 @@ impl Interpreter {
             shared_memory: EMPTY_SHARED_MEMORY,
             stack: Stack::new(),
             next_action: InterpreterAction::None,
+            cancellation: None,
         }
     }
 
@@ -163,6 +174,8 @@ impl Interpreter {
     /// - `Ok`: Pushes the address from `create_outcome` to the stack, updates gas costs, and records any gas refunds.
     /// - `Revert`: Pushes `U256::ZERO` to the stack and updates gas costs.
     /// - `FatalExternalError`: Sets the `instruction_result` to `InstructionResult::FatalExternalError`.
+    /// - `Canceled`: Sets the `instruction_result` to `InstructionResult::Canceled`, so the
+    ///   execution of the parent stops as well.
     /// - `Default`: Pushes `U256::ZERO` to the stack.
     ///
     /// # Side Effects
@@ -197,6 +210,9 @@ impl Interpreter {
             InstructionResult::FatalExternalError => {
                 panic!("Fatal external error in insert_create_outcome");
             }
+            InstructionResult::Canceled => {
+                self.instruction_result = InstructionResult::Canceled;
+            }
             _ => {
                 push!(self, U256::ZERO);
             }
@@ -204,6 +220,8 @@ impl Interpreter {
     }
 
     pub fn insert_eofcreate_outcome(&mut self, create_outcome: EOFCreateOutcome) {
+        self.instruction_result = InstructionResult::Continue;
+
         let instruction_result = create_outcome.instruction_result();
 
         self.return_data_buffer = if *instruction_result == InstructionResult::Revert {
@@ -227,6 +245,9 @@ impl Interpreter {
             InstructionResult::FatalExternalError => {
                 panic!("Fatal external error in insert_eofcreate_outcome");
             }
+            InstructionResult::Canceled => {
+                self.instruction_result = InstructionResult::Canceled;
+            }
             _ => {
                 push!(self, U256::ZERO);
             }
@@ -254,7 +275,12 @@ impl Interpreter {
     /// - `return_ok!()`: Processes successful execution, refunds gas, and updates shared memory.
     /// - `return_revert!()`: Handles a revert by only updating the gas usage and shared memory.
     /// - `InstructionResult::FatalExternalError`: Sets the instruction result to a fatal external error.
+    /// - `InstructionResult::Canceled`: Sets the instruction result to canceled, so the execution of
+    ///   the parent stops as well.
     /// - Any other result: No specific action is taken.
+    ///
+    /// Legacy call pushes `1` on success and `0` otherwise. EOF `EXT*CALL` pushes `0` on
+    /// success, `1` on revert and `2` on failure, as defined in EIP-7069.
     pub fn insert_call_outcome(
         &mut self,
         shared_memory: &mut SharedMemory,
@@ -275,18 +301,42 @@ impl Interpreter {
                 self.gas.erase_cost(remaining);
                 self.gas.record_refund(refunded);
                 shared_memory.set(out_offset, &self.return_data_buffer[..target_len]);
-                push!(self, U256::from(1));
+                push!(
+                    self,
+                    if self.is_eof {
+                        U256::ZERO
+                    } else {
+                        U256::from(1)
+                    }
+                );
             }
             return_revert!() => {
                 self.gas.erase_cost(call_outcome.gas().remaining());
                 shared_memory.set(out_offset, &self.return_data_buffer[..target_len]);
-                push!(self, U256::ZERO);
+                push!(
+                    self,
+                    if self.is_eof {
+                        U256::from(1)
+                    } else {
+                        U256::ZERO
+                    }
+                );
             }
             InstructionResult::FatalExternalError => {
                 panic!("Fatal external error in insert_call_outcome");
             }
+            InstructionResult::Canceled => {
+                self.instruction_result = InstructionResult::Canceled;
+            }
             _ => {
-                push!(self, U256::ZERO);
+                push!(
+                    self,
+                    if self.is_eof {
+                        U256::from(2)
+                    } else {
+                        U256::ZERO
+                    }
+                );
             }
         }
     }
@@ -361,10 +411,75 @@ impl Interpreter {
         self.next_action = InterpreterAction::None;
         self.shared_memory = shared_memory;
         // main loop
-        while self.instruction_result == InstructionResult::Continue {
-            self.step(instruction_table, host);
+        if let Some(token) = self.cancellation.clone() {
+            while self.instruction_result == InstructionResult::Continue {
+                for _ in 0..CANCELLATION_CHECK_INTERVAL {
+                    self.step(instruction_table, host);
+                    if self.instruction_result != InstructionResult::Continue {
+                        break;
+                    }
+                }
+                self.check_cancellation(&token);
+            }
+        } else {
+            while self.instruction_result == InstructionResult::Continue {
+                self.step(instruction_table, host);
+            }
+        }
+        self.take_next_action()
+    }
+
+    /// Executes the interpreter with the instructions of the spec until it returns or stops.
+    ///
+    /// Opcodes are dispatched with a `match` over the instructions instead of a call through
+    /// the instruction table, see [crate::opcode::dispatch]. This allows the compiler to inline
+    /// simple instructions into the loop.
+    pub fn run_specialized<SPEC: Spec, H: Host + ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        host: &mut H,
+    ) -> InterpreterAction {
+        self.next_action = InterpreterAction::None;
+        self.shared_memory = shared_memory;
+        // main loop
+        if let Some(token) = self.cancellation.clone() {
+            while self.instruction_result == InstructionResult::Continue {
+                for _ in 0..CANCELLATION_CHECK_INTERVAL {
+                    self.step_specialized::<SPEC, H>(host);
+                    if self.instruction_result != InstructionResult::Continue {
+                        break;
+                    }
+                }
+                self.check_cancellation(&token);
+            }
+        } else {
+            while self.instruction_result == InstructionResult::Continue {
+                self.step_specialized::<SPEC, H>(host);
+            }
+        }
+        self.take_next_action()
+    }
+
+    /// Executes the instruction at the instruction pointer, see [Self::run_specialized].
+    #[inline(always)]
+    fn step_specialized<SPEC: Spec, H: Host + ?Sized>(&mut self, host: &mut H) {
+        // Get current opcode.
+        let opcode = unsafe { *self.instruction_pointer };
+        // SAFETY: See [Self::step].
+        self.instruction_pointer = unsafe { self.instruction_pointer.offset(1) };
+        crate::opcode::dispatch::<H, SPEC>(opcode, self, host);
+    }
+
+    /// Halts the execution if the token was canceled.
+    #[inline]
+    fn check_cancellation(&mut self, token: &CancellationToken) {
+        if token.is_canceled() && self.instruction_result == InstructionResult::Continue {
+            self.instruction_result = InstructionResult::Canceled;
         }
+    }
 
+    /// Returns the action after the main loop stopped.
+    fn take_next_action(&mut self) -> InterpreterAction {
         // Return next action if it is some.
         if self.next_action.is_some() {
             return core::mem::take(&mut self.next_action);
@@ -443,5 +558,159 @@ mod tests {
         let table: InstructionTable<dyn Host> =
             crate::opcode::make_instruction_table::<dyn Host, CancunSpec>();
         let _ = interp.run(EMPTY_SHARED_MEMORY, &table, host);
+        let _ = interp.run_specialized::<CancunSpec, dyn Host>(EMPTY_SHARED_MEMORY, host);
+    }
+
+    #[test]
+    fn specialized_matches_table() {
+        use crate::opcode;
+
+        // 2 + 3, stored in memory and returned.
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x02,
+                opcode::PUSH1,
+                0x03,
+                opcode::ADD,
+                opcode::PUSH1,
+                0x00,
+                opcode::MSTORE,
+                opcode::PUSH1,
+                0x20,
+                opcode::PUSH1,
+                0x00,
+                opcode::RETURN,
+            ]
+            .into(),
+        );
+        let contract = || {
+            Contract::new(
+                Bytes::new(),
+                code.clone(),
+                None,
+                Default::default(),
+                Default::default(),
+                U256::ZERO,
+            )
+        };
+        let mut host = DummyHost::default();
+
+        let table: InstructionTable<DummyHost> =
+            crate::opcode::make_instruction_table::<DummyHost, CancunSpec>();
+        let mut interp = Interpreter::new(contract(), 100_000, false);
+        let expected = interp.run(SharedMemory::new(), &table, &mut host);
+
+        let mut interp = Interpreter::new(contract(), 100_000, false);
+        let action = interp.run_specialized::<CancunSpec, _>(SharedMemory::new(), &mut host);
+        assert_eq!(action, expected);
+        let InterpreterAction::Return { result } = action else {
+            panic!("expected return");
+        };
+        assert_eq!(result.output[31], 5);
+    }
+
+    #[test]
+    fn canceled_execution() {
+        use crate::opcode;
+
+        // infinite loop: JUMPDEST PUSH0 JUMP
+        let code = Bytecode::new_raw([opcode::JUMPDEST, opcode::PUSH0, opcode::JUMP].into());
+        let contract = Contract::new(
+            Bytes::new(),
+            code,
+            None,
+            Default::default(),
+            Default::default(),
+            U256::ZERO,
+        );
+        let mut host = DummyHost::default();
+        let table: InstructionTable<DummyHost> =
+            crate::opcode::make_instruction_table::<DummyHost, CancunSpec>();
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut interp = Interpreter::new(contract, u64::MAX, false);
+        interp.cancellation = Some(token);
+        let _ = interp.run(SharedMemory::new(), &table, &mut host);
+        assert_eq!(interp.instruction_result, InstructionResult::Canceled);
+
+        // canceled sub call stops the parent.
+        let mut interp = Interpreter::new(Contract::default(), 100_000, false);
+        interp.insert_call_outcome(
+            &mut SharedMemory::new(),
+            CallOutcome::new(
+                InterpreterResult {
+                    result: InstructionResult::Canceled,
+                    output: Bytes::new(),
+                    gas: Gas::new(0),
+                },
+                0..0,
+            ),
+        );
+        assert_eq!(interp.instruction_result, InstructionResult::Canceled);
+    }
+
+    #[test]
+    fn read_only_host() {
+        use crate::{opcode, ReadOnlyHost};
+
+        // SLOAD(0) is allowed, SSTORE(0, 1) halts.
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x00,
+                opcode::SLOAD,
+                opcode::POP,
+                opcode::PUSH1,
+                0x01,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+            ]
+            .into(),
+        );
+        let contract = Contract::new(
+            Bytes::new(),
+            code,
+            None,
+            Default::default(),
+            Default::default(),
+            U256::ZERO,
+        );
+        let mut host = ReadOnlyHost::new(DummyHost::default());
+        let table: InstructionTable<ReadOnlyHost<DummyHost>> =
+            crate::opcode::make_instruction_table::<ReadOnlyHost<DummyHost>, CancunSpec>();
+        // writes halt even if the interpreter is not static.
+        let mut interp = Interpreter::new(contract, 100_000, false);
+        let _ = interp.run(SharedMemory::new(), &table, &mut host);
+        assert_eq!(
+            interp.instruction_result,
+            InstructionResult::StateChangeDuringStaticCall
+        );
+
+        // TSTORE(0, 1) and LOG0 halt as well.
+        for code in [
+            [opcode::PUSH1, 0x01, opcode::PUSH0, opcode::TSTORE],
+            [opcode::PUSH0, opcode::PUSH0, opcode::LOG0, opcode::STOP],
+        ] {
+            let contract = Contract::new(
+                Bytes::new(),
+                Bytecode::new_raw(code.into()),
+                None,
+                Default::default(),
+                Default::default(),
+                U256::ZERO,
+            );
+            let mut interp = Interpreter::new(contract, 100_000, false);
+            let _ = interp.run(SharedMemory::new(), &table, &mut host);
+            assert_eq!(
+                interp.instruction_result,
+                InstructionResult::StateChangeDuringStaticCall
+            );
+        }
+        let host = host.into_inner();
+        assert_eq!(host.storage.len(), 1);
+        assert!(host.transient_storage.is_empty());
+        assert!(host.log.is_empty());
     }
 }
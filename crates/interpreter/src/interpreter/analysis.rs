@@ -72,6 +72,44 @@ pub fn validate_raw_eof(bytecode: Bytes) -> Result<Eof, EofError> {
     Ok(eof)
 }
 
+/// Same as [validate_raw_eof] but the error contains the location where the validation
+/// failed.
+pub fn validate_raw_eof_with_location(bytecode: Bytes) -> Result<Eof, EofDiagnostic> {
+    let eof = Eof::decode(bytecode).map_err(|error| EofDiagnostic {
+        error: error.into(),
+        location: EofErrorLocation::default(),
+    })?;
+
+    let mut queue = vec![(eof.clone(), Vec::new())];
+    while let Some((container, path)) = queue.pop() {
+        let mut location = EofErrorLocation::default();
+        if let Err(error) = validate_eof_codes_inner(&container, &mut location) {
+            location.container = path;
+            return Err(EofDiagnostic {
+                error: error.into(),
+                location,
+            });
+        }
+        for (i, section) in container.body.container_section.into_iter().enumerate() {
+            let mut path = path.clone();
+            path.push(i);
+            match Eof::decode(section) {
+                Ok(eof) => queue.push((eof, path)),
+                Err(error) => {
+                    return Err(EofDiagnostic {
+                        error: error.into(),
+                        location: EofErrorLocation {
+                            container: path,
+                            ..Default::default()
+                        },
+                    })
+                }
+            }
+        }
+    }
+    Ok(eof)
+}
+
 /// Validate Eof structures.
 pub fn validate_eof(eof: &Eof) -> Result<(), EofError> {
     // clone is cheap as it is Bytes and a header.
@@ -92,6 +130,14 @@ pub fn validate_eof(eof: &Eof) -> Result<(), EofError> {
 
 /// Validate EOF
 pub fn validate_eof_codes(eof: &Eof) -> Result<(), EofValidationError> {
+    validate_eof_codes_inner(eof, &mut EofErrorLocation::default())
+}
+
+/// Validates code sections of the container and records the location of the error.
+fn validate_eof_codes_inner(
+    eof: &Eof,
+    location: &mut EofErrorLocation,
+) -> Result<(), EofValidationError> {
     let mut queued_codes = vec![false; eof.body.code_section.len()];
     if eof.body.code_section.len() != eof.body.types_section.len() {
         return Err(EofValidationError::InvalidTypesSection);
@@ -108,19 +154,22 @@ pub fn validate_eof_codes(eof: &Eof) -> Result<(), EofValidationError> {
     // (0, 0x80, max_stack_height) (0 inputs non-returning function)
     let first_types = &eof.body.types_section[0];
     if first_types.inputs != 0 || first_types.outputs != EOF_NON_RETURNING_FUNCTION {
+        location.code_section = Some(0);
         return Err(EofValidationError::InvalidTypesSection);
     }
 
     // start validation from code section 0.
     let mut queue = vec![0];
     while let Some(index) = queue.pop() {
+        location.code_section = Some(index);
         let code = &eof.body.code_section[index];
-        let accessed_codes = validate_eof_code(
+        let accessed_codes = validate_eof_code_inner(
             code,
             eof.header.data_size as usize,
             index,
             eof.body.container_section.len(),
             &eof.body.types_section,
+            location,
         )?;
 
         // queue accessed codes.
@@ -132,13 +181,45 @@ pub fn validate_eof_codes(eof: &Eof) -> Result<(), EofValidationError> {
         });
     }
     // iterate over accessed codes and check if all are accessed.
-    if queued_codes.into_iter().any(|x| !x) {
+    if let Some(index) = queued_codes.into_iter().position(|x| !x) {
+        *location = EofErrorLocation {
+            code_section: Some(index),
+            ..Default::default()
+        };
         return Err(EofValidationError::CodeSectionNotAccessed);
     }
 
     Ok(())
 }
 
+/// Location of the EOF validation error, see [validate_raw_eof_with_location].
+///
+/// Fields that are not known for the error are `None`.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct EofErrorLocation {
+    /// Indices of the nested containers, starting from the top level container. Empty if
+    /// the error is in the top level container.
+    pub container: Vec<usize>,
+    /// Index of the code section.
+    pub code_section: Option<usize>,
+    /// Offset of the instruction in the code section.
+    pub offset: Option<usize>,
+    /// Opcode of the instruction.
+    pub opcode: Option<u8>,
+    /// Smallest and biggest stack height before the instruction. `None` if the
+    /// instruction is not reachable.
+    pub stack_height: Option<(i32, i32)>,
+}
+
+/// EOF error with the location where it happened.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct EofDiagnostic {
+    /// Error of the validation.
+    pub error: EofError,
+    /// Location of the error.
+    pub location: EofErrorLocation,
+}
+
 /// EOF Error.
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum EofError {
@@ -246,6 +327,25 @@ pub fn validate_eof_code(
     this_types_index: usize,
     num_of_containers: usize,
     types: &[TypesSection],
+) -> Result<HashSet<usize>, EofValidationError> {
+    validate_eof_code_inner(
+        code,
+        data_size,
+        this_types_index,
+        num_of_containers,
+        types,
+        &mut EofErrorLocation::default(),
+    )
+}
+
+/// Validates the code section and records the instruction where the error happened.
+fn validate_eof_code_inner(
+    code: &[u8],
+    data_size: usize,
+    this_types_index: usize,
+    num_of_containers: usize,
+    types: &[TypesSection],
+    location: &mut EofErrorLocation,
 ) -> Result<HashSet<usize>, EofValidationError> {
     let mut accessed_codes = HashSet::<usize>::new();
     let this_types = &types[this_types_index];
@@ -297,6 +397,9 @@ pub fn validate_eof_code(
     // We can check validity and jump destinations in one pass.
     while i < code.len() {
         let op = code[i];
+        location.offset = Some(i);
+        location.opcode = Some(op);
+        location.stack_height = None;
         let opcode = &OPCODE_INFO_JUMPTABLE[op as usize];
 
         let Some(opcode) = opcode else {
@@ -318,6 +421,9 @@ pub fn validate_eof_code(
         }
 
         let this_instruction = *this_instruction;
+        if this_instruction.smallest <= this_instruction.biggest {
+            location.stack_height = Some((this_instruction.smallest, this_instruction.biggest));
+        }
 
         // Opcodes after termination should be accessed by forward jumps.
         if is_after_termination && !this_instruction.is_jumpdest {
@@ -549,6 +655,9 @@ pub fn validate_eof_code(
     }
 
     if max_stack_requirement != types[this_types_index].max_stack_size as i32 {
+        location.offset = None;
+        location.opcode = None;
+        location.stack_height = None;
         // stack overflow
         return Err(EofValidationError::MaxStackMismatch);
     }
@@ -0,0 +1,77 @@
+use crate::primitives::{alloy_primitives::bytes::BytesMut, Bytes};
+use core::cmp::max;
+
+/// Arena that allocates log data and return data of a transaction as views into
+/// shared chunks of memory, instead of allocating every buffer separately.
+///
+/// Returned [Bytes] reference the chunk they were allocated in, which is freed
+/// wholesale when all views into it are dropped. Use [BytesArena::detach] to copy
+/// data that is retained for a long time, so it does not keep the whole chunk alive.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BytesArena {
+    /// Remaining capacity of the current chunk.
+    chunk: BytesMut,
+    /// Size of newly allocated chunks.
+    chunk_size: usize,
+}
+
+impl BytesArena {
+    /// Default size of the chunk, 16KiB.
+    pub const DEFAULT_CHUNK_SIZE: usize = 16 * 1024;
+
+    /// Creates new arena with the default chunk size.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_chunk_size(Self::DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates new arena that allocates chunks of the given size.
+    #[inline]
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            chunk: BytesMut::new(),
+            chunk_size,
+        }
+    }
+
+    /// Copies data into the arena and returns view of it.
+    ///
+    /// Data larger than the chunk size gets a chunk of its own.
+    #[inline]
+    pub fn alloc(&mut self, data: &[u8]) -> Bytes {
+        if self.chunk.capacity() < data.len() {
+            self.chunk = BytesMut::with_capacity(max(self.chunk_size, data.len()));
+        }
+        self.chunk.extend_from_slice(data);
+        self.chunk.split().freeze().into()
+    }
+
+    /// Returns owned copy of the data that does not reference the arena.
+    #[inline]
+    pub fn detach(bytes: &Bytes) -> Bytes {
+        Bytes::copy_from_slice(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_share_chunk() {
+        let mut arena = BytesArena::with_chunk_size(8);
+        let first = arena.alloc(&[1, 2, 3]);
+        let second = arena.alloc(&[4, 5]);
+        assert_eq!(first.as_ref(), &[1, 2, 3]);
+        assert_eq!(second.as_ref(), &[4, 5]);
+        assert_eq!(second.as_ptr(), first.as_ptr().wrapping_add(3));
+
+        // does not fit in the remaining capacity of the chunk.
+        let third = arena.alloc(&[0; 4]);
+        assert_ne!(third.as_ptr(), second.as_ptr().wrapping_add(2));
+
+        let owned = BytesArena::detach(&first);
+        assert_eq!(owned, first);
+        assert_ne!(owned.as_ptr(), first.as_ptr());
+    }
+}
@@ -0,0 +1,314 @@
+use super::{Interpreter, SharedMemory, Stack};
+use crate::{
+    CallOutcome, CreateOutcome, EOFCreateOutcome, Gas, Host, InstructionResult, InterpreterAction,
+};
+
+/// Interpreter that is driven one instruction at a time.
+///
+/// [Interpreter::run] executes the bytecode until the frame stops. A session instead
+/// executes a single instruction on every [InterpreterSession::step] and hands control
+/// back to the caller, that can inspect and modify the stack, memory, gas and program
+/// counter before resuming. This is useful for interactive debuggers and
+/// symbolic-execution front-ends.
+///
+/// Sub calls and creates are not executed by the session. When a step returns
+/// [InterpreterAction::Call] or [InterpreterAction::Create], the caller executes the
+/// sub call and inserts the outcome with [InterpreterSession::insert_call_outcome] or
+/// [InterpreterSession::insert_create_outcome], after which the session can be resumed.
+pub struct InterpreterSession<'a, FN> {
+    interpreter: Interpreter,
+    instruction_table: &'a [FN; 256],
+}
+
+impl<'a, FN> core::fmt::Debug for InterpreterSession<'a, FN> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InterpreterSession")
+            .field("interpreter", &self.interpreter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, FN> InterpreterSession<'a, FN> {
+    /// Creates a new session that executes the instructions of the table.
+    pub fn new(
+        mut interpreter: Interpreter,
+        shared_memory: SharedMemory,
+        instruction_table: &'a [FN; 256],
+    ) -> Self {
+        interpreter.next_action = InterpreterAction::None;
+        interpreter.shared_memory = shared_memory;
+        Self {
+            interpreter,
+            instruction_table,
+        }
+    }
+
+    /// Executes the instruction at the current program counter.
+    ///
+    /// Returns `None` if the execution can continue, otherwise the action the interpreter
+    /// stopped with. Stepping a stopped session returns `None` and does nothing until the
+    /// outcome of a sub call is inserted.
+    pub fn step<H: Host + ?Sized>(&mut self, host: &mut H) -> Option<InterpreterAction>
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        if self.is_stopped() {
+            return None;
+        }
+        self.interpreter.step(self.instruction_table, host);
+        if self.is_stopped() {
+            return Some(self.interpreter.take_next_action());
+        }
+        None
+    }
+
+    /// Executes instructions until the interpreter stops and returns its action.
+    ///
+    /// Returns `None` if the session was already stopped.
+    pub fn resume<H: Host + ?Sized>(&mut self, host: &mut H) -> Option<InterpreterAction>
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.resume_until(host, |_| false)
+    }
+
+    /// Executes instructions until the interpreter stops or the predicate returns `true`
+    /// for the interpreter before the next instruction, e.g. on a breakpoint.
+    ///
+    /// Returns the action if the interpreter stopped.
+    pub fn resume_until<H: Host + ?Sized>(
+        &mut self,
+        host: &mut H,
+        mut pause: impl FnMut(&Interpreter) -> bool,
+    ) -> Option<InterpreterAction>
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        while !self.is_stopped() {
+            if let Some(action) = self.step(host) {
+                return Some(action);
+            }
+            if pause(&self.interpreter) {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Returns whether the interpreter stopped, either because it finished or because it
+    /// waits for the outcome of a sub call.
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.interpreter.instruction_result != InstructionResult::Continue
+    }
+
+    /// Returns the result of the last executed instruction.
+    #[inline]
+    pub fn instruction_result(&self) -> InstructionResult {
+        self.interpreter.instruction_result
+    }
+
+    /// Returns the current program counter.
+    #[inline]
+    pub fn pc(&self) -> usize {
+        self.interpreter.program_counter()
+    }
+
+    /// Moves the program counter. Returns `false` and leaves the program counter unchanged
+    /// if it is out of the bytecode bounds.
+    ///
+    /// Note that the target is not checked to be a valid jump destination.
+    pub fn set_pc(&mut self, pc: usize) -> bool {
+        if pc >= self.interpreter.bytecode.len() {
+            return false;
+        }
+        // SAFETY: `pc` is in the bounds of the bytecode.
+        self.interpreter.instruction_pointer =
+            unsafe { self.interpreter.bytecode.as_ptr().add(pc) };
+        true
+    }
+
+    /// Returns the opcode at the current program counter.
+    #[inline]
+    pub fn current_opcode(&self) -> u8 {
+        self.interpreter.current_opcode()
+    }
+
+    /// Returns a reference to the stack.
+    #[inline]
+    pub fn stack(&self) -> &Stack {
+        &self.interpreter.stack
+    }
+
+    /// Returns a mutable reference to the stack.
+    #[inline]
+    pub fn stack_mut(&mut self) -> &mut Stack {
+        &mut self.interpreter.stack
+    }
+
+    /// Returns the memory of the current context.
+    #[inline]
+    pub fn memory(&self) -> &[u8] {
+        self.interpreter.shared_memory.context_memory()
+    }
+
+    /// Returns a mutable reference to the shared memory.
+    ///
+    /// Memory that is resized directly is not charged gas, use
+    /// [Interpreter::resize_memory] through [InterpreterSession::interpreter_mut] for that.
+    #[inline]
+    pub fn memory_mut(&mut self) -> &mut SharedMemory {
+        &mut self.interpreter.shared_memory
+    }
+
+    /// Returns a reference to the gas state.
+    #[inline]
+    pub fn gas(&self) -> &Gas {
+        &self.interpreter.gas
+    }
+
+    /// Returns a mutable reference to the gas state.
+    #[inline]
+    pub fn gas_mut(&mut self) -> &mut Gas {
+        &mut self.interpreter.gas
+    }
+
+    /// Returns a reference to the interpreter.
+    #[inline]
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    /// Returns a mutable reference to the interpreter.
+    #[inline]
+    pub fn interpreter_mut(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    /// Inserts the outcome of the sub call and resumes the execution on the next step.
+    pub fn insert_call_outcome(&mut self, call_outcome: CallOutcome) {
+        let mut shared_memory = self.interpreter.take_memory();
+        self.interpreter
+            .insert_call_outcome(&mut shared_memory, call_outcome);
+        self.interpreter.shared_memory = shared_memory;
+    }
+
+    /// Inserts the outcome of the sub create and resumes the execution on the next step.
+    pub fn insert_create_outcome(&mut self, create_outcome: CreateOutcome) {
+        self.interpreter.insert_create_outcome(create_outcome);
+    }
+
+    /// Inserts the outcome of the sub EOF create and resumes the execution on the next step.
+    pub fn insert_eofcreate_outcome(&mut self, create_outcome: EOFCreateOutcome) {
+        self.interpreter.instruction_result = InstructionResult::Continue;
+        self.interpreter.insert_eofcreate_outcome(create_outcome);
+    }
+
+    /// Consumes the session and returns the interpreter and its shared memory.
+    pub fn into_parts(mut self) -> (Interpreter, SharedMemory) {
+        let shared_memory = self.interpreter.take_memory();
+        (self.interpreter, shared_memory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::to_analysed,
+        opcode::{self, make_instruction_table, Instruction, InstructionTable},
+        primitives::{Bytecode, Bytes, CancunSpec, U256},
+        Contract, DummyHost, InterpreterResult,
+    };
+
+    fn session<'a>(
+        code: &[u8],
+        table: &'a InstructionTable<DummyHost>,
+    ) -> InterpreterSession<'a, Instruction<DummyHost>> {
+        let contract = Contract {
+            bytecode: to_analysed(Bytecode::new_raw(Bytes::copy_from_slice(code))),
+            ..Default::default()
+        };
+        InterpreterSession::new(
+            Interpreter::new(contract, 100_000, false),
+            SharedMemory::new(),
+            table,
+        )
+    }
+
+    #[test]
+    fn step_and_modify() {
+        let table = make_instruction_table::<DummyHost, CancunSpec>();
+        let mut host = DummyHost::default();
+        // PUSH1 1 PUSH1 2 ADD PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN
+        let code = [
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH1,
+            0x02,
+            opcode::ADD,
+            opcode::PUSH1,
+            0x00,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH1,
+            0x00,
+            opcode::RETURN,
+        ];
+        let mut session = session(&code, &table);
+
+        assert_eq!(session.step(&mut host), None);
+        assert_eq!(session.step(&mut host), None);
+        assert_eq!(session.pc(), 4);
+        assert_eq!(session.current_opcode(), opcode::ADD);
+        assert_eq!(session.stack().data(), &[U256::from(1), U256::from(2)]);
+
+        // replace the second operand before ADD.
+        session.stack_mut().set(0, U256::from(41)).unwrap();
+        assert_eq!(session.step(&mut host), None);
+        assert_eq!(session.stack().data(), &[U256::from(42)]);
+
+        // pause after MSTORE.
+        assert_eq!(
+            session.resume_until(&mut host, |interp| interp.program_counter() == 8),
+            None
+        );
+        assert_eq!(session.memory()[31], 42);
+        session.memory_mut().set_byte(31, 7);
+
+        let action = session.resume(&mut host).unwrap();
+        let InterpreterAction::Return {
+            result: InterpreterResult { result, output, .. },
+        } = action
+        else {
+            panic!("expected return, got {action:?}");
+        };
+        assert_eq!(result, InstructionResult::Return);
+        assert_eq!(output[31], 7);
+        assert!(session.is_stopped());
+        assert_eq!(session.step(&mut host), None);
+    }
+
+    #[test]
+    fn set_pc() {
+        let table = make_instruction_table::<DummyHost, CancunSpec>();
+        let mut host = DummyHost::default();
+        // PUSH1 1 STOP PUSH1 2 STOP
+        let code = [
+            opcode::PUSH1,
+            0x01,
+            opcode::STOP,
+            opcode::PUSH1,
+            0x02,
+            opcode::STOP,
+        ];
+        let mut session = session(&code, &table);
+        assert!(session.set_pc(3));
+        assert!(!session.set_pc(1000));
+        assert_eq!(session.pc(), 3);
+        assert!(session.resume(&mut host).is_some());
+        assert_eq!(session.instruction_result(), InstructionResult::Stop);
+        assert_eq!(session.stack().data(), &[U256::from(2)]);
+    }
+}
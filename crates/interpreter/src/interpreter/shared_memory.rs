@@ -1,5 +1,6 @@
+use super::BytesArena;
+use bcevm_primitives::{Bytes, B256, U256};
 use core::{cmp::min, fmt, ops::Range};
-use bcevm_primitives::{B256, U256};
 use std::vec::Vec;
 
 /// A sequential memory shared between calls, which uses
@@ -17,8 +18,12 @@ pub struct SharedMemory {
     /// Invariant: equals `self.checkpoints.last()`
     last_checkpoint: usize,
     /// Memory limit. See [`CfgEnv`](bcevm_primitives::CfgEnv).
-    #[cfg(feature = "memory_limit")]
     memory_limit: u64,
+    /// Memory limit of a single context. See [`CfgEnv`](bcevm_primitives::CfgEnv).
+    frame_memory_limit: u64,
+    /// Arena for log data and return data, if enabled.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bytes_arena: Option<BytesArena>,
 }
 
 /// Empty shared memory.
@@ -28,8 +33,9 @@ pub const EMPTY_SHARED_MEMORY: SharedMemory = SharedMemory {
     buffer: Vec::new(),
     checkpoints: Vec::new(),
     last_checkpoint: 0,
-    #[cfg(feature = "memory_limit")]
     memory_limit: u64::MAX,
+    frame_memory_limit: u64::MAX,
+    bytes_arena: None,
 };
 
 impl fmt::Debug for SharedMemory {
@@ -67,16 +73,24 @@ impl SharedMemory {
             buffer: Vec::with_capacity(capacity),
             checkpoints: Vec::with_capacity(32),
             last_checkpoint: 0,
-            #[cfg(feature = "memory_limit")]
             memory_limit: u64::MAX,
+            frame_memory_limit: u64::MAX,
+            bytes_arena: None,
         }
     }
 
+    /// Enables allocation of log data and return data in a [BytesArena].
+    ///
+    /// See [SharedMemory::slice_to_bytes].
+    #[inline]
+    pub fn enable_bytes_arena(&mut self) {
+        self.bytes_arena.get_or_insert_with(BytesArena::new);
+    }
+
     /// Creates a new memory instance that can be shared between calls,
     /// with `memory_limit` as upper bound for allocation size.
     ///
     /// The default initial capacity is 4KiB.
-    #[cfg(feature = "memory_limit")]
     #[inline]
     pub fn new_with_memory_limit(memory_limit: u64) -> Self {
         Self {
@@ -85,12 +99,19 @@ impl SharedMemory {
         }
     }
 
+    /// Sets the upper bound for the memory size of a single context.
+    #[inline]
+    pub fn set_frame_memory_limit(&mut self, frame_memory_limit: u64) {
+        self.frame_memory_limit = frame_memory_limit;
+    }
+
     /// Returns `true` if the `new_size` for the current context memory will
-    /// make the shared buffer length exceed the `memory_limit`.
-    #[cfg(feature = "memory_limit")]
+    /// make the shared buffer length exceed the `memory_limit` or the context memory
+    /// exceed the `frame_memory_limit`.
     #[inline]
     pub fn limit_reached(&self, new_size: usize) -> bool {
         (self.last_checkpoint + new_size) as u64 > self.memory_limit
+            || new_size as u64 > self.frame_memory_limit
     }
 
     /// Prepares the shared memory for a new context.
@@ -146,6 +167,24 @@ impl SharedMemory {
         self.slice_range(offset..offset + size)
     }
 
+    /// Copies the memory region at the given offset into [Bytes].
+    ///
+    /// If bytes arena is enabled, data is allocated in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics on out of bounds.
+    #[inline]
+    #[cfg_attr(debug_assertions, track_caller)]
+    pub fn slice_to_bytes(&mut self, offset: usize, size: usize) -> Bytes {
+        let start = self.last_checkpoint + offset;
+        let data = &self.buffer[start..start + size];
+        match &mut self.bytes_arena {
+            Some(arena) => arena.alloc(data),
+            None => Bytes::copy_from_slice(data),
+        }
+    }
+
     /// Returns a byte slice of the memory region at the given offset.
     ///
     /// # Panics
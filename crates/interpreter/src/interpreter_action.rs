@@ -9,7 +9,7 @@ pub use call_inputs::{CallInputs, CallScheme, CallValue};
 pub use call_outcome::CallOutcome;
 pub use create_inputs::{CreateInputs, CreateScheme};
 pub use create_outcome::CreateOutcome;
-pub use eof_create_inputs::EOFCreateInput;
+pub use eof_create_inputs::{EOFCreateInput, EOFCreateKind};
 pub use eof_create_outcome::EOFCreateOutcome;
 
 use crate::InterpreterResult;
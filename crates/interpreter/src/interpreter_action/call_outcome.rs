@@ -1,6 +1,6 @@
 use crate::{Gas, InstructionResult, InterpreterResult};
+use bcevm_primitives::{Bytes, FrameGas};
 use core::ops::Range;
-use bcevm_primitives::Bytes;
 
 /// Represents the outcome of a call operation in a virtual machine.
 ///
@@ -16,6 +16,9 @@ use bcevm_primitives::Bytes;
 pub struct CallOutcome {
     pub result: InterpreterResult,
     pub memory_offset: Range<usize>,
+    /// Gas used by the calls and creates made by the frame, see [FrameGas::child_gas_used].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub child_gas_used: u64,
 }
 
 impl CallOutcome {
@@ -31,6 +34,7 @@ impl CallOutcome {
         Self {
             result,
             memory_offset,
+            child_gas_used: 0,
         }
     }
 
@@ -56,6 +60,36 @@ impl CallOutcome {
         self.result.gas
     }
 
+    /// Returns the gas used by the frame, including the gas used by its children.
+    pub fn gas_used(&self) -> u64 {
+        self.result.gas.spent()
+    }
+
+    /// Returns the gas refund counter of the frame.
+    pub fn gas_refunded(&self) -> i64 {
+        self.result.gas.refunded()
+    }
+
+    /// Returns the gas used by the calls and creates made by the frame.
+    pub fn child_gas_used(&self) -> u64 {
+        self.child_gas_used
+    }
+
+    /// Returns the gas breakdown of the frame.
+    pub fn frame_gas(&self) -> FrameGas {
+        FrameGas {
+            gas_used: self.gas_used(),
+            gas_refunded: self.gas_refunded(),
+            child_gas_used: self.child_gas_used,
+        }
+    }
+
+    /// Sets the gas used by the calls and creates made by the frame.
+    pub fn with_child_gas_used(mut self, child_gas_used: u64) -> Self {
+        self.child_gas_used = child_gas_used;
+        self
+    }
+
     /// Returns a reference to the output data.
     ///
     /// Provides access to the output data generated by the executed instruction.
@@ -1,5 +1,5 @@
 use crate::{Gas, InstructionResult, InterpreterResult};
-use bcevm_primitives::{Address, Bytes};
+use bcevm_primitives::{Address, Bytes, FrameGas};
 
 /// Represents the outcome of a create operation in an interpreter.
 ///
@@ -12,6 +12,9 @@ pub struct CreateOutcome {
     pub result: InterpreterResult,
     // An optional address associated with the create operation.
     pub address: Option<Address>,
+    // Gas used by the calls and creates made by the frame.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub child_gas_used: u64,
 }
 
 impl CreateOutcome {
@@ -26,7 +29,11 @@ impl CreateOutcome {
     ///
     /// A new `CreateOutcome` instance.
     pub fn new(result: InterpreterResult, address: Option<Address>) -> Self {
-        Self { result, address }
+        Self {
+            result,
+            address,
+            child_gas_used: 0,
+        }
     }
 
     /// Retrieves a reference to the `InstructionResult` from the `InterpreterResult`.
@@ -67,4 +74,34 @@ impl CreateOutcome {
     pub fn gas(&self) -> &Gas {
         &self.result.gas
     }
+
+    /// Returns the gas used by the frame, including the gas used by its children.
+    pub fn gas_used(&self) -> u64 {
+        self.result.gas.spent()
+    }
+
+    /// Returns the gas refund counter of the frame.
+    pub fn gas_refunded(&self) -> i64 {
+        self.result.gas.refunded()
+    }
+
+    /// Returns the gas used by the calls and creates made by the frame.
+    pub fn child_gas_used(&self) -> u64 {
+        self.child_gas_used
+    }
+
+    /// Returns the gas breakdown of the frame.
+    pub fn frame_gas(&self) -> FrameGas {
+        FrameGas {
+            gas_used: self.gas_used(),
+            gas_refunded: self.gas_refunded(),
+            child_gas_used: self.child_gas_used,
+        }
+    }
+
+    /// Sets the gas used by the calls and creates made by the frame.
+    pub fn with_child_gas_used(mut self, child_gas_used: u64) -> Self {
+        self.child_gas_used = child_gas_used;
+        self
+    }
 }
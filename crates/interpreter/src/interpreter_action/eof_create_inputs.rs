@@ -1,6 +1,20 @@
-use crate::primitives::{Address, Eof, U256};
+use crate::primitives::{Address, Bytes, Eof, B256, U256};
 use core::ops::Range;
 
+/// Instruction that created the EOF create frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EOFCreateKind {
+    /// `EOFCREATE` with the initcode from the container section of the caller.
+    #[default]
+    Opcode,
+    /// `TXCREATE` with the initcode from the transaction.
+    Tx {
+        /// Hash of the initcode in [crate::primitives::TxEnv::eof_initcodes_hashed].
+        initcode_hash: B256,
+    },
+}
+
 /// Inputs for EOF create call.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -13,11 +27,17 @@ pub struct EOFCreateInput {
     pub value: U256,
     /// Init eof code that is going to be executed.
     pub eof_init_code: Eof,
+    /// Input of the init code, read with the `CALLDATA*` opcodes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub input: Bytes,
     /// Gas limit for the create call.
     pub gas_limit: u64,
     /// Return memory range. If EOF creation Reverts it can return the
     /// the memory range.
     pub return_memory_range: Range<usize>,
+    /// Instruction that created the frame.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: EOFCreateKind,
 }
 
 impl EOFCreateInput {
@@ -35,8 +55,27 @@ impl EOFCreateInput {
             created_address,
             value,
             eof_init_code,
+            input: Bytes::new(),
             gas_limit,
             return_memory_range,
+            kind: EOFCreateKind::Opcode,
         }
     }
+
+    /// Sets the input of the init code.
+    pub fn with_input(mut self, input: Bytes) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Sets the instruction that created the frame.
+    pub fn with_kind(mut self, kind: EOFCreateKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Returns true if the frame is created by `TXCREATE`.
+    pub fn is_txcreate(&self) -> bool {
+        matches!(self.kind, EOFCreateKind::Tx { .. })
+    }
 }
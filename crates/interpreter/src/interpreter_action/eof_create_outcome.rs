@@ -1,7 +1,7 @@
 use core::ops::Range;
 
 use crate::{Gas, InstructionResult, InterpreterResult};
-use bcevm_primitives::{Address, Bytes};
+use bcevm_primitives::{Address, Bytes, FrameGas};
 
 /// Represents the outcome of a create operation in an interpreter.
 ///
@@ -16,6 +16,9 @@ pub struct EOFCreateOutcome {
     pub address: Address,
     /// Return memory range. If EOF creation Reverts it can return bytes from the memory.
     pub return_memory_range: Range<usize>,
+    /// Gas used by the calls and creates made by the frame, see [FrameGas::child_gas_used].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub child_gas_used: u64,
 }
 
 impl EOFCreateOutcome {
@@ -39,6 +42,7 @@ impl EOFCreateOutcome {
             result,
             address,
             return_memory_range,
+            child_gas_used: 0,
         }
     }
 
@@ -81,6 +85,36 @@ impl EOFCreateOutcome {
         &self.result.gas
     }
 
+    /// Returns the gas used by the frame, including the gas used by its children.
+    pub fn gas_used(&self) -> u64 {
+        self.result.gas.spent()
+    }
+
+    /// Returns the gas refund counter of the frame.
+    pub fn gas_refunded(&self) -> i64 {
+        self.result.gas.refunded()
+    }
+
+    /// Returns the gas used by the calls and creates made by the frame.
+    pub fn child_gas_used(&self) -> u64 {
+        self.child_gas_used
+    }
+
+    /// Returns the gas breakdown of the frame.
+    pub fn frame_gas(&self) -> FrameGas {
+        FrameGas {
+            gas_used: self.gas_used(),
+            gas_refunded: self.gas_refunded(),
+            child_gas_used: self.child_gas_used,
+        }
+    }
+
+    /// Sets the gas used by the calls and creates made by the frame.
+    pub fn with_child_gas_used(mut self, child_gas_used: u64) -> Self {
+        self.child_gas_used = child_gas_used;
+        self
+    }
+
     /// Returns the memory range that Revert bytes are going to be written.
     pub fn return_range(&self) -> Range<usize> {
         self.return_memory_range.clone()
@@ -13,12 +13,18 @@ extern crate alloc as std;
 mod macros;
 
 // silence lint
+#[cfg(test)]
+use criterion as _;
+
 #[cfg(test)]
 use serde_json as _;
 
 #[cfg(test)]
 use walkdir as _;
 
+#[cfg(any(test, feature = "bench-utils"))]
+pub mod bench_utils;
+pub mod disasm;
 mod function_stack;
 pub mod gas;
 mod host;
@@ -31,15 +37,18 @@ pub mod opcode;
 // Reexport primary types.
 pub use function_stack::{FunctionReturnFrame, FunctionStack};
 pub use gas::Gas;
-pub use host::{DummyHost, Host, LoadAccountResult, SStoreResult, SelfDestructResult};
+pub use host::{
+    DummyHost, Host, HostRead, HostWrite, LoadAccountResult, ReadOnlyHost, SStoreResult,
+    SelfDestructResult,
+};
 pub use instruction_result::*;
 pub use interpreter::{
-    analysis, num_words, Contract, Interpreter, InterpreterResult, SharedMemory, Stack,
-    EMPTY_SHARED_MEMORY, STACK_LIMIT,
+    analysis, num_words, BytesArena, Contract, Interpreter, InterpreterResult, InterpreterSession,
+    SharedMemory, Stack, CANCELLATION_CHECK_INTERVAL, EMPTY_SHARED_MEMORY, STACK_LIMIT,
 };
 pub use interpreter_action::{
     CallInputs, CallOutcome, CallScheme, CallValue, CreateInputs, CreateOutcome, CreateScheme,
-    EOFCreateInput, EOFCreateOutcome, InterpreterAction,
+    EOFCreateInput, EOFCreateKind, EOFCreateOutcome, InterpreterAction,
 };
 pub use opcode::{Instruction, OpCode, OPCODE_INFO_JUMPTABLE};
 pub use primitives::{MAX_CODE_SIZE, MAX_INITCODE_SIZE};
@@ -1,8 +1,15 @@
 //! EVM opcode definitions and utilities.
 
+mod descriptor;
 pub mod eof_printer;
 
-use crate::{instructions::*, primitives::Spec, Host, Interpreter};
+pub use descriptor::{table_for_spec, OpCodeDescriptor};
+
+use crate::{
+    instructions::*,
+    primitives::{spec_to_generic, GasScheduleOverrides, Spec, SpecId},
+    Host, Interpreter,
+};
 use core::{fmt, ptr::NonNull};
 use std::boxed::Box;
 
@@ -25,9 +32,21 @@ pub type BoxedInstructionTable<'a, H> = [BoxedInstruction<'a, H>; 256];
 /// Note that `Plain` variant gives us 10-20% faster Interpreter execution.
 ///
 /// Boxed variant can be used to wrap plain function pointer with closure.
+///
+/// Specialized variant contains no table, instructions of the spec are dispatched with
+/// [dispatch] and simple instructions can be inlined into the interpreter loop. It is opt-in,
+/// created with [InstructionTables::new_specialized], and converted to the `Plain` variant
+/// when an instruction is inserted.
+///
+/// Cached variant references the table of the spec that is built once at compile time, see
+/// [cached_instruction_table]. It dispatches the same way as the `Plain` variant and is the
+/// default of the mainnet handler. Like the specialized variant, it is converted to the
+/// `Plain` variant when an instruction is inserted.
 pub enum InstructionTables<'a, H> {
     Plain(InstructionTable<H>),
     Boxed(BoxedInstructionTable<'a, H>),
+    Specialized(SpecId),
+    Cached(&'a InstructionTable<H>),
 }
 
 impl<H: Host> InstructionTables<'_, H> {
@@ -36,9 +55,37 @@ impl<H: Host> InstructionTables<'_, H> {
     pub const fn new_plain<SPEC: Spec>() -> Self {
         Self::Plain(make_instruction_table::<H, SPEC>())
     }
+
+    /// Creates specialized instructions for the given spec.
+    #[inline]
+    pub const fn new_specialized<SPEC: Spec>() -> Self {
+        Self::Specialized(SPEC::SPEC_ID)
+    }
+
+    /// Converts the specialized instructions and the cached table to a plain instruction table.
+    /// Other variants are not changed.
+    #[inline]
+    pub fn convert_plain(&mut self) {
+        match *self {
+            Self::Specialized(spec_id) => {
+                *self = spec_to_generic!(spec_id, Self::new_plain::<SPEC>());
+            }
+            Self::Cached(table) => *self = Self::Plain(*table),
+            Self::Plain(_) | Self::Boxed(_) => {}
+        }
+    }
 }
 
 impl<'a, H: Host + 'a> InstructionTables<'a, H> {
+    /// Returns the cached instruction table of the given spec.
+    ///
+    /// Unlike [InstructionTables::new_plain], the table is not copied, which makes this cheap
+    /// to call every time an EVM is built.
+    #[inline]
+    pub const fn cached<SPEC: Spec>() -> Self {
+        Self::Cached(cached_instruction_table::<H, SPEC>())
+    }
+
     /// Inserts a boxed instruction into the table with the specified index.
     ///
     /// This will convert the table into the [BoxedInstructionTable] variant if it is currently a
@@ -50,7 +97,7 @@ impl<'a, H: Host + 'a> InstructionTables<'a, H> {
 
         // now we can insert the instruction
         match self {
-            Self::Plain(_) => {
+            Self::Plain(_) | Self::Specialized(_) | Self::Cached(_) => {
                 unreachable!("we already converted the table to boxed variant");
             }
             Self::Boxed(table) => {
@@ -62,6 +109,7 @@ impl<'a, H: Host + 'a> InstructionTables<'a, H> {
     /// Inserts the instruction into the table with the specified index.
     #[inline]
     pub fn insert(&mut self, opcode: u8, instruction: Instruction<H>) {
+        self.convert_plain();
         match self {
             Self::Plain(table) => {
                 table[opcode as usize] = instruction;
@@ -69,6 +117,46 @@ impl<'a, H: Host + 'a> InstructionTables<'a, H> {
             Self::Boxed(table) => {
                 table[opcode as usize] = Box::new(instruction);
             }
+            Self::Specialized(_) | Self::Cached(_) => {
+                unreachable!("we already converted the table to plain variant");
+            }
+        }
+    }
+
+    /// Disables the opcode. Executing it halts with [InstructionResult::NotActivated].
+    ///
+    /// [InstructionResult::NotActivated]: crate::InstructionResult::NotActivated
+    #[inline]
+    pub fn disable_opcode(&mut self, opcode: u8) {
+        self.insert(opcode, control::not_activated);
+    }
+
+    /// Applies the opcode gas overrides of the spec to the table, see [GasScheduleOverrides].
+    ///
+    /// Overridden instructions are wrapped to charge the difference between the override and
+    /// the [base gas](OpCodeDescriptor::base_gas) of the opcode before they are executed, which
+    /// converts the table to the boxed variant. Overrides of opcodes with dynamic costs are
+    /// ignored.
+    pub fn apply_gas_schedule_overrides(&mut self, overrides: &GasScheduleOverrides, spec: SpecId) {
+        for (opcode, gas) in overrides.iter() {
+            let base_gas = descriptor::base_gas(opcode, spec);
+            if gas == base_gas || has_dynamic_gas(opcode) {
+                continue;
+            }
+            self.convert_boxed();
+            let Self::Boxed(table) = self else {
+                unreachable!("we already converted the table to boxed variant");
+            };
+            let instruction =
+                core::mem::replace(&mut table[opcode as usize], Box::new(control::unknown));
+            table[opcode as usize] = Box::new(move |interpreter, host| {
+                if gas > base_gas {
+                    crate::gas!(interpreter, gas - base_gas);
+                } else {
+                    interpreter.gas.erase_cost(base_gas - gas);
+                }
+                instruction(interpreter, host)
+            });
         }
     }
 
@@ -76,6 +164,7 @@ impl<'a, H: Host + 'a> InstructionTables<'a, H> {
     /// this is a no-op.
     #[inline]
     pub fn convert_boxed(&mut self) {
+        self.convert_plain();
         match self {
             Self::Plain(table) => {
                 *self = Self::Boxed(core::array::from_fn(|i| {
@@ -83,11 +172,33 @@ impl<'a, H: Host + 'a> InstructionTables<'a, H> {
                     instruction
                 }));
             }
-            Self::Boxed(_) => {}
+            Self::Boxed(_) | Self::Specialized(_) | Self::Cached(_) => {}
         };
     }
 }
 
+/// Opcodes whose cost depends on the storage, the logged data or the new frame, their costs
+/// are not overridden, see [GasScheduleOverrides].
+const fn has_dynamic_gas(opcode: u8) -> bool {
+    matches!(opcode, LOG0..=LOG4)
+        || matches!(
+            opcode,
+            SSTORE
+                | CREATE
+                | CREATE2
+                | EOFCREATE
+                | TXCREATE
+                | CALL
+                | CALLCODE
+                | DELEGATECALL
+                | STATICCALL
+                | EXTCALL
+                | EXFCALL
+                | EXTSCALL
+                | SELFDESTRUCT
+        )
+}
+
 /// Make instruction table.
 #[inline]
 pub const fn make_instruction_table<H: Host + ?Sized, SPEC: Spec>() -> InstructionTable<H> {
@@ -111,6 +222,24 @@ pub const fn make_instruction_table<H: Host + ?Sized, SPEC: Spec>() -> Instructi
     ConstTable::<H, SPEC>::NEW
 }
 
+/// Returns a reference to the instruction table of the spec.
+///
+/// The table is evaluated at compile time and placed in static memory once per host and spec,
+/// so no table is constructed at runtime. Boxed tables can not be cached, as their
+/// instructions may borrow from the host.
+#[inline]
+pub const fn cached_instruction_table<'a, H: Host + ?Sized + 'a, SPEC: Spec>(
+) -> &'a InstructionTable<H> {
+    struct CachedTable<H: Host + ?Sized, SPEC: Spec> {
+        _host: core::marker::PhantomData<H>,
+        _spec: core::marker::PhantomData<SPEC>,
+    }
+    impl<H: Host + ?Sized, SPEC: Spec> CachedTable<H, SPEC> {
+        const NEW: InstructionTable<H> = make_instruction_table::<H, SPEC>();
+    }
+    &CachedTable::<H, SPEC>::NEW
+}
+
 /// Make boxed instruction table that calls `outer` closure for every instruction.
 #[inline]
 pub fn make_boxed_instruction_table<'a, H, SPEC, FN>(
@@ -508,6 +637,22 @@ macro_rules! opcodes {
                 _ => control::unknown,
             }
         }
+
+        /// Executes the instruction of the given opcode and spec.
+        ///
+        /// Instructions are called directly instead of through a function pointer, so they
+        /// can be inlined into the caller, see [Interpreter::run_specialized].
+        #[inline(always)]
+        pub fn dispatch<H: Host + ?Sized, SPEC: Spec>(
+            opcode: u8,
+            interpreter: &mut Interpreter,
+            host: &mut H,
+        ) {
+            match opcode {
+                $($name => $f(interpreter, host),)*
+                _ => control::unknown(interpreter, host),
+            }
+        }
     };
 }
 
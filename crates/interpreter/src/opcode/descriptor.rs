@@ -0,0 +1,247 @@
+//! Per-spec description of the instruction set, for documentation and tooling.
+
+use super::*;
+use crate::{gas, SelfDestructResult};
+use bcevm_primitives::U256;
+use std::vec::Vec;
+
+/// Description of an opcode at a spec, see [table_for_spec].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OpCodeDescriptor {
+    /// The opcode.
+    pub opcode: OpCode,
+    /// Name of the opcode.
+    pub name: &'static str,
+    /// Spec that introduced the opcode.
+    pub introduced_in: SpecId,
+    /// Whether the opcode is enabled at the spec.
+    pub enabled: bool,
+    /// Gas charged by the opcode itself at the spec.
+    ///
+    /// Memory expansion, per word and per byte costs, cold access surcharges, value transfer,
+    /// new account and storage write costs, and gas passed to a new frame are not included.
+    /// Gas schedule overrides of the configuration are not applied, see
+    /// [InstructionTables::apply_gas_schedule_overrides].
+    pub base_gas: u64,
+    /// Stack inputs.
+    pub inputs: u8,
+    /// Stack outputs.
+    pub outputs: u8,
+    /// Number of immediate bytes, minimum number for `RJUMPV`.
+    pub immediate_size: u8,
+    /// Whether the opcode can be used in legacy bytecode.
+    pub legacy: bool,
+    /// Whether the opcode can be used in EOF bytecode.
+    pub eof: bool,
+    /// Whether the opcode stops execution.
+    pub terminating: bool,
+}
+
+/// Returns descriptions of all defined opcodes at the spec, ordered by opcode.
+pub fn table_for_spec(spec: SpecId) -> Vec<OpCodeDescriptor> {
+    OPCODE_INFO_JUMPTABLE
+        .iter()
+        .enumerate()
+        .filter_map(|(op, info)| {
+            let info = (*info)?;
+            let op = op as u8;
+            let introduced_in = introduced_in(op);
+            Some(OpCodeDescriptor {
+                opcode: OpCode(op),
+                name: info.name(),
+                introduced_in,
+                enabled: spec.is_enabled_in(introduced_in),
+                base_gas: base_gas(op, spec),
+                inputs: info.inputs(),
+                outputs: info.outputs(),
+                immediate_size: info.immediate_size(),
+                legacy: !is_eof_only(op),
+                eof: !info.is_disabled_in_eof(),
+                terminating: info.is_terminating(),
+            })
+        })
+        .collect()
+}
+
+/// Opcodes that are only valid in EOF bytecode.
+const fn is_eof_only(op: u8) -> bool {
+    matches!(
+        op,
+        RJUMP
+            | RJUMPI
+            | RJUMPV
+            | CALLF
+            | RETF
+            | JUMPF
+            | DATALOAD
+            | DATALOADN
+            | DATASIZE
+            | DATACOPY
+            | DUPN
+            | SWAPN
+            | EXCHANGE
+            | EOFCREATE
+            | TXCREATE
+            | RETURNCONTRACT
+            | RETURNDATALOAD
+            | EXTCALL
+            | EXFCALL
+            | EXTSCALL
+    )
+}
+
+/// Spec in which the opcode is activated.
+const fn introduced_in(op: u8) -> SpecId {
+    if is_eof_only(op) {
+        return SpecId::EXPERIMENTAL;
+    }
+    match op {
+        DELEGATECALL => SpecId::HOMESTEAD,
+        RETURNDATASIZE | RETURNDATACOPY | STATICCALL | REVERT => SpecId::BYZANTIUM,
+        SHL | SHR | SAR | EXTCODEHASH => SpecId::CONSTANTINOPLE,
+        CREATE2 => SpecId::PETERSBURG,
+        CHAINID | SELFBALANCE => SpecId::ISTANBUL,
+        BASEFEE => SpecId::LONDON,
+        PUSH0 => SpecId::SHANGHAI,
+        BLOBHASH | BLOBBASEFEE | TLOAD | TSTORE | MCOPY => SpecId::CANCUN,
+        _ => SpecId::FRONTIER,
+    }
+}
+
+/// Gas charged by the opcode itself, see [OpCodeDescriptor::base_gas].
+pub(super) fn base_gas(op: u8, spec: SpecId) -> u64 {
+    let berlin = spec.is_enabled_in(SpecId::BERLIN);
+    let istanbul = spec.is_enabled_in(SpecId::ISTANBUL);
+    let tangerine = spec.is_enabled_in(SpecId::TANGERINE);
+    match op {
+        STOP | RETURN | REVERT | INVALID | RETURNCONTRACT => gas::ZERO,
+        ADDRESS | ORIGIN | CALLER | CALLVALUE | CALLDATASIZE | CODESIZE | GASPRICE
+        | RETURNDATASIZE | COINBASE | TIMESTAMP | NUMBER | DIFFICULTY | GASLIMIT | CHAINID
+        | BASEFEE | BLOBBASEFEE | POP | PC | MSIZE | GAS | PUSH0 | RJUMP | DATASIZE => gas::BASE,
+        MUL | DIV | SDIV | MOD | SMOD | SIGNEXTEND | SELFBALANCE | CALLF | JUMPF => gas::LOW,
+        ADDMOD | MULMOD | JUMP => gas::MID,
+        JUMPI => gas::HIGH,
+        JUMPDEST => gas::JUMPDEST,
+        EXP => gas::EXP,
+        KECCAK256 => gas::KECCAK256,
+        RJUMPI | RJUMPV => gas::CONDITION_JUMP_GAS,
+        RETF => gas::RETF_GAS,
+        DATALOAD => gas::DATA_LOAD_GAS,
+        BLOCKHASH => gas::BLOCKHASH,
+        BALANCE if berlin => gas::WARM_STORAGE_READ_COST,
+        BALANCE if istanbul => 700,
+        BALANCE if tangerine => 400,
+        BALANCE => 20,
+        EXTCODESIZE if berlin => gas::WARM_STORAGE_READ_COST,
+        EXTCODESIZE if tangerine => 700,
+        EXTCODESIZE => 20,
+        EXTCODEHASH if berlin => gas::WARM_STORAGE_READ_COST,
+        EXTCODEHASH if istanbul => 700,
+        EXTCODEHASH => 400,
+        EXTCODECOPY => gas::extcodecopy_cost(spec, 0, false).unwrap_or_default(),
+        SLOAD => gas::sload_cost(spec, false),
+        SSTORE => gas::sstore_cost(spec, U256::ZERO, U256::ZERO, U256::ZERO, u64::MAX, false)
+            .unwrap_or_default(),
+        TLOAD | TSTORE => gas::WARM_STORAGE_READ_COST,
+        LOG0..=LOG4 => gas::log_cost(op - LOG0, 0).unwrap_or_default(),
+        CREATE | CREATE2 => gas::CREATE,
+        EOFCREATE | TXCREATE => gas::EOF_CREATE_GAS,
+        CALL | CALLCODE | DELEGATECALL | STATICCALL | EXTCALL | EXFCALL | EXTSCALL => {
+            gas::call_cost(spec, false, false, false)
+        }
+        SELFDESTRUCT => gas::selfdestruct_cost(
+            spec,
+            SelfDestructResult {
+                target_exists: true,
+                ..Default::default()
+            },
+        ),
+        // arithmetic, comparison and bitwise operations, calldata and memory access, copies,
+        // pushes, dups and swaps.
+        _ => gas::VERYLOW,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{Bytecode, Bytes, CancunSpec, FrontierSpec, HomesteadSpec, IstanbulSpec},
+        DummyHost, Gas, InstructionResult,
+    };
+
+    #[test]
+    fn push0_activation() {
+        let shanghai = table_for_spec(SpecId::SHANGHAI);
+        let merge = table_for_spec(SpecId::MERGE);
+        let push0 =
+            |table: &[OpCodeDescriptor]| *table.iter().find(|d| d.opcode == OpCode::PUSH0).unwrap();
+        assert!(push0(&shanghai).enabled);
+        assert!(!push0(&merge).enabled);
+        assert_eq!(push0(&shanghai).introduced_in, SpecId::SHANGHAI);
+        assert_eq!(push0(&shanghai).base_gas, gas::BASE);
+        assert_eq!(
+            shanghai.len(),
+            OPCODE_INFO_JUMPTABLE.iter().flatten().count()
+        );
+        assert!(shanghai.windows(2).all(|w| w[0].opcode < w[1].opcode));
+
+        let rjump = table_for_spec(SpecId::EXPERIMENTAL)
+            .into_iter()
+            .find(|d| d.opcode == OpCode::RJUMP)
+            .unwrap();
+        assert!(rjump.enabled && rjump.eof && !rjump.legacy);
+        assert_eq!(rjump.immediate_size, 2);
+    }
+
+    /// Executes every legacy opcode with zero inputs and checks the activation and the gas
+    /// against the descriptors.
+    fn check_against_execution<SPEC: Spec>() {
+        // gas of these depends on memory expansion, host state or the new frame.
+        const DYNAMIC: &[u8] = &[
+            MLOAD,
+            MSTORE,
+            MSTORE8,
+            SLOAD,
+            SSTORE,
+            CREATE,
+            CREATE2,
+            CALL,
+            CALLCODE,
+            DELEGATECALL,
+            STATICCALL,
+        ];
+
+        let table = make_instruction_table::<DummyHost, SPEC>();
+        for descriptor in table_for_spec(SPEC::SPEC_ID) {
+            // `SELFDESTRUCT` is not supported by the dummy host.
+            if !descriptor.legacy || descriptor.opcode == OpCode::SELFDESTRUCT {
+                continue;
+            }
+            let mut code = vec![descriptor.opcode.get()];
+            code.resize(1 + descriptor.immediate_size as usize, 0);
+            let mut interp = Interpreter::new_bytecode(Bytecode::new_raw(Bytes::from(code)));
+            interp.gas = Gas::new(1_000_000);
+            for _ in 0..descriptor.inputs {
+                interp.stack.push(U256::ZERO).unwrap();
+            }
+            interp.step(&table, &mut DummyHost::default());
+
+            let activated = interp.instruction_result != InstructionResult::NotActivated;
+            assert_eq!(activated, descriptor.enabled, "{descriptor:?}");
+            if interp.instruction_result == InstructionResult::Continue
+                && !DYNAMIC.contains(&descriptor.opcode.get())
+            {
+                assert_eq!(interp.gas.spent(), descriptor.base_gas, "{descriptor:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn table_matches_execution() {
+        check_against_execution::<FrontierSpec>();
+        check_against_execution::<HomesteadSpec>();
+        check_against_execution::<IstanbulSpec>();
+        check_against_execution::<CancunSpec>();
+    }
+}
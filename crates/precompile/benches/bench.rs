@@ -1,14 +1,15 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use bcevm_precompile::{
+    blake2::{self, algo},
     bn128::{
         pair::{ISTANBUL_PAIR_BASE, ISTANBUL_PAIR_PER_POINT},
-        run_pair,
+        run_pair, substrate, ADD_INPUT_LEN, MUL_INPUT_LEN,
     },
     kzg_point_evaluation::run,
     secp256k1::ec_recover_run,
     Bytes,
 };
 use bcevm_primitives::{hex, keccak256, Env, U256, VERSIONED_HASH_VERSION_KZG};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use secp256k1::{Message, SecretKey, SECP256K1};
 use sha2::{Digest, Sha256};
 
@@ -20,22 +21,7 @@ pub fn benchmark_crypto_precompiles(c: &mut Criterion) {
     // === ECPAIRING ===
 
     // set up ecpairing input
-    let input = hex::decode(
-        "\
-        1c76476f4def4bb94541d57ebba1193381ffa7aa76ada664dd31c16024c43f59\
-        3034dd2920f673e204fee2811c678745fc819b55d3e9d294e45c9b03a76aef41\
-        209dd15ebff5d46c4bd888e51a93cf99a7329636c63514396b4a452003a35bf7\
-        04bf11ca01483bfa8b34b43561848d28905960114c8ac04049af4b6315a41678\
-        2bb8324af6cfc93537a2ad1a445cfd0ca2a71acd7ac41fadbf933c2a51be344d\
-        120a2a4cf30c1bf9845f20c6fe39e07ea2cce61f0c9bb048165fe5e4de877550\
-        111e129f1cf1097710d41c4ac70fcdfa5ba2023c6ff1cbeac322de49d1b6df7c\
-        2032c61a830e3c17286de9462bf242fca2883585b93870a73853face6a6bf411\
-        198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
-        1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
-        090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
-        12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
-    )
-    .unwrap();
+    let input = ecpairing_input();
 
     let res = run_pair(
         &input,
@@ -44,7 +30,7 @@ pub fn benchmark_crypto_precompiles(c: &mut Criterion) {
         u64::MAX,
     )
     .unwrap()
-    .0;
+    .gas_used;
 
     println!("gas used by regular pairing call: {:?}", res);
 
@@ -88,7 +74,7 @@ pub fn benchmark_crypto_precompiles(c: &mut Criterion) {
 
     let gas = 50000;
     let env = Env::default();
-    let (actual_gas, _actual_output) = run(&kzg_input, gas, &env).unwrap();
+    let actual_gas = run(&kzg_input, gas, &env).unwrap().gas_used;
     println!("gas used by kzg precompile: {:?}", actual_gas);
 
     group.bench_function(group_name("ecrecover precompile"), |b| {
@@ -119,9 +105,122 @@ pub fn benchmark_crypto_precompiles(c: &mut Criterion) {
     });
 }
 
+/// Returns the input of a successful pairing check of two pairs.
+fn ecpairing_input() -> Vec<u8> {
+    hex::decode(
+        "\
+        1c76476f4def4bb94541d57ebba1193381ffa7aa76ada664dd31c16024c43f59\
+        3034dd2920f673e204fee2811c678745fc819b55d3e9d294e45c9b03a76aef41\
+        209dd15ebff5d46c4bd888e51a93cf99a7329636c63514396b4a452003a35bf7\
+        04bf11ca01483bfa8b34b43561848d28905960114c8ac04049af4b6315a41678\
+        2bb8324af6cfc93537a2ad1a445cfd0ca2a71acd7ac41fadbf933c2a51be344d\
+        120a2a4cf30c1bf9845f20c6fe39e07ea2cce61f0c9bb048165fe5e4de877550\
+        111e129f1cf1097710d41c4ac70fcdfa5ba2023c6ff1cbeac322de49d1b6df7c\
+        2032c61a830e3c17286de9462bf242fca2883585b93870a73853face6a6bf411\
+        198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+        1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+        090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+        12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+    )
+    .unwrap()
+}
+
+/// Benchmarks the bn128 backends on the same inputs.
+pub fn benchmark_bn128_backends(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bn128 backends");
+
+    let add_input = hex::decode(
+        "\
+        18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9\
+        063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266\
+        07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed\
+        06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+    )
+    .unwrap();
+    let add_input: [u8; ADD_INPUT_LEN] = add_input.try_into().unwrap();
+    let mul_input = hex::decode(
+        "\
+        2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7\
+        21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204\
+        00000000000000000000000000000000000000000000000011138ce750fa15c2",
+    )
+    .unwrap();
+    let mul_input: [u8; MUL_INPUT_LEN] = mul_input.try_into().unwrap();
+    let pair_input = ecpairing_input();
+
+    group.bench_function("substrate-bn | ecadd", |b| {
+        b.iter(|| black_box(substrate::g1_add(black_box(&add_input)).unwrap()))
+    });
+    group.bench_function("substrate-bn | ecmul", |b| {
+        b.iter(|| black_box(substrate::g1_mul(black_box(&mul_input)).unwrap()))
+    });
+    group.bench_function("substrate-bn | ecpairing", |b| {
+        b.iter(|| black_box(substrate::pairing_check(black_box(&pair_input)).unwrap()))
+    });
+
+    #[cfg(feature = "bn128-arkworks")]
+    {
+        use bcevm_precompile::bn128::arkworks;
+
+        group.bench_function("arkworks | ecadd", |b| {
+            b.iter(|| black_box(arkworks::g1_add(black_box(&add_input)).unwrap()))
+        });
+        group.bench_function("arkworks | ecmul", |b| {
+            b.iter(|| black_box(arkworks::g1_mul(black_box(&mul_input)).unwrap()))
+        });
+        group.bench_function("arkworks | ecpairing", |b| {
+            b.iter(|| black_box(arkworks::pairing_check(black_box(&pair_input)).unwrap()))
+        });
+    }
+}
+
+/// Benchmarks the BLAKE2 compression function and precompile with 12 rounds.
+pub fn benchmark_blake2(c: &mut Criterion) {
+    let mut group = c.benchmark_group("blake2");
+
+    // EIP-152 test vector 5, BLAKE2b of "abc".
+    let h = [
+        algo::IV[0] ^ 0x01010040,
+        algo::IV[1],
+        algo::IV[2],
+        algo::IV[3],
+        algo::IV[4],
+        algo::IV[5],
+        algo::IV[6],
+        algo::IV[7],
+    ];
+    let mut m = [0u64; 16];
+    m[0] = 0x636261;
+    let mut input = 12u32.to_be_bytes().to_vec();
+    h.iter().for_each(|w| input.extend(w.to_le_bytes()));
+    m.iter().for_each(|w| input.extend(w.to_le_bytes()));
+    input.extend(3u64.to_le_bytes());
+    input.extend(0u64.to_le_bytes());
+    input.push(1);
+    let input = Bytes::from(input);
+
+    group.bench_function("compress scalar", |b| {
+        b.iter(|| {
+            let mut h = black_box(h);
+            algo::compress_scalar(12, &mut h, black_box(m), [3, 0], true);
+            h
+        })
+    });
+    group.bench_function("compress", |b| {
+        b.iter(|| {
+            let mut h = black_box(h);
+            algo::compress(12, &mut h, black_box(m), [3, 0], true);
+            h
+        })
+    });
+    group.bench_function("precompile", |b| {
+        b.iter(|| black_box(blake2::run(black_box(&input), u64::MAX).unwrap()))
+    });
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default();
-    targets = benchmark_crypto_precompiles
+    targets = benchmark_crypto_precompiles, benchmark_bn128_backends, benchmark_blake2
 }
 criterion_main!(benches);
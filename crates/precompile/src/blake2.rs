@@ -1,6 +1,9 @@
-use crate::{Error, Precompile, PrecompileResult, PrecompileWithAddress};
+use crate::{Error, Precompile, PrecompileOutput, PrecompileResult, PrecompileWithAddress};
 use bcevm_primitives::Bytes;
 
+#[cfg(all(feature = "blake2-simd", target_arch = "x86_64"))]
+pub mod avx2;
+
 const F_ROUND: u64 = 1;
 const INPUT_LENGTH: usize = 213;
 
@@ -26,9 +29,7 @@ pub fn run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     // rounds 4 bytes
     let rounds = u32::from_be_bytes(input[..4].try_into().unwrap()) as usize;
     let gas_used = rounds as u64 * F_ROUND;
-    if gas_used > gas_limit {
-        return Err(Error::OutOfGas);
-    }
+    check_gas!(gas_used, gas_limit);
 
     let mut h = [0u64; 8];
     let mut m = [0u64; 16];
@@ -51,7 +52,7 @@ pub fn run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
         out[i..i + 8].copy_from_slice(&h.to_le_bytes());
     }
 
-    Ok((gas_used, out.into()))
+    Ok(PrecompileOutput::new(gas_used, out.into()))
 }
 
 pub mod algo {
@@ -95,6 +96,18 @@ pub mod algo {
         v[b] = (v[b] ^ v[c]).rotate_right(63);
     }
 
+    /// Compression function F, uses AVX2 if the `blake2-simd` feature is enabled and the
+    /// CPU supports it, [compress_scalar] otherwise.
+    #[inline]
+    pub fn compress(rounds: usize, h: &mut [u64; 8], m: [u64; 16], t: [u64; 2], f: bool) {
+        #[cfg(all(feature = "blake2-simd", target_arch = "x86_64"))]
+        if super::avx2::is_supported() {
+            // SAFETY: AVX2 support is checked.
+            return unsafe { super::avx2::compress(rounds, h, m, t, f) };
+        }
+        compress_scalar(rounds, h, m, t, f)
+    }
+
     // Compression function F takes as an argument the state vector "h",
     // message block vector "m" (last block is padded with zeros to full
     // block size, if required), 2w-bit offset counter "t", and final block
@@ -102,7 +115,7 @@ pub mod algo {
     // returns a new state vector.  The number of rounds, "r", is 12 for
     // BLAKE2b and 10 for BLAKE2s.  Rounds are numbered from 0 to r - 1.
     #[allow(clippy::many_single_char_names)]
-    pub fn compress(rounds: usize, h: &mut [u64; 8], m: [u64; 16], t: [u64; 2], f: bool) {
+    pub fn compress_scalar(rounds: usize, h: &mut [u64; 8], m: [u64; 16], t: [u64; 2], f: bool) {
         let mut v = [0u64; 16];
         v[..h.len()].copy_from_slice(h); // First half from state.
         v[h.len()..].copy_from_slice(&IV); // Second half from IV.
@@ -0,0 +1,173 @@
+//! BLAKE2b compression function F using AVX2.
+//!
+//! Enabled with the `blake2-simd` feature on `x86_64`. The state is kept in four vectors,
+//! one per row of the 4x4 matrix, so every G step mixes all four columns (or diagonals)
+//! at once.
+use super::algo::{IV, SIGMA};
+use core::arch::x86_64::*;
+
+/// Returns true if AVX2 is available on the running CPU.
+#[inline]
+pub fn is_supported() -> bool {
+    #[cfg(feature = "std")]
+    {
+        std::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "avx2")
+    }
+}
+
+/// Compression function F, same as [super::algo::compress_scalar].
+///
+/// # Safety
+///
+/// CPU needs to support AVX2, see [is_supported].
+#[target_feature(enable = "avx2")]
+pub unsafe fn compress(rounds: usize, h: &mut [u64; 8], m: [u64; 16], t: [u64; 2], f: bool) {
+    // SAFETY: `h` and `IV` are 8 words long, unaligned loads read 4 words each.
+    let (mut a, mut b, mut c, mut d) = unsafe {
+        (
+            _mm256_loadu_si256(h.as_ptr().cast()),
+            _mm256_loadu_si256(h.as_ptr().add(4).cast()),
+            _mm256_loadu_si256(IV.as_ptr().cast()),
+            _mm256_loadu_si256(IV.as_ptr().add(4).cast()),
+        )
+    };
+    d = _mm256_xor_si256(
+        d,
+        _mm256_setr_epi64x(t[0] as i64, t[1] as i64, if f { -1 } else { 0 }, 0),
+    );
+
+    for i in 0..rounds {
+        let s = &SIGMA[i % 10];
+        let word = |n: usize| m[s[n]] as i64;
+
+        // columns
+        let x = _mm256_setr_epi64x(word(0), word(2), word(4), word(6));
+        let y = _mm256_setr_epi64x(word(1), word(3), word(5), word(7));
+        g(&mut a, &mut b, &mut c, &mut d, x, y);
+
+        // diagonals, rows are rotated so that the diagonals are in the columns.
+        b = _mm256_permute4x64_epi64(b, 0b00_11_10_01);
+        c = _mm256_permute4x64_epi64(c, 0b01_00_11_10);
+        d = _mm256_permute4x64_epi64(d, 0b10_01_00_11);
+        let x = _mm256_setr_epi64x(word(8), word(10), word(12), word(14));
+        let y = _mm256_setr_epi64x(word(9), word(11), word(13), word(15));
+        g(&mut a, &mut b, &mut c, &mut d, x, y);
+        b = _mm256_permute4x64_epi64(b, 0b10_01_00_11);
+        c = _mm256_permute4x64_epi64(c, 0b01_00_11_10);
+        d = _mm256_permute4x64_epi64(d, 0b00_11_10_01);
+    }
+
+    // SAFETY: `h` is 8 words long, unaligned loads and stores access 4 words each.
+    unsafe {
+        let lo = h.as_mut_ptr().cast::<__m256i>();
+        let hi = h.as_mut_ptr().add(4).cast::<__m256i>();
+        _mm256_storeu_si256(
+            lo,
+            _mm256_xor_si256(_mm256_loadu_si256(lo), _mm256_xor_si256(a, c)),
+        );
+        _mm256_storeu_si256(
+            hi,
+            _mm256_xor_si256(_mm256_loadu_si256(hi), _mm256_xor_si256(b, d)),
+        );
+    }
+}
+
+/// G function applied to the four columns of the state.
+#[inline(always)]
+fn g(a: &mut __m256i, b: &mut __m256i, c: &mut __m256i, d: &mut __m256i, x: __m256i, y: __m256i) {
+    // SAFETY: only called from `compress` that has AVX2 enabled.
+    unsafe {
+        *a = _mm256_add_epi64(_mm256_add_epi64(*a, *b), x);
+        *d = rotate_right_32(_mm256_xor_si256(*d, *a));
+        *c = _mm256_add_epi64(*c, *d);
+        *b = rotate_right_24(_mm256_xor_si256(*b, *c));
+        *a = _mm256_add_epi64(_mm256_add_epi64(*a, *b), y);
+        *d = rotate_right_16(_mm256_xor_si256(*d, *a));
+        *c = _mm256_add_epi64(*c, *d);
+        *b = rotate_right_63(_mm256_xor_si256(*b, *c));
+    }
+}
+
+#[inline(always)]
+unsafe fn rotate_right_32(x: __m256i) -> __m256i {
+    _mm256_shuffle_epi32(x, 0b10_11_00_01)
+}
+
+#[inline(always)]
+unsafe fn rotate_right_24(x: __m256i) -> __m256i {
+    let mask = _mm256_setr_epi8(
+        3, 4, 5, 6, 7, 0, 1, 2, 11, 12, 13, 14, 15, 8, 9, 10, //
+        3, 4, 5, 6, 7, 0, 1, 2, 11, 12, 13, 14, 15, 8, 9, 10,
+    );
+    _mm256_shuffle_epi8(x, mask)
+}
+
+#[inline(always)]
+unsafe fn rotate_right_16(x: __m256i) -> __m256i {
+    let mask = _mm256_setr_epi8(
+        2, 3, 4, 5, 6, 7, 0, 1, 10, 11, 12, 13, 14, 15, 8, 9, //
+        2, 3, 4, 5, 6, 7, 0, 1, 10, 11, 12, 13, 14, 15, 8, 9,
+    );
+    _mm256_shuffle_epi8(x, mask)
+}
+
+#[inline(always)]
+unsafe fn rotate_right_63(x: __m256i) -> __m256i {
+    _mm256_or_si256(_mm256_srli_epi64(x, 63), _mm256_add_epi64(x, x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{blake2::algo::compress_scalar, testing::InputRng};
+    use bcevm_primitives::hex;
+
+    #[test]
+    fn blake2b_abc() {
+        if !is_supported() {
+            return;
+        }
+        // parameter block of 64 bytes output without key.
+        let mut h = IV;
+        h[0] ^= 0x01010040;
+        let mut m = [0u64; 16];
+        m[0] = u64::from_le_bytes(*b"abc\0\0\0\0\0");
+        unsafe { compress(12, &mut h, m, [3, 0], true) };
+
+        let out: Vec<u8> = h.iter().flat_map(|w| w.to_le_bytes()).collect();
+        assert_eq!(
+            out,
+            hex!(
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1"
+                "7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+            )
+        );
+    }
+
+    #[test]
+    fn compress_matches_scalar() {
+        if !is_supported() {
+            return;
+        }
+        let mut rng = InputRng::new(0xb1a2);
+        for i in 0..256 {
+            let mut h = [0u64; 8];
+            let mut m = [0u64; 16];
+            h.iter_mut().for_each(|w| *w = rng.next_u64());
+            m.iter_mut().for_each(|w| *w = rng.next_u64());
+            let t = [rng.next_u64(), rng.next_u64()];
+            let f = rng.bool();
+            // covers zero rounds and rounds that wrap around SIGMA.
+            let rounds = i % 25;
+
+            let mut expected = h;
+            compress_scalar(rounds, &mut expected, m, t, f);
+            unsafe { compress(rounds, &mut h, m, t, f) };
+            assert_eq!(h, expected, "rounds: {rounds}");
+        }
+    }
+}
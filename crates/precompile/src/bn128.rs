@@ -1,8 +1,18 @@
 use crate::{
     utilities::{bool_to_bytes32, right_pad},
-    Address, Error, Precompile, PrecompileResult, PrecompileWithAddress,
+    Address, Error, Precompile, PrecompileOutput, PrecompileResult, PrecompileWithAddress,
 };
-use bn::{AffineG1, AffineG2, Fq, Fq2, Group, Gt, G1, G2};
+
+#[cfg(feature = "bn128-arkworks")]
+pub mod arkworks;
+pub mod substrate;
+
+pub use substrate::{new_g1_point, read_fq, read_point};
+
+#[cfg(feature = "bn128-arkworks")]
+use arkworks as backend;
+#[cfg(not(feature = "bn128-arkworks"))]
+use substrate as backend;
 
 pub mod add {
     use super::*;
@@ -87,75 +97,20 @@ pub const MUL_INPUT_LEN: usize = 64 + 32;
 /// (128 bytes).
 pub const PAIR_ELEMENT_LEN: usize = 64 + 128;
 
-/// Reads a single `Fq` from the input slice.
-///
-/// # Panics
-///
-/// Panics if the input is not at least 32 bytes long.
-#[inline]
-pub fn read_fq(input: &[u8]) -> Result<Fq, Error> {
-    Fq::from_slice(&input[..32]).map_err(|_| Error::Bn128FieldPointNotAMember)
-}
-
-/// Reads the `x` and `y` points from the input slice.
-///
-/// # Panics
-///
-/// Panics if the input is not at least 64 bytes long.
-#[inline]
-pub fn read_point(input: &[u8]) -> Result<G1, Error> {
-    let px = read_fq(&input[0..32])?;
-    let py = read_fq(&input[32..64])?;
-    new_g1_point(px, py)
-}
-
-/// Creates a new `G1` point from the given `x` and `y` coordinates.
-pub fn new_g1_point(px: Fq, py: Fq) -> Result<G1, Error> {
-    if px == Fq::zero() && py == Fq::zero() {
-        Ok(G1::zero())
-    } else {
-        AffineG1::new(px, py)
-            .map(Into::into)
-            .map_err(|_| Error::Bn128AffineGFailedToCreate)
-    }
-}
-
 pub fn run_add(input: &[u8], gas_cost: u64, gas_limit: u64) -> PrecompileResult {
-    if gas_cost > gas_limit {
-        return Err(Error::OutOfGas);
-    }
+    check_gas!(gas_cost, gas_limit);
 
     let input = right_pad::<ADD_INPUT_LEN>(input);
-
-    let p1 = read_point(&input[..64])?;
-    let p2 = read_point(&input[64..])?;
-
-    let mut output = [0u8; 64];
-    if let Some(sum) = AffineG1::from_jacobian(p1 + p2) {
-        sum.x().to_big_endian(&mut output[..32]).unwrap();
-        sum.y().to_big_endian(&mut output[32..]).unwrap();
-    }
-    Ok((gas_cost, output.into()))
+    let output = backend::g1_add(&input)?;
+    Ok(PrecompileOutput::new(gas_cost, output.into()))
 }
 
 pub fn run_mul(input: &[u8], gas_cost: u64, gas_limit: u64) -> PrecompileResult {
-    if gas_cost > gas_limit {
-        return Err(Error::OutOfGas);
-    }
+    check_gas!(gas_cost, gas_limit);
 
     let input = right_pad::<MUL_INPUT_LEN>(input);
-
-    let p = read_point(&input[..64])?;
-
-    // `Fr::from_slice` can only fail when the length is not 32.
-    let fr = bn::Fr::from_slice(&input[64..96]).unwrap();
-
-    let mut output = [0u8; 64];
-    if let Some(mul) = AffineG1::from_jacobian(p * fr) {
-        mul.x().to_big_endian(&mut output[..32]).unwrap();
-        mul.y().to_big_endian(&mut output[32..]).unwrap();
-    }
-    Ok((gas_cost, output.into()))
+    let output = backend::g1_mul(&input)?;
+    Ok(PrecompileOutput::new(gas_cost, output.into()))
 }
 
 pub fn run_pair(
@@ -165,53 +120,14 @@ pub fn run_pair(
     gas_limit: u64,
 ) -> PrecompileResult {
     let gas_used = (input.len() / PAIR_ELEMENT_LEN) as u64 * pair_per_point_cost + pair_base_cost;
-    if gas_used > gas_limit {
-        return Err(Error::OutOfGas);
-    }
+    check_gas!(gas_used, gas_limit);
 
     if input.len() % PAIR_ELEMENT_LEN != 0 {
         return Err(Error::Bn128PairLength);
     }
 
-    let success = if input.is_empty() {
-        true
-    } else {
-        let elements = input.len() / PAIR_ELEMENT_LEN;
-
-        let mut mul = Gt::one();
-        for idx in 0..elements {
-            let read_fq_at = |n: usize| {
-                debug_assert!(n < PAIR_ELEMENT_LEN / 32);
-                let start = idx * PAIR_ELEMENT_LEN + n * 32;
-                // SAFETY: We're reading `6 * 32 == PAIR_ELEMENT_LEN` bytes from `input[idx..]`
-                // per iteration. This is guaranteed to be in-bounds.
-                let slice = unsafe { input.get_unchecked(start..start + 32) };
-                Fq::from_slice(slice).map_err(|_| Error::Bn128FieldPointNotAMember)
-            };
-            let ax = read_fq_at(0)?;
-            let ay = read_fq_at(1)?;
-            let bay = read_fq_at(2)?;
-            let bax = read_fq_at(3)?;
-            let bby = read_fq_at(4)?;
-            let bbx = read_fq_at(5)?;
-
-            let a = new_g1_point(ax, ay)?;
-            let b = {
-                let ba = Fq2::new(bax, bay);
-                let bb = Fq2::new(bbx, bby);
-                if ba.is_zero() && bb.is_zero() {
-                    G2::zero()
-                } else {
-                    G2::from(AffineG2::new(ba, bb).map_err(|_| Error::Bn128AffineGFailedToCreate)?)
-                }
-            };
-
-            mul = mul * bn::pairing(a, b);
-        }
-
-        mul == Gt::one()
-    };
-    Ok((gas_used, bool_to_bytes32(success)))
+    let success = input.is_empty() || backend::pairing_check(input)?;
+    Ok(PrecompileOutput::new(gas_used, bool_to_bytes32(success)))
 }
 
 #[cfg(test)]
@@ -240,7 +156,7 @@ mod tests {
         )
         .unwrap();
 
-        let (_, res) = run_add(&input, BYZANTIUM_ADD_GAS_COST, 500).unwrap();
+        let res = run_add(&input, BYZANTIUM_ADD_GAS_COST, 500).unwrap().bytes;
         assert_eq!(res, expected);
 
         // zero sum test
@@ -259,7 +175,7 @@ mod tests {
         )
         .unwrap();
 
-        let (_, res) = run_add(&input, BYZANTIUM_ADD_GAS_COST, 500).unwrap();
+        let res = run_add(&input, BYZANTIUM_ADD_GAS_COST, 500).unwrap().bytes;
         assert_eq!(res, expected);
 
         // out of gas test
@@ -285,7 +201,7 @@ mod tests {
         )
         .unwrap();
 
-        let (_, res) = run_add(&input, BYZANTIUM_ADD_GAS_COST, 500).unwrap();
+        let res = run_add(&input, BYZANTIUM_ADD_GAS_COST, 500).unwrap().bytes;
         assert_eq!(res, expected);
 
         // point not on curve fail
@@ -318,7 +234,9 @@ mod tests {
         )
         .unwrap();
 
-        let (_, res) = run_mul(&input, BYZANTIUM_MUL_GAS_COST, 40_000).unwrap();
+        let res = run_mul(&input, BYZANTIUM_MUL_GAS_COST, 40_000)
+            .unwrap()
+            .bytes;
         assert_eq!(res, expected);
 
         // out of gas test
@@ -348,7 +266,9 @@ mod tests {
         )
         .unwrap();
 
-        let (_, res) = run_mul(&input, BYZANTIUM_MUL_GAS_COST, 40_000).unwrap();
+        let res = run_mul(&input, BYZANTIUM_MUL_GAS_COST, 40_000)
+            .unwrap()
+            .bytes;
         assert_eq!(res, expected);
 
         // no input test
@@ -360,7 +280,9 @@ mod tests {
         )
         .unwrap();
 
-        let (_, res) = run_mul(&input, BYZANTIUM_MUL_GAS_COST, 40_000).unwrap();
+        let res = run_mul(&input, BYZANTIUM_MUL_GAS_COST, 40_000)
+            .unwrap()
+            .bytes;
         assert_eq!(res, expected);
 
         // point not on curve fail
@@ -398,13 +320,14 @@ mod tests {
             hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
                 .unwrap();
 
-        let (_, res) = run_pair(
+        let res = run_pair(
             &input,
             BYZANTIUM_PAIR_PER_POINT,
             BYZANTIUM_PAIR_BASE,
             260_000,
         )
-        .unwrap();
+        .unwrap()
+        .bytes;
         assert_eq!(res, expected);
 
         // out of gas test
@@ -439,13 +362,14 @@ mod tests {
             hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
                 .unwrap();
 
-        let (_, res) = run_pair(
+        let res = run_pair(
             &input,
             BYZANTIUM_PAIR_PER_POINT,
             BYZANTIUM_PAIR_BASE,
             260_000,
         )
-        .unwrap();
+        .unwrap()
+        .bytes;
         assert_eq!(res, expected);
 
         // point not on curve fail
@@ -0,0 +1,191 @@
+//! bn128 backend using the arkworks crates.
+//!
+//! Enabled with the `bn128-arkworks` feature, the `substrate-bn` backend is still built and
+//! is used in the differential tests.
+use super::{ADD_INPUT_LEN, MUL_INPUT_LEN, PAIR_ELEMENT_LEN};
+use crate::Error;
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInt, BigInteger, One, PrimeField, Zero};
+use std::vec::Vec;
+
+/// Reads the big endian 256-bit number into little endian limbs.
+///
+/// # Panics
+///
+/// Panics if the input is not at least 32 bytes long.
+#[inline]
+fn read_limbs(input: &[u8]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = 24 - i * 8;
+        *limb = u64::from_be_bytes(input[start..start + 8].try_into().unwrap());
+    }
+    limbs
+}
+
+/// Reads a single `Fq` from the input slice.
+///
+/// # Panics
+///
+/// Panics if the input is not at least 32 bytes long.
+#[inline]
+pub fn read_fq(input: &[u8]) -> Result<Fq, Error> {
+    Fq::from_bigint(BigInt::new(read_limbs(input))).ok_or(Error::Bn128FieldPointNotAMember)
+}
+
+/// Creates a new `G1` point from the given `x` and `y` coordinates.
+pub fn new_g1_point(px: Fq, py: Fq) -> Result<G1Affine, Error> {
+    if px.is_zero() && py.is_zero() {
+        return Ok(<G1Affine as AffineRepr>::zero());
+    }
+    // `G1` has cofactor one, every point on the curve is in the subgroup.
+    let point = G1Affine::new_unchecked(px, py);
+    if !point.is_on_curve() {
+        return Err(Error::Bn128AffineGFailedToCreate);
+    }
+    Ok(point)
+}
+
+/// Creates a new `G2` point from the given `x` and `y` coordinates.
+pub fn new_g2_point(x: Fq2, y: Fq2) -> Result<G2Affine, Error> {
+    if x.is_zero() && y.is_zero() {
+        return Ok(<G2Affine as AffineRepr>::zero());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::Bn128AffineGFailedToCreate);
+    }
+    Ok(point)
+}
+
+/// Reads the `x` and `y` points from the input slice.
+///
+/// # Panics
+///
+/// Panics if the input is not at least 64 bytes long.
+#[inline]
+pub fn read_point(input: &[u8]) -> Result<G1Affine, Error> {
+    let px = read_fq(&input[0..32])?;
+    let py = read_fq(&input[32..64])?;
+    new_g1_point(px, py)
+}
+
+/// Encodes the `G1` point, the point at infinity is encoded as zeros.
+fn encode_g1(point: G1Affine) -> [u8; 64] {
+    let mut output = [0u8; 64];
+    if let Some((x, y)) = point.xy() {
+        output[..32].copy_from_slice(&(*x).into_bigint().to_bytes_be());
+        output[32..].copy_from_slice(&(*y).into_bigint().to_bytes_be());
+    }
+    output
+}
+
+/// Adds the two encoded `G1` points and returns the encoded sum.
+pub fn g1_add(input: &[u8; ADD_INPUT_LEN]) -> Result<[u8; 64], Error> {
+    let p1 = read_point(&input[..64])?;
+    let p2 = read_point(&input[64..])?;
+    Ok(encode_g1((p1.into_group() + p2).into_affine()))
+}
+
+/// Multiplies the encoded `G1` point with the scalar and returns the encoded product.
+///
+/// Scalar is not reduced, multiplying by the full 256-bit number gives the same point.
+pub fn g1_mul(input: &[u8; MUL_INPUT_LEN]) -> Result<[u8; 64], Error> {
+    let p = read_point(&input[..64])?;
+    let scalar = read_limbs(&input[64..96]);
+    Ok(encode_g1(p.mul_bigint(scalar).into_affine()))
+}
+
+/// Returns true if the product of the pairings of the encoded `G1` and `G2` points is one.
+///
+/// Input length needs to be a multiple of [PAIR_ELEMENT_LEN].
+pub fn pairing_check(input: &[u8]) -> Result<bool, Error> {
+    debug_assert_eq!(input.len() % PAIR_ELEMENT_LEN, 0);
+    let elements = input.len() / PAIR_ELEMENT_LEN;
+
+    let mut g1 = Vec::with_capacity(elements);
+    let mut g2 = Vec::with_capacity(elements);
+    for element in input.chunks_exact(PAIR_ELEMENT_LEN) {
+        // Same order of checks as the `substrate-bn` backend: all coordinates are read
+        // before the points are created, so both return the same error.
+        let read_fq_at = |n: usize| read_fq(&element[n * 32..]);
+        let ax = read_fq_at(0)?;
+        let ay = read_fq_at(1)?;
+        let bay = read_fq_at(2)?;
+        let bax = read_fq_at(3)?;
+        let bby = read_fq_at(4)?;
+        let bbx = read_fq_at(5)?;
+
+        g1.push(new_g1_point(ax, ay)?);
+        g2.push(new_g2_point(Fq2::new(bax, bay), Fq2::new(bbx, bby))?);
+    }
+
+    Ok(Bn254::multi_pairing(g1, g2).0.is_one())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bn128::substrate,
+        testing::{
+            bn128_add_input, bn128_adversarial_input, bn128_mul_input,
+            bn128_pair_adversarial_input, bn128_pair_input, InputGenerator, InputRng,
+        },
+        utilities::right_pad,
+    };
+
+    const ITERATIONS: u64 = 256;
+
+    fn inputs(generators: &[InputGenerator]) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let mut rng = InputRng::new(0x6b6e);
+        (0..ITERATIONS).flat_map(move |_| {
+            generators
+                .iter()
+                .map(|generate| generate(&mut rng).to_vec())
+                .collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn add_matches_substrate() {
+        for input in inputs(&[bn128_add_input, bn128_adversarial_input]) {
+            let input = right_pad::<ADD_INPUT_LEN>(&input);
+            assert_eq!(
+                g1_add(&input),
+                substrate::g1_add(&input),
+                "input: {:x?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn mul_matches_substrate() {
+        for input in inputs(&[bn128_mul_input, bn128_adversarial_input]) {
+            let input = right_pad::<MUL_INPUT_LEN>(&input);
+            assert_eq!(
+                g1_mul(&input),
+                substrate::g1_mul(&input),
+                "input: {:x?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn pairing_matches_substrate() {
+        for input in inputs(&[bn128_pair_input, bn128_pair_adversarial_input]) {
+            if input.len() % PAIR_ELEMENT_LEN != 0 {
+                continue;
+            }
+            assert_eq!(
+                pairing_check(&input),
+                substrate::pairing_check(&input),
+                "input: {:x?}",
+                input
+            );
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! bn128 backend using the `substrate-bn` crate.
+use super::{ADD_INPUT_LEN, MUL_INPUT_LEN, PAIR_ELEMENT_LEN};
+use crate::Error;
+use bn::{AffineG1, AffineG2, Fq, Fq2, Group, Gt, G1, G2};
+
+/// Reads a single `Fq` from the input slice.
+///
+/// # Panics
+///
+/// Panics if the input is not at least 32 bytes long.
+#[inline]
+pub fn read_fq(input: &[u8]) -> Result<Fq, Error> {
+    Fq::from_slice(&input[..32]).map_err(|_| Error::Bn128FieldPointNotAMember)
+}
+
+/// Reads the `x` and `y` points from the input slice.
+///
+/// # Panics
+///
+/// Panics if the input is not at least 64 bytes long.
+#[inline]
+pub fn read_point(input: &[u8]) -> Result<G1, Error> {
+    let px = read_fq(&input[0..32])?;
+    let py = read_fq(&input[32..64])?;
+    new_g1_point(px, py)
+}
+
+/// Creates a new `G1` point from the given `x` and `y` coordinates.
+pub fn new_g1_point(px: Fq, py: Fq) -> Result<G1, Error> {
+    if px == Fq::zero() && py == Fq::zero() {
+        Ok(G1::zero())
+    } else {
+        AffineG1::new(px, py)
+            .map(Into::into)
+            .map_err(|_| Error::Bn128AffineGFailedToCreate)
+    }
+}
+
+/// Encodes the `G1` point, the point at infinity is encoded as zeros.
+fn encode_g1(point: G1) -> [u8; 64] {
+    let mut output = [0u8; 64];
+    if let Some(point) = AffineG1::from_jacobian(point) {
+        point.x().to_big_endian(&mut output[..32]).unwrap();
+        point.y().to_big_endian(&mut output[32..]).unwrap();
+    }
+    output
+}
+
+/// Adds the two encoded `G1` points and returns the encoded sum.
+pub fn g1_add(input: &[u8; ADD_INPUT_LEN]) -> Result<[u8; 64], Error> {
+    let p1 = read_point(&input[..64])?;
+    let p2 = read_point(&input[64..])?;
+    Ok(encode_g1(p1 + p2))
+}
+
+/// Multiplies the encoded `G1` point with the scalar and returns the encoded product.
+pub fn g1_mul(input: &[u8; MUL_INPUT_LEN]) -> Result<[u8; 64], Error> {
+    let p = read_point(&input[..64])?;
+
+    // `Fr::from_slice` can only fail when the length is not 32.
+    let fr = bn::Fr::from_slice(&input[64..96]).unwrap();
+
+    Ok(encode_g1(p * fr))
+}
+
+/// Returns true if the product of the pairings of the encoded `G1` and `G2` points is one.
+///
+/// Input length needs to be a multiple of [PAIR_ELEMENT_LEN].
+pub fn pairing_check(input: &[u8]) -> Result<bool, Error> {
+    debug_assert_eq!(input.len() % PAIR_ELEMENT_LEN, 0);
+    let elements = input.len() / PAIR_ELEMENT_LEN;
+
+    let mut mul = Gt::one();
+    for idx in 0..elements {
+        let read_fq_at = |n: usize| {
+            debug_assert!(n < PAIR_ELEMENT_LEN / 32);
+            let start = idx * PAIR_ELEMENT_LEN + n * 32;
+            // SAFETY: We're reading `6 * 32 == PAIR_ELEMENT_LEN` bytes from `input[idx..]`
+            // per iteration. This is guaranteed to be in-bounds.
+            let slice = unsafe { input.get_unchecked(start..start + 32) };
+            Fq::from_slice(slice).map_err(|_| Error::Bn128FieldPointNotAMember)
+        };
+        let ax = read_fq_at(0)?;
+        let ay = read_fq_at(1)?;
+        let bay = read_fq_at(2)?;
+        let bax = read_fq_at(3)?;
+        let bby = read_fq_at(4)?;
+        let bbx = read_fq_at(5)?;
+
+        let a = new_g1_point(ax, ay)?;
+        let b = {
+            let ba = Fq2::new(bax, bay);
+            let bb = Fq2::new(bbx, bby);
+            if ba.is_zero() && bb.is_zero() {
+                G2::zero()
+            } else {
+                G2::from(AffineG2::new(ba, bb).map_err(|_| Error::Bn128AffineGFailedToCreate)?)
+            }
+        };
+
+        mul = mul * bn::pairing(a, b);
+    }
+
+    Ok(mul == Gt::one())
+}
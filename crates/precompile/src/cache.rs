@@ -0,0 +1,161 @@
+//! Caching layer for pure precompiles.
+//!
+//! Outputs of precompiles that depend only on their input are memoized in a LRU cache
+//! keyed by precompile address and input. Cache is shared between all precompiles
+//! created by the same [CachedPrecompiles], so it survives across transactions.
+use crate::{
+    bn128, modexp, primitives::Env, secp256k1, Address, Bytes, Precompile, PrecompileError,
+    PrecompileResult, PrecompileWithAddress, Precompiles, StandardPrecompileFn, StatefulPrecompile,
+};
+use core::num::NonZeroUsize;
+use lru::LruCache;
+use std::sync::{Arc, Mutex};
+
+/// Addresses of precompiles that are cached by default: ecrecover, modexp and bn128
+/// add, mul and pairing.
+pub const DEFAULT_CACHED_ADDRESSES: [Address; 5] = [
+    secp256k1::ECRECOVER.0,
+    modexp::BYZANTIUM.0,
+    bn128::add::ISTANBUL.0,
+    bn128::mul::ISTANBUL.0,
+    bn128::pair::ISTANBUL.0,
+];
+
+/// Cached outcome of a precompile call.
+///
+/// Out of gas errors are not cached as they depend on the gas limit.
+type CachedResult = PrecompileResult;
+
+type SharedCache = Arc<Mutex<LruCache<(Address, Bytes), CachedResult>>>;
+
+/// Wraps standard precompiles with a LRU cache shared across all wrapped precompiles.
+#[derive(Clone, Debug)]
+pub struct CachedPrecompiles {
+    cache: SharedCache,
+}
+
+impl CachedPrecompiles {
+    /// Creates new cache that holds up to `size` precompile outputs.
+    pub fn new(size: NonZeroUsize) -> Self {
+        Self {
+            cache: Arc::new(Mutex::new(LruCache::new(size))),
+        }
+    }
+
+    /// Returns copy of precompiles where precompiles at [DEFAULT_CACHED_ADDRESSES] are cached.
+    pub fn wrap(&self, precompiles: &Precompiles) -> Precompiles {
+        self.wrap_addresses(precompiles, &DEFAULT_CACHED_ADDRESSES)
+    }
+
+    /// Returns copy of precompiles where precompiles at given addresses are cached.
+    ///
+    /// Only [Precompile::Standard] precompiles are wrapped, as others can depend on
+    /// environment or internal state.
+    pub fn wrap_addresses(&self, precompiles: &Precompiles, addresses: &[Address]) -> Precompiles {
+        let mut precompiles = precompiles.clone();
+        for address in addresses {
+            if let Some(&Precompile::Standard(fun)) = precompiles.get(address) {
+                precompiles
+                    .inner
+                    .insert(*address, self.cached(*address, fun));
+            }
+        }
+        precompiles
+    }
+
+    /// Wraps a single standard precompile.
+    pub fn wrap_precompile(
+        &self,
+        PrecompileWithAddress(address, precompile): PrecompileWithAddress,
+    ) -> PrecompileWithAddress {
+        match precompile {
+            Precompile::Standard(fun) => PrecompileWithAddress(address, self.cached(address, fun)),
+            precompile => PrecompileWithAddress(address, precompile),
+        }
+    }
+
+    /// Returns number of cached outputs.
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+
+    /// Returns true if cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached outputs.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    fn cached(&self, address: Address, fun: StandardPrecompileFn) -> Precompile {
+        Precompile::new_stateful(CachedPrecompile {
+            address,
+            fun,
+            cache: self.cache.clone(),
+        })
+    }
+}
+
+/// Standard precompile with its outputs cached.
+struct CachedPrecompile {
+    address: Address,
+    fun: StandardPrecompileFn,
+    cache: SharedCache,
+}
+
+impl StatefulPrecompile for CachedPrecompile {
+    fn call(&self, bytes: &Bytes, gas_limit: u64, _env: &Env) -> PrecompileResult {
+        let key = (self.address, bytes.clone());
+        if let Some(result) = self.cache.lock().unwrap().get(&key) {
+            return match result {
+                Ok(output) if output.gas_used > gas_limit => Err(PrecompileError::OutOfGas),
+                result => result.clone(),
+            };
+        }
+
+        let result = (self.fun)(bytes, gas_limit);
+        if result != Err(PrecompileError::OutOfGas) {
+            self.cache.lock().unwrap().put(key, result.clone());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrecompileSpecId;
+
+    #[test]
+    fn cached_ecrecover() {
+        let cache = CachedPrecompiles::new(NonZeroUsize::new(1).unwrap());
+        let mut precompiles = cache.wrap(Precompiles::new(PrecompileSpecId::LATEST));
+        let ecrecover = precompiles.get_mut(&secp256k1::ECRECOVER.0).unwrap();
+        assert!(matches!(ecrecover, Precompile::Stateful(_)));
+
+        let input = Bytes::from([1u8; 128]);
+        let env = Env::default();
+        let expected = secp256k1::ec_recover_run(&input, 10_000);
+        assert_eq!(ecrecover.call(&input, 10_000, &env), expected);
+        assert_eq!(cache.len(), 1);
+        // served from the cache, gas limit is still checked.
+        assert_eq!(ecrecover.call(&input, 10_000, &env), expected);
+        assert_eq!(
+            ecrecover.call(&input, 100, &env),
+            Err(PrecompileError::OutOfGas)
+        );
+
+        // out of gas is not cached and least recently used entry is evicted.
+        let other = Bytes::from([2u8; 128]);
+        ecrecover.call(&other, 100, &env).unwrap_err();
+        assert_eq!(cache.len(), 1);
+        ecrecover.call(&other, 10_000, &env).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        // sha256 is not cached by default.
+        let sha256 = precompiles.get(&crate::hash::SHA256.0).unwrap();
+        assert!(matches!(sha256, Precompile::Standard(_)));
+    }
+}
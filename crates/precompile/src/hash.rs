@@ -1,5 +1,5 @@
 use super::calc_linear_cost_u32;
-use crate::{Error, Precompile, PrecompileResult, PrecompileWithAddress};
+use crate::{Precompile, PrecompileOutput, PrecompileResult, PrecompileWithAddress};
 use bcevm_primitives::Bytes;
 use sha2::Digest;
 
@@ -16,12 +16,9 @@ pub const RIPEMD160: PrecompileWithAddress = PrecompileWithAddress(
 /// See: <https://etherscan.io/address/0000000000000000000000000000000000000002>
 pub fn sha256_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     let cost = calc_linear_cost_u32(input.len(), 60, 12);
-    if cost > gas_limit {
-        Err(Error::OutOfGas)
-    } else {
-        let output = sha2::Sha256::digest(input);
-        Ok((cost, output.to_vec().into()))
-    }
+    check_gas!(cost, gas_limit);
+    let output = sha2::Sha256::digest(input);
+    Ok(PrecompileOutput::new(cost, output.to_vec().into()))
 }
 
 /// See: <https://ethereum.github.io/yellowpaper/paper.pdf>
@@ -29,14 +26,11 @@ pub fn sha256_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
 /// See: <https://etherscan.io/address/0000000000000000000000000000000000000003>
 pub fn ripemd160_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     let gas_used = calc_linear_cost_u32(input.len(), 600, 120);
-    if gas_used > gas_limit {
-        Err(Error::OutOfGas)
-    } else {
-        let mut hasher = ripemd::Ripemd160::new();
-        hasher.update(input);
+    check_gas!(gas_used, gas_limit);
+    let mut hasher = ripemd::Ripemd160::new();
+    hasher.update(input);
 
-        let mut output = [0u8; 32];
-        hasher.finalize_into((&mut output[12..]).into());
-        Ok((gas_used, output.to_vec().into()))
-    }
+    let mut output = [0u8; 32];
+    hasher.finalize_into((&mut output[12..]).into());
+    Ok(PrecompileOutput::new(gas_used, output.to_vec().into()))
 }
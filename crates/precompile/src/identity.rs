@@ -1,5 +1,5 @@
 use super::calc_linear_cost_u32;
-use crate::{Error, Precompile, PrecompileResult, PrecompileWithAddress};
+use crate::{Precompile, PrecompileOutput, PrecompileResult, PrecompileWithAddress};
 use bcevm_primitives::Bytes;
 
 pub const FUN: PrecompileWithAddress =
@@ -16,8 +16,6 @@ pub const IDENTITY_PER_WORD: u64 = 3;
 /// See: <https://etherscan.io/address/0000000000000000000000000000000000000004>
 pub fn identity_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     let gas_used = calc_linear_cost_u32(input.len(), IDENTITY_BASE, IDENTITY_PER_WORD);
-    if gas_used > gas_limit {
-        return Err(Error::OutOfGas);
-    }
-    Ok((gas_used, input.clone()))
+    check_gas!(gas_used, gas_limit);
+    Ok(PrecompileOutput::new(gas_used, input.clone()))
 }
@@ -1,6 +1,8 @@
-use crate::{Address, Error, Precompile, PrecompileResult, PrecompileWithAddress};
-use c_kzg::{Bytes32, Bytes48, KzgProof, KzgSettings};
+use crate::{
+    Address, Error, Precompile, PrecompileOutput, PrecompileResult, PrecompileWithAddress,
+};
 use bcevm_primitives::{hex_literal::hex, Bytes, Env};
+use c_kzg::{Bytes32, Bytes48, KzgProof, KzgSettings};
 use sha2::{Digest, Sha256};
 
 pub const POINT_EVALUATION: PrecompileWithAddress =
@@ -25,9 +27,7 @@ pub const RETURN_VALUE: &[u8; 64] = &hex!(
 /// |     32         | 32  | 32  |     48     |   48  |
 /// with z and y being padded 32 byte big endian values
 pub fn run(input: &Bytes, gas_limit: u64, env: &Env) -> PrecompileResult {
-    if gas_limit < GAS_COST {
-        return Err(Error::OutOfGas);
-    }
+    check_gas!(GAS_COST, gas_limit);
 
     // Verify input length.
     if input.len() != 192 {
@@ -51,7 +51,7 @@ pub fn run(input: &Bytes, gas_limit: u64, env: &Env) -> PrecompileResult {
     }
 
     // Return FIELD_ELEMENTS_PER_BLOB and BLS_MODULUS as padded 32 byte big endian values
-    Ok((GAS_COST, RETURN_VALUE.into()))
+    Ok(PrecompileOutput::new(GAS_COST, RETURN_VALUE.into()))
 }
 
 /// `VERSIONED_HASH_VERSION_KZG ++ sha256(commitment)[1..]`
@@ -113,8 +113,8 @@ mod tests {
         let expected_output = hex!("000000000000000000000000000000000000000000000000000000000000100073eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
         let gas = 50000;
         let env = Env::default();
-        let (actual_gas, actual_output) = run(&input.into(), gas, &env).unwrap();
-        assert_eq!(actual_gas, gas);
-        assert_eq!(actual_output[..], expected_output);
+        let output = run(&input.into(), gas, &env).unwrap();
+        assert_eq!(output.gas_used, gas);
+        assert_eq!(output.bytes[..], expected_output);
     }
 }
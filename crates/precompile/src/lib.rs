@@ -10,46 +10,39 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc as std;
 
+#[macro_use]
+mod macros;
+
 pub mod blake2;
 pub mod bn128;
+#[cfg(feature = "cache")]
+pub mod cache;
 pub mod hash;
 pub mod identity;
 #[cfg(feature = "c-kzg")]
 pub mod kzg_point_evaluation;
 pub mod modexp;
 pub mod secp256k1;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
 pub mod utilities;
 
-use core::hash::Hash;
-use once_cell::race::OnceBox;
 #[doc(hidden)]
 pub use bcevm_primitives as primitives;
 pub use bcevm_primitives::{
     precompile::{PrecompileError as Error, *},
     Address, Bytes, HashMap, Log, B256,
 };
+#[cfg(feature = "cache")]
+pub use cache::CachedPrecompiles;
+use core::hash::Hash;
+use once_cell::race::OnceBox;
 use std::{boxed::Box, vec::Vec};
 
 pub fn calc_linear_cost_u32(len: usize, base: u64, word: u64) -> u64 {
     (len as u64 + 32 - 1) / 32 * word + base
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
-pub struct PrecompileOutput {
-    pub cost: u64,
-    pub output: Vec<u8>,
-    pub logs: Vec<Log>,
-}
-
-impl PrecompileOutput {
-    pub fn without_logs(cost: u64, output: Vec<u8>) -> Self {
-        Self {
-            cost,
-            output,
-            logs: Vec::new(),
-        }
-    }
-}
 #[derive(Clone, Default, Debug)]
 pub struct Precompiles {
     /// Precompiles.
@@ -65,6 +58,7 @@ impl Precompiles {
             PrecompileSpecId::ISTANBUL => Self::istanbul(),
             PrecompileSpecId::BERLIN => Self::berlin(),
             PrecompileSpecId::CANCUN => Self::cancun(),
+            PrecompileSpecId::EXPERIMENTAL => Self::experimental(),
             PrecompileSpecId::LATEST => Self::latest(),
         }
     }
@@ -156,9 +150,21 @@ impl Precompiles {
         })
     }
 
+    /// Returns precompiles for the experimental spec.
+    ///
+    /// Precompiles that are still in progress are added here before they are part of a
+    /// fork. There are none at the moment, so the set matches Cancun.
+    pub fn experimental() -> &'static Self {
+        static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
+        INSTANCE.get_or_init(|| {
+            let precompiles = Self::cancun().clone();
+            Box::new(precompiles)
+        })
+    }
+
     /// Returns the precompiles for the latest spec.
     pub fn latest() -> &'static Self {
-        Self::cancun()
+        Self::experimental()
     }
 
     /// Returns an iterator over the precompiles addresses.
@@ -207,6 +213,31 @@ impl Precompiles {
     pub fn extend(&mut self, other: impl IntoIterator<Item = PrecompileWithAddress>) {
         self.inner.extend(other.into_iter().map(Into::into));
     }
+
+    /// Returns a copy of the precompiles with the precompiles moved to other addresses.
+    ///
+    /// Each `(from, to)` pair moves the precompile at `from` to `to`, overwriting the
+    /// precompile at `to` if there is one. Pairs are applied at once, so precompiles can be
+    /// swapped. Pairs with `from` that is not a precompile are ignored.
+    pub fn with_remapped_addresses(
+        &self,
+        remap: impl IntoIterator<Item = (Address, Address)>,
+    ) -> Self {
+        let mut precompiles = self.clone();
+        let moved: Vec<_> = remap
+            .into_iter()
+            .filter_map(|(from, to)| Some((from, to, self.inner.get(&from)?.clone())))
+            .collect();
+        for (from, ..) in &moved {
+            precompiles.inner.remove(from);
+        }
+        precompiles.inner.extend(
+            moved
+                .into_iter()
+                .map(|(_, to, precompile)| (to, precompile)),
+        );
+        precompiles
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -231,6 +262,7 @@ pub enum PrecompileSpecId {
     ISTANBUL,
     BERLIN,
     CANCUN,
+    EXPERIMENTAL,
     LATEST,
 }
 
@@ -246,6 +278,7 @@ impl PrecompileSpecId {
             ISTANBUL | MUIR_GLACIER => Self::ISTANBUL,
             BERLIN | LONDON | ARROW_GLACIER | GRAY_GLACIER | MERGE | SHANGHAI => Self::BERLIN,
             CANCUN | PRAGUE => Self::CANCUN,
+            EXPERIMENTAL => Self::EXPERIMENTAL,
             LATEST => Self::LATEST,
             #[cfg(feature = "optimism")]
             BEDROCK | REGOLITH | CANYON => Self::BERLIN,
@@ -266,3 +299,36 @@ pub const fn u64_to_address(x: u64) -> Address {
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, x[0], x[1], x[2], x[3], x[4], x[5], x[6], x[7],
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remapped_addresses() {
+        let precompiles = Precompiles::berlin();
+        let sha256 = u64_to_address(2);
+        let ripemd = u64_to_address(3);
+        let custom = u64_to_address(0x100);
+        let remapped = precompiles.with_remapped_addresses([
+            (sha256, ripemd),
+            (ripemd, sha256),
+            (identity::FUN.0, custom),
+            (u64_to_address(0x200), u64_to_address(0x300)),
+        ]);
+
+        assert_eq!(remapped.len(), precompiles.len());
+        assert!(!remapped.contains(&identity::FUN.0));
+        assert!(!remapped.contains(&u64_to_address(0x300)));
+        // ripemd160 is called at the sha256 address.
+        let input = Bytes::from_static(b"abc");
+        let env = primitives::Env::default();
+        let mut moved = remapped.get(&sha256).unwrap().clone();
+        let mut expected = hash::RIPEMD160.1;
+        assert_eq!(
+            moved.call(&input, 10_000, &env),
+            expected.call(&input, 10_000, &env)
+        );
+        assert!(remapped.contains(&custom));
+    }
+}
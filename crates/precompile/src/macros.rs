@@ -0,0 +1,12 @@
+/// Returns [PrecompileError::OutOfGas](crate::PrecompileError::OutOfGas) from the
+/// precompile if the gas cost exceeds the gas limit.
+///
+/// A cost equal to the limit uses all of the gas and is not an error.
+#[macro_export]
+macro_rules! check_gas {
+    ($cost:expr, $limit:expr) => {
+        if $cost > $limit {
+            return Err($crate::PrecompileError::OutOfGas);
+        }
+    };
+}
@@ -1,11 +1,16 @@
 use crate::{
     primitives::U256,
-    utilities::{left_pad, left_pad_vec, right_pad_vec, right_pad_with_offset},
-    Error, Precompile, PrecompileResult, PrecompileWithAddress,
+    utilities::{left_pad, left_pad_vec, right_pad_with_offset, right_pad_with_offset_vec},
+    Error, Precompile, PrecompileOutput, PrecompileResult, PrecompileWithAddress,
 };
-use aurora_engine_modexp::modexp;
-use core::cmp::{max, min};
 use bcevm_primitives::Bytes;
+use core::cmp::{max, min};
+use std::vec::Vec;
+
+/// Length in bytes of the base or modulus from which the GMP backend is used. Smaller operands
+/// are faster without the conversions.
+#[cfg(feature = "gmp")]
+pub const GMP_MIN_OPERAND_LEN: usize = 64;
 
 pub const BYZANTIUM: PrecompileWithAddress = PrecompileWithAddress(
     crate::u64_to_address(5),
@@ -46,12 +51,10 @@ pub fn calculate_iteration_count(exp_length: u64, exp_highp: &U256) -> u64 {
 
 pub fn run_inner<F>(input: &[u8], gas_limit: u64, min_gas: u64, calc_gas: F) -> PrecompileResult
 where
-    F: FnOnce(u64, u64, u64, &U256) -> u64,
+    F: Fn(u64, u64, u64, &U256) -> u64,
 {
     // If there is no minimum gas, return error.
-    if min_gas > gas_limit {
-        return Err(Error::OutOfGas);
-    }
+    check_gas!(min_gas, gas_limit);
 
     // The format of input is:
     // <length_of_BASE> <length_of_EXPONENT> <length_of_MODULUS> <BASE> <EXPONENT> <MODULUS>
@@ -74,7 +77,7 @@ where
 
     // Handle a special case when both the base and mod length are zero.
     if base_len == 0 && mod_len == 0 {
-        return Ok((min_gas, Bytes::new()));
+        return Ok(PrecompileOutput::new(min_gas, Bytes::new()));
     }
 
     // Cast exponent length to usize, since it does not make sense to handle larger values.
@@ -82,6 +85,13 @@ where
         return Err(Error::ModexpModOverflow);
     };
 
+    // Reject from the lengths alone, before the operands are read. Gas with zero exponent
+    // head is the lowest gas for these lengths.
+    check_gas!(
+        calc_gas(base_len as u64, exp_len as u64, mod_len as u64, &U256::ZERO),
+        gas_limit
+    );
+
     // Used to extract ADJUSTED_EXPONENT_LENGTH.
     let exp_highp_len = min(exp_len, 32);
 
@@ -98,22 +108,51 @@ where
 
     // Check if we have enough gas.
     let gas_cost = calc_gas(base_len as u64, exp_len as u64, mod_len as u64, &exp_highp);
-    if gas_cost > gas_limit {
-        return Err(Error::OutOfGas);
-    }
+    check_gas!(gas_cost, gas_limit);
 
-    // Padding is needed if the input does not contain all 3 values.
-    let input_len = base_len.saturating_add(exp_len).saturating_add(mod_len);
-    let input = right_pad_vec(input, input_len);
-    let (base, input) = input.split_at(base_len);
-    let (exponent, modulus) = input.split_at(exp_len);
-    debug_assert_eq!(modulus.len(), mod_len);
+    // Padding is needed if the input does not contain all 3 values, only the truncated
+    // values are copied.
+    let base = right_pad_with_offset_vec(input, 0, base_len);
+    let exponent = right_pad_with_offset_vec(input, base_len, exp_len);
+    let modulus = right_pad_with_offset_vec(input, base_len.saturating_add(exp_len), mod_len);
 
     // Call the modexp.
-    let output = modexp(base, exponent, modulus);
+    let output = modexp(&base, &exponent, &modulus);
 
     // left pad the result to modulus length. bytes will always by less or equal to modulus length.
-    Ok((gas_cost, left_pad_vec(&output, mod_len).into_owned().into()))
+    Ok(PrecompileOutput::new(
+        gas_cost,
+        left_pad_vec(&output, mod_len).into_owned().into(),
+    ))
+}
+
+/// Computes `base ^ exponent % modulus` of big-endian operands.
+///
+/// With the `gmp` feature, GMP is used if the base or modulus has at least
+/// [GMP_MIN_OPERAND_LEN] bytes.
+pub fn modexp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
+    #[cfg(feature = "gmp")]
+    if max(base.len(), modulus.len()) >= GMP_MIN_OPERAND_LEN {
+        return gmp_modexp(base, exponent, modulus);
+    }
+    aurora_engine_modexp::modexp(base, exponent, modulus)
+}
+
+/// [modexp] with GMP.
+#[cfg(feature = "gmp")]
+pub fn gmp_modexp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
+    use rug::{integer::Order, Integer};
+
+    let modulus = Integer::from_digits(modulus, Order::Msf);
+    if modulus == 0 {
+        return Vec::new();
+    }
+    let base = Integer::from_digits(base, Order::Msf);
+    let exponent = Integer::from_digits(exponent, Order::Msf);
+    // the exponent is not negative, so this never fails.
+    base.pow_mod(&exponent, &modulus)
+        .map(|result| result.to_digits(Order::Msf))
+        .unwrap_or_default()
 }
 
 pub fn byzantium_gas_calc(base_len: u64, exp_len: u64, mod_len: u64, exp_highp: &U256) -> u64 {
@@ -350,11 +389,11 @@ mod tests {
             let res = byzantium_run(&input, 100_000_000).unwrap();
             let expected = hex::decode(test.expected).unwrap();
             assert_eq!(
-                res.0, test_gas,
+                res.gas_used, test_gas,
                 "used gas not matching for test: {}",
                 test.name
             );
-            assert_eq!(res.1, expected, "test:{}", test.name);
+            assert_eq!(res.bytes, expected, "test:{}", test.name);
         }
     }
 
@@ -365,11 +404,11 @@ mod tests {
             let res = berlin_run(&input, 100_000_000).unwrap();
             let expected = hex::decode(test.expected).unwrap();
             assert_eq!(
-                res.0, test_gas,
+                res.gas_used, test_gas,
                 "used gas not matching for test: {}",
                 test.name
             );
-            assert_eq!(res.1, expected, "test:{}", test.name);
+            assert_eq!(res.bytes, expected, "test:{}", test.name);
         }
     }
 
@@ -377,6 +416,41 @@ mod tests {
     fn test_berlin_modexp_empty_input() {
         let res = berlin_run(&Bytes::new(), 100_000).unwrap();
         let expected: Vec<u8> = Vec::new();
-        assert_eq!(res.1, expected)
+        assert_eq!(res.bytes, expected)
+    }
+
+    #[test]
+    fn test_modexp_rejects_lengths_before_reading() {
+        // 1 MiB modulus with a 4 byte input, rejected without padding the operands.
+        let mut input = [0u8; 96];
+        input[31] = 1;
+        input[63] = 1;
+        input[93] = 0x10;
+        let res = berlin_run(&Bytes::copy_from_slice(&input), 30_000_000);
+        assert_eq!(res, Err(Error::OutOfGas));
+    }
+
+    #[cfg(feature = "gmp")]
+    #[test]
+    fn test_gmp_modexp() {
+        use crate::utilities::right_pad_vec;
+
+        for test in TESTS.iter() {
+            let input = hex::decode(test.input).unwrap();
+            let lens: Vec<usize> = (0..3)
+                .map(|i| U256::from_be_slice(&input[i * 32..i * 32 + 32]).to())
+                .collect();
+            let input = right_pad_vec(&input[96..], lens.iter().sum());
+            let (base, input) = input.split_at(lens[0]);
+            let (exponent, modulus) = input.split_at(lens[1]);
+            let expected = aurora_engine_modexp::modexp(base, exponent, modulus);
+            let output = gmp_modexp(base, exponent, modulus);
+            assert_eq!(
+                left_pad_vec(&output, lens[2]),
+                left_pad_vec(&expected, lens[2]),
+                "test '{}' failed",
+                test.name
+            );
+        }
     }
 }
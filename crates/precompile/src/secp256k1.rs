@@ -1,4 +1,6 @@
-use crate::{utilities::right_pad, Error, Precompile, PrecompileResult, PrecompileWithAddress};
+use crate::{
+    utilities::right_pad, Precompile, PrecompileOutput, PrecompileResult, PrecompileWithAddress,
+};
 use bcevm_primitives::{alloy_primitives::B512, Bytes, B256};
 
 pub const ECRECOVER: PrecompileWithAddress = PrecompileWithAddress(
@@ -8,11 +10,11 @@ pub const ECRECOVER: PrecompileWithAddress = PrecompileWithAddress(
 
 pub use self::secp256k1::ecrecover;
 
-#[cfg(not(feature = "secp256k1"))]
+#[cfg(any(not(feature = "secp256k1"), feature = "pure-rust"))]
 #[allow(clippy::module_inception)]
 mod secp256k1 {
-    use k256::ecdsa::{Error, RecoveryId, Signature, VerifyingKey};
     use bcevm_primitives::{alloy_primitives::B512, keccak256, B256};
+    use k256::ecdsa::{Error, RecoveryId, Signature, VerifyingKey};
 
     pub fn ecrecover(sig: &B512, mut recid: u8, msg: &B256) -> Result<B256, Error> {
         // parse signature
@@ -40,7 +42,7 @@ mod secp256k1 {
     }
 }
 
-#[cfg(feature = "secp256k1")]
+#[cfg(all(feature = "secp256k1", not(feature = "pure-rust")))]
 #[allow(clippy::module_inception)]
 mod secp256k1 {
     use bcevm_primitives::{alloy_primitives::B512, keccak256, B256};
@@ -69,15 +71,13 @@ mod secp256k1 {
 pub fn ec_recover_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     const ECRECOVER_BASE: u64 = 3_000;
 
-    if ECRECOVER_BASE > gas_limit {
-        return Err(Error::OutOfGas);
-    }
+    check_gas!(ECRECOVER_BASE, gas_limit);
 
     let input = right_pad::<128>(input);
 
     // `v` must be a 32-byte big-endian integer equal to 27 or 28.
     if !(input[32..63].iter().all(|&b| b == 0) && matches!(input[63], 27 | 28)) {
-        return Ok((ECRECOVER_BASE, Bytes::new()));
+        return Ok(PrecompileOutput::new(ECRECOVER_BASE, Bytes::new()));
     }
 
     let msg = <&B256>::try_from(&input[0..32]).unwrap();
@@ -87,5 +87,24 @@ pub fn ec_recover_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     let out = secp256k1::ecrecover(sig, recid, msg)
         .map(|o| o.to_vec().into())
         .unwrap_or_default();
-    Ok((ECRECOVER_BASE, out))
+    Ok(PrecompileOutput::new(ECRECOVER_BASE, out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bcevm_primitives::hex;
+
+    #[test]
+    fn ecrecover_valid_signature() {
+        let input = Bytes::from(hex!("38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e000000000000000000000000000000000000000000000000000000000000001b38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e789d1dd423d25f0772d2748d60f7e4b81bb14d086eba8e8e8efb6dcff8a4ae02"));
+        let output = ec_recover_run(&input, 3_000).unwrap();
+        assert_eq!(output.gas_used, 3_000);
+        assert_eq!(
+            output.bytes,
+            Bytes::from(hex!(
+                "000000000000000000000000ceaccac640adf55b2028469bd36ba501f28b699d"
+            ))
+        );
+    }
 }
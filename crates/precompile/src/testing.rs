@@ -0,0 +1,505 @@
+//! Input generators and property checks for precompiles.
+//!
+//! Generators produce well-formed inputs that exercise the success paths of a precompile
+//! and adversarial inputs like truncated data, oversized length fields and points that are
+//! not on the curve. [check_precompile] runs a precompile over generated inputs and returns
+//! the first violated property, so the same suite can be run on custom precompiles with
+//! their own generators.
+//!
+//! Randomness is deterministic and seeded, so failures are reproducible.
+
+use crate::{
+    bn128, hash, identity, modexp, primitives::Env, u64_to_address, Address, Bytes, Error,
+    Precompile, PrecompileOutput, Precompiles, StandardPrecompileFn,
+};
+use bcevm_primitives::U256;
+use bn::{AffineG1, AffineG2, Fr, Group, G1, G2};
+use core::fmt;
+use std::{vec, vec::Vec};
+
+/// Gas limit used for the checks, big enough for any well-formed input.
+pub const GAS_LIMIT: u64 = 30_000_000;
+
+/// Generates an input for a precompile.
+pub type InputGenerator = fn(&mut InputRng) -> Bytes;
+
+/// Deterministic pseudo random generator of inputs, based on SplitMix64.
+#[derive(Clone, Debug)]
+pub struct InputRng {
+    state: u64,
+}
+
+impl InputRng {
+    /// Creates a new generator from the seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number in `0..n`. `n` needs to be positive.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns a random boolean.
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// Returns `len` random bytes.
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next_u64() as u8).collect()
+    }
+
+    /// Returns 32 random bytes.
+    pub fn word(&mut self) -> [u8; 32] {
+        let mut word = [0; 32];
+        for chunk in word.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_be_bytes());
+        }
+        word
+    }
+}
+
+/// Property of a precompile violated by an input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertyViolation {
+    /// Address of the precompile.
+    pub address: Address,
+    /// Input that violates the property.
+    pub input: Bytes,
+    /// Description of the property.
+    pub property: &'static str,
+}
+
+impl fmt::Display for PropertyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "precompile {} violates `{}` with input {}",
+            self.address, self.property, self.input
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PropertyViolation {}
+
+/// Checks the properties that every precompile needs to hold on the input:
+///
+/// - Execution is deterministic.
+/// - Used gas is not above the gas limit.
+/// - Execution with exactly the used gas succeeds with the same output, and with one gas
+///   less fails with [Error::OutOfGas].
+pub fn check_properties(
+    address: Address,
+    precompile: &mut Precompile,
+    input: &Bytes,
+) -> Result<(), PropertyViolation> {
+    let env = Env::default();
+    let violation = |property| PropertyViolation {
+        address,
+        input: input.clone(),
+        property,
+    };
+
+    let result = precompile.call(input, GAS_LIMIT, &env);
+    if precompile.call(input, GAS_LIMIT, &env) != result {
+        return Err(violation("deterministic execution"));
+    }
+    let Ok(output) = result else {
+        return Ok(());
+    };
+    let gas_used = output.gas_used;
+    if gas_used > GAS_LIMIT {
+        return Err(violation("gas used is within the gas limit"));
+    }
+    if precompile.call(input, gas_used, &env) != Ok(output) {
+        return Err(violation("succeeds with the used gas"));
+    }
+    if gas_used > 0 && precompile.call(input, gas_used - 1, &env) != Err(Error::OutOfGas) {
+        return Err(violation("runs out of gas below the used gas"));
+    }
+    Ok(())
+}
+
+/// Runs [check_properties] on `iterations` inputs from each generator.
+pub fn check_precompile(
+    address: Address,
+    precompile: &mut Precompile,
+    generators: &[InputGenerator],
+    rng: &mut InputRng,
+    iterations: usize,
+) -> Result<(), PropertyViolation> {
+    for generator in generators {
+        for _ in 0..iterations {
+            check_properties(address, precompile, &generator(rng))?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns well-formed and adversarial generators of the Ethereum precompile at the address.
+pub fn generators(address: &Address) -> Option<[InputGenerator; 2]> {
+    let generators: [InputGenerator; 2] = match address {
+        a if *a == u64_to_address(1) => [ecrecover_input, ecrecover_adversarial_input],
+        a if *a == u64_to_address(2) || *a == u64_to_address(3) || *a == u64_to_address(4) => {
+            [random_input, random_input]
+        }
+        a if *a == u64_to_address(5) => [modexp_random_input, modexp_adversarial_input],
+        a if *a == u64_to_address(6) => [bn128_add_input, bn128_adversarial_input],
+        a if *a == u64_to_address(7) => [bn128_mul_input, bn128_adversarial_input],
+        a if *a == u64_to_address(8) => [bn128_pair_input, bn128_pair_adversarial_input],
+        a if *a == u64_to_address(9) => [blake2_input, blake2_adversarial_input],
+        _ => return None,
+    };
+    Some(generators)
+}
+
+/// Runs [check_precompile] on every precompile of the set that has [generators].
+pub fn check_precompiles(
+    precompiles: &Precompiles,
+    seed: u64,
+    iterations: usize,
+) -> Result<(), PropertyViolation> {
+    let mut rng = InputRng::new(seed);
+    // sorted, so the inputs depend only on the seed.
+    let mut addresses: Vec<_> = precompiles.addresses().collect();
+    addresses.sort();
+    for address in addresses {
+        if let Some(generators) = generators(address) {
+            let mut precompile = precompiles.inner[address].clone();
+            check_precompile(*address, &mut precompile, &generators, &mut rng, iterations)?;
+        }
+    }
+    Ok(())
+}
+
+/// Random bytes of up to 256 bytes.
+pub fn random_input(rng: &mut InputRng) -> Bytes {
+    let len = rng.below(257);
+    rng.bytes(len).into()
+}
+
+/// Encodes the modexp input.
+pub fn modexp_input(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Bytes {
+    let mut input = Vec::new();
+    for len in [base.len(), exponent.len(), modulus.len()] {
+        input.extend_from_slice(&U256::from(len).to_be_bytes::<32>());
+    }
+    input.extend_from_slice(base);
+    input.extend_from_slice(exponent);
+    input.extend_from_slice(modulus);
+    input.into()
+}
+
+/// Modexp input with operands of up to 64 bytes.
+pub fn modexp_random_input(rng: &mut InputRng) -> Bytes {
+    let (base_len, exp_len, mod_len) = (rng.below(65), rng.below(65), rng.below(65));
+    modexp_input(
+        &rng.bytes(base_len),
+        &rng.bytes(exp_len),
+        &rng.bytes(mod_len),
+    )
+}
+
+/// Modexp input with huge length fields, a truncated body or a zero modulus.
+pub fn modexp_adversarial_input(rng: &mut InputRng) -> Bytes {
+    match rng.below(4) {
+        0 => {
+            // huge length field at a random position.
+            let mut input = modexp_random_input(rng).to_vec();
+            let field = rng.below(3) * 32;
+            input[field..field + 32].copy_from_slice(&rng.word());
+            input.into()
+        }
+        1 => {
+            let input = modexp_random_input(rng);
+            let len = rng.below(input.len() + 1);
+            input.slice(..len)
+        }
+        2 => {
+            let (base_len, mod_len) = (rng.below(65), rng.below(65));
+            modexp_input(&rng.bytes(base_len), &rng.word(), &vec![0; mod_len])
+        }
+        _ => {
+            // long exponent with the high bits set.
+            let exp_len = rng.below(1024) + 1;
+            let mut exponent = rng.bytes(exp_len);
+            exponent[0] = 0xff;
+            modexp_input(&rng.bytes(32), &exponent, &rng.bytes(32))
+        }
+    }
+}
+
+/// Ecrecover input with a `v` of 27 or 28, usually with an invalid signature.
+pub fn ecrecover_input(rng: &mut InputRng) -> Bytes {
+    let mut input = vec![0; 128];
+    input[..32].copy_from_slice(&rng.word());
+    input[63] = 27 + rng.bool() as u8;
+    input[64..96].copy_from_slice(&rng.word());
+    input[96..].copy_from_slice(&rng.word());
+    input.into()
+}
+
+/// Ecrecover input with a wrong `v`, zero or overflowing `r` and `s`, or a wrong length.
+pub fn ecrecover_adversarial_input(rng: &mut InputRng) -> Bytes {
+    let mut input = ecrecover_input(rng).to_vec();
+    match rng.below(4) {
+        0 => input[32..64].copy_from_slice(&rng.word()),
+        1 => input[64..96].fill(0),
+        2 => input[96..].fill(0xff),
+        _ => input.truncate(rng.below(128)),
+    }
+    input.into()
+}
+
+/// Returns a random multiple of the `G1` generator.
+fn g1_point(rng: &mut InputRng) -> G1 {
+    G1::one() * fr(rng)
+}
+
+/// Returns a random scalar.
+fn fr(rng: &mut InputRng) -> Fr {
+    let mut word = rng.word();
+    // keeps the scalar below the group order.
+    word[0] &= 0x0f;
+    Fr::from_slice(&word).expect("scalar is below the group order")
+}
+
+fn encode_g1(point: G1, out: &mut Vec<u8>) {
+    let mut encoded = [0; 64];
+    if let Some(point) = AffineG1::from_jacobian(point) {
+        point.x().to_big_endian(&mut encoded[..32]).unwrap();
+        point.y().to_big_endian(&mut encoded[32..]).unwrap();
+    }
+    out.extend_from_slice(&encoded);
+}
+
+fn encode_g2(point: G2, out: &mut Vec<u8>) {
+    let mut encoded = [0; 128];
+    if let Some(point) = AffineG2::from_jacobian(point) {
+        let coordinates = [
+            point.x().imaginary(),
+            point.x().real(),
+            point.y().imaginary(),
+            point.y().real(),
+        ];
+        for (chunk, coordinate) in encoded.chunks_mut(32).zip(coordinates) {
+            coordinate.to_big_endian(chunk).unwrap();
+        }
+    }
+    out.extend_from_slice(&encoded);
+}
+
+/// Addition of two points on the curve, one of them possibly zero.
+pub fn bn128_add_input(rng: &mut InputRng) -> Bytes {
+    let mut input = Vec::with_capacity(bn128::ADD_INPUT_LEN);
+    encode_g1(g1_point(rng), &mut input);
+    let other = if rng.below(8) == 0 {
+        G1::zero()
+    } else {
+        g1_point(rng)
+    };
+    encode_g1(other, &mut input);
+    input.into()
+}
+
+/// Multiplication of a point on the curve with a scalar.
+pub fn bn128_mul_input(rng: &mut InputRng) -> Bytes {
+    let mut input = Vec::with_capacity(bn128::MUL_INPUT_LEN);
+    encode_g1(g1_point(rng), &mut input);
+    input.extend_from_slice(&rng.word());
+    input.into()
+}
+
+/// Addition or multiplication input with coordinates that are not field elements, points
+/// that are not on the curve, or a truncated point.
+pub fn bn128_adversarial_input(rng: &mut InputRng) -> Bytes {
+    let mut input = bn128_add_input(rng).to_vec();
+    match rng.below(3) {
+        0 => input[..32].fill(0xff),
+        1 => input[32..64].copy_from_slice(&U256::from(1).to_be_bytes::<32>()),
+        _ => input.truncate(rng.below(bn128::ADD_INPUT_LEN)),
+    }
+    input.into()
+}
+
+/// Pairing check of up to two pairs of points on the curves.
+pub fn bn128_pair_input(rng: &mut InputRng) -> Bytes {
+    let pairs = rng.below(3);
+    let mut input = Vec::with_capacity(pairs * bn128::PAIR_ELEMENT_LEN);
+    for _ in 0..pairs {
+        encode_g1(g1_point(rng), &mut input);
+        encode_g2(G2::one() * fr(rng), &mut input);
+    }
+    input.into()
+}
+
+/// Pairing input with a wrong length or a `G2` point that is not on the curve.
+pub fn bn128_pair_adversarial_input(rng: &mut InputRng) -> Bytes {
+    let mut input = Vec::with_capacity(bn128::PAIR_ELEMENT_LEN);
+    encode_g1(g1_point(rng), &mut input);
+    encode_g2(G2::one(), &mut input);
+    if rng.bool() {
+        input.truncate(rng.below(bn128::PAIR_ELEMENT_LEN));
+    } else {
+        let offset = 64 + rng.below(4) * 32;
+        input[offset..offset + 32].copy_from_slice(&rng.word());
+    }
+    input.into()
+}
+
+/// Blake2 compression input with up to 64 rounds.
+pub fn blake2_input(rng: &mut InputRng) -> Bytes {
+    let mut input = rng.bytes(213);
+    input[..4].copy_from_slice(&(rng.below(65) as u32).to_be_bytes());
+    input[212] = rng.bool() as u8;
+    input.into()
+}
+
+/// Blake2 input with a wrong length, a wrong final block flag or too many rounds.
+pub fn blake2_adversarial_input(rng: &mut InputRng) -> Bytes {
+    let mut input = blake2_input(rng).to_vec();
+    match rng.below(3) {
+        0 => input.truncate(rng.below(213)),
+        1 => input[212] = 2 + rng.below(254) as u8,
+        _ => input[..4].copy_from_slice(&u32::MAX.to_be_bytes()),
+    }
+    input.into()
+}
+
+/// Compares modexp with a reference implementation on [U256] for operands of up to 32
+/// bytes.
+pub fn check_modexp_reference(
+    run: StandardPrecompileFn,
+    rng: &mut InputRng,
+    iterations: usize,
+) -> Result<(), PropertyViolation> {
+    for _ in 0..iterations {
+        let (base_len, exp_len) = (rng.below(33), rng.below(33));
+        let mod_len = rng.below(32) + 1;
+        let (base, exponent, modulus) =
+            (rng.bytes(base_len), rng.bytes(exp_len), rng.bytes(mod_len));
+        let input = modexp_input(&base, &exponent, &modulus);
+        let expected = U256::from_be_slice(&base).pow_mod(
+            U256::from_be_slice(&exponent),
+            U256::from_be_slice(&modulus),
+        );
+        let expected = Bytes::copy_from_slice(&expected.to_be_bytes::<32>()[32 - mod_len..]);
+        if !matches!(run(&input, GAS_LIMIT), Ok(output) if output.bytes == expected) {
+            return Err(PropertyViolation {
+                address: modexp::BERLIN.0,
+                input,
+                property: "matches the reference implementation",
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that doubling a point with addition and multiplication gives the same result.
+pub fn check_bn128_add_mul_consistency(
+    rng: &mut InputRng,
+    iterations: usize,
+) -> Result<(), PropertyViolation> {
+    for _ in 0..iterations {
+        let mut point = Vec::new();
+        encode_g1(g1_point(rng), &mut point);
+        let add_input = [point.as_slice(), &point].concat();
+        let mul_input = [point.as_slice(), &U256::from(2).to_be_bytes::<32>()].concat();
+        let add = bn128::run_add(&add_input, 0, 0);
+        let mul = bn128::run_mul(&mul_input, 0, 0);
+        if add.map(|out| out.bytes) != mul.map(|out| out.bytes) {
+            return Err(PropertyViolation {
+                address: bn128::add::ISTANBUL.0,
+                input: add_input.into(),
+                property: "addition of a point to itself equals multiplication by two",
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the identity precompile returns its input, and that hashes have a fixed
+/// output length.
+pub fn check_identity_and_hashes(
+    rng: &mut InputRng,
+    iterations: usize,
+) -> Result<(), PropertyViolation> {
+    for _ in 0..iterations {
+        let input = random_input(rng);
+        let checks: [(Address, StandardPrecompileFn, Option<usize>); 3] = [
+            (identity::FUN.0, identity::identity_run, None),
+            (hash::SHA256.0, hash::sha256_run, Some(32)),
+            (hash::RIPEMD160.0, hash::ripemd160_run, Some(32)),
+        ];
+        for (address, run, len) in checks {
+            let Ok(PrecompileOutput { bytes: output, .. }) = run(&input, GAS_LIMIT) else {
+                continue;
+            };
+            let valid = match len {
+                Some(len) => output.len() == len,
+                None => output == input,
+            };
+            if !valid {
+                return Err(PropertyViolation {
+                    address,
+                    input,
+                    property: "output matches the input or the hash length",
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secp256k1;
+
+    #[test]
+    fn latest_precompiles() {
+        check_precompiles(Precompiles::latest(), 1, 8).unwrap();
+    }
+
+    #[test]
+    fn reference_checks() {
+        let mut rng = InputRng::new(2);
+        check_modexp_reference(modexp::berlin_run, &mut rng, 256).unwrap();
+        check_modexp_reference(modexp::byzantium_run, &mut rng, 64).unwrap();
+        check_bn128_add_mul_consistency(&mut rng, 32).unwrap();
+        check_identity_and_hashes(&mut rng, 64).unwrap();
+        check_precompile(
+            secp256k1::ECRECOVER.0,
+            &mut secp256k1::ECRECOVER.1.clone(),
+            &[ecrecover_input, ecrecover_adversarial_input],
+            &mut rng,
+            16,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn detects_violation() {
+        fn undercharging(input: &Bytes, _gas_limit: u64) -> crate::PrecompileResult {
+            Ok(PrecompileOutput::new(1, input.clone()))
+        }
+        let violation = check_properties(
+            Address::ZERO,
+            &mut Precompile::Standard(undercharging),
+            &Bytes::from_static(b"input"),
+        )
+        .unwrap_err();
+        assert_eq!(violation.property, "runs out of gas below the used gas");
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{
+    fake_exponential, BlobExcessGasAndPrice, BLOB_GASPRICE_UPDATE_FRACTION, GAS_PER_BLOB,
+    MAX_BLOB_GAS_PER_BLOCK, MIN_BLOB_GASPRICE, TARGET_BLOB_GAS_PER_BLOCK, U256,
+};
+
+/// Blob gas accounting parameters of [EIP-4844].
+///
+/// The default parameters are the ones of Cancun, other chains can use different
+/// targets and update fraction.
+///
+/// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlobGasAccounting {
+    /// Blob gas used by a single blob.
+    pub gas_per_blob: u64,
+    /// Target blob gas per block.
+    pub target_blob_gas_per_block: u64,
+    /// Maximum blob gas per block.
+    pub max_blob_gas_per_block: u64,
+    /// Minimum blob gas price.
+    pub min_blob_gasprice: u64,
+    /// Controls the maximum rate of change of the blob gas price.
+    pub blob_gasprice_update_fraction: u64,
+}
+
+impl Default for BlobGasAccounting {
+    fn default() -> Self {
+        Self::cancun()
+    }
+}
+
+impl BlobGasAccounting {
+    /// Parameters of the Cancun upgrade.
+    pub const fn cancun() -> Self {
+        Self {
+            gas_per_blob: GAS_PER_BLOB,
+            target_blob_gas_per_block: TARGET_BLOB_GAS_PER_BLOCK,
+            max_blob_gas_per_block: MAX_BLOB_GAS_PER_BLOCK,
+            min_blob_gasprice: MIN_BLOB_GASPRICE,
+            blob_gasprice_update_fraction: BLOB_GASPRICE_UPDATE_FRACTION,
+        }
+    }
+
+    /// Returns the maximum number of blobs in a block.
+    #[inline]
+    pub const fn max_blobs_per_block(&self) -> u64 {
+        self.max_blob_gas_per_block / self.gas_per_blob
+    }
+
+    /// Returns the blob gas used by the given number of blobs.
+    #[inline]
+    pub const fn blob_gas(&self, num_blobs: u64) -> u64 {
+        num_blobs.saturating_mul(self.gas_per_blob)
+    }
+
+    /// Calculates the `excess_blob_gas` from the parent header's `excess_blob_gas` and
+    /// `blob_gas_used`.
+    ///
+    /// See also [crate::calc_excess_blob_gas].
+    #[inline]
+    pub const fn calc_excess_blob_gas(
+        &self,
+        parent_excess_blob_gas: u64,
+        parent_blob_gas_used: u64,
+    ) -> u64 {
+        parent_excess_blob_gas
+            .saturating_add(parent_blob_gas_used)
+            .saturating_sub(self.target_blob_gas_per_block)
+    }
+
+    /// Calculates the blob gas price from the header's excess blob gas field.
+    ///
+    /// See also [crate::calc_blob_gasprice].
+    #[inline]
+    pub fn calc_blob_gasprice(&self, excess_blob_gas: u64) -> u128 {
+        fake_exponential(
+            self.min_blob_gasprice,
+            excess_blob_gas,
+            self.blob_gasprice_update_fraction,
+        )
+    }
+
+    /// Returns the excess blob gas and blob gas price of the block following the parent.
+    ///
+    /// Result can be set as [crate::BlockEnv::blob_excess_gas_and_price].
+    #[inline]
+    pub fn next_block_excess_gas_and_price(
+        &self,
+        parent_excess_blob_gas: u64,
+        parent_blob_gas_used: u64,
+    ) -> BlobExcessGasAndPrice {
+        let excess_blob_gas =
+            self.calc_excess_blob_gas(parent_excess_blob_gas, parent_blob_gas_used);
+        BlobExcessGasAndPrice {
+            excess_blob_gas,
+            blob_gasprice: self.calc_blob_gasprice(excess_blob_gas),
+        }
+    }
+
+    /// Predicts the blob gas price of the block following the parent.
+    #[inline]
+    pub fn next_block_blob_gasprice(
+        &self,
+        parent_excess_blob_gas: u64,
+        parent_blob_gas_used: u64,
+    ) -> u128 {
+        self.next_block_excess_gas_and_price(parent_excess_blob_gas, parent_blob_gas_used)
+            .blob_gasprice
+    }
+
+    /// Returns the highest blob gas price that can be reached `blocks` blocks after the
+    /// block with the given excess blob gas, if all blocks in between are full.
+    ///
+    /// Transactions that are paying at least this price stay includable for that many
+    /// blocks.
+    pub fn max_blob_gasprice_after(&self, excess_blob_gas: u64, blocks: u64) -> u128 {
+        let increase = self
+            .max_blob_gas_per_block
+            .saturating_sub(self.target_blob_gas_per_block)
+            .saturating_mul(blocks);
+        self.calc_blob_gasprice(excess_blob_gas.saturating_add(increase))
+    }
+
+    /// Returns the blob fee of the given number of blobs with the blob gas price.
+    #[inline]
+    pub fn blob_fee(&self, num_blobs: u64, blob_gasprice: u128) -> U256 {
+        U256::from(blob_gasprice).saturating_mul(U256::from(self.blob_gas(num_blobs)))
+    }
+
+    /// Returns the maximum blob fee the transaction can pay, the amount that the caller
+    /// balance needs to cover.
+    ///
+    /// See also [crate::Env::calc_max_data_fee].
+    #[inline]
+    pub fn max_blob_fee(&self, num_blobs: u64, max_fee_per_blob_gas: U256) -> U256 {
+        max_fee_per_blob_gas.saturating_mul(U256::from(self.blob_gas(num_blobs)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calc_blob_gasprice, calc_excess_blob_gas};
+
+    #[test]
+    fn cancun_matches_helpers() {
+        let accounting = BlobGasAccounting::default();
+        assert_eq!(accounting.max_blobs_per_block(), 6);
+        for (excess, used) in [
+            (0, 0),
+            (0, 6 * GAS_PER_BLOB),
+            (10_000_000, 2 * GAS_PER_BLOB),
+        ] {
+            let next = accounting.next_block_excess_gas_and_price(excess, used);
+            assert_eq!(next.excess_blob_gas, calc_excess_blob_gas(excess, used));
+            assert_eq!(
+                next.blob_gasprice,
+                calc_blob_gasprice(calc_excess_blob_gas(excess, used))
+            );
+            assert_eq!(next, BlobExcessGasAndPrice::new(next.excess_blob_gas));
+        }
+    }
+
+    #[test]
+    fn predicted_prices() {
+        let accounting = BlobGasAccounting::default();
+        let excess = 10_000_000;
+        let full = accounting.max_blob_gas_per_block;
+        assert_eq!(
+            accounting.max_blob_gasprice_after(excess, 1),
+            accounting.next_block_blob_gasprice(excess, full)
+        );
+        assert!(
+            accounting.max_blob_gasprice_after(excess, 2)
+                > accounting.max_blob_gasprice_after(excess, 1)
+        );
+        assert_eq!(accounting.max_blob_gasprice_after(excess, 0), 19);
+
+        assert_eq!(accounting.blob_fee(2, 3), U256::from(6 * GAS_PER_BLOB));
+        assert_eq!(
+            accounting.max_blob_fee(1, U256::from(5)),
+            U256::from(5 * GAS_PER_BLOB)
+        );
+    }
+}
@@ -130,6 +130,17 @@ impl Bytecode {
         }
     }
 
+    /// Copies the original bytes starting at `offset` into `dest`. Bytes past the end of
+    /// the bytecode are set to zero.
+    #[inline]
+    pub fn copy_original_bytes(&self, offset: usize, dest: &mut [u8]) {
+        let bytes = self.original_byte_slice();
+        let start = offset.min(bytes.len());
+        let copied = (bytes.len() - start).min(dest.len());
+        dest[..copied].copy_from_slice(&bytes[start..start + copied]);
+        dest[copied..].fill(0);
+    }
+
     /// Returns the length of the raw bytes.
     #[inline]
     pub fn len(&self) -> usize {
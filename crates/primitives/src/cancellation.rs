@@ -0,0 +1,34 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Flag that aborts execution when set.
+///
+/// Token is cheap to clone and clones share the same flag, so it can be canceled from
+/// another thread while the EVM is running.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new token that is not canceled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the execution that uses this token.
+    #[inline]
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token was canceled.
+    #[inline]
+    pub fn is_canceled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Clears the flag so the token can be reused.
+    #[inline]
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
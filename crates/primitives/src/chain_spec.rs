@@ -0,0 +1,174 @@
+use crate::SpecId;
+use std::vec::Vec;
+
+/// Condition that activates a hardfork.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ForkCondition {
+    /// Fork is activated at the given block number.
+    Block(u64),
+    /// Fork is activated at the given block timestamp.
+    Timestamp(u64),
+}
+
+impl ForkCondition {
+    /// Returns `true` if fork is active at the given block number and timestamp.
+    #[inline]
+    pub const fn is_active(&self, block: u64, timestamp: u64) -> bool {
+        match *self {
+            Self::Block(activation) => block >= activation,
+            Self::Timestamp(activation) => timestamp >= activation,
+        }
+    }
+}
+
+/// Chain specification: chain id and the fork schedule of the chain.
+///
+/// It is used to resolve [SpecId] that is active at the given block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainSpec {
+    /// Chain id of the chain.
+    pub chain_id: u64,
+    /// Forks of the chain with their activation conditions, sorted by [SpecId].
+    forks: Vec<(SpecId, ForkCondition)>,
+}
+
+impl ChainSpec {
+    /// Creates new chain specification without any forks.
+    pub fn new(chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            forks: Vec::new(),
+        }
+    }
+
+    /// Ethereum mainnet chain specification.
+    pub fn mainnet() -> Self {
+        use ForkCondition::*;
+        Self::new(1)
+            .with_fork(SpecId::FRONTIER, Block(0))
+            .with_fork(SpecId::FRONTIER_THAWING, Block(200_000))
+            .with_fork(SpecId::HOMESTEAD, Block(1_150_000))
+            .with_fork(SpecId::DAO_FORK, Block(1_920_000))
+            .with_fork(SpecId::TANGERINE, Block(2_463_000))
+            .with_fork(SpecId::SPURIOUS_DRAGON, Block(2_675_000))
+            .with_fork(SpecId::BYZANTIUM, Block(4_370_000))
+            .with_fork(SpecId::PETERSBURG, Block(7_280_000))
+            .with_fork(SpecId::ISTANBUL, Block(9_069_000))
+            .with_fork(SpecId::MUIR_GLACIER, Block(9_200_000))
+            .with_fork(SpecId::BERLIN, Block(12_244_000))
+            .with_fork(SpecId::LONDON, Block(12_965_000))
+            .with_fork(SpecId::ARROW_GLACIER, Block(13_773_000))
+            .with_fork(SpecId::GRAY_GLACIER, Block(15_050_000))
+            .with_fork(SpecId::MERGE, Block(15_537_394))
+            .with_fork(SpecId::SHANGHAI, Timestamp(1_681_338_455))
+            .with_fork(SpecId::CANCUN, Timestamp(1_710_338_135))
+    }
+
+    /// Sets the activation condition of the fork, replacing the previous one.
+    pub fn with_fork(mut self, spec_id: SpecId, condition: ForkCondition) -> Self {
+        match self.forks.binary_search_by_key(&spec_id, |(id, _)| *id) {
+            Ok(index) => self.forks[index].1 = condition,
+            Err(index) => self.forks.insert(index, (spec_id, condition)),
+        }
+        self
+    }
+
+    /// Returns forks with their activation conditions, sorted by [SpecId].
+    pub fn forks(&self) -> &[(SpecId, ForkCondition)] {
+        &self.forks
+    }
+
+    /// Returns activation condition of the fork.
+    pub fn fork(&self, spec_id: SpecId) -> Option<ForkCondition> {
+        self.forks
+            .iter()
+            .find(|(id, _)| *id == spec_id)
+            .map(|(_, condition)| *condition)
+    }
+
+    /// Returns the latest [SpecId] active at the given block number and timestamp.
+    ///
+    /// If no fork is active [SpecId::FRONTIER] is returned.
+    pub fn spec_id(&self, block: u64, timestamp: u64) -> SpecId {
+        self.forks
+            .iter()
+            .rev()
+            .find(|(_, condition)| condition.is_active(block, timestamp))
+            .map(|(id, _)| *id)
+            .unwrap_or(SpecId::FRONTIER)
+    }
+}
+
+/// Chains with a handler preset, see [HandlerCfg::for_chain](crate::HandlerCfg::for_chain).
+#[cfg(feature = "chain-presets")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NamedChain {
+    /// Polygon PoS mainnet.
+    Polygon,
+    /// BNB Smart Chain mainnet.
+    Bsc,
+    /// Arbitrum One, running Nitro.
+    Arbitrum,
+}
+
+#[cfg(feature = "chain-presets")]
+impl NamedChain {
+    /// Returns the chain id of the chain.
+    pub const fn chain_id(self) -> u64 {
+        match self {
+            Self::Polygon => 137,
+            Self::Bsc => 56,
+            Self::Arbitrum => 42161,
+        }
+    }
+
+    /// Returns the chain with the given chain id, if it has a preset.
+    pub const fn from_chain_id(chain_id: u64) -> Option<Self> {
+        match chain_id {
+            137 => Some(Self::Polygon),
+            56 => Some(Self::Bsc),
+            42161 => Some(Self::Arbitrum),
+            _ => None,
+        }
+    }
+
+    /// Returns the latest Ethereum spec that the chain has adopted.
+    pub const fn spec_id(self) -> SpecId {
+        match self {
+            Self::Polygon | Self::Bsc | Self::Arbitrum => SpecId::CANCUN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mainnet_spec_id() {
+        let spec = ChainSpec::mainnet();
+        assert_eq!(spec.spec_id(0, 0), SpecId::FRONTIER);
+        assert_eq!(spec.spec_id(7_280_000, 0), SpecId::PETERSBURG);
+        assert_eq!(spec.spec_id(15_537_394, 1_663_224_162), SpecId::MERGE);
+        assert_eq!(spec.spec_id(17_034_870, 1_681_338_455), SpecId::SHANGHAI);
+        assert_eq!(spec.spec_id(19_426_587, 1_710_338_135), SpecId::CANCUN);
+    }
+
+    #[test]
+    fn custom_fork_schedule() {
+        let spec = ChainSpec::new(1337)
+            .with_fork(SpecId::CANCUN, ForkCondition::Timestamp(100))
+            .with_fork(SpecId::LONDON, ForkCondition::Block(0))
+            .with_fork(SpecId::SHANGHAI, ForkCondition::Block(10));
+        assert_eq!(spec.forks()[0].0, SpecId::LONDON);
+        assert_eq!(spec.spec_id(0, 0), SpecId::LONDON);
+        assert_eq!(spec.spec_id(10, 0), SpecId::SHANGHAI);
+        assert_eq!(spec.spec_id(10, 100), SpecId::CANCUN);
+
+        let spec = spec.with_fork(SpecId::SHANGHAI, ForkCondition::Block(20));
+        assert_eq!(spec.fork(SpecId::SHANGHAI), Some(ForkCondition::Block(20)));
+        assert_eq!(spec.spec_id(10, 0), SpecId::LONDON);
+    }
+}
@@ -15,7 +15,7 @@ pub const BLOCK_HASH_HISTORY: usize = 256;
 /// # Note
 ///
 /// This is named `HISTORY_SERVE_WINDOW` in the EIP.
-pub const BLOCKHASH_SERVE_WINDOW: usize = 8192;
+pub const BLOCKHASH_SERVE_WINDOW: usize = 8191;
 
 /// EIP-2935: Serve historical block hashes from state
 ///
@@ -24,7 +24,28 @@ pub const BLOCKHASH_SERVE_WINDOW: usize = 8192;
 /// # Note
 ///
 /// This is named `HISTORY_STORAGE_ADDRESS` in the EIP.
-pub const BLOCKHASH_STORAGE_ADDRESS: Address = address!("25a219378dad9b3503c8268c9ca836a52427a4fb");
+pub const BLOCKHASH_STORAGE_ADDRESS: Address = address!("0000F90827F1C53a10cb7A02335B175320002935");
+
+/// EIP-4788: Beacon block root in the EVM
+///
+/// The address of the contract that stores beacon block roots.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+
+/// EIP-4788: Beacon block root in the EVM
+///
+/// Length of the ring buffers of the timestamps and the roots in the storage of the
+/// beacon roots contract.
+///
+/// # Note
+///
+/// This is named `HISTORY_BUFFER_LENGTH` in the EIP.
+pub const BEACON_ROOTS_HISTORY_LENGTH: u64 = 8191;
+
+/// Caller of the system calls, EIP-4788 and EIP-2935.
+pub const SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+
+/// Gas limit of the system calls.
+pub const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
 
 /// EIP-3860: Limit and meter initcode
 ///
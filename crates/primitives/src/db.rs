@@ -18,6 +18,23 @@ pub trait Database {
     /// Get account code by its hash.
     fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error>;
 
+    /// Copy the original bytes of the code with the given hash, starting at `offset`,
+    /// into `dest`. Bytes past the end of the code are set to zero.
+    ///
+    /// Default implementation loads the full code with [`Database::code_by_hash`].
+    /// Databases that store large contracts can override it to read only the copied range.
+    #[inline]
+    fn copy_code_by_hash(
+        &mut self,
+        code_hash: B256,
+        offset: usize,
+        dest: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.code_by_hash(code_hash)?
+            .copy_original_bytes(offset, dest);
+        Ok(())
+    }
+
     /// Get storage value of address at index.
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error>;
 
@@ -49,6 +66,22 @@ pub trait DatabaseRef {
     /// Get account code by its hash.
     fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error>;
 
+    /// Copy the original bytes of the code with the given hash, starting at `offset`,
+    /// into `dest`. Bytes past the end of the code are set to zero.
+    ///
+    /// See [`Database::copy_code_by_hash`].
+    #[inline]
+    fn copy_code_by_hash_ref(
+        &self,
+        code_hash: B256,
+        offset: usize,
+        dest: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.code_by_hash_ref(code_hash)?
+            .copy_original_bytes(offset, dest);
+        Ok(())
+    }
+
     /// Get storage value of address at index.
     fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error>;
 
@@ -86,6 +119,16 @@ impl<T: DatabaseRef> Database for WrapDatabaseRef<T> {
         self.0.code_by_hash_ref(code_hash)
     }
 
+    #[inline]
+    fn copy_code_by_hash(
+        &mut self,
+        code_hash: B256,
+        offset: usize,
+        dest: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.0.copy_code_by_hash_ref(code_hash, offset, dest)
+    }
+
     #[inline]
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
         self.0.storage_ref(address, index)
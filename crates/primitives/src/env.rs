@@ -1,14 +1,23 @@
+#[cfg(feature = "c-kzg")]
+mod blob_sidecar;
+mod block_builder;
+mod gas_schedule;
 pub mod handler_cfg;
 
+#[cfg(feature = "c-kzg")]
+pub use blob_sidecar::{BlobSidecar, BlobSidecarError};
+pub use block_builder::BlockEnvBuilder;
+pub use gas_schedule::GasScheduleOverrides;
 pub use handler_cfg::{CfgEnvWithHandlerCfg, EnvWithHandlerCfg, HandlerCfg};
 
 use crate::{
-    calc_blob_gasprice, Account, Address, Bytes, HashMap, InvalidHeader, InvalidTransaction, Spec,
-    SpecId, B256, GAS_PER_BLOB, KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK, MAX_INITCODE_SIZE, U256,
-    VERSIONED_HASH_VERSION_KZG,
+    calc_blob_gasprice, keccak256, Account, Address, BlobGasAccounting, Bytes, HashMap, HashSet,
+    InvalidHeader, InvalidTransaction, Spec, SpecId, ValidationIssue, B256, GAS_PER_BLOB,
+    KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK, MAX_INITCODE_SIZE, U256, VERSIONED_HASH_VERSION_KZG,
 };
 use core::cmp::{min, Ordering};
 use core::hash::Hash;
+use core::ops::ControlFlow;
 use std::boxed::Box;
 use std::vec::Vec;
 
@@ -22,6 +31,12 @@ pub struct Env {
     pub block: BlockEnv,
     /// Configuration of the transaction that is being executed.
     pub tx: TxEnv,
+    /// Chain specific context keyed by an arbitrary identifier, e.g. L1 origin data.
+    ///
+    /// Not used by the EVM itself, custom instructions can read it with
+    /// `HostRead::aux_env`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aux_env: HashMap<B256, Bytes>,
 }
 
 impl Env {
@@ -34,7 +49,12 @@ impl Env {
     /// Create boxed [Env].
     #[inline]
     pub fn boxed(cfg: CfgEnv, block: BlockEnv, tx: TxEnv) -> Box<Self> {
-        Box::new(Self { cfg, block, tx })
+        Box::new(Self {
+            cfg,
+            block,
+            tx,
+            aux_env: HashMap::new(),
+        })
     }
 
     /// Calculates the effective gas price of the transaction.
@@ -75,15 +95,46 @@ impl Env {
     /// Validate the block environment.
     #[inline]
     pub fn validate_block_env<SPEC: Spec>(&self) -> Result<(), InvalidHeader> {
+        let mut error = None;
+        let _ = self.visit_block_issues(SPEC::SPEC_ID, &mut |e| {
+            error = Some(e);
+            ControlFlow::Break(())
+        });
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Returns all issues of the block and transaction environment for the given spec.
+    ///
+    /// Runs the same checks as [Env::validate_block_env] and [Env::validate_tx] but does
+    /// not stop at the first one. Checks against the state are not included.
+    pub fn validation_issues(&self, spec: SpecId) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let _ = self.visit_block_issues(spec, &mut |e| {
+            issues.push(ValidationIssue::Header(e));
+            ControlFlow::Continue(())
+        });
+        let _ = self.visit_tx_issues(spec, true, true, &mut |e| {
+            issues.push(ValidationIssue::Transaction(e));
+            ControlFlow::Continue(())
+        });
+        issues
+    }
+
+    /// Reports issues of the block environment until `report` breaks.
+    fn visit_block_issues(
+        &self,
+        spec: SpecId,
+        report: &mut dyn FnMut(InvalidHeader) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
         // `prevrandao` is required for the merge
-        if SPEC::enabled(SpecId::MERGE) && self.block.prevrandao.is_none() {
-            return Err(InvalidHeader::PrevrandaoNotSet);
+        if SpecId::enabled(spec, SpecId::MERGE) && self.block.prevrandao.is_none() {
+            report(InvalidHeader::PrevrandaoNotSet)?;
         }
         // `excess_blob_gas` is required for Cancun
-        if SPEC::enabled(SpecId::CANCUN) && self.block.blob_excess_gas_and_price.is_none() {
-            return Err(InvalidHeader::ExcessBlobGasNotSet);
+        if SpecId::enabled(spec, SpecId::CANCUN) && self.block.blob_excess_gas_and_price.is_none() {
+            report(InvalidHeader::ExcessBlobGasNotSet)?;
         }
-        Ok(())
+        ControlFlow::Continue(())
     }
 
     /// Validate transaction data that is set inside ENV and return error if something is wrong.
@@ -91,20 +142,77 @@ impl Env {
     /// Return initial spend gas (Gas needed to execute transaction).
     #[inline]
     pub fn validate_tx<SPEC: Spec>(&self) -> Result<(), InvalidTransaction> {
+        self.validate_tx_inner::<SPEC>(true, true)
+    }
+
+    /// Same as [Env::validate_tx] but without the chain id check.
+    ///
+    /// Used when chain id is validated separately, see [Env::validate_tx_chain_id].
+    #[inline]
+    pub fn validate_tx_without_chain_id<SPEC: Spec>(&self) -> Result<(), InvalidTransaction> {
+        self.validate_tx_inner::<SPEC>(false, true)
+    }
+
+    /// Same as [Env::validate_tx_without_chain_id] but without the check of the effective
+    /// gas price against the block basefee.
+    ///
+    /// Used by chains that do not charge fees for transactions.
+    #[inline]
+    pub fn validate_tx_without_base_fee<SPEC: Spec>(&self) -> Result<(), InvalidTransaction> {
+        self.validate_tx_inner::<SPEC>(false, false)
+    }
+
+    /// Check if the transaction's chain id matches the one in [CfgEnv].
+    ///
+    /// Legacy transactions without chain id are always valid.
+    #[inline]
+    pub fn validate_tx_chain_id(&self) -> Result<(), InvalidTransaction> {
+        if let Some(tx_chain_id) = self.tx.chain_id {
+            if tx_chain_id != self.cfg.chain_id {
+                return Err(InvalidTransaction::InvalidChainId);
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn validate_tx_inner<SPEC: Spec>(
+        &self,
+        check_chain_id: bool,
+        check_base_fee: bool,
+    ) -> Result<(), InvalidTransaction> {
+        let mut error = None;
+        let _ = self.visit_tx_issues(SPEC::SPEC_ID, check_chain_id, check_base_fee, &mut |e| {
+            error = Some(e);
+            ControlFlow::Break(())
+        });
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Reports issues of the transaction environment until `report` breaks.
+    fn visit_tx_issues(
+        &self,
+        spec: SpecId,
+        check_chain_id: bool,
+        check_base_fee: bool,
+        report: &mut dyn FnMut(InvalidTransaction) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        let enabled = |id| SpecId::enabled(spec, id);
         // BASEFEE tx check
-        if SPEC::enabled(SpecId::LONDON) {
+        if enabled(SpecId::LONDON) {
             if let Some(priority_fee) = self.tx.gas_priority_fee {
                 if priority_fee > self.tx.gas_price {
                     // or gas_max_fee for eip1559
-                    return Err(InvalidTransaction::PriorityFeeGreaterThanMaxFee);
+                    report(InvalidTransaction::PriorityFeeGreaterThanMaxFee)?;
                 }
             }
 
             // check minimal cost against basefee
-            if !self.cfg.is_base_fee_check_disabled()
+            if check_base_fee
+                && !self.cfg.is_base_fee_check_disabled()
                 && self.effective_gas_price() < self.block.basefee
             {
-                return Err(InvalidTransaction::GasPriceLessThanBasefee);
+                report(InvalidTransaction::GasPriceLessThanBasefee)?;
             }
         }
 
@@ -112,47 +220,49 @@ impl Env {
         if !self.cfg.is_block_gas_limit_disabled()
             && U256::from(self.tx.gas_limit) > self.block.gas_limit
         {
-            return Err(InvalidTransaction::CallerGasLimitMoreThanBlock);
+            report(InvalidTransaction::CallerGasLimitMoreThanBlock)?;
         }
 
         // EIP-3860: Limit and meter initcode
-        if SPEC::enabled(SpecId::SHANGHAI) && self.tx.transact_to.is_create() {
+        if enabled(SpecId::SHANGHAI) && self.tx.transact_to.is_create() {
             let max_initcode_size = self
                 .cfg
                 .limit_contract_code_size
                 .map(|limit| limit.saturating_mul(2))
                 .unwrap_or(MAX_INITCODE_SIZE);
             if self.tx.data.len() > max_initcode_size {
-                return Err(InvalidTransaction::CreateInitCodeSizeLimit);
+                report(InvalidTransaction::CreateInitCodeSizeLimit)?;
             }
         }
 
         // Check if the transaction's chain id is correct
-        if let Some(tx_chain_id) = self.tx.chain_id {
-            if tx_chain_id != self.cfg.chain_id {
-                return Err(InvalidTransaction::InvalidChainId);
+        if check_chain_id {
+            if let Err(e) = self.validate_tx_chain_id() {
+                report(e)?;
             }
         }
 
         // Check that access list is empty for transactions before BERLIN
-        if !SPEC::enabled(SpecId::BERLIN) && !self.tx.access_list.is_empty() {
-            return Err(InvalidTransaction::AccessListNotSupported);
+        if !enabled(SpecId::BERLIN) && !self.tx.access_list.is_empty() {
+            report(InvalidTransaction::AccessListNotSupported)?;
         }
 
         // - For CANCUN and later, check that the gas price is not more than the tx max
         // - For before CANCUN, check that `blob_hashes` and `max_fee_per_blob_gas` are empty / not set
-        if SPEC::enabled(SpecId::CANCUN) {
+        if enabled(SpecId::CANCUN) {
             // Presence of max_fee_per_blob_gas means that this is blob transaction.
             if let Some(max) = self.tx.max_fee_per_blob_gas {
                 // ensure that the user was willing to at least pay the current blob gasprice
-                let price = self.block.get_blob_gasprice().expect("already checked");
-                if U256::from(price) > max {
-                    return Err(InvalidTransaction::BlobGasPriceGreaterThanMax);
+                // missing blob gas price is reported by the block checks.
+                if let Some(price) = self.block.get_blob_gasprice() {
+                    if U256::from(price) > max {
+                        report(InvalidTransaction::BlobGasPriceGreaterThanMax)?;
+                    }
                 }
 
                 // there must be at least one blob
                 if self.tx.blob_hashes.is_empty() {
-                    return Err(InvalidTransaction::EmptyBlobs);
+                    report(InvalidTransaction::EmptyBlobs)?;
                 }
 
                 // The field `to` deviates slightly from the semantics with the exception
@@ -160,67 +270,73 @@ impl Env {
                 // a 20-byte address. This means that blob transactions cannot
                 // have the form of a create transaction.
                 if self.tx.transact_to.is_create() {
-                    return Err(InvalidTransaction::BlobCreateTransaction);
+                    report(InvalidTransaction::BlobCreateTransaction)?;
                 }
 
                 // all versioned blob hashes must start with VERSIONED_HASH_VERSION_KZG
-                for blob in self.tx.blob_hashes.iter() {
-                    if blob[0] != VERSIONED_HASH_VERSION_KZG {
-                        return Err(InvalidTransaction::BlobVersionNotSupported);
-                    }
+                if self
+                    .tx
+                    .blob_hashes
+                    .iter()
+                    .any(|blob| blob[0] != VERSIONED_HASH_VERSION_KZG)
+                {
+                    report(InvalidTransaction::BlobVersionNotSupported)?;
                 }
 
                 // ensure the total blob gas spent is at most equal to the limit
                 // assert blob_gas_used <= MAX_BLOB_GAS_PER_BLOCK
                 if self.tx.blob_hashes.len() > MAX_BLOB_NUMBER_PER_BLOCK as usize {
-                    return Err(InvalidTransaction::TooManyBlobs);
+                    report(InvalidTransaction::TooManyBlobs)?;
                 }
             }
         } else {
             if !self.tx.blob_hashes.is_empty() {
-                return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
+                report(InvalidTransaction::BlobVersionedHashesNotSupported)?;
             }
             if self.tx.max_fee_per_blob_gas.is_some() {
-                return Err(InvalidTransaction::MaxFeePerBlobGasNotSupported);
+                report(InvalidTransaction::MaxFeePerBlobGasNotSupported)?;
             }
         }
 
-        if SPEC::enabled(SpecId::PRAGUE) {
+        if enabled(SpecId::EXPERIMENTAL) {
             if !self.tx.eof_initcodes.is_empty() {
                 // If initcode is set other fields must be empty
                 if !self.tx.blob_hashes.is_empty() {
-                    return Err(InvalidTransaction::BlobVersionedHashesNotSupported);
+                    report(InvalidTransaction::BlobVersionedHashesNotSupported)?;
                 }
-                // EOF Create tx extends EIP-1559 tx. It must have max_fee_per_blob_gas
+                // EOF Create tx extends EIP-1559 tx. It must not have max_fee_per_blob_gas
                 if self.tx.max_fee_per_blob_gas.is_some() {
-                    return Err(InvalidTransaction::MaxFeePerBlobGasNotSupported);
+                    report(InvalidTransaction::MaxFeePerBlobGasNotSupported)?;
                 }
                 // EOF Create must have a to address
-                if matches!(self.tx.transact_to, TransactTo::Call(_)) {
-                    return Err(InvalidTransaction::EofCrateShouldHaveToAddress);
+                if self.tx.transact_to.is_create() {
+                    report(InvalidTransaction::EofCrateShouldHaveToAddress)?;
                 }
-            } else {
-                // If initcode is set check its bounds.
+                // Check bounds of the initcodes.
                 if self.tx.eof_initcodes.len() > 256 {
-                    return Err(InvalidTransaction::EofInitcodesNumberLimit);
+                    report(InvalidTransaction::EofInitcodesNumberLimit)?;
                 }
                 if self
                     .tx
-                    .eof_initcodes_hashed
+                    .eof_initcodes
                     .iter()
-                    .any(|(_, i)| i.len() >= MAX_INITCODE_SIZE)
+                    .any(|i| i.len() > MAX_INITCODE_SIZE)
                 {
-                    return Err(InvalidTransaction::EofInitcodesSizeLimit);
+                    report(InvalidTransaction::EofInitcodesSizeLimit)?;
+                }
+                // TXCREATE finds the initcodes by their hashes.
+                if !self.tx.eof_initcodes_hashes_match() {
+                    report(InvalidTransaction::EofInitcodesHashMismatch)?;
                 }
             }
         } else {
             // Initcode set when not supported.
             if !self.tx.eof_initcodes.is_empty() {
-                return Err(InvalidTransaction::EofInitcodesNotSupported);
+                report(InvalidTransaction::EofInitcodesNotSupported)?;
             }
         }
 
-        Ok(())
+        ControlFlow::Continue(())
     }
 
     /// Validate transaction against state.
@@ -229,16 +345,26 @@ impl Env {
         &self,
         account: &mut Account,
     ) -> Result<(), InvalidTransaction> {
-        // EIP-3607: Reject transactions from senders with deployed code
-        // This EIP is introduced after london but there was no collision in past
-        // so we can leave it enabled always
-        if !self.cfg.is_eip3607_disabled() && account.info.code_hash != KECCAK_EMPTY {
-            return Err(InvalidTransaction::RejectCallerWithCode);
-        }
+        self.validate_tx_against_state_inner::<SPEC>(account, true)
+    }
 
-        // Check that the transaction's nonce is correct
+    /// Same as [Env::validate_tx_against_state] but without the nonce check.
+    ///
+    /// Used when nonce is validated separately, see [Env::validate_tx_nonce].
+    #[inline]
+    pub fn validate_tx_against_state_without_nonce<SPEC: Spec>(
+        &self,
+        account: &mut Account,
+    ) -> Result<(), InvalidTransaction> {
+        self.validate_tx_against_state_inner::<SPEC>(account, false)
+    }
+
+    /// Check if the transaction's nonce is equal to the nonce of the caller account.
+    ///
+    /// Transactions without nonce are always valid.
+    #[inline]
+    pub fn validate_tx_nonce(&self, state: u64) -> Result<(), InvalidTransaction> {
         if let Some(tx) = self.tx.nonce {
-            let state = account.info.nonce;
             match tx.cmp(&state) {
                 Ordering::Greater => {
                     return Err(InvalidTransaction::NonceTooHigh { tx, state });
@@ -249,6 +375,26 @@ impl Env {
                 _ => {}
             }
         }
+        Ok(())
+    }
+
+    #[inline]
+    fn validate_tx_against_state_inner<SPEC: Spec>(
+        &self,
+        account: &mut Account,
+        check_nonce: bool,
+    ) -> Result<(), InvalidTransaction> {
+        // EIP-3607: Reject transactions from senders with deployed code
+        // This EIP is introduced after london but there was no collision in past
+        // so we can leave it enabled always
+        if !self.cfg.is_eip3607_disabled() && account.info.code_hash != KECCAK_EMPTY {
+            return Err(InvalidTransaction::RejectCallerWithCode);
+        }
+
+        // Check that the transaction's nonce is correct
+        if check_nonce {
+            self.validate_tx_nonce(account.info.nonce)?;
+        }
 
         let mut balance_check = U256::from(self.tx.gas_limit)
             .checked_mul(self.tx.gas_price)
@@ -301,13 +447,82 @@ pub struct CfgEnv {
     /// If some it will effects EIP-170: Contract code size limit. Useful to increase this because of tests.
     /// By default it is 0x6000 (~25kb).
     pub limit_contract_code_size: Option<usize>,
-    /// A hard memory limit in bytes beyond which [crate::result::OutOfGasError::Memory] cannot be resized.
+    /// Allocates log data and return data of the transaction in a per-transaction arena,
+    /// instead of allocating every buffer separately. Reduces allocator pressure for
+    /// log-heavy workloads, see `BytesArena` in `bcevm-interpreter`.
+    ///
+    /// Default: false
+    pub bytes_arena: bool,
+    /// Records storage accesses of the `SLOAD` and `SSTORE` opcodes in
+    /// [crate::ResultAndState::storage_accesses].
+    ///
+    /// Default: false
+    pub record_storage_accesses: bool,
+    /// Prunes the accounts and storage slots that were only loaded from the returned state,
+    /// see [crate::ResultAndState::prune_unchanged_state].
+    ///
+    /// Default: false
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub prune_unchanged_state: bool,
+    /// Verifies at the end of every successful transaction that the journal was finalized:
+    /// the call depth is zero and the transient storage is empty. The transaction fails
+    /// with [crate::EVMError::Custom] otherwise.
+    ///
+    /// Useful to catch bugs of custom handlers that leave checkpoints open or skip
+    /// finalizing the journal.
+    ///
+    /// Default: false
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub verify_journal_finalized: bool,
+    /// Overrides the constant gas costs of the given opcodes, see [GasScheduleOverrides].
+    ///
+    /// Default: None
+    pub gas_schedule_overrides: Option<GasScheduleOverrides>,
+    /// Moves precompiles to chain specific addresses. Each `(from, to)` pair exposes the
+    /// precompile of the spec at `from` at the `to` address instead.
+    ///
+    /// Default: empty
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub precompile_address_remap: Vec<(Address, Address)>,
+    /// Overrides whether EIP-161 state clear semantics are used, independent of the spec id.
+    ///
+    /// With state clear, empty accounts are treated as not existing and created contracts
+    /// start with nonce 1. Without it, touched empty accounts exist as before Spurious
+    /// Dragon. Gas costs still follow the spec id. If `None`, state clear is enabled from
+    /// [SpecId::SPURIOUS_DRAGON].
+    ///
+    /// Default: None
+    pub state_clear: Option<bool>,
+    /// Overrides whether EIP-6780 SELFDESTRUCT semantics are used, independent of the
+    /// spec id.
+    ///
+    /// With EIP-6780, SELFDESTRUCT removes code and storage only if the contract was
+    /// created in the same transaction, otherwise it only transfers the balance. Without
+    /// it, the account is always destroyed. Gas costs still follow the spec id. If `None`,
+    /// EIP-6780 is enabled from [SpecId::CANCUN].
+    ///
+    /// Default: None
+    pub eip6780: Option<bool>,
+    /// A hard memory limit in bytes beyond which memory cannot be resized. Execution halts
+    /// with [crate::result::OutOfGasError::MemoryLimit] when it is exceeded.
     ///
     /// In cases where the gas limit may be extraordinarily high, it is recommended to set this to
     /// a sane value to prevent memory allocation panics. Defaults to `2^32 - 1` bytes per
     /// EIP-1985.
-    #[cfg(feature = "memory_limit")]
     pub memory_limit: u64,
+    /// Memory limit in bytes of a single call frame, checked in addition to
+    /// [CfgEnv::memory_limit]. Execution of the frame halts with
+    /// [crate::result::OutOfGasError::MemoryLimit] when it is exceeded.
+    ///
+    /// Default: None, memory of a frame is limited only by [CfgEnv::memory_limit].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub frame_memory_limit: Option<u64>,
+    /// Maximum call depth, calls and creates deeper than it fail with `CallTooDeep`. It can
+    /// only lower the limit of 1024 of the mainnet rules.
+    ///
+    /// Default: None, the mainnet limit of 1024.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_call_depth: Option<u64>,
     /// Skip balance checks if true. Adds transaction cost to balance to ensure execution doesn't fail.
     #[cfg(feature = "optional_balance_check")]
     pub disable_balance_check: bool,
@@ -410,10 +625,19 @@ impl Default for CfgEnv {
             chain_id: 1,
             perf_analyse_created_bytecodes: AnalysisKind::default(),
             limit_contract_code_size: None,
+            bytes_arena: false,
+            record_storage_accesses: false,
+            prune_unchanged_state: false,
+            verify_journal_finalized: false,
+            gas_schedule_overrides: None,
+            precompile_address_remap: Vec::new(),
+            state_clear: None,
+            eip6780: None,
             #[cfg(feature = "c-kzg")]
             kzg_settings: crate::kzg::EnvKzgSettings::Default,
-            #[cfg(feature = "memory_limit")]
             memory_limit: (1 << 32) - 1,
+            frame_memory_limit: None,
+            max_call_depth: None,
             #[cfg(feature = "optional_balance_check")]
             disable_balance_check: false,
             #[cfg(feature = "optional_block_gas_limit")]
@@ -469,6 +693,13 @@ pub struct BlockEnv {
     ///
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
     pub blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
+    /// Root of the parent beacon block, stored into the beacon roots contract by the system
+    /// call at the start of the block.
+    ///
+    /// Incorporated as part of the Cancun upgrade via [EIP-4788].
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    pub parent_beacon_block_root: Option<B256>,
 }
 
 impl BlockEnv {
@@ -477,6 +708,19 @@ impl BlockEnv {
     pub fn set_blob_excess_gas_and_price(&mut self, excess_blob_gas: u64) {
         self.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice::new(excess_blob_gas));
     }
+    /// Sets the excess blob gas and blob gas price of the block from the parent header's
+    /// `excess_blob_gas` and `blob_gas_used`, with the Cancun [BlobGasAccounting].
+    pub fn set_blob_excess_gas_and_price_from_parent(
+        &mut self,
+        parent_excess_blob_gas: u64,
+        parent_blob_gas_used: u64,
+    ) {
+        self.blob_excess_gas_and_price = Some(
+            BlobGasAccounting::cancun()
+                .next_block_excess_gas_and_price(parent_excess_blob_gas, parent_blob_gas_used),
+        );
+    }
+
     /// See [EIP-4844] and [`crate::calc_blob_gasprice`].
     ///
     /// Returns `None` if `Cancun` is not enabled. This is enforced in [`Env::validate_block_env`].
@@ -519,6 +763,7 @@ impl Default for BlockEnv {
             difficulty: U256::ZERO,
             prevrandao: Some(B256::ZERO),
             blob_excess_gas_and_price: Some(BlobExcessGasAndPrice::new(0)),
+            parent_beacon_block_root: None,
         }
     }
 }
@@ -587,13 +832,20 @@ pub struct TxEnv {
     /// [EOF]: https://eips.ethereum.org/EIPS/eip-4844
     pub eof_initcodes: Vec<Bytes>,
 
-    /// Internal Temporary field that stores the hashes of the EOF initcodes.
+    /// EOF initcodes by their hashes, used by the `TXCREATE` opcode.
     ///
-    /// Those are always cleared after the transaction is executed.
-    /// And calculated/overwritten every time transaction starts.
-    /// They are calculated from the [`Self::eof_initcodes`] field.
+    /// They are calculated from the [`Self::eof_initcodes`] field by
+    /// [`Self::set_eof_initcodes`] and checked to match it when the transaction is
+    /// validated.
     pub eof_initcodes_hashed: HashMap<B256, Bytes>,
 
+    /// System transaction fields, set if the transaction was created by the chain itself,
+    /// e.g. a deposit bridged from another chain.
+    ///
+    /// System transactions are handled by the
+    /// `bcevm::handler::system_tx::system_tx_handle_register` register.
+    pub system_tx: Option<SystemTx>,
+
     #[cfg_attr(feature = "serde", serde(flatten))]
     #[cfg(feature = "optimism")]
     /// Optimism fields.
@@ -616,11 +868,40 @@ impl TxEnv {
         GAS_PER_BLOB * self.blob_hashes.len() as u64
     }
 
+    /// Returns `true` if the transaction is a [SystemTx].
+    #[inline]
+    pub fn is_system_tx(&self) -> bool {
+        self.system_tx.is_some()
+    }
+
     /// Clears environment and resets fields to default values.
     #[inline]
     pub fn clear(&mut self) {
         *self = Self::default();
     }
+
+    /// Sets the EOF initcodes of the transaction and calculates their hashes.
+    pub fn set_eof_initcodes(&mut self, initcodes: Vec<Bytes>) {
+        self.eof_initcodes_hashed = initcodes
+            .iter()
+            .map(|initcode| (keccak256(initcode), initcode.clone()))
+            .collect();
+        self.eof_initcodes = initcodes;
+    }
+
+    /// Returns `true` if [`Self::eof_initcodes_hashed`] contains exactly the hashes of
+    /// [`Self::eof_initcodes`].
+    pub fn eof_initcodes_hashes_match(&self) -> bool {
+        let mut hashes = HashSet::new();
+        for initcode in &self.eof_initcodes {
+            let hash = keccak256(initcode);
+            if self.eof_initcodes_hashed.get(&hash) != Some(initcode) {
+                return false;
+            }
+            hashes.insert(hash);
+        }
+        hashes.len() == self.eof_initcodes_hashed.len()
+    }
 }
 
 impl Default for TxEnv {
@@ -640,6 +921,7 @@ impl Default for TxEnv {
             max_fee_per_blob_gas: None,
             eof_initcodes: Vec::new(),
             eof_initcodes_hashed: HashMap::new(),
+            system_tx: None,
             #[cfg(feature = "optimism")]
             optimism: OptimismFields::default(),
         }
@@ -705,6 +987,21 @@ pub struct OptimismFields {
     pub enveloped_tx: Option<Bytes>,
 }
 
+/// Transaction created by the chain itself, like a deposit bridged from another chain.
+///
+/// System transactions are pre-verified by the chain: they are not validated, they do not
+/// pay for gas and the `mint` amount is credited to the caller before the execution. The
+/// minted amount and the nonce bump are persisted even if the transaction reverts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemTx {
+    /// Hash that identifies the source of the transaction, e.g. the hash of the
+    /// bridge event. It makes sure that system transactions do not have identical hashes.
+    pub source_hash: B256,
+    /// The amount credited to the caller before the execution.
+    pub mint: Option<u128>,
+}
+
 /// Transaction destination.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -779,6 +1076,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_tx_without_chain_id() {
+        let mut env = Env::default();
+        env.tx.chain_id = Some(1);
+        env.cfg.chain_id = 2;
+        assert_eq!(
+            env.validate_tx_without_chain_id::<crate::LatestSpec>(),
+            Ok(())
+        );
+        assert_eq!(
+            env.validate_tx_chain_id(),
+            Err(InvalidTransaction::InvalidChainId)
+        );
+    }
+
     #[test]
     fn test_validate_tx_access_list() {
         let mut env = Env::default();
@@ -0,0 +1,217 @@
+//! EIP-4844 blob sidecar verification.
+use super::TxEnv;
+use crate::{
+    kzg::{EnvKzgSettings, KzgSettings},
+    B256, VERSIONED_HASH_VERSION_KZG,
+};
+use c_kzg::{Blob, Bytes48, KzgProof};
+use core::fmt;
+use sha2::{Digest, Sha256};
+use std::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Blobs of a blob transaction with their KZG commitments and proofs.
+///
+/// Sidecar is not part of the executed transaction, only versioned hashes of the commitments
+/// are, see [TxEnv::blob_hashes].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlobSidecar {
+    /// Blobs.
+    pub blobs: Vec<Blob>,
+    /// KZG commitment of each blob.
+    pub commitments: Vec<Bytes48>,
+    /// KZG proof of each blob.
+    pub proofs: Vec<Bytes48>,
+}
+
+/// Error of [TxEnv::verify_blob_sidecar].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlobSidecarError {
+    /// Number of blobs, commitments and proofs is not the number of versioned hashes.
+    LengthMismatch {
+        versioned_hashes: usize,
+        blobs: usize,
+        commitments: usize,
+        proofs: usize,
+    },
+    /// Versioned hash of the commitment is not the versioned hash of the transaction.
+    VersionedHashMismatch {
+        index: usize,
+        expected: B256,
+        got: B256,
+    },
+    /// KZG proofs do not prove the commitments of the blobs.
+    InvalidProof,
+    /// Blob, commitment or proof is malformed.
+    Kzg(String),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlobSidecarError {}
+
+impl fmt::Display for BlobSidecarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch {
+                versioned_hashes,
+                blobs,
+                commitments,
+                proofs,
+            } => write!(
+                f,
+                "sidecar length mismatch: {versioned_hashes} versioned hashes, \
+                 {blobs} blobs, {commitments} commitments, {proofs} proofs"
+            ),
+            Self::VersionedHashMismatch {
+                index,
+                expected,
+                got,
+            } => write!(
+                f,
+                "versioned hash mismatch at index {index}: expected {expected}, got {got}"
+            ),
+            Self::InvalidProof => write!(f, "invalid blob KZG proof"),
+            Self::Kzg(e) => write!(f, "KZG error: {e}"),
+        }
+    }
+}
+
+impl TxEnv {
+    /// Verifies the blob sidecar against the versioned hashes of the transaction, using the
+    /// mainnet trusted setup.
+    ///
+    /// See [TxEnv::verify_blob_sidecar_with_settings].
+    pub fn verify_blob_sidecar(&self, sidecar: &BlobSidecar) -> Result<(), BlobSidecarError> {
+        self.verify_blob_sidecar_with_settings(sidecar, EnvKzgSettings::Default.get())
+    }
+
+    /// Verifies the blob sidecar against the versioned hashes of the transaction.
+    ///
+    /// Every commitment needs to hash to the versioned hash at the same index of
+    /// [TxEnv::blob_hashes] and the proofs need to prove that the commitments are
+    /// commitments of the blobs.
+    pub fn verify_blob_sidecar_with_settings(
+        &self,
+        sidecar: &BlobSidecar,
+        kzg_settings: &KzgSettings,
+    ) -> Result<(), BlobSidecarError> {
+        let versioned_hashes = self.blob_hashes.len();
+        if sidecar.blobs.len() != versioned_hashes
+            || sidecar.commitments.len() != versioned_hashes
+            || sidecar.proofs.len() != versioned_hashes
+        {
+            return Err(BlobSidecarError::LengthMismatch {
+                versioned_hashes,
+                blobs: sidecar.blobs.len(),
+                commitments: sidecar.commitments.len(),
+                proofs: sidecar.proofs.len(),
+            });
+        }
+
+        for (index, (expected, commitment)) in self
+            .blob_hashes
+            .iter()
+            .zip(&sidecar.commitments)
+            .enumerate()
+        {
+            let got = kzg_to_versioned_hash(commitment);
+            if got != *expected {
+                return Err(BlobSidecarError::VersionedHashMismatch {
+                    index,
+                    expected: *expected,
+                    got,
+                });
+            }
+        }
+
+        let valid = KzgProof::verify_blob_kzg_proof_batch(
+            &sidecar.blobs,
+            &sidecar.commitments,
+            &sidecar.proofs,
+            kzg_settings,
+        )
+        .map_err(|e| BlobSidecarError::Kzg(e.to_string()))?;
+        if !valid {
+            return Err(BlobSidecarError::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+/// `VERSIONED_HASH_VERSION_KZG ++ sha256(commitment)[1..]`
+fn kzg_to_versioned_hash(commitment: &Bytes48) -> B256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment.as_slice()).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    B256::from(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_kzg::{KzgCommitment, BYTES_PER_BLOB};
+
+    fn sidecar(settings: &KzgSettings) -> (TxEnv, BlobSidecar) {
+        let mut bytes = [0u8; BYTES_PER_BLOB];
+        // field elements need to be smaller than the modulus.
+        bytes[1] = 0x42;
+        let blob = Blob::from(bytes);
+        let commitment = KzgCommitment::blob_to_kzg_commitment(&blob, settings)
+            .unwrap()
+            .to_bytes();
+        let proof = KzgProof::compute_blob_kzg_proof(&blob, &commitment, settings)
+            .unwrap()
+            .to_bytes();
+        let tx = TxEnv {
+            blob_hashes: vec![kzg_to_versioned_hash(&commitment)],
+            ..Default::default()
+        };
+        let sidecar = BlobSidecar {
+            blobs: vec![blob],
+            commitments: vec![commitment],
+            proofs: vec![proof],
+        };
+        (tx, sidecar)
+    }
+
+    #[test]
+    fn verify_blob_sidecar() {
+        let settings = EnvKzgSettings::Default.get();
+        let (mut tx, mut sidecar) = sidecar(settings);
+        assert_eq!(tx.verify_blob_sidecar(&sidecar), Ok(()));
+
+        let proof = sidecar.proofs[0];
+        sidecar.proofs[0] = sidecar.commitments[0];
+        assert_eq!(
+            tx.verify_blob_sidecar(&sidecar),
+            Err(BlobSidecarError::InvalidProof)
+        );
+        sidecar.proofs[0] = proof;
+
+        let expected = B256::with_last_byte(1);
+        let got = tx.blob_hashes[0];
+        tx.blob_hashes[0] = expected;
+        assert_eq!(
+            tx.verify_blob_sidecar(&sidecar),
+            Err(BlobSidecarError::VersionedHashMismatch {
+                index: 0,
+                expected,
+                got
+            })
+        );
+
+        tx.blob_hashes.push(got);
+        assert_eq!(
+            tx.verify_blob_sidecar(&sidecar),
+            Err(BlobSidecarError::LengthMismatch {
+                versioned_hashes: 2,
+                blobs: 1,
+                commitments: 1,
+                proofs: 1
+            })
+        );
+    }
+}
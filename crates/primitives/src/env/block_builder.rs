@@ -0,0 +1,114 @@
+use super::BlockEnv;
+use crate::{keccak256, B256, U256};
+
+/// Builds a deterministic sequence of [BlockEnv]s for multi-block test scenarios.
+///
+/// Every block increments the number by one and the timestamp by
+/// [the step](BlockEnvBuilder::with_timestamp_step). PREVRANDAO and difficulty are derived
+/// from the seed and the block number, so the same seed always produces the same blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockEnvBuilder {
+    block: BlockEnv,
+    seed: u64,
+    timestamp_step: u64,
+}
+
+impl BlockEnvBuilder {
+    /// Creates a new builder from the seed, starting at [BlockEnv::default] with a
+    /// timestamp step of 12 seconds.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            block: BlockEnv::default(),
+            seed,
+            timestamp_step: 12,
+        }
+    }
+
+    /// Sets the first block of the sequence. Its PREVRANDAO and difficulty are overwritten.
+    pub fn with_block(mut self, block: BlockEnv) -> Self {
+        self.block = block;
+        self
+    }
+
+    /// Sets the number of seconds the timestamp is incremented by every block.
+    pub fn with_timestamp_step(mut self, timestamp_step: u64) -> Self {
+        self.timestamp_step = timestamp_step;
+        self
+    }
+
+    /// Returns the PREVRANDAO of the block with the given number.
+    pub fn prevrandao(&self, number: U256) -> B256 {
+        let mut preimage = [0; 40];
+        preimage[..8].copy_from_slice(&self.seed.to_be_bytes());
+        preimage[8..].copy_from_slice(&number.to_be_bytes::<32>());
+        keccak256(preimage)
+    }
+
+    /// Returns the next block of the sequence and advances the builder.
+    ///
+    /// Difficulty is set to the PREVRANDAO, so the `DIFFICULTY` opcode returns the same
+    /// value before and after the merge.
+    pub fn next_block(&mut self) -> BlockEnv {
+        let prevrandao = self.prevrandao(self.block.number);
+        let mut block = self.block.clone();
+        block.prevrandao = Some(prevrandao);
+        block.difficulty = U256::from_be_bytes(prevrandao.0);
+
+        self.block.number = self.block.number.saturating_add(U256::from(1));
+        self.block.timestamp = self
+            .block
+            .timestamp
+            .saturating_add(U256::from(self.timestamp_step));
+        block
+    }
+}
+
+impl Iterator for BlockEnvBuilder {
+    type Item = BlockEnv;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_block())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn deterministic_sequence() {
+        let blocks: Vec<_> = BlockEnvBuilder::new(7).take(3).collect();
+        assert_eq!(blocks, BlockEnvBuilder::new(7).take(3).collect::<Vec<_>>());
+
+        for (i, block) in blocks.iter().enumerate() {
+            assert_eq!(block.number, U256::from(i));
+            assert_eq!(block.timestamp, U256::from(1 + 12 * i));
+            assert_eq!(
+                block.difficulty,
+                U256::from_be_bytes(block.prevrandao.unwrap().0)
+            );
+        }
+        assert_ne!(blocks[0].prevrandao, blocks[1].prevrandao);
+        assert_ne!(
+            blocks[0].prevrandao,
+            BlockEnvBuilder::new(8).next_block().prevrandao
+        );
+    }
+
+    #[test]
+    fn custom_first_block() {
+        let mut builder = BlockEnvBuilder::new(0)
+            .with_block(BlockEnv {
+                number: U256::from(100),
+                timestamp: U256::from(1000),
+                ..Default::default()
+            })
+            .with_timestamp_step(2);
+        builder.next_block();
+        let block = builder.next_block();
+        assert_eq!(block.number, U256::from(101));
+        assert_eq!(block.timestamp, U256::from(1002));
+        assert_eq!(block.prevrandao, Some(builder.prevrandao(U256::from(101))));
+    }
+}
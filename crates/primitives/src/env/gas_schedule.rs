@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+/// Overrides of the constant gas costs of opcodes, see [super::CfgEnv::gas_schedule_overrides].
+///
+/// The override replaces the base cost that the opcode charges itself. For opcodes with warm
+/// and cold access costs (`SLOAD`, `BALANCE`, `EXTCODESIZE`, `EXTCODEHASH` and
+/// `EXTCODECOPY`), it replaces the warm cost and the cold access surcharge is still charged.
+/// Dynamic costs like memory expansion, copy costs and the costs of `SSTORE`, `LOG*`,
+/// `CALL*`, `CREATE*` and `SELFDESTRUCT` are not affected.
+///
+/// Overrides are applied to the instruction table once, when the handler is built for the
+/// configuration, so they do not slow down the execution of opcodes that are not overridden.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GasScheduleOverrides {
+    costs: BTreeMap<u8, u64>,
+}
+
+impl GasScheduleOverrides {
+    /// Creates an empty set of overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the cost of the opcode.
+    pub fn with_cost(mut self, opcode: u8, gas: u64) -> Self {
+        self.set_cost(opcode, gas);
+        self
+    }
+
+    /// Sets the cost of the opcode.
+    pub fn set_cost(&mut self, opcode: u8, gas: u64) {
+        self.costs.insert(opcode, gas);
+    }
+
+    /// Removes the override of the opcode.
+    pub fn remove_cost(&mut self, opcode: u8) -> Option<u64> {
+        self.costs.remove(&opcode)
+    }
+
+    /// Returns the overridden cost of the opcode.
+    #[inline]
+    pub fn cost(&self, opcode: u8) -> Option<u64> {
+        self.costs.get(&opcode).copied()
+    }
+
+    /// Returns true if no opcode is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.costs.is_empty()
+    }
+
+    /// Returns the overridden opcodes and their costs, ordered by the opcode.
+    pub fn iter(&self) -> impl Iterator<Item = (u8, u64)> + '_ {
+        self.costs.iter().map(|(&opcode, &gas)| (opcode, gas))
+    }
+}
+
+impl FromIterator<(u8, u64)> for GasScheduleOverrides {
+    fn from_iter<T: IntoIterator<Item = (u8, u64)>>(iter: T) -> Self {
+        Self {
+            costs: iter.into_iter().collect(),
+        }
+    }
+}
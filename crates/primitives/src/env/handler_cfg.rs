@@ -1,4 +1,6 @@
 use super::{BlockEnv, CfgEnv, Env, SpecId, TxEnv};
+#[cfg(feature = "chain-presets")]
+use crate::NamedChain;
 use core::ops::{Deref, DerefMut};
 use std::boxed::Box;
 
@@ -12,6 +14,9 @@ pub struct HandlerCfg {
     /// Optimism related field, it will append the Optimism handle register to the EVM.
     #[cfg(feature = "optimism")]
     pub is_optimism: bool,
+    /// Chain whose handler preset is applied on top of the handler.
+    #[cfg(feature = "chain-presets")]
+    pub chain: Option<NamedChain>,
 }
 
 impl Default for HandlerCfg {
@@ -35,6 +40,20 @@ impl HandlerCfg {
             spec_id,
             #[cfg(feature = "optimism")]
             is_optimism,
+            #[cfg(feature = "chain-presets")]
+            chain: None,
+        }
+    }
+
+    /// Creates new `HandlerCfg` instance with the handler preset of the chain, at the latest
+    /// spec of the chain, see [NamedChain::spec_id].
+    ///
+    /// Chain id of the [CfgEnv] is not changed and should be set to [NamedChain::chain_id].
+    #[cfg(feature = "chain-presets")]
+    pub fn for_chain(chain: NamedChain) -> Self {
+        Self {
+            chain: Some(chain),
+            ..Self::new(chain.spec_id())
         }
     }
 
@@ -44,6 +63,8 @@ impl HandlerCfg {
         Self {
             spec_id,
             is_optimism,
+            #[cfg(feature = "chain-presets")]
+            chain: None,
         }
     }
 
@@ -9,7 +9,10 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc as std;
 
+mod blob_gas;
 mod bytecode;
+mod cancellation;
+mod chain_spec;
 mod constants;
 pub mod db;
 pub mod env;
@@ -17,16 +20,21 @@ pub mod env;
 #[cfg(feature = "c-kzg")]
 pub mod kzg;
 pub mod precompile;
+mod receipt;
 pub mod result;
+mod revert;
 pub mod specification;
 pub mod state;
 pub mod utilities;
 pub use alloy_primitives::{
-    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bytes,
-    FixedBytes, Log, LogData, B256, I256, U256,
+    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bloom,
+    BloomInput, Bytes, FixedBytes, Log, LogData, B256, I256, U256,
 };
 pub use bitvec;
+pub use blob_gas::*;
 pub use bytecode::*;
+pub use cancellation::CancellationToken;
+pub use chain_spec::*;
 pub use constants::*;
 pub use env::*;
 
@@ -42,7 +50,9 @@ cfg_if::cfg_if! {
 #[cfg(feature = "c-kzg")]
 pub use kzg::{EnvKzgSettings, KzgSettings};
 pub use precompile::*;
+pub use receipt::*;
 pub use result::*;
+pub use revert::*;
 pub use specification::*;
 pub use state::*;
 pub use utilities::*;
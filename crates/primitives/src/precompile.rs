@@ -1,12 +1,42 @@
-use crate::{Bytes, Env};
+use crate::{Bytes, Env, Log};
 use core::fmt;
 use dyn_clone::DynClone;
-use std::{boxed::Box, string::String, sync::Arc};
+use std::{boxed::Box, string::String, sync::Arc, vec::Vec};
 
 /// A precompile operation result.
 ///
-/// Returns either `Ok((gas_used, return_bytes))` or `Err(error)`.
-pub type PrecompileResult = Result<(u64, Bytes), PrecompileError>;
+/// Returns either `Ok(output)` or `Err(error)`.
+pub type PrecompileResult = Result<PrecompileOutput, PrecompileError>;
+
+/// Output of a successful precompile call.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PrecompileOutput {
+    /// Gas used by the precompile.
+    pub gas_used: u64,
+    /// Output bytes.
+    pub bytes: Bytes,
+    /// Logs emitted by the precompile.
+    ///
+    /// They are appended to the journaled state and reverted with the call.
+    pub logs: Vec<Log>,
+}
+
+impl PrecompileOutput {
+    /// Returns the output without logs.
+    pub fn new(gas_used: u64, bytes: Bytes) -> Self {
+        Self {
+            gas_used,
+            bytes,
+            logs: Vec::new(),
+        }
+    }
+
+    /// Sets the logs of the output.
+    pub fn with_logs(mut self, logs: Vec<Log>) -> Self {
+        self.logs = logs;
+        self
+    }
+}
 
 pub type StandardPrecompileFn = fn(&Bytes, u64) -> PrecompileResult;
 pub type EnvPrecompileFn = fn(&Bytes, u64, env: &Env) -> PrecompileResult;
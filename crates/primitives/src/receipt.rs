@@ -0,0 +1,221 @@
+use crate::{Address, Bloom, Bytes, ExecutionResult, Log, Output, B256};
+use std::vec::Vec;
+
+/// Block and transaction data of a receipt that are not part of the [ExecutionResult].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ReceiptContext {
+    /// Hash of the transaction.
+    pub transaction_hash: B256,
+    /// Index of the transaction in the block.
+    pub transaction_index: u64,
+    /// Hash of the block.
+    pub block_hash: B256,
+    /// Number of the block.
+    pub block_number: u64,
+    /// Gas used by the transactions of the block before this one.
+    pub cumulative_gas_used: u64,
+    /// Number of logs emitted by the transactions of the block before this one.
+    ///
+    /// Log index is the position of the log in the block, not in the transaction.
+    pub first_log_index: u64,
+}
+
+/// Log in the shape of the Ethereum JSON-RPC receipt.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RpcLog {
+    /// Address of the contract that emitted the log.
+    pub address: Address,
+    /// Topics of the log.
+    pub topics: Vec<B256>,
+    /// Data of the log.
+    pub data: Bytes,
+    /// Hash of the block.
+    pub block_hash: B256,
+    /// Number of the block.
+    #[cfg_attr(feature = "serde", serde(with = "quantity"))]
+    pub block_number: u64,
+    /// Hash of the transaction.
+    pub transaction_hash: B256,
+    /// Index of the transaction in the block.
+    #[cfg_attr(feature = "serde", serde(with = "quantity"))]
+    pub transaction_index: u64,
+    /// Index of the log in the block.
+    #[cfg_attr(feature = "serde", serde(with = "quantity"))]
+    pub log_index: u64,
+    /// Whether the log was removed by a chain reorganization. Always `false` for
+    /// executed transactions.
+    pub removed: bool,
+}
+
+/// Receipt in the shape of the Ethereum JSON-RPC `eth_getTransactionReceipt` response.
+///
+/// Contains the fields that are derived from the execution and the [ReceiptContext].
+/// Transaction fields like `from`, `to`, `type` and `effectiveGasPrice` are set by the
+/// RPC server.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RpcReceipt {
+    /// Hash of the transaction.
+    pub transaction_hash: B256,
+    /// Index of the transaction in the block.
+    #[cfg_attr(feature = "serde", serde(with = "quantity"))]
+    pub transaction_index: u64,
+    /// Hash of the block.
+    pub block_hash: B256,
+    /// Number of the block.
+    #[cfg_attr(feature = "serde", serde(with = "quantity"))]
+    pub block_number: u64,
+    /// `1` if the transaction succeeded, `0` if it reverted or halted ([EIP-658]).
+    ///
+    /// [EIP-658]: https://eips.ethereum.org/EIPS/eip-658
+    #[cfg_attr(feature = "serde", serde(with = "quantity"))]
+    pub status: u64,
+    /// Gas used by the transaction.
+    #[cfg_attr(feature = "serde", serde(with = "quantity"))]
+    pub gas_used: u64,
+    /// Gas used by the transactions of the block up to and including this one.
+    #[cfg_attr(feature = "serde", serde(with = "quantity"))]
+    pub cumulative_gas_used: u64,
+    /// Address of the created contract.
+    pub contract_address: Option<Address>,
+    /// Logs of the transaction, empty if it failed.
+    pub logs: Vec<RpcLog>,
+    /// Bloom filter of the logs.
+    pub logs_bloom: Bloom,
+}
+
+impl ExecutionResult {
+    /// Converts the result to a receipt in the shape of the Ethereum JSON-RPC response.
+    pub fn to_rpc_receipt(&self, context: &ReceiptContext) -> RpcReceipt {
+        let logs: Vec<_> = self
+            .logs()
+            .iter()
+            .enumerate()
+            .map(|(i, log)| RpcLog {
+                address: log.address,
+                topics: log.topics().to_vec(),
+                data: log.data.data.clone(),
+                block_hash: context.block_hash,
+                block_number: context.block_number,
+                transaction_hash: context.transaction_hash,
+                transaction_index: context.transaction_index,
+                log_index: context.first_log_index + i as u64,
+                removed: false,
+            })
+            .collect();
+        let contract_address = match self {
+            Self::Success {
+                output: Output::Create(_, address),
+                ..
+            } => *address,
+            _ => None,
+        };
+        RpcReceipt {
+            transaction_hash: context.transaction_hash,
+            transaction_index: context.transaction_index,
+            block_hash: context.block_hash,
+            block_number: context.block_number,
+            status: self.is_success() as u64,
+            gas_used: self.gas_used(),
+            cumulative_gas_used: context.cumulative_gas_used + self.gas_used(),
+            contract_address,
+            logs,
+            logs_bloom: logs_bloom(self.logs()),
+        }
+    }
+}
+
+/// Returns the bloom filter of the logs.
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Bloom {
+    let mut bloom = Bloom::ZERO;
+    for log in logs {
+        bloom.accrue_log(log);
+    }
+    bloom
+}
+
+/// Serializes `u64` as a JSON-RPC quantity, a `0x` prefixed hex string without leading
+/// zeros.
+#[cfg(feature = "serde")]
+mod quantity {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::{format, string::String};
+
+    pub(super) fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:#x}"))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let digits = value
+            .strip_prefix("0x")
+            .ok_or_else(|| D::Error::custom("quantity is missing the 0x prefix"))?;
+        u64::from_str_radix(digits, 16).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address, bytes, LogData, SuccessReason};
+    use std::vec;
+
+    fn result() -> ExecutionResult {
+        let log = Log {
+            address: address!("1000000000000000000000000000000000000001"),
+            data: LogData::new_unchecked(vec![B256::with_last_byte(1)], bytes!("cafe")),
+        };
+        ExecutionResult::Success {
+            reason: SuccessReason::Return,
+            gas_used: 50_000,
+            gas_refunded: 0,
+            logs: vec![log.clone(), log],
+            output: Output::Create(
+                Bytes::new(),
+                Some(address!("2000000000000000000000000000000000000002")),
+            ),
+        }
+    }
+
+    #[test]
+    fn to_rpc_receipt() {
+        let context = ReceiptContext {
+            transaction_index: 3,
+            block_number: 10,
+            cumulative_gas_used: 100_000,
+            first_log_index: 5,
+            ..Default::default()
+        };
+        let receipt = result().to_rpc_receipt(&context);
+        assert_eq!(receipt.status, 1);
+        assert_eq!(receipt.cumulative_gas_used, 150_000);
+        assert_eq!(
+            receipt.contract_address,
+            Some(address!("2000000000000000000000000000000000000002"))
+        );
+        assert_eq!(
+            receipt
+                .logs
+                .iter()
+                .map(|log| log.log_index)
+                .collect::<Vec<_>>(),
+            vec![5, 6]
+        );
+        assert!(receipt.logs_bloom.contains_input(crate::BloomInput::Raw(
+            address!("1000000000000000000000000000000000000001").as_slice()
+        )));
+
+        let reverted = ExecutionResult::Revert {
+            gas_used: 21_000,
+            output: Bytes::new(),
+        };
+        let receipt = reverted.to_rpc_receipt(&context);
+        assert_eq!(receipt.status, 0);
+        assert_eq!(receipt.contract_address, None);
+        assert!(receipt.logs.is_empty());
+        assert_eq!(receipt.logs_bloom, Bloom::ZERO);
+    }
+}
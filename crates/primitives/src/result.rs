@@ -1,4 +1,4 @@
-use crate::{Address, Bytes, Log, State, U256};
+use crate::{Address, Bytes, ErrorFragment, Log, RevertReason, State, U256};
 use core::fmt;
 use std::{boxed::Box, string::String, vec::Vec};
 
@@ -15,6 +15,128 @@ pub struct ResultAndState {
     pub result: ExecutionResult,
     /// State that got updated
     pub state: State,
+    /// Behavior of the transaction that changed between hardforks.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub warnings: Vec<Warning>,
+    /// Storage reads and writes of the `SLOAD` and `SSTORE` opcodes, in execution order.
+    ///
+    /// Recorded only if [crate::CfgEnv::record_storage_accesses] is enabled. Accesses of
+    /// reverted calls are kept.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub storage_accesses: Vec<StorageAccess>,
+    /// Gas of the calls and creates made directly by the transaction, in execution order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub subcalls: Vec<SubcallGas>,
+}
+
+impl ResultAndState {
+    /// Removes the accounts that were only loaded or warmed from the state, and the storage
+    /// slots that were not changed from the remaining accounts.
+    ///
+    /// Touched accounts are kept even if unchanged, as committing them can remove empty
+    /// accounts (EIP-161). Pruned state commits the same as the full one.
+    pub fn prune_unchanged_state(&mut self) {
+        self.state.retain(|_, account| account.is_touched());
+        for account in self.state.values_mut() {
+            account.storage.retain(|_, slot| slot.is_changed());
+        }
+    }
+}
+
+/// Storage slot access made by the `SLOAD` or `SSTORE` opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageAccess {
+    /// Address of the account.
+    pub address: Address,
+    /// Storage slot.
+    pub key: U256,
+    /// Value of the slot at the start of the transaction.
+    pub original_value: U256,
+    /// Value of the slot before the access.
+    pub present_value: U256,
+    /// Value of the slot after the access, same as `present_value` for reads.
+    pub new_value: U256,
+    /// Whether the access is `SSTORE`.
+    pub is_write: bool,
+}
+
+/// Gas used by a call or create frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameGas {
+    /// Gas used by the frame, including the gas used by its children.
+    pub gas_used: u64,
+    /// Gas refund counter of the frame, including the refunds of its successful children.
+    pub gas_refunded: i64,
+    /// Gas used by the calls and creates made by the frame.
+    pub child_gas_used: u64,
+}
+
+impl FrameGas {
+    /// Returns the gas used by the frame itself, without its children.
+    #[inline]
+    pub const fn own_gas_used(&self) -> u64 {
+        self.gas_used.saturating_sub(self.child_gas_used)
+    }
+}
+
+/// Call or create made directly by the transaction, see [ResultAndState::subcalls].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubcallGas {
+    /// Address of the called code or of the created contract.
+    pub address: Address,
+    /// Whether the subcall is a create.
+    pub is_create: bool,
+    /// Whether the subcall succeeded.
+    pub success: bool,
+    /// Gas used by the subcall.
+    pub gas: FrameGas,
+}
+
+/// Warning about behavior of the transaction that silently changed in a hardfork.
+///
+/// Warnings do not affect the execution, they help to understand differences when
+/// contracts are executed on different forks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Warning {
+    /// EIP-6780 (Cancun): SELFDESTRUCT of a contract that was not created in the same
+    /// transaction only transfers its balance, code and storage are kept.
+    ///
+    /// EIP-6780 can be enabled independently of the spec id with `CfgEnv::eip6780`.
+    SelfDestructWithoutDestroy {
+        /// Address of the contract that executed SELFDESTRUCT.
+        address: Address,
+    },
+    /// EIP-3529 (London): gas refund is capped to the fifth of the gas spent
+    /// instead of the half.
+    RefundCapped {
+        /// Gas refund accumulated during execution.
+        refunded: u64,
+        /// Gas refund after the cap is applied.
+        capped: u64,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SelfDestructWithoutDestroy { address } => {
+                write!(
+                    f,
+                    "SELFDESTRUCT of {address} only transfers balance (EIP-6780)"
+                )
+            }
+            Self::RefundCapped { refunded, capped } => {
+                write!(
+                    f,
+                    "gas refund of {refunded} capped to {capped} post-London (EIP-3529)"
+                )
+            }
+        }
+    }
 }
 
 /// Result of a transaction execution.
@@ -36,9 +158,24 @@ pub enum ExecutionResult {
         reason: HaltReason,
         /// Halting will spend all the gas, and will be equal to gas_limit.
         gas_used: u64,
+        /// Instruction that halted the execution, `None` if the halt did not happen in
+        /// the interpreter.
+        context: Option<HaltContext>,
     },
 }
 
+/// Instruction that halted the execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HaltContext {
+    /// Address of the account whose code was executed.
+    pub address: Address,
+    /// Program counter of the instruction.
+    pub pc: usize,
+    /// Opcode of the instruction.
+    pub opcode: u8,
+}
+
 impl ExecutionResult {
     /// Returns if transaction execution is successful.
     /// 1 indicates success, 0 indicates revert.
@@ -74,6 +211,26 @@ impl ExecutionResult {
         }
     }
 
+    /// Decodes the `Error(string)` or `Panic(uint256)` revert reason, see [RevertReason::decode].
+    ///
+    /// Returns `None` if the execution was not reverted or the output is not a known revert
+    /// reason.
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        self.revert_reason_with_errors(&[])
+    }
+
+    /// Decodes the revert reason, including the custom errors of the ABI, see
+    /// [RevertReason::decode_with_errors].
+    ///
+    /// Returns `None` if the execution was not reverted or the output is not a known revert
+    /// reason.
+    pub fn revert_reason_with_errors(&self, errors: &[ErrorFragment]) -> Option<RevertReason> {
+        match self {
+            Self::Revert { output, .. } => RevertReason::decode_with_errors(output, errors),
+            _ => None,
+        }
+    }
+
     /// Returns the logs if execution is successful, or an empty list otherwise.
     pub fn logs(&self) -> &[Log] {
         match self {
@@ -90,6 +247,26 @@ impl ExecutionResult {
         }
     }
 
+    /// Returns the error code of the execution, see [HaltReason::error_code].
+    ///
+    /// Returns `None` if the execution was successful and [REVERT_ERROR_CODE] if it was
+    /// reverted.
+    pub fn error_code(&self) -> Option<u16> {
+        match self {
+            Self::Success { .. } => None,
+            Self::Revert { .. } => Some(REVERT_ERROR_CODE),
+            Self::Halt { reason, .. } => Some(reason.error_code()),
+        }
+    }
+
+    /// Returns the instruction that halted the execution, if any.
+    pub fn halt_context(&self) -> Option<&HaltContext> {
+        match self {
+            Self::Halt { context, .. } => context.as_ref(),
+            _ => None,
+        }
+    }
+
     /// Returns the gas used.
     pub fn gas_used(&self) -> u64 {
         match *self {
@@ -148,6 +325,8 @@ pub enum EVMError<DBError> {
     ///
     /// Useful for handler registers where custom logic would want to return their own custom error.
     Custom(String),
+    /// Execution was aborted with a [CancellationToken](crate::CancellationToken).
+    Canceled,
 }
 
 #[cfg(feature = "std")]
@@ -157,7 +336,7 @@ impl<DBError: std::error::Error + 'static> std::error::Error for EVMError<DBErro
             Self::Transaction(e) => Some(e),
             Self::Header(e) => Some(e),
             Self::Database(e) => Some(e),
-            Self::Custom(_) => None,
+            Self::Custom(_) | Self::Canceled => None,
         }
     }
 }
@@ -169,6 +348,24 @@ impl<DBError: fmt::Display> fmt::Display for EVMError<DBError> {
             Self::Header(e) => write!(f, "header validation error: {e}"),
             Self::Database(e) => write!(f, "database error: {e}"),
             Self::Custom(e) => f.write_str(e),
+            Self::Canceled => f.write_str("execution canceled"),
+        }
+    }
+}
+
+impl<DBError> EVMError<DBError> {
+    /// Returns the stable error code of the error.
+    ///
+    /// Transaction errors have codes in `2000..3000`, see [InvalidTransaction::error_code],
+    /// header errors in `3000..4000`, database errors `4000`, custom errors `5000` and
+    /// canceled execution `6000`.
+    pub fn error_code(&self) -> u16 {
+        match self {
+            Self::Transaction(e) => e.error_code(),
+            Self::Header(e) => e.error_code(),
+            Self::Database(_) => 4000,
+            Self::Custom(_) => 5000,
+            Self::Canceled => 6000,
         }
     }
 }
@@ -253,6 +450,8 @@ pub enum InvalidTransaction {
     EofInitcodesSizeLimit,
     /// EOF crate should have `to` address
     EofCrateShouldHaveToAddress,
+    /// EOF initcode hashes do not match the initcodes, see `TxEnv::set_eof_initcodes`.
+    EofInitcodesHashMismatch,
     /// System transactions are not supported post-regolith hardfork.
     ///
     /// Before the Regolith hardfork, there was a special field in the `Deposit` transaction
@@ -287,6 +486,45 @@ pub enum InvalidTransaction {
     HaltedDepositPostRegolith,
 }
 
+impl InvalidTransaction {
+    /// Returns the stable error code of the error, in `2000..3000`.
+    ///
+    /// Codes are never changed or reused, new variants get new codes.
+    pub fn error_code(&self) -> u16 {
+        match self {
+            Self::PriorityFeeGreaterThanMaxFee => 2000,
+            Self::GasPriceLessThanBasefee => 2001,
+            Self::CallerGasLimitMoreThanBlock => 2002,
+            Self::CallGasCostMoreThanGasLimit => 2003,
+            Self::RejectCallerWithCode => 2004,
+            Self::LackOfFundForMaxFee { .. } => 2005,
+            Self::OverflowPaymentInTransaction => 2006,
+            Self::NonceOverflowInTransaction => 2007,
+            Self::NonceTooHigh { .. } => 2008,
+            Self::NonceTooLow { .. } => 2009,
+            Self::CreateInitCodeSizeLimit => 2010,
+            Self::InvalidChainId => 2011,
+            Self::AccessListNotSupported => 2012,
+            Self::MaxFeePerBlobGasNotSupported => 2013,
+            Self::BlobVersionedHashesNotSupported => 2014,
+            Self::BlobGasPriceGreaterThanMax => 2015,
+            Self::EmptyBlobs => 2016,
+            Self::BlobCreateTransaction => 2017,
+            Self::TooManyBlobs => 2018,
+            Self::BlobVersionNotSupported => 2019,
+            Self::EofInitcodesNotSupported => 2020,
+            Self::EofInitcodesNumberLimit => 2021,
+            Self::EofInitcodesSizeLimit => 2022,
+            Self::EofCrateShouldHaveToAddress => 2023,
+            Self::EofInitcodesHashMismatch => 2024,
+            #[cfg(feature = "optimism")]
+            Self::DepositSystemTxPostRegolith => 2900,
+            #[cfg(feature = "optimism")]
+            Self::HaltedDepositPostRegolith => 2901,
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 impl std::error::Error for InvalidTransaction {}
 
@@ -345,6 +583,7 @@ impl fmt::Display for InvalidTransaction {
             Self::EofCrateShouldHaveToAddress => write!(f, "EOF crate should have `to` address"),
             Self::EofInitcodesSizeLimit => write!(f, "EOF initcodes size limit"),
             Self::EofInitcodesNumberLimit => write!(f, "EOF initcodes number limit"),
+            Self::EofInitcodesHashMismatch => write!(f, "EOF initcode hashes do not match"),
             #[cfg(feature = "optimism")]
             Self::DepositSystemTxPostRegolith => {
                 write!(
@@ -373,6 +612,16 @@ pub enum InvalidHeader {
     ExcessBlobGasNotSet,
 }
 
+impl InvalidHeader {
+    /// Returns the stable error code of the error, in `3000..4000`.
+    pub fn error_code(&self) -> u16 {
+        match self {
+            Self::PrevrandaoNotSet => 3000,
+            Self::ExcessBlobGasNotSet => 3001,
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 impl std::error::Error for InvalidHeader {}
 
@@ -385,6 +634,52 @@ impl fmt::Display for InvalidHeader {
     }
 }
 
+/// Single issue found while validating the environment, see [Env::validation_issues].
+///
+/// [Env::validation_issues]: crate::Env::validation_issues
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValidationIssue {
+    /// Block environment is invalid.
+    Header(InvalidHeader),
+    /// Transaction environment is invalid.
+    Transaction(InvalidTransaction),
+}
+
+impl ValidationIssue {
+    /// Returns the stable error code of the issue, same as the one of the wrapped error.
+    pub fn error_code(&self) -> u16 {
+        match self {
+            Self::Header(e) => e.error_code(),
+            Self::Transaction(e) => e.error_code(),
+        }
+    }
+}
+
+impl From<InvalidHeader> for ValidationIssue {
+    fn from(value: InvalidHeader) -> Self {
+        Self::Header(value)
+    }
+}
+
+impl From<InvalidTransaction> for ValidationIssue {
+    fn from(value: InvalidTransaction) -> Self {
+        Self::Transaction(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationIssue {}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Header(e) => write!(f, "header validation error: {e}"),
+            Self::Transaction(e) => write!(f, "transaction validation error: {e}"),
+        }
+    }
+}
+
 /// Reason a transaction successfully completed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -416,6 +711,10 @@ pub enum HaltReason {
     CreateContractStartingWithEF,
     /// EIP-3860: Limit and meter initcode. Initcode size limit exceeded.
     CreateInitCodeSizeLimit,
+    /// `RETURNCONTRACT` data section of the deployed container is bigger than `0xFFFF`.
+    EofAuxDataOverflow,
+    /// `RETURNCONTRACT` data section of the deployed container is smaller than declared.
+    EofAuxDataTooSmall,
 
     /* Internal Halts that can be only found inside Inspector */
     OverflowPayment,
@@ -429,6 +728,43 @@ pub enum HaltReason {
     FailedDeposit,
 }
 
+/// Error code of a reverted execution, see [ExecutionResult::error_code].
+pub const REVERT_ERROR_CODE: u16 = 1000;
+
+impl HaltReason {
+    /// Returns the stable error code of the halt, in `1001..2000`.
+    ///
+    /// Codes are never changed or reused, new variants get new codes. Out of gas halts
+    /// have codes in `1100..1200`, see [OutOfGasError::error_code].
+    pub fn error_code(&self) -> u16 {
+        match self {
+            Self::OutOfGas(e) => e.error_code(),
+            Self::OpcodeNotFound => 1001,
+            Self::InvalidFEOpcode => 1002,
+            Self::InvalidJump => 1003,
+            Self::NotActivated => 1004,
+            Self::StackUnderflow => 1005,
+            Self::StackOverflow => 1006,
+            Self::OutOfOffset => 1007,
+            Self::CreateCollision => 1008,
+            Self::PrecompileError => 1009,
+            Self::NonceOverflow => 1010,
+            Self::CreateContractSizeLimit => 1011,
+            Self::CreateContractStartingWithEF => 1012,
+            Self::CreateInitCodeSizeLimit => 1013,
+            Self::OverflowPayment => 1014,
+            Self::StateChangeDuringStaticCall => 1015,
+            Self::CallNotAllowedInsideStatic => 1016,
+            Self::OutOfFunds => 1017,
+            Self::CallTooDeep => 1018,
+            Self::EofAuxDataOverflow => 1019,
+            Self::EofAuxDataTooSmall => 1020,
+            #[cfg(feature = "optimism")]
+            Self::FailedDeposit => 1900,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OutOfGasError {
@@ -444,3 +780,16 @@ pub enum OutOfGasError {
     // i.e. in `as_usize_or_fail`
     InvalidOperand,
 }
+
+impl OutOfGasError {
+    /// Returns the stable error code of the halt, in `1100..1200`.
+    pub fn error_code(&self) -> u16 {
+        match self {
+            Self::Basic => 1100,
+            Self::MemoryLimit => 1101,
+            Self::Memory => 1102,
+            Self::Precompile => 1103,
+            Self::InvalidOperand => 1104,
+        }
+    }
+}
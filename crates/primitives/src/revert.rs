@@ -0,0 +1,287 @@
+use crate::{keccak256, Bytes, U256};
+use core::fmt;
+use std::{string::String, vec::Vec};
+
+/// Selector of the solidity `Error(string)` revert payload.
+pub const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Selector of the solidity `Panic(uint256)` revert payload.
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Decoded revert data of a reverted execution, see [RevertReason::decode].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RevertReason {
+    /// Solidity `Error(string)`, emitted by `require` and `revert` with a message.
+    Error(String),
+    /// Solidity `Panic(uint256)`, emitted by failed assertions and checked arithmetic. See
+    /// [RevertReason::panic_description].
+    Panic(U256),
+    /// Custom error that matched one of the given [ErrorFragment]s.
+    Custom {
+        /// Canonical signature of the error, e.g. `InsufficientBalance(uint256,uint256)`.
+        signature: String,
+        /// ABI-encoded arguments of the error, without the selector.
+        args: Bytes,
+    },
+}
+
+impl RevertReason {
+    /// Decodes `Error(string)` and `Panic(uint256)` revert data.
+    ///
+    /// Returns `None` if the selector is unknown or the data is malformed.
+    pub fn decode(output: &[u8]) -> Option<Self> {
+        Self::decode_with_errors(output, &[])
+    }
+
+    /// Decodes `Error(string)` and `Panic(uint256)` revert data and the custom errors of the
+    /// ABI.
+    ///
+    /// Returns `None` if the selector is unknown or the data is malformed.
+    pub fn decode_with_errors(output: &[u8], errors: &[ErrorFragment]) -> Option<Self> {
+        let (selector, data) = output.split_first_chunk::<4>()?;
+        match *selector {
+            ERROR_STRING_SELECTOR => {
+                // ABI encoding: offset (32 bytes), length (32 bytes), string bytes.
+                let offset = read_usize(data, 0)?;
+                let len = read_usize(data, offset)?;
+                let start = offset.checked_add(32)?;
+                let reason = data.get(start..start.checked_add(len)?)?;
+                core::str::from_utf8(reason)
+                    .ok()
+                    .map(|s| Self::Error(s.into()))
+            }
+            PANIC_SELECTOR => Some(Self::Panic(U256::try_from_be_slice(data.get(..32)?)?)),
+            _ => {
+                let error = errors.iter().find(|error| error.selector == *selector)?;
+                Some(Self::Custom {
+                    signature: error.signature.clone(),
+                    args: Bytes::copy_from_slice(data),
+                })
+            }
+        }
+    }
+
+    /// Returns the description of the `Panic(uint256)` code, `None` for other reasons and
+    /// unknown codes.
+    pub fn panic_description(&self) -> Option<&'static str> {
+        let Self::Panic(code) = self else {
+            return None;
+        };
+        let description = match u8::try_from(*code).ok()? {
+            0x00 => "generic compiler inserted panic",
+            0x01 => "assertion failed",
+            0x11 => "arithmetic overflow or underflow",
+            0x12 => "division or modulo by zero",
+            0x21 => "invalid enum value",
+            0x22 => "invalid storage byte array encoding",
+            0x31 => "pop on empty array",
+            0x32 => "array index out of bounds",
+            0x41 => "out of memory",
+            0x51 => "call to zero-initialized function",
+            _ => return None,
+        };
+        Some(description)
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(reason) => f.write_str(reason),
+            Self::Panic(code) => match self.panic_description() {
+                Some(description) => write!(f, "panic: {description} ({code:#x})"),
+                None => write!(f, "panic: {code:#x}"),
+            },
+            Self::Custom { signature, .. } => f.write_str(signature),
+        }
+    }
+}
+
+/// Custom error of a contract ABI, used to decode its revert data.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorFragment {
+    /// Canonical signature, e.g. `InsufficientBalance(uint256,uint256)`.
+    pub signature: String,
+    /// First four bytes of the hash of the signature.
+    pub selector: [u8; 4],
+}
+
+impl ErrorFragment {
+    /// Parses a human-readable ABI error, e.g.
+    /// `error InsufficientBalance(uint256 available, uint256 required)`.
+    ///
+    /// The `error` keyword and parameter names are optional. Returns `None` if the fragment
+    /// is malformed.
+    pub fn parse(fragment: &str) -> Option<Self> {
+        let fragment = fragment.trim();
+        let fragment = fragment.strip_prefix("error ").unwrap_or(fragment).trim();
+        let (name, params) = fragment.split_once('(')?;
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return None;
+        }
+        let params = params.trim_end().strip_suffix(')')?;
+        let mut signature = String::from(name);
+        signature.push('(');
+        signature.push_str(&canonical_params(params)?);
+        signature.push(')');
+        let selector = keccak256(signature.as_bytes())[..4].try_into().unwrap();
+        Some(Self {
+            signature,
+            selector,
+        })
+    }
+}
+
+/// Returns the canonical comma separated types of the parameter list, without names.
+fn canonical_params(params: &str) -> Option<String> {
+    if params.trim().is_empty() {
+        return Some(String::new());
+    }
+    let mut types = Vec::new();
+    for param in split_params(params)? {
+        let param = param.trim();
+        let ty = if let Some(tuple) = param.strip_prefix('(') {
+            // tuple, its components and array suffix.
+            let end = closing_paren(tuple)?;
+            let rest = tuple[end + 1..].trim_start();
+            let suffix = if rest.starts_with('[') {
+                rest.split_whitespace().next().unwrap_or_default()
+            } else {
+                ""
+            };
+            let mut ty = String::from("(");
+            ty.push_str(&canonical_params(&tuple[..end])?);
+            ty.push(')');
+            ty.push_str(suffix);
+            ty
+        } else {
+            param.split_whitespace().next()?.into()
+        };
+        types.push(ty);
+    }
+    Some(types.join(","))
+}
+
+/// Splits the parameter list on the commas outside of tuples.
+fn split_params(params: &str) -> Option<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                parts.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    parts.push(&params[start..]);
+    Some(parts)
+}
+
+/// Returns the index of the parenthesis that closes the tuple opened before `s`.
+fn closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Reads the ABI word at the offset as `usize`.
+fn read_usize(data: &[u8], offset: usize) -> Option<usize> {
+    let word = data.get(offset..offset.checked_add(32)?)?;
+    usize::try_from(U256::try_from_be_slice(word)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_string(reason: &str) -> Vec<u8> {
+        let mut data = ERROR_STRING_SELECTOR.to_vec();
+        data.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+        data.extend_from_slice(&U256::from(reason.len()).to_be_bytes::<32>());
+        data.extend_from_slice(reason.as_bytes());
+        data.resize(data.len().next_multiple_of(32) + 4, 0);
+        data
+    }
+
+    #[test]
+    fn decode_revert_reason() {
+        let data = error_string("not owner");
+        assert_eq!(
+            RevertReason::decode(&data),
+            Some(RevertReason::Error("not owner".into()))
+        );
+        assert_eq!(RevertReason::decode(&data[..40]), None);
+        assert_eq!(RevertReason::decode(&[0xde, 0xad]), None);
+
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend_from_slice(&U256::from(0x11).to_be_bytes::<32>());
+        let reason = RevertReason::decode(&data).unwrap();
+        assert_eq!(reason, RevertReason::Panic(U256::from(0x11)));
+        assert_eq!(
+            reason.to_string(),
+            "panic: arithmetic overflow or underflow (0x11)"
+        );
+    }
+
+    #[test]
+    fn decode_custom_error() {
+        let error = ErrorFragment::parse(
+            "error InsufficientBalance(uint256 available, (address, uint256[]) [] required)",
+        )
+        .unwrap();
+        assert_eq!(
+            error.signature,
+            "InsufficientBalance(uint256,(address,uint256[])[])"
+        );
+        let transfer = ErrorFragment::parse("TransferFailed()").unwrap();
+        assert_eq!(
+            ErrorFragment::parse("Error(string reason)")
+                .unwrap()
+                .selector,
+            ERROR_STRING_SELECTOR
+        );
+        assert_eq!(
+            ErrorFragment::parse("error Panic(uint256)")
+                .unwrap()
+                .selector,
+            PANIC_SELECTOR
+        );
+        assert_eq!(
+            ErrorFragment::parse("Nested((uint8 a) b, bool)")
+                .unwrap()
+                .signature,
+            "Nested((uint8),bool)"
+        );
+        assert_eq!(ErrorFragment::parse("Broken(uint256"), None);
+
+        let mut data = transfer.selector.to_vec();
+        data.extend_from_slice(&[1; 32]);
+        let errors = [error, transfer];
+        assert_eq!(
+            RevertReason::decode_with_errors(&data, &errors),
+            Some(RevertReason::Custom {
+                signature: "TransferFailed()".into(),
+                args: Bytes::from(vec![1; 32]),
+            })
+        );
+        assert_eq!(RevertReason::decode(&data), None);
+    }
+}
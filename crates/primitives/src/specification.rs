@@ -29,6 +29,9 @@ pub enum SpecId {
     SHANGHAI = 16,        // Shanghai               17034870 (Timestamp: 1681338455)
     CANCUN = 17,          // Cancun                 19426587 (Timestamp: 1710338135)
     PRAGUE = 18,          // Praque                 TBD
+    /// Features that are still in progress, like full EOF. They are not part of any fork
+    /// yet, so finalized specs up to [SpecId::PRAGUE] do not pick them up.
+    EXPERIMENTAL = 19,
     #[default]
     LATEST = u8::MAX,
 }
@@ -64,6 +67,9 @@ pub enum SpecId {
     CANCUN = 20,
     ECOTONE = 21,
     PRAGUE = 22,
+    /// Features that are still in progress, like full EOF. They are not part of any fork
+    /// yet, so finalized specs up to [SpecId::PRAGUE] do not pick them up.
+    EXPERIMENTAL = 23,
     #[default]
     LATEST = u8::MAX,
 }
@@ -106,6 +112,7 @@ impl From<&str> for SpecId {
             "Shanghai" => Self::SHANGHAI,
             "Cancun" => Self::CANCUN,
             "Prague" => Self::PRAGUE,
+            "Experimental" => Self::EXPERIMENTAL,
             #[cfg(feature = "optimism")]
             "Bedrock" => SpecId::BEDROCK,
             #[cfg(feature = "optimism")]
@@ -150,6 +157,7 @@ impl From<SpecId> for &'static str {
             #[cfg(feature = "optimism")]
             SpecId::ECOTONE => "Ecotone",
             SpecId::LATEST => "Latest",
+            SpecId::EXPERIMENTAL => "Experimental",
         }
     }
 }
@@ -197,6 +205,7 @@ spec!(CANCUN, CancunSpec);
 spec!(PRAGUE, PragueSpec);
 
 spec!(LATEST, LatestSpec);
+spec!(EXPERIMENTAL, ExperimentalSpec);
 
 // Optimism Hardforks
 #[cfg(feature = "optimism")]
@@ -271,6 +280,10 @@ macro_rules! spec_to_generic {
                 use $crate::PragueSpec as SPEC;
                 $e
             }
+            $crate::SpecId::EXPERIMENTAL => {
+                use $crate::ExperimentalSpec as SPEC;
+                $e
+            }
             #[cfg(feature = "optimism")]
             $crate::SpecId::BEDROCK => {
                 use $crate::BedrockSpec as SPEC;
@@ -329,6 +342,16 @@ mod tests {
         spec_to_generic!(CANCUN, assert_eq!(SPEC::SPEC_ID, CANCUN));
         spec_to_generic!(PRAGUE, assert_eq!(SPEC::SPEC_ID, PRAGUE));
         spec_to_generic!(LATEST, assert_eq!(SPEC::SPEC_ID, LATEST));
+        spec_to_generic!(EXPERIMENTAL, assert_eq!(SPEC::SPEC_ID, EXPERIMENTAL));
+    }
+
+    #[test]
+    fn experimental_is_not_a_fork() {
+        assert!(ExperimentalSpec::enabled(SpecId::PRAGUE));
+        assert!(!PragueSpec::enabled(SpecId::EXPERIMENTAL));
+        assert!(LatestSpec::enabled(SpecId::EXPERIMENTAL));
+        assert_eq!(SpecId::default(), SpecId::LATEST);
+        assert_eq!(SpecId::from("Experimental"), SpecId::EXPERIMENTAL);
     }
 }
 
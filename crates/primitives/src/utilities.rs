@@ -1,5 +1,6 @@
 use crate::{
-    b256, B256, BLOB_GASPRICE_UPDATE_FRACTION, MIN_BLOB_GASPRICE, TARGET_BLOB_GAS_PER_BLOCK,
+    b256, Address, B256, BLOB_GASPRICE_UPDATE_FRACTION, MIN_BLOB_GASPRICE,
+    TARGET_BLOB_GAS_PER_BLOCK,
 };
 pub use alloy_primitives::keccak256;
 
@@ -59,10 +60,53 @@ pub fn fake_exponential(factor: u64, numerator: u64, denominator: u64) -> u128 {
     output / denominator
 }
 
+/// Returns the address of the contract created with `CREATE` by `deployer` with its `nonce`.
+#[inline]
+pub fn create_address(deployer: Address, nonce: u64) -> Address {
+    deployer.create(nonce)
+}
+
+/// Returns the address of the contract created with `CREATE2` by `deployer` with the
+/// given `salt` and `init_code`.
+#[inline]
+pub fn create2_address(deployer: Address, salt: B256, init_code: &[u8]) -> Address {
+    deployer.create2_from_code(salt, init_code)
+}
+
+/// Returns the address of the contract created with `EOFCREATE` or `TXCREATE` by
+/// `deployer` with the given `salt` and EOF init container.
+///
+/// Address is derived as with `CREATE2`, from the hash of the whole init container.
+#[inline]
+pub fn eofcreate_address(deployer: Address, salt: B256, init_container: &[u8]) -> Address {
+    deployer.create2(salt, keccak256(init_container))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::GAS_PER_BLOB;
+    use crate::{address, bytes, GAS_PER_BLOB};
+
+    // https://eips.ethereum.org/EIPS/eip-1014#examples
+    #[test]
+    fn test_create_addresses() {
+        assert_eq!(
+            create_address(address!("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0"), 1),
+            address!("343c43a37d37dff08ae8c4a11544c718abb4fcf8")
+        );
+        assert_eq!(
+            create2_address(
+                address!("deadbeef00000000000000000000000000000000"),
+                B256::ZERO,
+                &bytes!("00")
+            ),
+            address!("b928f69bb1d91cd65274e3c79d8986362984fda3")
+        );
+        assert_eq!(
+            eofcreate_address(Address::ZERO, B256::ZERO, &bytes!("00")),
+            create2_address(Address::ZERO, B256::ZERO, &bytes!("00"))
+        );
+    }
 
     // https://github.com/ethereum/go-ethereum/blob/28857080d732857030eda80c69b9ba2c8926f221/consensus/misc/eip4844/eip4844_test.go#L27
     #[test]